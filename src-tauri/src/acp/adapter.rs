@@ -1,7 +1,9 @@
 //! Agent Adapter - protocol conversion layer between ACP and native CLI
 
+use std::collections::HashMap;
+
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
@@ -81,6 +83,69 @@ pub struct ContextEntry {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Typed value stored in the shared context
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ContextValue {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Json(serde_json::Value),
+}
+
+impl ContextValue {
+    /// Render the value for embedding into a prompt
+    pub fn to_prompt_string(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Number(n) => n.to_string(),
+            Self::Bool(b) => b.to_string(),
+            Self::Json(value) => value.to_string(),
+        }
+    }
+}
+
+/// A key in the shared context with its metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextItem {
+    pub value: ContextValue,
+    /// Agent (or "ui") that last wrote the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+    /// The key disappears after this time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ContextItem {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// Kind of change made to a context key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextChangeKind {
+    Set,
+    Deleted,
+    Expired,
+}
+
+/// Change notification for a context key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextChange {
+    pub workspace: String,
+    pub key: String,
+    pub kind: ContextChangeKind,
+    /// New value (only for `Set`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item: Option<ContextItem>,
+}
+
 /// Shared context between agents
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SharedContext {
@@ -91,6 +156,9 @@ pub struct SharedContext {
     /// Additional metadata
     #[serde(default)]
     pub metadata: serde_json::Value,
+    /// Key-value store
+    #[serde(default)]
+    pub values: HashMap<String, ContextItem>,
 }
 
 impl SharedContext {
@@ -115,6 +183,50 @@ impl SharedContext {
             timestamp: Utc::now(),
         });
     }
+
+    /// Set a key, optionally expiring after `ttl`
+    pub fn set_value(
+        &mut self,
+        key: impl Into<String>,
+        value: ContextValue,
+        ttl: Option<Duration>,
+        updated_by: Option<String>,
+    ) -> ContextItem {
+        let now = Utc::now();
+        let item = ContextItem {
+            value,
+            updated_by,
+            updated_at: now,
+            expires_at: ttl.map(|ttl| now + ttl),
+        };
+        self.values.insert(key.into(), item.clone());
+        item
+    }
+
+    /// Get a key (expired keys are treated as missing)
+    pub fn get_value(&self, key: &str) -> Option<&ContextItem> {
+        self.values.get(key).filter(|item| !item.is_expired(Utc::now()))
+    }
+
+    /// Remove a key, returning the previous value
+    pub fn remove_value(&mut self, key: &str) -> Option<ContextItem> {
+        self.values.remove(key)
+    }
+
+    /// Drop expired keys and return their names
+    pub fn purge_expired(&mut self) -> Vec<String> {
+        let now = Utc::now();
+        let expired: Vec<String> = self
+            .values
+            .iter()
+            .filter(|(_, item)| item.is_expired(now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            self.values.remove(key);
+        }
+        expired
+    }
 }
 
 /// Task request
@@ -301,6 +413,22 @@ mod tests {
         assert_eq!(context.conversation_history[0].agent_id, "agent-1");
     }
 
+    #[test]
+    fn test_context_values_with_ttl() {
+        let mut context = SharedContext::new();
+        context.set_value("lang", ContextValue::Text("ja".into()), None, Some("ui".into()));
+        context.set_value("count", ContextValue::Number(3.0), Some(Duration::seconds(-1)), None);
+
+        assert_eq!(context.get_value("lang").unwrap().value, ContextValue::Text("ja".into()));
+        assert!(context.get_value("count").is_none());
+        assert_eq!(context.purge_expired(), vec!["count".to_string()]);
+        assert!(context.remove_value("lang").is_some());
+        assert!(context.values.is_empty());
+
+        let json = serde_json::to_value(ContextValue::Bool(true)).unwrap();
+        assert_eq!(json, serde_json::json!({"type": "bool", "value": true}));
+    }
+
     #[test]
     fn test_stream_chunk() {
         let chunk = StreamChunk::new("Hello");
@@ -19,7 +19,13 @@ impl InputConverter for ClaudeCodeInputConverter {
     }
 
     fn embed_context(&self, prompt: &str, context: &SharedContext) -> String {
-        if context.conversation_history.is_empty() && context.shared_files.is_empty() {
+        let now = chrono::Utc::now();
+        let mut values: Vec<_> = context
+            .values
+            .iter()
+            .filter(|(_, item)| !item.is_expired(now))
+            .collect();
+        if context.conversation_history.is_empty() && context.shared_files.is_empty() && values.is_empty() {
             return prompt.to_string();
         }
 
@@ -34,6 +40,16 @@ impl InputConverter for ClaudeCodeInputConverter {
             full_prompt.push('\n');
         }
 
+        // Add shared values
+        if !values.is_empty() {
+            values.sort_by(|a, b| a.0.cmp(b.0));
+            full_prompt.push_str("## Shared Values\n\n");
+            for (key, item) in values {
+                full_prompt.push_str(&format!("- {}: {}\n", key, item.value.to_prompt_string()));
+            }
+            full_prompt.push('\n');
+        }
+
         // Add conversation history
         if !context.conversation_history.is_empty() {
             full_prompt.push_str("## Previous Context\n\n");
@@ -0,0 +1,135 @@
+//! OpenAI Codex CLI adapter
+//!
+//! Parses the JSONL printed by `codex exec --json` (thread / turn / item events).
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::acp::cli_agent::{CliEvent, CliStreamParser};
+use crate::acp::stream_parser::UsageTotals;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum CodexEvent {
+    #[serde(rename = "thread.started")]
+    ThreadStarted { thread_id: String },
+    #[serde(rename = "item.started")]
+    ItemStarted { item: Value },
+    #[serde(rename = "item.completed")]
+    ItemCompleted { item: Value },
+    #[serde(rename = "turn.completed")]
+    TurnCompleted {
+        #[serde(default)]
+        usage: Option<CodexUsage>,
+    },
+    #[serde(rename = "turn.failed")]
+    TurnFailed { error: Value },
+    #[serde(rename = "error")]
+    Error { message: String },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodexUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    cached_input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+/// Stream parser for Codex CLI
+///
+/// Codex reports progress notes as separate agent messages, so the last one
+/// of the turn is taken as the answer.
+#[derive(Debug, Default)]
+pub struct CodexStreamParser {
+    last_message: Option<String>,
+}
+
+impl CliStreamParser for CodexStreamParser {
+    fn parse_line(&mut self, line: &str) -> Vec<CliEvent> {
+        let Ok(event) = serde_json::from_str::<CodexEvent>(line.trim()) else {
+            return Vec::new();
+        };
+
+        match event {
+            CodexEvent::ThreadStarted { thread_id } => vec![CliEvent::Session(thread_id)],
+            CodexEvent::ItemStarted { item } => match item["type"].as_str() {
+                Some("command_execution") => vec![CliEvent::ToolUse {
+                    name: item["command"].as_str().unwrap_or("command").to_string(),
+                }],
+                Some("mcp_tool_call") => vec![CliEvent::ToolUse {
+                    name: item["tool"].as_str().unwrap_or("mcp").to_string(),
+                }],
+                Some(kind @ ("file_change" | "web_search")) => vec![CliEvent::ToolUse { name: kind.to_string() }],
+                _ => Vec::new(),
+            },
+            CodexEvent::ItemCompleted { item } if item["type"] == "agent_message" => {
+                let text = item["text"].as_str().unwrap_or_default().to_string();
+                self.last_message = Some(text.clone());
+                vec![CliEvent::Text(text)]
+            }
+            CodexEvent::TurnCompleted { usage } => {
+                let mut events = Vec::new();
+                if let Some(usage) = usage {
+                    events.push(CliEvent::Usage(UsageTotals {
+                        input_tokens: usage.input_tokens.saturating_sub(usage.cached_input_tokens),
+                        output_tokens: usage.output_tokens,
+                        cache_read_input_tokens: usage.cached_input_tokens,
+                        requests: 1,
+                        ..Default::default()
+                    }));
+                }
+                events.push(CliEvent::Completed {
+                    output: self.last_message.take().unwrap_or_default(),
+                });
+                events
+            }
+            CodexEvent::TurnFailed { error } => vec![CliEvent::Failed(
+                error["message"].as_str().map(str::to_string).unwrap_or_else(|| error.to_string()),
+            )],
+            CodexEvent::Error { message } => vec![CliEvent::Failed(message)],
+            CodexEvent::ItemCompleted { .. } | CodexEvent::Other => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_turn() {
+        let mut parser = CodexStreamParser::default();
+        let lines = [
+            r#"{"type":"thread.started","thread_id":"thread-1"}"#,
+            r#"{"type":"turn.started"}"#,
+            r#"{"type":"item.started","item":{"id":"item_0","type":"command_execution","command":"bash -lc ls","status":"in_progress"}}"#,
+            r#"{"type":"item.completed","item":{"id":"item_1","type":"reasoning","text":"thinking"}}"#,
+            r#"{"type":"item.completed","item":{"id":"item_2","type":"agent_message","text":"Checking the files"}}"#,
+            r#"{"type":"item.completed","item":{"id":"item_3","type":"agent_message","text":"[1] こんにちは"}}"#,
+            r#"{"type":"turn.completed","usage":{"input_tokens":1200,"cached_input_tokens":1000,"output_tokens":30}}"#,
+        ];
+        let events: Vec<CliEvent> = lines.iter().flat_map(|l| parser.parse_line(l)).collect();
+
+        assert_eq!(events[0], CliEvent::Session("thread-1".to_string()));
+        assert_eq!(events[1], CliEvent::ToolUse { name: "bash -lc ls".to_string() });
+        assert_eq!(events[2], CliEvent::Text("Checking the files".to_string()));
+        let CliEvent::Usage(usage) = &events[4] else { panic!("{:?}", events) };
+        assert_eq!((usage.input_tokens, usage.cache_read_input_tokens, usage.output_tokens), (200, 1000, 30));
+        assert_eq!(events[5], CliEvent::Completed { output: "[1] こんにちは".to_string() });
+    }
+
+    #[test]
+    fn test_parse_failure() {
+        let mut parser = CodexStreamParser::default();
+        assert!(parser.parse_line("not json").is_empty());
+        assert_eq!(
+            parser.parse_line(r#"{"type":"turn.failed","error":{"message":"stream disconnected"}}"#),
+            [CliEvent::Failed("stream disconnected".to_string())]
+        );
+    }
+}
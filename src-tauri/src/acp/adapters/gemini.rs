@@ -0,0 +1,131 @@
+//! Gemini CLI adapter
+//!
+//! Parses the JSONL printed by `gemini --output-format stream-json`
+//! (init / message / tool_use / result events).
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::acp::cli_agent::{CliEvent, CliStreamParser};
+use crate::acp::stream_parser::UsageTotals;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum GeminiEvent {
+    Init {
+        #[serde(default)]
+        session_id: Option<String>,
+    },
+    Message {
+        role: String,
+        #[serde(default)]
+        content: String,
+    },
+    ToolUse {
+        #[serde(default)]
+        tool_name: String,
+    },
+    Result {
+        #[serde(default)]
+        status: Option<String>,
+        #[serde(default)]
+        error: Option<Value>,
+        #[serde(default)]
+        stats: Option<GeminiStats>,
+    },
+    Error {
+        #[serde(default)]
+        severity: Option<String>,
+        #[serde(default)]
+        message: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiStats {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+/// Stream parser for Gemini CLI
+///
+/// Assistant messages arrive as deltas and are concatenated into the answer.
+#[derive(Debug, Default)]
+pub struct GeminiStreamParser {
+    answer: String,
+}
+
+impl CliStreamParser for GeminiStreamParser {
+    fn parse_line(&mut self, line: &str) -> Vec<CliEvent> {
+        let Ok(event) = serde_json::from_str::<GeminiEvent>(line.trim()) else {
+            return Vec::new();
+        };
+
+        match event {
+            GeminiEvent::Init { session_id } => session_id.map(CliEvent::Session).into_iter().collect(),
+            GeminiEvent::Message { role, content } if role == "assistant" => {
+                self.answer.push_str(&content);
+                vec![CliEvent::Text(content)]
+            }
+            GeminiEvent::ToolUse { tool_name } => vec![CliEvent::ToolUse { name: tool_name }],
+            GeminiEvent::Result { status, error, stats } => {
+                let mut events: Vec<CliEvent> = stats
+                    .map(|s| CliEvent::Usage(UsageTotals {
+                        input_tokens: s.input_tokens,
+                        output_tokens: s.output_tokens,
+                        requests: 1,
+                        ..Default::default()
+                    }))
+                    .into_iter()
+                    .collect();
+                if status.as_deref() == Some("error") {
+                    let message = error
+                        .map(|e| e["message"].as_str().map(str::to_string).unwrap_or_else(|| e.to_string()))
+                        .unwrap_or_else(|| "Gemini CLI reported an error".to_string());
+                    events.push(CliEvent::Failed(message));
+                } else {
+                    events.push(CliEvent::Completed { output: std::mem::take(&mut self.answer) });
+                }
+                events
+            }
+            // Warnings (e.g. retries) do not end the run
+            GeminiEvent::Error { severity, message } if severity.as_deref() != Some("warning") => {
+                vec![CliEvent::Failed(message)]
+            }
+            GeminiEvent::Message { .. } | GeminiEvent::Error { .. } | GeminiEvent::Other => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stream() {
+        let mut parser = GeminiStreamParser::default();
+        let lines = [
+            r#"{"type":"init","session_id":"gem-1","model":"gemini-2.5-pro"}"#,
+            r#"{"type":"message","role":"user","content":"translate"}"#,
+            r#"{"type":"message","role":"assistant","content":"[1] こん","delta":true}"#,
+            r#"{"type":"tool_use","tool_name":"read_file","tool_id":"t1","parameters":{}}"#,
+            r#"{"type":"error","severity":"warning","message":"retrying"}"#,
+            r#"{"type":"message","role":"assistant","content":"にちは","delta":true}"#,
+            r#"{"type":"result","status":"success","stats":{"total_tokens":60,"input_tokens":50,"output_tokens":10}}"#,
+        ];
+        let events: Vec<CliEvent> = lines.iter().flat_map(|l| parser.parse_line(l)).collect();
+
+        assert_eq!(events[0], CliEvent::Session("gem-1".to_string()));
+        assert_eq!(events[2], CliEvent::ToolUse { name: "read_file".to_string() });
+        let CliEvent::Usage(usage) = &events[4] else { panic!("{:?}", events) };
+        assert_eq!((usage.input_tokens, usage.output_tokens), (50, 10));
+        assert_eq!(events[5], CliEvent::Completed { output: "[1] こんにちは".to_string() });
+
+        let failed = parser.parse_line(r#"{"type":"result","status":"error","error":{"type":"auth","message":"not authenticated"}}"#);
+        assert_eq!(failed, [CliEvent::Failed("not authenticated".to_string())]);
+    }
+}
@@ -1,4 +1,6 @@
 //! Agent adapters
 
 pub mod claude_code;
+pub mod codex;
+pub mod gemini;
 
@@ -587,6 +587,27 @@ impl AgentCard {
         ])
     }
 
+    /// Card Re-Voice itself publishes (the URL is set by the server that serves it)
+    pub fn re_voice() -> Self {
+        Self::new("Re-Voice", "http://127.0.0.1/")
+            .with_id(crate::acp::transport::LOCAL_SENDER)
+            .with_description("Video translation and Japanese dubbing app; tasks are handed to its Claude Code agent")
+            .with_version(env!("CARGO_PKG_VERSION"))
+            .with_authentication(Authentication::none())
+            .with_default_input_modes(vec!["text/plain".to_string()])
+            .with_default_output_modes(vec!["text/plain".to_string()])
+            .with_transport(Transport::Http)
+            .with_skills(vec![
+                Skill::new("translation", "Translation")
+                    .with_description("Translate text or subtitles into Japanese")
+                    .with_tags(vec!["multilingual".to_string(), "subtitles".to_string()])
+                    .with_examples(vec!["Translate these subtitles to Japanese".to_string()]),
+                Skill::new("task", "General Task")
+                    .with_description("Run a prompt on the app's Claude Code agent")
+                    .with_tags(vec!["delegation".to_string()]),
+            ])
+    }
+
     /// Export to JSON for .well-known/agent.json
     pub fn to_a2a_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(self)
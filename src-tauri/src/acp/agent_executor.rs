@@ -0,0 +1,480 @@
+//! Agent executors
+//!
+//! A stage prompt can be answered by the shared Claude Code CLI executor, a
+//! Codex / Gemini CLI, the legacy PTY session or an agent in a tmux pane.
+//! `AgentExecutor` gives them all the same `execute(prompt)` returning a
+//! stream of `AgentEvent`s, and `AgentTarget` picks the backend from a
+//! stage's `AgentAddress`.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use thiserror::Error;
+use tokio::sync::{mpsc, RwLock};
+
+use super::cli_agent::{CliAgentExecutor, CliAgentKind, CliAgentOptions, CliEvent};
+use super::executor::{ClaudeCodeExecutor, ExecutorError};
+use super::message::{ACPEnvelope, ACPMessageV3, AgentAddress, MessageType};
+use super::stream_parser::UsageTotals;
+use super::tmux::{AgentStatus, TmuxOrchestrator};
+use super::transport::pty::PtyHandle;
+use super::transport::{TransportEvent, TransportHandle, LOCAL_SENDER};
+
+/// Interval between pane captures while a tmux agent is working
+const PANE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Idle captures with new output after which a tmux agent that was never
+/// seen processing is considered done (fast answers can finish between polls)
+const IDLE_POLLS_WITHOUT_PROCESSING: u32 = 3;
+
+/// Agent executor errors
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum AgentExecutorError {
+    #[error("Agent not available: {0}")]
+    NotAvailable(String),
+
+    #[error("Execution failed: {0}")]
+    Failed(String),
+
+    #[error("Timeout: {0}")]
+    Timeout(String),
+}
+
+impl From<ExecutorError> for AgentExecutorError {
+    fn from(e: ExecutorError) -> Self {
+        match e {
+            ExecutorError::Timeout(message) => Self::Timeout(message),
+            e => Self::Failed(e.to_string()),
+        }
+    }
+}
+
+/// Event streamed while an agent works on a prompt
+#[derive(Debug, Clone, PartialEq)]
+pub enum AgentEvent {
+    /// Output produced so far
+    Output(String),
+    /// A tool or command the agent started
+    ToolUse { name: String },
+    /// Tokens / cost spent by a request
+    Usage(UsageTotals),
+    /// Final answer (last event)
+    Completed(String),
+    /// The execution failed (last event)
+    Failed(AgentExecutorError),
+}
+
+impl AgentEvent {
+    fn is_final(&self) -> bool {
+        matches!(self, AgentEvent::Completed(_) | AgentEvent::Failed(_))
+    }
+}
+
+/// Events of one execution; dropping the receiver abandons the execution
+pub type AgentEventStream = mpsc::UnboundedReceiver<AgentEvent>;
+
+/// Backend that executes a prompt and streams the result
+#[async_trait]
+pub trait AgentExecutor: Send + Sync {
+    /// Name used in logs
+    fn name(&self) -> String;
+
+    /// Start executing `prompt`
+    ///
+    /// Fails right away if the backend is not running; errors during the
+    /// execution arrive as a `Failed` event.
+    async fn execute(&self, prompt: &str) -> Result<AgentEventStream, AgentExecutorError>;
+}
+
+/// Wait for the end of an execution, passing every event to `on_event`
+pub async fn wait_for_output(
+    mut events: AgentEventStream,
+    mut on_event: impl FnMut(&AgentEvent),
+) -> Result<String, AgentExecutorError> {
+    while let Some(event) = events.recv().await {
+        on_event(&event);
+        match event {
+            AgentEvent::Completed(output) => return Ok(output),
+            AgentEvent::Failed(e) => return Err(e),
+            _ => {}
+        }
+    }
+    Err(AgentExecutorError::Failed("agent stopped without a result".to_string()))
+}
+
+/// Backend a stage's agent address resolves to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentTarget {
+    /// Built-in Rust stage (`rust-direct`)
+    RustDirect,
+    /// Shared Claude Code CLI executor (`claude-code`, `claude-code/cli` and unknown agents)
+    ClaudeCode,
+    /// Codex / Gemini CLI (`codex@localhost`, `gemini@localhost/cli`)
+    Cli(CliAgentKind),
+    /// Legacy PTY session (`claude-code/pty`)
+    Pty,
+    /// Agent running in a tmux pane, by agent id (`<agent-id>/tmux`)
+    Tmux(String),
+}
+
+impl AgentTarget {
+    pub fn from_address(address: &AgentAddress) -> Self {
+        let agent = address.id.split('@').next().unwrap_or_default();
+        match address.instance.as_deref() {
+            _ if agent == "rust-direct" => Self::RustDirect,
+            Some("pty") => Self::Pty,
+            Some("tmux") => Self::Tmux(agent.to_string()),
+            _ => CliAgentKind::from_address(address).map(Self::Cli).unwrap_or(Self::ClaudeCode),
+        }
+    }
+}
+
+/// Run `execution` in the background until it finishes or the stream is dropped
+fn spawn_execution<F>(tx: mpsc::UnboundedSender<AgentEvent>, execution: F)
+where
+    F: std::future::Future<Output = AgentEvent> + Send + 'static,
+{
+    tokio::spawn(async move {
+        tokio::select! {
+            event = execution => {
+                let _ = tx.send(event);
+            }
+            _ = tx.closed() => {}
+        }
+    });
+}
+
+/// The shared Claude Code CLI executor
+pub struct ClaudeCodeAgent {
+    executor: Arc<RwLock<Option<ClaudeCodeExecutor>>>,
+}
+
+impl ClaudeCodeAgent {
+    pub fn new(executor: Arc<RwLock<Option<ClaudeCodeExecutor>>>) -> Self {
+        Self { executor }
+    }
+}
+
+#[async_trait]
+impl AgentExecutor for ClaudeCodeAgent {
+    fn name(&self) -> String {
+        "Claude Code".to_string()
+    }
+
+    async fn execute(&self, prompt: &str) -> Result<AgentEventStream, AgentExecutorError> {
+        if self.executor.read().await.is_none() {
+            return Err(AgentExecutorError::NotAvailable("Claude Code executor is not started".to_string()));
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let executor = self.executor.clone();
+        let usage = tx.clone();
+        let prompt = prompt.to_string();
+        spawn_execution(tx, async move {
+            let mut guard = executor.write().await;
+            let Some(executor) = guard.as_mut() else {
+                return AgentEvent::Failed(AgentExecutorError::NotAvailable("Claude Code executor was stopped".to_string()));
+            };
+            let result = executor.execute(&prompt).await;
+            let _ = usage.send(AgentEvent::Usage(executor.last_usage()));
+            match result {
+                Ok(output) => AgentEvent::Completed(output),
+                Err(e) => AgentEvent::Failed(e.into()),
+            }
+        });
+        Ok(rx)
+    }
+}
+
+/// Codex CLI / Gemini CLI, started once per prompt
+pub struct CliAgent {
+    kind: CliAgentKind,
+    options: CliAgentOptions,
+}
+
+impl CliAgent {
+    pub fn new(kind: CliAgentKind, options: CliAgentOptions) -> Self {
+        Self { kind, options }
+    }
+}
+
+#[async_trait]
+impl AgentExecutor for CliAgent {
+    fn name(&self) -> String {
+        self.kind.label().to_string()
+    }
+
+    async fn execute(&self, prompt: &str) -> Result<AgentEventStream, AgentExecutorError> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut executor = CliAgentExecutor::new(self.kind, self.options.clone());
+        let events = tx.clone();
+        let prompt = prompt.to_string();
+        spawn_execution(tx, async move {
+            // The final result comes from `execute_with` (it also checks the exit status)
+            let result = executor.execute_with(&prompt, move |event| {
+                let event = match event {
+                    CliEvent::Text(text) => AgentEvent::Output(text.clone()),
+                    CliEvent::ToolUse { name } => AgentEvent::ToolUse { name: name.clone() },
+                    CliEvent::Usage(usage) => AgentEvent::Usage(*usage),
+                    CliEvent::Session(_) | CliEvent::Completed { .. } | CliEvent::Failed(_) => return,
+                };
+                let _ = events.send(event);
+            }).await;
+            match result {
+                Ok(output) => AgentEvent::Completed(output),
+                Err(e) => AgentEvent::Failed(e.into()),
+            }
+        });
+        Ok(rx)
+    }
+}
+
+/// The legacy PTY session
+///
+/// The prompt is sent as an envelope; `PtyHandle` answers it with a correlated
+/// `Response` once the agent shows its input prompt again.
+pub struct PtyAgent {
+    handle: Arc<PtyHandle>,
+    timeout: Duration,
+}
+
+impl PtyAgent {
+    pub fn new(handle: Arc<PtyHandle>, timeout: Duration) -> Self {
+        Self { handle, timeout }
+    }
+}
+
+#[async_trait]
+impl AgentExecutor for PtyAgent {
+    fn name(&self) -> String {
+        "PTY".to_string()
+    }
+
+    async fn execute(&self, prompt: &str) -> Result<AgentEventStream, AgentExecutorError> {
+        if !self.handle.is_open() {
+            return Err(AgentExecutorError::NotAvailable("PTY is not running".to_string()));
+        }
+
+        let envelope = ACPEnvelope::new(ACPMessageV3::prompt(LOCAL_SENDER, "claude-code/pty", prompt));
+        let message_id = envelope.message.id.clone();
+
+        // Subscribe before sending so a fast reply is not missed
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let subscription = self.handle.subscribe(Arc::new(move |event| {
+            let event = match event {
+                TransportEvent::Output { text } => AgentEvent::Output(text.clone()),
+                TransportEvent::Message { envelope } if envelope.correlation_id() == Some(message_id.as_str()) => {
+                    let content = envelope.message.payload.content.clone();
+                    match envelope.message.message_type {
+                        MessageType::Error => AgentEvent::Failed(AgentExecutorError::Failed(content)),
+                        _ => AgentEvent::Completed(content),
+                    }
+                }
+                TransportEvent::Closed => AgentEvent::Failed(AgentExecutorError::Failed("PTY closed".to_string())),
+                _ => return,
+            };
+            let _ = events_tx.send(event);
+        }));
+        if let Err(e) = self.handle.send_envelope(&envelope) {
+            self.handle.unsubscribe(subscription);
+            return Err(AgentExecutorError::Failed(e.to_string()));
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = self.handle.clone();
+        let timeout = self.timeout;
+        tokio::spawn(async move {
+            let deadline = tokio::time::Instant::now() + timeout;
+            loop {
+                let event = tokio::select! {
+                    event = tokio::time::timeout_at(deadline, events.recv()) => match event {
+                        Ok(Some(event)) => event,
+                        Ok(None) => break,
+                        Err(_) => AgentEvent::Failed(AgentExecutorError::Timeout(format!(
+                            "PTY did not answer within {:?}", timeout
+                        ))),
+                    },
+                    _ = tx.closed() => break,
+                };
+                let done = event.is_final();
+                let _ = tx.send(event);
+                if done {
+                    break;
+                }
+            }
+            handle.unsubscribe(subscription);
+        });
+        Ok(rx)
+    }
+}
+
+/// An agent running in a tmux pane
+///
+/// tmux has no output stream, so the pane is captured periodically and the
+/// answer is complete when the output parser sees the agent idle again.
+pub struct TmuxPaneAgent {
+    orchestrator: Arc<Mutex<Option<TmuxOrchestrator>>>,
+    agent_id: String,
+    timeout: Duration,
+}
+
+impl TmuxPaneAgent {
+    pub fn new(orchestrator: Arc<Mutex<Option<TmuxOrchestrator>>>, agent_id: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            orchestrator,
+            agent_id: agent_id.into(),
+            timeout,
+        }
+    }
+
+    /// Send the prompt and poll the pane until the agent is done (blocking)
+    fn run(
+        orchestrator: &Mutex<Option<TmuxOrchestrator>>,
+        pane_id: &str,
+        prompt: &str,
+        deadline: Instant,
+        tx: &mpsc::UnboundedSender<AgentEvent>,
+    ) -> AgentEvent {
+        let failed = |message: String| AgentEvent::Failed(AgentExecutorError::Failed(message));
+
+        let mut seq = {
+            let guard = orchestrator.lock();
+            let Some(orch) = guard.as_ref() else {
+                return AgentEvent::Failed(AgentExecutorError::NotAvailable("tmux session closed".to_string()));
+            };
+            let before = match orch.capture_pane_incremental(pane_id) {
+                Ok((_, seq)) => seq,
+                Err(e) => return failed(e.to_string()),
+            };
+            if let Err(e) = orch.send_keys(pane_id, prompt) {
+                return failed(e.to_string());
+            }
+            before
+        };
+
+        let mut output = String::new();
+        let mut processing_seen = false;
+        let mut idle_polls = 0;
+        loop {
+            std::thread::sleep(PANE_POLL_INTERVAL);
+            if tx.is_closed() {
+                return failed("abandoned".to_string());
+            }
+            if Instant::now() >= deadline {
+                return AgentEvent::Failed(AgentExecutorError::Timeout(format!("tmux pane {} did not finish", pane_id)));
+            }
+
+            let guard = orchestrator.lock();
+            let Some(orch) = guard.as_ref() else {
+                return AgentEvent::Failed(AgentExecutorError::NotAvailable("tmux session closed".to_string()));
+            };
+            // 1回のキャプチャで出力の差分と状態の両方を見る
+            let content = match orch.capture_pane_incremental(pane_id) {
+                Ok((content, _)) => content,
+                Err(e) => return failed(e.to_string()),
+            };
+            let slice = orch.output_since(pane_id, seq);
+            seq = slice.next_seq;
+            let text = slice.text();
+            if !text.is_empty() {
+                output.push_str(&text);
+                output.push('\n');
+                let _ = tx.send(AgentEvent::Output(text));
+            }
+            match orch.detect_status_in(pane_id, &content) {
+                AgentStatus::Processing => processing_seen = true,
+                AgentStatus::Idle if processing_seen || (!output.is_empty() && idle_polls >= IDLE_POLLS_WITHOUT_PROCESSING) => {
+                    return AgentEvent::Completed(orch.extract_meaningful_content(&output));
+                }
+                AgentStatus::Idle => idle_polls += 1,
+                AgentStatus::WaitingForInput { question } => {
+                    return failed(format!("agent is waiting for input: {}", question));
+                }
+                AgentStatus::Error { message } => return failed(message),
+                AgentStatus::Initializing | AgentStatus::Unknown => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AgentExecutor for TmuxPaneAgent {
+    fn name(&self) -> String {
+        format!("tmux:{}", self.agent_id)
+    }
+
+    async fn execute(&self, prompt: &str) -> Result<AgentEventStream, AgentExecutorError> {
+        let pane_id = {
+            let guard = self.orchestrator.lock();
+            let orch = guard.as_ref()
+                .ok_or_else(|| AgentExecutorError::NotAvailable("tmux session not created".to_string()))?;
+            orch.get_pane_id(&self.agent_id)
+                .ok_or_else(|| AgentExecutorError::NotAvailable(format!("tmux agent not found: {}", self.agent_id)))?
+                .to_string()
+        };
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let orchestrator = self.orchestrator.clone();
+        let prompt = prompt.to_string();
+        let deadline = Instant::now() + self.timeout;
+        tokio::task::spawn_blocking(move || {
+            let event = Self::run(&orchestrator, &pane_id, &prompt, deadline, &tx);
+            let _ = tx.send(event);
+        });
+        Ok(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::transport::Subscribers;
+    use crate::pty::PtyManager;
+
+    #[test]
+    fn test_target_from_address() {
+        let target = |s: &str| AgentTarget::from_address(&AgentAddress::parse(s).unwrap());
+        assert_eq!(target("rust-direct"), AgentTarget::RustDirect);
+        assert_eq!(target("claude-code"), AgentTarget::ClaudeCode);
+        assert_eq!(target("claude-code@localhost/cli"), AgentTarget::ClaudeCode);
+        assert_eq!(target("codex@localhost"), AgentTarget::Cli(CliAgentKind::Codex));
+        assert_eq!(target("claude-code/pty"), AgentTarget::Pty);
+        assert_eq!(target("translator-1@localhost/tmux"), AgentTarget::Tmux("translator-1".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_backends() {
+        let claude = ClaudeCodeAgent::new(Arc::new(RwLock::new(None)));
+        assert!(matches!(claude.execute("hi").await, Err(AgentExecutorError::NotAvailable(_))));
+
+        let pty = PtyAgent::new(
+            Arc::new(PtyHandle::new(Arc::new(Mutex::new(PtyManager::new())), Arc::new(Subscribers::new()))),
+            Duration::from_secs(1),
+        );
+        assert!(matches!(pty.execute("hi").await, Err(AgentExecutorError::NotAvailable(_))));
+
+        let tmux = TmuxPaneAgent::new(Arc::new(Mutex::new(None)), "agent-1", Duration::from_secs(1));
+        assert!(matches!(tmux.execute("hi").await, Err(AgentExecutorError::NotAvailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_output() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tx.send(AgentEvent::Output("partial".to_string())).unwrap();
+        tx.send(AgentEvent::Usage(UsageTotals { output_tokens: 5, requests: 1, ..Default::default() })).unwrap();
+        tx.send(AgentEvent::Completed("done".to_string())).unwrap();
+
+        let mut seen = Vec::new();
+        let output = tokio::time::timeout(Duration::from_secs(5), wait_for_output(rx, |e| seen.push(e.clone())))
+            .await
+            .unwrap();
+        assert_eq!(output, Ok("done".to_string()));
+        assert_eq!(seen.len(), 3);
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        drop(tx);
+        assert!(matches!(wait_for_output(rx, |_| {}).await, Err(AgentExecutorError::Failed(_))));
+    }
+}
@@ -9,13 +9,15 @@
 //! 3. **人間へのエスカレーション**: ポリシーにない質問はフロントエンドに通知
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 use parking_lot::Mutex;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 
+use crate::events::RecordedEmit;
 use crate::log;
 
 /// Ask Toolの種類
@@ -65,6 +67,9 @@ pub struct ParsedQuestion {
     pub suggested_answer: Option<String>,
 }
 
+/// 自動応答ポリシーの保存ファイル名（データディレクトリ直下）
+pub const ASK_POLICY_FILE: &str = "ask_policies.json";
+
 /// 自動応答ポリシー
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoAnswerPolicy {
@@ -101,9 +106,9 @@ pub struct HumanAnswer {
 /// Ask Tool Handler
 pub struct AskToolHandler {
     /// 自動応答ポリシー
-    policies: Vec<AutoAnswerPolicy>,
+    policies: Arc<Mutex<Vec<AutoAnswerPolicy>>>,
     /// コンパイル済み正規表現
-    compiled_patterns: Vec<(Regex, AutoAnswerPolicy)>,
+    compiled_patterns: Arc<Mutex<Vec<(Regex, AutoAnswerPolicy)>>>,
     /// 保留中の質問（人間の回答待ち）
     pending_questions: Arc<Mutex<HashMap<String, ParsedQuestion>>>,
     /// 人間からの回答
@@ -117,9 +122,9 @@ pub struct AskToolHandler {
 impl AskToolHandler {
     /// 新しいHandlerを作成
     pub fn new() -> Self {
-        let mut handler = Self {
-            policies: Self::default_policies(),
-            compiled_patterns: Vec::new(),
+        let handler = Self {
+            policies: Arc::new(Mutex::new(Self::default_policies())),
+            compiled_patterns: Arc::new(Mutex::new(Vec::new())),
             pending_questions: Arc::new(Mutex::new(HashMap::new())),
             human_answers: Arc::new(Mutex::new(HashMap::new())),
             app_handle: Arc::new(Mutex::new(None)),
@@ -169,8 +174,9 @@ impl AskToolHandler {
     }
 
     /// ポリシーの正規表現をコンパイル
-    fn compile_patterns(&mut self) {
-        self.compiled_patterns = self.policies
+    fn compile_patterns(&self) {
+        *self.compiled_patterns.lock() = self.policies
+            .lock()
             .iter()
             .filter_map(|p| {
                 Regex::new(&p.resource_pattern)
@@ -376,7 +382,7 @@ impl AskToolHandler {
         };
 
         // ポリシーをチェック
-        for (pattern, policy) in &self.compiled_patterns {
+        for (pattern, policy) in self.compiled_patterns.lock().iter() {
             if pattern.is_match(&resource) {
                 log::info("AskToolHandler", &format!(
                     "Policy matched: {} -> {}",
@@ -406,7 +412,7 @@ impl AskToolHandler {
                 "parsed": parsed,
             });
 
-            if let Err(e) = h.emit("acp:ask_required", &payload) {
+            if let Err(e) = h.emit_recorded("acp:ask_required", &payload) {
                 log::error("AskToolHandler", &format!("Failed to emit ask event: {:?}", e));
             }
         }
@@ -465,11 +471,43 @@ impl AskToolHandler {
     }
 
     /// ポリシーを追加
-    pub fn add_policy(&mut self, policy: AutoAnswerPolicy) {
+    pub fn add_policy(&self, policy: AutoAnswerPolicy) {
         if let Ok(re) = Regex::new(&policy.resource_pattern) {
-            self.compiled_patterns.push((re, policy.clone()));
+            self.compiled_patterns.lock().push((re, policy.clone()));
+        }
+        self.policies.lock().push(policy);
+    }
+
+    /// ポリシー一覧を取得
+    pub fn policies(&self) -> Vec<AutoAnswerPolicy> {
+        self.policies.lock().clone()
+    }
+
+    /// ポリシーを置き換え
+    pub fn set_policies(&self, policies: Vec<AutoAnswerPolicy>) {
+        *self.policies.lock() = policies;
+        self.compile_patterns();
+    }
+
+    /// 保存したポリシーで置き換える（ファイルがなければ既定のまま）
+    pub fn load_policies(&self, path: &Path) -> Result<usize, String> {
+        if !path.exists() {
+            return Ok(self.policies.lock().len());
+        }
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let policies: Vec<AutoAnswerPolicy> = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        let count = policies.len();
+        self.set_policies(policies);
+        Ok(count)
+    }
+
+    /// 現在のポリシーをJSONファイルに保存
+    pub fn save_policies(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
-        self.policies.push(policy);
+        let json = serde_json::to_string_pretty(&self.policies()).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
     }
 }
 
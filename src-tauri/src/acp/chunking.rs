@@ -0,0 +1,169 @@
+//! 翻訳チャンク分割
+//!
+//! 長い字幕を一定数のセグメントごとのチャンクに分割し、前後に数セグメントの
+//! 重なり（文脈）を持たせて翻訳する。翻訳後はインデックスで再結合し、
+//! 重なり部分で訳が食い違う場合はチャンク中央に近い方の訳を採用する。
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// チャンク分割設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkConfig {
+    /// 1チャンクあたりのセグメント数（重なりを除く）
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: usize,
+    /// 前後に付ける文脈セグメント数
+    #[serde(default = "default_overlap")]
+    pub overlap: usize,
+    /// 同時に翻訳するチャンク数（1なら順番に翻訳）
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+}
+
+fn default_chunk_size() -> usize { 40 }
+fn default_overlap() -> usize { 3 }
+fn default_concurrency() -> usize { 1 }
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: default_chunk_size(),
+            overlap: default_overlap(),
+            concurrency: default_concurrency(),
+        }
+    }
+}
+
+/// 翻訳チャンク（セグメント位置の範囲）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranslationChunk {
+    /// 重なりを含む範囲の開始（含む）
+    pub start: usize,
+    /// 重なりを含む範囲の終了（含まない）
+    pub end: usize,
+    /// このチャンクが担当する範囲の開始（含む）
+    pub core_start: usize,
+    /// このチャンクが担当する範囲の終了（含まない）
+    pub core_end: usize,
+}
+
+impl TranslationChunk {
+    /// 位置がチャンク範囲に含まれるか
+    pub fn contains(&self, position: usize) -> bool {
+        position >= self.start && position < self.end
+    }
+
+    /// チャンク端からの距離（大きいほど文脈が十分）
+    fn edge_distance(&self, position: usize) -> usize {
+        (position - self.start).min(self.end - 1 - position)
+    }
+
+    /// 担当範囲内か
+    fn is_core(&self, position: usize) -> bool {
+        position >= self.core_start && position < self.core_end
+    }
+}
+
+/// `total` 個のセグメントをチャンクに分割
+pub fn split_into_chunks(total: usize, config: &ChunkConfig) -> Vec<TranslationChunk> {
+    let size = config.chunk_size.max(1);
+    let mut chunks = Vec::new();
+    let mut core_start = 0;
+
+    while core_start < total {
+        let core_end = (core_start + size).min(total);
+        chunks.push(TranslationChunk {
+            start: core_start.saturating_sub(config.overlap),
+            end: (core_end + config.overlap).min(total),
+            core_start,
+            core_end,
+        });
+        core_start = core_end;
+    }
+
+    chunks
+}
+
+/// チャンクごとの翻訳結果（位置 -> 訳文）を再結合する
+///
+/// 同じ位置に複数の訳がある場合は、担当チャンクの訳を優先し、
+/// それ以外はチャンク端から遠い訳を採用する。訳が無い位置は `None`。
+pub fn reassemble(
+    total: usize,
+    results: &[(TranslationChunk, BTreeMap<usize, String>)],
+) -> Vec<Option<String>> {
+    let mut merged: Vec<Option<(bool, usize, &String)>> = vec![None; total];
+
+    for (chunk, translations) in results {
+        for (&position, text) in translations {
+            if position >= total || !chunk.contains(position) {
+                continue;
+            }
+            let candidate = (chunk.is_core(position), chunk.edge_distance(position), text);
+            let better = match merged[position] {
+                None => true,
+                Some((core, distance, _)) => (candidate.0, candidate.1) > (core, distance),
+            };
+            if better {
+                merged[position] = Some(candidate);
+            }
+        }
+    }
+
+    merged.into_iter().map(|m| m.map(|(_, _, text)| text.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_into_chunks() {
+        let config = ChunkConfig { chunk_size: 4, overlap: 1, ..Default::default() };
+        let chunks = split_into_chunks(10, &config);
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!((chunks[0].start, chunks[0].end), (0, 5));
+        assert_eq!((chunks[1].start, chunks[1].end), (3, 9));
+        assert_eq!((chunks[2].core_start, chunks[2].core_end), (8, 10));
+        assert_eq!((chunks[2].start, chunks[2].end), (7, 10));
+        assert!(split_into_chunks(0, &config).is_empty());
+    }
+
+    #[test]
+    fn test_reassemble_prefers_core_chunk() {
+        let config = ChunkConfig { chunk_size: 2, overlap: 1, ..Default::default() };
+        let chunks = split_into_chunks(4, &config);
+
+        let first: BTreeMap<usize, String> = [(0, "a"), (1, "b"), (2, "c-overlap")]
+            .into_iter().map(|(i, t)| (i, t.to_string())).collect();
+        let second: BTreeMap<usize, String> = [(1, "b-overlap"), (2, "c"), (3, "d")]
+            .into_iter().map(|(i, t)| (i, t.to_string())).collect();
+
+        let merged = reassemble(4, &[(chunks[0].clone(), first), (chunks[1].clone(), second)]);
+        assert_eq!(merged, vec![
+            Some("a".to_string()),
+            Some("b".to_string()),
+            Some("c".to_string()),
+            Some("d".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_reassemble_falls_back_to_overlap() {
+        let config = ChunkConfig { chunk_size: 2, overlap: 1, ..Default::default() };
+        let chunks = split_into_chunks(4, &config);
+
+        // 担当チャンクが位置2を落とした場合は重なり側の訳を使う
+        let first: BTreeMap<usize, String> = [(0, "a"), (1, "b"), (2, "c-overlap")]
+            .into_iter().map(|(i, t)| (i, t.to_string())).collect();
+        let second: BTreeMap<usize, String> = [(3, "d")]
+            .into_iter().map(|(i, t)| (i, t.to_string())).collect();
+
+        let merged = reassemble(4, &[(chunks[0].clone(), first), (chunks[1].clone(), second)]);
+        assert_eq!(merged[2].as_deref(), Some("c-overlap"));
+        assert_eq!(reassemble(2, &[])[0], None);
+    }
+}
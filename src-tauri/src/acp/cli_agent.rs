@@ -0,0 +1,284 @@
+//! Claude Code以外のCLIエージェント（Codex CLI・Gemini CLI）のエグゼキューター
+//!
+//! プロンプトごとにCLIを非対話モードで起動し、stdinでプロンプトを渡して
+//! JSONLの出力を各CLIのパーサー（`adapters::codex`・`adapters::gemini`）で共通のイベントに変換する。
+
+use std::collections::VecDeque;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+use crate::log;
+use super::adapters::codex::CodexStreamParser;
+use super::adapters::gemini::GeminiStreamParser;
+use super::executor::{process_error, push_stderr_tail, ExecutorError};
+use super::message::AgentAddress;
+use super::stream_parser::UsageTotals;
+
+/// CLIエージェントの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CliAgentKind {
+    /// OpenAI Codex CLI（`codex exec --json`）
+    Codex,
+    /// Gemini CLI（`gemini --output-format stream-json`）
+    Gemini,
+}
+
+impl CliAgentKind {
+    /// アドレスのエージェント種別（`codex@localhost/cli` の `codex`）から判別する
+    pub fn from_address(address: &AgentAddress) -> Option<Self> {
+        match address.id.split('@').next().unwrap_or_default() {
+            "codex" => Some(Self::Codex),
+            "gemini" => Some(Self::Gemini),
+            _ => None,
+        }
+    }
+
+    /// 表示名
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Codex => "Codex CLI",
+            Self::Gemini => "Gemini CLI",
+        }
+    }
+
+    /// 設定のCLIのパス
+    pub fn program(&self) -> String {
+        let settings = crate::settings::current();
+        match self {
+            Self::Codex => settings.codex_path,
+            Self::Gemini => settings.gemini_path,
+        }
+    }
+
+    /// 非対話モードでJSONLを出力させる引数（プロンプトはstdinで渡す）
+    fn args(&self, options: &CliAgentOptions) -> Vec<String> {
+        let mut args: Vec<String> = match self {
+            Self::Codex => ["exec", "--json", "--skip-git-repo-check"].map(String::from).to_vec(),
+            Self::Gemini => ["--output-format", "stream-json"].map(String::from).to_vec(),
+        };
+        if let Some(ref model) = options.model {
+            args.extend(["--model".to_string(), model.clone()]);
+        }
+        args.extend(options.extra_args.iter().cloned());
+        // Codexは `-` でプロンプトをstdinから読む
+        if *self == Self::Codex {
+            args.push("-".to_string());
+        }
+        args
+    }
+
+    fn parser(&self) -> Box<dyn CliStreamParser> {
+        match self {
+            Self::Codex => Box::new(CodexStreamParser::default()),
+            Self::Gemini => Box::new(GeminiStreamParser::default()),
+        }
+    }
+}
+
+/// CLIの出力から変換した共通のイベント
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliEvent {
+    /// セッション（スレッド）ID
+    Session(String),
+    /// アシスタントのテキスト出力（受信した順）
+    Text(String),
+    /// ツール・コマンドの実行
+    ToolUse { name: String },
+    /// 使用量
+    Usage(UsageTotals),
+    /// 正常終了（最終的な回答）
+    Completed { output: String },
+    /// エラー終了
+    Failed(String),
+}
+
+/// CLIごとのJSONLパーサー
+pub trait CliStreamParser: Send {
+    /// 1行を解析する（JSONでない行・未知のイベントは無視する）
+    fn parse_line(&mut self, line: &str) -> Vec<CliEvent>;
+}
+
+/// CLIエージェントの実行オプション
+#[derive(Debug, Clone)]
+pub struct CliAgentOptions {
+    /// 作業ディレクトリ
+    pub working_dir: Option<String>,
+    /// モデル（`--model`）
+    pub model: Option<String>,
+    /// そのまま渡す追加のCLI引数
+    pub extra_args: Vec<String>,
+    /// タイムアウト（秒）
+    pub timeout_secs: u64,
+}
+
+impl Default for CliAgentOptions {
+    fn default() -> Self {
+        Self {
+            working_dir: None,
+            model: None,
+            extra_args: vec![],
+            timeout_secs: crate::settings::current().executor_timeout_secs,
+        }
+    }
+}
+
+/// Codex CLI・Gemini CLIのエグゼキューター
+pub struct CliAgentExecutor {
+    kind: CliAgentKind,
+    options: CliAgentOptions,
+    /// 最後に受け取ったセッションID
+    session_id: Option<String>,
+    /// 使用量の累計
+    usage: UsageTotals,
+}
+
+impl CliAgentExecutor {
+    pub fn new(kind: CliAgentKind, options: CliAgentOptions) -> Self {
+        Self {
+            kind,
+            options,
+            session_id: None,
+            usage: UsageTotals::default(),
+        }
+    }
+
+    /// 使用量の累計
+    pub fn usage(&self) -> UsageTotals {
+        self.usage
+    }
+
+    /// CLIがインストールされているか確認し、バージョンを返す
+    pub async fn check_available(&self) -> Result<String, ExecutorError> {
+        let program = self.kind.program();
+        let output = crate::process_env::tokio_command(&program)
+            .arg("--version")
+            .output()
+            .await
+            .map_err(|e| ExecutorError::Process(format!("{} not found ({}): {}", self.kind.label(), program, e)))?;
+        if !output.status.success() {
+            return Err(ExecutorError::Process(format!("{} --version failed", program)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// プロンプトを実行して最終的なテキストを返す
+    pub async fn execute(&mut self, prompt: &str) -> Result<String, ExecutorError> {
+        self.execute_with(prompt, |_| {}).await
+    }
+
+    /// プロンプトを実行し、イベントを受け取るたびに `on_event` を呼ぶ
+    pub async fn execute_with<F>(&mut self, prompt: &str, on_event: F) -> Result<String, ExecutorError>
+    where
+        F: FnMut(&CliEvent) + Send,
+    {
+        let timeout = std::time::Duration::from_secs(self.options.timeout_secs);
+        match tokio::time::timeout(timeout, self.run(prompt, on_event)).await {
+            Ok(result) => result,
+            Err(_) => Err(ExecutorError::Timeout(format!(
+                "{} did not complete within {} seconds",
+                self.kind.label(),
+                self.options.timeout_secs
+            ))),
+        }
+    }
+
+    async fn run<F>(&mut self, prompt: &str, mut on_event: F) -> Result<String, ExecutorError>
+    where
+        F: FnMut(&CliEvent) + Send,
+    {
+        let program = self.kind.program();
+        let mut cmd = crate::process_env::tokio_command(&program);
+        // Windowsではコンソールウィンドウを開かない（CREATE_NO_WINDOW）
+        #[cfg(windows)]
+        cmd.creation_flags(0x0800_0000);
+        cmd.args(self.kind.args(&self.options));
+        if let Some(ref dir) = self.options.working_dir {
+            cmd.current_dir(dir);
+        }
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        log::info("CliAgentExecutor", &format!("Starting {}: {} chars prompt", self.kind.label(), prompt.len()));
+        let mut child = cmd.spawn()
+            .map_err(|e| ExecutorError::Process(format!("Failed to start {} ({}): {}", self.kind.label(), program, e)))?;
+
+        let mut stdin = child.stdin.take().ok_or_else(|| ExecutorError::Process("Failed to open stdin".to_string()))?;
+        let stdout = child.stdout.take().ok_or_else(|| ExecutorError::Process("Failed to open stdout".to_string()))?;
+        let stderr = child.stderr.take().ok_or_else(|| ExecutorError::Process("Failed to open stderr".to_string()))?;
+
+        // stdinを閉じるとプロンプトの終わりになる
+        stdin.write_all(prompt.as_bytes()).await?;
+        drop(stdin);
+
+        let stderr_tail = Arc::new(Mutex::new(VecDeque::new()));
+        let tail = stderr_tail.clone();
+        let stderr_reader = tokio::spawn(async move {
+            let mut lines = tokio::io::BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                push_stderr_tail(&tail, line);
+            }
+        });
+
+        let mut parser = self.kind.parser();
+        let mut output = None;
+        let mut failure = None;
+
+        let mut lines = tokio::io::BufReader::new(stdout).lines();
+        while let Some(line) = lines.next_line().await? {
+            for event in parser.parse_line(&line) {
+                on_event(&event);
+                match event {
+                    CliEvent::Session(id) => self.session_id = Some(id),
+                    CliEvent::Usage(usage) => self.usage.add(&usage),
+                    CliEvent::Completed { output: text } => output = Some(text),
+                    CliEvent::Failed(message) => failure = Some(message),
+                    CliEvent::Text(_) | CliEvent::ToolUse { .. } => {}
+                }
+            }
+        }
+
+        let status = child.wait().await?;
+        let _ = stderr_reader.await;
+
+        let output = match (output, failure) {
+            (Some(output), None) if status.success() => output,
+            (_, failure) => {
+                let message = failure.unwrap_or_else(|| format!("{} exited with {} before completing", self.kind.label(), status));
+                log::error("CliAgentExecutor", &message);
+                return Err(process_error(&message, &stderr_tail));
+            }
+        };
+
+        log::info("CliAgentExecutor", &format!(
+            "{} completed (session {})",
+            self.kind.label(),
+            self.session_id.as_deref().unwrap_or("-")
+        ));
+        Ok(output.trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_from_address() {
+        let codex = AgentAddress::parse("codex@localhost/cli").unwrap();
+        assert_eq!(CliAgentKind::from_address(&codex), Some(CliAgentKind::Codex));
+        let gemini = AgentAddress::new("gemini@localhost");
+        assert_eq!(CliAgentKind::from_address(&gemini), Some(CliAgentKind::Gemini));
+        assert_eq!(CliAgentKind::from_address(&AgentAddress::new("claude-code@localhost/cli")), None);
+
+        let options = CliAgentOptions { model: Some("gpt-5".to_string()), ..Default::default() };
+        assert_eq!(CliAgentKind::Codex.args(&options), ["exec", "--json", "--skip-git-repo-check", "--model", "gpt-5", "-"]);
+        assert_eq!(CliAgentKind::Gemini.args(&CliAgentOptions::default()), ["--output-format", "stream-json"]);
+    }
+}
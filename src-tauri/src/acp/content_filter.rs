@@ -0,0 +1,217 @@
+//! コンテンツフィルター
+//!
+//! 音声生成の前に訳文から不適切な表現（罵倒語・センシティブな内容）を検出し、
+//! 単語リストに一致した箇所を伏せ字に置換するかフラグを付ける。
+//! LLMによるチェックはフラグのみ（置換箇所を特定できないため）。
+
+use serde::{Deserialize, Serialize};
+
+/// 一致したときの処理
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    /// 置換文字列に置き換える
+    #[default]
+    Mask,
+    /// 置換せずに報告のみ
+    Flag,
+}
+
+/// コンテンツフィルター設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentFilterConfig {
+    /// 禁止語リスト（大文字小文字は区別しない）
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// 一致したときの処理
+    #[serde(default)]
+    pub action: FilterAction,
+    /// 伏せ字（音声で読み上げられるため既定は「ピー」）
+    #[serde(default = "default_replacement")]
+    pub replacement: String,
+    /// LLMで不適切な内容をチェックする
+    #[serde(default)]
+    pub llm_check: bool,
+}
+
+fn default_replacement() -> String {
+    "ピー".to_string()
+}
+
+impl Default for ContentFilterConfig {
+    fn default() -> Self {
+        Self {
+            words: Vec::new(),
+            action: FilterAction::default(),
+            replacement: default_replacement(),
+            llm_check: false,
+        }
+    }
+}
+
+/// 検出結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterMatch {
+    /// セグメント番号
+    pub segment: usize,
+    /// 一致した語（LLMの場合は理由）
+    pub term: String,
+    /// 検出方法（"word_list" / "llm"）
+    pub detector: String,
+    /// 伏せ字に置換したか
+    pub masked: bool,
+}
+
+/// フィルター結果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterReport {
+    pub masked: usize,
+    pub flagged: usize,
+    pub matches: Vec<FilterMatch>,
+}
+
+impl FilterReport {
+    /// 検出結果を追加
+    pub fn push(&mut self, m: FilterMatch) {
+        if m.masked {
+            self.masked += 1;
+        } else {
+            self.flagged += 1;
+        }
+        self.matches.push(m);
+    }
+}
+
+impl ContentFilterConfig {
+    /// 単語リストを訳文に適用する
+    pub fn apply_word_list(&self, translations: &mut [(usize, String)], report: &mut FilterReport) {
+        for (index, text) in translations.iter_mut() {
+            for word in self.words.iter().filter(|w| !w.trim().is_empty()) {
+                let Some(masked) = replace_ignore_case(text, word.trim(), &self.replacement) else {
+                    continue;
+                };
+                let mask = self.action == FilterAction::Mask;
+                if mask {
+                    *text = masked;
+                }
+                report.push(FilterMatch {
+                    segment: *index,
+                    term: word.trim().to_string(),
+                    detector: "word_list".to_string(),
+                    masked: mask,
+                });
+            }
+        }
+    }
+}
+
+/// LLMチェック用プロンプト
+pub fn build_llm_check_prompt(translations: &[(usize, String)]) -> String {
+    let text = translations
+        .iter()
+        .map(|(index, text)| format!("[{}] {}", index, text))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "以下は動画の吹替用字幕です。子ども向け・動画プラットフォームの規約上不適切な表現\n\
+         （罵倒語、性的な表現、暴力的な描写、差別的な表現など）を含むセグメントを探してください。\n\n\
+         {}\n\n\
+         該当するセグメントだけを「[番号] 理由」の形式で1行ずつ出力してください。\n\
+         該当がなければ「なし」とだけ出力してください。",
+        text
+    )
+}
+
+/// 大文字小文字を区別せずに単語単位で置換する（一致しなければNone）
+///
+/// 英字などスペースで区切る文字の語は前後が単語境界のときだけ一致させる
+/// （"ass" で "class" を伏せ字にしない）。日本語の語は部分一致のまま。
+fn replace_ignore_case(text: &str, word: &str, replacement: &str) -> Option<String> {
+    let re = regex::RegexBuilder::new(&regex::escape(word))
+        .case_insensitive(true)
+        .build()
+        .ok()?;
+
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut found = false;
+    for m in re.find_iter(text) {
+        let before = text[..m.start()].chars().next_back();
+        let after = text[m.end()..].chars().next();
+        let first = m.as_str().chars().next();
+        let end = m.as_str().chars().next_back();
+        if joins_word(before, first) || joins_word(end, after) {
+            continue;
+        }
+        result.push_str(&text[last..m.start()]);
+        result.push_str(replacement);
+        last = m.end();
+        found = true;
+    }
+    if !found {
+        return None;
+    }
+    result.push_str(&text[last..]);
+    Some(result)
+}
+
+/// 隣り合う2文字が同じ単語の一部か（スペースで区切る文字どうしのときだけ）
+fn joins_word(a: Option<char>, b: Option<char>) -> bool {
+    let spaced = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() && c < '\u{3000}');
+    spaced(a) && spaced(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_word_list() {
+        let config = ContentFilterConfig {
+            words: vec!["damn".to_string(), "くそ".to_string()],
+            ..Default::default()
+        };
+        let mut translations = vec![(0, "Damn, くそっ".to_string()), (1, "こんにちは".to_string())];
+        let mut report = FilterReport::default();
+        config.apply_word_list(&mut translations, &mut report);
+
+        assert_eq!(translations[0].1, "ピー, ピーっ");
+        assert_eq!(translations[1].1, "こんにちは");
+        assert_eq!(report.masked, 2);
+    }
+
+    #[test]
+    fn test_flag_only() {
+        let config = ContentFilterConfig {
+            words: vec!["damn".to_string()],
+            action: FilterAction::Flag,
+            ..Default::default()
+        };
+        let mut translations = vec![(3, "damn it".to_string())];
+        let mut report = FilterReport::default();
+        config.apply_word_list(&mut translations, &mut report);
+
+        assert_eq!(translations[0].1, "damn it");
+        assert_eq!(report.flagged, 1);
+        assert_eq!(report.matches[0].segment, 3);
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        let config = ContentFilterConfig {
+            words: vec!["ass".to_string()],
+            ..Default::default()
+        };
+        let mut translations = vec![
+            (0, "First class passage".to_string()),
+            (1, "Ass! You ass.".to_string()),
+        ];
+        let mut report = FilterReport::default();
+        config.apply_word_list(&mut translations, &mut report);
+
+        assert_eq!(translations[0].1, "First class passage");
+        assert_eq!(translations[1].1, "ピー! You ピー.");
+        assert_eq!(report.masked, 1);
+    }
+}
@@ -0,0 +1,151 @@
+//! Remote agent discovery
+//!
+//! Fetches A2A agent cards from `https://<host>/.well-known/agent.json` so
+//! agents running elsewhere can be registered with the orchestrator.
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+use super::agent::{AgentCard, Transport, A2A_PROTOCOL_VERSION};
+
+/// Path where A2A agents publish their card
+pub const WELL_KNOWN_PATH: &str = "/.well-known/agent.json";
+
+/// Timeout for fetching a card
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Discovery error types
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Request failed: {0}")]
+    Http(String),
+
+    #[error("Invalid agent card: {0}")]
+    InvalidCard(String),
+
+    #[error("Unsupported protocol version {found} (expected {expected})")]
+    UnsupportedProtocol { found: String, expected: String },
+}
+
+/// Resolve the card URL for a host or base URL
+///
+/// Accepts `host`, `https://host`, `https://host/base` or a full
+/// `.../agent.json` URL. Scheme defaults to https.
+pub fn well_known_url(base: &str) -> Result<String, DiscoveryError> {
+    let base = base.trim().trim_end_matches('/');
+    if base.is_empty() {
+        return Err(DiscoveryError::InvalidUrl("empty URL".to_string()));
+    }
+    if base.ends_with(".json") {
+        return Ok(base.to_string());
+    }
+
+    let base = if base.contains("://") {
+        if !(base.starts_with("http://") || base.starts_with("https://")) {
+            return Err(DiscoveryError::InvalidUrl(base.to_string()));
+        }
+        base.to_string()
+    } else {
+        format!("https://{}", base)
+    };
+    Ok(format!("{}{}", base, WELL_KNOWN_PATH))
+}
+
+/// Whether a card's protocol version can be used (same major.minor)
+pub fn is_compatible_version(version: &str) -> bool {
+    let major_minor = |v: &str| {
+        let mut parts = v.trim().trim_start_matches('v').split('.');
+        (parts.next().map(str::to_string), parts.next().map(str::to_string))
+    };
+    let (major, minor) = major_minor(version);
+    major.is_some() && minor.is_some() && (major, minor) == major_minor(A2A_PROTOCOL_VERSION)
+}
+
+/// Parse and validate a fetched card
+///
+/// Cards without an internal `id` get their endpoint URL as ID, and the
+/// transport is inferred from the endpoint scheme when missing.
+pub fn parse_card(json: &str) -> Result<AgentCard, DiscoveryError> {
+    let mut card: AgentCard =
+        serde_json::from_str(json).map_err(|e| DiscoveryError::InvalidCard(e.to_string()))?;
+
+    if !is_compatible_version(&card.protocol_version) {
+        return Err(DiscoveryError::UnsupportedProtocol {
+            found: card.protocol_version,
+            expected: A2A_PROTOCOL_VERSION.to_string(),
+        });
+    }
+    if card.name.trim().is_empty() || card.url.trim().is_empty() {
+        return Err(DiscoveryError::InvalidCard("name and url are required".to_string()));
+    }
+
+    if card.id.is_none() {
+        card.id = Some(card.url.clone());
+    }
+    if card.transport.is_none() {
+        card.transport = if card.url.starts_with("ws://") || card.url.starts_with("wss://") {
+            Some(Transport::WebSocket)
+        } else {
+            Some(Transport::Http)
+        };
+    }
+    Ok(card)
+}
+
+/// Fetch and validate the agent card published by `base`
+pub async fn fetch_agent_card(base: &str) -> Result<AgentCard, DiscoveryError> {
+    let url = well_known_url(base)?;
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+
+    let resp = client
+        .get(&url)
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| DiscoveryError::Http(format!("{}: {}", url, e)))?;
+    if !resp.status().is_success() {
+        return Err(DiscoveryError::Http(format!("{}: HTTP {}", url, resp.status())));
+    }
+
+    let body = resp.text().await.map_err(|e| DiscoveryError::Http(e.to_string()))?;
+    parse_card(&body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_well_known_url() {
+        assert_eq!(well_known_url("agent.example.com").unwrap(), "https://agent.example.com/.well-known/agent.json");
+        assert_eq!(well_known_url("http://localhost:8080/").unwrap(), "http://localhost:8080/.well-known/agent.json");
+        assert_eq!(
+            well_known_url("https://example.com/agents/a.json").unwrap(),
+            "https://example.com/agents/a.json"
+        );
+        assert!(matches!(well_known_url("ftp://example.com"), Err(DiscoveryError::InvalidUrl(_))));
+        assert!(matches!(well_known_url("  "), Err(DiscoveryError::InvalidUrl(_))));
+    }
+
+    #[test]
+    fn test_parse_card() {
+        let json = AgentCard::new("Remote", "wss://agent.example.com/acp").to_a2a_json().unwrap();
+        let card = parse_card(&json).unwrap();
+        assert_eq!(card.id.as_deref(), Some("wss://agent.example.com/acp"));
+        assert_eq!(card.transport, Some(Transport::WebSocket));
+
+        let mut old = AgentCard::new("Old", "https://old.example.com");
+        old.protocol_version = "0.1.0".to_string();
+        let result = parse_card(&old.to_a2a_json().unwrap());
+        assert!(matches!(result, Err(DiscoveryError::UnsupportedProtocol { .. })));
+
+        assert!(matches!(parse_card("{}"), Err(DiscoveryError::InvalidCard(_))));
+    }
+}
@@ -3,23 +3,30 @@
 //! CLIモード（--print --output-format stream-json）でClaude Codeを実行する。
 //! 子プロセス管理、stdin/stdout処理、イベント発行を担当。
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 use thiserror::Error;
 use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt};
-use tokio::process::{Child, ChildStdin, Command};
-use tokio::sync::mpsc;
+use tokio::process::{Child, ChildStdin};
+use tokio::sync::{mpsc, watch, Notify};
+use tokio::task::JoinHandle;
 
+use crate::events::RecordedEmit;
 use crate::log;
-use super::permission::{PermissionDecision, PermissionManager};
-use super::state_machine::{AgentState, StateEvent, StateMachine};
-use super::stream_parser::{ParsedEvent, StreamParser};
+use crate::transcript::{self, EntryKind, TranscriptEntry, TranscriptError};
+use super::parser::content_hash;
+use super::permission::{PermissionDecision, PermissionManager, PermissionResponder, PolicyStore};
+use super::state_machine::{AgentState, InvalidTransitionPolicy, StateEvent, StateMachine, StateTransition};
+use super::stream_parser::{ParsedEvent, StreamParser, UsageTotals};
 
 /// エグゼキューターエラー
 #[derive(Debug, Error)]
@@ -44,6 +51,12 @@ pub enum ExecutorError {
 
     #[error("Not running")]
     NotRunning,
+
+    #[error("Invalid option: {0}")]
+    InvalidOption(String),
+
+    #[error("Cancelled")]
+    Cancelled,
 }
 
 /// エグゼキューターイベント
@@ -55,29 +68,115 @@ pub enum ExecutorEvent {
         old_state: AgentState,
         new_state: AgentState,
     },
-    /// 出力受信
-    Output { content: String },
-    /// ツール実行
+    /// 出力受信（ストリーミング実行中はそのタスクID付き）
+    Output { content: String, task_id: Option<String> },
+    /// ツール実行（結果を受信した時点で1回）
     ToolExecution {
+        id: String,
         name: String,
         input: Value,
         result: Option<String>,
         is_error: bool,
-    },
-    /// 権限要求
-    PermissionRequired {
-        request_id: String,
-        tool_name: String,
-        options: Vec<String>,
+        duration_ms: Option<u64>,
     },
     /// 進捗更新
     Progress { message: String, percentage: u8 },
+    /// 1リクエスト分の使用量と、このセッションの累計
+    Usage { request: UsageTotals, session: UsageTotals },
     /// 完了
     Completed { output: String },
     /// エラー
     Error { message: String, recoverable: bool },
 }
 
+/// `executor:output` のペイロード
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputChunk {
+    /// `execute_streaming` に渡したタスクID
+    pub task_id: String,
+    pub content: String,
+}
+
+/// `executor:usage` のペイロード
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageUpdate {
+    /// 今回のリクエスト分
+    pub request: UsageTotals,
+    /// このセッション（エグゼキューター起動から）の累計
+    pub session: UsageTotals,
+}
+
+/// stderrの末尾として保持する行数（`ExecutorError::Process` に含める）
+const STDERR_TAIL_LINES: usize = 20;
+
+/// stderrから判別した既知の失敗
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// 未ログイン・認証切れ
+    NotLoggedIn,
+    /// 不明なフラグ・引数
+    InvalidFlag,
+    /// CLIのバージョンが古い
+    VersionTooOld,
+}
+
+impl DiagnosticKind {
+    /// stderrの1行を判別する（既知の失敗でなければNone）
+    pub fn classify(line: &str) -> Option<Self> {
+        let line = line.to_lowercase();
+        let has = |patterns: &[&str]| patterns.iter().any(|p| line.contains(p));
+
+        if has(&["not logged in", "/login", "invalid api key", "authentication_error", "oauth token has expired", "401 unauthorized"]) {
+            Some(Self::NotLoggedIn)
+        } else if has(&["unknown option", "unknown argument", "unrecognized option", "unexpected argument", "error: option"]) {
+            Some(Self::InvalidFlag)
+        } else if has(&["too old", "newer version", "no longer supported", "please upgrade"]) {
+            Some(Self::VersionTooOld)
+        } else {
+            None
+        }
+    }
+
+    /// 対処方法
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::NotLoggedIn => "ターミナルで `claude` を起動して /login でログインしてください",
+            Self::InvalidFlag => "executor_start の extraArgs を確認するか、Claude Codeを更新してください",
+            Self::VersionTooOld => "`claude update` でClaude Codeを更新してください",
+        }
+    }
+}
+
+/// `executor:diagnostic` のペイロード
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// stderrの該当行
+    pub line: String,
+    pub hint: String,
+}
+
+/// stderrの行を末尾に追加する（`STDERR_TAIL_LINES` 行を超えたら古い行から捨てる）
+pub(crate) fn push_stderr_tail(stderr_tail: &Mutex<VecDeque<String>>, line: String) {
+    let mut tail = stderr_tail.lock();
+    if tail.len() == STDERR_TAIL_LINES {
+        tail.pop_front();
+    }
+    tail.push_back(line);
+}
+
+/// メッセージにstderrの末尾を付けたプロセスエラー
+pub(crate) fn process_error(message: &str, stderr_tail: &Mutex<VecDeque<String>>) -> ExecutorError {
+    let tail = stderr_tail.lock();
+    if tail.is_empty() {
+        ExecutorError::Process(message.to_string())
+    } else {
+        let lines: Vec<&str> = tail.iter().map(String::as_str).collect();
+        ExecutorError::Process(format!("{}\nstderr:\n{}", message, lines.join("\n")))
+    }
+}
+
 /// 実行オプション
 #[derive(Debug, Clone)]
 pub struct ExecutorOptions {
@@ -89,6 +188,14 @@ pub struct ExecutorOptions {
     pub timeout_secs: u64,
     /// セッションID（resume用）
     pub session_id: Option<String>,
+    /// 遷移表にないイベントを受け取ったときの扱い
+    pub transition_policy: InvalidTransitionPolicy,
+    /// モデル（`--model`、`haiku`・`opus` などの別名も可。Noneなら既定）
+    pub model: Option<String>,
+    /// 1プロンプトあたりの最大ターン数（`--max-turns`）
+    pub max_turns: Option<u32>,
+    /// そのまま渡す追加のCLI引数
+    pub extra_args: Vec<String>,
 }
 
 impl Default for ExecutorOptions {
@@ -96,8 +203,309 @@ impl Default for ExecutorOptions {
         Self {
             working_dir: None,
             allowed_tools: vec![],
-            timeout_secs: 300,
+            timeout_secs: crate::settings::current().executor_timeout_secs,
             session_id: None,
+            transition_policy: InvalidTransitionPolicy::default(),
+            model: None,
+            max_turns: None,
+            extra_args: vec![],
+        }
+    }
+}
+
+/// エグゼキューターが管理するため `extra_args` に指定できないフラグ
+const RESERVED_FLAGS: &[&str] = &[
+    "-p", "--print", "--output-format", "--input-format", "-r", "--resume", "-c", "--continue",
+    "--model", "--max-turns",
+];
+
+impl ExecutorOptions {
+    /// `--model`・`--max-turns`・追加引数（起動時のコマンドラインの末尾）
+    ///
+    /// ストリームの形式やセッションはエグゼキューターが管理するので、追加引数では上書きできない。
+    pub fn cli_args(&self) -> Result<Vec<String>, ExecutorError> {
+        let mut args = Vec::new();
+        if let Some(ref model) = self.model {
+            if model.trim().is_empty() {
+                return Err(ExecutorError::InvalidOption("model is empty".to_string()));
+            }
+            args.extend(["--model".to_string(), model.trim().to_string()]);
+        }
+        if let Some(max_turns) = self.max_turns {
+            if max_turns == 0 {
+                return Err(ExecutorError::InvalidOption("max_turns must be at least 1".to_string()));
+            }
+            args.extend(["--max-turns".to_string(), max_turns.to_string()]);
+        }
+        for arg in &self.extra_args {
+            let flag = arg.split('=').next().unwrap_or_default();
+            if RESERVED_FLAGS.contains(&flag) {
+                return Err(ExecutorError::InvalidOption(format!("{} is managed by the executor", flag)));
+            }
+            args.push(arg.clone());
+        }
+        Ok(args)
+    }
+}
+
+/// 権限要求への人間の回答を待つ時間（秒、過ぎたら拒否）
+pub const PERMISSION_TIMEOUT_SECS: u64 = 300;
+
+/// エグゼキューター状態の保存先ファイル名（アプリデータディレクトリ直下）
+pub const EXECUTOR_STATE_FILE: &str = "executor_state.json";
+
+/// 再起動後に再接続するためのエグゼキューター状態
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutorSnapshot {
+    /// Claude CodeのセッションID（`--resume` 用）
+    pub session_id: Option<String>,
+    /// 保存時の状態
+    pub state: AgentState,
+    /// 実行中だったプロンプトのハッシュ（完了済みならNone）
+    pub prompt_hash: Option<String>,
+    /// 作業ディレクトリ
+    pub working_dir: Option<String>,
+    /// 事前許可ツール
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// モデル
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 最大ターン数
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+    /// 追加のCLI引数
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// 保存時刻
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub saved_at: DateTime<Utc>,
+}
+
+impl ExecutorSnapshot {
+    /// ファイルから読み込む（ファイルがなければNone）
+    pub fn load(path: &Path) -> Result<Option<Self>, ExecutorError> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// ファイルに保存する
+    pub fn save(&self, path: &Path) -> Result<(), ExecutorError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 実行中のタスクが中断されたか
+    pub fn is_interrupted(&self) -> bool {
+        self.prompt_hash.is_some()
+    }
+
+    /// 同じセッション・設定で再開するためのオプション
+    pub fn resume_options(&self) -> ExecutorOptions {
+        ExecutorOptions {
+            working_dir: self.working_dir.clone(),
+            allowed_tools: self.allowed_tools.clone(),
+            session_id: self.session_id.clone(),
+            model: self.model.clone(),
+            max_turns: self.max_turns,
+            extra_args: self.extra_args.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// 過去のセッションを再開するための状態（実行中のタスクはないものとする）
+    pub fn for_session(session: &ExecutorSession) -> Self {
+        Self {
+            session_id: Some(session.session_id.clone()),
+            state: AgentState::idle(),
+            prompt_hash: None,
+            working_dir: session.working_dir.clone(),
+            allowed_tools: Vec::new(),
+            model: None,
+            max_turns: None,
+            extra_args: Vec::new(),
+            saved_at: Utc::now(),
+        }
+    }
+}
+
+/// エグゼキューターの記録のファイル名の接頭辞
+const TRANSCRIPT_PREFIX: &str = "executor";
+
+/// 過去のClaude Codeセッション（エグゼキューターの記録から集計）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecutorSession {
+    /// Claude CodeのセッションID（`--resume` 用）
+    pub session_id: String,
+    /// このセッションを含む記録のID（古い順）
+    pub transcript_ids: Vec<String>,
+    /// 作業ディレクトリ（initイベントの `cwd`）
+    pub working_dir: Option<String>,
+    pub model: Option<String>,
+    /// 最初のプロンプト（先頭200バイトまで）
+    pub first_prompt: Option<String>,
+    pub prompt_count: usize,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+impl ExecutorSession {
+    fn new(session_id: &str, transcript_id: &str, timestamp: &str) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            transcript_ids: vec![transcript_id.to_string()],
+            working_dir: None,
+            model: None,
+            first_prompt: None,
+            prompt_count: 0,
+            started_at: timestamp.to_string(),
+            updated_at: timestamp.to_string(),
+        }
+    }
+
+    fn add_prompt(&mut self, entry: &TranscriptEntry) {
+        if self.first_prompt.is_none() {
+            self.first_prompt = Some(truncate_safe(entry.text.trim(), 200).to_string());
+        }
+        self.prompt_count += 1;
+        self.started_at = self.started_at.clone().min(entry.timestamp.clone());
+    }
+}
+
+/// 記録（ID・古い順のエントリー）からセッションを集計する（新しい順）
+///
+/// プロンプトはその後に届いたイベントのセッションに数える。
+fn summarize_sessions(transcripts: Vec<(String, Vec<TranscriptEntry>)>) -> Vec<ExecutorSession> {
+    let mut sessions: Vec<ExecutorSession> = Vec::new();
+    for (transcript_id, entries) in transcripts {
+        let mut prompts: Vec<&TranscriptEntry> = Vec::new();
+        for entry in &entries {
+            if entry.kind == EntryKind::Prompt {
+                prompts.push(entry);
+                continue;
+            }
+            let Some(data) = entry.data.as_ref() else { continue };
+            let Some(session_id) = data.get("session_id").and_then(Value::as_str) else { continue };
+
+            let index = match sessions.iter().position(|s| s.session_id == session_id) {
+                Some(index) => index,
+                None => {
+                    sessions.push(ExecutorSession::new(session_id, &transcript_id, &entry.timestamp));
+                    sessions.len() - 1
+                }
+            };
+            let session = &mut sessions[index];
+            if !session.transcript_ids.contains(&transcript_id) {
+                session.transcript_ids.push(transcript_id.clone());
+            }
+            if let Some(cwd) = data.get("cwd").and_then(Value::as_str) {
+                session.working_dir = Some(cwd.to_string());
+            }
+            if let Some(model) = data.get("model").and_then(Value::as_str) {
+                session.model = Some(model.to_string());
+            }
+            for prompt in prompts.drain(..) {
+                session.add_prompt(prompt);
+            }
+            session.updated_at = session.updated_at.clone().max(entry.timestamp.clone());
+        }
+    }
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    sessions
+}
+
+/// 記録に残っている過去のセッション（新しい順）
+pub fn list_sessions() -> Result<Vec<ExecutorSession>, TranscriptError> {
+    let mut transcripts = Vec::new();
+    for summary in transcript::list()? {
+        if !summary.session_id.starts_with(&format!("{}-", TRANSCRIPT_PREFIX)) {
+            continue;
+        }
+        match transcript::load(&summary.session_id) {
+            Ok(entries) => transcripts.push((summary.session_id, entries)),
+            Err(e) => log::warn("ClaudeCodeExecutor", &format!("Failed to read {}: {}", summary.session_id, e)),
+        }
+    }
+    // ファイル名に日時が入っているので名前順が古い順
+    transcripts.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(summarize_sessions(transcripts))
+}
+
+/// 状態が変わるたびにスナップショットを書き出す（stdout読み込みタスクと共有）
+#[derive(Clone)]
+struct SnapshotWriter {
+    path: PathBuf,
+    session_id: Arc<Mutex<Option<String>>>,
+    prompt_hash: Arc<Mutex<Option<String>>>,
+    state_machine: Arc<Mutex<StateMachine>>,
+    working_dir: Option<String>,
+    allowed_tools: Vec<String>,
+    model: Option<String>,
+    max_turns: Option<u32>,
+    extra_args: Vec<String>,
+}
+
+impl SnapshotWriter {
+    fn write(&self) {
+        let snapshot = ExecutorSnapshot {
+            session_id: self.session_id.lock().clone(),
+            state: self.state_machine.lock().current_state().clone(),
+            prompt_hash: self.prompt_hash.lock().clone(),
+            working_dir: self.working_dir.clone(),
+            allowed_tools: self.allowed_tools.clone(),
+            model: self.model.clone(),
+            max_turns: self.max_turns,
+            extra_args: self.extra_args.clone(),
+            saved_at: Utc::now(),
+        };
+        if let Err(e) = snapshot.save(&self.path) {
+            log::error("ClaudeCodeExecutor", &format!("Failed to save executor state: {}", e));
+        }
+    }
+}
+
+/// 実行中のタスクの中止要求
+///
+/// 複製しても同じ要求を共有するので、実行中のエグゼキューターをロックせずに中止できる。
+#[derive(Clone, Default)]
+pub struct ExecutorCanceller {
+    requested: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl ExecutorCanceller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 中止を要求する（実行中の待機はすぐに `ExecutorError::Cancelled` で戻る）
+    pub fn cancel(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    fn reset(&self) {
+        self.requested.store(false, Ordering::SeqCst);
+    }
+
+    /// 中止が要求されるまで待つ
+    async fn cancelled(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
         }
     }
 }
@@ -108,12 +516,28 @@ pub struct ClaudeCodeExecutor {
     process: Option<Child>,
     /// stdin
     stdin: Option<ChildStdin>,
-    /// セッションID
-    session_id: Option<String>,
+    /// セッションID（stdout読み込みタスクが更新する）
+    session_id: Arc<Mutex<Option<String>>>,
+    /// 実行中のプロンプトのハッシュ
+    prompt_hash: Arc<Mutex<Option<String>>>,
+    /// ストリーミング実行中のタスクID（出力を `executor:output` で転送する）
+    stream_task: Arc<Mutex<Option<String>>>,
+    /// セッションの使用量の累計（stdout読み込みタスクが加算する）
+    usage: Arc<Mutex<UsageTotals>>,
+    /// 直前の `execute` で使った分
+    last_usage: UsageTotals,
+    /// stderrの末尾（stderr読み込みタスクが追記する）
+    stderr_tail: Arc<Mutex<VecDeque<String>>>,
+    /// 状態の保存先（未設定なら保存しない）
+    snapshot: Option<SnapshotWriter>,
+    /// 実行中のタスクの中止要求
+    canceller: ExecutorCanceller,
     /// 権限マネージャー
     permission_manager: Arc<Mutex<PermissionManager>>,
     /// 状態マシン
     state_machine: Arc<Mutex<StateMachine>>,
+    /// 最新の状態（状態マシンのリスナーが更新する）
+    state_tx: Arc<watch::Sender<AgentState>>,
     /// ストリームパーサー
     parser: StreamParser,
     /// イベント送信チャネル
@@ -126,6 +550,8 @@ pub struct ClaudeCodeExecutor {
     options: ExecutorOptions,
     /// 実行中かどうか
     is_running: bool,
+    /// 記録（transcript）のセッションID
+    transcript_id: String,
 }
 
 impl ClaudeCodeExecutor {
@@ -139,17 +565,34 @@ impl ClaudeCodeExecutor {
             permission_manager.add_pre_approved(tool);
         }
 
+        // 状態遷移をwatchチャネルに流し、待機側がポーリングせずに済むようにする
+        let state_tx = Arc::new(watch::channel(AgentState::Initializing).0);
+        let mut state_machine = StateMachine::new().with_policy(options.transition_policy);
+        let tx = state_tx.clone();
+        state_machine.subscribe(move |transition| {
+            tx.send_replace(transition.to.clone());
+        });
+
         Self {
             process: None,
             stdin: None,
-            session_id: options.session_id.clone(),
+            session_id: Arc::new(Mutex::new(options.session_id.clone())),
+            prompt_hash: Arc::new(Mutex::new(None)),
+            stream_task: Arc::new(Mutex::new(None)),
+            usage: Arc::new(Mutex::new(UsageTotals::default())),
+            last_usage: UsageTotals::default(),
+            stderr_tail: Arc::new(Mutex::new(VecDeque::new())),
+            snapshot: None,
+            canceller: ExecutorCanceller::new(),
             permission_manager: Arc::new(Mutex::new(permission_manager)),
-            state_machine: Arc::new(Mutex::new(StateMachine::new())),
+            state_machine: Arc::new(Mutex::new(state_machine)),
+            state_tx,
             parser: StreamParser::new(),
             event_tx,
             event_rx: Some(event_rx),
             app_handle: Arc::new(Mutex::new(None)),
             options,
+            transcript_id: crate::transcript::new_session_id(TRANSCRIPT_PREFIX),
             is_running: false,
         }
     }
@@ -160,14 +603,111 @@ impl ClaudeCodeExecutor {
         self.permission_manager.lock().set_app_handle(handle);
     }
 
+    /// 権限要求の回答先を差し替える（エグゼキューターをロックせずに回答できるよう共有する）
+    pub fn set_permission_responder(&self, responder: PermissionResponder) {
+        self.permission_manager.lock().set_responder(responder);
+    }
+
+    /// 権限ルールの保存先を差し替える（コマンドで編集したルールをすぐ反映するため共有する）
+    pub fn set_permission_policies(&self, policies: Arc<Mutex<PolicyStore>>) {
+        self.permission_manager.lock().set_policy_store(policies);
+    }
+
+    /// 中止要求の受け口を差し替える（実行中でもエグゼキューターをロックせずに中止できるよう共有する）
+    pub fn set_canceller(&mut self, canceller: ExecutorCanceller) {
+        self.canceller = canceller;
+    }
+
+    /// 使用量の集計先を差し替える（実行中でもエグゼキューターをロックせずに読めるよう共有する）
+    ///
+    /// stdout読み込みタスクが参照するので、`start` より前に呼ぶ。
+    pub fn set_usage_counter(&mut self, usage: Arc<Mutex<UsageTotals>>) {
+        self.usage = usage;
+    }
+
     /// 現在の状態を取得
     pub fn current_state(&self) -> AgentState {
         self.state_machine.lock().current_state().clone()
     }
 
+    /// 状態の変化を受け取るレシーバーを取得
+    pub fn subscribe_state(&self) -> watch::Receiver<AgentState> {
+        self.state_tx.subscribe()
+    }
+
+    /// 状態遷移のリスナーを登録し、解除用のIDを返す
+    ///
+    /// リスナーは状態マシンのロック中に呼ばれるため、エグゼキューターを操作してはいけない。
+    pub fn on_state_change<F>(&self, listener: F) -> u64
+    where
+        F: Fn(&StateTransition) + Send + Sync + 'static,
+    {
+        self.state_machine.lock().subscribe(listener)
+    }
+
+    /// リスナーを解除
+    pub fn remove_state_listener(&self, id: u64) -> bool {
+        self.state_machine.lock().unsubscribe(id)
+    }
+
+    /// 状態遷移履歴を取得（古い順）
+    pub fn state_history(&self) -> Vec<StateTransition> {
+        self.state_machine.lock().history().iter().cloned().collect()
+    }
+
     /// セッションIDを取得
-    pub fn session_id(&self) -> Option<&str> {
-        self.session_id.as_deref()
+    pub fn session_id(&self) -> Option<String> {
+        self.session_id.lock().clone()
+    }
+
+    /// 直近のstderr出力（古い順、最大20行）
+    pub fn stderr_tail(&self) -> Vec<String> {
+        self.stderr_tail.lock().iter().cloned().collect()
+    }
+
+    /// このセッションの使用量の累計
+    pub fn usage(&self) -> UsageTotals {
+        *self.usage.lock()
+    }
+
+    /// 直前の `execute` の使用量（失敗した実行も含む）
+    ///
+    /// 実行中はエグゼキューターを排他的に借りているので、呼び出し元の分だけが入る。
+    pub fn last_usage(&self) -> UsageTotals {
+        self.last_usage
+    }
+
+    /// 記録のセッションID（`export_transcript` に渡す）
+    pub fn transcript_id(&self) -> &str {
+        &self.transcript_id
+    }
+
+    /// 状態の保存先を設定し、以降は状態が変わるたびに書き出す
+    pub fn set_state_path(&mut self, path: PathBuf) {
+        self.snapshot = Some(SnapshotWriter {
+            path,
+            session_id: self.session_id.clone(),
+            prompt_hash: self.prompt_hash.clone(),
+            state_machine: self.state_machine.clone(),
+            working_dir: self.options.working_dir.clone(),
+            allowed_tools: self.options.allowed_tools.clone(),
+            model: self.options.model.clone(),
+            max_turns: self.options.max_turns,
+            extra_args: self.options.extra_args.clone(),
+        });
+    }
+
+    /// 保存済みの状態から再開する（中断されたプロンプトのハッシュを引き継ぐ）
+    pub fn restore(&mut self, snapshot: &ExecutorSnapshot) {
+        *self.session_id.lock() = snapshot.session_id.clone();
+        *self.prompt_hash.lock() = snapshot.prompt_hash.clone();
+    }
+
+    /// 状態を書き出す（保存先が設定されている場合）
+    fn save_state(&self) {
+        if let Some(ref snapshot) = self.snapshot {
+            snapshot.write();
+        }
     }
 
     /// Claude Codeを起動
@@ -177,13 +717,19 @@ impl ClaudeCodeExecutor {
         }
 
         log::info("ClaudeCodeExecutor", "Starting Claude Code...");
-
-        let mut cmd = Command::new("claude");
+        self.canceller.reset();
+
+        let extra_args = self.options.cli_args()?;
+        let settings = crate::settings::current();
+        let mut cmd = crate::process_env::tokio_command(&settings.claude_path);
+        // Windowsではコンソールウィンドウを開かない（CREATE_NO_WINDOW）
+        #[cfg(windows)]
+        cmd.creation_flags(0x0800_0000);
         cmd.args(["--print", "--output-format", "stream-json"]);
 
         // セッション再開
-        if let Some(ref session_id) = self.session_id {
-            cmd.args(["--resume", session_id]);
+        if let Some(session_id) = self.session_id() {
+            cmd.args(["--resume", &session_id]);
         }
 
         // 事前許可ツール
@@ -195,6 +741,9 @@ impl ClaudeCodeExecutor {
             }
         }
 
+        // モデル・最大ターン数・追加引数
+        cmd.args(&extra_args);
+
         // 作業ディレクトリ
         if let Some(ref dir) = self.options.working_dir {
             cmd.current_dir(dir);
@@ -207,13 +756,16 @@ impl ClaudeCodeExecutor {
         // プロセス起動
         let mut child = cmd.spawn()?;
 
-        // stdin/stdoutを取得
+        // stdin/stdout/stderrを取得
         let stdin = child.stdin.take().ok_or_else(|| {
             ExecutorError::Process("Failed to open stdin".to_string())
         })?;
         let stdout = child.stdout.take().ok_or_else(|| {
             ExecutorError::Process("Failed to open stdout".to_string())
         })?;
+        let stderr = child.stderr.take().ok_or_else(|| {
+            ExecutorError::Process("Failed to open stderr".to_string())
+        })?;
 
         self.process = Some(child);
         self.stdin = Some(stdin);
@@ -224,21 +776,64 @@ impl ClaudeCodeExecutor {
             let mut sm = self.state_machine.lock();
             sm.force_state(AgentState::initializing());
         }
+        self.save_state();
 
-        // stdout読み込みタスクを開始
-        self.start_stdout_reader(stdout);
+        // stderr・stdout読み込みタスクを開始
+        self.stderr_tail.lock().clear();
+        let stderr_reader = self.start_stderr_reader(stderr);
+        self.start_stdout_reader(stdout, Some(stderr_reader));
 
         log::info("ClaudeCodeExecutor", "Claude Code started successfully");
         Ok(())
     }
 
+    /// stderr読み込みタスクを開始
+    ///
+    /// 末尾の行を保持し、既知の失敗（未ログイン・不明なフラグ・古いバージョン）は `executor:diagnostic` で通知する。
+    fn start_stderr_reader<R: AsyncRead + Unpin + Send + 'static>(&self, stderr: R) -> JoinHandle<()> {
+        let stderr_tail = self.stderr_tail.clone();
+        let app_handle = self.app_handle.clone();
+
+        tokio::spawn(async move {
+            let mut lines = tokio::io::BufReader::new(stderr).lines();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                log::warn("ClaudeCodeExecutor", &format!("stderr: {}", truncate_safe(&line, 500)));
+
+                push_stderr_tail(&stderr_tail, line.clone());
+
+                if let Some(kind) = DiagnosticKind::classify(&line) {
+                    log::error("ClaudeCodeExecutor", &format!("Diagnosed {:?}: {}", kind, kind.hint()));
+                    if let Some(ref handle) = *app_handle.lock() {
+                        let _ = handle.emit_recorded("executor:diagnostic", &Diagnostic {
+                            kind,
+                            line,
+                            hint: kind.hint().to_string(),
+                        });
+                    }
+                }
+            }
+        })
+    }
+
     /// stdout読み込みタスクを開始
-    fn start_stdout_reader<R: AsyncRead + Unpin + Send + 'static>(&mut self, stdout: R) {
+    ///
+    /// プロンプトの実行中にstdoutが閉じたら（プロセスの異常終了）、stderrを読み終えてから
+    /// 回復不可能なエラーにして待機中の `execute` を終わらせる。
+    fn start_stdout_reader<R: AsyncRead + Unpin + Send + 'static>(&mut self, stdout: R, stderr_reader: Option<JoinHandle<()>>) {
         let event_tx = self.event_tx.clone();
         let state_machine = self.state_machine.clone();
         let permission_manager = self.permission_manager.clone();
         let app_handle = self.app_handle.clone();
-        let session_id = Arc::new(Mutex::new(self.session_id.clone()));
+        let session_id = self.session_id.clone();
+        let prompt_hash = self.prompt_hash.clone();
+        let stream_task = self.stream_task.clone();
+        let usage = self.usage.clone();
+        let snapshot = self.snapshot.clone();
+        let transcript_id = self.transcript_id.clone();
 
         tokio::spawn(async move {
             let reader = tokio::io::BufReader::new(stdout);
@@ -251,6 +846,7 @@ impl ClaudeCodeExecutor {
                 }
 
                 log::info("ClaudeCodeExecutor", &format!("Received: {}", truncate_safe(&line, 200)));
+                record_stream_line(&transcript_id, &line);
 
                 // JSONをパース
                 match parser.parse_line(&line) {
@@ -261,21 +857,34 @@ impl ClaudeCodeExecutor {
                                     // 状態遷移
                                     let old_state;
                                     let new_state;
+                                    let invalid;
                                     {
                                         let mut sm = state_machine.lock();
                                         old_state = sm.current_state().clone();
                                         new_state = sm.transition(state_event);
+                                        invalid = sm.take_invalid();
                                     }
 
-                                    // セッションIDを更新
-                                    if let AgentState::Idle = &new_state {
-                                        // session_idが未設定の場合は生成
-                                        let mut sid = session_id.lock();
-                                        if sid.is_none() {
-                                            *sid = Some(uuid::Uuid::new_v4().to_string());
+                                    // 遷移表にない遷移はパーサーの誤分類の可能性があるので通知
+                                    if let Some(ref invalid) = invalid {
+                                        if let Some(ref handle) = *app_handle.lock() {
+                                            let _ = handle.emit_recorded("executor:invalid_transition", invalid);
                                         }
                                     }
 
+                                    // セッションIDを更新
+                                    if let Some(id) = parser.session_id() {
+                                        *session_id.lock() = Some(id.to_string());
+                                    }
+
+                                    // 完了・回復不可能なエラーで実行中のプロンプトを解除
+                                    if matches!(new_state, AgentState::Completed { .. } | AgentState::Error { recoverable: false, .. }) {
+                                        *prompt_hash.lock() = None;
+                                    }
+                                    if let Some(ref snapshot) = snapshot {
+                                        snapshot.write();
+                                    }
+
                                     // イベント送信
                                     let _ = event_tx.send(ExecutorEvent::StateChanged {
                                         old_state,
@@ -284,43 +893,38 @@ impl ClaudeCodeExecutor {
 
                                     // フロントエンドにも送信
                                     if let Some(ref handle) = *app_handle.lock() {
-                                        let _ = handle.emit("executor:state_changed", &new_state);
+                                        let _ = handle.emit_recorded("executor:state_changed", &new_state);
                                     }
                                 }
 
                                 ParsedEvent::TextOutput(text) => {
-                                    let _ = event_tx.send(ExecutorEvent::Output {
-                                        content: text,
-                                    }).await;
-                                }
-
-                                ParsedEvent::ToolExecution { name, input, result, is_error } => {
-                                    // 権限エラーの場合
-                                    if is_error && result.as_ref().map(|r| r.contains("requires approval")).unwrap_or(false) {
-                                        let request_id = uuid::Uuid::new_v4().to_string();
-
-                                        // 権限要求イベント
-                                        let _ = event_tx.send(ExecutorEvent::PermissionRequired {
-                                            request_id: request_id.clone(),
-                                            tool_name: name.clone(),
-                                            options: vec!["Yes".to_string(), "No".to_string()],
-                                        }).await;
+                                    let task_id = stream_task.lock().clone();
 
-                                        // フロントエンドにも送信
+                                    // ストリーミング実行中ならフロントエンドに転送
+                                    if let Some(ref task_id) = task_id {
                                         if let Some(ref handle) = *app_handle.lock() {
-                                            let _ = handle.emit("executor:permission_required", &serde_json::json!({
-                                                "request_id": request_id,
-                                                "tool_name": name,
-                                                "tool_input": input,
-                                            }));
+                                            let _ = handle.emit_recorded("executor:output", &OutputChunk {
+                                                task_id: task_id.clone(),
+                                                content: text.clone(),
+                                            });
                                         }
                                     }
 
+                                    let _ = event_tx.send(ExecutorEvent::Output {
+                                        content: text,
+                                        task_id,
+                                    }).await;
+                                }
+
+                                ParsedEvent::ToolExecution { id, name, input, result, is_error, duration_ms } => {
+                                    // 権限要求はステートマシンの request_id で handle_permission_request が通知する
                                     let _ = event_tx.send(ExecutorEvent::ToolExecution {
+                                        id,
                                         name,
                                         input,
                                         result,
                                         is_error,
+                                        duration_ms,
                                     }).await;
                                 }
 
@@ -330,6 +934,20 @@ impl ClaudeCodeExecutor {
                                         percentage: percentage.unwrap_or(0),
                                     }).await;
                                 }
+
+                                ParsedEvent::Usage(request) => {
+                                    // 完了より先に届くので、executeが戻る時点で加算済みになる
+                                    let session = {
+                                        let mut total = usage.lock();
+                                        total.add(&request);
+                                        *total
+                                    };
+
+                                    if let Some(ref handle) = *app_handle.lock() {
+                                        let _ = handle.emit_recorded("executor:usage", &UsageUpdate { request, session });
+                                    }
+                                    let _ = event_tx.send(ExecutorEvent::Usage { request, session }).await;
+                                }
                             }
                         }
                     }
@@ -340,6 +958,28 @@ impl ClaudeCodeExecutor {
             }
 
             log::info("ClaudeCodeExecutor", "stdout reader finished");
+
+            // 停止時は先にプロンプトを解除しているので、ここに来るのは異常終了のときだけ
+            if prompt_hash.lock().is_none() {
+                return;
+            }
+            if let Some(stderr_reader) = stderr_reader {
+                let _ = tokio::time::timeout(std::time::Duration::from_secs(1), stderr_reader).await;
+            }
+            let new_state = {
+                let mut sm = state_machine.lock();
+                sm.transition(StateEvent::ErrorOccurred {
+                    message: "Claude Code exited unexpectedly".to_string(),
+                    recoverable: false,
+                })
+            };
+            *prompt_hash.lock() = None;
+            if let Some(ref snapshot) = snapshot {
+                snapshot.write();
+            }
+            if let Some(ref handle) = *app_handle.lock() {
+                let _ = handle.emit_recorded("executor:state_changed", &new_state);
+            }
         });
     }
 
@@ -357,6 +997,7 @@ impl ClaudeCodeExecutor {
             stdin.write_all(prompt.as_bytes()).await?;
             stdin.write_all(b"\n").await?;
             stdin.flush().await?;
+            transcript::record(&self.transcript_id, TranscriptEntry::prompt(prompt));
 
             // 状態をProcessingに
             {
@@ -365,23 +1006,47 @@ impl ClaudeCodeExecutor {
                     prompt: prompt.to_string(),
                 });
             }
+            *self.prompt_hash.lock() = Some(format!("{:016x}", content_hash(prompt)));
+            self.save_state();
 
             log::info("ClaudeCodeExecutor", "Prompt sent, waiting for completion...");
 
             // 完了を待機
-            self.wait_for_completion().await
+            let before = self.usage();
+            let result = self.wait_for_completion().await;
+            self.last_usage = self.usage().since(&before);
+            result
         } else {
             Err(ExecutorError::NotRunning)
         }
     }
 
+    /// タスクを実行し、出力を `task_id` 付きの `executor:output` で逐次通知する
+    pub async fn execute_streaming(&mut self, prompt: &str, task_id: &str) -> Result<String, ExecutorError> {
+        *self.stream_task.lock() = Some(task_id.to_string());
+        let result = self.execute(prompt).await;
+        *self.stream_task.lock() = None;
+        result
+    }
+
     /// 完了を待機
+    ///
+    /// 状態の変化を購読し、変化があったときだけ状態を確認する。
+    /// 中止が要求されたら `ExecutorError::Cancelled` を返す。
     async fn wait_for_completion(&mut self) -> Result<String, ExecutorError> {
         let timeout = std::time::Duration::from_secs(self.options.timeout_secs);
         let start = std::time::Instant::now();
+        let mut state_rx = self.subscribe_state();
+        let canceller = self.canceller.clone();
 
         loop {
+            if canceller.is_cancelled() {
+                log::info("ClaudeCodeExecutor", "Task cancelled");
+                return Err(ExecutorError::Cancelled);
+            }
+
             // 現在の状態をチェック
+            state_rx.borrow_and_update();
             let state = self.current_state();
 
             match state {
@@ -394,34 +1059,45 @@ impl ClaudeCodeExecutor {
                         // 回復可能なエラーは継続待機
                         log::info("ClaudeCodeExecutor", &format!("Recoverable error: {}", message));
                     } else {
-                        return Err(ExecutorError::Process(message));
+                        return Err(process_error(&message, &self.stderr_tail));
                     }
                 }
                 AgentState::WaitingForPermission { tool_name, .. } => {
                     // 権限要求を処理
                     log::info("ClaudeCodeExecutor", &format!("Waiting for permission: {}", tool_name));
-                    self.handle_permission_request().await?;
+                    tokio::select! {
+                        result = self.handle_permission_request() => result?,
+                        _ = canceller.cancelled() => {}
+                    }
+                    continue;
                 }
                 _ => {
                     // Processing, Idle, WaitingForInput - 継続
                 }
             }
 
-            // タイムアウトチェック
-            if start.elapsed() >= timeout {
-                return Err(ExecutorError::Timeout(format!(
-                    "Task did not complete within {} seconds",
-                    self.options.timeout_secs
-                )));
+            // 次の状態変化まで待機（タイムアウト付き）
+            let remaining = timeout.saturating_sub(start.elapsed());
+            tokio::select! {
+                changed = tokio::time::timeout(remaining, state_rx.changed()) => {
+                    if changed.is_err() {
+                        return Err(ExecutorError::Timeout(format!(
+                            "Task did not complete within {} seconds",
+                            self.options.timeout_secs
+                        )));
+                    }
+                }
+                _ = canceller.cancelled() => {}
             }
-
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         }
     }
 
     /// 権限要求を処理
+    ///
+    /// 権限マネージャーで判定し、人間の確認が必要なら `executor:permission_required` を送って
+    /// 共有の `PermissionResponder` への回答（`executor_submit_permission`）を待つ（その間はWaitingForPermissionのまま）。
+    /// 判定が決まってから stdin に "1"（許可）か "3"（拒否）を書く。回答がなければ拒否する。
     async fn handle_permission_request(&mut self) -> Result<(), ExecutorError> {
-        // 人間の回答を待機
         let state = self.current_state();
         let (tool_name, tool_input, request_id) = match state {
             AgentState::WaitingForPermission { tool_name, tool_input, request_id } => {
@@ -433,66 +1109,72 @@ impl ClaudeCodeExecutor {
         log::info("ClaudeCodeExecutor", &format!("Handling permission request for {}", tool_name));
 
         // 権限マネージャーでチェック
-        let decision = {
-            let mut pm = self.permission_manager.lock();
-            // 同期的にチェック（asyncではない）
-            // 実際の実装では人間の回答を待つ必要がある
-            PermissionDecision::Allow { always: false }
-        };
+        let decision = self.permission_manager.lock().check_permission(&tool_name, &tool_input, &request_id);
+
+        let decision = match decision {
+            PermissionDecision::RequireHuman { options, .. } => {
+                if let Some(ref handle) = *self.app_handle.lock() {
+                    let _ = handle.emit_recorded("executor:permission_required", &serde_json::json!({
+                        "request_id": request_id,
+                        "tool_name": tool_name,
+                        "tool_input": tool_input,
+                        "options": options,
+                    }));
+                }
 
-        // 権限をstdinに送信
-        if let Some(ref mut stdin) = self.stdin {
-            match decision {
-                PermissionDecision::Allow { .. } => {
-                    // "1" は "Yes" に相当
-                    stdin.write_all(b"1\n").await?;
-                    stdin.flush().await?;
-
-                    // 状態をProcessingに戻す
-                    {
-                        let mut sm = self.state_machine.lock();
-                        sm.transition(StateEvent::PermissionGranted {
-                            request_id: request_id.clone(),
-                        });
+                // 人間の回答を待機
+                let responder = self.permission_manager.lock().responder();
+                let timeout = std::time::Duration::from_secs(PERMISSION_TIMEOUT_SECS);
+                match responder.wait(&request_id, timeout).await {
+                    Ok(decision) => {
+                        self.permission_manager.lock().record_decision(&tool_name, &tool_input, &decision);
+                        decision
                     }
-
-                    log::info("ClaudeCodeExecutor", "Permission granted");
-                }
-                PermissionDecision::Deny { reason } => {
-                    stdin.write_all(b"3\n").await?; // "3" は "No" に相当
-                    stdin.flush().await?;
-
-                    {
-                        let mut sm = self.state_machine.lock();
-                        sm.transition(StateEvent::PermissionDenied {
-                            request_id: request_id.clone(),
-                            reason: reason.clone(),
-                        });
+                    Err(e) => {
+                        log::warn("ClaudeCodeExecutor", &e);
+                        PermissionDecision::Deny {
+                            reason: format!("No response within {} seconds", PERMISSION_TIMEOUT_SECS),
+                        }
                     }
+                }
+            }
+            decision => decision,
+        };
 
-                    return Err(ExecutorError::PermissionDenied(reason));
+        let stdin = self.stdin.as_mut().ok_or(ExecutorError::NotRunning)?;
+        match decision {
+            PermissionDecision::Deny { reason } => {
+                stdin.write_all(b"3\n").await?; // "3" は "No" に相当
+                stdin.flush().await?;
+
+                {
+                    let mut sm = self.state_machine.lock();
+                    sm.transition(StateEvent::PermissionDenied {
+                        request_id: request_id.clone(),
+                        reason: reason.clone(),
+                    });
                 }
-                PermissionDecision::RequireHuman { .. } => {
-                    // 人間の回答を待機（タイムアウト付き）
-                    // 注: Send問題を避けるため、別の方法で実装
-                    // 現在はデフォルトで許可する
-                    log::info("ClaudeCodeExecutor", "Permission required but auto-allowing for now");
-
-                    stdin.write_all(b"1\n").await?;
-                    stdin.flush().await?;
-
-                    // 状態をProcessingに戻す
-                    {
-                        let mut sm = self.state_machine.lock();
-                        sm.transition(StateEvent::PermissionGranted {
-                            request_id: request_id.clone(),
-                        });
-                    }
+
+                log::info("ClaudeCodeExecutor", &format!("Permission denied: {}", reason));
+                Err(ExecutorError::PermissionDenied(reason))
+            }
+            _ => {
+                // "1" は "Yes" に相当
+                stdin.write_all(b"1\n").await?;
+                stdin.flush().await?;
+
+                // 状態をProcessingに戻す
+                {
+                    let mut sm = self.state_machine.lock();
+                    sm.transition(StateEvent::PermissionGranted {
+                        request_id: request_id.clone(),
+                    });
                 }
+
+                log::info("ClaudeCodeExecutor", "Permission granted");
+                Ok(())
             }
         }
-
-        Ok(())
     }
 
     /// イベントを受信
@@ -504,15 +1186,6 @@ impl ClaudeCodeExecutor {
         }
     }
 
-    /// 人間の回答を送信（権限要求用）
-    pub async fn submit_permission_response(
-        &self,
-        request_id: &str,
-        decision: PermissionDecision,
-    ) -> Result<(), String> {
-        self.permission_manager.lock().submit_human_response(request_id, decision)
-    }
-
     /// 停止
     pub async fn stop(&mut self) -> Result<(), ExecutorError> {
         if !self.is_running {
@@ -521,6 +1194,8 @@ impl ClaudeCodeExecutor {
 
         log::info("ClaudeCodeExecutor", "Stopping Claude Code...");
 
+        // stdout読み込みタスクが異常終了と見なさないよう、先にプロンプトを解除する
+        *self.prompt_hash.lock() = None;
         if let Some(ref mut child) = self.process {
             // SIGTERMを送信
             let _ = child.kill().await;
@@ -536,6 +1211,11 @@ impl ClaudeCodeExecutor {
             sm.force_state(AgentState::idle());
         }
 
+        // 明示的な停止では再開しないので保存した状態を消す
+        if let Some(ref snapshot) = self.snapshot {
+            let _ = std::fs::remove_file(&snapshot.path);
+        }
+
         log::info("ClaudeCodeExecutor", "Claude Code stopped");
         Ok(())
     }
@@ -553,6 +1233,18 @@ fn truncate_safe(s: &str, max_bytes: usize) -> &str {
     &s[..boundary]
 }
 
+/// stream-jsonの1行を記録する（JSONでなければ出力として記録）
+fn record_stream_line(transcript_id: &str, line: &str) {
+    let entry = match serde_json::from_str::<Value>(line) {
+        Ok(value) => {
+            let name = value.get("type").and_then(|t| t.as_str()).unwrap_or("stream").to_string();
+            TranscriptEntry::event(&name, value)
+        }
+        Err(_) => TranscriptEntry::output(&format!("{}\n", line)),
+    };
+    transcript::record(transcript_id, entry);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -563,13 +1255,36 @@ mod tests {
         assert!(options.working_dir.is_none());
         assert!(options.allowed_tools.is_empty());
         assert_eq!(options.timeout_secs, 300);
+        assert!(options.cli_args().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cli_args() {
+        let options = ExecutorOptions {
+            model: Some("haiku".to_string()),
+            max_turns: Some(3),
+            extra_args: vec!["--verbose".to_string(), "--append-system-prompt".to_string(), "字幕".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            options.cli_args().unwrap(),
+            ["--model", "haiku", "--max-turns", "3", "--verbose", "--append-system-prompt", "字幕"]
+        );
+
+        // ストリーム形式・セッションは上書きできない
+        for reserved in ["--output-format=json", "--resume", "-p"] {
+            let options = ExecutorOptions { extra_args: vec![reserved.to_string()], ..Default::default() };
+            assert!(matches!(options.cli_args(), Err(ExecutorError::InvalidOption(_))), "{}", reserved);
+        }
+        let options = ExecutorOptions { max_turns: Some(0), ..Default::default() };
+        assert!(options.cli_args().is_err());
     }
 
     #[test]
     fn test_executor_new() {
         let executor = ClaudeCodeExecutor::new(ExecutorOptions::default());
         assert!(!executor.is_running);
-        assert!(executor.session_id.is_none());
+        assert!(executor.session_id().is_none());
     }
 
     #[test]
@@ -578,4 +1293,193 @@ mod tests {
         let state = executor.current_state();
         assert!(matches!(state, AgentState::Initializing));
     }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("re-voice-executor-{}", uuid::Uuid::new_v4()))
+            .join(EXECUTOR_STATE_FILE);
+        assert!(ExecutorSnapshot::load(&path).unwrap().is_none());
+
+        let mut executor = ClaudeCodeExecutor::new(ExecutorOptions {
+            working_dir: Some("/work".to_string()),
+            session_id: Some("session-1".to_string()),
+            model: Some("opus".to_string()),
+            ..Default::default()
+        });
+        executor.set_state_path(path.clone());
+        *executor.prompt_hash.lock() = Some("abc".to_string());
+        executor.save_state();
+
+        let snapshot = ExecutorSnapshot::load(&path).unwrap().unwrap();
+        assert_eq!(snapshot.session_id.as_deref(), Some("session-1"));
+        assert!(snapshot.is_interrupted());
+        assert_eq!(snapshot.state, AgentState::Initializing);
+
+        let options = snapshot.resume_options();
+        assert_eq!(options.working_dir.as_deref(), Some("/work"));
+        assert_eq!(options.session_id.as_deref(), Some("session-1"));
+        assert_eq!(options.model.as_deref(), Some("opus"));
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_state() {
+        let executor = ClaudeCodeExecutor::new(ExecutorOptions::default());
+        let mut rx = executor.subscribe_state();
+
+        executor.state_machine.lock().transition(StateEvent::Initialized);
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow(), AgentState::Idle);
+    }
+
+    #[test]
+    fn test_summarize_sessions() {
+        let event = |timestamp: &str, data: Value| TranscriptEntry {
+            timestamp: timestamp.to_string(),
+            ..TranscriptEntry::event("stream", data)
+        };
+        let prompt = |timestamp: &str, text: &str| TranscriptEntry {
+            timestamp: timestamp.to_string(),
+            ..TranscriptEntry::prompt(text)
+        };
+        let first = vec![
+            prompt("2026-01-01T10:00:00", "字幕を翻訳して"),
+            event("2026-01-01T10:00:01", serde_json::json!({ "type": "system", "session_id": "s1", "cwd": "/work", "model": "opus" })),
+            event("2026-01-01T10:00:05", serde_json::json!({ "type": "result", "session_id": "s1" })),
+            prompt("2026-01-01T10:01:00", "もう一度"),
+            event("2026-01-01T10:01:05", serde_json::json!({ "type": "result", "session_id": "s1" })),
+        ];
+        // 再開した記録は同じセッションにまとめる
+        let resumed = vec![
+            prompt("2026-01-02T09:00:00", "続き"),
+            event("2026-01-02T09:00:03", serde_json::json!({ "type": "result", "session_id": "s1" })),
+        ];
+        let other = vec![
+            prompt("2026-01-01T12:00:00", "別件"),
+            event("2026-01-01T12:00:01", serde_json::json!({ "type": "system", "session_id": "s2" })),
+        ];
+
+        let sessions = summarize_sessions(vec![
+            ("executor-a".to_string(), first),
+            ("executor-b".to_string(), other),
+            ("executor-c".to_string(), resumed),
+        ]);
+        assert_eq!(sessions.len(), 2);
+        let s1 = &sessions[0];
+        assert_eq!(s1.session_id, "s1");
+        assert_eq!(s1.transcript_ids, vec!["executor-a".to_string(), "executor-c".to_string()]);
+        assert_eq!(s1.working_dir.as_deref(), Some("/work"));
+        assert_eq!(s1.first_prompt.as_deref(), Some("字幕を翻訳して"));
+        assert_eq!(s1.prompt_count, 3);
+        assert_eq!(s1.started_at, "2026-01-01T10:00:00");
+        assert_eq!(s1.updated_at, "2026-01-02T09:00:03");
+        assert_eq!(sessions[1].session_id, "s2");
+
+        let snapshot = ExecutorSnapshot::for_session(s1);
+        assert!(!snapshot.is_interrupted());
+        assert_eq!(snapshot.resume_options().working_dir.as_deref(), Some("/work"));
+    }
+
+    #[tokio::test]
+    async fn test_output_tagged_with_stream_task() {
+        let mut executor = ClaudeCodeExecutor::new(ExecutorOptions::default());
+        *executor.stream_task.lock() = Some("task-1".to_string());
+        let stdout = r#"{"type":"assistant","message":{"id":"m1","type":"message","role":"assistant","model":"claude","content":[{"type":"text","text":"こんにちは"}]}}
+"#;
+        executor.start_stdout_reader(stdout.as_bytes(), None);
+
+        let output = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            loop {
+                if let ExecutorEvent::Output { content, task_id } = executor.recv_event().await.unwrap() {
+                    break (content, task_id);
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(output, ("こんにちは".to_string(), Some("task-1".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_usage_accumulated_per_session() {
+        let mut executor = ClaudeCodeExecutor::new(ExecutorOptions::default());
+        let stdout = r#"{"type":"result","subtype":"success","result":"a","cost_usd":0.01,"usage":{"input_tokens":100,"output_tokens":20}}
+{"type":"result","subtype":"error","is_error":true,"result":"b","cost_usd":0.02,"usage":{"input_tokens":50,"output_tokens":5}}
+"#;
+        executor.start_stdout_reader(stdout.as_bytes(), None);
+
+        let session = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            loop {
+                if let ExecutorEvent::Usage { session, .. } = executor.recv_event().await.unwrap() {
+                    if session.requests == 2 {
+                        break session;
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!((session.input_tokens, session.output_tokens), (150, 25));
+        assert!((session.cost_usd - 0.03).abs() < 1e-9);
+        assert_eq!(executor.usage(), session);
+    }
+
+    #[test]
+    fn test_classify_stderr() {
+        assert_eq!(DiagnosticKind::classify("Invalid API key · Please run /login"), Some(DiagnosticKind::NotLoggedIn));
+        assert_eq!(DiagnosticKind::classify("error: unknown option '--max-turn'"), Some(DiagnosticKind::InvalidFlag));
+        assert_eq!(
+            DiagnosticKind::classify("This version of Claude Code is too old, please upgrade"),
+            Some(DiagnosticKind::VersionTooOld)
+        );
+        assert_eq!(DiagnosticKind::classify("Debugger attached."), None);
+    }
+
+    #[tokio::test]
+    async fn test_unexpected_exit_reports_stderr() {
+        let mut executor = ClaudeCodeExecutor::new(ExecutorOptions { timeout_secs: 5, ..Default::default() });
+        {
+            let mut sm = executor.state_machine.lock();
+            sm.force_state(AgentState::idle());
+            sm.transition(StateEvent::TaskStarted { prompt: "hello".to_string() });
+        }
+        *executor.prompt_hash.lock() = Some("abc".to_string());
+
+        let stderr_reader = executor.start_stderr_reader("Warning: slow disk\nError: Not logged in\n".as_bytes());
+        executor.start_stdout_reader("".as_bytes(), Some(stderr_reader));
+
+        let error = executor.wait_for_completion().await.unwrap_err();
+        let ExecutorError::Process(message) = error else {
+            panic!("expected a process error: {:?}", error);
+        };
+        assert!(message.starts_with("Claude Code exited unexpectedly"));
+        assert!(message.ends_with("Warning: slow disk\nError: Not logged in"));
+        assert_eq!(executor.stderr_tail().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_interrupts_wait_without_lock() {
+        let mut executor = ClaudeCodeExecutor::new(ExecutorOptions { timeout_secs: 30, ..Default::default() });
+        {
+            let mut sm = executor.state_machine.lock();
+            sm.force_state(AgentState::idle());
+            sm.transition(StateEvent::TaskStarted { prompt: "hello".to_string() });
+        }
+        let canceller = ExecutorCanceller::new();
+        executor.set_canceller(canceller.clone());
+
+        let handle = canceller.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            handle.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let error = executor.wait_for_completion().await.unwrap_err();
+        assert!(matches!(error, ExecutorError::Cancelled), "unexpected error: {:?}", error);
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(canceller.is_cancelled());
+    }
 }
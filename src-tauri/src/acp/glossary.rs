@@ -0,0 +1,272 @@
+//! 用語集（Glossary）
+//!
+//! プロジェクトごとに「原語 → 必須の日本語訳」を管理し、
+//! 翻訳プロンプトへの注入と翻訳後の検証（自動修正・フラグ付け）を行う。
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// 既定のプロジェクト名
+pub const DEFAULT_PROJECT: &str = "default";
+
+/// 保存ファイル名（データディレクトリ直下）
+pub const GLOSSARY_FILE: &str = "glossaries.json";
+
+/// 用語集エントリ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    /// 原語
+    pub source: String,
+    /// 必須の訳語
+    pub target: String,
+    /// 自動修正対象の誤訳候補（見つかったら訳語に置換）
+    #[serde(default)]
+    pub variants: Vec<String>,
+    /// 原語の大文字小文字を区別するか
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+impl GlossaryEntry {
+    pub fn new(source: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            target: target.into(),
+            variants: Vec::new(),
+            case_sensitive: false,
+        }
+    }
+
+    pub fn with_variants(mut self, variants: Vec<String>) -> Self {
+        self.variants = variants;
+        self
+    }
+
+    /// 原文にこの用語が含まれるか
+    pub fn matches_source(&self, text: &str) -> bool {
+        if self.case_sensitive {
+            text.contains(&self.source)
+        } else {
+            text.to_lowercase().contains(&self.source.to_lowercase())
+        }
+    }
+}
+
+/// 用語集違反
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryViolation {
+    /// セグメント位置
+    pub segment: usize,
+    /// 原語
+    pub source: String,
+    /// 期待される訳語
+    pub expected: String,
+    /// 自動修正したか（falseならフラグのみ）
+    pub corrected: bool,
+}
+
+/// 用語集適用結果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GlossaryReport {
+    /// 自動修正した件数
+    pub corrected: usize,
+    /// 修正できずフラグを立てた件数
+    pub flagged: usize,
+    /// 違反一覧
+    pub violations: Vec<GlossaryViolation>,
+}
+
+/// 用語集
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Glossary {
+    pub entries: Vec<GlossaryEntry>,
+}
+
+impl Glossary {
+    pub fn new(entries: Vec<GlossaryEntry>) -> Self {
+        Self { entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 翻訳プロンプトに挿入する用語集セクション（空なら空文字列）
+    pub fn to_prompt_section(&self) -> String {
+        if self.entries.is_empty() {
+            return String::new();
+        }
+
+        let mut section = String::from("【用語集】以下の用語は必ず指定の訳語を使用してください。\n");
+        for entry in &self.entries {
+            section.push_str(&format!("- {} → {}\n", entry.source, entry.target));
+        }
+        section
+    }
+
+    /// 翻訳結果を検証し、可能なものは自動修正する
+    ///
+    /// `sources` と `translations` は同じ順序のセグメント列。
+    pub fn enforce(&self, sources: &[String], translations: &mut [String]) -> GlossaryReport {
+        let mut report = GlossaryReport::default();
+
+        for (i, source) in sources.iter().enumerate() {
+            let Some(translation) = translations.get_mut(i) else {
+                break;
+            };
+
+            for entry in self.entries.iter().filter(|e| e.matches_source(source)) {
+                if translation.contains(&entry.target) {
+                    continue;
+                }
+
+                let variant = entry.variants.iter().find(|v| !v.is_empty() && translation.contains(v.as_str()));
+                let corrected = match variant {
+                    Some(v) => {
+                        *translation = translation.replace(v.as_str(), &entry.target);
+                        report.corrected += 1;
+                        true
+                    }
+                    None => {
+                        report.flagged += 1;
+                        false
+                    }
+                };
+
+                report.violations.push(GlossaryViolation {
+                    segment: i,
+                    source: entry.source.clone(),
+                    expected: entry.target.clone(),
+                    corrected,
+                });
+            }
+        }
+
+        report
+    }
+}
+
+/// プロジェクト別の用語集ストア
+#[derive(Debug, Clone, Default)]
+pub struct GlossaryStore {
+    /// 保存先（Noneならメモリのみ）
+    path: Option<PathBuf>,
+    /// プロジェクト名 -> 用語集
+    projects: BTreeMap<String, Glossary>,
+}
+
+impl GlossaryStore {
+    /// メモリのみのストアを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ファイルから読み込む（存在しなければ空のストア）
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let projects = if path.exists() {
+            let content = std::fs::read_to_string(path)?;
+            serde_json::from_str(&content)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            projects,
+        })
+    }
+
+    /// ファイルに保存
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&self.projects)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)
+    }
+
+    /// プロジェクトの用語集を取得（未登録なら空）
+    pub fn get(&self, project: &str) -> Glossary {
+        self.projects.get(project).cloned().unwrap_or_default()
+    }
+
+    /// プロジェクトの用語集を設定
+    pub fn set(&mut self, project: &str, glossary: Glossary) {
+        self.projects.insert(project.to_string(), glossary);
+    }
+
+    /// プロジェクトを削除
+    pub fn remove(&mut self, project: &str) -> bool {
+        self.projects.remove(project).is_some()
+    }
+
+    /// プロジェクト名一覧
+    pub fn projects(&self) -> Vec<String> {
+        self.projects.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Glossary {
+        Glossary::new(vec![
+            GlossaryEntry::new("machine learning", "機械学習")
+                .with_variants(vec!["マシンラーニング".to_string()]),
+            GlossaryEntry::new("Rust", "Rust"),
+        ])
+    }
+
+    #[test]
+    fn test_prompt_section() {
+        let section = sample().to_prompt_section();
+        assert!(section.contains("machine learning → 機械学習"));
+        assert!(Glossary::default().to_prompt_section().is_empty());
+    }
+
+    #[test]
+    fn test_enforce_corrects_variant() {
+        let sources = vec!["Machine learning is fun".to_string()];
+        let mut translations = vec!["マシンラーニングは楽しい".to_string()];
+
+        let report = sample().enforce(&sources, &mut translations);
+        assert_eq!(report.corrected, 1);
+        assert_eq!(report.flagged, 0);
+        assert_eq!(translations[0], "機械学習は楽しい");
+    }
+
+    #[test]
+    fn test_enforce_flags_unknown_rendering() {
+        let sources = vec!["I like Rust".to_string(), "No terms here".to_string()];
+        let mut translations = vec!["錆が好き".to_string(), "用語なし".to_string()];
+
+        let report = sample().enforce(&sources, &mut translations);
+        assert_eq!(report.flagged, 1);
+        assert_eq!(report.violations[0].segment, 0);
+        assert!(!report.violations[0].corrected);
+        assert_eq!(translations[0], "錆が好き");
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let path = std::env::temp_dir().join(format!("re-voice-glossary-{}.json", uuid::Uuid::new_v4()));
+        let mut store = GlossaryStore::load(&path).unwrap();
+        store.set("demo", sample());
+        store.save().unwrap();
+
+        let loaded = GlossaryStore::load(&path).unwrap();
+        assert_eq!(loaded.projects(), vec!["demo".to_string()]);
+        assert_eq!(loaded.get("demo").entries.len(), 2);
+        assert!(loaded.get("missing").is_empty());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,203 @@
+//! Message history
+//!
+//! Every ACP message the orchestrator delivers to an agent or receives from one
+//! is appended to `<data_dir>/acp_history.jsonl` (one `HistoryEntry` per line)
+//! so inter-agent conversations can be audited after the fact.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::message::ACPMessageV3;
+
+/// History file name (directly under the data directory)
+pub const HISTORY_FILE: &str = "acp_history.jsonl";
+
+/// Entries returned when no limit is given
+pub const DEFAULT_HISTORY_LIMIT: usize = 200;
+
+/// Whether the message went to the agent or came from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// One recorded message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub seq: u64,
+    /// Agent the message was sent to or received from
+    pub agent_id: String,
+    pub direction: Direction,
+    pub recorded_at: DateTime<Utc>,
+    pub message: ACPMessageV3,
+}
+
+impl HistoryEntry {
+    fn matches(&self, query: &str) -> bool {
+        let message = &self.message;
+        let correlation_id = message.metadata.as_ref().and_then(|m| m.correlation_id.as_deref());
+        [
+            Some(message.payload.content.as_str()),
+            Some(message.id.as_str()),
+            Some(self.agent_id.as_str()),
+            Some(message.from.id.as_str()),
+            correlation_id,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(query))
+            || message
+                .to
+                .recipients()
+                .iter()
+                .any(|a| a.to_address_string().to_lowercase().contains(query))
+    }
+}
+
+/// Append-only message log
+#[derive(Debug, Default)]
+pub struct MessageHistory {
+    /// Backing file (None keeps the history in memory only)
+    path: Option<PathBuf>,
+    entries: Vec<HistoryEntry>,
+    next_seq: u64,
+}
+
+impl MessageHistory {
+    /// In-memory history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load from a JSONL file (missing file means empty; unreadable lines are skipped)
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let entries: Vec<HistoryEntry> = if path.exists() {
+            fs::read_to_string(path)?
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let next_seq = entries.iter().map(|e| e.seq + 1).max().unwrap_or(0);
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            entries,
+            next_seq,
+        })
+    }
+
+    /// Record a message and append it to the file
+    pub fn record(
+        &mut self,
+        agent_id: &str,
+        direction: Direction,
+        message: &ACPMessageV3,
+    ) -> std::io::Result<HistoryEntry> {
+        let entry = HistoryEntry {
+            seq: self.next_seq,
+            agent_id: agent_id.to_string(),
+            direction,
+            recorded_at: Utc::now(),
+            message: message.clone(),
+        };
+        self.next_seq += 1;
+        self.entries.push(entry.clone());
+
+        if let Some(ref path) = self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut line = serde_json::to_string(&entry)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            line.push('\n');
+            OpenOptions::new().create(true).append(true).open(path)?.write_all(line.as_bytes())?;
+        }
+        Ok(entry)
+    }
+
+    /// Latest `limit` entries (oldest first), optionally for one agent and after `since`
+    pub fn query(&self, agent_id: Option<&str>, since: Option<DateTime<Utc>>, limit: Option<usize>) -> Vec<HistoryEntry> {
+        self.latest(
+            |e| agent_id.map_or(true, |id| e.agent_id == id) && since.map_or(true, |t| e.recorded_at > t),
+            limit,
+        )
+    }
+
+    /// Latest `limit` entries whose content, IDs or addresses contain `query` (case-insensitive)
+    pub fn search(&self, query: &str, limit: Option<usize>) -> Vec<HistoryEntry> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        self.latest(|e| e.matches(&query), limit)
+    }
+
+    fn latest(&self, filter: impl Fn(&HistoryEntry) -> bool, limit: Option<usize>) -> Vec<HistoryEntry> {
+        let mut entries: Vec<HistoryEntry> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| filter(e))
+            .take(limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+            .cloned()
+            .collect();
+        entries.reverse();
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_reload() {
+        let dir = std::env::temp_dir().join(format!("re-voice-history-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(HISTORY_FILE);
+
+        let mut history = MessageHistory::load(&path).unwrap();
+        let prompt = ACPMessageV3::prompt("app", "translator", "Translate: こんにちは");
+        history.record("translator", Direction::Sent, &prompt).unwrap();
+        let cutoff = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let reply = ACPMessageV3::response("translator", "app", "Hello", &prompt.id);
+        history.record("translator", Direction::Received, &reply).unwrap();
+        history.record("reviewer", Direction::Sent, &ACPMessageV3::prompt("app", "reviewer", "check")).unwrap();
+
+        let reloaded = MessageHistory::load(&path).unwrap();
+        assert_eq!(reloaded.query(None, None, None).len(), 3);
+        assert_eq!(reloaded.query(Some("translator"), None, None).len(), 2);
+        let recent = reloaded.query(Some("translator"), Some(cutoff), None);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].direction, Direction::Received);
+        assert_eq!(reloaded.query(None, None, Some(1))[0].agent_id, "reviewer");
+
+        let mut reloaded = reloaded;
+        assert_eq!(reloaded.record("x", Direction::Sent, &prompt).unwrap().seq, 3);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_search() {
+        let mut history = MessageHistory::new();
+        let prompt = ACPMessageV3::prompt("app", "translator", "Translate the Subtitle");
+        history.record("translator", Direction::Sent, &prompt).unwrap();
+        history
+            .record("translator", Direction::Received, &ACPMessageV3::response("translator", "app", "字幕", &prompt.id))
+            .unwrap();
+
+        assert_eq!(history.search("subtitle", None).len(), 1);
+        // A correlation ID finds both the request and its reply
+        assert_eq!(history.search(&prompt.id, None).len(), 2);
+        assert_eq!(history.search("字幕", None)[0].direction, Direction::Received);
+        assert!(history.search("  ", None).is_empty());
+    }
+}
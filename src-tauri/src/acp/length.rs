@@ -0,0 +1,96 @@
+//! 字幕の長さ制約
+//!
+//! 各セグメントの表示時間と1秒あたりの文字数から訳文の最大文字数を求め、
+//! 翻訳プロンプトへの指示と、長すぎる訳文の検出に使う。
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::subtitle_parser::SubtitleSegment;
+
+/// 長さ制約の設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LengthConfig {
+    /// 1秒あたりに読み上げられる文字数
+    #[serde(default = "default_chars_per_second")]
+    pub chars_per_second: f64,
+    /// 短いセグメントでも許容する最小文字数
+    #[serde(default = "default_min_chars")]
+    pub min_chars: usize,
+}
+
+fn default_chars_per_second() -> f64 {
+    8.0
+}
+
+fn default_min_chars() -> usize {
+    4
+}
+
+impl Default for LengthConfig {
+    fn default() -> Self {
+        Self {
+            chars_per_second: default_chars_per_second(),
+            min_chars: default_min_chars(),
+        }
+    }
+}
+
+impl LengthConfig {
+    /// 表示時間から最大文字数を求める
+    pub fn max_chars(&self, duration_ms: u64) -> usize {
+        let budget = (duration_ms as f64 / 1000.0 * self.chars_per_second).floor() as usize;
+        budget.max(self.min_chars)
+    }
+
+    /// セグメント番号ごとの最大文字数
+    pub fn limits(&self, segments: &[SubtitleSegment]) -> BTreeMap<usize, usize> {
+        segments
+            .iter()
+            .map(|s| (s.index as usize, self.max_chars(s.duration_ms())))
+            .collect()
+    }
+}
+
+/// 読み上げ対象の文字数（空白を除く）
+pub fn spoken_length(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+/// 上限を超えたセグメント番号
+pub fn over_long(translations: &BTreeMap<usize, String>, limits: &BTreeMap<usize, usize>) -> Vec<usize> {
+    translations
+        .iter()
+        .filter(|(index, text)| {
+            limits.get(index).map(|max| spoken_length(text) > *max).unwrap_or(false)
+        })
+        .map(|(index, _)| *index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_chars() {
+        let config = LengthConfig::default();
+        assert_eq!(config.max_chars(2500), 20);
+        assert_eq!(config.max_chars(200), 4);
+    }
+
+    #[test]
+    fn test_over_long() {
+        let segments = vec![
+            SubtitleSegment::new(0, 0, 1000, "Hi".to_string()),
+            SubtitleSegment::new(1, 1000, 3000, "Hello there".to_string()),
+        ];
+        let limits = LengthConfig::default().limits(&segments);
+        let translations: BTreeMap<usize, String> =
+            [(0, "こんにちは、お元気ですか".to_string()), (1, "こんにちは".to_string())].into();
+
+        assert_eq!(over_long(&translations, &limits), vec![0]);
+        assert_eq!(spoken_length("あ い う"), 3);
+    }
+}
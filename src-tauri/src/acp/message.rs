@@ -56,8 +56,8 @@ pub enum MessageType {
     PipelineEnd,
 }
 
-/// Message priority (v3 extended)
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+/// Message priority (v3 extended), ordered from `Low` to `Urgent`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum Priority {
     Low,
@@ -202,11 +202,23 @@ impl CapabilityFilter {
 pub struct PipelineStage {
     /// Stage name
     pub name: String,
-    /// Agent address for this stage
+    /// Agent address for this stage (ignored when `selector` is set)
     pub agent: AgentAddress,
+    /// Pick the agent by skills/tags at execution time instead of `agent`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<CapabilityFilter>,
     /// Optional prompt template
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt_template: Option<String>,
+    /// Parallel group: consecutive stages with the same group run concurrently
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Retry policy for failed attempts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// Options for the agent that runs this stage (e.g. `model`, `working_dir` for CLI agents)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Value>,
 }
 
 impl PipelineStage {
@@ -214,14 +226,93 @@ impl PipelineStage {
         Self {
             name: name.into(),
             agent,
+            selector: None,
             prompt_template: None,
+            group: None,
+            retry: None,
+            params: None,
         }
     }
 
+    pub fn with_selector(mut self, selector: CapabilityFilter) -> Self {
+        self.selector = Some(selector);
+        self
+    }
+
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
     pub fn with_prompt_template(mut self, template: impl Into<String>) -> Self {
         self.prompt_template = Some(template.into());
         self
     }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    pub fn with_params(mut self, params: serde_json::Value) -> Self {
+        self.params = Some(params);
+        self
+    }
+}
+
+/// Retry policy for a pipeline stage
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled on every further retry
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,
+    /// Regex patterns matched against the error message (empty = retry any error)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_on: Vec<String>,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_backoff_ms() -> u64 {
+    1000
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, backoff_ms: u64) -> Self {
+        Self {
+            max_attempts,
+            backoff_ms,
+            retry_on: Vec::new(),
+        }
+    }
+
+    pub fn with_retry_on(mut self, patterns: Vec<String>) -> Self {
+        self.retry_on = patterns;
+        self
+    }
+
+    /// Whether another attempt should be made after `attempt` (1-based) failed with `error`
+    pub fn should_retry(&self, attempt: u32, error: &str) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        // Invalid patterns never match
+        self.retry_on.is_empty()
+            || self.retry_on.iter().any(|pattern| {
+                regex::Regex::new(pattern).is_ok_and(|re| re.is_match(error))
+            })
+    }
+
+    /// Delay before the retry that follows failed attempt `attempt` (1-based)
+    pub fn backoff(&self, attempt: u32) -> std::time::Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        std::time::Duration::from_millis(self.backoff_ms.saturating_mul(factor))
+    }
 }
 
 /// Address type for routing (v3 extended)
@@ -380,6 +471,34 @@ impl ACPEnvelope {
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
+
+    /// Correlation ID from the message metadata, falling back to the envelope metadata
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.message
+            .metadata
+            .as_ref()
+            .and_then(|m| m.correlation_id.as_deref())
+            .or_else(|| self.metadata.as_ref().and_then(|m| m.correlation_id.as_deref()))
+    }
+
+    /// Priority from the message metadata, falling back to the envelope metadata
+    pub fn priority(&self) -> Priority {
+        self.message
+            .metadata
+            .as_ref()
+            .and_then(|m| m.priority)
+            .or_else(|| self.metadata.as_ref().and_then(|m| m.priority))
+            .unwrap_or_default()
+    }
+
+    /// Time-to-live in seconds from the message metadata, falling back to the envelope metadata
+    pub fn ttl(&self) -> Option<u64> {
+        self.message
+            .metadata
+            .as_ref()
+            .and_then(|m| m.ttl)
+            .or_else(|| self.metadata.as_ref().and_then(|m| m.ttl))
+    }
 }
 
 /// Envelope-level metadata
@@ -553,6 +672,19 @@ impl ACPMessageV3 {
         }
     }
 
+    /// Create a heartbeat (keep-alive) message
+    pub fn heartbeat(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            from: AgentAddress::new(from),
+            to: AddressType::single(to),
+            message_type: MessageType::Heartbeat,
+            payload: MessagePayload::new(""),
+            metadata: None,
+        }
+    }
+
     /// Create a cancel message
     pub fn cancel(from: impl Into<String>, to: impl Into<String>, task_id: impl Into<String>) -> Self {
         Self {
@@ -655,6 +787,15 @@ impl ACPMessageV3 {
         self
     }
 
+    /// Set correlation ID
+    pub fn with_correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.metadata = Some(self.metadata.unwrap_or_default());
+        if let Some(ref mut meta) = self.metadata {
+            meta.correlation_id = Some(correlation_id.into());
+        }
+        self
+    }
+
     /// Convert to envelope
     pub fn into_envelope(self) -> ACPEnvelope {
         ACPEnvelope::new(self)
@@ -968,4 +1109,22 @@ mod tests {
         assert_eq!(v3.from.id, "agent-a");
         assert_eq!(v3.message_type, MessageType::Prompt);
     }
+
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy::new(3, 500).with_retry_on(vec!["timed out|HTTP Error 5\\d\\d".to_string()]);
+        assert!(policy.should_retry(1, "ERROR: HTTP Error 503: Service Unavailable"));
+        assert!(policy.should_retry(2, "read timed out"));
+        assert!(!policy.should_retry(3, "read timed out"));
+        assert!(!policy.should_retry(1, "subtitle not found"));
+        assert_eq!(policy.backoff(1).as_millis(), 500);
+        assert_eq!(policy.backoff(3).as_millis(), 2000);
+
+        let stage: PipelineStage = serde_json::from_value(serde_json::json!({
+            "name": "download",
+            "agent": { "id": "rust-direct" },
+            "retry": { "max_attempts": 5 }
+        })).unwrap();
+        assert_eq!(stage.retry, Some(RetryPolicy::new(5, 1000)));
+    }
 }
@@ -13,19 +13,35 @@
 
 pub mod adapter;
 pub mod agent;
+pub mod agent_executor;  // Unified prompt execution (CLI / PTY / tmux)
 pub mod adapters;
 pub mod ask;  // ACP v3: Ask Tool handler
+pub mod chunking;  // Chunked translation with context overlap
+pub mod cli_agent;  // Codex CLI / Gemini CLI executors
+pub mod content_filter;  // Profanity / sensitive content filter
+pub mod discovery;  // Remote agent card discovery (.well-known/agent.json)
 pub mod executor;  // CLI-based Claude Code executor
+pub mod glossary;  // Glossary enforcement for translation
+pub mod history;  // Persistent ACP message history
+pub mod length;  // Length constraints from segment timing
 pub mod message;
 pub mod orchestrator;
 pub mod permission;  // Permission management
+pub mod queue;  // Outbound message queue (priority / TTL)
 pub mod pipeline;  // ACP v3: Pipeline execution
+pub mod quality;  // Back-translation quality check
 pub mod registry;
+pub mod review;  // Human review before synthesis
 pub mod runner;  // ACP v3: Pipeline runner
+pub mod server;  // Serves our own agent card and inbound A2A tasks over HTTP
 pub mod state_machine;  // State machine for agent states
 pub mod stream_parser;  // Stream JSON parser
-pub mod subtitle_parser;  // VTT subtitle parser
+pub mod subtitle_parser;  // VTT/SRT subtitle parser
+pub mod translation_memory;  // Cached segment translations
+pub mod translator;  // Provider-agnostic translation interface
+pub mod translators;  // Translator implementations
 pub mod transport;
+pub mod validation;  // Translation output validation
 
 // Legacy modules (kept for backward compatibility during migration)
 pub mod parser;  // Output parser for status detection (legacy)
@@ -33,30 +49,41 @@ pub mod poller;  // Status polling and event emission (legacy)
 pub mod tmux;  // tmux-based orchestrator (legacy)
 
 // Re-exports for convenience
-pub use adapter::SharedContext;
+pub use adapter::{ContextItem, ContextValue, SharedContext};
 pub use agent::{
     A2A_PROTOCOL_VERSION, AgentCapabilities, AgentCard, Authentication, DiscoveryQuery,
     JSONSchema, Provider, Skill, Transport,
 };
 // Legacy alias
 pub use agent::Skill as Capability;
-pub use executor::{ClaudeCodeExecutor, ExecutorError, ExecutorEvent, ExecutorOptions};
+pub use executor::{ClaudeCodeExecutor, ExecutorCanceller, ExecutorError, ExecutorEvent, ExecutorOptions};
 pub use message::{
     ACP_VERSION, ACPEnvelope, ACPMessage, ACPMessageV3, Address, AddressType,
     AgentAddress, CapabilityFilter, EnvelopeMetadata, MessageMetadata, MessagePayload,
     MessageType, PipelineStage, Priority,
 };
-pub use orchestrator::{AgentOrchestrator, OrchestratorStats, TaskState};
-pub use parser::OutputParser;
-pub use permission::{PermissionDecision, PermissionManager, PermissionPolicy, PermissionRequest};
+pub use orchestrator::{
+    execution_workspace, AgentOrchestrator, CorrelatedReply, OrchestratorStats, TaskState, DEFAULT_WORKSPACE,
+};
+pub use parser::{AgentRules, OutputParser};
+pub use permission::{
+    PermissionDecision, PermissionManager, PermissionPolicy, PermissionRequest, PermissionResponder,
+    PermissionRule, PolicyStore, RuleAction,
+};
 pub use pipeline::{
     PipelineDefinition, PipelineError, PipelineExecution, PipelineExecutor, PipelineStatus,
     StageResult, StageStatus,
 };
 pub use poller::{PollerConfig, StatusPoller, StatusChangedPayload, OutputReadyPayload, QuestionPayload};
-pub use runner::{PipelineRunner, RunnerError, ExecutionContext, ProgressPayload};
-pub use state_machine::{AgentState, StateEvent, StateMachine};
-pub use stream_parser::{StreamParser, StreamEvent, ParsedEvent, ParseError};
-pub use subtitle_parser::{VttParser, SubtitleSegment, ParseError as SubtitleParseError};
+pub use glossary::{Glossary, GlossaryEntry, GlossaryReport, GlossaryStore};
+pub use review::{ReviewDecision, ReviewManager, ReviewSegment, ReviewSession};
+pub use runner::{PipelineRunner, RunnerError, ExecutionContext, ProgressPayload, TranslationOptions};
+pub use translator::{SpeechStyle, TranslateOptions, TranslationProvider, Translator, TranslatorError};
+pub use state_machine::{
+    AgentState, InvalidTransition, InvalidTransitionPolicy, StateEvent, StateListener, StateMachine,
+    StateTransition,
+};
+pub use stream_parser::{StreamParser, StreamEvent, ParsedEvent, ParseError, UsageTotals};
+pub use subtitle_parser::{VttParser, SrtParser, SubtitleFormat, SubtitleSegment, ParseError as SubtitleParseError};
 pub use tmux::{TmuxOrchestrator, TmuxError, AgentType as TmuxAgentType, AgentStatus, PaneInfo};
 pub use ask::{AskToolHandler, AskType, AskOption, AskResult, ParsedQuestion, HumanAnswer, AutoAnswerPolicy};
@@ -1,16 +1,26 @@
 //! Agent Orchestrator - manages multiple agents and routes messages
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
-use parking_lot::RwLock;
+use chrono::{Duration, Utc};
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
-use super::adapter::{AdapterError, SharedContext, TaskRequest, TaskResult};
+use super::adapter::{
+    AdapterError, ContextChange, ContextChangeKind, ContextItem, ContextValue, SharedContext, TaskRequest,
+    TaskResult,
+};
 use super::agent::{AgentCard, DiscoveryQuery};
-use super::registry::AgentRegistry;
+use super::history::{Direction, HistoryEntry, MessageHistory};
+use super::message::{ACPEnvelope, ACPMessageV3, AgentAddress, CapabilityFilter, MessageType};
+use super::queue::OutboundQueue;
+use super::registry::{AgentRegistry, AgentStatus};
+use super::transport::{TransportError, TransportEvent, TransportHandle, LOCAL_SENDER};
 
 /// Orchestrator error types
 #[derive(Debug, Error)]
@@ -38,6 +48,15 @@ pub enum OrchestratorError {
 
     #[error("Invalid message: {0}")]
     InvalidMessage(String),
+
+    #[error("No transport attached to agent: {0}")]
+    NoTransport(String),
+
+    #[error("Transport error: {0}")]
+    Transport(#[from] TransportError),
+
+    #[error("Timed out waiting for reply: {0}")]
+    Timeout(String),
 }
 
 impl From<String> for OrchestratorError {
@@ -93,8 +112,142 @@ pub struct OrchestratorStats {
     pub tasks_failed: usize,
     /// Tasks in progress
     pub tasks_in_progress: usize,
+    /// Messages waiting in outbound queues
+    pub queued_messages: usize,
+    /// Queued messages per agent
+    pub queue_depth: HashMap<String, usize>,
+    /// Messages dropped because their TTL expired before delivery
+    pub expired_messages: usize,
+    /// Requests waiting for a reply, per agent
+    pub in_flight: HashMap<String, usize>,
+}
+
+/// Reply matched to a request by correlation ID
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelatedReply {
+    /// ID of the request message
+    pub message_id: String,
+    /// Agent the request was delivered to
+    pub agent_id: String,
+    /// The matching `Response` envelope
+    pub response: ACPEnvelope,
+    /// Content of the `Stream` messages received before the response
+    pub chunks: Vec<String>,
+}
+
+/// Reply-related event seen by a `PendingReply`
+enum ReplyEvent {
+    Chunk(String),
+    Response(ACPEnvelope),
+    Failed(String),
+}
+
+/// Requests in flight and dispatch order per agent, used to spread work
+#[derive(Debug, Default)]
+struct DispatchLoad {
+    in_flight: HashMap<String, usize>,
+    /// Sequence number of the latest request sent to each agent
+    last_dispatch: HashMap<String, u64>,
+    next_seq: u64,
+}
+
+impl DispatchLoad {
+    fn begin(&mut self, agent_id: &str) {
+        *self.in_flight.entry(agent_id.to_string()).or_default() += 1;
+        self.next_seq += 1;
+        self.last_dispatch.insert(agent_id.to_string(), self.next_seq);
+    }
+
+    fn finish(&mut self, agent_id: &str) {
+        if let Some(count) = self.in_flight.get_mut(agent_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.in_flight.remove(agent_id);
+            }
+        }
+    }
+
+    /// (requests in flight, last dispatch) - lower is less loaded
+    fn rank(&self, agent_id: &str) -> (usize, u64) {
+        (
+            self.in_flight.get(agent_id).copied().unwrap_or(0),
+            self.last_dispatch.get(agent_id).copied().unwrap_or(0),
+        )
+    }
+}
+
+/// Request that has been sent and is waiting for its reply
+///
+/// Dropping it stops watching the transport.
+pub struct PendingReply {
+    message_id: String,
+    agent_id: String,
+    transport: Arc<dyn TransportHandle>,
+    subscription: u64,
+    events: mpsc::UnboundedReceiver<ReplyEvent>,
+    load: Arc<Mutex<DispatchLoad>>,
+}
+
+impl PendingReply {
+    /// ID of the request message (replies carry it as `correlation_id`)
+    pub fn message_id(&self) -> &str {
+        &self.message_id
+    }
+
+    /// Agent the request was delivered to
+    pub fn agent_id(&self) -> &str {
+        &self.agent_id
+    }
+
+    /// Wait until the matching `Response` arrives
+    ///
+    /// A correlated `Error` message or the transport closing fails the request.
+    pub async fn wait(mut self, timeout: std::time::Duration) -> Result<CorrelatedReply, OrchestratorError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut chunks = Vec::new();
+        loop {
+            match tokio::time::timeout_at(deadline, self.events.recv()).await {
+                Ok(Some(ReplyEvent::Chunk(text))) => chunks.push(text),
+                Ok(Some(ReplyEvent::Response(response))) => {
+                    return Ok(CorrelatedReply {
+                        message_id: self.message_id.clone(),
+                        agent_id: self.agent_id.clone(),
+                        response,
+                        chunks,
+                    });
+                }
+                Ok(Some(ReplyEvent::Failed(message))) => return Err(OrchestratorError::TaskFailed(message)),
+                Ok(None) => return Err(OrchestratorError::RoutingFailed(format!("{}: transport dropped", self.agent_id))),
+                Err(_) => {
+                    return Err(OrchestratorError::Timeout(format!(
+                        "{} did not answer {} within {:?}",
+                        self.agent_id, self.message_id, timeout
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for PendingReply {
+    fn drop(&mut self) {
+        self.transport.unsubscribe(self.subscription);
+        self.load.lock().finish(&self.agent_id);
+    }
+}
+
+/// Workspace used when none is given
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Workspace holding the values of one pipeline execution (stage outputs keyed by stage name)
+pub fn execution_workspace(execution_id: &str) -> String {
+    format!("execution:{}", execution_id)
 }
 
+/// Callback invoked on every shared context change
+pub type ContextListener = Arc<dyn Fn(&ContextChange) + Send + Sync>;
+
 /// Agent Orchestrator
 ///
 /// This is a simplified version that doesn't store adapters directly.
@@ -102,10 +255,21 @@ pub struct OrchestratorStats {
 pub struct AgentOrchestrator {
     /// Agent registry
     registry: AgentRegistry,
-    /// Shared context for multi-agent tasks
-    shared_context: Arc<RwLock<SharedContext>>,
+    /// Shared context for multi-agent tasks, per workspace
+    contexts: Arc<RwLock<HashMap<String, SharedContext>>>,
+    /// Context change listeners
+    context_listeners: Arc<RwLock<Vec<(u64, ContextListener)>>>,
+    next_listener_id: AtomicU64,
     /// Pending tasks
     tasks: Arc<RwLock<HashMap<String, TaskState>>>,
+    /// Messages waiting for delivery, per agent
+    queue: Arc<Mutex<OutboundQueue>>,
+    /// Sent and received messages
+    history: Arc<Mutex<MessageHistory>>,
+    /// Transport listeners recording received messages, per agent
+    history_subscriptions: Mutex<HashMap<String, (Arc<dyn TransportHandle>, u64)>>,
+    /// Requests in flight per agent (scheduler load signal)
+    load: Arc<Mutex<DispatchLoad>>,
     /// Statistics
     stats: Arc<RwLock<OrchestratorStats>>,
 }
@@ -115,8 +279,14 @@ impl AgentOrchestrator {
     pub fn new() -> Self {
         Self {
             registry: AgentRegistry::new(),
-            shared_context: Arc::new(RwLock::new(SharedContext::new())),
+            contexts: Arc::new(RwLock::new(HashMap::new())),
+            context_listeners: Arc::new(RwLock::new(Vec::new())),
+            next_listener_id: AtomicU64::new(0),
             tasks: Arc::new(RwLock::new(HashMap::new())),
+            queue: Arc::new(Mutex::new(OutboundQueue::new())),
+            history: Arc::new(Mutex::new(MessageHistory::new())),
+            history_subscriptions: Mutex::new(HashMap::new()),
+            load: Arc::new(Mutex::new(DispatchLoad::default())),
             stats: Arc::new(RwLock::new(OrchestratorStats::default())),
         }
     }
@@ -128,6 +298,12 @@ impl AgentOrchestrator {
         Ok(())
     }
 
+    /// Replace the card of a registered agent
+    pub fn update_agent_card(&self, card: AgentCard) -> Result<(), OrchestratorError> {
+        self.registry.update(card)?;
+        Ok(())
+    }
+
     /// Unregister an agent
     pub fn unregister_agent(&self, agent_id: &str) -> Result<(), OrchestratorError> {
         self.registry.unregister(agent_id)?;
@@ -135,11 +311,284 @@ impl AgentOrchestrator {
         Ok(())
     }
 
+    /// Attach a transport so the agent can be messaged with `send`
+    ///
+    /// Messages already queued for the agent are delivered right away, and
+    /// ACP messages received through the transport are recorded in the history.
+    pub fn attach_transport(
+        &self,
+        agent_id: &str,
+        transport: Arc<dyn TransportHandle>,
+    ) -> Result<(), OrchestratorError> {
+        self.registry.attach_transport(agent_id, transport.clone())?;
+
+        let history = self.history.clone();
+        let id = agent_id.to_string();
+        let subscription = transport.subscribe(Arc::new(move |event| {
+            if let TransportEvent::Message { envelope } = event {
+                if let Err(e) = history.lock().record(&id, Direction::Received, &envelope.message) {
+                    crate::log::warn("Orchestrator", &format!("Failed to record message history: {}", e));
+                }
+            }
+        }));
+        let previous = self
+            .history_subscriptions
+            .lock()
+            .insert(agent_id.to_string(), (transport, subscription));
+        if let Some((previous, id)) = previous {
+            previous.unsubscribe(id);
+        }
+
+        self.flush_queue(agent_id);
+        Ok(())
+    }
+
+    /// Use a file-backed message history (replaces the in-memory one)
+    pub fn set_history(&self, history: MessageHistory) {
+        *self.history.lock() = history;
+    }
+
+    /// Recorded messages (oldest first), optionally for one agent and after `since`
+    pub fn history(
+        &self,
+        agent_id: Option<&str>,
+        since: Option<chrono::DateTime<Utc>>,
+        limit: Option<usize>,
+    ) -> Vec<HistoryEntry> {
+        self.history.lock().query(agent_id, since, limit)
+    }
+
+    /// Recorded messages containing `query` in their content, IDs or addresses
+    pub fn search_history(&self, query: &str, limit: Option<usize>) -> Vec<HistoryEntry> {
+        self.history.lock().search(query, limit)
+    }
+
+    /// Get the transport of an agent
+    pub fn transport(&self, agent_id: &str) -> Option<Arc<dyn TransportHandle>> {
+        self.registry.transport(agent_id)
+    }
+
+    /// Send a message to an agent through its transport
+    pub fn send(&self, agent_id: &str, content: &str) -> Result<(), OrchestratorError> {
+        self.route(LOCAL_SENDER, agent_id, content).map(|_| ())
+    }
+
+    /// Resolve an address ("type@host/instance" or a bare agent ID) to a registered agent ID
+    ///
+    /// The full address is tried first, then the address without its instance.
+    pub fn resolve_address(&self, address: &AgentAddress) -> Option<String> {
+        [address.to_address_string(), address.id.clone()]
+            .into_iter()
+            .find(|id| self.registry.get(id).is_some())
+    }
+
+    /// Route a message to the agent addressed by `to`
+    ///
+    /// Plain text is wrapped in a prompt envelope from `from`; content that is
+    /// already an ACP envelope is forwarded unchanged. The envelope goes through
+    /// the agent's outbound queue and its message ID is returned so replies can
+    /// be correlated.
+    pub fn route(&self, from: &str, to: &str, content: &str) -> Result<String, OrchestratorError> {
+        let (agent_id, envelope) = self.prepare(from, to, content)?;
+        let message_id = envelope.message.id.clone();
+        self.enqueue(&agent_id, envelope)?;
+        Ok(message_id)
+    }
+
+    /// Route a message and start watching for its reply
+    ///
+    /// The envelope carries its message ID as `correlation_id`; `Response`, `Stream`
+    /// and `Error` messages from the transport with that ID are matched to it.
+    pub fn request(&self, from: &str, to: &str, content: &str) -> Result<PendingReply, OrchestratorError> {
+        let (agent_id, mut envelope) = self.prepare(from, to, content)?;
+        let transport = self
+            .registry
+            .transport(&agent_id)
+            .ok_or_else(|| OrchestratorError::NoTransport(agent_id.clone()))?;
+        let message_id = envelope.message.id.clone();
+        envelope.metadata = Some(envelope.metadata.take().unwrap_or_default().with_correlation_id(&message_id));
+
+        // Subscribe before sending so a fast reply is not missed
+        let (tx, events) = mpsc::unbounded_channel();
+        let id = message_id.clone();
+        let subscription = transport.subscribe(Arc::new(move |event| {
+            let reply = match event {
+                TransportEvent::Message { envelope } if envelope.correlation_id() == Some(id.as_str()) => {
+                    match envelope.message.message_type {
+                        MessageType::Response => ReplyEvent::Response((**envelope).clone()),
+                        MessageType::Stream => ReplyEvent::Chunk(envelope.message.payload.content.clone()),
+                        MessageType::Error => ReplyEvent::Failed(envelope.message.payload.content.clone()),
+                        _ => return,
+                    }
+                }
+                TransportEvent::Closed => ReplyEvent::Failed("transport closed".to_string()),
+                _ => return,
+            };
+            let _ = tx.send(reply);
+        }));
+        self.load.lock().begin(&agent_id);
+        let pending = PendingReply {
+            message_id,
+            agent_id: agent_id.clone(),
+            transport,
+            subscription,
+            events,
+            load: self.load.clone(),
+        };
+
+        self.enqueue(&agent_id, envelope)?;
+        Ok(pending)
+    }
+
+    /// Send a message and wait for the correlated `Response`
+    pub async fn send_and_wait(
+        &self,
+        from: &str,
+        to: &str,
+        content: &str,
+        timeout: std::time::Duration,
+    ) -> Result<CorrelatedReply, OrchestratorError> {
+        self.request(from, to, content)?.wait(timeout).await
+    }
+
+    /// Send a request to the least-loaded agent matching a filter
+    ///
+    /// See `select_agents` for how the agent is chosen.
+    pub fn dispatch(&self, from: &str, filter: &CapabilityFilter, content: &str) -> Result<PendingReply, OrchestratorError> {
+        let agent_id = self.select_agents(filter).into_iter().next().ok_or_else(|| {
+            OrchestratorError::NoAgentsAvailable(
+                filter.capabilities.as_ref().map(|c| c.join(", ")).unwrap_or_else(|| "any".to_string()),
+            )
+        })?;
+        self.request(from, &agent_id, content)
+    }
+
+    /// Queue an envelope for an agent and deliver what can be delivered now
+    ///
+    /// Fails only if this envelope itself could not be sent.
+    fn enqueue(&self, agent_id: &str, envelope: ACPEnvelope) -> Result<(), OrchestratorError> {
+        let message_id = envelope.message.id.clone();
+        self.queue.lock().push(agent_id, envelope);
+        match self.flush_queue(agent_id).into_iter().find(|(id, _)| *id == message_id) {
+            Some((_, e)) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Deliver queued messages to an agent, highest priority first
+    ///
+    /// Delivery stops while the transport is closed or the agent is busy; those
+    /// messages stay queued. Expired messages are dropped. Messages that fail to
+    /// send are dropped and returned with their errors.
+    pub fn flush_queue(&self, agent_id: &str) -> Vec<(String, TransportError)> {
+        let mut failures = Vec::new();
+        let Some(transport) = self.registry.transport(agent_id) else {
+            return failures;
+        };
+        while transport.is_open() && !self.is_busy(agent_id) {
+            let dequeued = self.queue.lock().pop(agent_id, Utc::now());
+            self.stats.write().expired_messages += dequeued.expired.len();
+            let Some(envelope) = dequeued.next else {
+                break;
+            };
+            match transport.send_envelope(&envelope) {
+                Ok(()) => {
+                    if let Err(e) = self.history.lock().record(agent_id, Direction::Sent, &envelope.message) {
+                        crate::log::warn("Orchestrator", &format!("Failed to record message history: {}", e));
+                    }
+                }
+                Err(e) => failures.push((envelope.message.id, e)),
+            }
+        }
+        failures
+    }
+
+    /// Flush every agent's queue and drop expired messages
+    ///
+    /// Returns the failed deliveries as (agent ID, message ID, error).
+    pub fn flush_queues(&self) -> Vec<(String, String, TransportError)> {
+        let expired = self.queue.lock().purge_expired(Utc::now()).len();
+        self.stats.write().expired_messages += expired;
+
+        let agents = self.queue.lock().agents();
+        agents
+            .into_iter()
+            .flat_map(|agent_id| {
+                self.flush_queue(&agent_id)
+                    .into_iter()
+                    .map(move |(message_id, e)| (agent_id.clone(), message_id, e))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn is_busy(&self, agent_id: &str) -> bool {
+        self.registry
+            .get_registered(agent_id)
+            .is_some_and(|agent| agent.status == AgentStatus::Busy)
+    }
+
+    /// Resolve the target and build the envelope to deliver
+    fn prepare(&self, from: &str, to: &str, content: &str) -> Result<(String, ACPEnvelope), OrchestratorError> {
+        let address = AgentAddress::parse(to)
+            .filter(|a| !a.id.is_empty())
+            .ok_or_else(|| OrchestratorError::InvalidMessage(format!("invalid address: {}", to)))?;
+        let agent_id = self
+            .resolve_address(&address)
+            .ok_or_else(|| OrchestratorError::AgentNotFound(to.to_string()))?;
+        if self.registry.transport(&agent_id).is_none() {
+            return Err(OrchestratorError::NoTransport(agent_id));
+        }
+
+        let envelope = match ACPEnvelope::from_json(content.trim()) {
+            Ok(envelope) => envelope,
+            Err(_) => ACPEnvelope::new(ACPMessageV3::prompt(from, &agent_id, content)),
+        };
+        Ok((agent_id, envelope))
+    }
+
+    /// Send a message to every available agent matching the query that has an open transport
+    ///
+    /// Returns the IDs of the agents the message was delivered to.
+    pub fn broadcast(&self, query: &DiscoveryQuery, content: &str) -> Vec<String> {
+        self.discover_agents(query)
+            .into_iter()
+            .filter_map(|card| card.id.clone().or_else(|| Some(card.name.clone())))
+            .filter(|id| {
+                self.registry
+                    .transport(id)
+                    .is_some_and(|t| t.is_open() && t.send(content).is_ok())
+            })
+            .collect()
+    }
+
     /// Discover agents by query
     pub fn discover_agents(&self, query: &DiscoveryQuery) -> Vec<AgentCard> {
         self.registry.discover(query)
     }
 
+    /// Agents matching a filter that can take work now, least loaded first
+    ///
+    /// Only agents with an open transport are returned. Idle (`Online`) agents
+    /// come before busy ones (the status poller and executor state keep the
+    /// registry status current), then agents with fewer requests in flight, then
+    /// the one dispatched to longest ago, so equal agents take turns.
+    pub fn select_agents(&self, filter: &CapabilityFilter) -> Vec<String> {
+        let load = self.load.lock();
+        let mut candidates: Vec<(bool, (usize, u64), String)> = self
+            .registry
+            .matching(filter)
+            .into_iter()
+            .map(|agent| {
+                let id = agent.card.id.clone().unwrap_or_else(|| agent.card.name.clone());
+                (agent.status == AgentStatus::Busy, load.rank(&id), id)
+            })
+            .filter(|(_, _, id)| self.registry.transport(id).is_some_and(|t| t.is_open()))
+            .collect();
+        candidates.sort();
+        candidates.into_iter().map(|(_, _, id)| id).collect()
+    }
+
     /// Get all registered agents
     pub fn list_agents(&self) -> Vec<AgentCard> {
         self.registry.list_available()
@@ -184,7 +633,7 @@ impl AgentOrchestrator {
         }
 
         // Create task request with shared context
-        let request = TaskRequest::new(content).with_context(self.shared_context.read().clone());
+        let request = TaskRequest::new(content).with_context(self.get_shared_context());
 
         Ok(request)
     }
@@ -197,8 +646,10 @@ impl AgentOrchestrator {
             task.result = Some(result.clone());
 
             // Update shared context
-            self.shared_context
+            self.contexts
                 .write()
+                .entry(DEFAULT_WORKSPACE.to_string())
+                .or_default()
                 .add_entry(task.to.clone(), result.output);
         }
 
@@ -222,7 +673,19 @@ impl AgentOrchestrator {
 
     /// Get orchestrator statistics
     pub fn stats(&self) -> OrchestratorStats {
-        self.stats.read().clone()
+        // Cards may be added or removed by the card watcher
+        let mut stats = self.stats.read().clone();
+        stats.total_agents = self.registry.count();
+        let queue = self.queue.lock();
+        stats.queued_messages = queue.len();
+        stats.queue_depth = queue.depths();
+        stats.in_flight = self.load.lock().in_flight.clone();
+        stats
+    }
+
+    /// Shared handle to the registry (e.g. for `CardWatcher`)
+    pub fn registry(&self) -> AgentRegistry {
+        self.registry.clone()
     }
 
     /// Get task state
@@ -242,9 +705,132 @@ impl AgentOrchestrator {
         self.registry.cleanup_stale()
     }
 
-    /// Get shared context
+    /// Get shared context of the default workspace
     pub fn get_shared_context(&self) -> SharedContext {
-        self.shared_context.read().clone()
+        self.get_workspace_context(DEFAULT_WORKSPACE)
+    }
+
+    /// Get shared context of a workspace (expired keys are purged first)
+    pub fn get_workspace_context(&self, workspace: &str) -> SharedContext {
+        self.purge_expired_context();
+        self.contexts.read().get(workspace).cloned().unwrap_or_default()
+    }
+
+    /// Set a typed value in a workspace, optionally expiring after `ttl`
+    pub fn set_context_value(
+        &self,
+        workspace: &str,
+        key: &str,
+        value: ContextValue,
+        ttl: Option<Duration>,
+        updated_by: Option<String>,
+    ) -> ContextItem {
+        let item = self
+            .contexts
+            .write()
+            .entry(workspace.to_string())
+            .or_default()
+            .set_value(key, value, ttl, updated_by);
+        self.notify_context(ContextChange {
+            workspace: workspace.to_string(),
+            key: key.to_string(),
+            kind: ContextChangeKind::Set,
+            item: Some(item.clone()),
+        });
+        item
+    }
+
+    /// Get a value from a workspace
+    pub fn get_context_value(&self, workspace: &str, key: &str) -> Option<ContextItem> {
+        self.purge_expired_context();
+        self.contexts.read().get(workspace)?.get_value(key).cloned()
+    }
+
+    /// Delete a value from a workspace; returns whether it existed
+    pub fn delete_context_value(&self, workspace: &str, key: &str) -> bool {
+        let removed = self
+            .contexts
+            .write()
+            .get_mut(workspace)
+            .and_then(|context| context.remove_value(key))
+            .is_some();
+        if removed {
+            self.notify_context(ContextChange {
+                workspace: workspace.to_string(),
+                key: key.to_string(),
+                kind: ContextChangeKind::Deleted,
+                item: None,
+            });
+        }
+        removed
+    }
+
+    /// Delete every value in a workspace; returns how many were removed
+    pub fn clear_workspace(&self, workspace: &str) -> usize {
+        let Some(context) = self.contexts.write().remove(workspace) else {
+            return 0;
+        };
+        for key in context.values.keys() {
+            self.notify_context(ContextChange {
+                workspace: workspace.to_string(),
+                key: key.clone(),
+                kind: ContextChangeKind::Deleted,
+                item: None,
+            });
+        }
+        context.values.len()
+    }
+
+    /// Drop expired keys in all workspaces and notify listeners
+    pub fn purge_expired_context(&self) -> usize {
+        let expired: Vec<(String, String)> = self
+            .contexts
+            .write()
+            .iter_mut()
+            .flat_map(|(workspace, context)| {
+                context
+                    .purge_expired()
+                    .into_iter()
+                    .map(|key| (workspace.clone(), key))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        for (workspace, key) in &expired {
+            self.notify_context(ContextChange {
+                workspace: workspace.clone(),
+                key: key.clone(),
+                kind: ContextChangeKind::Expired,
+                item: None,
+            });
+        }
+        expired.len()
+    }
+
+    /// Register a context change listener; returns an id for unsubscribing
+    pub fn subscribe_context<F>(&self, listener: F) -> u64
+    where
+        F: Fn(&ContextChange) + Send + Sync + 'static,
+    {
+        let id = self.next_listener_id.fetch_add(1, Ordering::SeqCst);
+        self.context_listeners.write().push((id, Arc::new(listener)));
+        id
+    }
+
+    /// Remove a context change listener
+    pub fn unsubscribe_context(&self, id: u64) -> bool {
+        let mut listeners = self.context_listeners.write();
+        let before = listeners.len();
+        listeners.retain(|(entry_id, _)| *entry_id != id);
+        listeners.len() != before
+    }
+
+    fn notify_context(&self, change: ContextChange) {
+        // Clone the list so listeners can call back into the orchestrator
+        let listeners: Vec<ContextListener> =
+            self.context_listeners.read().iter().map(|(_, l)| l.clone()).collect();
+        for listener in listeners {
+            listener(&change);
+        }
     }
 }
 
@@ -273,4 +859,274 @@ mod tests {
         orchestrator.register_agent_card(card).unwrap();
         assert_eq!(orchestrator.stats().total_agents, 1);
     }
+
+    /// Records sent messages
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: RwLock<Vec<String>>,
+        subscribers: crate::acp::transport::Subscribers,
+        closed: std::sync::atomic::AtomicBool,
+    }
+
+    impl TransportHandle for RecordingTransport {
+        fn kind(&self) -> crate::acp::agent::Transport {
+            crate::acp::agent::Transport::Pty
+        }
+        fn send(&self, content: &str) -> Result<(), TransportError> {
+            self.sent.write().push(content.to_string());
+            Ok(())
+        }
+        fn subscribe(&self, listener: crate::acp::transport::TransportListener) -> u64 {
+            self.subscribers.add(listener)
+        }
+        fn unsubscribe(&self, id: u64) -> bool {
+            self.subscribers.remove(id)
+        }
+        fn close(&self) -> Result<(), TransportError> {
+            Ok(())
+        }
+        fn is_open(&self) -> bool {
+            !self.closed.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn test_send_through_transport() {
+        let orchestrator = AgentOrchestrator::new();
+        orchestrator.register_agent_card(AgentCard::claude_code("a")).unwrap();
+        orchestrator.register_agent_card(AgentCard::claude_code("b")).unwrap();
+
+        assert!(matches!(
+            orchestrator.send("claude-code@localhost/a", "hi"),
+            Err(OrchestratorError::NoTransport(_))
+        ));
+        assert!(matches!(orchestrator.send("missing", "hi"), Err(OrchestratorError::AgentNotFound(_))));
+
+        let transport = Arc::new(RecordingTransport::default());
+        orchestrator.attach_transport("claude-code@localhost/a", transport.clone()).unwrap();
+        let message_id = orchestrator.route("app", "claude-code@localhost/a", "hello").unwrap();
+        assert!(Uuid::parse_str(&message_id).is_ok());
+
+        // Only agents with a transport receive broadcasts
+        let delivered = orchestrator.broadcast(&DiscoveryQuery::new(), "all");
+        assert_eq!(delivered, vec!["claude-code@localhost/a".to_string()]);
+        assert_eq!(*transport.sent.read(), vec!["hello".to_string(), "all".to_string()]);
+    }
+
+    #[test]
+    fn test_route_envelope() {
+        let orchestrator = AgentOrchestrator::new();
+        orchestrator.register_agent_card(AgentCard::new("stdio-agent", "stdio://agent").with_id("stdio-agent")).unwrap();
+        let transport = Arc::new(RecordingTransport::default());
+        orchestrator.attach_transport("stdio-agent", transport.clone()).unwrap();
+
+        // An existing envelope keeps its message ID; text transports receive only the content
+        let envelope = ACPEnvelope::new(ACPMessageV3::prompt("app", "stdio-agent", "translate"));
+        let json = envelope.to_json().unwrap();
+        assert_eq!(orchestrator.route("app", "stdio-agent", &json).unwrap(), envelope.message.id);
+        assert_eq!(*transport.sent.read(), vec!["translate".to_string()]);
+
+        assert!(matches!(orchestrator.route("app", "", "hi"), Err(OrchestratorError::InvalidMessage(_))));
+        assert!(matches!(orchestrator.route("app", "other/x", "hi"), Err(OrchestratorError::AgentNotFound(_))));
+    }
+
+    /// Answers every envelope with a stream chunk and a correlated response
+    #[derive(Default)]
+    struct EchoTransport {
+        subscribers: crate::acp::transport::Subscribers,
+    }
+
+    impl TransportHandle for EchoTransport {
+        fn kind(&self) -> crate::acp::agent::Transport {
+            crate::acp::agent::Transport::Stdio
+        }
+        fn send(&self, _content: &str) -> Result<(), TransportError> {
+            Ok(())
+        }
+        fn send_envelope(&self, envelope: &ACPEnvelope) -> Result<(), TransportError> {
+            use crate::acp::transport::reply_to;
+            let content = &envelope.message.payload.content;
+            // An unrelated reply must be ignored
+            let other = ACPEnvelope::new(ACPMessageV3::response("echo", "app", "other", "unrelated"));
+            for reply in [
+                other,
+                reply_to(envelope, MessageType::Stream, "partial"),
+                reply_to(envelope, MessageType::Response, &content.to_uppercase()),
+            ] {
+                self.subscribers.notify(&TransportEvent::Message { envelope: Box::new(reply) });
+            }
+            Ok(())
+        }
+        fn subscribe(&self, listener: crate::acp::transport::TransportListener) -> u64 {
+            self.subscribers.add(listener)
+        }
+        fn unsubscribe(&self, id: u64) -> bool {
+            self.subscribers.remove(id)
+        }
+        fn close(&self) -> Result<(), TransportError> {
+            Ok(())
+        }
+        fn is_open(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_select_agents() {
+        let orchestrator = AgentOrchestrator::new();
+        for id in ["a", "b", "c"] {
+            orchestrator.register_agent_card(AgentCard::claude_code(id)).unwrap();
+        }
+        orchestrator.register_agent_card(AgentCard::codex("d")).unwrap();
+        for id in ["claude-code@localhost/a", "claude-code@localhost/b", "codex@localhost/d"] {
+            orchestrator.attach_transport(id, Arc::new(RecordingTransport::default())).unwrap();
+        }
+        orchestrator.registry().set_status("claude-code@localhost/a", AgentStatus::Busy).unwrap();
+
+        // Idle before busy; "c" has no transport
+        let translation = CapabilityFilter::new().with_capabilities(vec!["translation".into()]);
+        assert_eq!(
+            orchestrator.select_agents(&translation),
+            ["claude-code@localhost/b", "claude-code@localhost/a"]
+        );
+        let debugging = CapabilityFilter::new().with_capabilities(vec!["debugging".into()]);
+        assert_eq!(orchestrator.select_agents(&debugging), ["codex@localhost/d"]);
+
+        orchestrator.registry().set_status("claude-code@localhost/b", AgentStatus::Offline).unwrap();
+        assert_eq!(orchestrator.select_agents(&translation), ["claude-code@localhost/a"]);
+    }
+
+    #[test]
+    fn test_dispatch_spreads_load() {
+        let orchestrator = AgentOrchestrator::new();
+        for id in ["a", "b"] {
+            orchestrator.register_agent_card(AgentCard::claude_code(id)).unwrap();
+            let agent_id = format!("claude-code@localhost/{}", id);
+            orchestrator.attach_transport(&agent_id, Arc::new(RecordingTransport::default())).unwrap();
+        }
+        let any = CapabilityFilter::new();
+
+        // An agent with a request in flight is skipped
+        let first = orchestrator.dispatch("app", &any, "one").unwrap();
+        let second = orchestrator.dispatch("app", &any, "two").unwrap();
+        assert_ne!(first.agent_id(), second.agent_id());
+        assert_eq!(orchestrator.stats().in_flight.len(), 2);
+        drop(first);
+        drop(second);
+        assert!(orchestrator.stats().in_flight.is_empty());
+
+        // Idle agents take turns
+        let picks: Vec<String> = (0..4)
+            .map(|_| orchestrator.dispatch("app", &any, "x").unwrap().agent_id().to_string())
+            .collect();
+        assert_eq!(picks[0], picks[2]);
+        assert_eq!(picks[1], picks[3]);
+        assert_ne!(picks[0], picks[1]);
+
+        // A busy agent is only used when no idle one is left
+        orchestrator.registry().set_status(&picks[0], AgentStatus::Busy).unwrap();
+        let pick = orchestrator.dispatch("app", &any, "x").unwrap();
+        assert_eq!(pick.agent_id(), picks[1]);
+        let skills = CapabilityFilter::new().with_capabilities(vec!["debugging".into()]);
+        assert!(matches!(orchestrator.dispatch("app", &skills, "x"), Err(OrchestratorError::NoAgentsAvailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_send_and_wait() {
+        let orchestrator = AgentOrchestrator::new();
+        orchestrator.register_agent_card(AgentCard::new("echo", "stdio://echo").with_id("echo")).unwrap();
+        orchestrator.register_agent_card(AgentCard::new("silent", "stdio://silent").with_id("silent")).unwrap();
+        let echo = Arc::new(EchoTransport::default());
+        orchestrator.attach_transport("echo", echo.clone()).unwrap();
+        orchestrator.attach_transport("silent", Arc::new(RecordingTransport::default())).unwrap();
+
+        let timeout = std::time::Duration::from_millis(200);
+        let reply = orchestrator.send_and_wait("app", "echo", "hello", timeout).await.unwrap();
+        assert_eq!(reply.response.message.payload.content, "HELLO");
+        assert_eq!(reply.response.correlation_id(), Some(reply.message_id.as_str()));
+        assert_eq!(reply.chunks, vec!["partial".to_string()]);
+        // The reply watcher is removed once the request is done; the history recorder stays
+        assert!(!echo.subscribers.is_empty());
+
+        // Request, unrelated reply, stream chunk and response are all recorded
+        let history = orchestrator.history(Some("echo"), None, None);
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.iter().filter(|e| e.direction == Direction::Sent).count(), 1);
+        assert_eq!(orchestrator.search_history(&reply.message_id, None).len(), 3);
+
+        assert!(matches!(
+            orchestrator.send_and_wait("app", "silent", "hello", timeout).await,
+            Err(OrchestratorError::Timeout(_))
+        ));
+    }
+
+    #[test]
+    fn test_queue_while_transport_closed() {
+        use crate::acp::message::Priority;
+
+        let orchestrator = AgentOrchestrator::new();
+        orchestrator.register_agent_card(AgentCard::new("agent", "stdio://agent").with_id("agent")).unwrap();
+        let transport = Arc::new(RecordingTransport::default());
+        transport.closed.store(true, Ordering::SeqCst);
+        orchestrator.attach_transport("agent", transport.clone()).unwrap();
+
+        let send = |content: &str, priority: Priority, ttl: Option<u64>| {
+            let mut message = ACPMessageV3::prompt("app", "agent", content).with_priority(priority);
+            if let Some(ttl) = ttl {
+                message = message.with_ttl(ttl);
+                message.timestamp = Utc::now() - Duration::seconds(10);
+            }
+            orchestrator.route("app", "agent", &message.into_envelope().to_json().unwrap()).unwrap();
+        };
+        send("low", Priority::Low, None);
+        send("normal", Priority::Normal, None);
+        send("expired", Priority::Urgent, Some(5));
+        send("high", Priority::High, None);
+
+        let stats = orchestrator.stats();
+        assert_eq!(stats.queued_messages, 4);
+        assert_eq!(stats.queue_depth["agent"], 4);
+        assert!(transport.sent.read().is_empty());
+
+        transport.closed.store(false, Ordering::SeqCst);
+        assert!(orchestrator.flush_queues().is_empty());
+        assert_eq!(*transport.sent.read(), vec!["high".to_string(), "normal".to_string(), "low".to_string()]);
+
+        let stats = orchestrator.stats();
+        assert_eq!(stats.queued_messages, 0);
+        assert_eq!(stats.expired_messages, 1);
+    }
+
+    #[test]
+    fn test_context_store_notifies_listeners() {
+        let orchestrator = AgentOrchestrator::new();
+        let changes = Arc::new(RwLock::new(Vec::new()));
+        let sink = changes.clone();
+        let id = orchestrator.subscribe_context(move |change| sink.write().push((change.key.clone(), change.kind)));
+
+        orchestrator.set_context_value("ws", "lang", ContextValue::Text("ja".into()), None, None);
+        orchestrator.set_context_value("ws", "tmp", ContextValue::Bool(true), Some(Duration::seconds(-1)), None);
+        assert!(orchestrator.get_context_value("ws", "lang").is_some());
+        assert!(orchestrator.get_context_value("ws", "tmp").is_none());
+        assert!(orchestrator.get_context_value(DEFAULT_WORKSPACE, "lang").is_none());
+        assert!(orchestrator.delete_context_value("ws", "lang"));
+
+        orchestrator.set_context_value(&execution_workspace("e1"), "translate", ContextValue::Text("訳".into()), None, None);
+        assert_eq!(orchestrator.clear_workspace(&execution_workspace("e1")), 1);
+        assert_eq!(orchestrator.clear_workspace(&execution_workspace("e1")), 0);
+        assert!(orchestrator.unsubscribe_context(id));
+        orchestrator.set_context_value("ws", "after", ContextValue::Number(1.0), None, None);
+
+        assert_eq!(
+            *changes.read(),
+            vec![
+                ("lang".to_string(), ContextChangeKind::Set),
+                ("tmp".to_string(), ContextChangeKind::Set),
+                ("tmp".to_string(), ContextChangeKind::Expired),
+                ("lang".to_string(), ContextChangeKind::Deleted),
+                ("translate".to_string(), ContextChangeKind::Set),
+                ("translate".to_string(), ContextChangeKind::Deleted),
+            ]
+        );
+    }
 }
@@ -1,11 +1,12 @@
 //! Claude Code 出力パーサー（状態遷移ベース版）
 //!
 //! 送信前後の画面変化を検出して状態を判定する。
+//! Claude Code以外（Codex、素のシェル）はエージェント種別ごとのルールで判定する。
 
 use regex::Regex;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
-use super::tmux::AgentStatus;
+use super::tmux::{AgentStatus, AgentType};
 
 /// 画面のハッシュ値を計算
 pub fn content_hash(content: &str) -> u64 {
@@ -14,6 +15,56 @@ pub fn content_hash(content: &str) -> u64 {
     hasher.finish()
 }
 
+/// エージェント種別ごとの判定ルール
+///
+/// 質問 → 処理中 → アイドル（最終行のプロンプト）の順に照合し、
+/// どれにも当てはまらなければ汎用のシェル判定にフォールバックする。
+#[derive(Debug, Clone)]
+pub struct AgentRules {
+    /// 処理中の表示
+    pub processing: Vec<Regex>,
+    /// 入力待ち（アイドル）のプロンプト。最終行に対して照合する
+    pub idle: Vec<Regex>,
+    /// 確認・質問のプロンプト
+    pub question: Vec<Regex>,
+}
+
+impl AgentRules {
+    /// Codex CLI
+    pub fn codex() -> Self {
+        Self {
+            processing: vec![
+                Regex::new(r"(?i)esc to interrupt").unwrap(),
+                Regex::new(r"(?i)^\s*[•◦·⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏]?\s*(Working|Thinking|Running)\b").unwrap(),
+            ],
+            idle: vec![
+                Regex::new(r"^\s*[▌›>]\s*$").unwrap(),
+                Regex::new(r"(?i)(⏎ send|send a message|ctrl\+j newline)").unwrap(),
+            ],
+            question: vec![
+                Regex::new(r"(?i)allow (command|codex)").unwrap(),
+                Regex::new(r"(?i)(approve|yes, proceed|\(y/n\)|\[y/n\])").unwrap(),
+            ],
+        }
+    }
+
+    /// 汎用シェル（bash/zsh/fish/PowerShell）
+    pub fn shell() -> Self {
+        Self {
+            processing: Vec::new(),
+            idle: vec![Self::shell_prompt()],
+            question: vec![
+                Regex::new(r"(?i)(\[y/n\]|\(y/n\)|\(yes/no\)|password( for [^:]+)?:\s*$|continue\?\s*$)").unwrap(),
+            ],
+        }
+    }
+
+    /// シェルのプロンプト行（`$` `#` `%` `>` `❯` で終わる）
+    fn shell_prompt() -> Regex {
+        Regex::new(r"[$#%>❯]\s*$").unwrap()
+    }
+}
+
 /// 出力パーサー（状態遷移ベース）
 pub struct OutputParser {
     /// マーカー検出用正規表現
@@ -26,6 +77,11 @@ pub struct OutputParser {
     tool_execution: Regex,
     spinner_pattern: Regex,
     thinking_pattern: Regex,
+    /// Claude Code以外のルール
+    codex_rules: AgentRules,
+    shell_rules: AgentRules,
+    /// ルールに一致しなかったときのシェル判定
+    shell_prompt: Regex,
 }
 
 impl OutputParser {
@@ -41,9 +97,22 @@ impl OutputParser {
             tool_execution: Regex::new(r"⏺\s*(Bash|Read|Write|Edit|Grep|Glob|Task)").unwrap(),
             spinner_pattern: Regex::new(r"[✢✳✶✻✷✸✹✺·⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏]").unwrap(),
             thinking_pattern: Regex::new(r"(?i)(Thinking|Processing|Working|Generating)[.。…]*").unwrap(),
+            codex_rules: AgentRules::codex(),
+            shell_rules: AgentRules::shell(),
+            shell_prompt: AgentRules::shell_prompt(),
         }
     }
 
+    /// Codex/シェルのルールを差し替える
+    pub fn with_rules(mut self, agent_type: &AgentType, rules: AgentRules) -> Self {
+        match agent_type {
+            AgentType::Codex => self.codex_rules = rules,
+            AgentType::GenericShell => self.shell_rules = rules,
+            AgentType::ClaudeCode => {}
+        }
+        self
+    }
+
     /// Claude Codeの権限プロンプト（AskTool）を検出
     fn is_permission_prompt(&self, content: &str) -> bool {
         // Claude Codeの権限プロンプトの特徴的なパターン
@@ -140,6 +209,58 @@ impl OutputParser {
         status
     }
 
+    /// エージェント種別に応じて状態を判定
+    pub fn parse_for(&self, agent_type: &AgentType, content: &str) -> AgentStatus {
+        let rules = match agent_type {
+            AgentType::ClaudeCode => return self.parse(content),
+            AgentType::Codex => &self.codex_rules,
+            AgentType::GenericShell => &self.shell_rules,
+        };
+        self.parse_with_rules(rules, content)
+    }
+
+    /// ルールで判定（マーカーはClaude Codeと同じく最優先）
+    fn parse_with_rules(&self, rules: &AgentRules, content: &str) -> AgentStatus {
+        let stripped = Self::strip_ansi(content);
+        let content_trimmed = stripped.trim();
+        if content_trimmed.is_empty() {
+            return AgentStatus::Unknown;
+        }
+
+        if self.error_marker.is_match(content_trimmed) {
+            let message = self.extract_error_message(content_trimmed);
+            return AgentStatus::Error { message };
+        }
+        if self.waiting_marker.is_match(content_trimmed) || self.ask_marker.is_match(content_trimmed) {
+            let question = self.extract_question(content_trimmed);
+            return AgentStatus::WaitingForInput { question };
+        }
+        if self.done_marker.is_match(content_trimmed) {
+            return AgentStatus::Idle;
+        }
+
+        // 画面の末尾だけを見る（スクロールバックの古い表示で誤判定しない）
+        let tail: Vec<&str> = content_trimmed.lines().rev().take(8).collect();
+        let last_line = tail.first().copied().unwrap_or_default();
+
+        if let Some(line) = tail.iter().find(|line| rules.question.iter().any(|re| re.is_match(line))) {
+            return AgentStatus::WaitingForInput { question: line.trim().to_string() };
+        }
+        if tail.iter().any(|line| rules.processing.iter().any(|re| re.is_match(line))) {
+            return AgentStatus::Processing;
+        }
+        if tail.iter().take(3).any(|line| rules.idle.iter().any(|re| re.is_match(line))) {
+            return AgentStatus::Idle;
+        }
+
+        // フォールバック: 最終行がシェルのプロンプトならアイドル、そうでなければコマンド実行中
+        if self.shell_prompt.is_match(last_line) {
+            AgentStatus::Idle
+        } else {
+            AgentStatus::Processing
+        }
+    }
+
     /// ウェルカム画面かどうか
     fn is_welcome_screen(&self, content: &str) -> bool {
         content.contains("Claude Code")
@@ -378,4 +499,35 @@ mod tests {
 
         assert!(!parser.is_permission_prompt(content));
     }
+
+    #[test]
+    fn test_codex_rules() {
+        let parser = OutputParser::new();
+
+        let working = "› fix the tests\n\n• Working (12s • esc to interrupt)";
+        assert_eq!(parser.parse_for(&AgentType::Codex, working), AgentStatus::Processing);
+
+        let idle = "• Done. All tests pass.\n\n▌\n ⏎ send   Ctrl+J newline";
+        assert_eq!(parser.parse_for(&AgentType::Codex, idle), AgentStatus::Idle);
+
+        let approval = "$ rm -rf build\nAllow command? (y/n)";
+        assert!(matches!(
+            parser.parse_for(&AgentType::Codex, approval),
+            AgentStatus::WaitingForInput { .. }
+        ));
+    }
+
+    #[test]
+    fn test_generic_shell_detector() {
+        let parser = OutputParser::new();
+
+        assert_eq!(parser.parse_for(&AgentType::GenericShell, "$ ls\nsrc\nuser@host:~/repo$ "), AgentStatus::Idle);
+        assert_eq!(parser.parse_for(&AgentType::GenericShell, "$ cargo build\n   Compiling foo v0.1.0"), AgentStatus::Processing);
+        assert_eq!(
+            parser.parse_for(&AgentType::GenericShell, "$ apt remove foo\nDo you want to continue? [Y/n] "),
+            AgentStatus::WaitingForInput { question: "Do you want to continue? [Y/n]".to_string() }
+        );
+        // Claude Codeは従来どおりマーカーがなければProcessing
+        assert_eq!(parser.parse_for(&AgentType::ClaudeCode, "user@host:~$ "), AgentStatus::Processing);
+    }
 }
@@ -2,15 +2,23 @@
 //!
 //! ツール実行の権限を管理する。
 //! 読み取り系は自動許可、書き込み系は人間確認。
+//! ユーザーが登録したルール（ツール名・パス・コマンド → 許可/拒否/確認）は
+//! `permission_policies.json` に保存し、組み込みの判定より優先する。
 
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
+use tokio::sync::oneshot;
 
+use crate::events::RecordedEmit;
 use crate::log;
 
 /// 権限決定
@@ -55,6 +63,205 @@ impl Default for PermissionPolicy {
     }
 }
 
+/// 権限ルールの保存先ファイル名（アプリデータディレクトリ直下）
+pub const POLICY_FILE: &str = "permission_policies.json";
+
+/// ルールに合ったときの扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    Allow,
+    Deny,
+    /// 人間に確認する
+    Ask,
+}
+
+/// 権限ルール
+///
+/// `tool` は完全一致（`*` で全ツール）。`path` はツール入力のパス（`file_path` など）に対するglob
+/// （`*` は `/` を含まない任意の文字列、`**` は `/` を含む）、`command` はBashコマンド全体に対する
+/// パターン（`*` は任意の文字列）。指定した条件がすべて合うと一致する。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionRule {
+    pub id: String,
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    pub action: RuleAction,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PermissionRule {
+    pub fn new(tool: impl Into<String>, action: RuleAction) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            tool: tool.into(),
+            path: None,
+            command: None,
+            action,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    /// 「常に許可」の回答から作るルール（Bashはそのコマンドだけ、それ以外はツール全体）
+    ///
+    /// コマンド中の `*` はワイルドカードにならないよう1文字の `?` にする。
+    pub fn always_allow(tool_name: &str, tool_input: &Value) -> Self {
+        let rule = Self::new(tool_name, RuleAction::Allow);
+        match tool_input.get("command").and_then(Value::as_str) {
+            Some(command) if tool_name == "Bash" => rule.with_command(command.replace('*', "?")),
+            _ => rule,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.tool.trim().is_empty() {
+            return Err("Rule tool must not be empty".to_string());
+        }
+        if self.path.as_deref().is_some_and(|p| p.trim().is_empty())
+            || self.command.as_deref().is_some_and(|c| c.trim().is_empty())
+        {
+            return Err("Rule patterns must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// 条件が同じルールか
+    fn same_target(&self, other: &Self) -> bool {
+        self.tool == other.tool && self.path == other.path && self.command == other.command
+    }
+
+    fn matches(&self, tool_name: &str, tool_input: &Value) -> bool {
+        if self.tool != "*" && self.tool != tool_name {
+            return false;
+        }
+        if let Some(ref pattern) = self.path {
+            let path = ["file_path", "path", "notebook_path"]
+                .iter()
+                .find_map(|key| tool_input.get(*key).and_then(Value::as_str));
+            if !path.is_some_and(|path| wildcard_match(pattern, path, true)) {
+                return false;
+            }
+        }
+        if let Some(ref pattern) = self.command {
+            let command = tool_input.get("command").and_then(Value::as_str);
+            if !command.is_some_and(|command| wildcard_match(pattern, command.trim(), false)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// `*`・`**`・`?` のワイルドカードで全体一致を判定（`path_glob` なら `*`・`?` は `/` に合わない）
+fn wildcard_match(pattern: &str, text: &str, path_glob: bool) -> bool {
+    let (any, one) = if path_glob { ("[^/]*", "[^/]") } else { (".*", ".") };
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str(any),
+            '?' => regex.push_str(one),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).is_ok_and(|re| re.is_match(text))
+}
+
+/// 権限ルールの保存（JSON配列）
+#[derive(Debug, Default)]
+pub struct PolicyStore {
+    /// 保存先（Noneならメモリのみ）
+    path: Option<PathBuf>,
+    rules: Vec<PermissionRule>,
+}
+
+impl PolicyStore {
+    /// JSONファイルから読み込む（ファイルがなければ空）
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let rules = if path.exists() {
+            let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            serde_json::from_str(&text).map_err(|e| e.to_string())?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            rules,
+        })
+    }
+
+    pub fn rules(&self) -> &[PermissionRule] {
+        &self.rules
+    }
+
+    /// ルールを追加して保存（条件が同じルールがあれば扱いを置き換える）
+    pub fn add(&mut self, rule: PermissionRule) -> Result<PermissionRule, String> {
+        rule.validate()?;
+        let rule = match self.rules.iter_mut().find(|r| r.same_target(&rule)) {
+            Some(existing) => {
+                existing.action = rule.action;
+                existing.clone()
+            }
+            None => {
+                self.rules.push(rule.clone());
+                rule
+            }
+        };
+        self.save()?;
+        Ok(rule)
+    }
+
+    /// ルールを削除して保存（なければfalse）
+    pub fn remove(&mut self, id: &str) -> Result<bool, String> {
+        let Some(index) = self.rules.iter().position(|r| r.id == id) else {
+            return Ok(false);
+        };
+        self.rules.remove(index);
+        self.save().map(|_| true)
+    }
+
+    /// 一致するルールの扱い（複数合えば拒否 > 確認 > 許可）
+    pub fn evaluate(&self, tool_name: &str, tool_input: &Value) -> Option<&PermissionRule> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(tool_name, tool_input))
+            .min_by_key(|rule| match rule.action {
+                RuleAction::Deny => 0,
+                RuleAction::Ask => 1,
+                RuleAction::Allow => 2,
+            })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(&self.rules).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+}
+
 /// 権限要求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionRequest {
@@ -65,6 +272,82 @@ pub struct PermissionRequest {
     pub timestamp: String,
 }
 
+/// 届いた回答と回答待ち（同じロックで扱い、待機開始前に届いた回答も取りこぼさない）
+#[derive(Default)]
+struct Answers {
+    /// 待機開始前に届いた回答
+    received: HashMap<String, PermissionDecision>,
+    /// 回答待ちのチャネル
+    waiters: HashMap<String, oneshot::Sender<PermissionDecision>>,
+}
+
+/// 権限要求と人間の回答の受け渡し
+///
+/// 複製しても同じ要求を共有するので、実行中のエグゼキューターをロックせずに回答できる。
+#[derive(Clone, Default)]
+pub struct PermissionResponder {
+    /// 回答待ちの権限要求
+    pending: Arc<Mutex<HashMap<String, PermissionRequest>>>,
+    answers: Arc<Mutex<Answers>>,
+}
+
+impl PermissionResponder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 回答待ちの権限要求（古い順）
+    pub fn pending(&self) -> Vec<PermissionRequest> {
+        let mut requests: Vec<PermissionRequest> = self.pending.lock().values().cloned().collect();
+        requests.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        requests
+    }
+
+    fn add_request(&self, request: PermissionRequest) {
+        self.pending.lock().insert(request.request_id.clone(), request);
+    }
+
+    /// 人間の回答を渡す（回答待ちの要求がなければエラー）
+    pub fn submit(&self, request_id: &str, decision: PermissionDecision) -> Result<(), String> {
+        if self.pending.lock().remove(request_id).is_none() {
+            return Err(format!("No pending permission request: {}", request_id));
+        }
+
+        let mut answers = self.answers.lock();
+        match answers.waiters.remove(request_id) {
+            Some(waiter) => {
+                let _ = waiter.send(decision);
+            }
+            None => {
+                answers.received.insert(request_id.to_string(), decision);
+            }
+        }
+        Ok(())
+    }
+
+    /// 回答を待つ（タイムアウトしたら要求を取り下げる）
+    pub async fn wait(&self, request_id: &str, timeout: Duration) -> Result<PermissionDecision, String> {
+        let receiver = {
+            let mut answers = self.answers.lock();
+            if let Some(decision) = answers.received.remove(request_id) {
+                return Ok(decision);
+            }
+            let (sender, receiver) = oneshot::channel();
+            answers.waiters.insert(request_id.to_string(), sender);
+            receiver
+        };
+
+        match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(decision)) => Ok(decision),
+            _ => {
+                self.pending.lock().remove(request_id);
+                self.answers.lock().waiters.remove(request_id);
+                Err(format!("Timeout waiting for human response: {}", request_id))
+            }
+        }
+    }
+}
+
 /// 権限管理
 pub struct PermissionManager {
     /// 現在のポリシー
@@ -73,10 +356,10 @@ pub struct PermissionManager {
     pre_approved: HashSet<String>,
     /// セッション中に許可されたツール
     session_approved: HashSet<String>,
-    /// 待機中の権限要求
-    pending_requests: Arc<Mutex<HashMap<String, PermissionRequest>>>,
-    /// 人間の回答待ち
-    human_responses: Arc<Mutex<HashMap<String, PermissionDecision>>>,
+    /// 権限要求と回答の受け渡し
+    responder: PermissionResponder,
+    /// 保存された権限ルール
+    policies: Arc<Mutex<PolicyStore>>,
     /// アプリハンドル（イベント送信用）
     app_handle: Arc<Mutex<Option<AppHandle>>>,
 }
@@ -88,8 +371,8 @@ impl PermissionManager {
             policy: PermissionPolicy::Standard,
             pre_approved: HashSet::new(),
             session_approved: HashSet::new(),
-            pending_requests: Arc::new(Mutex::new(HashMap::new())),
-            human_responses: Arc::new(Mutex::new(HashMap::new())),
+            responder: PermissionResponder::new(),
+            policies: Arc::new(Mutex::new(PolicyStore::default())),
             app_handle: Arc::new(Mutex::new(None)),
         };
 
@@ -138,8 +421,35 @@ impl PermissionManager {
         *self.app_handle.lock() = Some(handle);
     }
 
+    /// 回答の受け渡しを差し替える（アプリ全体で共有するため）
+    pub fn set_responder(&mut self, responder: PermissionResponder) {
+        self.responder = responder;
+    }
+
+    /// 回答の受け渡し
+    pub fn responder(&self) -> PermissionResponder {
+        self.responder.clone()
+    }
+
+    /// 権限ルールの保存先を差し替える（アプリ全体で共有するため）
+    pub fn set_policy_store(&mut self, policies: Arc<Mutex<PolicyStore>>) {
+        self.policies = policies;
+    }
+
+    /// 人間の回答を反映（「常に許可」なら許可ルールを保存し、以後は確認しない）
+    pub fn record_decision(&mut self, tool_name: &str, tool_input: &Value, decision: &PermissionDecision) {
+        if let PermissionDecision::Allow { always: true } = decision {
+            let rule = PermissionRule::always_allow(tool_name, tool_input);
+            if let Err(e) = self.policies.lock().add(rule) {
+                // 保存できなくてもこのセッション中は確認しない
+                log::warn("PermissionManager", &format!("Failed to save permission rule: {}", e));
+                self.session_approved.insert(tool_name.to_string());
+            }
+        }
+    }
+
     /// 権限要求を処理
-    pub async fn check_permission(
+    pub fn check_permission(
         &mut self,
         tool_name: &str,
         tool_input: &Value,
@@ -150,6 +460,21 @@ impl PermissionManager {
             tool_name, request_id
         ));
 
+        // 0. 保存されたルール（組み込みの判定より優先）
+        let rule = self.policies.lock().evaluate(tool_name, tool_input).map(|r| (r.id.clone(), r.action));
+        if let Some((rule_id, action)) = rule {
+            log::info("PermissionManager", &format!("{} matched rule {} ({:?})", tool_name, rule_id, action));
+            match action {
+                RuleAction::Allow => return PermissionDecision::Allow { always: true },
+                RuleAction::Deny => {
+                    return PermissionDecision::Deny {
+                        reason: format!("Denied by rule {}", rule_id),
+                    };
+                }
+                RuleAction::Ask => return self.require_human_approval(tool_name, tool_input, request_id, vec![]),
+            }
+        }
+
         // 1. ポリシーレベルのチェック
         match self.policy {
             PermissionPolicy::Permissive => {
@@ -302,14 +627,11 @@ impl PermissionManager {
         };
 
         // 待機中の要求に追加
-        {
-            let mut pending = self.pending_requests.lock();
-            pending.insert(request_id.to_string(), request.clone());
-        }
+        self.responder.add_request(request.clone());
 
         // イベントを送信
         if let Some(ref handle) = *self.app_handle.lock() {
-            let _ = handle.emit("permission:required", &request);
+            let _ = handle.emit_recorded("permission:required", &request);
         }
 
         PermissionDecision::RequireHuman {
@@ -326,60 +648,7 @@ impl PermissionManager {
         request_id: &str,
         decision: PermissionDecision,
     ) -> Result<(), String> {
-        // 待機中の要求から削除
-        {
-            let mut pending = self.pending_requests.lock();
-            pending.remove(request_id);
-        }
-
-        // 回答を保存
-        {
-            let mut responses = self.human_responses.lock();
-            responses.insert(request_id.to_string(), decision.clone());
-        }
-
-        // セッション許可に追加（always の場合）
-        if let PermissionDecision::Allow { always: true } = decision {
-            // request_id から tool_name を取得
-            let pending = self.pending_requests.lock();
-            // 既に削除されているので、別の方法で tool_name を取得する必要がある
-            // 現在は簡易実装
-        }
-
-        Ok(())
-    }
-
-    /// 人間の回答を待機
-    pub async fn wait_for_human_response(
-        &self,
-        request_id: &str,
-        timeout_secs: u64,
-    ) -> Result<PermissionDecision, String> {
-        let start = std::time::Instant::now();
-        let timeout = std::time::Duration::from_secs(timeout_secs);
-
-        loop {
-            // 回答をチェック
-            {
-                let responses = self.human_responses.lock();
-                if let Some(decision) = responses.get(request_id) {
-                    let decision = decision.clone();
-                    // 回答を削除
-                    drop(responses);
-                    let mut responses = self.human_responses.lock();
-                    responses.remove(request_id);
-                    return Ok(decision);
-                }
-            }
-
-            // タイムアウトチェック
-            if start.elapsed() >= timeout {
-                return Err(format!("Timeout waiting for human response: {}", request_id));
-            }
-
-            // 短く待機
-            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-        }
+        self.responder.submit(request_id, decision)
     }
 
     /// セッション許可をクリア
@@ -482,14 +751,99 @@ mod tests {
 
         // Permissiveポリシーでは全て許可
         let input = serde_json::json!({"command": "rm -rf /"});
-        let _decision = manager.check_permission(
+        let decision = manager.check_permission(
             "Bash",
             &input,
             "test-1",
         );
 
-        // Note: check_permission is async, so we can't test it directly here
-        // This test is for demonstration purposes
+        assert_eq!(decision, PermissionDecision::Allow { always: false });
+    }
+
+    #[tokio::test]
+    async fn test_human_response_flow() {
+        let mut manager = PermissionManager::new();
+        let input = serde_json::json!({"file_path": "/home/user/a.txt", "content": "x"});
+        let decision = manager.check_permission("Write", &input, "req-1");
+        assert!(matches!(decision, PermissionDecision::RequireHuman { .. }));
+
+        let responder = manager.responder();
+        assert_eq!(responder.pending().len(), 1);
+        let answer = responder.clone();
+        tokio::spawn(async move {
+            answer.submit("req-1", PermissionDecision::Allow { always: true }).unwrap();
+        });
+        let decision = responder.wait("req-1", Duration::from_secs(5)).await.unwrap();
+        assert_eq!(decision, PermissionDecision::Allow { always: true });
+        assert!(responder.pending().is_empty());
+        assert!(responder.submit("req-1", PermissionDecision::Allow { always: false }).is_err());
+
+        // 「常に許可」したツールは以後確認しない
+        manager.record_decision("Write", &input, &decision);
+        assert_eq!(manager.check_permission("Write", &input, "req-2"), PermissionDecision::Allow { always: true });
+    }
+
+    #[tokio::test]
+    async fn test_wait_times_out() {
+        let mut manager = PermissionManager::new();
+        manager.set_policy(PermissionPolicy::Strict);
+        manager.check_permission("Read", &serde_json::json!({}), "req-1");
+
+        let responder = manager.responder();
+        assert!(responder.wait("req-1", Duration::from_millis(10)).await.is_err());
+        assert!(responder.pending().is_empty());
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        let mut store = PolicyStore::default();
+        store.add(PermissionRule::new("Write", RuleAction::Allow).with_path("/work/**")).unwrap();
+        store.add(PermissionRule::new("Write", RuleAction::Deny).with_path("/work/*.env")).unwrap();
+        store.add(PermissionRule::new("Bash", RuleAction::Ask).with_command("git push*")).unwrap();
+
+        let write = |path: &str| serde_json::json!({ "file_path": path });
+        let action = |tool: &str, input: &Value| store.evaluate(tool, input).map(|r| r.action);
+        assert_eq!(action("Write", &write("/work/src/main.rs")), Some(RuleAction::Allow));
+        // 拒否ルールが優先
+        assert_eq!(action("Write", &write("/work/.env")), Some(RuleAction::Deny));
+        assert_eq!(action("Write", &write("/other/a.txt")), None);
+        assert_eq!(action("Bash", &serde_json::json!({ "command": "git push origin main" })), Some(RuleAction::Ask));
+        assert_eq!(action("Bash", &serde_json::json!({ "command": "git status" })), None);
+
+        // 条件が同じなら置き換え
+        store.add(PermissionRule::new("Bash", RuleAction::Deny).with_command("git push*")).unwrap();
+        assert_eq!(store.rules().len(), 3);
+        assert!(PermissionRule::new(" ", RuleAction::Allow).validate().is_err());
+
+        let mut manager = PermissionManager::new();
+        manager.set_policy_store(Arc::new(Mutex::new(store)));
+        let decision = manager.check_permission("Bash", &serde_json::json!({ "command": "git push" }), "req-1");
+        assert!(matches!(decision, PermissionDecision::Deny { .. }));
+    }
+
+    #[test]
+    fn test_policy_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("re-voice-policies-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(POLICY_FILE);
+
+        let policies = Arc::new(Mutex::new(PolicyStore::load(&path).unwrap()));
+        let mut manager = PermissionManager::new();
+        manager.set_policy_store(policies.clone());
+        let input = serde_json::json!({ "command": "npm run build" });
+        manager.record_decision("Bash", &input, &PermissionDecision::Allow { always: true });
+        manager.record_decision("Edit", &serde_json::json!({}), &PermissionDecision::Allow { always: false });
+
+        let reloaded = PolicyStore::load(&path).unwrap();
+        assert_eq!(reloaded.rules().len(), 1);
+        assert_eq!(reloaded.rules()[0].command.as_deref(), Some("npm run build"));
+        assert!(reloaded.evaluate("Bash", &input).is_some());
+        assert!(reloaded.evaluate("Bash", &serde_json::json!({ "command": "npm publish" })).is_none());
+
+        let mut reloaded = reloaded;
+        let id = reloaded.rules()[0].id.clone();
+        assert!(reloaded.remove(&id).unwrap());
+        assert!(PolicyStore::load(&path).unwrap().rules().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
     #[test]
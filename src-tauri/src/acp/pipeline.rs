@@ -13,11 +13,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 use super::message::{ACPMessageV3, AddressType, AgentAddress, MessageType, PipelineStage};
 use super::agent::AgentCard;
+use super::stream_parser::UsageTotals;
 
 // ============================================================================
 // Pipeline State Types
@@ -31,6 +33,8 @@ pub enum PipelineStatus {
     Pending,
     /// Pipeline is currently running
     Running,
+    /// Pipeline is paused between stages (in-flight stages still finish)
+    Paused,
     /// Pipeline completed successfully
     Completed,
     /// Pipeline failed at some stage
@@ -39,6 +43,13 @@ pub enum PipelineStatus {
     Cancelled,
 }
 
+impl PipelineStatus {
+    /// Whether the execution has not finished yet (running or paused)
+    pub fn is_active(&self) -> bool {
+        matches!(self, PipelineStatus::Running | PipelineStatus::Paused)
+    }
+}
+
 /// Individual stage status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -115,6 +126,22 @@ impl PipelineDefinition {
     pub fn stage_count(&self) -> usize {
         self.stages.len()
     }
+
+    /// Stage indices in execution order; each inner Vec runs concurrently and
+    /// is joined before the next one starts
+    pub fn stage_groups(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for (i, stage) in self.stages.iter().enumerate() {
+            let joins_previous = stage.group.is_some()
+                && i > 0
+                && self.stages[i - 1].group == stage.group;
+            match groups.last_mut() {
+                Some(last) if joins_previous => last.push(i),
+                _ => groups.push(vec![i]),
+            }
+        }
+        groups
+    }
 }
 
 // ============================================================================
@@ -128,6 +155,9 @@ pub struct StageResult {
     pub stage_name: String,
     /// Stage index in pipeline
     pub stage_index: usize,
+    /// Parallel group the stage belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
     /// Status
     pub status: StageStatus,
     /// Output data
@@ -148,6 +178,7 @@ impl StageResult {
         Self {
             stage_name,
             stage_index,
+            group: None,
             status: StageStatus::Pending,
             output: None,
             error: None,
@@ -160,6 +191,7 @@ impl StageResult {
         Self {
             stage_name,
             stage_index,
+            group: None,
             status: StageStatus::Running,
             output: None,
             error: None,
@@ -219,6 +251,9 @@ pub struct PipelineExecution {
     /// Error message if pipeline failed
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Tokens and cost of the Claude Code requests made by this execution
+    #[serde(default)]
+    pub usage: UsageTotals,
 }
 
 impl PipelineExecution {
@@ -228,7 +263,10 @@ impl PipelineExecution {
             .stages
             .iter()
             .enumerate()
-            .map(|(i, stage)| StageResult::pending(stage.name.clone(), i))
+            .map(|(i, stage)| StageResult {
+                group: stage.group.clone(),
+                ..StageResult::pending(stage.name.clone(), i)
+            })
             .collect();
 
         Self {
@@ -241,6 +279,7 @@ impl PipelineExecution {
             start_time: Utc::now(),
             end_time: None,
             error: None,
+            usage: UsageTotals::default(),
         }
     }
 
@@ -281,6 +320,50 @@ impl PipelineExecution {
         }
     }
 
+    /// Mark stages as running (a sequential stage or all branches of a group)
+    pub fn start_stages(&mut self, indices: &[usize]) {
+        let now = Utc::now();
+        for &i in indices {
+            if let Some(result) = self.stage_results.get_mut(i) {
+                result.status = StageStatus::Running;
+                result.start_time = now;
+                result.end_time = None;
+            }
+        }
+    }
+
+    /// Complete a stage by index (branches of a group may finish in any order)
+    ///
+    /// `current_stage` advances past every completed stage; the pipeline is
+    /// completed once all stages are.
+    pub fn complete_stage_at(&mut self, index: usize, output: serde_json::Value) {
+        let Some(result) = self.stage_results.get_mut(index) else {
+            return;
+        };
+        *result = result.clone().complete(output.clone());
+        self.context.insert(result.stage_name.clone(), output);
+
+        while self.current_stage < self.stage_results.len()
+            && self.stage_results[self.current_stage].status == StageStatus::Completed
+        {
+            self.current_stage += 1;
+        }
+        if self.current_stage == self.stage_results.len() {
+            self.status = PipelineStatus::Completed;
+            self.end_time = Some(Utc::now());
+        }
+    }
+
+    /// Fail a stage by index (the first failure's error is kept)
+    pub fn fail_stage_at(&mut self, index: usize, error: String) {
+        if let Some(result) = self.stage_results.get_mut(index) {
+            *result = result.clone().fail(error.clone());
+        }
+        self.status = PipelineStatus::Failed;
+        self.error.get_or_insert(error);
+        self.end_time = Some(Utc::now());
+    }
+
     /// Fail current stage
     pub fn fail_stage(&mut self, error: String) {
         if self.current_stage < self.stage_results.len() {
@@ -352,6 +435,12 @@ pub enum PipelineError {
     #[error("Pipeline already running: {0}")]
     AlreadyRunning(String),
 
+    #[error("Execution is not running: {0}")]
+    NotRunning(String),
+
+    #[error("Execution is not paused: {0}")]
+    NotPaused(String),
+
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
 }
@@ -437,7 +526,7 @@ impl PipelineExecutor {
         let execution = executions.get_mut(execution_id)
             .ok_or_else(|| PipelineError::ExecutionNotFound(execution_id.to_string()))?;
 
-        if execution.status != PipelineStatus::Running {
+        if !execution.status.is_active() {
             return Err(PipelineError::AlreadyRunning(execution_id.to_string()));
         }
 
@@ -459,6 +548,92 @@ impl PipelineExecutor {
         Ok(execution.clone())
     }
 
+    /// Mark stages of an execution as running
+    pub fn start_stages(&self, execution_id: &str, indices: &[usize]) -> Result<PipelineExecution, PipelineError> {
+        let mut executions = self.executions.lock().unwrap();
+        let execution = executions.get_mut(execution_id)
+            .ok_or_else(|| PipelineError::ExecutionNotFound(execution_id.to_string()))?;
+
+        execution.start_stages(indices);
+        Ok(execution.clone())
+    }
+
+    /// Complete a stage of an execution by index
+    pub fn complete_stage_at(
+        &self,
+        execution_id: &str,
+        index: usize,
+        output: serde_json::Value,
+    ) -> Result<PipelineExecution, PipelineError> {
+        let mut executions = self.executions.lock().unwrap();
+        let execution = executions.get_mut(execution_id)
+            .ok_or_else(|| PipelineError::ExecutionNotFound(execution_id.to_string()))?;
+
+        if !execution.status.is_active() {
+            return Err(PipelineError::AlreadyRunning(execution_id.to_string()));
+        }
+        if index >= execution.stage_results.len() {
+            return Err(PipelineError::InvalidStageIndex(index));
+        }
+
+        execution.complete_stage_at(index, output);
+        Ok(execution.clone())
+    }
+
+    /// Fail a stage of an execution by index
+    pub fn fail_stage_at(
+        &self,
+        execution_id: &str,
+        index: usize,
+        error: String,
+    ) -> Result<PipelineExecution, PipelineError> {
+        let mut executions = self.executions.lock().unwrap();
+        let execution = executions.get_mut(execution_id)
+            .ok_or_else(|| PipelineError::ExecutionNotFound(execution_id.to_string()))?;
+
+        execution.fail_stage_at(index, error);
+        Ok(execution.clone())
+    }
+
+    /// Add the usage of one or more requests to an execution's totals
+    pub fn add_usage(&self, execution_id: &str, usage: &UsageTotals) -> Result<PipelineExecution, PipelineError> {
+        let mut executions = self.executions.lock().unwrap();
+        let execution = executions.get_mut(execution_id)
+            .ok_or_else(|| PipelineError::ExecutionNotFound(execution_id.to_string()))?;
+
+        execution.usage.add(usage);
+        Ok(execution.clone())
+    }
+
+    /// Pause a running execution
+    ///
+    /// Stages already in flight run to completion; the runner waits before
+    /// starting the next stage until the execution is resumed or cancelled.
+    pub fn pause_execution(&self, execution_id: &str) -> Result<PipelineExecution, PipelineError> {
+        let mut executions = self.executions.lock().unwrap();
+        let execution = executions.get_mut(execution_id)
+            .ok_or_else(|| PipelineError::ExecutionNotFound(execution_id.to_string()))?;
+
+        if execution.status != PipelineStatus::Running {
+            return Err(PipelineError::NotRunning(execution_id.to_string()));
+        }
+        execution.status = PipelineStatus::Paused;
+        Ok(execution.clone())
+    }
+
+    /// Resume a paused execution
+    pub fn resume_execution(&self, execution_id: &str) -> Result<PipelineExecution, PipelineError> {
+        let mut executions = self.executions.lock().unwrap();
+        let execution = executions.get_mut(execution_id)
+            .ok_or_else(|| PipelineError::ExecutionNotFound(execution_id.to_string()))?;
+
+        if execution.status != PipelineStatus::Paused {
+            return Err(PipelineError::NotPaused(execution_id.to_string()));
+        }
+        execution.status = PipelineStatus::Running;
+        Ok(execution.clone())
+    }
+
     /// Cancel an execution
     pub fn cancel_execution(&self, execution_id: &str) -> Result<PipelineExecution, PipelineError> {
         let mut executions = self.executions.lock().unwrap();
@@ -469,15 +644,46 @@ impl PipelineExecutor {
         Ok(execution.clone())
     }
 
-    /// Get all active executions
+    /// Get all active (running or paused) executions
     pub fn get_active_executions(&self) -> Vec<PipelineExecution> {
         let executions = self.executions.lock().unwrap();
         executions.values()
-            .filter(|e| e.status == PipelineStatus::Running)
+            .filter(|e| e.status.is_active())
             .cloned()
             .collect()
     }
 
+    /// Finished (completed/failed/cancelled) executions, newest first
+    pub fn finished_executions(&self) -> Vec<PipelineExecution> {
+        let executions = self.executions.lock().unwrap();
+        let mut finished: Vec<PipelineExecution> = executions.values()
+            .filter(|e| !e.status.is_active() && e.status != PipelineStatus::Pending)
+            .cloned()
+            .collect();
+        finished.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+        finished
+    }
+
+    /// Restore a previously persisted execution (history only, not resumed)
+    pub fn restore_execution(&self, execution: PipelineExecution) {
+        let mut executions = self.executions.lock().unwrap();
+        executions.entry(execution.execution_id.clone()).or_insert(execution);
+    }
+
+    /// Attach a value to an execution's context (e.g. where its checkpoint is saved)
+    pub fn set_context_value(
+        &self,
+        execution_id: &str,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<(), PipelineError> {
+        let mut executions = self.executions.lock().unwrap();
+        let execution = executions.get_mut(execution_id)
+            .ok_or_else(|| PipelineError::ExecutionNotFound(execution_id.to_string()))?;
+        execution.context.insert(key.to_string(), value);
+        Ok(())
+    }
+
     /// Clean up completed/failed executions older than specified seconds
     pub fn cleanup_stale(&self, max_age_seconds: i64) -> Vec<String> {
         let mut removed = Vec::new();
@@ -486,7 +692,7 @@ impl PipelineExecutor {
         let now = Utc::now();
         let ids_to_remove: Vec<String> = executions.iter()
             .filter(|(_, e)| {
-                e.status != PipelineStatus::Running && e.status != PipelineStatus::Pending
+                !e.status.is_active() && e.status != PipelineStatus::Pending
             })
             .filter(|(_, e)| {
                 e.end_time.map_or(false, |end| {
@@ -511,6 +717,86 @@ impl Default for PipelineExecutor {
     }
 }
 
+// ============================================================================
+// Pipeline Persistence
+// ============================================================================
+
+/// File name of the pipeline store in the app data dir
+pub const PIPELINES_FILE: &str = "pipelines.json";
+
+/// Maximum number of finished executions kept in the store
+const MAX_HISTORY: usize = 100;
+
+/// Saved pipeline definitions and execution history (JSON file)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PipelineStore {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Saved pipeline definitions
+    #[serde(default)]
+    pub pipelines: Vec<PipelineDefinition>,
+    /// Finished executions (newest first)
+    #[serde(default)]
+    pub history: Vec<PipelineExecution>,
+}
+
+impl PipelineStore {
+    /// Load the store (empty if the file does not exist)
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let mut store: Self = if path.exists() {
+            let content = std::fs::read_to_string(path)?;
+            serde_json::from_str(&content)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        } else {
+            Self::default()
+        };
+        store.path = Some(path.to_path_buf());
+        Ok(store)
+    }
+
+    /// Write the store back to its file
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)
+    }
+
+    /// Add or replace a pipeline definition (matched by ID)
+    pub fn upsert(&mut self, pipeline: PipelineDefinition) {
+        match self.pipelines.iter_mut().find(|p| p.id == pipeline.id) {
+            Some(existing) => *existing = pipeline,
+            None => self.pipelines.push(pipeline),
+        }
+    }
+
+    /// Merge finished executions into the history (newest first, capped)
+    pub fn record_history(&mut self, executions: Vec<PipelineExecution>) {
+        for execution in executions {
+            self.history.retain(|e| e.execution_id != execution.execution_id);
+            self.history.push(execution);
+        }
+        self.history.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+        self.history.truncate(MAX_HISTORY);
+    }
+
+    /// Register everything in the store with an executor; returns the pipeline count
+    pub fn restore_into(&self, executor: &PipelineExecutor) -> usize {
+        for pipeline in &self.pipelines {
+            executor.register(pipeline.clone());
+        }
+        for execution in &self.history {
+            executor.restore_execution(execution.clone());
+        }
+        self.pipelines.len()
+    }
+}
+
 // ============================================================================
 // Message Creation Helpers
 // ============================================================================
@@ -633,4 +919,88 @@ mod tests {
         assert_eq!(execution.status, PipelineStatus::Cancelled);
         assert_eq!(execution.stage_results[1].status, StageStatus::Skipped);
     }
+
+    #[test]
+    fn test_pipeline_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("revoice-pipelines-{}", Uuid::new_v4()));
+        let path = dir.join(PIPELINES_FILE);
+
+        let executor = PipelineExecutor::new();
+        let pipeline = PipelineDefinition::new("saved")
+            .add_stage(PipelineStage::new("stage1", AgentAddress::new("agent-a")));
+        let pipeline_id = executor.register(pipeline.clone());
+        let execution = executor.start_execution(&pipeline_id).unwrap();
+        executor.complete_stage(&execution.execution_id, serde_json::json!("done")).unwrap();
+
+        let mut store = PipelineStore::load(&path).unwrap();
+        store.upsert(pipeline.clone());
+        store.upsert(pipeline);
+        store.record_history(executor.finished_executions());
+        store.save().unwrap();
+
+        let restored = PipelineExecutor::new();
+        assert_eq!(PipelineStore::load(&path).unwrap().restore_into(&restored), 1);
+        assert_eq!(restored.get_pipeline(&pipeline_id).unwrap().name, "saved");
+        let history = restored.get_execution(&execution.execution_id).unwrap();
+        assert_eq!(history.status, PipelineStatus::Completed);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_parallel_stage_groups() {
+        let stage = |name: &str| PipelineStage::new(name, AgentAddress::new("agent"));
+        let pipeline = PipelineDefinition::new("fan-out").with_stages(vec![
+            stage("split"),
+            stage("chunk-1").with_group("chunks"),
+            stage("chunk-2").with_group("chunks"),
+            stage("chunk-3").with_group("chunks"),
+            stage("merge"),
+        ]);
+        assert_eq!(pipeline.stage_groups(), vec![vec![0], vec![1, 2, 3], vec![4]]);
+
+        let executor = PipelineExecutor::new();
+        let pipeline_id = executor.register(pipeline);
+        let execution = executor.start_execution(&pipeline_id).unwrap();
+        let id = execution.execution_id;
+
+        executor.complete_stage_at(&id, 0, serde_json::json!("split")).unwrap();
+        executor.start_stages(&id, &[1, 2, 3]).unwrap();
+        // Branches finish out of order; the group joins once all are done
+        executor.complete_stage_at(&id, 3, serde_json::json!("c3")).unwrap();
+        let execution = executor.complete_stage_at(&id, 1, serde_json::json!("c1")).unwrap();
+        assert_eq!(execution.current_stage, 2);
+        assert_eq!(execution.stage_results[2].status, StageStatus::Running);
+        assert_eq!(execution.stage_results[2].group.as_deref(), Some("chunks"));
+
+        executor.complete_stage_at(&id, 2, serde_json::json!("c2")).unwrap();
+        executor.start_stages(&id, &[4]).unwrap();
+        let execution = executor.complete_stage_at(&id, 4, serde_json::json!("merged")).unwrap();
+        assert_eq!(execution.status, PipelineStatus::Completed);
+        assert_eq!(execution.context.len(), 5);
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let stage = |name: &str| PipelineStage::new(name, AgentAddress::new("agent"));
+        let executor = PipelineExecutor::new();
+        let pipeline_id = executor.register(
+            PipelineDefinition::new("Pausable").with_stages(vec![stage("a"), stage("b")]),
+        );
+        let id = executor.start_execution(&pipeline_id).unwrap().execution_id;
+
+        assert!(matches!(executor.resume_execution(&id), Err(PipelineError::NotPaused(_))));
+        let execution = executor.pause_execution(&id).unwrap();
+        assert_eq!(execution.status, PipelineStatus::Paused);
+        assert!(matches!(executor.pause_execution(&id), Err(PipelineError::NotRunning(_))));
+        assert_eq!(executor.get_active_executions().len(), 1);
+
+        // A stage in flight when pausing still completes
+        let execution = executor.complete_stage_at(&id, 0, serde_json::json!("a")).unwrap();
+        assert_eq!(execution.status, PipelineStatus::Paused);
+
+        assert_eq!(executor.resume_execution(&id).unwrap().status, PipelineStatus::Running);
+        executor.cancel_execution(&id).unwrap();
+        assert!(matches!(executor.pause_execution(&id), Err(PipelineError::NotRunning(_))));
+    }
 }
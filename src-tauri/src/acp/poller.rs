@@ -10,10 +10,12 @@ use std::time::Duration;
 
 use parking_lot::Mutex;
 use serde::Serialize;
-use tauri::{AppHandle, Emitter, Runtime};
+use tauri::{AppHandle, Runtime};
 
 use super::parser::OutputParser;
+use super::registry::{AgentRegistry, AgentStatus as RegistryStatus};
 use super::tmux::{AgentStatus, PaneInfo, TmuxOrchestrator};
+use crate::events::RecordedEmit;
 use crate::log;
 
 /// ポーリング設定
@@ -28,7 +30,7 @@ pub struct PollerConfig {
 impl Default for PollerConfig {
     fn default() -> Self {
         Self {
-            interval_ms: 200,  // 200ms間隔でポーリング（Processing状態の検出を改善）
+            interval_ms: crate::settings::current().poller_interval_ms,  // 既定200ms（Processing状態の検出を改善）
             min_output_change: 10,
         }
     }
@@ -46,8 +48,11 @@ pub struct StatusChangedPayload {
 #[derive(Debug, Clone, Serialize)]
 pub struct OutputReadyPayload {
     pub agent_id: String,
+    /// 前回の出力準備完了イベント以降に増えた出力
     pub content: String,
     pub content_length: usize,
+    /// ペイン出力ログのカーソル（`tmux_capture_since` で以降の差分を取得できる）
+    pub seq: u64,
 }
 
 /// 質問イベントのペイロード
@@ -63,8 +68,10 @@ pub struct QuestionPayload {
 #[derive(Debug, Clone)]
 struct AgentSnapshot {
     status: AgentStatus,
-    last_output: String,
+    /// 前回の出力準備完了イベント以降に増えた出力の長さ
     output_length: usize,
+    /// 前回の出力準備完了イベントで送った出力ログのカーソル
+    output_seq: u64,
 }
 
 /// ステータスポーラー
@@ -77,6 +84,8 @@ pub struct StatusPoller {
     handle: Option<JoinHandle<()>>,
     /// エージェントの状態スナップショット
     snapshots: Arc<Mutex<HashMap<String, AgentSnapshot>>>,
+    /// 検出した状態を反映するレジストリ（負荷に応じた振り分けに使う）
+    registry: Option<AgentRegistry>,
 }
 
 impl StatusPoller {
@@ -87,9 +96,16 @@ impl StatusPoller {
             running: Arc::new(AtomicBool::new(false)),
             handle: None,
             snapshots: Arc::new(Mutex::new(HashMap::new())),
+            registry: None,
         }
     }
 
+    /// 検出した状態をレジストリのエージェントにも反映する
+    pub fn with_registry(mut self, registry: AgentRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
     /// ポーリングを開始
     pub fn start<R: Runtime>(
         &mut self,
@@ -104,6 +120,7 @@ impl StatusPoller {
         let running = self.running.clone();
         let config = self.config.clone();
         let snapshots = self.snapshots.clone();
+        let registry = self.registry.clone();
         let parser = OutputParser::new();
 
         let handle = thread::spawn(move || {
@@ -122,16 +139,21 @@ impl StatusPoller {
 
                 // 各エージェントの状態をチェック
                 for agent in agents {
+                    // キャプチャは差分として出力ログにも記録する（スナップショットに全文は保持しない）
+                    let output_seq = snapshots.lock().get(&agent.agent_id).map(|snap| snap.output_seq).unwrap_or(0);
                     let pane_content = {
                         let orch = orchestrator.lock();
                         if let Some(ref o) = *orch {
-                            o.capture_pane_plain(&agent.pane_id).ok()
+                            o.capture_pane_incremental(&agent.pane_id).ok().map(|(content, seq)| {
+                                let output = o.output_since(&agent.pane_id, output_seq).text();
+                                (content, seq, output)
+                            })
                         } else {
                             None
                         }
                     };
 
-                    if let Some(content) = pane_content {
+                    if let Some((content, seq, output)) = pane_content {
                         // デバッグ: コンテンツ全体の行数と最後の10行を表示
                         let total_lines = content.lines().count();
                         let last_lines: Vec<&str> = content.lines().rev().take(10).collect();
@@ -141,7 +163,7 @@ impl StatusPoller {
                         }
 
                         // パーサーで状態を検出
-                        let mut detected_status = parser.parse(&content);
+                        let mut detected_status = parser.parse_for(&agent.agent_type, &content);
 
                         // 選択メニューが表示されている場合はWaitingForInputとして扱う
                         if content.contains("Enter to select") || content.contains("↑/↓ to navigate") {
@@ -159,6 +181,10 @@ impl StatusPoller {
 
                         log::debug("StatusPoller", &format!("Agent {} detected_status: {:?}", agent.agent_id, detected_status));
 
+                        if let (Some(registry), Some(status)) = (&registry, RegistryStatus::from_pane(&detected_status)) {
+                            let _ = registry.set_status(&agent.agent_id, status);
+                        }
+
                         // 前回の状態と比較（更新前の状態を保存）
                         let (status_changed, old_status) = {
                             let mut snaps = snapshots.lock();
@@ -174,18 +200,22 @@ impl StatusPoller {
                                 Some(prev) => {
                                     // 状態が変化した、または出力が大きく変化した
                                     prev.status != detected_status
-                                        || content.len().abs_diff(prev.output_length) > config.min_output_change
+                                        || output.len().abs_diff(prev.output_length) > config.min_output_change
                                 }
                                 None => true,
                             };
 
+                            // 出力準備完了イベントを送るなら、次回はここからの差分を送る
+                            let output_ready = changed
+                                && matches!(detected_status, AgentStatus::Idle | AgentStatus::WaitingForInput { .. });
+
                             // スナップショットを更新
                             snaps.insert(
                                 agent.agent_id.clone(),
                                 AgentSnapshot {
                                     status: detected_status.clone(),
-                                    last_output: content.clone(),
-                                    output_length: content.len(),
+                                    output_length: if output_ready { 0 } else { output.len() },
+                                    output_seq: if output_ready { seq } else { output_seq },
                                 },
                             );
 
@@ -223,7 +253,7 @@ impl StatusPoller {
                                 new_status: new_status_str.to_string(),
                             };
 
-                            if let Err(e) = app_handle.emit("tmux:status_changed", &payload) {
+                            if let Err(e) = app_handle.emit_recorded("tmux:status_changed", &payload) {
                                 log::error("StatusPoller", &format!("Failed to emit status_changed: {:?}", e));
                             }
 
@@ -231,11 +261,12 @@ impl StatusPoller {
                             if matches!(detected_status, AgentStatus::Idle | AgentStatus::WaitingForInput { .. }) {
                                 let output_payload = OutputReadyPayload {
                                     agent_id: agent.agent_id.clone(),
-                                    content: parser.extract_meaningful_content(&content),
-                                    content_length: content.len(),
+                                    content_length: output.len(),
+                                    content: output,
+                                    seq,
                                 };
 
-                                if let Err(e) = app_handle.emit("tmux:output_ready", &output_payload) {
+                                if let Err(e) = app_handle.emit_recorded("tmux:output_ready", &output_payload) {
                                     log::error("StatusPoller", &format!("Failed to emit output_ready: {:?}", e));
                                 }
                             }
@@ -252,7 +283,7 @@ impl StatusPoller {
                                         context: parser.extract_meaningful_content(&content),
                                     };
 
-                                    if let Err(e) = app_handle.emit("tmux:question", &question_payload) {
+                                    if let Err(e) = app_handle.emit_recorded("tmux:question", &question_payload) {
                                         log::error("StatusPoller", &format!("Failed to emit question: {:?}", e));
                                     }
 
@@ -0,0 +1,158 @@
+//! 逆翻訳による品質チェック
+//!
+//! 訳文を原文の言語へ逆翻訳し、原文との類似度をセグメントごとに算出する。
+//! しきい値を下回ったセグメントは低信頼としてフラグを付け、レビューで確認できるようにする。
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+/// 品質チェック設定（パイプラインごと）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityConfig {
+    /// この類似度未満のセグメントにフラグを付ける（0.0〜1.0）
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+    /// フラグ付きセグメントの割合がこれを超えたらステージを失敗させる（未指定なら失敗させない）
+    #[serde(default)]
+    pub max_flagged_ratio: Option<f64>,
+    /// 逆翻訳先の言語（未指定なら字幕の言語、不明なら英語）
+    #[serde(default)]
+    pub source_lang: Option<String>,
+}
+
+fn default_threshold() -> f64 {
+    0.3
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        Self {
+            threshold: default_threshold(),
+            max_flagged_ratio: None,
+            source_lang: None,
+        }
+    }
+}
+
+/// セグメントごとの品質
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentQuality {
+    pub index: usize,
+    pub source: String,
+    pub translation: String,
+    pub back_translation: String,
+    /// 原文と逆翻訳の類似度（0.0〜1.0）
+    pub similarity: f64,
+    pub flagged: bool,
+}
+
+/// 品質チェック結果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QualityReport {
+    pub threshold: f64,
+    /// 平均類似度
+    pub average: f64,
+    /// フラグ付きセグメントの番号
+    pub flagged: Vec<usize>,
+    pub segments: Vec<SegmentQuality>,
+}
+
+impl QualityReport {
+    /// 原文・訳文・逆翻訳から結果を作成
+    pub fn build(
+        sources: &[(usize, String)],
+        translations: &BTreeMap<usize, String>,
+        back_translations: &BTreeMap<usize, String>,
+        threshold: f64,
+    ) -> Self {
+        let segments: Vec<SegmentQuality> = sources
+            .iter()
+            .filter_map(|(index, source)| {
+                let translation = translations.get(index)?;
+                let back = back_translations.get(index).cloned().unwrap_or_default();
+                let score = similarity(source, &back);
+                Some(SegmentQuality {
+                    index: *index,
+                    source: source.clone(),
+                    translation: translation.clone(),
+                    back_translation: back,
+                    similarity: score,
+                    flagged: score < threshold,
+                })
+            })
+            .collect();
+
+        let average = if segments.is_empty() {
+            1.0
+        } else {
+            segments.iter().map(|s| s.similarity).sum::<f64>() / segments.len() as f64
+        };
+
+        Self {
+            threshold,
+            average,
+            flagged: segments.iter().filter(|s| s.flagged).map(|s| s.index).collect(),
+            segments,
+        }
+    }
+
+    /// フラグ付きセグメントの割合
+    pub fn flagged_ratio(&self) -> f64 {
+        if self.segments.is_empty() {
+            0.0
+        } else {
+            self.flagged.len() as f64 / self.segments.len() as f64
+        }
+    }
+
+    /// セグメント番号から品質を取得
+    pub fn get(&self, index: usize) -> Option<&SegmentQuality> {
+        self.segments.iter().find(|s| s.index == index)
+    }
+}
+
+/// 2つのテキストの類似度（単語集合のDice係数、大文字小文字・記号は無視）
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a = tokens(a);
+    let b = tokens(b);
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let common = a.intersection(&b).count();
+    2.0 * common as f64 / (a.len() + b.len()) as f64
+}
+
+fn tokens(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Hello, world!", "hello world"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("cat", "dog"), 0.0);
+        assert!((similarity("the quick fox", "the slow fox") - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_report_flags_low_similarity() {
+        let sources = vec![(0, "Good morning".to_string()), (1, "Turn left here".to_string())];
+        let translations: BTreeMap<usize, String> =
+            [(0, "おはよう".to_string()), (1, "ここで右へ".to_string())].into();
+        let back: BTreeMap<usize, String> =
+            [(0, "Good morning".to_string()), (1, "Go right".to_string())].into();
+
+        let report = QualityReport::build(&sources, &translations, &back, 0.3);
+        assert_eq!(report.flagged, vec![1]);
+        assert_eq!(report.flagged_ratio(), 0.5);
+        assert_eq!(report.get(0).unwrap().similarity, 1.0);
+    }
+}
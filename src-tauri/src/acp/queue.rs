@@ -0,0 +1,162 @@
+//! Outbound message queue
+//!
+//! Messages waiting to be delivered to an agent are kept per agent and handed
+//! out highest priority first (FIFO within the same priority). Messages whose
+//! `ttl` has run out are dropped instead of delivered.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::message::{ACPEnvelope, Priority};
+
+/// Message waiting in the queue
+#[derive(Debug, Clone)]
+struct QueuedMessage {
+    envelope: ACPEnvelope,
+    priority: Priority,
+    expires_at: Option<DateTime<Utc>>,
+    seq: u64,
+}
+
+/// Result of taking the next message for an agent
+#[derive(Debug, Default)]
+pub struct Dequeued {
+    /// Next message to deliver
+    pub next: Option<ACPEnvelope>,
+    /// Messages dropped because their TTL expired
+    pub expired: Vec<ACPEnvelope>,
+}
+
+/// Per-agent outbound queue ordered by priority
+#[derive(Debug, Default)]
+pub struct OutboundQueue {
+    queues: HashMap<String, Vec<QueuedMessage>>,
+    next_seq: u64,
+}
+
+impl OutboundQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a message for an agent
+    pub fn push(&mut self, agent_id: &str, envelope: ACPEnvelope) {
+        let priority = envelope.priority();
+        // A TTL too large to represent never expires
+        let expires_at = envelope
+            .ttl()
+            .and_then(|ttl| Duration::try_seconds(i64::try_from(ttl).ok()?))
+            .and_then(|ttl| envelope.message.timestamp.checked_add_signed(ttl));
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.queues.entry(agent_id.to_string()).or_default().push(QueuedMessage {
+            envelope,
+            priority,
+            expires_at,
+            seq,
+        });
+    }
+
+    /// Take the highest-priority message for an agent, dropping expired ones
+    pub fn pop(&mut self, agent_id: &str, now: DateTime<Utc>) -> Dequeued {
+        let Some(queue) = self.queues.get_mut(agent_id) else {
+            return Dequeued::default();
+        };
+        let expired = drain_expired(queue, now);
+        let next = queue
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.priority.cmp(&b.priority).then(b.seq.cmp(&a.seq)))
+            .map(|(index, _)| index)
+            .map(|index| queue.remove(index).envelope);
+        if queue.is_empty() {
+            self.queues.remove(agent_id);
+        }
+        Dequeued { next, expired }
+    }
+
+    /// Drop expired messages of every agent; returns them with their agent IDs
+    pub fn purge_expired(&mut self, now: DateTime<Utc>) -> Vec<(String, ACPEnvelope)> {
+        let mut expired = Vec::new();
+        for (agent_id, queue) in self.queues.iter_mut() {
+            expired.extend(drain_expired(queue, now).into_iter().map(|e| (agent_id.clone(), e)));
+        }
+        self.queues.retain(|_, queue| !queue.is_empty());
+        expired
+    }
+
+    /// Number of queued messages per agent
+    pub fn depths(&self) -> HashMap<String, usize> {
+        self.queues.iter().map(|(id, queue)| (id.clone(), queue.len())).collect()
+    }
+
+    /// Total number of queued messages
+    pub fn len(&self) -> usize {
+        self.queues.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+
+    /// Agents with queued messages
+    pub fn agents(&self) -> Vec<String> {
+        self.queues.keys().cloned().collect()
+    }
+}
+
+fn drain_expired(queue: &mut Vec<QueuedMessage>, now: DateTime<Utc>) -> Vec<ACPEnvelope> {
+    let (expired, alive): (Vec<_>, Vec<_>) = queue
+        .drain(..)
+        .partition(|m| m.expires_at.is_some_and(|at| at <= now));
+    *queue = alive;
+    expired.into_iter().map(|m| m.envelope).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::message::ACPMessageV3;
+
+    fn envelope(content: &str, priority: Priority) -> ACPEnvelope {
+        ACPEnvelope::new(ACPMessageV3::prompt("app", "agent", content).with_priority(priority))
+    }
+
+    fn contents(queue: &mut OutboundQueue, now: DateTime<Utc>) -> Vec<String> {
+        std::iter::from_fn(|| queue.pop("agent", now).next)
+            .map(|e| e.message.payload.content)
+            .collect()
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let mut queue = OutboundQueue::new();
+        queue.push("agent", envelope("normal-1", Priority::Normal));
+        queue.push("agent", envelope("low", Priority::Low));
+        queue.push("agent", envelope("high", Priority::High));
+        queue.push("agent", envelope("normal-2", Priority::Normal));
+        queue.push("agent", envelope("urgent", Priority::Urgent));
+        assert_eq!(queue.depths()["agent"], 5);
+
+        assert_eq!(contents(&mut queue, Utc::now()), ["urgent", "high", "normal-1", "normal-2", "low"]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let mut queue = OutboundQueue::new();
+        let short = ACPEnvelope::new(ACPMessageV3::prompt("app", "agent", "short").with_ttl(5));
+        queue.push("agent", short);
+        queue.push("agent", envelope("keep", Priority::Normal));
+
+        let later = Utc::now() + Duration::seconds(10);
+        let dequeued = queue.pop("agent", later);
+        assert_eq!(dequeued.next.unwrap().message.payload.content, "keep");
+        assert_eq!(dequeued.expired.len(), 1);
+
+        queue.push("other", ACPEnvelope::new(ACPMessageV3::prompt("app", "other", "x").with_ttl(1)));
+        assert_eq!(queue.purge_expired(later).len(), 1);
+        assert_eq!(queue.len(), 0);
+    }
+}
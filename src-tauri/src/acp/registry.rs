@@ -1,13 +1,33 @@
 //! Agent Registry - manages registered agents
-
-use std::collections::HashMap;
+//!
+//! Cards can also be declared as JSON files in an `agents/` directory;
+//! `CardWatcher` keeps the registry in sync with that directory. Cards
+//! registered at runtime are kept in a `RegistryStore` so they survive restarts.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
 
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
-use super::agent::{AgentCard, DiscoveryQuery};
+use super::agent::{AgentCard, DiscoveryQuery, A2A_PROTOCOL_VERSION};
+use super::message::CapabilityFilter;
+use super::state_machine::AgentState;
+use super::tmux::AgentStatus as PaneStatus;
+use super::discovery::is_compatible_version;
+use super::transport::TransportHandle;
+use crate::log;
+
+/// Card directory name under the app data directory
+pub const AGENTS_DIR: &str = "agents";
+
+/// File of cards registered at runtime (directly under the data directory)
+pub const REGISTRY_FILE: &str = "acp_registry.json";
 
 /// Agent status in the registry
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -22,6 +42,28 @@ pub enum AgentStatus {
     Error,
 }
 
+impl AgentStatus {
+    /// Status of a tmux pane as detected by the status poller (None if unknown)
+    pub fn from_pane(status: &PaneStatus) -> Option<Self> {
+        match status {
+            PaneStatus::Idle => Some(Self::Online),
+            PaneStatus::Initializing | PaneStatus::Processing | PaneStatus::WaitingForInput { .. } => Some(Self::Busy),
+            PaneStatus::Error { .. } => Some(Self::Error),
+            PaneStatus::Unknown => None,
+        }
+    }
+
+    /// Status of a CLI executor from its state machine
+    pub fn from_executor(state: &AgentState) -> Self {
+        match state {
+            AgentState::Idle | AgentState::Completed { .. } => Self::Online,
+            AgentState::Error { recoverable: false, .. } => Self::Error,
+            AgentState::Error { .. } => Self::Online,
+            _ => Self::Busy,
+        }
+    }
+}
+
 /// Registered agent information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegisteredAgent {
@@ -72,8 +114,13 @@ impl RegisteredAgent {
 }
 
 /// Agent Registry
+///
+/// Clones share the same underlying agent map.
+#[derive(Clone)]
 pub struct AgentRegistry {
     agents: Arc<RwLock<HashMap<String, RegisteredAgent>>>,
+    /// Transports attached to agents
+    transports: Arc<RwLock<HashMap<String, Arc<dyn TransportHandle>>>>,
     /// Heartbeat timeout in seconds
     heartbeat_timeout: i64,
 }
@@ -83,6 +130,7 @@ impl AgentRegistry {
     pub fn new() -> Self {
         Self {
             agents: Arc::new(RwLock::new(HashMap::new())),
+            transports: Arc::new(RwLock::new(HashMap::new())),
             heartbeat_timeout: 3600, // Default: 1 hour (no automatic heartbeat yet)
         }
     }
@@ -91,6 +139,7 @@ impl AgentRegistry {
     pub fn with_heartbeat_timeout(timeout_seconds: i64) -> Self {
         Self {
             agents: Arc::new(RwLock::new(HashMap::new())),
+            transports: Arc::new(RwLock::new(HashMap::new())),
             heartbeat_timeout: timeout_seconds,
         }
     }
@@ -108,6 +157,33 @@ impl AgentRegistry {
         Ok(())
     }
 
+    /// Register a new agent or replace the card of an existing one
+    ///
+    /// Returns true if the agent was newly registered.
+    pub fn upsert(&self, card: AgentCard) -> bool {
+        let id = card.id.clone().unwrap_or_else(|| card.name.clone());
+        let mut agents = self.agents.write();
+
+        if let Some(agent) = agents.get_mut(&id) {
+            agent.card = card;
+            agent.heartbeat();
+            false
+        } else {
+            agents.insert(id, RegisteredAgent::new(card));
+            true
+        }
+    }
+
+    /// Replace the card of a registered agent (status and timestamps are kept)
+    pub fn update(&self, card: AgentCard) -> Result<(), String> {
+        let id = card.id.clone().unwrap_or_else(|| card.name.clone());
+        let mut agents = self.agents.write();
+
+        let agent = agents.get_mut(&id).ok_or_else(|| format!("Agent {} not found", id))?;
+        agent.card = card;
+        Ok(())
+    }
+
     /// Unregister an agent
     pub fn unregister(&self, agent_id: &str) -> Result<(), String> {
         let mut agents = self.agents.write();
@@ -115,10 +191,30 @@ impl AgentRegistry {
         if agents.remove(agent_id).is_none() {
             return Err(format!("Agent {} not found", agent_id));
         }
+        self.transports.write().remove(agent_id);
 
         Ok(())
     }
 
+    /// Attach a transport to a registered agent (replaces any previous one)
+    pub fn attach_transport(&self, agent_id: &str, transport: Arc<dyn TransportHandle>) -> Result<(), String> {
+        if !self.agents.read().contains_key(agent_id) {
+            return Err(format!("Agent {} not found", agent_id));
+        }
+        self.transports.write().insert(agent_id.to_string(), transport);
+        Ok(())
+    }
+
+    /// Detach the transport of an agent
+    pub fn detach_transport(&self, agent_id: &str) -> Option<Arc<dyn TransportHandle>> {
+        self.transports.write().remove(agent_id)
+    }
+
+    /// Get the transport of an agent
+    pub fn transport(&self, agent_id: &str) -> Option<Arc<dyn TransportHandle>> {
+        self.transports.read().get(agent_id).cloned()
+    }
+
     /// Update agent heartbeat
     pub fn heartbeat(&self, agent_id: &str) -> Result<(), String> {
         let mut agents = self.agents.write();
@@ -170,6 +266,18 @@ impl AgentRegistry {
             .collect()
     }
 
+    /// Available agents (online or busy, not stale) whose card matches a capability filter
+    pub fn matching(&self, filter: &CapabilityFilter) -> Vec<RegisteredAgent> {
+        let agents = self.agents.read();
+
+        agents
+            .values()
+            .filter(|agent| agent.is_available() && !agent.is_stale(self.heartbeat_timeout))
+            .filter(|agent| agent.card.matches_filter(filter))
+            .cloned()
+            .collect()
+    }
+
     /// List all registered agents
     pub fn list_all(&self) -> Vec<RegisteredAgent> {
         let agents = self.agents.read();
@@ -223,9 +331,285 @@ impl Default for AgentRegistry {
     }
 }
 
+/// Result of syncing the registry with a card directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CardSyncReport {
+    pub registered: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    /// (file path, error message)
+    pub errors: Vec<(String, String)>,
+}
+
+impl CardSyncReport {
+    pub fn is_empty(&self) -> bool {
+        self.registered.is_empty() && self.updated.is_empty() && self.removed.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// Load an agent card from a JSON file
+pub fn load_card(path: &Path) -> Result<AgentCard, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let card: AgentCard = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    if card.name.trim().is_empty() {
+        return Err("Agent card has no name".to_string());
+    }
+    Ok(card)
+}
+
+/// Check a card supplied as JSON before registering it
+///
+/// The protocol version must match ours (major.minor) and every skill needs
+/// a unique, non-empty `id` and a `name`.
+pub fn validate_card(card: &AgentCard) -> Result<(), String> {
+    if !is_compatible_version(&card.protocol_version) {
+        return Err(format!(
+            "Unsupported protocol version {} (expected {})",
+            card.protocol_version, A2A_PROTOCOL_VERSION
+        ));
+    }
+    if card.name.trim().is_empty() {
+        return Err("Agent card has no name".to_string());
+    }
+
+    let skills = card.skills.as_deref().unwrap_or_default();
+    if skills.is_empty() {
+        return Err("Agent card has no skills".to_string());
+    }
+    let mut ids = HashSet::new();
+    for skill in skills {
+        if skill.id.trim().is_empty() || skill.name.trim().is_empty() {
+            return Err("Every skill needs an id and a name".to_string());
+        }
+        if !ids.insert(skill.id.as_str()) {
+            return Err(format!("Duplicate skill id: {}", skill.id));
+        }
+    }
+    Ok(())
+}
+
+/// Cards registered at runtime, saved as a JSON array
+///
+/// Cards from the card directory or remote discovery are not stored here;
+/// they are registered again by their own source.
+#[derive(Debug, Default)]
+pub struct RegistryStore {
+    /// Backing file (None keeps the cards in memory only)
+    path: Option<PathBuf>,
+    cards: Vec<AgentCard>,
+}
+
+impl RegistryStore {
+    /// Load from a JSON file (missing file means empty)
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let cards = if path.exists() {
+            let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            serde_json::from_str(&text).map_err(|e| e.to_string())?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            cards,
+        })
+    }
+
+    pub fn cards(&self) -> &[AgentCard] {
+        &self.cards
+    }
+
+    pub fn contains(&self, agent_id: &str) -> bool {
+        self.position(agent_id).is_some()
+    }
+
+    /// Add or replace a card and save
+    pub fn insert(&mut self, card: AgentCard) -> Result<(), String> {
+        let id = card.id.clone().unwrap_or_else(|| card.name.clone());
+        match self.position(&id) {
+            Some(index) => self.cards[index] = card,
+            None => self.cards.push(card),
+        }
+        self.save()
+    }
+
+    /// Remove a card and save; returns false if it was not stored
+    pub fn remove(&mut self, agent_id: &str) -> Result<bool, String> {
+        let Some(index) = self.position(agent_id) else {
+            return Ok(false);
+        };
+        self.cards.remove(index);
+        self.save().map(|_| true)
+    }
+
+    fn position(&self, agent_id: &str) -> Option<usize> {
+        self.cards
+            .iter()
+            .position(|card| card.id.as_deref().unwrap_or(&card.name) == agent_id)
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(&self.cards).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+}
+
+/// Directory of agent card files (`*.json` or `<name>/agent.json`)
+pub struct CardDirectory {
+    dir: PathBuf,
+    /// file -> (modified time, agent id)
+    files: HashMap<PathBuf, (SystemTime, String)>,
+}
+
+impl CardDirectory {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            files: HashMap::new(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Card files currently in the directory
+    fn card_files(&self) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter_map(|path| {
+                if path.is_dir() {
+                    Some(path.join("agent.json")).filter(|p| p.is_file())
+                } else if path.extension().is_some_and(|ext| ext == "json") {
+                    Some(path)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Register, update, or remove cards according to file changes since the last scan
+    pub fn sync(&mut self, registry: &AgentRegistry) -> CardSyncReport {
+        let mut report = CardSyncReport::default();
+        let current = self.card_files();
+
+        // Removed files
+        let removed: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| !current.contains(path))
+            .cloned()
+            .collect();
+        for path in removed {
+            if let Some((_, id)) = self.files.remove(&path) {
+                if registry.unregister(&id).is_ok() {
+                    report.removed.push(id);
+                }
+            }
+        }
+
+        for path in current {
+            let modified = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let previous = self.files.get(&path).cloned();
+            if previous.as_ref().is_some_and(|(time, _)| *time == modified) {
+                continue;
+            }
+
+            let card = match load_card(&path) {
+                Ok(card) => card,
+                Err(e) => {
+                    // Keep the previous card until the file is fixed
+                    report.errors.push((path.display().to_string(), e));
+                    if let Some((_, id)) = previous {
+                        self.files.insert(path, (modified, id));
+                    }
+                    continue;
+                }
+            };
+            let id = card.id.clone().unwrap_or_else(|| card.name.clone());
+
+            // The file now describes a different agent
+            if let Some((_, old_id)) = previous.filter(|(_, old_id)| *old_id != id) {
+                if registry.unregister(&old_id).is_ok() {
+                    report.removed.push(old_id);
+                }
+            }
+
+            if registry.upsert(card) {
+                report.registered.push(id.clone());
+            } else {
+                report.updated.push(id.clone());
+            }
+            self.files.insert(path, (modified, id));
+        }
+
+        report
+    }
+}
+
+/// Polls a card directory and keeps the registry in sync
+///
+/// Polling stops (after the current tick) when the watcher is dropped.
+pub struct CardWatcher {
+    running: Arc<AtomicBool>,
+}
+
+impl CardWatcher {
+    /// Sync once, then keep polling every `interval`
+    ///
+    /// `on_change` is called with every non-empty report (including the initial one).
+    pub fn start<F>(
+        mut directory: CardDirectory,
+        registry: AgentRegistry,
+        interval: Duration,
+        on_change: F,
+    ) -> Self
+    where
+        F: Fn(&CardSyncReport) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+
+        thread::spawn(move || {
+            log::info("CardWatcher", &format!("Watching {}", directory.dir().display()));
+            while flag.load(Ordering::SeqCst) {
+                let report = directory.sync(&registry);
+                if !report.is_empty() {
+                    for (path, error) in &report.errors {
+                        log::warn("CardWatcher", &format!("Invalid agent card {}: {}", path, error));
+                    }
+                    on_change(&report);
+                }
+                thread::sleep(interval);
+            }
+            log::info("CardWatcher", "Stopped");
+        });
+
+        Self { running }
+    }
+}
+
+impl Drop for CardWatcher {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::acp::agent::Skill;
 
     #[test]
     fn test_register_agent() {
@@ -282,4 +666,85 @@ mod tests {
         let available = registry.list_available();
         assert_eq!(available.len(), 0);
     }
+
+    #[test]
+    fn test_card_directory_sync() {
+        let dir = std::env::temp_dir().join(format!("revoice-agents-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("reviewer")).unwrap();
+        let write = |path: PathBuf, card: &AgentCard| {
+            std::fs::write(path, serde_json::to_string(card).unwrap()).unwrap();
+        };
+
+        let mut card = AgentCard::codex("custom");
+        write(dir.join("custom.json"), &card);
+        write(dir.join("reviewer").join("agent.json"), &AgentCard::claude_code("reviewer"));
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+
+        let registry = AgentRegistry::new();
+        let mut directory = CardDirectory::new(&dir);
+        let report = directory.sync(&registry);
+        assert_eq!(report.registered.len(), 2);
+        assert_eq!(report.errors.len(), 1);
+        assert!(directory.sync(&registry).updated.is_empty());
+
+        // Update (force a different mtime) and remove
+        card.description = Some("Edited".to_string());
+        write(dir.join("custom.json"), &card);
+        let file = std::fs::File::options().write(true).open(dir.join("custom.json")).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5)).unwrap();
+        std::fs::remove_dir_all(dir.join("reviewer")).unwrap();
+
+        let report = directory.sync(&registry);
+        assert_eq!(report.updated, vec!["codex@localhost/custom".to_string()]);
+        assert_eq!(report.removed, vec!["claude-code@localhost/reviewer".to_string()]);
+        assert_eq!(registry.get("codex@localhost/custom").unwrap().description.as_deref(), Some("Edited"));
+        assert_eq!(registry.count(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_status_from_load_signals() {
+        assert_eq!(AgentStatus::from_pane(&PaneStatus::Processing), Some(AgentStatus::Busy));
+        assert_eq!(AgentStatus::from_pane(&PaneStatus::Idle), Some(AgentStatus::Online));
+        assert_eq!(AgentStatus::from_pane(&PaneStatus::Unknown), None);
+        assert_eq!(AgentStatus::from_executor(&AgentState::processing(None)), AgentStatus::Busy);
+        assert_eq!(AgentStatus::from_executor(&AgentState::idle()), AgentStatus::Online);
+        let fatal = AgentState::Error { message: "crashed".into(), recoverable: false };
+        assert_eq!(AgentStatus::from_executor(&fatal), AgentStatus::Error);
+    }
+
+    #[test]
+    fn test_validate_card() {
+        assert!(validate_card(&AgentCard::claude_code("main")).is_ok());
+
+        let mut old = AgentCard::claude_code("main");
+        old.protocol_version = "0.1.0".to_string();
+        assert!(validate_card(&old).unwrap_err().contains("protocol version"));
+
+        assert!(validate_card(&AgentCard::new("bare", "ws://localhost:9000")).is_err());
+
+        let skill = Skill::new("translate", "Translate");
+        let card = AgentCard::new("dup", "ws://localhost:9000").with_skills(vec![skill.clone(), skill]);
+        assert!(validate_card(&card).unwrap_err().contains("Duplicate"));
+    }
+
+    #[test]
+    fn test_registry_store_round_trip() {
+        let path = std::env::temp_dir().join(format!("revoice-registry-{}.json", uuid::Uuid::new_v4()));
+
+        let mut store = RegistryStore::load(&path).unwrap();
+        store.insert(AgentCard::claude_code("main")).unwrap();
+        store.insert(AgentCard::codex("helper")).unwrap();
+        store.insert(AgentCard::claude_code("main").with_description("Edited")).unwrap();
+        assert!(store.remove("codex@localhost/helper").unwrap());
+        assert!(!store.remove("codex@localhost/helper").unwrap());
+
+        let reloaded = RegistryStore::load(&path).unwrap();
+        assert_eq!(reloaded.cards().len(), 1);
+        assert!(reloaded.contains("claude-code@localhost/main"));
+        assert_eq!(reloaded.cards()[0].description.as_deref(), Some("Edited"));
+
+        std::fs::remove_file(&path).ok();
+    }
 }
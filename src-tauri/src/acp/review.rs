@@ -0,0 +1,221 @@
+//! 翻訳レビュー（音声生成前の人手確認）
+//!
+//! `review` ステージでパイプラインを一時停止し、翻訳済みセグメントを
+//! 編集セッションとしてフロントエンドに公開する。ユーザーが承認すると
+//! 編集後のテキストで後続ステージを再開し、却下するとパイプラインを失敗させる。
+
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+/// レビューエラー
+#[derive(Debug, Error)]
+pub enum ReviewError {
+    #[error("Review session not found: {0}")]
+    NotFound(String),
+
+    #[error("Segment not found: {0}")]
+    SegmentNotFound(usize),
+}
+
+/// レビュー対象のセグメント
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewSegment {
+    /// セグメント番号
+    pub index: usize,
+    /// 開始時刻（ミリ秒）
+    pub start_ms: u64,
+    /// 終了時刻（ミリ秒）
+    pub end_ms: u64,
+    /// 原文
+    pub source: String,
+    /// 訳文（編集後）
+    pub translation: String,
+    /// ユーザーが編集したか
+    #[serde(default)]
+    pub edited: bool,
+    /// 品質チェックの類似度（品質チェックを行った場合）
+    #[serde(default)]
+    pub similarity: Option<f64>,
+    /// 品質チェックで低信頼とされたか
+    #[serde(default)]
+    pub flagged: bool,
+}
+
+/// レビューセッション（実行・翻訳先言語ごとに1つ）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewSession {
+    pub execution_id: String,
+    /// 2言語目以降の翻訳先（最初の言語ならNone）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_lang: Option<String>,
+    pub segments: Vec<ReviewSegment>,
+}
+
+impl ReviewSession {
+    /// レビューID（実行ID、2言語目以降は `<実行ID>:<言語>`）
+    pub fn id(&self) -> String {
+        match self.target_lang {
+            Some(ref lang) => format!("{}:{}", self.execution_id, lang),
+            None => self.execution_id.clone(),
+        }
+    }
+
+    /// 番号付きの訳文一覧
+    pub fn entries(&self) -> Vec<(usize, String)> {
+        self.segments
+            .iter()
+            .map(|s| (s.index, s.translation.clone()))
+            .collect()
+    }
+}
+
+/// レビュー結果
+#[derive(Debug, Clone)]
+pub enum ReviewDecision {
+    /// 承認（編集後のセッション）
+    Approved(ReviewSession),
+    /// 却下
+    Rejected(String),
+}
+
+/// 保留中のレビュー
+struct PendingReview {
+    session: ReviewSession,
+    responder: oneshot::Sender<ReviewDecision>,
+}
+
+/// レビューセッション管理
+#[derive(Default)]
+pub struct ReviewManager {
+    pending: Mutex<HashMap<String, PendingReview>>,
+}
+
+impl ReviewManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// セッションを開始し、結果を待つレシーバーを返す
+    pub fn open(&self, session: ReviewSession) -> oneshot::Receiver<ReviewDecision> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().insert(session.id(), PendingReview { session, responder: tx });
+        rx
+    }
+
+    /// セッションを取得（IDは `ReviewSession::id`）
+    pub fn get(&self, review_id: &str) -> Option<ReviewSession> {
+        self.pending.lock().get(review_id).map(|p| p.session.clone())
+    }
+
+    /// レビュー待ちのレビューID一覧
+    pub fn pending_ids(&self) -> Vec<String> {
+        self.pending.lock().keys().cloned().collect()
+    }
+
+    /// セグメントの訳文を編集
+    pub fn update_segment(
+        &self,
+        review_id: &str,
+        index: usize,
+        translation: &str,
+    ) -> Result<ReviewSegment, ReviewError> {
+        let mut pending = self.pending.lock();
+        let review = pending.get_mut(review_id)
+            .ok_or_else(|| ReviewError::NotFound(review_id.to_string()))?;
+        let segment = review.session.segments
+            .iter_mut()
+            .find(|s| s.index == index)
+            .ok_or(ReviewError::SegmentNotFound(index))?;
+
+        segment.translation = translation.trim().to_string();
+        segment.edited = true;
+        Ok(segment.clone())
+    }
+
+    /// 承認してパイプラインを再開
+    pub fn approve(&self, review_id: &str) -> Result<(), ReviewError> {
+        let review = self.take(review_id)?;
+        let _ = review.responder.send(ReviewDecision::Approved(review.session));
+        Ok(())
+    }
+
+    /// 却下してパイプラインを失敗させる
+    pub fn reject(&self, review_id: &str, reason: &str) -> Result<(), ReviewError> {
+        let review = self.take(review_id)?;
+        let _ = review.responder.send(ReviewDecision::Rejected(reason.to_string()));
+        Ok(())
+    }
+
+    /// 実行の全言語のセッションを破棄（実行キャンセル時）
+    pub fn cancel(&self, execution_id: &str) {
+        self.pending.lock().retain(|_, review| review.session.execution_id != execution_id);
+    }
+
+    fn take(&self, review_id: &str) -> Result<PendingReview, ReviewError> {
+        self.pending.lock()
+            .remove(review_id)
+            .ok_or_else(|| ReviewError::NotFound(review_id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> ReviewSession {
+        ReviewSession {
+            execution_id: "exec-1".to_string(),
+            target_lang: None,
+            segments: vec![ReviewSegment {
+                index: 0,
+                start_ms: 0,
+                end_ms: 1000,
+                source: "Hello".to_string(),
+                translation: "こんにちわ".to_string(),
+                edited: false,
+                similarity: None,
+                flagged: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_edit_and_approve() {
+        let manager = ReviewManager::new();
+        let mut rx = manager.open(session());
+
+        manager.update_segment("exec-1", 0, " こんにちは ").unwrap();
+        assert!(matches!(manager.update_segment("exec-1", 5, "x"), Err(ReviewError::SegmentNotFound(5))));
+        manager.approve("exec-1").unwrap();
+
+        match rx.try_recv().unwrap() {
+            ReviewDecision::Approved(session) => {
+                assert_eq!(session.entries(), vec![(0, "こんにちは".to_string())]);
+                assert!(session.segments[0].edited);
+            }
+            other => panic!("unexpected decision: {:?}", other),
+        }
+        assert!(manager.get("exec-1").is_none());
+    }
+
+    #[test]
+    fn test_reject_and_cancel() {
+        let manager = ReviewManager::new();
+        let mut rx = manager.open(session());
+        manager.reject("exec-1", "bad").unwrap();
+        assert!(matches!(rx.try_recv().unwrap(), ReviewDecision::Rejected(r) if r == "bad"));
+
+        let mut rx = manager.open(session());
+        let mut rx_en = manager.open(ReviewSession { target_lang: Some("en".to_string()), ..session() });
+        assert_eq!(manager.get("exec-1:en").unwrap().target_lang.as_deref(), Some("en"));
+        manager.cancel("exec-1");
+        assert!(rx.try_recv().is_err());
+        assert!(rx_en.try_recv().is_err());
+        assert!(matches!(manager.approve("exec-1"), Err(ReviewError::NotFound(_))));
+        assert!(manager.pending_ids().is_empty());
+    }
+}
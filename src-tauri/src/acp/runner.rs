@@ -12,25 +12,60 @@
 //! 2. Stage2: VTT解析 (Rust)
 //! 3. Stage3: 翻訳 (Claude Code)
 //! 4. Stage4: 音声生成 (VOICEVOX/Rust)
+//!
+//! `review` オプション指定時は翻訳と音声生成の間にレビューステージを挟み、
+//! ユーザーの承認まで一時停止する。
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 use thiserror::Error;
 use tokio::sync::RwLock;
 
+use super::agent_executor::{self, AgentEvent, AgentExecutor, AgentExecutorError, AgentTarget, ClaudeCodeAgent, CliAgent, PtyAgent, TmuxPaneAgent};
 use super::ask::AskToolHandler;
+use super::chunking::{self, ChunkConfig, TranslationChunk};
+use super::content_filter::{self, ContentFilterConfig, FilterMatch, FilterReport};
 use super::executor::{ClaudeCodeExecutor, ExecutorOptions};
-use super::pipeline::{PipelineDefinition, PipelineError, PipelineExecution, PipelineExecutor};
-use super::message::PipelineStage;
-use super::subtitle_parser::{VttParser, SubtitleSegment, parse_translated_text};
+use super::glossary::{GlossaryReport, GlossaryStore, DEFAULT_PROJECT};
+use super::length::{self, LengthConfig};
+use super::quality::{QualityConfig, QualityReport};
+use super::validation::{self, VerificationReport};
+use super::pipeline::{PipelineDefinition, PipelineError, PipelineExecution, PipelineExecutor, PipelineStatus};
+use super::review::{ReviewDecision, ReviewManager, ReviewSegment, ReviewSession};
+use super::translation_memory::{self, MemoryStats, TranslationMemory};
+use super::adapter::ContextValue;
+use super::cli_agent::CliAgentOptions;
+use super::message::{CapabilityFilter, PipelineStage, RetryPolicy};
+use super::orchestrator::{execution_workspace, AgentOrchestrator, OrchestratorError, DEFAULT_WORKSPACE};
+use super::tmux::TmuxOrchestrator;
+use super::transport::pty::PtyHandle;
+use super::transport::LOCAL_SENDER;
+use super::stream_parser::UsageTotals;
+use super::translator::{
+    create_translator, SpeechStyle, TranslateOptions, TranslationProvider, TranslationUsage, Translator,
+    TranslatorError,
+};
+use super::subtitle_parser::{
+    VttParser, SrtParser, SubtitleFormat, SubtitleSegment, format_indexed_text, format_translated_text,
+    parse_indexed_text, parse_subtitle_file, parse_translated_text,
+};
+use crate::diarization::{self, DiarizationConfig, DiarizationError};
+use crate::events::RecordedEmit;
+use crate::export::{self, ExportError, ExportOptions};
 use crate::log;
-use crate::youtube::YoutubeDownloader;
-use crate::voicevox::VoicevoxClient;
+use crate::mux::MuxOptions;
+use crate::preflight::{self, ExternalTool, PreflightError};
+use crate::secrets::SecretStore;
+use crate::timeline::{self, SilenceTrim, TimeStretch, TimelineError};
+use crate::whisper::{TranscriptionConfig, WhisperError};
+use crate::youtube::{PlaylistEntry, YoutubeDownloader, YoutubeError, YtdlpCookies};
+use crate::tts::{self, TtsConfig};
+use crate::voicevox::{self, SpeakerMapping, SpeedFit, SynthesisOptions};
 
 /// UTF-8安全な文字列切り詰め
 fn truncate_safe(s: &str, max_bytes: usize) -> &str {
@@ -44,6 +79,36 @@ fn truncate_safe(s: &str, max_bytes: usize) -> &str {
     &s[..boundary]
 }
 
+/// 既定の翻訳先言語
+pub const DEFAULT_TARGET_LANG: &str = "ja";
+
+/// 出力ディレクトリに記録するプロジェクト設定のファイル名
+pub const PROJECT_FILE: &str = "project.json";
+
+/// 実行コンテキストのチェックポイント（出力ディレクトリに各ステージ後に保存）
+pub const PIPELINE_STATE_FILE: &str = "pipeline_state.json";
+
+/// 翻訳オプションのプリセットの保存ファイル名（データディレクトリ直下）
+pub const PRESETS_FILE: &str = "presets.json";
+
+/// チェックポイントの保存先を記録する実行コンテキストのキー
+const CHECKPOINT_DIR_KEY: &str = "checkpoint_dir";
+
+/// 話者ラベルとVOICEVOX話者の対応（話者分離ステージが書き出す）
+const SPEAKERS_FILE: &str = "speakers.json";
+
+/// 翻訳出力の欠落・重複を再翻訳する最大回数
+const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+/// 一時停止中に再開・キャンセルを確認する間隔
+const PAUSE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Runnerが実行ごとに自動生成するパイプライン名（エクスポート対象外）
+pub const BUILTIN_PIPELINE_NAMES: &[&str] = &["subtitle-translation", "local-subtitle-translation", LOCAL_VIDEO_PIPELINE];
+
+/// ローカル動画＋字幕の吹替パイプライン名（起動時にテンプレートとして登録する）
+pub const LOCAL_VIDEO_PIPELINE: &str = "local-video-revoice";
+
 /// PipelineRunnerエラー
 #[derive(Debug, Error)]
 pub enum RunnerError {
@@ -69,7 +134,7 @@ pub enum RunnerError {
     Io(#[from] std::io::Error),
 
     #[error("YouTube download error: {0}")]
-    Youtube(String),
+    Youtube(#[from] YoutubeError),
 
     #[error("VTT parse error: {0}")]
     VttParse(String),
@@ -82,6 +147,27 @@ pub enum RunnerError {
 
     #[error("Executor not available")]
     ExecutorNotAvailable,
+
+    #[error("Preflight check failed: {0}")]
+    Preflight(#[from] PreflightError),
+
+    #[error("Invalid option: {0}")]
+    InvalidOption(String),
+
+    #[error("Translation provider error: {0}")]
+    Translation(#[from] TranslatorError),
+
+    #[error("Dub track assembly error: {0}")]
+    Timeline(#[from] TimelineError),
+
+    #[error("Transcription error: {0}")]
+    Whisper(#[from] WhisperError),
+
+    #[error("Diarization error: {0}")]
+    Diarization(#[from] DiarizationError),
+
+    #[error("Video export error: {0}")]
+    Export(#[from] ExportError),
 }
 
 /// 実行コンテキスト（ステージ間で共有）
@@ -112,6 +198,29 @@ impl ExecutionContext {
             input,
         }
     }
+
+    /// 入力の出力ディレクトリ
+    pub fn output_dir(&self) -> Option<&str> {
+        self.input.get("output_dir").and_then(Value::as_str).filter(|dir| !dir.is_empty())
+    }
+
+    /// `{output_dir}/pipeline_state.json` に保存（出力ディレクトリがなければ何もしない）
+    pub fn save_checkpoint(&self) -> Result<(), RunnerError> {
+        let Some(output_dir) = self.output_dir() else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(output_dir)?;
+        let path = std::path::Path::new(output_dir).join(PIPELINE_STATE_FILE);
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 出力ディレクトリのチェックポイントを読み込む
+    pub fn load_checkpoint(output_dir: &str) -> Result<Self, RunnerError> {
+        let path = std::path::Path::new(output_dir).join(PIPELINE_STATE_FILE);
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
 }
 
 /// 進捗イベントのペイロード
@@ -125,6 +234,397 @@ pub struct ProgressPayload {
     pub message: String,
 }
 
+/// 翻訳オプション（実行ごと）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranslationOptions {
+    /// 用語集のプロジェクト名（未指定なら既定プロジェクト）
+    #[serde(default)]
+    pub project: Option<String>,
+    /// 長い字幕のチャンク分割設定
+    #[serde(default)]
+    pub chunking: ChunkConfig,
+    /// 翻訳先の言語コード（未指定なら日本語）
+    #[serde(default)]
+    pub target_lang: Option<String>,
+    /// 複数の翻訳先言語（指定時は `target_lang` より優先し、言語ごとに翻訳・音声生成する）
+    #[serde(default)]
+    pub target_langs: Vec<String>,
+    /// 翻訳プロバイダー
+    #[serde(default)]
+    pub provider: TranslationProvider,
+    /// LLMプロバイダーのモデル名（未指定ならプロバイダーの既定）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 吹替の文体（未指定ならプロジェクトに記録された文体）
+    #[serde(default)]
+    pub style: Option<SpeechStyle>,
+    /// 表示時間に応じた訳文の長さ制約（未指定なら制約しない）
+    #[serde(default)]
+    pub length: Option<LengthConfig>,
+    /// 翻訳メモリを使わずに全セグメントを翻訳し直す
+    #[serde(default)]
+    pub skip_translation_memory: bool,
+    /// 音声生成の前に翻訳をレビューする
+    #[serde(default)]
+    pub review: bool,
+    /// 翻訳後に欠落・未翻訳のセグメントを検証し、該当分だけ再翻訳する
+    #[serde(default)]
+    pub verify: bool,
+    /// 逆翻訳による品質チェック（未指定なら行わない）
+    #[serde(default)]
+    pub quality_check: Option<QualityConfig>,
+    /// 音声生成前のコンテンツフィルター（未指定なら行わない）
+    #[serde(default)]
+    pub content_filter: Option<ContentFilterConfig>,
+    /// 組み立て前に合成音声の前後の無音を削る設定（未指定なら既定値）
+    #[serde(default)]
+    pub trim_silence: Option<SilenceTrim>,
+    /// 次の字幕までに収まらない音声を縮める設定（未指定なら縮めずに後ろにずらす）
+    #[serde(default)]
+    pub time_stretch: Option<TimeStretch>,
+    /// 字幕の表示時間を超える音声は話速を上げて合成し直す
+    #[serde(default)]
+    pub speed_fit: Option<SpeedFit>,
+    /// 音声生成で同時に合成するセグメント数（既定4）
+    #[serde(default)]
+    pub synthesis_concurrency: Option<usize>,
+    /// 音声生成のVOICEVOX話者ID（未指定なら設定の既定話者）
+    #[serde(default)]
+    pub speaker: Option<i32>,
+    /// 字幕の話者・セグメントごとのVOICEVOX話者（話者分離の割り当てより優先）
+    #[serde(default)]
+    pub speaker_mapping: Option<SpeakerMapping>,
+    /// 音声合成エンジン（既定はVOICEVOX）
+    #[serde(default)]
+    pub tts: Option<TtsConfig>,
+    /// 字幕がない動画をWhisperで文字起こしする設定（未指定なら字幕がなければ失敗）
+    #[serde(default)]
+    pub transcription: Option<TranscriptionConfig>,
+    /// 話者分離して話者ごとに声を変える設定（未指定なら1人の声で吹き替える）
+    #[serde(default)]
+    pub diarization: Option<DiarizationConfig>,
+    /// 吹替動画を書き出すときの多重化設定（動画を入力にするパイプラインと `include_video`）
+    #[serde(default)]
+    pub mux: MuxOptions,
+    /// YouTubeの字幕翻訳パイプラインで元動画をダウンロードし、吹替動画まで書き出す
+    #[serde(default)]
+    pub include_video: bool,
+    /// yt-dlpに渡すcookie（メンバー限定・年齢制限のある動画）
+    #[serde(default, skip_serializing_if = "YtdlpCookies::is_empty")]
+    pub cookies: YtdlpCookies,
+}
+
+impl TranslationOptions {
+    /// 用語集のプロジェクト名
+    pub fn project(&self) -> &str {
+        self.project.as_deref().unwrap_or(DEFAULT_PROJECT)
+    }
+
+    /// 翻訳先の言語コード（複数指定時は最初の言語）
+    pub fn target_lang(&self) -> &str {
+        self.target_langs
+            .first()
+            .map(|s| s.as_str())
+            .or(self.target_lang.as_deref())
+            .unwrap_or(DEFAULT_TARGET_LANG)
+    }
+
+    /// 翻訳先の言語コード一覧（重複は除く）
+    pub fn target_langs(&self) -> Vec<String> {
+        let mut languages: Vec<String> = Vec::new();
+        for lang in &self.target_langs {
+            if !languages.contains(lang) {
+                languages.push(lang.clone());
+            }
+        }
+        if languages.is_empty() {
+            languages.push(self.target_lang().to_string());
+        }
+        languages
+    }
+
+    /// オプションを検証（言語コードはファイル名に使うため英数字とハイフンのみ）
+    pub fn validate(&self) -> Result<(), RunnerError> {
+        for lang in self.target_langs() {
+            if lang.is_empty() || !lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(RunnerError::InvalidOption(format!("target_lang: {}", lang)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 出力ディレクトリのプロジェクト設定から読み込む
+    pub fn from_project(output_dir: &str) -> Option<Self> {
+        let path = std::path::Path::new(output_dir).join(PROJECT_FILE);
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    /// 出力ディレクトリのプロジェクト設定に記録する
+    ///
+    /// 文体が未指定なら前回記録した文体を引き継ぎ、同じ動画の吹替で口調が揺れないようにする。
+    pub fn record_in_project(&self, output_dir: &str) -> Result<Self, RunnerError> {
+        let path = std::path::Path::new(output_dir).join(PROJECT_FILE);
+        let mut options = self.clone();
+        if options.style.is_none() {
+            options.style = Self::from_project(output_dir).and_then(|previous| previous.style);
+        }
+
+        std::fs::create_dir_all(output_dir)?;
+        std::fs::write(&path, serde_json::to_string_pretty(&options)?)?;
+        Ok(options)
+    }
+
+    /// 保存済みのプリセット（名前 -> 翻訳オプション）を読み込む（ファイルがなければ空）
+    pub fn load_presets(path: &std::path::Path) -> Result<BTreeMap<String, Self>, RunnerError> {
+        if !path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// プリセットを保存する
+    pub fn save_presets(path: &std::path::Path, presets: &BTreeMap<String, Self>) -> Result<(), RunnerError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(presets)?)?;
+        Ok(())
+    }
+}
+
+/// 用語集チェック結果イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct GlossaryReportPayload {
+    pub execution_id: String,
+    pub project: String,
+    /// 2言語目以降の翻訳先（最初の言語ならNone）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_lang: Option<String>,
+    pub report: GlossaryReport,
+}
+
+/// 品質チェック結果イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityReportPayload {
+    pub execution_id: String,
+    pub target_lang: String,
+    pub report: QualityReport,
+}
+
+/// 翻訳検証結果イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationPayload {
+    pub execution_id: String,
+    pub target_lang: String,
+    pub report: VerificationReport,
+}
+
+/// コンテンツフィルター結果イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentFilterPayload {
+    pub execution_id: String,
+    pub target_lang: String,
+    pub report: FilterReport,
+}
+
+/// バッチ内の動画の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchVideoStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// バッチ実行の1動画
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchVideo {
+    pub url: String,
+    pub title: Option<String>,
+    /// 動画ごとの出力先（`<output_dir>/<連番>-<動画ID>`）
+    pub output_dir: String,
+    pub status: BatchVideoStatus,
+    pub execution_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// バッチ進捗イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgressPayload {
+    pub batch_id: String,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    /// 処理中の動画のインデックス
+    pub current: Option<usize>,
+    pub videos: Vec<BatchVideo>,
+}
+
+impl BatchProgressPayload {
+    fn new(batch_id: &str, videos: &[BatchVideo], current: Option<usize>) -> Self {
+        let count = |status| videos.iter().filter(|v| v.status == status).count();
+        Self {
+            batch_id: batch_id.to_string(),
+            total: videos.len(),
+            completed: count(BatchVideoStatus::Completed),
+            failed: count(BatchVideoStatus::Failed),
+            current,
+            videos: videos.to_vec(),
+        }
+    }
+}
+
+/// ステージ引数の `cookies` を使うダウンローダー
+fn youtube_downloader(params: &Value) -> YoutubeDownloader {
+    YoutubeDownloader::new().with_cookies(serde_json::from_value(params["cookies"].clone()).unwrap_or_default())
+}
+
+/// バッチ内の動画の出力先
+fn batch_output_dir(output_dir: &str, index: usize, video_id: &str) -> String {
+    let id: String = video_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    std::path::Path::new(output_dir).join(format!("{:03}-{}", index + 1, id)).to_string_lossy().to_string()
+}
+
+/// 翻訳API使用量イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslationUsagePayload {
+    pub execution_id: String,
+    pub provider: TranslationProvider,
+    #[serde(flatten)]
+    pub usage: TranslationUsage,
+}
+
+/// Rust直接実行できるステージ名（`execute_rust_direct` の分岐と対応）
+pub const RUST_DIRECT_STAGES: &[&str] = &[
+    "download", "import", "transcribe", "diarize", "parse", "translate", "verify", "quality_check",
+    "content_filter", "review", "voicevox", "assemble", "mux",
+];
+
+/// パイプラインのRust直接実行ステージが使う外部ツール（重複なし、最初に使う順）
+///
+/// VOICEVOXは接続できなければ字幕だけ出力し、Claude Codeはエグゼキューターがなければ手動実行にするので含めない。
+fn required_tools(definition: &PipelineDefinition) -> Vec<ExternalTool> {
+    let mut tools = Vec::new();
+    for stage in &definition.stages {
+        let Some(params) = stage.prompt_template.as_deref()
+            .and_then(|t| t.strip_prefix("RUST_DIRECT:"))
+            .and_then(|json| serde_json::from_str::<Value>(json).ok())
+        else {
+            continue;
+        };
+        let needed: &[ExternalTool] = match params["stage"].as_str().unwrap_or("") {
+            "download" | "diarize" => &[ExternalTool::Ytdlp],
+            "transcribe" => &[ExternalTool::Ytdlp, ExternalTool::Ffmpeg],
+            "mux" => &[ExternalTool::Ffmpeg],
+            _ => &[],
+        };
+        for tool in needed {
+            if !tools.contains(tool) {
+                tools.push(*tool);
+            }
+        }
+    }
+    tools
+}
+
+/// ユーザー定義パイプラインのステージ
+///
+/// パラメータやプロンプト中の `{{key}}` は実行時の入力、`{{ステージ名}}` は
+/// そのステージの出力で置き換えられる（Claude Codeステージのみ）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CustomStage {
+    /// Rust直接実行ステージ（`stage` は `RUST_DIRECT_STAGES` のいずれか）
+    Rust {
+        name: String,
+        stage: String,
+        #[serde(default)]
+        params: serde_json::Map<String, Value>,
+        /// 並列グループ（連続する同じグループのステージは並列に実行）
+        #[serde(default)]
+        group: Option<String>,
+        #[serde(default)]
+        retry: Option<RetryPolicy>,
+    },
+    /// Claude Codeステージ（プロンプトテンプレートで実行）
+    ClaudeCode {
+        name: String,
+        prompt: String,
+        /// 指定するとCLIエグゼキューターの代わりにスキル・タグで選んだエージェントで実行
+        #[serde(default)]
+        selector: Option<CapabilityFilter>,
+        /// 実行するエージェントのアドレス（`codex@localhost`・`claude-code/pty`・`<agent-id>/tmux` など、既定は `claude-code`）
+        #[serde(default)]
+        agent: Option<String>,
+        /// CLIエージェントに渡すオプション（`model`・`working_dir`、`{{key}}` は入力で置き換え）
+        #[serde(default)]
+        params: serde_json::Map<String, Value>,
+        #[serde(default)]
+        group: Option<String>,
+        #[serde(default)]
+        retry: Option<RetryPolicy>,
+    },
+}
+
+impl CustomStage {
+    pub fn name(&self) -> &str {
+        match self {
+            CustomStage::Rust { name, .. } | CustomStage::ClaudeCode { name, .. } => name,
+        }
+    }
+
+    /// `PipelineStage` に変換
+    fn to_stage(&self) -> Result<PipelineStage, RunnerError> {
+        use super::message::AgentAddress;
+
+        let (mut pipeline_stage, group, retry) = match self {
+            CustomStage::Rust { name, stage, params, group, retry } => {
+                if !RUST_DIRECT_STAGES.contains(&stage.as_str()) {
+                    return Err(RunnerError::InvalidOption(format!("Unknown stage: {}", stage)));
+                }
+                let mut params = params.clone();
+                params.insert("stage".to_string(), Value::String(stage.clone()));
+                let pipeline_stage = PipelineStage::new(name.as_str(), AgentAddress::new("rust-direct"))
+                    .with_prompt_template(format!("RUST_DIRECT:{}", Value::Object(params)));
+                (pipeline_stage, group, retry)
+            }
+            CustomStage::ClaudeCode { name, prompt, selector, agent, params, group, retry } => {
+                if prompt.starts_with("RUST_DIRECT:") {
+                    return Err(RunnerError::InvalidOption(format!("Invalid prompt in stage: {}", name)));
+                }
+                let address = match agent.as_deref() {
+                    Some(agent) => AgentAddress::parse(agent)
+                        .filter(|a| AgentTarget::from_address(a) != AgentTarget::RustDirect)
+                        .ok_or_else(|| RunnerError::InvalidOption(format!("Invalid agent in stage {}: {}", name, agent)))?,
+                    None => AgentAddress::new("claude-code"),
+                };
+                let mut pipeline_stage = PipelineStage::new(name.as_str(), address)
+                    .with_prompt_template(prompt.as_str());
+                if let Some(selector) = selector {
+                    pipeline_stage = pipeline_stage.with_selector(selector.clone());
+                }
+                if !params.is_empty() {
+                    pipeline_stage = pipeline_stage.with_params(Value::Object(params.clone()));
+                }
+                (pipeline_stage, group, retry)
+            }
+        };
+        if let Some(group) = group {
+            pipeline_stage = pipeline_stage.with_group(group.as_str());
+        }
+        if let Some(retry) = retry {
+            pipeline_stage = pipeline_stage.with_retry(retry.clone());
+        }
+        Ok(pipeline_stage)
+    }
+}
+
 /// PipelineRunner - パイプライン自動実行エンジン（CLIベース版）
 ///
 /// 注: CLIエグゼキューターはlib.rs側で管理され、このrunnerは
@@ -141,13 +641,27 @@ pub struct PipelineRunner {
     app_handle: Arc<Mutex<Option<AppHandle>>>,
     /// 実行コンテキスト
     contexts: Arc<Mutex<HashMap<String, ExecutionContext>>>,
+    /// プロジェクト別用語集
+    glossaries: Arc<Mutex<GlossaryStore>>,
+    /// 翻訳メモリ
+    translation_memory: Arc<Mutex<TranslationMemory>>,
+    /// APIキー等のシークレット
+    secrets: Arc<Mutex<SecretStore>>,
+    /// 翻訳レビューセッション
+    reviews: Arc<ReviewManager>,
+    /// スキル指定のステージでエージェントを選ぶオーケストレーター
+    orchestrator: Arc<Mutex<Option<Arc<Mutex<AgentOrchestrator>>>>>,
+    /// `<agent-id>/tmux` 宛てのステージを実行するtmuxセッション
+    tmux: Arc<Mutex<Option<TmuxOrchestrator>>>,
+    /// `claude-code/pty` 宛てのステージを実行するPTYのトランスポート
+    pty: Arc<Mutex<Option<Arc<PtyHandle>>>>,
 }
 
 impl PipelineRunner {
     /// 新しいPipelineRunnerを作成
     pub fn new(
         executor: Arc<Mutex<PipelineExecutor>>,
-        _tmux: Arc<Mutex<Option<super::tmux::TmuxOrchestrator>>>,
+        tmux: Arc<Mutex<Option<TmuxOrchestrator>>>,
     ) -> Self {
         Self {
             executor,
@@ -155,6 +669,13 @@ impl PipelineRunner {
             ask_handler: Arc::new(AskToolHandler::new()),
             app_handle: Arc::new(Mutex::new(None)),
             contexts: Arc::new(Mutex::new(HashMap::new())),
+            glossaries: Arc::new(Mutex::new(GlossaryStore::new())),
+            translation_memory: Arc::new(Mutex::new(TranslationMemory::new())),
+            secrets: Arc::new(Mutex::new(SecretStore::new())),
+            reviews: Arc::new(ReviewManager::new()),
+            orchestrator: Arc::new(Mutex::new(None)),
+            tmux,
+            pty: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -169,9 +690,28 @@ impl PipelineRunner {
             ask_handler: Arc::new(AskToolHandler::new()),
             app_handle: Arc::new(Mutex::new(None)),
             contexts: Arc::new(Mutex::new(HashMap::new())),
+            glossaries: Arc::new(Mutex::new(GlossaryStore::new())),
+            translation_memory: Arc::new(Mutex::new(TranslationMemory::new())),
+            secrets: Arc::new(Mutex::new(SecretStore::new())),
+            reviews: Arc::new(ReviewManager::new()),
+            orchestrator: Arc::new(Mutex::new(None)),
+            tmux: Arc::new(Mutex::new(None)),
+            pty: Arc::new(Mutex::new(None)),
         }
     }
 
+    /// スキル指定のステージでエージェントを選ぶオーケストレーターを設定
+    pub fn set_orchestrator(&self, orchestrator: Arc<Mutex<AgentOrchestrator>>) {
+        *self.orchestrator.lock() = Some(orchestrator);
+    }
+
+    /// tmux・PTY宛てのステージを実行するバックエンドを指定して作成
+    pub fn with_agent_backends(mut self, tmux: Arc<Mutex<Option<TmuxOrchestrator>>>, pty: Arc<PtyHandle>) -> Self {
+        self.tmux = tmux;
+        self.pty = Arc::new(Mutex::new(Some(pty)));
+        self
+    }
+
     /// CLIエグゼキューターを設定
     pub fn set_cli_executor(&self, executor: Arc<RwLock<Option<ClaudeCodeExecutor>>>) {
         // 実際にはArcをcloneできないので、このメソッドは使用しない
@@ -185,25 +725,93 @@ impl PipelineRunner {
         self.ask_handler.set_app_handle(handle);
     }
 
+    /// 用語集ストアを取得
+    pub fn glossaries(&self) -> Arc<Mutex<GlossaryStore>> {
+        self.glossaries.clone()
+    }
+
+    /// 用語集をファイルから読み込み直す
+    pub fn load_glossaries(&self, path: &std::path::Path) -> Result<(), RunnerError> {
+        let store = GlossaryStore::load(path)?;
+        log::info("PipelineRunner", &format!(
+            "Loaded glossaries for {} projects from {}",
+            store.projects().len(), path.display()
+        ));
+        *self.glossaries.lock() = store;
+        Ok(())
+    }
+
+    /// 翻訳メモリをファイルから読み込み直す
+    pub fn load_translation_memory(&self, path: &std::path::Path) -> Result<(), RunnerError> {
+        let memory = TranslationMemory::load(path)?;
+        log::info("PipelineRunner", &format!(
+            "Loaded translation memory ({} entries) from {}",
+            memory.stats().entries, path.display()
+        ));
+        *self.translation_memory.lock() = memory;
+        Ok(())
+    }
+
+    /// 翻訳メモリの件数
+    pub fn translation_memory_stats(&self) -> MemoryStats {
+        self.translation_memory.lock().stats()
+    }
+
+    /// 翻訳メモリを空にして保存
+    pub fn clear_translation_memory(&self) -> Result<(), RunnerError> {
+        let mut memory = self.translation_memory.lock();
+        memory.clear();
+        Ok(memory.save()?)
+    }
+
+    /// シークレットストアを取得
+    pub fn secrets(&self) -> Arc<Mutex<SecretStore>> {
+        self.secrets.clone()
+    }
+
+    /// シークレットをファイルから読み込み直す
+    pub fn load_secrets(&self, path: &std::path::Path) -> Result<(), RunnerError> {
+        *self.secrets.lock() = SecretStore::load(path)?;
+        Ok(())
+    }
+
+    /// レビューセッション管理を取得
+    pub fn reviews(&self) -> Arc<ReviewManager> {
+        self.reviews.clone()
+    }
+
+    /// 翻訳ステージ用の入力（プロジェクト名・翻訳先言語・チャンク設定）を追加
+    fn add_translation_input(&self, input: &mut Value, options: &TranslationOptions) {
+        let project = options.project();
+        input["project"] = Value::String(project.to_string());
+        input["target_lang"] = Value::String(options.target_lang().to_string());
+        input["chunking"] = serde_json::to_value(&options.chunking).unwrap_or(Value::Null);
+    }
+
     /// 字幕翻訳パイプラインを実行
     ///
     /// ## 実行フロー
     /// 1. **Rustで字幕ダウンロード** (yt-dlp)
     /// 2. **Claude Codeで翻訳** (CLIベース)
     /// 3. **Rustで音声生成** (VOICEVOX)
+    /// 4. **ffmpegで吹替動画を書き出し**（`include_video` の場合）
     pub async fn run_subtitle_pipeline(
         &self,
         youtube_url: &str,
         subtitle_lang: &str,
         output_dir: &str,
+        options: &TranslationOptions,
     ) -> Result<PipelineExecution, RunnerError> {
         log::info("PipelineRunner", &format!(
             "Starting subtitle pipeline: url={}, lang={}, output={}",
             youtube_url, subtitle_lang, output_dir
         ));
 
+        options.validate()?;
+        let options = &options.record_in_project(output_dir)?;
+
         // パイプライン定義を作成
-        let pipeline = self.create_subtitle_pipeline(youtube_url, subtitle_lang, output_dir)?;
+        let pipeline = self.create_subtitle_pipeline(youtube_url, subtitle_lang, output_dir, options)?;
 
         // パイプラインを登録
         let pipeline_id = {
@@ -212,335 +820,1993 @@ impl PipelineRunner {
         };
 
         // 入力データ
-        let input = serde_json::json!({
+        let mut input = serde_json::json!({
             "youtube_url": youtube_url,
             "subtitle_lang": subtitle_lang,
             "output_dir": output_dir,
         });
+        self.add_translation_input(&mut input, options);
 
         // 実行開始
         self.run(&pipeline_id, input).await
     }
 
-    /// 字幕翻訳パイプラインの定義を作成（4ステージ版）
-    fn create_subtitle_pipeline(
+    /// プレイリストや複数URLを動画ごとに展開し、字幕翻訳パイプラインを順に実行
+    ///
+    /// 動画ごとに `<output_dir>/<連番>-<動画ID>` へ出力する。1本が失敗しても残りは続け、
+    /// 状態が変わるたびに `pipeline:batch_progress` を送信する。
+    pub async fn run_batch_pipeline(
         &self,
-        youtube_url: &str,
+        urls: &[String],
         subtitle_lang: &str,
         output_dir: &str,
-    ) -> Result<PipelineDefinition, RunnerError> {
-        use super::message::AgentAddress;
+        options: &TranslationOptions,
+    ) -> Result<Vec<BatchVideo>, RunnerError> {
+        options.validate()?;
+        let batch_id = uuid::Uuid::new_v4().to_string();
+
+        // yt-dlp --flat-playlist で動画一覧に展開
+        let urls_owned = urls.to_vec();
+        let cookies = options.cookies.clone();
+        let entries = tokio::task::spawn_blocking(move || -> Result<Vec<PlaylistEntry>, YoutubeError> {
+            let downloader = YoutubeDownloader::new().with_cookies(cookies);
+            let mut entries = Vec::new();
+            for url in &urls_owned {
+                entries.extend(downloader.expand_playlist(url)?);
+            }
+            Ok(entries)
+        }).await
+            .map_err(|e| RunnerError::StageFailed(e.to_string()))??;
+
+        let mut videos: Vec<BatchVideo> = entries.into_iter()
+            .enumerate()
+            .map(|(i, entry)| BatchVideo {
+                output_dir: batch_output_dir(output_dir, i, &entry.id),
+                url: entry.url,
+                title: entry.title,
+                status: BatchVideoStatus::Queued,
+                execution_id: None,
+                error: None,
+            })
+            .collect();
+        log::info("PipelineRunner", &format!("Starting batch {}: {} videos", batch_id, videos.len()));
+        self.emit_batch_progress(&BatchProgressPayload::new(&batch_id, &videos, None));
+
+        // Claude CLIを共有するので1本ずつ実行する
+        for i in 0..videos.len() {
+            videos[i].status = BatchVideoStatus::Running;
+            self.emit_batch_progress(&BatchProgressPayload::new(&batch_id, &videos, Some(i)));
+
+            let (url, dir) = (videos[i].url.clone(), videos[i].output_dir.clone());
+            match self.run_subtitle_pipeline(&url, subtitle_lang, &dir, options).await {
+                Ok(execution) => {
+                    videos[i].status = if execution.status == PipelineStatus::Completed {
+                        BatchVideoStatus::Completed
+                    } else {
+                        BatchVideoStatus::Failed
+                    };
+                    videos[i].error = execution.error.clone();
+                    videos[i].execution_id = Some(execution.execution_id);
+                }
+                Err(e) => {
+                    log::error("PipelineRunner", &format!("Batch {} video {} failed: {}", batch_id, url, e));
+                    videos[i].status = BatchVideoStatus::Failed;
+                    videos[i].error = Some(e.to_string());
+                }
+            }
+        }
 
-        let mut pipeline = PipelineDefinition::new("subtitle-translation");
+        self.emit_batch_progress(&BatchProgressPayload::new(&batch_id, &videos, None));
+        Ok(videos)
+    }
 
-        // ステージ1: 字幕ダウンロード（Rust/yt-dlp）
-        let download_stage = PipelineStage::new(
-            "download-subtitles",
-            AgentAddress::new("rust-direct"),
-        )
-        .with_prompt_template(format!(
-            "RUST_DIRECT:{}",
-            serde_json::json!({
-                "url": youtube_url,
-                "lang": subtitle_lang,
-                "output_dir": output_dir,
-                "stage": "download"
-            }).to_string()
+    /// 失敗・キャンセルした字幕翻訳パイプラインの再開を準備し、新しい実行を開始状態にする
+    ///
+    /// 前回の実行で完了したステージは保存済みの出力を引き継いでスキップする。
+    /// アプリ再起動後などメモリに実行が残っていない場合は `output_dir` の
+    /// `pipeline_state.json` から読み込む。
+    /// 戻り値は `(パイプラインID, 新しい実行ID)`。ステージは `run_started` で実行する。
+    pub async fn start_resume_subtitle_pipeline(
+        &self,
+        execution_id: &str,
+        output_dir: Option<&str>,
+    ) -> Result<(String, String), RunnerError> {
+        if let Some(execution) = self.get_execution(execution_id) {
+            if execution.status.is_active() {
+                return Err(PipelineError::AlreadyRunning(execution_id.to_string()).into());
+            }
+        }
+
+        let checkpoint = self.load_context(execution_id, output_dir)?;
+
+        let input_str = |key: &str| checkpoint.input.get(key).and_then(Value::as_str).map(str::to_string);
+        let (Some(youtube_url), Some(subtitle_lang), Some(output_dir)) =
+            (input_str("youtube_url"), input_str("subtitle_lang"), input_str("output_dir"))
+        else {
+            return Err(RunnerError::InvalidOption(format!("Not a subtitle pipeline: {}", execution_id)));
+        };
+
+        log::info("PipelineRunner", &format!(
+            "Resuming subtitle pipeline {} ({} completed stages)",
+            execution_id, checkpoint.stage_outputs.len()
         ));
 
-        // ステージ2: VTT解析（Rust）
-        let parse_stage = PipelineStage::new(
-            "parse-subtitles",
-            AgentAddress::new("rust-direct"),
-        )
-        .with_prompt_template(format!(
-            "RUST_DIRECT:{}",
-            serde_json::json!({
-                "stage": "parse",
-                "output_dir": output_dir
-            }).to_string()
+        let options = TranslationOptions::from_project(&output_dir).unwrap_or_default();
+        let pipeline = self.create_subtitle_pipeline(&youtube_url, &subtitle_lang, &output_dir, &options)?;
+        let pipeline_id = {
+            let executor = self.executor.lock();
+            executor.register(pipeline)
+        };
+
+        let input = checkpoint.input.clone();
+        let new_execution_id = self.start_from(&pipeline_id, input, Some(checkpoint)).await?;
+        Ok((pipeline_id, new_execution_id))
+    }
+
+    /// ローカル字幕ファイル（VTT/SRT）から吹替パイプラインを実行
+    ///
+    /// 字幕DLの代わりにファイルを出力ディレクトリへ取り込み、以降は
+    /// `run_subtitle_pipeline` と同じ解析・翻訳・音声生成を行う。
+    pub async fn run_local_subtitle_pipeline(
+        &self,
+        subtitle_path: &str,
+        output_dir: &str,
+        options: &TranslationOptions,
+    ) -> Result<PipelineExecution, RunnerError> {
+        log::info("PipelineRunner", &format!(
+            "Starting local subtitle pipeline: file={}, output={}",
+            subtitle_path, output_dir
         ));
 
-        // ステージ3: 翻訳（Claude Code）
-        let translate_stage = PipelineStage::new(
-            "translate-subtitles",
-            AgentAddress::new("claude-code"),
-        )
-        .with_prompt_template(
-            r#"以下の字幕テキストを日本語に翻訳してください。
-翻訳結果のみを出力してください。各セグメントの番号を維持してください。
+        options.validate()?;
+        let options = &options.record_in_project(output_dir)?;
 
-{{parse-subtitles}}
+        let pipeline = self.create_local_subtitle_pipeline(subtitle_path, output_dir, options)?;
 
-【翻訳ルール】
-1. 自然な日本語に翻訳
-2. 短すぎず長すぎない、適切な長さに
-3. 番号付きフォーマットを維持: [0] テキスト
+        let pipeline_id = {
+            let executor = self.executor.lock();
+            executor.register(pipeline)
+        };
 
-翻訳結果:""#,
-        );
+        let mut input = serde_json::json!({
+            "subtitle_path": subtitle_path,
+            "output_dir": output_dir,
+        });
+        self.add_translation_input(&mut input, options);
 
-        // ステージ4: 音声生成（Rust/VOICEVOX）
-        let voice_stage = PipelineStage::new(
-            "voice-synthesis",
-            AgentAddress::new("rust-direct"),
-        )
-        .with_prompt_template(format!(
-            "RUST_DIRECT:{}",
-            serde_json::json!({
-                "stage": "voicevox",
-                "output_dir": output_dir,
-                "speaker": 1
-            }).to_string()
-        ));
+        self.run(&pipeline_id, input).await
+    }
 
-        pipeline = pipeline
-            .add_stage(download_stage)
-            .add_stage(parse_stage)
-            .add_stage(translate_stage)
-            .add_stage(voice_stage);
+    /// ローカル字幕パイプラインの定義を作成
+    fn create_local_subtitle_pipeline(
+        &self,
+        subtitle_path: &str,
+        output_dir: &str,
+        options: &TranslationOptions,
+    ) -> Result<PipelineDefinition, RunnerError> {
+        let mut pipeline = PipelineDefinition::new("local-subtitle-translation");
+        for stage in Self::create_import_stages(subtitle_path, output_dir)
+            .into_iter()
+            .chain(Self::create_translation_stages(output_dir, options))
+        {
+            pipeline = pipeline.add_stage(stage);
+        }
 
         Ok(pipeline)
     }
 
-    /// パイプラインを実行
-    pub async fn run(
+    /// ローカル動画と字幕ファイルから吹替動画を作るパイプラインを実行
+    ///
+    /// 字幕の取り込み・解析・翻訳・音声生成・組み立てのあと、元動画に吹替トラックと
+    /// 翻訳済み字幕を多重化して `dubbed.<lang>.mp4` を書き出す。
+    pub async fn run_local_video_pipeline(
         &self,
-        pipeline_id: &str,
-        input: Value,
+        video_path: &str,
+        subtitle_path: &str,
+        output_dir: &str,
+        options: &TranslationOptions,
     ) -> Result<PipelineExecution, RunnerError> {
-        log::info("PipelineRunner", &format!("Running pipeline: {}", pipeline_id));
+        log::info("PipelineRunner", &format!(
+            "Starting local video pipeline: video={}, subtitles={}, output={}",
+            video_path, subtitle_path, output_dir
+        ));
 
-        // 実行開始
-        let execution = {
+        if !std::path::Path::new(video_path).is_file() {
+            return Err(RunnerError::InvalidOption(format!("video_path: {}", video_path)));
+        }
+        options.validate()?;
+        let options = &options.record_in_project(output_dir)?;
+
+        let pipeline = Self::create_local_video_pipeline(video_path, subtitle_path, output_dir, options);
+
+        let pipeline_id = {
             let executor = self.executor.lock();
-            executor.start_execution(pipeline_id)?
+            executor.register(pipeline)
         };
 
-        let execution_id = execution.execution_id.clone();
+        let mut input = serde_json::json!({
+            "video_path": video_path,
+            "subtitle_path": subtitle_path,
+            "output_dir": output_dir,
+        });
+        self.add_translation_input(&mut input, options);
 
-        // コンテキスト作成
-        let context = ExecutionContext::new(pipeline_id, &execution_id, input.clone());
-        {
-            let mut ctx = self.contexts.lock();
+        self.run(&pipeline_id, input).await
+    }
+
+    /// ユーザー定義のステージ列からパイプライン定義を作成
+    pub fn create_custom_pipeline(name: &str, stages: &[CustomStage]) -> Result<PipelineDefinition, RunnerError> {
+        if stages.is_empty() {
+            return Err(RunnerError::InvalidOption("stages: empty".to_string()));
+        }
+        if BUILTIN_PIPELINE_NAMES.contains(&name) {
+            return Err(RunnerError::InvalidOption(format!("name: {} is reserved", name)));
+        }
+
+        let mut pipeline = PipelineDefinition::new(name);
+        let mut names = std::collections::HashSet::new();
+        for stage in stages {
+            if stage.name().is_empty() || !names.insert(stage.name()) {
+                return Err(RunnerError::InvalidOption(format!("Duplicate or empty stage name: {:?}", stage.name())));
+            }
+            pipeline = pipeline.add_stage(stage.to_stage()?);
+        }
+        Ok(pipeline)
+    }
+
+    /// ユーザー定義パイプラインを登録してIDを返す
+    ///
+    /// 同じ名前のパイプラインが登録済みなら、そのIDのまま定義を置き換え、
+    /// 実行のたびに定義が増えないようにする。実行中の実行は開始時の定義の複製で進むので影響しない。
+    pub fn register_custom_pipeline(&self, name: &str, stages: &[CustomStage]) -> Result<String, RunnerError> {
+        let mut pipeline = Self::create_custom_pipeline(name, stages)?;
+        let executor = self.executor.lock();
+        if let Some(existing) = executor.list_pipelines().into_iter().find(|p| p.name == name) {
+            pipeline.id = existing.id;
+        }
+        Ok(executor.register(pipeline))
+    }
+
+    /// 組み込みのパイプラインテンプレートを登録し、ローカル動画パイプラインのIDを返す
+    ///
+    /// パスは `{{video_path}}` などのプレースホルダーのままで、実行時の入力で置き換える。
+    pub fn register_templates(&self) -> String {
+        let options = TranslationOptions::default();
+        let mut input = serde_json::json!({
+            "video_path": "",
+            "subtitle_path": "",
+            "output_dir": "",
+        });
+        self.add_translation_input(&mut input, &options);
+        let template = Self::create_local_video_pipeline(
+            "{{video_path}}",
+            "{{subtitle_path}}",
+            "{{output_dir}}",
+            &options,
+        )
+        .with_default_input(input);
+        let executor = self.executor.lock();
+        executor.register(template)
+    }
+
+    /// ローカル動画パイプラインの定義を作成
+    fn create_local_video_pipeline(
+        video_path: &str,
+        subtitle_path: &str,
+        output_dir: &str,
+        options: &TranslationOptions,
+    ) -> PipelineDefinition {
+        let mut pipeline = PipelineDefinition::new(LOCAL_VIDEO_PIPELINE);
+        for stage in Self::create_import_stages(subtitle_path, output_dir)
+            .into_iter()
+            .chain(Self::create_translation_stages(output_dir, options))
+            .chain(Self::create_mux_stages(output_dir, Some(video_path), options))
+        {
+            pipeline = pipeline.add_stage(stage);
+        }
+        pipeline
+    }
+
+    /// ローカル字幕ファイルの取り込み・解析ステージを作成
+    fn create_import_stages(subtitle_path: &str, output_dir: &str) -> Vec<PipelineStage> {
+        use super::message::AgentAddress;
+
+        let import_stage = PipelineStage::new(
+            "import-subtitles",
+            AgentAddress::new("rust-direct"),
+        )
+        .with_prompt_template(format!(
+            "RUST_DIRECT:{}",
+            serde_json::json!({
+                "stage": "import",
+                "path": subtitle_path,
+                "output_dir": output_dir
+            }).to_string()
+        ));
+
+        let parse_stage = PipelineStage::new(
+            "parse-subtitles",
+            AgentAddress::new("rust-direct"),
+        )
+        .with_prompt_template(format!(
+            "RUST_DIRECT:{}",
+            serde_json::json!({
+                "stage": "parse",
+                "output_dir": output_dir,
+                "source_stage": "import-subtitles"
+            }).to_string()
+        ));
+
+        vec![import_stage, parse_stage]
+    }
+
+    /// 翻訳先の言語ごとに吹替動画の多重化ステージを作成
+    ///
+    /// `video_path` が `None` の場合はYouTubeから元動画をダウンロードする。
+    fn create_mux_stages(
+        output_dir: &str,
+        video_path: Option<&str>,
+        options: &TranslationOptions,
+    ) -> Vec<PipelineStage> {
+        use super::message::AgentAddress;
+
+        options.target_langs()
+            .iter()
+            .enumerate()
+            .map(|(i, target_lang)| {
+                let name = if i == 0 {
+                    "mux-video".to_string()
+                } else {
+                    format!("mux-video:{}", target_lang)
+                };
+                PipelineStage::new(name.as_str(), AgentAddress::new("rust-direct"))
+                    .with_prompt_template(format!(
+                        "RUST_DIRECT:{}",
+                        serde_json::json!({
+                            "stage": "mux",
+                            "output_dir": output_dir,
+                            "video_path": video_path,
+                            "target_lang": target_lang,
+                            "mux": options.mux,
+                            "cookies": options.cookies
+                        }).to_string()
+                    ))
+            })
+            .collect()
+    }
+
+    /// 字幕翻訳パイプラインの定義を作成（4ステージ版）
+    fn create_subtitle_pipeline(
+        &self,
+        youtube_url: &str,
+        subtitle_lang: &str,
+        output_dir: &str,
+        options: &TranslationOptions,
+    ) -> Result<PipelineDefinition, RunnerError> {
+        use super::message::AgentAddress;
+
+        let mut pipeline = PipelineDefinition::new("subtitle-translation");
+
+        // ステージ1: 字幕ダウンロード（Rust/yt-dlp）
+        let download_stage = PipelineStage::new(
+            "download-subtitles",
+            AgentAddress::new("rust-direct"),
+        )
+        .with_prompt_template(format!(
+            "RUST_DIRECT:{}",
+            serde_json::json!({
+                "url": youtube_url,
+                "lang": subtitle_lang,
+                "output_dir": output_dir,
+                "stage": "download",
+                "allow_missing": options.transcription.is_some(),
+                "cookies": options.cookies
+            }).to_string()
+        ));
+        // yt-dlpの一時的な失敗（ネットワーク・レート制限）はダウンローダーが再試行する
+        pipeline = pipeline.add_stage(download_stage);
+
+        // ステージ1b: 字幕がなければWhisperで文字起こし（Rust）
+        let subtitle_stage = match options.transcription {
+            Some(ref transcription) => {
+                pipeline = pipeline.add_stage(
+                    PipelineStage::new("transcribe-audio", AgentAddress::new("rust-direct"))
+                        .with_prompt_template(format!(
+                            "RUST_DIRECT:{}",
+                            serde_json::json!({
+                                "stage": "transcribe",
+                                "url": youtube_url,
+                                "output_dir": output_dir,
+                                "source_stage": "download-subtitles",
+                                "transcription": transcription,
+                                "cookies": options.cookies
+                            }).to_string()
+                        )),
+                );
+                "transcribe-audio"
+            }
+            None => "download-subtitles",
+        };
+
+        // ステージ2: VTT解析（Rust）
+        let parse_stage = PipelineStage::new(
+            "parse-subtitles",
+            AgentAddress::new("rust-direct"),
+        )
+        .with_prompt_template(format!(
+            "RUST_DIRECT:{}",
+            serde_json::json!({
+                "stage": "parse",
+                "output_dir": output_dir,
+                "source_stage": subtitle_stage
+            }).to_string()
+        ));
+
+        pipeline = pipeline.add_stage(parse_stage);
+
+        // ステージ2b: 話者分離（Rust + 外部コマンド）
+        if let Some(ref diarization) = options.diarization {
+            pipeline = pipeline.add_stage(
+                PipelineStage::new("diarize-speakers", AgentAddress::new("rust-direct"))
+                    .with_prompt_template(format!(
+                        "RUST_DIRECT:{}",
+                        serde_json::json!({
+                            "stage": "diarize",
+                            "url": youtube_url,
+                            "output_dir": output_dir,
+                            "diarization": diarization,
+                            "cookies": options.cookies
+                        }).to_string()
+                    )),
+            );
+        }
+        for stage in Self::create_translation_stages(output_dir, options) {
+            pipeline = pipeline.add_stage(stage);
+        }
+
+        // ステージ6: 元動画をyt-dlpでダウンロードして吹替トラックと多重化（ffmpeg）
+        if options.include_video {
+            for stage in Self::create_mux_stages(output_dir, None, options) {
+                pipeline = pipeline.add_stage(stage);
+            }
+        }
+
+        Ok(pipeline)
+    }
+
+    /// 翻訳・（レビュー）・音声生成ステージを作成
+    ///
+    /// 翻訳はプロバイダーによらずRust直接実行の `translate` ステージで行う。
+    /// 翻訳先が複数ある場合は言語ごとに検証・品質チェック・レビュー・音声生成まで行い、
+    /// 同じ工程のステージは言語間で並列に実行する（`stage_groups` の同じグループ）。
+    fn create_translation_stages(
+        output_dir: &str,
+        options: &TranslationOptions,
+    ) -> Vec<PipelineStage> {
+        use super::message::AgentAddress;
+
+        let languages = options.target_langs();
+        let multi_target = languages.len() > 1;
+        // 言語ごとの工程（工程ごとに言語をまとめて並べ直す）
+        let mut steps: Vec<Vec<PipelineStage>> = Vec::new();
+
+        for (i, target_lang) in languages.iter().enumerate() {
+            let primary = i == 0;
+            let stage_name = |base: &str| if primary {
+                base.to_string()
+            } else {
+                format!("{}:{}", base, target_lang)
+            };
+            let report_file = |base: &str| if primary {
+                format!("{}.json", base)
+            } else {
+                format!("{}.{}.json", base, target_lang)
+            };
+            let mut chain = Vec::new();
+
+            // ステージ3: 翻訳（Translator）
+            let translate_name = stage_name("translate-subtitles");
+            chain.push(
+                PipelineStage::new(translate_name.as_str(), AgentAddress::new("rust-direct"))
+                    .with_prompt_template(format!(
+                        "RUST_DIRECT:{}",
+                        serde_json::json!({
+                            "stage": "translate",
+                            "provider": options.provider,
+                            "model": options.model,
+                            "style": options.style,
+                            "length": options.length,
+                            "target_lang": target_lang,
+                            "skip_memory": options.skip_translation_memory
+                        }).to_string()
+                    )),
+            );
+
+            let mut source_stage = translate_name;
+
+            // 翻訳検証（欠落・未翻訳セグメントの再翻訳）
+            if options.verify {
+                let verify_name = stage_name("verify-translation");
+                chain.push(
+                    PipelineStage::new(verify_name.as_str(), AgentAddress::new("rust-direct"))
+                        .with_prompt_template(format!(
+                            "RUST_DIRECT:{}",
+                            serde_json::json!({
+                                "stage": "verify",
+                                "provider": options.provider,
+                                "model": options.model,
+                                "style": options.style,
+                                "output_dir": output_dir,
+                                "target_lang": target_lang,
+                                "source_stage": source_stage
+                            }).to_string()
+                        )),
+                );
+                source_stage = verify_name;
+            }
+
+            // 逆翻訳による品質チェック
+            let quality_name = stage_name("quality-check");
+            if let Some(ref quality) = options.quality_check {
+                chain.push(
+                    PipelineStage::new(quality_name.as_str(), AgentAddress::new("rust-direct"))
+                        .with_prompt_template(format!(
+                            "RUST_DIRECT:{}",
+                            serde_json::json!({
+                                "stage": "quality_check",
+                                "provider": options.provider,
+                                "model": options.model,
+                                "target_lang": target_lang,
+                                "output_dir": output_dir,
+                                "source_stage": source_stage,
+                                "report": report_file("quality_report"),
+                                "config": quality
+                            }).to_string()
+                        )),
+                );
+            }
+
+            // コンテンツフィルター（伏せ字・フラグ）
+            if let Some(ref filter) = options.content_filter {
+                let filter_name = stage_name("content-filter");
+                chain.push(
+                    PipelineStage::new(filter_name.as_str(), AgentAddress::new("rust-direct"))
+                        .with_prompt_template(format!(
+                            "RUST_DIRECT:{}",
+                            serde_json::json!({
+                                "stage": "content_filter",
+                                "provider": options.provider,
+                                "model": options.model,
+                                "output_dir": output_dir,
+                                "target_lang": target_lang,
+                                "source_stage": source_stage,
+                                "config": filter
+                            }).to_string()
+                        )),
+                );
+                source_stage = filter_name;
+            }
+
+            // レビュー（承認まで一時停止）
+            if options.review {
+                let review_name = stage_name("review-translation");
+                chain.push(
+                    PipelineStage::new(review_name.as_str(), AgentAddress::new("rust-direct"))
+                        .with_prompt_template(format!(
+                            "RUST_DIRECT:{}",
+                            serde_json::json!({
+                                "stage": "review",
+                                "output_dir": output_dir,
+                                "target_lang": (!primary).then_some(target_lang),
+                                "source_stage": source_stage,
+                                "quality_stage": quality_name
+                            }).to_string()
+                        )),
+                );
+                source_stage = review_name;
+            }
+
+            // ステージ4: 音声生成（Rust/VOICEVOX）
+            let audio_dir = if multi_target {
+                format!("{}/audio/{}", output_dir, target_lang)
+            } else {
+                format!("{}/audio", output_dir)
+            };
+            let mut voice_params = serde_json::json!({
+                "stage": "voicevox",
+                "output_dir": output_dir,
+                "audio_dir": audio_dir,
+                "target_lang": target_lang,
+                "source_stage": source_stage,
+                "speed_fit": options.speed_fit,
+                "concurrency": options.synthesis_concurrency,
+                "speaker_mapping": options.speaker_mapping,
+                "tts": options.tts
+            });
+            // 話者を指定しなければ音声生成ステージが設定の既定話者を使う
+            if let Some(speaker) = options.speaker {
+                voice_params["speaker"] = speaker.into();
+            }
+            chain.push(
+                PipelineStage::new(stage_name("voice-synthesis").as_str(), AgentAddress::new("rust-direct"))
+                    .with_prompt_template(format!("RUST_DIRECT:{}", voice_params)),
+            );
+
+            // ステージ5: 吹替音声トラックの組み立て（Rust）
+            chain.push(
+                PipelineStage::new(stage_name("assemble-dub").as_str(), AgentAddress::new("rust-direct"))
+                    .with_prompt_template(format!(
+                        "RUST_DIRECT:{}",
+                        serde_json::json!({
+                            "stage": "assemble",
+                            "output_dir": output_dir,
+                            "audio_dir": audio_dir,
+                            "target_lang": target_lang,
+                            "trim_silence": options.trim_silence,
+                            "time_stretch": options.time_stretch
+                        }).to_string()
+                    )),
+            );
+
+            for (step, stage) in chain.into_iter().enumerate() {
+                if steps.len() <= step {
+                    steps.push(Vec::new());
+                }
+                steps[step].push(stage);
+            }
+        }
+
+        // 全言語が同じ工程を持つので、工程ごとに1つのグループにする
+        steps
+            .into_iter()
+            .flat_map(|step| {
+                let group = step.first().map(|s| s.name.clone()).unwrap_or_default();
+                step.into_iter().map(move |stage| {
+                    if multi_target { stage.with_group(group.as_str()) } else { stage }
+                })
+            })
+            .collect()
+    }
+
+    /// パイプラインを実行
+    pub async fn run(
+        &self,
+        pipeline_id: &str,
+        input: Value,
+    ) -> Result<PipelineExecution, RunnerError> {
+        self.run_from(pipeline_id, input, None).await
+    }
+
+    /// パイプラインを実行（チェックポイントがあれば出力済みのステージをスキップ）
+    async fn run_from(
+        &self,
+        pipeline_id: &str,
+        input: Value,
+        checkpoint: Option<ExecutionContext>,
+    ) -> Result<PipelineExecution, RunnerError> {
+        let execution_id = self.start_from(pipeline_id, input, checkpoint).await?;
+        self.run_started(pipeline_id, &execution_id).await
+    }
+
+    /// 事前確認を行って実行を開始状態にし、コンテキストを登録して実行IDを返す
+    async fn start_from(
+        &self,
+        pipeline_id: &str,
+        input: Value,
+        checkpoint: Option<ExecutionContext>,
+    ) -> Result<String, RunnerError> {
+        log::info("PipelineRunner", &format!("Running pipeline: {}", pipeline_id));
+
+        // 外部ツールはステージの途中ではなく開始前に確認する
+        let required = self.executor.lock().get_pipeline(pipeline_id)
+            .map(|p| required_tools(&p))
+            .unwrap_or_default();
+        if !required.is_empty() {
+            preflight::check_tools(&required).await.ensure()?;
+        }
+
+        // 実行開始
+        let execution = {
+            let executor = self.executor.lock();
+            executor.start_execution(pipeline_id)?
+        };
+
+        let execution_id = execution.execution_id.clone();
+
+        // 入力にない項目はパイプラインの既定入力で補う
+        let mut input = input;
+        let defaults = self.executor.lock().get_pipeline(pipeline_id).and_then(|p| p.default_input);
+        if let (Some(Value::Object(defaults)), Some(obj)) = (defaults, input.as_object_mut()) {
+            for (key, value) in defaults {
+                obj.entry(key).or_insert(value);
+            }
+        }
+
+        // コンテキスト作成（再開時は前回のステージ出力と抽出ファイルを引き継ぐ）
+        let mut context = ExecutionContext::new(pipeline_id, &execution_id, input.clone());
+        if let Some(checkpoint) = checkpoint {
+            context.stage_outputs = checkpoint.stage_outputs;
+            context.extracted_files = checkpoint.extracted_files;
+        }
+        // 終了後もチェックポイントから書き出し・再開できるよう保存先を実行に記録する
+        if let Some(dir) = context.output_dir() {
+            let executor = self.executor.lock();
+            executor.set_context_value(&execution_id, CHECKPOINT_DIR_KEY, Value::String(dir.to_string()))?;
+        }
+        {
+            let mut ctx = self.contexts.lock();
             ctx.insert(execution_id.clone(), context);
         }
 
-        // 進捗イベントを送信
-        self.emit_progress(&execution_id, 0, "pipeline-started", "パイプライン開始");
+        Ok(execution_id)
+    }
+
+    /// `start_from` / `start_resume_subtitle_pipeline` で開始した実行のステージを実行する
+    pub async fn run_started(
+        &self,
+        pipeline_id: &str,
+        execution_id: &str,
+    ) -> Result<PipelineExecution, RunnerError> {
+        // コンテキストと共有コンテキストの実行ごとの値は実行中だけ保持する
+        // （完了・失敗・キャンセル後はチェックポイントから読み込む）
+        let result = self.run_stages(pipeline_id, execution_id).await;
+        self.contexts.lock().remove(execution_id);
+        self.clear_execution_workspace(execution_id);
+        result
+    }
+
+    /// 登録済みのコンテキストでパイプラインのステージを順に実行する
+    async fn run_stages(
+        &self,
+        pipeline_id: &str,
+        execution_id: &str,
+    ) -> Result<PipelineExecution, RunnerError> {
+        let execution_id = execution_id.to_string();
+
+        // 進捗イベントを送信
+        self.emit_progress(&execution_id, 0, "pipeline-started", "パイプライン開始");
+
+        // パイプライン定義を取得
+        let pipeline = {
+            let executor = self.executor.lock();
+            executor.get_pipeline(pipeline_id)
+                .ok_or_else(|| RunnerError::ExecutionNotFound(pipeline_id.to_string()))?
+        };
+
+        // ステージをグループ単位で実行（同じグループのステージは並列に実行し、全て終わってから次へ）
+        for group in pipeline.stage_groups() {
+            self.wait_while_paused(&execution_id).await?;
+
+            // 前回の実行で完了済みのステージは出力を引き継いでスキップ
+            let completed: Vec<(usize, String)> = {
+                let ctx = self.contexts.lock();
+                let outputs = ctx.get(&execution_id).map(|c| &c.stage_outputs);
+                group.iter()
+                    .filter_map(|&i| {
+                        let output = outputs?.get(&pipeline.stages[i].name)?;
+                        Some((i, output.clone()))
+                    })
+                    .collect()
+            };
+            for (stage_index, output) in &completed {
+                self.publish_stage_output(&execution_id, &pipeline.stages[*stage_index].name, output);
+                {
+                    let executor = self.executor.lock();
+                    executor.complete_stage_at(&execution_id, *stage_index, serde_json::json!({ "output": output }))?;
+                }
+                self.emit_progress(
+                    &execution_id,
+                    *stage_index,
+                    "stage-skipped",
+                    &format!("完了済みステージをスキップ: {}", pipeline.stages[*stage_index].name),
+                );
+            }
+            let group: Vec<usize> = group.into_iter()
+                .filter(|i| !completed.iter().any(|(done, _)| done == i))
+                .collect();
+            if group.is_empty() {
+                continue;
+            }
+
+            {
+                let executor = self.executor.lock();
+                executor.start_stages(&execution_id, &group)?;
+            }
+
+            for &stage_index in &group {
+                let stage = &pipeline.stages[stage_index];
+                log::info("PipelineRunner", &format!(
+                    "Executing stage {}: {}",
+                    stage_index, stage.name
+                ));
+
+                self.emit_progress(
+                    &execution_id,
+                    stage_index,
+                    "stage-started",
+                    &format!("ステージ開始: {}", stage.name),
+                );
+            }
+
+            // ステージを実行
+            let results = futures::future::join_all(group.iter().map(|&stage_index| {
+                self.execute_stage(&execution_id, &pipeline.stages[stage_index], stage_index)
+            }))
+            .await;
+
+            let mut first_error = None;
+            for (&stage_index, result) in group.iter().zip(results) {
+                let stage = &pipeline.stages[stage_index];
+                match result {
+                    Ok(output) => {
+                        let output = self.postprocess_output(&execution_id, stage, output);
+
+                        // 出力をコンテキストに保存
+                        {
+                            let mut ctx = self.contexts.lock();
+                            if let Some(c) = ctx.get_mut(&execution_id) {
+                                c.stage_outputs.insert(stage.name.clone(), output.clone());
+                            }
+                        }
+                        self.publish_stage_output(&execution_id, &stage.name, &output);
+
+                        // ステージ完了（先に失敗した分岐があれば実行は既に失敗扱い）
+                        if first_error.is_none() {
+                            let executor = self.executor.lock();
+                            executor.complete_stage_at(&execution_id, stage_index, serde_json::json!({ "output": output }))?;
+                        }
+
+                        self.emit_progress(
+                            &execution_id,
+                            stage_index,
+                            "stage-completed",
+                            &format!("ステージ完了: {}", stage.name),
+                        );
+                    }
+                    Err(e) => {
+                        log::error("PipelineRunner", &format!("Stage {} failed: {}", stage_index, e));
+
+                        // ステージ失敗
+                        {
+                            let executor = self.executor.lock();
+                            executor.fail_stage_at(&execution_id, stage_index, e.to_string())?;
+                        }
+
+                        self.emit_progress(
+                            &execution_id,
+                            stage_index,
+                            "stage-failed",
+                            &format!("ステージ失敗: {} - {}", stage.name, e),
+                        );
+
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+
+            // チェックポイントを保存（失敗時も成功した分岐の出力は残す）
+            let checkpoint = {
+                let mut ctx = self.contexts.lock();
+                ctx.get_mut(&execution_id).map(|c| {
+                    c.current_stage = group.iter().max().map_or(c.current_stage, |i| i + 1);
+                    c.clone()
+                })
+            };
+            if let Some(Err(e)) = checkpoint.map(|c| c.save_checkpoint()) {
+                log::warn("PipelineRunner", &format!("Failed to save checkpoint: {}", e));
+            }
+
+            if let Some(e) = first_error {
+                return Err(e);
+            }
+        }
+
+        // 最終的な実行状態を取得
+        let final_execution = {
+            let executor = self.executor.lock();
+            executor.get_execution(&execution_id)
+                .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.clone()))?
+        };
+
+        self.emit_progress(&execution_id, pipeline.stages.len() - 1, "pipeline-completed", "パイプライン完了");
+
+        log::info("PipelineRunner", &format!(
+            "Pipeline completed: {} with status {:?}",
+            execution_id, final_execution.status
+        ));
+
+        Ok(final_execution)
+    }
+
+    /// 一時停止中なら再開されるまで待つ（待機中にキャンセルされたらエラー）
+    async fn wait_while_paused(&self, execution_id: &str) -> Result<(), RunnerError> {
+        loop {
+            let status = self.executor.lock().get_execution(execution_id).map(|e| e.status);
+            match status {
+                Some(PipelineStatus::Paused) => tokio::time::sleep(PAUSE_POLL_INTERVAL).await,
+                Some(PipelineStatus::Cancelled) => {
+                    return Err(RunnerError::StageFailed("Pipeline cancelled".to_string()))
+                }
+                Some(_) => return Ok(()),
+                None => return Err(RunnerError::ExecutionNotFound(execution_id.to_string())),
+            }
+        }
+    }
+
+    /// ステージ出力の後処理（翻訳ステージの用語集チェック）
+    fn postprocess_output(&self, execution_id: &str, stage: &PipelineStage, output: String) -> String {
+        // 2言語目以降は `translate-subtitles:<lang>`
+        let Some(suffix) = stage.name.strip_prefix("translate-subtitles") else {
+            return output;
+        };
+        if !(suffix.is_empty() || suffix.starts_with(':')) || output.starts_with("[FALLBACK") {
+            return output;
+        }
+        self.enforce_glossary(execution_id, suffix.strip_prefix(':'), output)
+    }
+
+    /// 翻訳結果に用語集を適用する
+    ///
+    /// 誤訳候補が見つかった用語は訳語に置換し、それ以外の違反はフラグとして
+    /// `glossary_report.json`（2言語目以降は `glossary_report.<lang>.json`）とイベントで通知する。
+    fn enforce_glossary(&self, execution_id: &str, target_lang: Option<&str>, output: String) -> String {
+        let (project, sources, output_dir) = {
+            let ctx = self.contexts.lock();
+            let Some(c) = ctx.get(execution_id) else {
+                return output;
+            };
+            let project = c.input["project"].as_str().unwrap_or(DEFAULT_PROJECT).to_string();
+            let sources = c.stage_outputs.get("parse-subtitles")
+                .map(|s| parse_translated_text(s))
+                .unwrap_or_default();
+            let output_dir = c.input["output_dir"].as_str().map(|s| s.to_string());
+            (project, sources, output_dir)
+        };
+
+        let glossary = self.glossaries.lock().get(&project);
+        if glossary.is_empty() || sources.is_empty() {
+            return output;
+        }
+
+        let mut translations = parse_translated_text(&output);
+        let report = glossary.enforce(&sources, &mut translations);
+
+        log::info("PipelineRunner", &format!(
+            "Glossary check ({}): {} corrected, {} flagged",
+            project, report.corrected, report.flagged
+        ));
+
+        if let Some(dir) = output_dir {
+            let report_path = match target_lang {
+                Some(lang) => format!("{}/glossary_report.{}.json", dir, lang),
+                None => format!("{}/glossary_report.json", dir),
+            };
+            if let Err(e) = serde_json::to_string_pretty(&report)
+                .map_err(std::io::Error::from)
+                .and_then(|json| std::fs::write(&report_path, json))
+            {
+                log::warn("PipelineRunner", &format!("Failed to save glossary report: {}", e));
+            }
+        }
+
+        let corrected = report.corrected > 0;
+        if let Some(ref h) = *self.app_handle.lock() {
+            let payload = GlossaryReportPayload {
+                execution_id: execution_id.to_string(),
+                project,
+                target_lang: target_lang.map(str::to_string),
+                report,
+            };
+            if let Err(e) = h.emit_recorded("pipeline:glossary_report", &payload) {
+                log::error("PipelineRunner", &format!("Failed to emit glossary report: {:?}", e));
+            }
+        }
+
+        if corrected {
+            format_translated_text(&translations)
+        } else {
+            output
+        }
+    }
+
+    /// 単一ステージを実行（リトライポリシーがあれば失敗時に再試行する）
+    async fn execute_stage(
+        &self,
+        execution_id: &str,
+        stage: &PipelineStage,
+        stage_index: usize,
+    ) -> Result<String, RunnerError> {
+        let mut attempt = 1;
+        loop {
+            let error = match self.execute_stage_attempt(execution_id, stage, stage_index).await {
+                Ok(output) => return Ok(output),
+                Err(e) => e,
+            };
+            let Some(ref retry) = stage.retry else {
+                return Err(error);
+            };
+            if !retry.should_retry(attempt, &error.to_string()) {
+                return Err(error);
+            }
+
+            let delay = retry.backoff(attempt);
+            log::warn("PipelineRunner", &format!(
+                "Stage {} ({}) failed on attempt {}/{}: {} - retrying in {}ms",
+                stage_index, stage.name, attempt, retry.max_attempts, error, delay.as_millis()
+            ));
+            self.emit_progress(
+                execution_id,
+                stage_index,
+                "stage-retrying",
+                &format!("ステージ再試行 ({}/{}): {} - {}", attempt + 1, retry.max_attempts, stage.name, error),
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// ステージを1回実行
+    ///
+    /// 実行モード:
+    /// - RUST_DIRECT: Rust直接実行（字幕DL、VTT解析、音声生成）
+    /// - その他: ステージのエージェントアドレスで決まるエージェントで実行（`AgentTarget`）
+    async fn execute_stage_attempt(
+        &self,
+        execution_id: &str,
+        stage: &PipelineStage,
+        stage_index: usize,
+    ) -> Result<String, RunnerError> {
+        log::info("PipelineRunner", &format!("Starting stage {} ({})", stage_index, stage.name));
+
+        let target = AgentTarget::from_address(&stage.agent);
+        match stage.prompt_template.as_deref() {
+            Some(template) if template.starts_with("RUST_DIRECT:") => {
+                return self.execute_rust_direct(template, execution_id).await;
+            }
+            _ if target == AgentTarget::RustDirect => {
+                return Err(RunnerError::StageFailed(format!("Stage {} has no RUST_DIRECT parameters", stage.name)));
+            }
+            _ => {}
+        }
+
+        self.execute_agent_stage(execution_id, stage, stage_index, &target).await
+    }
+
+    /// Rust直接実行（字幕ダウンロード、VTT解析、音声生成）
+    async fn execute_rust_direct(
+        &self,
+        template: &str,
+        execution_id: &str,
+    ) -> Result<String, RunnerError> {
+        let json_str = template.strip_prefix("RUST_DIRECT:")
+            .ok_or_else(|| RunnerError::StageFailed("Invalid RUST_DIRECT format".to_string()))?;
+
+        let mut params: Value = serde_json::from_str(json_str)
+            .map_err(|e| RunnerError::StageFailed(format!("Invalid JSON in RUST_DIRECT: {}", e)))?;
+
+        // テンプレートのプレースホルダー（`{{output_dir}}` など）を入力で置き換える
+        if let Some(input) = self.get_context(execution_id).map(|c| c.input) {
+            fill_input_placeholders(&mut params, &input);
+        }
+
+        let stage = params["stage"].as_str().unwrap_or("");
+
+        match stage {
+            "download" => {
+                self.execute_download_stage(&params).await
+            }
+            "import" => {
+                self.execute_import_stage(&params).await
+            }
+            "transcribe" => {
+                self.execute_transcribe_stage(execution_id, &params).await
+            }
+            "diarize" => {
+                self.execute_diarize_stage(&params).await
+            }
+            "parse" => {
+                self.execute_parse_stage(execution_id, &params).await
+            }
+            "translate" => {
+                self.execute_translate_stage(execution_id, &params).await
+            }
+            "verify" => {
+                self.execute_verify_stage(execution_id, &params).await
+            }
+            "quality_check" => {
+                self.execute_quality_stage(execution_id, &params).await
+            }
+            "content_filter" => {
+                self.execute_content_filter_stage(execution_id, &params).await
+            }
+            "review" => {
+                self.execute_review_stage(execution_id, &params).await
+            }
+            "voicevox" => {
+                self.execute_voicevox_stage(execution_id, &params).await
+            }
+            "assemble" => {
+                self.execute_assemble_stage(&params).await
+            }
+            "mux" => {
+                self.execute_mux_stage(execution_id, &params).await
+            }
+            _ => {
+                Err(RunnerError::StageFailed(format!("Unknown RUST_DIRECT stage: {}", stage)))
+            }
+        }
+    }
+
+    /// Stage1: 字幕ダウンロード
+    async fn execute_download_stage(&self, params: &Value) -> Result<String, RunnerError> {
+        let url = params["url"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing url".to_string()))?;
+        let lang = params["lang"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing lang".to_string()))?;
+        let output_dir = params["output_dir"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
+
+        log::info("PipelineRunner", &format!("Stage1: Downloading subtitle from {} [{}]", url, lang));
+
+        // 空き容量チェック（字幕・中間ファイル分）
+        preflight::ensure_disk_space(std::path::Path::new(output_dir), preflight::SUBTITLE_STAGE_BYTES)?;
+
+        let url_owned = url.to_string();
+        let lang_owned = lang.to_string();
+        let output_dir_owned = output_dir.to_string();
+        let downloader = youtube_downloader(params);
+
+        let result = tokio::task::spawn_blocking(move || {
+            downloader.download_subtitle(&url_owned, &output_dir_owned, &lang_owned)
+        }).await.map_err(|e| RunnerError::StageFailed(e.to_string()))?;
+
+        match result {
+            Ok(download_result) => {
+                log::info("PipelineRunner", &format!(
+                    "Stage1 complete: {} ({} bytes)",
+                    download_result.file_path, download_result.size
+                ));
+                Ok(download_result.file_path)
+            }
+            Err(YoutubeError::SubtitleNotFound { lang }) if params["allow_missing"].as_bool().unwrap_or(false) => {
+                // どの言語の字幕もない場合だけ文字起こしに回す
+                let url_owned = url.to_string();
+                let downloader = youtube_downloader(params);
+                let available = tokio::task::spawn_blocking(move || {
+                    downloader.list_available_subs(&url_owned)
+                }).await.map_err(|e| RunnerError::StageFailed(e.to_string()))?
+                    .unwrap_or_default();
+                if !available.is_empty() {
+                    return Err(RunnerError::StageFailed(format!(
+                        "{}の字幕が見つかりません（利用可能: {}）", lang, available.join(", ")
+                    )));
+                }
+                log::info("PipelineRunner", "Stage1: no subtitles in any language, falling back to transcription");
+                Ok(String::new())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Stage1b: 文字起こし（字幕がない場合のフォールバック）
+    ///
+    /// ダウンロードステージが字幕を取得できていればそのまま渡す。
+    async fn execute_transcribe_stage(&self, execution_id: &str, params: &Value) -> Result<String, RunnerError> {
+        let source_stage = params["source_stage"].as_str().unwrap_or("download-subtitles");
+        let downloaded = {
+            let ctx = self.contexts.lock();
+            ctx.get(execution_id)
+                .and_then(|c| c.stage_outputs.get(source_stage).cloned())
+                .unwrap_or_default()
+        };
+        if !downloaded.trim().is_empty() {
+            return Ok(downloaded);
+        }
+
+        let url = params["url"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing url".to_string()))?
+            .to_string();
+        let output_dir = params["output_dir"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?
+            .to_string();
+        let config: TranscriptionConfig = serde_json::from_value(params["transcription"].clone())
+            .unwrap_or_default();
+
+        log::info("PipelineRunner", &format!("Stage1b: Transcribing audio of {}", url));
+
+        let downloader = youtube_downloader(params);
+        let vtt_path = tokio::task::spawn_blocking(move || -> Result<String, RunnerError> {
+            let audio = downloader.download_audio(&url, &output_dir)?;
+            Ok(config.transcribe(&audio, &output_dir)?)
+        }).await.map_err(|e| RunnerError::StageFailed(e.to_string()))??;
+
+        log::info("PipelineRunner", &format!("Stage1b complete: {}", vtt_path));
+        Ok(vtt_path)
+    }
+
+    /// Stage1（ローカル版）: 字幕ファイルの取り込み
+    ///
+    /// SRTの場合はVTTに変換して出力ディレクトリに保存する。
+    async fn execute_import_stage(&self, params: &Value) -> Result<String, RunnerError> {
+        let path = params["path"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing path".to_string()))?;
+        let output_dir = params["output_dir"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
+
+        log::info("PipelineRunner", &format!("Stage1: Importing subtitle file {}", path));
+
+        preflight::ensure_disk_space(std::path::Path::new(output_dir), preflight::SUBTITLE_STAGE_BYTES)?;
+
+        let source = std::path::Path::new(path);
+        let content = std::fs::read_to_string(source)?;
+        let vtt = match SubtitleFormat::from_path(path) {
+            SubtitleFormat::Srt => {
+                let segments = SrtParser::parse(&content)
+                    .map_err(|e| RunnerError::VttParse(e.to_string()))?;
+                VttParser::rebuild_vtt(&segments, &[])
+            }
+            SubtitleFormat::Vtt => content,
+        };
+
+        std::fs::create_dir_all(output_dir)?;
+        let stem = source.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "subtitle".to_string());
+        let dest = format!("{}/{}.vtt", output_dir, stem);
+        std::fs::write(&dest, vtt)?;
+
+        log::info("PipelineRunner", &format!("Stage1 complete: imported to {}", dest));
+        Ok(dest)
+    }
+
+    /// Stage2b: 話者分離
+    ///
+    /// `segments.json` の各セグメントに話者ラベルを付け、話者とVOICEVOX話者の対応を
+    /// `speakers.json` に保存する。音声生成ステージはこの対応で声を切り替える。
+    async fn execute_diarize_stage(&self, params: &Value) -> Result<String, RunnerError> {
+        let url = params["url"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing url".to_string()))?
+            .to_string();
+        let output_dir = params["output_dir"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?
+            .to_string();
+        let config: DiarizationConfig = serde_json::from_value(params["diarization"].clone())
+            .unwrap_or_default();
 
-        // パイプライン定義を取得
-        let pipeline = {
-            let executor = self.executor.lock();
-            executor.get_pipeline(pipeline_id)
-                .ok_or_else(|| RunnerError::ExecutionNotFound(pipeline_id.to_string()))?
+        log::info("PipelineRunner", &format!("Stage2b: Diarizing speakers of {}", url));
+
+        let segments_path = format!("{}/segments.json", output_dir);
+        let mut segments: Vec<SubtitleSegment> = serde_json::from_str(&std::fs::read_to_string(&segments_path)?)?;
+
+        let dir = output_dir.clone();
+        let runner_config = config.clone();
+        let downloader = youtube_downloader(params);
+        let turns = tokio::task::spawn_blocking(move || -> Result<_, RunnerError> {
+            let audio = downloader.download_audio(&url, &dir)?;
+            Ok(runner_config.run(&audio, &format!("{}/diarization.rttm", dir))?)
+        }).await.map_err(|e| RunnerError::StageFailed(e.to_string()))??;
+
+        diarization::label_segments(&mut segments, &turns);
+        let voices = config.assign_voices(&segments);
+
+        std::fs::write(&segments_path, serde_json::to_string(&segments)?)?;
+        std::fs::write(format!("{}/{}", output_dir, SPEAKERS_FILE), serde_json::to_string_pretty(&voices)?)?;
+
+        log::info("PipelineRunner", &format!(
+            "Stage2b complete: {} speakers ({:?})", voices.len(), voices
+        ));
+        Ok(format!("{} speakers detected", voices.len()))
+    }
+
+    /// Stage2: VTT解析
+    async fn execute_parse_stage(
+        &self,
+        execution_id: &str,
+        params: &Value,
+    ) -> Result<String, RunnerError> {
+        let output_dir = params["output_dir"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
+
+        let source_stage = params["source_stage"].as_str().unwrap_or("download-subtitles");
+
+        // 前のステージから字幕ファイルパスを取得
+        let vtt_path = {
+            let ctx = self.contexts.lock();
+            let c = ctx.get(execution_id)
+                .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
+            c.stage_outputs.get(source_stage)
+                .cloned()
+                .ok_or_else(|| RunnerError::StageFailed("No subtitle file from stage1".to_string()))?
+        };
+
+        log::info("PipelineRunner", &format!("Stage2: Parsing VTT file: {}", vtt_path));
+
+        // VTT/SRTをパース
+        let segments = parse_subtitle_file(&vtt_path)
+            .map_err(|e| RunnerError::VttParse(e.to_string()))?;
+
+        log::info("PipelineRunner", &format!("Stage2: Parsed {} segments", segments.len()));
+
+        // 翻訳用テキストを生成
+        let translation_text = VttParser::to_translation_text(&segments);
+
+        // セグメント情報をJSONとして保存（後で使用）
+        let segments_json = serde_json::to_string(&segments)
+            .map_err(|e| RunnerError::Json(e))?;
+
+        let segments_path = format!("{}/segments.json", output_dir);
+        std::fs::write(&segments_path, &segments_json)
+            .map_err(|e| RunnerError::Io(e))?;
+
+        log::info("PipelineRunner", &format!("Stage2 complete: saved segments to {}", segments_path));
+
+        Ok(translation_text)
+    }
+
+    /// Stage3: 翻訳（プロバイダー非依存）
+    ///
+    /// 長い字幕は重なり付きチャンクに分割し、欠落・重複した番号は
+    /// 最大 `MAX_REPAIR_ATTEMPTS` 回まで再翻訳する。埋まらない番号は原文のまま残す。
+    async fn execute_translate_stage(
+        &self,
+        execution_id: &str,
+        params: &Value,
+    ) -> Result<String, RunnerError> {
+        let target_lang = params["target_lang"].as_str().unwrap_or(DEFAULT_TARGET_LANG);
+        let style: Option<SpeechStyle> = serde_json::from_value(params["style"].clone()).unwrap_or_default();
+
+        let (sources, input) = {
+            let ctx = self.contexts.lock();
+            let c = ctx.get(execution_id)
+                .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
+            let sources = c.stage_outputs.get("parse-subtitles")
+                .map(|s| parse_indexed_text(s))
+                .ok_or_else(|| RunnerError::StageFailed("No parsed subtitles from stage2".to_string()))?;
+            (sources, c.input.clone())
+        };
+
+        let translator = self.create_stage_translator(params).await?;
+
+        // 表示時間から各セグメントの最大文字数を求める
+        let max_chars = match serde_json::from_value::<Option<LengthConfig>>(params["length"].clone()) {
+            Ok(Some(config)) => {
+                let output_dir = input["output_dir"].as_str().unwrap_or(".");
+                let segments_json = std::fs::read_to_string(format!("{}/segments.json", output_dir))?;
+                config.limits(&serde_json::from_str::<Vec<SubtitleSegment>>(&segments_json)?)
+            }
+            _ => BTreeMap::new(),
+        };
+
+        let project = input["project"].as_str().unwrap_or(DEFAULT_PROJECT);
+        let options = TranslateOptions {
+            source_lang: input["subtitle_lang"].as_str().map(|s| s.to_string()),
+            target_lang: target_lang.to_string(),
+            glossary: self.glossaries.lock().get(project),
+            style,
+            max_chars,
         };
 
-        // 各ステージを実行
-        for (stage_index, stage) in pipeline.stages.iter().enumerate() {
+        log::info("PipelineRunner", &format!(
+            "Stage3: Translating {} segments with {:?} ({})",
+            sources.len(), translator.provider(), target_lang
+        ));
+
+        // 翻訳メモリにある原文は再利用し、新しい・変更されたセグメントだけを翻訳する
+        let skip_memory = params["skip_memory"].as_bool().unwrap_or(false);
+        let memory_context = translation_memory::context_key(
+            &[
+                &serde_json::to_string(&translator.provider())?,
+                params["model"].as_str().unwrap_or_default(),
+                target_lang,
+                &serde_json::to_string(&options.style)?,
+            ],
+            &options.glossary.to_prompt_section(),
+        );
+        let (cached, pending): (BTreeMap<usize, String>, Vec<(usize, String)>) = if skip_memory {
+            (BTreeMap::new(), sources.clone())
+        } else {
+            let memory = self.translation_memory.lock();
+            let mut cached = BTreeMap::new();
+            let mut pending = Vec::new();
+            for (index, source) in &sources {
+                match memory.lookup(&memory_context, source) {
+                    Some(text) => { cached.insert(*index, text.to_string()); }
+                    None => pending.push((*index, source.clone())),
+                }
+            }
+            (cached, pending)
+        };
+        if !cached.is_empty() {
             log::info("PipelineRunner", &format!(
-                "Executing stage {}: {}",
-                stage_index, stage.name
+                "Stage3: reusing {} of {} segments from translation memory", cached.len(), sources.len()
             ));
-
             self.emit_progress(
-                &execution_id,
-                stage_index,
-                "stage-started",
-                &format!("ステージ開始: {}", stage.name),
+                execution_id,
+                self.current_stage_index(execution_id),
+                "translation-memory",
+                &format!("翻訳メモリから{}件を再利用（翻訳: {}件）", cached.len(), pending.len()),
             );
+        }
 
-            // ステージを実行
-            match self.execute_stage(&execution_id, stage, stage_index).await {
-                Ok(output) => {
-                    // 出力をコンテキストに保存
-                    {
-                        let mut ctx = self.contexts.lock();
-                        if let Some(c) = ctx.get_mut(&execution_id) {
-                            c.stage_outputs.insert(stage.name.clone(), output.clone());
-                        }
-                    }
+        let mut translations = if pending.is_empty() {
+            BTreeMap::new()
+        } else {
+            self.translate_all(execution_id, translator.as_ref(), &pending, &input, &options).await?
+        };
+        translations.extend(cached);
 
-                    // ステージ完了
-                    {
-                        let executor = self.executor.lock();
-                        executor.complete_stage(&execution_id, serde_json::json!({ "output": output }))?;
-                    }
+        // 欠落・重複した番号だけを再翻訳
+        for attempt in 1..=MAX_REPAIR_ATTEMPTS {
+            let missing: Vec<(usize, String)> = sources
+                .iter()
+                .filter(|(index, _)| !translations.contains_key(index))
+                .cloned()
+                .collect();
+            if missing.is_empty() {
+                break;
+            }
 
-                    self.emit_progress(
-                        &execution_id,
-                        stage_index,
-                        "stage-completed",
-                        &format!("ステージ完了: {}", stage.name),
-                    );
-                }
-                Err(e) => {
-                    log::error("PipelineRunner", &format!("Stage {} failed: {}", stage_index, e));
+            log::warn("PipelineRunner", &format!(
+                "Stage3: {} segments missing, re-requesting (attempt {})", missing.len(), attempt
+            ));
+            self.emit_progress(
+                execution_id,
+                self.current_stage_index(execution_id),
+                "translation-repair",
+                &format!("翻訳の欠落・重複を再翻訳中: {}件（{}回目）", missing.len(), attempt),
+            );
 
-                    // ステージ失敗
-                    {
-                        let executor = self.executor.lock();
-                        executor.fail_stage(&execution_id, e.to_string())?;
-                    }
+            translations.extend(translator.translate_segments(&missing, &options).await?);
+        }
 
-                    self.emit_progress(
-                        &execution_id,
-                        stage_index,
-                        "stage-failed",
-                        &format!("ステージ失敗: {} - {}", stage.name, e),
-                    );
+        // 上限を超えた訳文を再翻訳（短くなった場合のみ採用）
+        // 文字数の指示を解釈しないプロバイダーでは再送しても変わらないので行わない
+        for attempt in 1..=MAX_REPAIR_ATTEMPTS {
+            let over = length::over_long(&translations, &options.max_chars);
+            if over.is_empty() {
+                break;
+            }
+            if !translator.honors_length_limits() {
+                log::info("PipelineRunner", &format!(
+                    "Stage3: {} segments exceed their length budget; {:?} ignores length limits, keeping them",
+                    over.len(), translator.provider()
+                ));
+                break;
+            }
+
+            log::warn("PipelineRunner", &format!(
+                "Stage3: {} segments exceed their length budget, re-requesting (attempt {})", over.len(), attempt
+            ));
+            self.emit_progress(
+                execution_id,
+                self.current_stage_index(execution_id),
+                "length-repair",
+                &format!("長すぎる訳文を再翻訳中: {}件（{}回目）", over.len(), attempt),
+            );
 
-                    return Err(e);
+            let retry: Vec<(usize, String)> = sources
+                .iter()
+                .filter(|(index, _)| over.contains(index))
+                .cloned()
+                .collect();
+            for (index, text) in translator.translate_segments(&retry, &options).await? {
+                let shorter = translations
+                    .get(&index)
+                    .map(|previous| length::spoken_length(&text) < length::spoken_length(previous))
+                    .unwrap_or(true);
+                if shorter {
+                    translations.insert(index, text);
                 }
             }
         }
 
-        // 最終的な実行状態を取得
-        let final_execution = {
-            let executor = self.executor.lock();
-            executor.get_execution(&execution_id)
-                .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.clone()))?
+        let entries: Vec<(usize, String)> = {
+            let mut memory = self.translation_memory.lock();
+            let entries = sources
+                .into_iter()
+                .map(|(index, source)| match translations.remove(&index) {
+                    Some(text) => {
+                        memory.insert(&memory_context, &source, &text);
+                        (index, text)
+                    }
+                    None => {
+                        log::warn("PipelineRunner", &format!(
+                            "Stage3: segment {} still missing after repair, keeping source text", index
+                        ));
+                        (index, source)
+                    }
+                })
+                .collect();
+            if let Err(e) = memory.save() {
+                log::warn("PipelineRunner", &format!("Failed to save translation memory: {}", e));
+            }
+            entries
         };
 
-        self.emit_progress(&execution_id, pipeline.stages.len() - 1, "pipeline-completed", "パイプライン完了");
+        if let Some(usage) = translator.usage().await {
+            self.emit_usage(execution_id, translator.provider(), usage);
+        }
+
+        log::info("PipelineRunner", &format!("Stage3 complete: {} segments translated", entries.len()));
+        Ok(format_indexed_text(&entries))
+    }
+
+    /// ステージパラメータ（provider・model）から翻訳プロバイダーを作成し、利用可能か確認する
+    ///
+    /// `agent` にエージェントアドレス（`codex@localhost` など）を指定すると `provider` より優先する。
+    async fn create_stage_translator(&self, params: &Value) -> Result<Box<dyn Translator>, RunnerError> {
+        use super::message::AgentAddress;
 
+        let provider: TranslationProvider = match params["agent"].as_str() {
+            Some(agent) => AgentAddress::parse(agent)
+                .and_then(|address| TranslationProvider::from_address(&address))
+                .ok_or_else(|| RunnerError::InvalidOption(format!("No translator for agent: {}", agent)))?,
+            None => serde_json::from_value(params["provider"].clone()).unwrap_or_default(),
+        };
+        self.create_translator(provider, params["model"].as_str()).await
+    }
+
+    /// 翻訳プロバイダーを作成し、利用可能か確認する
+    pub async fn create_translator(
+        &self,
+        provider: TranslationProvider,
+        model: Option<&str>,
+    ) -> Result<Box<dyn Translator>, RunnerError> {
+        let translator = {
+            let secrets = self.secrets.lock();
+            create_translator(provider, model, self.cli_executor.clone(), &secrets)?
+        };
+        translator.check_available().await?;
+        Ok(translator)
+    }
+
+    /// 必要ならチャンクに分割して全セグメントを翻訳する
+    async fn translate_all(
+        &self,
+        execution_id: &str,
+        translator: &dyn Translator,
+        sources: &[(usize, String)],
+        input: &Value,
+        options: &TranslateOptions,
+    ) -> Result<BTreeMap<usize, String>, RunnerError> {
+        // チャンク分割（プロバイダーのコンテキスト上限も考慮）
+        let mut config: ChunkConfig = serde_json::from_value(input["chunking"].clone()).unwrap_or_default();
+        if let Some(max) = translator.max_chunk_size() {
+            config.chunk_size = config.chunk_size.min(max);
+        }
+
+        if translator.uses_chunking() && sources.len() > config.chunk_size {
+            self.translate_in_chunks(execution_id, translator, sources, &config, options).await
+        } else {
+            Ok(translator.translate_segments(sources, options).await?)
+        }
+    }
+
+    /// 重なり付きチャンクに分割して翻訳し、インデックスで再結合する
+    async fn translate_in_chunks(
+        &self,
+        execution_id: &str,
+        translator: &dyn Translator,
+        sources: &[(usize, String)],
+        config: &ChunkConfig,
+        options: &TranslateOptions,
+    ) -> Result<BTreeMap<usize, String>, RunnerError> {
+        use futures::StreamExt;
+
+        let chunks = chunking::split_into_chunks(sources.len(), config);
         log::info("PipelineRunner", &format!(
-            "Pipeline completed: {} with status {:?}",
-            execution_id, final_execution.status
+            "Stage3: {} segments in {} chunks (overlap {}, concurrency {})",
+            sources.len(), chunks.len(), config.overlap, config.concurrency
         ));
 
-        Ok(final_execution)
+        let stage_index = self.current_stage_index(execution_id);
+        // 複数チャンクを同時に翻訳しても結果はチャンク順に受け取る
+        let requests: Vec<_> = chunks.iter().map(|chunk| async move {
+            let segments = &sources[chunk.start..chunk.end];
+            translator.translate_segments(segments, options).await.map(|t| (chunk, segments, t))
+        }).collect();
+        let mut translated_chunks = futures::stream::iter(requests).buffered(config.concurrency.max(1));
+
+        let mut results: Vec<(TranslationChunk, BTreeMap<usize, String>)> = Vec::new();
+        while let Some(translated) = translated_chunks.next().await {
+            let (chunk, segments, translated) = translated?;
+
+            // セグメント番号 -> 位置
+            let by_position = translated
+                .into_iter()
+                .filter_map(|(index, text)| {
+                    segments
+                        .iter()
+                        .position(|(src_index, _)| *src_index == index)
+                        .map(|offset| (chunk.start + offset, text))
+                })
+                .collect();
+            results.push((chunk.clone(), by_position));
+
+            self.emit_progress(
+                execution_id,
+                stage_index,
+                "chunk-completed",
+                &format!("翻訳チャンク {}/{} 完了", results.len(), chunks.len()),
+            );
+        }
+
+        // 欠落した位置は含めず、呼び出し側で再翻訳させる
+        Ok(chunking::reassemble(sources.len(), &results)
+            .into_iter()
+            .zip(sources)
+            .filter_map(|(translated, (index, _))| translated.map(|t| (*index, t)))
+            .collect())
     }
 
-    /// 単一ステージを実行
+    /// 翻訳検証: 欠落・未翻訳のセグメントを検出し、該当分だけを再翻訳する
     ///
-    /// 実行モード:
-    /// - RUST_DIRECT: Rust直接実行（字幕DL、VTT解析、音声生成）
-    /// - その他: Claude Code実行（翻訳）
-    async fn execute_stage(
+    /// 修復できなかった番号は原文のまま残し、番号は原文と必ず1対1にそろえる。
+    async fn execute_verify_stage(
         &self,
         execution_id: &str,
-        stage: &PipelineStage,
-        stage_index: usize,
+        params: &Value,
     ) -> Result<String, RunnerError> {
-        log::info("PipelineRunner", &format!("Starting stage {} ({})", stage_index, stage.name));
+        let output_dir = params["output_dir"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
+        let target_lang = params["target_lang"].as_str().unwrap_or(DEFAULT_TARGET_LANG);
+        let source_stage = params["source_stage"].as_str().unwrap_or("translate-subtitles");
+        let style: Option<SpeechStyle> = serde_json::from_value(params["style"].clone()).unwrap_or_default();
 
-        // Rust直接実行チェック
-        if let Some(ref template) = stage.prompt_template {
-            if template.starts_with("RUST_DIRECT:") {
-                return self.execute_rust_direct(template, execution_id).await;
+        let (sources, mut translations, input) = {
+            let ctx = self.contexts.lock();
+            let c = ctx.get(execution_id)
+                .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
+            let sources = c.stage_outputs.get("parse-subtitles")
+                .map(|s| parse_indexed_text(s))
+                .ok_or_else(|| RunnerError::StageFailed("No parsed subtitles from stage2".to_string()))?;
+            let translations: BTreeMap<usize, String> = c.stage_outputs.get(source_stage)
+                .map(|s| parse_indexed_text(s).into_iter().collect())
+                .ok_or_else(|| RunnerError::StageFailed(format!("No translated text from {}", source_stage)))?;
+            (sources, translations, c.input.clone())
+        };
+
+        let mut report = validation::verify_translation(&sources, &translations, target_lang);
+        let broken = report.broken();
+
+        if !broken.is_empty() {
+            let translator = self.create_stage_translator(params).await?;
+            let project = input["project"].as_str().unwrap_or(DEFAULT_PROJECT);
+            let options = TranslateOptions {
+                source_lang: input["subtitle_lang"].as_str().map(|s| s.to_string()),
+                target_lang: target_lang.to_string(),
+                glossary: self.glossaries.lock().get(project),
+                style,
+                ..Default::default()
+            };
+
+            let mut remaining = broken.clone();
+            for attempt in 1..=MAX_REPAIR_ATTEMPTS {
+                log::warn("PipelineRunner", &format!(
+                    "Verify: {} broken segments, re-requesting (attempt {})", remaining.len(), attempt
+                ));
+                self.emit_progress(
+                    execution_id,
+                    self.current_stage_index(execution_id),
+                    "translation-verify",
+                    &format!("欠落・未翻訳のセグメントを再翻訳中: {}件（{}回目）", remaining.len(), attempt),
+                );
+
+                let retry: Vec<(usize, String)> = sources
+                    .iter()
+                    .filter(|(index, _)| remaining.contains(index))
+                    .cloned()
+                    .collect();
+                translations.extend(translator.translate_segments(&retry, &options).await?);
+
+                remaining = validation::verify_translation(&sources, &translations, target_lang).broken();
+                if remaining.is_empty() {
+                    break;
+                }
+            }
+
+            report.repaired = broken.iter().copied().filter(|i| !remaining.contains(i)).collect();
+            report.unresolved = remaining;
+        }
+
+        log::info("PipelineRunner", &format!(
+            "Verify ({}): {} missing, {} untranslated, {} repaired",
+            target_lang, report.missing.len(), report.untranslated.len(), report.repaired.len()
+        ));
+
+        let report_path = format!("{}/verification_report.{}.json", output_dir, target_lang);
+        std::fs::write(&report_path, serde_json::to_string_pretty(&report)?)?;
+
+        if let Some(ref h) = *self.app_handle.lock() {
+            let payload = VerificationPayload {
+                execution_id: execution_id.to_string(),
+                target_lang: target_lang.to_string(),
+                report,
+            };
+            if let Err(e) = h.emit_recorded("pipeline:verification_report", &payload) {
+                log::error("PipelineRunner", &format!("Failed to emit verification report: {:?}", e));
             }
         }
 
-        // Claude Code実行
-        self.execute_claude_code(execution_id, stage, stage_index).await
+        let entries: Vec<(usize, String)> = sources
+            .into_iter()
+            .map(|(index, source)| {
+                let text = translations.remove(&index).filter(|t| !t.trim().is_empty()).unwrap_or(source);
+                (index, text)
+            })
+            .collect();
+        Ok(format_indexed_text(&entries))
     }
 
-    /// Rust直接実行（字幕ダウンロード、VTT解析、音声生成）
-    async fn execute_rust_direct(
+    /// 品質チェック: 訳文を原文の言語へ逆翻訳し、類似度の低いセグメントにフラグを付ける
+    ///
+    /// 出力は `QualityReport` のJSON（レビューステージが参照する）。
+    async fn execute_quality_stage(
         &self,
-        template: &str,
         execution_id: &str,
+        params: &Value,
     ) -> Result<String, RunnerError> {
-        let json_str = template.strip_prefix("RUST_DIRECT:")
-            .ok_or_else(|| RunnerError::StageFailed("Invalid RUST_DIRECT format".to_string()))?;
+        let output_dir = params["output_dir"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
+        let target_lang = params["target_lang"].as_str().unwrap_or(DEFAULT_TARGET_LANG);
+        let source_stage = params["source_stage"].as_str().unwrap_or("translate-subtitles");
+        let config: QualityConfig = serde_json::from_value(params["config"].clone()).unwrap_or_default();
 
-        let params: Value = serde_json::from_str(json_str)
-            .map_err(|e| RunnerError::StageFailed(format!("Invalid JSON in RUST_DIRECT: {}", e)))?;
+        let (sources, translated, input) = {
+            let ctx = self.contexts.lock();
+            let c = ctx.get(execution_id)
+                .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
+            let sources = c.stage_outputs.get("parse-subtitles")
+                .map(|s| parse_indexed_text(s))
+                .ok_or_else(|| RunnerError::StageFailed("No parsed subtitles from stage2".to_string()))?;
+            let translated = c.stage_outputs.get(source_stage)
+                .map(|s| parse_indexed_text(s))
+                .ok_or_else(|| RunnerError::StageFailed(format!("No translated text from {}", source_stage)))?;
+            (sources, translated, c.input.clone())
+        };
 
-        let stage = params["stage"].as_str().unwrap_or("");
+        let source_lang = config.source_lang.clone()
+            .or_else(|| input["subtitle_lang"].as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| "en".to_string());
 
-        match stage {
-            "download" => {
-                self.execute_download_stage(&params).await
+        log::info("PipelineRunner", &format!(
+            "Quality check: back-translating {} segments to {}", translated.len(), source_lang
+        ));
+
+        let translator = self.create_stage_translator(params).await?;
+        let options = TranslateOptions {
+            source_lang: Some(target_lang.to_string()),
+            target_lang: source_lang,
+            ..Default::default()
+        };
+        let back_translations = self
+            .translate_all(execution_id, translator.as_ref(), &translated, &input, &options)
+            .await?;
+
+        let translations: BTreeMap<usize, String> = translated.into_iter().collect();
+        let report = QualityReport::build(&sources, &translations, &back_translations, config.threshold);
+
+        log::info("PipelineRunner", &format!(
+            "Quality check: average similarity {:.2}, {} segments flagged (threshold {:.2})",
+            report.average, report.flagged.len(), report.threshold
+        ));
+
+        let json = serde_json::to_string_pretty(&report)?;
+        let report_file = params["report"].as_str().unwrap_or("quality_report.json");
+        std::fs::write(format!("{}/{}", output_dir, report_file), &json)?;
+
+        if let Some(ref h) = *self.app_handle.lock() {
+            let payload = QualityReportPayload {
+                execution_id: execution_id.to_string(),
+                target_lang: target_lang.to_string(),
+                report: report.clone(),
+            };
+            if let Err(e) = h.emit_recorded("pipeline:quality_report", &payload) {
+                log::error("PipelineRunner", &format!("Failed to emit quality report: {:?}", e));
             }
-            "parse" => {
-                self.execute_parse_stage(execution_id, &params).await
+        }
+
+        if let Some(max_ratio) = config.max_flagged_ratio {
+            if report.flagged_ratio() > max_ratio {
+                return Err(RunnerError::StageFailed(format!(
+                    "Quality check failed: {} of {} segments below similarity {:.2}",
+                    report.flagged.len(), report.segments.len(), report.threshold
+                )));
             }
-            "voicevox" => {
-                self.execute_voicevox_stage(execution_id, &params).await
+        }
+
+        Ok(json)
+    }
+
+    /// 翻訳API使用量を通知し、トークン数・コストがあれば実行の合計に加える
+    fn emit_usage(&self, execution_id: &str, provider: TranslationProvider, usage: TranslationUsage) {
+        log::info("PipelineRunner", &format!(
+            "Translation usage ({:?}): sent {} chars, period {:?}/{:?}",
+            provider, usage.characters, usage.character_count, usage.character_limit
+        ));
+        if let Some(ref tokens) = usage.tokens {
+            self.add_usage(execution_id, tokens);
+        }
+
+        if let Some(ref h) = *self.app_handle.lock() {
+            let payload = TranslationUsagePayload {
+                execution_id: execution_id.to_string(),
+                provider,
+                usage,
+            };
+            if let Err(e) = h.emit_recorded("pipeline:translation_usage", &payload) {
+                log::error("PipelineRunner", &format!("Failed to emit usage: {:?}", e));
             }
-            _ => {
-                Err(RunnerError::StageFailed(format!("Unknown RUST_DIRECT stage: {}", stage)))
+        }
+    }
+
+    /// バッチ進捗イベントを送信
+    fn emit_batch_progress(&self, payload: &BatchProgressPayload) {
+        if let Some(ref h) = *self.app_handle.lock() {
+            if let Err(e) = h.emit_recorded("pipeline:batch_progress", payload) {
+                log::error("PipelineRunner", &format!("Failed to emit batch progress: {:?}", e));
             }
         }
     }
 
-    /// Stage1: 字幕ダウンロード
-    async fn execute_download_stage(&self, params: &Value) -> Result<String, RunnerError> {
-        let url = params["url"].as_str()
-            .ok_or_else(|| RunnerError::StageFailed("Missing url".to_string()))?;
-        let lang = params["lang"].as_str()
-            .ok_or_else(|| RunnerError::StageFailed("Missing lang".to_string()))?;
+    /// 実行中のステージインデックス
+    fn current_stage_index(&self, execution_id: &str) -> usize {
+        self.get_execution(execution_id).map(|e| e.current_stage).unwrap_or(0)
+    }
+
+    /// コンテンツフィルター: 禁止語を伏せ字に置換し、LLMチェックで不適切な内容にフラグを付ける
+    async fn execute_content_filter_stage(
+        &self,
+        execution_id: &str,
+        params: &Value,
+    ) -> Result<String, RunnerError> {
         let output_dir = params["output_dir"].as_str()
             .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
+        let target_lang = params["target_lang"].as_str().unwrap_or(DEFAULT_TARGET_LANG);
+        let source_stage = params["source_stage"].as_str().unwrap_or("translate-subtitles");
+        let config: ContentFilterConfig = serde_json::from_value(params["config"].clone()).unwrap_or_default();
 
-        log::info("PipelineRunner", &format!("Stage1: Downloading subtitle from {} [{}]", url, lang));
-
-        let url_owned = url.to_string();
-        let lang_owned = lang.to_string();
-        let output_dir_owned = output_dir.to_string();
+        let mut translations = {
+            let ctx = self.contexts.lock();
+            let c = ctx.get(execution_id)
+                .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
+            c.stage_outputs.get(source_stage)
+                .map(|s| parse_indexed_text(s))
+                .ok_or_else(|| RunnerError::StageFailed(format!("No translated text from {}", source_stage)))?
+        };
 
-        let result = tokio::task::spawn_blocking(move || {
-            let downloader = YoutubeDownloader::new();
-            downloader.download_subtitle(&url_owned, &output_dir_owned, &lang_owned)
-        }).await.map_err(|e| RunnerError::Youtube(e.to_string()))?;
+        let mut report = FilterReport::default();
+        config.apply_word_list(&mut translations, &mut report);
 
-        match result {
-            Ok(download_result) => {
-                log::info("PipelineRunner", &format!(
-                    "Stage1 complete: {} ({} bytes)",
-                    download_result.file_path, download_result.size
-                ));
-                Ok(download_result.file_path)
+        if config.llm_check {
+            let prompt = content_filter::build_llm_check_prompt(&translations);
+            let checked = match self.create_stage_translator(params).await {
+                Ok(translator) => translator.complete(&prompt).await.map_err(RunnerError::from),
+                Err(e) => Err(e),
+            };
+            match checked {
+                Ok(output) => {
+                    for (index, reason) in parse_indexed_text(&output) {
+                        report.push(FilterMatch {
+                            segment: index,
+                            term: reason,
+                            detector: "llm".to_string(),
+                            masked: false,
+                        });
+                    }
+                }
+                Err(RunnerError::Translation(TranslatorError::NotAvailable(reason))) => {
+                    log::warn("PipelineRunner", &format!("Content filter: skipping LLM check ({})", reason));
+                }
+                Err(e) => return Err(e),
             }
-            Err(e) => {
-                Err(RunnerError::Youtube(e.to_string()))
+        }
+
+        log::info("PipelineRunner", &format!(
+            "Content filter ({}): {} masked, {} flagged", target_lang, report.masked, report.flagged
+        ));
+
+        let report_path = format!("{}/content_filter_report.{}.json", output_dir, target_lang);
+        std::fs::write(&report_path, serde_json::to_string_pretty(&report)?)?;
+
+        if let Some(ref h) = *self.app_handle.lock() {
+            let payload = ContentFilterPayload {
+                execution_id: execution_id.to_string(),
+                target_lang: target_lang.to_string(),
+                report,
+            };
+            if let Err(e) = h.emit_recorded("pipeline:content_filter_report", &payload) {
+                log::error("PipelineRunner", &format!("Failed to emit content filter report: {:?}", e));
             }
         }
+
+        Ok(format_indexed_text(&translations))
     }
 
-    /// Stage2: VTT解析
-    async fn execute_parse_stage(
+    /// 翻訳レビュー: 承認されるまで一時停止し、編集後の訳文を返す
+    async fn execute_review_stage(
         &self,
         execution_id: &str,
         params: &Value,
     ) -> Result<String, RunnerError> {
         let output_dir = params["output_dir"].as_str()
             .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
+        let source_stage = params["source_stage"].as_str().unwrap_or("translate-subtitles");
+        let quality_stage = params["quality_stage"].as_str().unwrap_or("quality-check");
+        let target_lang = params["target_lang"].as_str().map(str::to_string);
 
-        // 前のステージから字幕ファイルパスを取得
-        let vtt_path = {
+        let (sources, translations, quality) = {
             let ctx = self.contexts.lock();
             let c = ctx.get(execution_id)
                 .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
-            c.stage_outputs.get("download-subtitles")
-                .cloned()
-                .ok_or_else(|| RunnerError::StageFailed("No subtitle file from stage1".to_string()))?
+            let sources: HashMap<usize, String> = c.stage_outputs.get("parse-subtitles")
+                .map(|s| parse_indexed_text(s).into_iter().collect())
+                .unwrap_or_default();
+            let translations = c.stage_outputs.get(source_stage)
+                .map(|s| parse_indexed_text(s))
+                .ok_or_else(|| RunnerError::StageFailed("No translated text from stage3".to_string()))?;
+            let quality: Option<QualityReport> = c.stage_outputs.get(quality_stage)
+                .and_then(|s| serde_json::from_str(s).ok());
+            (sources, translations, quality)
         };
 
-        log::info("PipelineRunner", &format!("Stage2: Parsing VTT file: {}", vtt_path));
-
-        // VTTをパース
-        let segments = VttParser::parse_file(&vtt_path)
-            .map_err(|e| RunnerError::VttParse(e.to_string()))?;
-
-        log::info("PipelineRunner", &format!("Stage2: Parsed {} segments", segments.len()));
-
-        // 翻訳用テキストを生成
-        let translation_text = VttParser::to_translation_text(&segments);
+        // タイミングはsegments.jsonから取得
+        let segments_json = std::fs::read_to_string(format!("{}/segments.json", output_dir))?;
+        let timings: HashMap<usize, (u64, u64)> = serde_json::from_str::<Vec<SubtitleSegment>>(&segments_json)?
+            .into_iter()
+            .map(|s| (s.index as usize, (s.start_ms, s.end_ms)))
+            .collect();
 
-        // セグメント情報をJSONとして保存（後で使用）
-        let segments_json = serde_json::to_string(&segments)
-            .map_err(|e| RunnerError::Json(e))?;
+        let session = ReviewSession {
+            execution_id: execution_id.to_string(),
+            target_lang,
+            segments: translations
+                .into_iter()
+                .map(|(index, translation)| {
+                    let (start_ms, end_ms) = timings.get(&index).copied().unwrap_or_default();
+                    let segment_quality = quality.as_ref().and_then(|q| q.get(index));
+                    ReviewSegment {
+                        index,
+                        start_ms,
+                        end_ms,
+                        source: sources.get(&index).cloned().unwrap_or_default(),
+                        translation,
+                        edited: false,
+                        similarity: segment_quality.map(|q| q.similarity),
+                        flagged: segment_quality.map(|q| q.flagged).unwrap_or(false),
+                    }
+                })
+                .collect(),
+        };
 
-        let segments_path = format!("{}/segments.json", output_dir);
-        std::fs::write(&segments_path, &segments_json)
-            .map_err(|e| RunnerError::Io(e))?;
+        log::info("PipelineRunner", &format!(
+            "Review: waiting for approval of {} segments", session.segments.len()
+        ));
 
-        log::info("PipelineRunner", &format!("Stage2 complete: saved segments to {}", segments_path));
+        let receiver = self.reviews.open(session.clone());
+        if let Some(ref h) = *self.app_handle.lock() {
+            if let Err(e) = h.emit_recorded("pipeline:review_requested", &session) {
+                log::error("PipelineRunner", &format!("Failed to emit review request: {:?}", e));
+            }
+        }
+        self.emit_progress(
+            execution_id,
+            self.current_stage_index(execution_id),
+            "review-waiting",
+            "翻訳のレビュー待ち",
+        );
 
-        Ok(translation_text)
+        match receiver.await {
+            Ok(ReviewDecision::Approved(session)) => {
+                let edited = session.segments.iter().filter(|s| s.edited).count();
+                log::info("PipelineRunner", &format!("Review approved ({} segments edited)", edited));
+                Ok(format_indexed_text(&session.entries()))
+            }
+            Ok(ReviewDecision::Rejected(reason)) => {
+                Err(RunnerError::StageFailed(format!("Translation rejected in review: {}", reason)))
+            }
+            Err(_) => Err(RunnerError::StageFailed("Review cancelled".to_string())),
+        }
     }
 
     /// Stage4: 音声生成（VOICEVOX）
@@ -549,21 +2815,35 @@ impl PipelineRunner {
         execution_id: &str,
         params: &Value,
     ) -> Result<String, RunnerError> {
+        use futures::StreamExt;
+
         let output_dir = params["output_dir"].as_str()
             .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
-        let speaker = params["speaker"].as_i64().unwrap_or(1) as i32;
+        let speaker = params["speaker"].as_i64().map(|s| s as i32).unwrap_or(crate::settings::current().default_speaker);
+        let target_lang = params["target_lang"].as_str().unwrap_or(DEFAULT_TARGET_LANG);
+        let source_stage = params["source_stage"].as_str().unwrap_or("translate-subtitles");
 
-        // 前のステージから翻訳テキストを取得
+        // 前のステージから翻訳テキストを取得（レビュー時は編集後のテキスト）
         let translated_text = {
             let ctx = self.contexts.lock();
             let c = ctx.get(execution_id)
                 .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
-            c.stage_outputs.get("translate-subtitles")
+            c.stage_outputs.get(source_stage)
                 .cloned()
                 .ok_or_else(|| RunnerError::StageFailed("No translated text from stage3".to_string()))?
         };
 
-        log::info("PipelineRunner", &format!("Stage4: Synthesizing audio with VOICEVOX (speaker={})", speaker));
+        let tts_config: TtsConfig = serde_json::from_value(params["tts"].clone())
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let backend = {
+            let secrets = self.secrets.lock();
+            tts::create_backend(&tts_config, &secrets).map_err(|e| RunnerError::Voicevox(e.to_string()))?
+        };
+        log::info("PipelineRunner", &format!(
+            "Stage4: Synthesizing audio with {:?} (speaker={})", backend.provider(), speaker
+        ));
 
         // 翻訳テキストをパース
         let translations = parse_translated_text(&translated_text);
@@ -576,44 +2856,104 @@ impl PipelineRunner {
         let original_segments: Vec<SubtitleSegment> = serde_json::from_str(&segments_json)
             .map_err(|e| RunnerError::Json(e))?;
 
-        // 翻訳済みVTTを生成
+        // 翻訳済みVTT/SRTを生成
         let translated_vtt = VttParser::rebuild_vtt(&original_segments, &translations);
-        let vtt_path = format!("{}/translated.ja.vtt", output_dir);
+        let vtt_path = format!("{}/translated.{}.vtt", output_dir, target_lang);
         std::fs::write(&vtt_path, &translated_vtt)
             .map_err(|e| RunnerError::Io(e))?;
+        let srt = SubtitleFormat::Srt;
+        let srt_path = format!("{}/translated.{}.{}", output_dir, target_lang, srt.extension());
+        std::fs::write(&srt_path, srt.rebuild(&original_segments, &translations))?;
+
+        // VOICEVOX互換エンジンは日本語のみ対応
+        if !backend.supports_language(target_lang) {
+            log::info("PipelineRunner", &format!(
+                "Stage4: target language is {}, skipping {:?} synthesis", target_lang, backend.provider()
+            ));
+            return Ok(format!("Translated VTT saved to {} (no TTS for {})", vtt_path, target_lang));
+        }
+
+        // 音声生成（エンジンに接続できなければ字幕だけ出力）
+        if !backend.is_available().await {
+            log::warn("PipelineRunner", &format!("{:?} not available, skipping audio synthesis", backend.provider()));
+            return Ok(format!("Translated VTT saved to {} ({:?} not available)", vtt_path, backend.provider()));
+        }
 
-        // 音声生成ディレクトリ
-        let audio_dir = format!("{}/audio", output_dir);
+        // 空き容量チェック（合成WAVの見積もり）
+        let estimated_bytes = preflight::estimate_synthesis_bytes(&original_segments);
+        preflight::ensure_disk_space(std::path::Path::new(output_dir), estimated_bytes)?;
+
+        // 音声生成ディレクトリ（複数言語の場合は言語ごと）
+        let audio_dir = params["audio_dir"].as_str()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| format!("{}/audio", output_dir));
         std::fs::create_dir_all(&audio_dir)
             .map_err(|e| RunnerError::Io(e))?;
 
-        // VOICEVOXで音声生成
-        let client = VoicevoxClient::new();
-        if !client.is_running() {
-            log::warn("PipelineRunner", "VOICEVOX Engine not running, skipping audio synthesis");
-            return Ok(format!("Translated VTT saved to {} (VOICEVOX not running)", vtt_path));
-        }
+        // 話者ごとの声: 指定した割り当て → 話者分離の割り当て → 既定の話者
+        let mapping: SpeakerMapping = serde_json::from_value(params["speaker_mapping"].clone())
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let voices: HashMap<String, i32> = std::fs::read_to_string(format!("{}/{}", output_dir, SPEAKERS_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        let speed_fit: Option<SpeedFit> = serde_json::from_value(params["speed_fit"].clone()).unwrap_or(None);
+        let concurrency = params["concurrency"].as_u64()
+            .map(|n| n as usize)
+            .unwrap_or(voicevox::DEFAULT_CONCURRENCY)
+            .max(1);
+        log::info("PipelineRunner", &format!("Stage4: synthesizing up to {} segments at a time", concurrency));
+
+        // セグメントは互いに独立なので同時に合成する（完了順に受け取る）
+        let requests: Vec<_> = translations.iter().enumerate()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .map(|(i, text)| {
+                let label = original_segments.get(i).and_then(|s| s.speaker.as_deref());
+                let voice = mapping.resolve(i, label, &voices).unwrap_or(speaker);
+                let window_ms = original_segments.get(i).map(|s| s.duration_ms());
+                let audio_path = format!("{}/audio_{:04}.wav", audio_dir, i);
+                let (backend, speed_fit) = (&backend, &speed_fit);
+                async move {
+                    let options = SynthesisOptions { speaker: voice, ..Default::default() };
+                    let base_speed = options.speed_scale;
+                    let result = match (speed_fit, window_ms) {
+                        (Some(fit), Some(window_ms)) => backend
+                            .synthesize_fitted(text, &options, window_ms, fit, &audio_path)
+                            .await,
+                        _ => backend
+                            .synthesize(text, &options, &audio_path)
+                            .await
+                            .map(|_| base_speed),
+                    };
+                    (i, audio_path, result.map(|speed| speed > base_speed))
+                }
+            })
+            .collect();
+        let mut results = futures::stream::iter(requests).buffer_unordered(concurrency);
 
+        let mut fitted = 0;
         let mut audio_files = Vec::new();
-        for (i, text) in translations.iter().enumerate() {
-            if text.trim().is_empty() {
-                continue;
-            }
-            let audio_path = format!("{}/audio_{:04}.wav", audio_dir, i);
-            match client.text_to_speech(text, speaker, &audio_path) {
-                Ok(path) => {
-                    audio_files.push(path);
+        while let Some((i, audio_path, result)) = results.next().await {
+            match result {
+                Ok(sped_up) => {
+                    if sped_up {
+                        fitted += 1;
+                    }
                     log::info("PipelineRunner", &format!("Generated: {}", audio_path));
+                    audio_files.push(audio_path);
                 }
                 Err(e) => {
-                    log::error("PipelineRunner", &format!("VOICEVOX error for segment {}: {}", i, e));
+                    log::error("PipelineRunner", &format!("TTS error for segment {}: {}", i, e));
                 }
             }
         }
 
         log::info("PipelineRunner", &format!(
-            "Stage4 complete: {} audio files generated",
-            audio_files.len()
+            "Stage4 complete: {} audio files generated ({} sped up to fit)",
+            audio_files.len(), fitted
         ));
 
         Ok(format!(
@@ -623,44 +2963,123 @@ impl PipelineRunner {
         ))
     }
 
+    /// Stage5: 吹替音声トラックの組み立て
+    ///
+    /// セグメントごとのWAVを字幕の開始時刻に配置し、`dub.<lang>.wav` に書き出す。
+    /// 音声が生成されていない場合（VOICEVOX未起動・非対応言語）はスキップする。
+    async fn execute_assemble_stage(&self, params: &Value) -> Result<String, RunnerError> {
+        let output_dir = params["output_dir"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
+        let target_lang = params["target_lang"].as_str().unwrap_or(DEFAULT_TARGET_LANG);
+        let audio_dir = params["audio_dir"].as_str()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| format!("{}/audio", output_dir));
+
+        let segments_json = std::fs::read_to_string(format!("{}/segments.json", output_dir))?;
+        let segments: Vec<SubtitleSegment> = serde_json::from_str(&segments_json)?;
+        let dub_path = format!("{}/dub.{}.wav", output_dir, target_lang);
+        let trim: SilenceTrim = serde_json::from_value(params["trim_silence"].clone()).unwrap_or_default();
+        let stretch: Option<TimeStretch> = serde_json::from_value(params["time_stretch"].clone()).unwrap_or(None);
+
+        log::info("PipelineRunner", &format!("Stage5: Assembling dub track from {}", audio_dir));
+
+        let path = dub_path.clone();
+        let result = tokio::task::spawn_blocking(move || -> Result<_, TimelineError> {
+            let Some((track, report)) = timeline::assemble_from_dir(&segments, std::path::Path::new(&audio_dir), &trim, stretch.as_ref())? else {
+                return Ok(None);
+            };
+            track.write_wav(std::path::Path::new(&path))?;
+            Ok(Some(report))
+        }).await.map_err(|e| RunnerError::StageFailed(e.to_string()))??;
+
+        match result {
+            Some(report) => {
+                log::info("PipelineRunner", &format!(
+                    "Stage5 complete: {} ({} clips, {} shifted, {} stretched, max drift {}ms, {}ms silence trimmed)",
+                    dub_path, report.clips, report.shifted, report.stretched, report.max_drift_ms, report.trimmed_ms
+                ));
+                Ok(dub_path)
+            }
+            None => {
+                log::info("PipelineRunner", "Stage5: no synthesized audio, skipping dub track");
+                Ok(format!("No synthesized audio for {}, dub track skipped", target_lang))
+            }
+        }
+    }
+
+    /// Stage6: 吹替動画の多重化
+    ///
+    /// 元動画に `dub.<lang>.wav` と翻訳済み字幕を重ねて `dubbed.<lang>.mp4` を書き出す。
+    /// 吹替トラックがない場合（音声未生成）はスキップする。
+    async fn execute_mux_stage(&self, execution_id: &str, params: &Value) -> Result<String, RunnerError> {
+        let output_dir = params["output_dir"].as_str()
+            .ok_or_else(|| RunnerError::StageFailed("Missing output_dir".to_string()))?;
+        let target_lang = params["target_lang"].as_str().unwrap_or(DEFAULT_TARGET_LANG);
+
+        if !std::path::Path::new(&format!("{}/dub.{}.wav", output_dir, target_lang)).exists() {
+            log::info("PipelineRunner", "Stage6: no dub track, skipping mux");
+            return Ok(format!("No dub track for {}, mux skipped", target_lang));
+        }
+
+        let mut context = self.get_context(execution_id)
+            .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
+        context.input["output_dir"] = Value::String(output_dir.to_string());
+
+        let options = ExportOptions {
+            mux: serde_json::from_value(params["mux"].clone()).unwrap_or_default(),
+            target_lang: Some(target_lang.to_string()),
+            include_subtitles: std::path::Path::new(&format!("{}/translated.{}.vtt", output_dir, target_lang)).exists(),
+            video_path: params["video_path"].as_str().map(|s| s.to_string()),
+            output_path: None,
+            cookies: serde_json::from_value(params["cookies"].clone()).unwrap_or_default(),
+        };
+
+        log::info("PipelineRunner", &format!("Stage6: Muxing dubbed video [{}]", target_lang));
+
+        let app_handle = self.app_handle.lock().clone();
+        let output = tokio::task::spawn_blocking(move || {
+            export::export_dubbed_video(&context, &options, |progress| {
+                if let Some(ref handle) = app_handle {
+                    let _ = handle.emit_recorded("export:progress", &progress);
+                }
+            })
+        }).await.map_err(|e| RunnerError::StageFailed(e.to_string()))??;
+
+        log::info("PipelineRunner", &format!("Stage6 complete: {}", output));
+        Ok(output)
+    }
+
     /// Claude Code実行（翻訳ステージ）
-    async fn execute_claude_code(
+    async fn execute_agent_stage(
         &self,
         execution_id: &str,
         stage: &PipelineStage,
         stage_index: usize,
+        target: &AgentTarget,
     ) -> Result<String, RunnerError> {
-        // プロンプトを構築
-        let prompt = {
+        // プロンプトとエージェントのオプションを構築
+        let shared = self.shared_values(execution_id);
+        let (prompt, params) = {
             let ctx = self.contexts.lock();
             let c = ctx.get(execution_id)
                 .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
 
-            self.build_prompt(stage, &c.stage_outputs, &c.extracted_files, &c.input)
+            let mut params = stage.params.clone().unwrap_or(Value::Null);
+            fill_input_placeholders(&mut params, &c.input);
+            (self.build_prompt(stage, &c.stage_outputs, &c.extracted_files, &c.input, &shared), params)
         };
 
+        // スキル指定のステージは実行時に選んだエージェントに送る
+        if let Some(ref selector) = stage.selector {
+            return self.execute_on_selected_agent(execution_id, stage, stage_index, selector, &prompt).await;
+        }
+
         log::info("PipelineRunner", &format!(
-            "Stage {} (Claude Code): {} chars prompt",
-            stage_index, prompt.len()
+            "Stage {} ({:?}): {} chars prompt",
+            stage_index, target, prompt.len()
         ));
 
-        // CLIエグゼキューターを使用
-        let cli_executor = self.cli_executor.clone();
-        let prompt_owned = prompt.clone();
-
-        // 非同期で実行
-        let result = async move {
-            let mut guard = cli_executor.write().await;
-
-            if let Some(ref mut executor) = *guard {
-                executor.execute(&prompt_owned).await
-                    .map_err(|e| RunnerError::Executor(e.to_string()))
-            } else {
-                Err(RunnerError::ExecutorNotAvailable)
-            }
-        }.await;
-
-        match result {
+        match self.execute_on_agent(execution_id, target, &params, &prompt).await {
             Ok(output) => {
                 log::info("PipelineRunner", &format!(
                     "Stage {} complete: {} chars output",
@@ -669,7 +3088,7 @@ impl PipelineRunner {
                 Ok(output)
             }
             Err(e) => {
-                log::error("PipelineRunner", &format!("Claude Code execution failed: {}", e));
+                log::error("PipelineRunner", &format!("Agent execution failed: {}", e));
 
                 // エグゼキューターが利用できない場合はフォールバック
                 if matches!(e, RunnerError::ExecutorNotAvailable) {
@@ -682,6 +3101,150 @@ impl PipelineRunner {
         }
     }
 
+    /// `selector` に合うエージェントにプロンプトを送り、応答を返す
+    ///
+    /// 空いているエージェントから順に試し、送れない・失敗したら次の候補に回す
+    /// （タイムアウトは他の候補でも起きやすいので回さない）。
+    async fn execute_on_selected_agent(
+        &self,
+        execution_id: &str,
+        stage: &PipelineStage,
+        stage_index: usize,
+        selector: &CapabilityFilter,
+        prompt: &str,
+    ) -> Result<String, RunnerError> {
+        let orchestrator = self.orchestrator.lock().clone()
+            .ok_or_else(|| RunnerError::AgentNotFound("orchestrator is not configured".to_string()))?;
+        let candidates = orchestrator.lock().select_agents(selector);
+        if candidates.is_empty() {
+            return Err(RunnerError::AgentNotFound(format!("no available agent matches stage {}", stage.name)));
+        }
+
+        let timeout = std::time::Duration::from_secs(crate::settings::current().executor_timeout_secs);
+        let mut errors = Vec::new();
+        for agent_id in candidates {
+            log::info("PipelineRunner", &format!(
+                "Stage {} ({}): sending {} chars prompt to {}",
+                stage_index, stage.name, prompt.len(), agent_id
+            ));
+            self.emit_progress(
+                execution_id,
+                stage_index,
+                "stage-agent-selected",
+                &format!("エージェントを選択: {} → {}", stage.name, agent_id),
+            );
+
+            let pending = orchestrator.lock().request(LOCAL_SENDER, &agent_id, prompt);
+            let result = match pending {
+                Ok(pending) => pending.wait(timeout).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(reply) => return Ok(reply.response.message.payload.content),
+                Err(OrchestratorError::Timeout(message)) => return Err(RunnerError::Timeout(message)),
+                Err(e) => {
+                    log::warn("PipelineRunner", &format!("Agent {} failed stage {}: {} - trying next", agent_id, stage.name, e));
+                    errors.push(format!("{}: {}", agent_id, e));
+                }
+            }
+        }
+        Err(RunnerError::StageFailed(format!("All agents failed for stage {}: {}", stage.name, errors.join("; "))))
+    }
+
+    /// ステージ出力を実行ごとの共有コンテキストに公開（オーケストレーター未設定なら何もしない）
+    fn publish_stage_output(&self, execution_id: &str, stage_name: &str, output: &str) {
+        if let Some(orchestrator) = self.orchestrator.lock().clone() {
+            orchestrator.lock().set_context_value(
+                &execution_workspace(execution_id),
+                stage_name,
+                ContextValue::Text(output.to_string()),
+                None,
+                Some(LOCAL_SENDER.to_string()),
+            );
+        }
+    }
+
+    /// 実行ごとの共有コンテキストを削除（オーケストレーター未設定なら何もしない）
+    fn clear_execution_workspace(&self, execution_id: &str) {
+        if let Some(orchestrator) = self.orchestrator.lock().clone() {
+            orchestrator.lock().clear_workspace(&execution_workspace(execution_id));
+        }
+    }
+
+    /// `{{context.KEY}}` で参照できる共有コンテキストの値（実行ごとの値が既定ワークスペースより優先）
+    fn shared_values(&self, execution_id: &str) -> HashMap<String, String> {
+        let Some(orchestrator) = self.orchestrator.lock().clone() else {
+            return HashMap::new();
+        };
+        let orchestrator = orchestrator.lock();
+        [DEFAULT_WORKSPACE.to_string(), execution_workspace(execution_id)]
+            .iter()
+            .flat_map(|workspace| orchestrator.get_workspace_context(workspace).values)
+            .map(|(key, item)| (key, item.value.to_prompt_string()))
+            .collect()
+    }
+
+    /// ステージのエージェントアドレスに対応するエグゼキューターを作成
+    ///
+    /// CLIエージェントにはステージのパラメータの `model`・`working_dir` を渡す。
+    fn agent_executor(&self, target: &AgentTarget, params: &Value) -> Result<Box<dyn AgentExecutor>, RunnerError> {
+        let timeout = std::time::Duration::from_secs(crate::settings::current().executor_timeout_secs);
+        Ok(match target {
+            AgentTarget::RustDirect => {
+                return Err(RunnerError::AgentNotFound("rust-direct is not an agent".to_string()));
+            }
+            AgentTarget::ClaudeCode => Box::new(ClaudeCodeAgent::new(self.cli_executor.clone())),
+            AgentTarget::Cli(kind) => Box::new(CliAgent::new(*kind, CliAgentOptions {
+                model: params["model"].as_str().map(str::to_string),
+                working_dir: params["working_dir"].as_str().map(str::to_string),
+                ..Default::default()
+            })),
+            AgentTarget::Pty => {
+                let handle = self.pty.lock().clone()
+                    .ok_or_else(|| RunnerError::AgentNotFound("PTY is not configured".to_string()))?;
+                Box::new(PtyAgent::new(handle, timeout))
+            }
+            AgentTarget::Tmux(agent_id) => Box::new(TmuxPaneAgent::new(self.tmux.clone(), agent_id.as_str(), timeout)),
+        })
+    }
+
+    /// エージェントでプロンプトを実行し、使用量を実行に加算して最終的な出力を返す
+    ///
+    /// Claude Codeのエグゼキューターが起動していなければ `ExecutorNotAvailable`（呼び出し側で手動実行にフォールバック）。
+    async fn execute_on_agent(
+        &self,
+        execution_id: &str,
+        target: &AgentTarget,
+        params: &Value,
+        prompt: &str,
+    ) -> Result<String, RunnerError> {
+        let agent = self.agent_executor(target, params)?;
+        let result = match agent.execute(prompt).await {
+            Ok(events) => agent_executor::wait_for_output(events, |event| {
+                if let AgentEvent::Usage(usage) = event {
+                    self.add_usage(execution_id, usage);
+                }
+            }).await,
+            Err(e) => Err(e),
+        };
+        result.map_err(|e| match e {
+            AgentExecutorError::NotAvailable(_) if *target == AgentTarget::ClaudeCode => RunnerError::ExecutorNotAvailable,
+            AgentExecutorError::NotAvailable(message) => RunnerError::AgentNotFound(format!("{}: {}", agent.name(), message)),
+            AgentExecutorError::Timeout(message) => RunnerError::Timeout(message),
+            AgentExecutorError::Failed(message) => RunnerError::Executor(message),
+        })
+    }
+
+    /// 実行の使用量に加算
+    fn add_usage(&self, execution_id: &str, usage: &UsageTotals) {
+        if usage.is_empty() {
+            return;
+        }
+        if let Err(e) = self.executor.lock().add_usage(execution_id, usage) {
+            log::warn("PipelineRunner", &format!("Failed to record usage: {}", e));
+        }
+    }
+
     /// プロンプトを構築
     fn build_prompt(
         &self,
@@ -689,10 +3252,16 @@ impl PipelineRunner {
         stage_outputs: &HashMap<String, String>,
         extracted_files: &HashMap<String, Vec<String>>,
         input: &Value,
+        shared: &HashMap<String, String>,
     ) -> String {
         if let Some(ref template) = stage.prompt_template {
             let mut result = template.clone();
 
+            // 共有コンテキストの値を置換
+            for (key, value) in shared {
+                result = result.replace(&format!("{{{{context.{}}}}}", key), value);
+            }
+
             // 前段階の出力を置換
             for (stage_name, output) in stage_outputs {
                 let placeholder = format!("{{{{{}}}}}", stage_name);
@@ -762,7 +3331,7 @@ impl PipelineRunner {
                 message: message.to_string(),
             };
 
-            if let Err(e) = h.emit("pipeline:progress", &payload) {
+            if let Err(e) = h.emit_recorded("pipeline:progress", &payload) {
                 log::error("PipelineRunner", &format!("Failed to emit progress: {:?}", e));
             }
         }
@@ -774,16 +3343,55 @@ impl PipelineRunner {
         executor.get_execution(execution_id)
     }
 
+    /// 実行中のコンテキスト（入力・ステージ出力）を取得
+    pub fn get_context(&self, execution_id: &str) -> Option<ExecutionContext> {
+        self.contexts.lock().get(execution_id).cloned()
+    }
+
+    /// 実行コンテキストを取得（終了した実行はチェックポイントから読み込む）
+    ///
+    /// チェックポイントの場所は `output_dir`、なければ実行に記録した保存先を使う。
+    pub fn load_context(&self, execution_id: &str, output_dir: Option<&str>) -> Result<ExecutionContext, RunnerError> {
+        if let Some(context) = self.get_context(execution_id) {
+            return Ok(context);
+        }
+
+        let recorded = self.get_execution(execution_id)
+            .and_then(|e| e.context.get(CHECKPOINT_DIR_KEY).and_then(Value::as_str).map(str::to_string));
+        let dir = output_dir.map(str::to_string).or(recorded)
+            .ok_or_else(|| RunnerError::ExecutionNotFound(execution_id.to_string()))?;
+        let context = ExecutionContext::load_checkpoint(&dir)?;
+        if context.execution_id != execution_id {
+            return Err(RunnerError::ExecutionNotFound(execution_id.to_string()));
+        }
+        Ok(context)
+    }
+
     /// アクティブな実行一覧を取得
     pub fn get_active_executions(&self) -> Vec<PipelineExecution> {
         let executor = self.executor.lock();
         executor.get_active_executions()
     }
 
+    /// 実行を一時停止（実行中のステージは完了まで続き、次のステージの前で止まる）
+    pub fn pause_execution(&self, execution_id: &str) -> Result<PipelineExecution, RunnerError> {
+        let execution = self.executor.lock().pause_execution(execution_id)?;
+        self.emit_progress(execution_id, execution.current_stage, "paused", "パイプライン一時停止");
+        Ok(execution)
+    }
+
+    /// 一時停止した実行を再開
+    pub fn resume_execution(&self, execution_id: &str) -> Result<PipelineExecution, RunnerError> {
+        let execution = self.executor.lock().resume_execution(execution_id)?;
+        self.emit_progress(execution_id, execution.current_stage, "resumed", "パイプライン再開");
+        Ok(execution)
+    }
+
     /// 実行をキャンセル
     pub fn cancel_execution(&self, execution_id: &str) -> Result<PipelineExecution, RunnerError> {
         let executor = self.executor.lock();
         let execution = executor.cancel_execution(execution_id)?;
+        self.reviews.cancel(execution_id);
 
         self.emit_progress(execution_id, execution.current_stage, "cancelled", "パイプラインキャンセル");
 
@@ -796,6 +3404,24 @@ impl PipelineRunner {
     }
 }
 
+/// JSON中の文字列に含まれる `{{key}}` を入力の文字列値で置き換える
+fn fill_input_placeholders(value: &mut Value, input: &Value) {
+    match value {
+        Value::String(s) if s.contains("{{") => {
+            if let Some(obj) = input.as_object() {
+                for (key, v) in obj {
+                    if let Some(text) = v.as_str() {
+                        *s = s.replace(&format!("{{{{{}}}}}", key), text);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| fill_input_placeholders(item, input)),
+        Value::Object(map) => map.values_mut().for_each(|item| fill_input_placeholders(item, input)),
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -829,6 +3455,179 @@ mod tests {
         assert!(json.contains("test-stage"));
     }
 
+    #[test]
+    fn test_srt_to_vtt() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,500\nHello, world\n\n2\n00:00:03,000 --> 00:00:04,000\nBye\n";
+        let vtt = VttParser::rebuild_vtt(&SrtParser::parse(srt).unwrap(), &[]);
+        let segments = VttParser::parse(&vtt).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].end_ms, 2500);
+        assert_eq!(segments[0].text, "Hello, world");
+    }
+
+    #[test]
+    fn test_translation_options_target_lang() {
+        let options = TranslationOptions::default();
+        assert_eq!(options.target_lang(), "ja");
+        assert!(options.validate().is_ok());
+
+        let options = TranslationOptions { target_lang: Some("../x".to_string()), ..Default::default() };
+        assert!(matches!(options.validate(), Err(RunnerError::InvalidOption(_))));
+    }
+
+    #[test]
+    fn test_multi_target_stages() {
+        let options = TranslationOptions {
+            target_langs: vec!["ja".to_string(), "en".to_string(), "ja".to_string()],
+            verify: true,
+            review: true,
+            content_filter: Some(ContentFilterConfig::default()),
+            ..Default::default()
+        };
+        assert_eq!(options.target_lang(), "ja");
+
+        let stages = PipelineRunner::create_translation_stages("/tmp/out", &options);
+        let names: Vec<&str> = stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec![
+            "translate-subtitles",
+            "translate-subtitles:en",
+            "verify-translation",
+            "verify-translation:en",
+            "content-filter",
+            "content-filter:en",
+            "review-translation",
+            "review-translation:en",
+            "voice-synthesis",
+            "voice-synthesis:en",
+            "assemble-dub",
+            "assemble-dub:en",
+        ]);
+        // 同じ工程は言語間で並列に実行する
+        let pipeline = PipelineDefinition::new("multi").with_stages(stages.clone());
+        assert_eq!(pipeline.stage_groups().len(), 6);
+        assert!(pipeline.stage_groups().iter().all(|group| group.len() == 2));
+        // 検証済みの訳文が後続ステージの入力になる
+        assert!(stages[5].prompt_template.as_deref().unwrap().contains(r#""source_stage":"verify-translation:en""#));
+        // 話者を指定しなければ設定の既定話者を使う
+        assert!(!stages[8].prompt_template.as_deref().unwrap().contains(r#""speaker""#));
+
+        let options = TranslationOptions { speaker: Some(3), ..options };
+        let stages = PipelineRunner::create_translation_stages("/tmp/out", &options);
+        assert!(stages[8].prompt_template.as_deref().unwrap().contains(r#""speaker":3"#));
+    }
+
+    #[test]
+    fn test_glossary_enforced_for_every_language() {
+        use crate::acp::glossary::{Glossary, GlossaryEntry};
+        use crate::acp::message::AgentAddress;
+
+        let runner = PipelineRunner::new(Arc::new(Mutex::new(PipelineExecutor::new())), Arc::new(Mutex::new(None)));
+        let entry = GlossaryEntry::new("Rust", "Rust").with_variants(vec!["rust".to_string()]);
+        runner.glossaries().lock().set(DEFAULT_PROJECT, Glossary::new(vec![entry]));
+        let mut context = ExecutionContext::new("pipeline-1", "exec-1", serde_json::json!({}));
+        context.stage_outputs.insert("parse-subtitles".to_string(), "[0] I love Rust".to_string());
+        runner.contexts.lock().insert("exec-1".to_string(), context);
+
+        let postprocess = |name: &str| {
+            let stage = PipelineStage::new(name, AgentAddress::new("rust-direct"));
+            runner.postprocess_output("exec-1", &stage, "[0] I love rust".to_string())
+        };
+        assert_eq!(postprocess("translate-subtitles"), "[0] I love Rust");
+        assert_eq!(postprocess("translate-subtitles:en"), "[0] I love Rust");
+        assert_eq!(postprocess("translate-subtitles-draft"), "[0] I love rust");
+    }
+
+    #[test]
+    fn test_batch_progress() {
+        let video = |i: usize, status| BatchVideo {
+            url: format!("https://www.youtube.com/watch?v={}", i),
+            title: None,
+            output_dir: batch_output_dir("/out", i, &i.to_string()),
+            status,
+            execution_id: None,
+            error: None,
+        };
+        let videos = vec![
+            video(0, BatchVideoStatus::Completed),
+            video(1, BatchVideoStatus::Failed),
+            video(2, BatchVideoStatus::Running),
+        ];
+        let payload = BatchProgressPayload::new("batch-1", &videos, Some(2));
+        assert_eq!((payload.total, payload.completed, payload.failed), (3, 1, 1));
+        assert_eq!(videos[2].output_dir, "/out/003-2");
+        assert_eq!(batch_output_dir("/out", 0, "a/b?c"), "/out/001-a_b_c");
+    }
+
+    #[test]
+    fn test_subtitle_pipeline_with_video() {
+        let runner = PipelineRunner::new(Arc::new(Mutex::new(PipelineExecutor::new())), Arc::new(Mutex::new(None)));
+        let options = TranslationOptions { include_video: true, ..Default::default() };
+        let pipeline = runner
+            .create_subtitle_pipeline("https://youtu.be/x", "en", "/tmp/out", &options)
+            .unwrap();
+
+        let last = pipeline.stages.last().unwrap();
+        assert_eq!(last.name, "mux-video");
+        // 動画パスを渡さないので書き出し時にyt-dlpでダウンロードする
+        assert!(last.prompt_template.as_deref().unwrap().contains(r#""video_path":null"#));
+
+        let without = runner
+            .create_subtitle_pipeline("https://youtu.be/x", "en", "/tmp/out", &TranslationOptions::default())
+            .unwrap();
+        assert!(without.stages.iter().all(|s| s.name != "mux-video"));
+    }
+
+    #[test]
+    fn test_cookies_passed_to_download_stages() {
+        let runner = PipelineRunner::new(Arc::new(Mutex::new(PipelineExecutor::new())), Arc::new(Mutex::new(None)));
+        let cookies = YtdlpCookies { from_browser: Some("firefox".to_string()), file: None };
+        let options = TranslationOptions { cookies: cookies.clone(), include_video: true, ..Default::default() };
+        let pipeline = runner
+            .create_subtitle_pipeline("https://youtu.be/x", "en", "/tmp/out", &options)
+            .unwrap();
+
+        for name in ["download-subtitles", "mux-video"] {
+            let stage = pipeline.stages.iter().find(|s| s.name == name).unwrap();
+            let params: Value = serde_json::from_str(
+                stage.prompt_template.as_deref().unwrap().strip_prefix("RUST_DIRECT:").unwrap(),
+            ).unwrap();
+            assert_eq!(serde_json::from_value::<YtdlpCookies>(params["cookies"].clone()).unwrap(), cookies);
+        }
+    }
+
+    #[test]
+    fn test_style_recorded_in_project() {
+        let dir = std::env::temp_dir().join(format!("re-voice-project-{}", uuid::Uuid::new_v4()));
+        let output_dir = dir.to_string_lossy().to_string();
+
+        let options = TranslationOptions { style: Some(SpeechStyle::Narration), ..Default::default() };
+        options.record_in_project(&output_dir).unwrap();
+
+        // 文体未指定の再実行では記録済みの文体を引き継ぐ
+        let rerun = TranslationOptions::default().record_in_project(&output_dir).unwrap();
+        assert_eq!(rerun.style, Some(SpeechStyle::Narration));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_execute_on_agent_unavailable() {
+        let runner = PipelineRunner::new(Arc::new(Mutex::new(PipelineExecutor::new())), Arc::new(Mutex::new(None)));
+        let run = |target: AgentTarget| {
+            let runner = &runner;
+            async move {
+                tokio::time::timeout(std::time::Duration::from_secs(5), runner.execute_on_agent("exec-1", &target, &Value::Null, "hi"))
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Claude Codeは手動実行にフォールバックできるよう区別する
+        assert!(matches!(run(AgentTarget::ClaudeCode).await, Err(RunnerError::ExecutorNotAvailable)));
+        assert!(matches!(run(AgentTarget::Pty).await, Err(RunnerError::AgentNotFound(_))));
+        assert!(matches!(run(AgentTarget::Tmux("agent-1".to_string())).await, Err(RunnerError::AgentNotFound(_))));
+    }
+
     #[test]
     fn test_truncate_safe() {
         let s = "日本語テスト";
@@ -836,4 +3635,220 @@ mod tests {
         assert!(truncated.len() <= 10);
         assert!(s.starts_with(truncated));
     }
+
+    #[test]
+    fn test_local_video_pipeline_stages() {
+        let pipeline = PipelineRunner::create_local_video_pipeline(
+            "{{video_path}}", "{{subtitle_path}}", "{{output_dir}}", &TranslationOptions::default(),
+        );
+        let names: Vec<&str> = pipeline.stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(pipeline.name, LOCAL_VIDEO_PIPELINE);
+        assert_eq!(names, vec![
+            "import-subtitles",
+            "parse-subtitles",
+            "translate-subtitles",
+            "voice-synthesis",
+            "assemble-dub",
+            "mux-video",
+        ]);
+    }
+
+    #[test]
+    fn test_local_subtitle_pipeline_stages() {
+        let runner = PipelineRunner::new(Arc::new(Mutex::new(PipelineExecutor::new())), Arc::new(Mutex::new(None)));
+        let pipeline = runner
+            .create_local_subtitle_pipeline("/tmp/in.srt", "/tmp/out", &TranslationOptions::default())
+            .unwrap();
+        let names: Vec<&str> = pipeline.stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(pipeline.name, "local-subtitle-translation");
+        assert_eq!(names[..3], ["import-subtitles", "parse-subtitles", "translate-subtitles"]);
+        assert!(!names.contains(&"download-subtitles"));
+    }
+
+    #[test]
+    fn test_fill_input_placeholders() {
+        let mut params = serde_json::json!({
+            "stage": "mux",
+            "output_dir": "{{output_dir}}",
+            "video_path": "{{video_path}}",
+            "nested": ["{{output_dir}}/audio"],
+            "speaker": 1
+        });
+        let input = serde_json::json!({ "output_dir": "/tmp/out", "video_path": "/v/movie.mkv" });
+        fill_input_placeholders(&mut params, &input);
+        assert_eq!(params["output_dir"], "/tmp/out");
+        assert_eq!(params["video_path"], "/v/movie.mkv");
+        assert_eq!(params["nested"][0], "/tmp/out/audio");
+        assert_eq!(params["speaker"], 1);
+    }
+
+    #[test]
+    fn test_custom_pipeline() {
+        let stages: Vec<CustomStage> = serde_json::from_value(serde_json::json!([
+            { "kind": "rust", "name": "download", "stage": "download",
+              "params": { "url": "{{url}}", "lang": "en", "output_dir": "{{output_dir}}" } },
+            { "kind": "claude_code", "name": "summarize", "prompt": "Summarize:\n{{download}}" },
+        ])).unwrap();
+
+        let pipeline = PipelineRunner::create_custom_pipeline("download-summarize", &stages).unwrap();
+        assert_eq!(pipeline.stages.len(), 2);
+        let template = pipeline.stages[0].prompt_template.as_deref().unwrap();
+        let params: Value = serde_json::from_str(template.strip_prefix("RUST_DIRECT:").unwrap()).unwrap();
+        assert_eq!(params["stage"], "download");
+        assert_eq!(params["url"], "{{url}}");
+        assert_eq!(pipeline.stages[1].prompt_template.as_deref(), Some("Summarize:\n{{download}}"));
+
+        let unknown = vec![CustomStage::Rust { name: "x".into(), stage: "nope".into(), params: Default::default(), group: None, retry: None }];
+        assert!(PipelineRunner::create_custom_pipeline("p", &unknown).is_err());
+        let duplicate = vec![stages[1].clone(), stages[1].clone()];
+        assert!(PipelineRunner::create_custom_pipeline("p", &duplicate).is_err());
+        assert!(PipelineRunner::create_custom_pipeline(LOCAL_VIDEO_PIPELINE, &stages).is_err());
+
+        let on_codex: Vec<CustomStage> = serde_json::from_value(serde_json::json!([
+            {
+                "kind": "claude_code", "name": "review", "prompt": "review", "agent": "codex@localhost",
+                "params": { "model": "gpt-5-codex", "working_dir": "{{output_dir}}" }
+            },
+        ])).unwrap();
+        let pipeline = PipelineRunner::create_custom_pipeline("review", &on_codex).unwrap();
+        assert_eq!(AgentTarget::from_address(&pipeline.stages[0].agent), AgentTarget::Cli(crate::acp::cli_agent::CliAgentKind::Codex));
+        let params = pipeline.stages[0].params.as_ref().unwrap();
+        assert_eq!(params["model"], "gpt-5-codex");
+        assert_eq!(params["working_dir"], "{{output_dir}}");
+        let on_rust: Vec<CustomStage> = serde_json::from_value(serde_json::json!([
+            { "kind": "claude_code", "name": "x", "prompt": "x", "agent": "rust-direct" },
+        ])).unwrap();
+        assert!(PipelineRunner::create_custom_pipeline("x", &on_rust).is_err());
+    }
+
+    #[test]
+    fn test_register_custom_pipeline_reuses_id() {
+        let executor = Arc::new(Mutex::new(PipelineExecutor::new()));
+        let runner = PipelineRunner::new(executor.clone(), Arc::new(Mutex::new(None)));
+        let stages = |prompt: &str| -> Vec<CustomStage> {
+            serde_json::from_value(serde_json::json!([{ "kind": "claude_code", "name": "a", "prompt": prompt }])).unwrap()
+        };
+
+        let first = runner.register_custom_pipeline("notes", &stages("first")).unwrap();
+        let second = runner.register_custom_pipeline("notes", &stages("second")).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(executor.lock().list_pipelines().len(), 1);
+        let pipeline = executor.lock().get_pipeline(&first).unwrap();
+        assert_eq!(pipeline.stages[0].prompt_template.as_deref(), Some("second"));
+
+        // 実行中でも同じIDのまま置き換える（実行は開始時の定義で進む）
+        let execution = executor.lock().start_execution(&first).unwrap();
+        let third = runner.register_custom_pipeline("notes", &stages("third")).unwrap();
+        assert_eq!(third, first);
+        assert_eq!(executor.lock().list_pipelines().len(), 1);
+        assert_eq!(executor.lock().get_pipeline(&first).unwrap().stages[0].prompt_template.as_deref(), Some("third"));
+        assert_eq!(executor.lock().get_execution(&execution.execution_id).unwrap().stage_results.len(), 1);
+    }
+
+    #[test]
+    fn test_required_tools() {
+        let stages: Vec<CustomStage> = serde_json::from_value(serde_json::json!([
+            { "kind": "rust", "name": "dl", "stage": "download" },
+            { "kind": "claude_code", "name": "note", "prompt": "summarize" },
+            { "kind": "rust", "name": "tts", "stage": "voicevox" },
+            { "kind": "rust", "name": "mux", "stage": "mux" },
+            { "kind": "rust", "name": "asr", "stage": "transcribe" },
+        ])).unwrap();
+        let definition = PipelineRunner::create_custom_pipeline("tools", &stages).unwrap();
+        assert_eq!(required_tools(&definition), [ExternalTool::Ytdlp, ExternalTool::Ffmpeg]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_skips_completed_stages() {
+        let dir = std::env::temp_dir().join(format!("re-voice-checkpoint-{}", uuid::Uuid::new_v4()));
+        let output_dir = dir.to_string_lossy().to_string();
+        let executor = Arc::new(Mutex::new(PipelineExecutor::new()));
+        let runner = PipelineRunner::new(executor.clone(), Arc::new(Mutex::new(None)));
+
+        let stages: Vec<CustomStage> = serde_json::from_value(serde_json::json!([
+            { "kind": "claude_code", "name": "a", "prompt": "first" },
+            { "kind": "claude_code", "name": "b", "prompt": "second:\n{{a}}" },
+        ])).unwrap();
+        let pipeline_id = executor.lock().register(PipelineRunner::create_custom_pipeline("two-steps", &stages).unwrap());
+
+        let input = serde_json::json!({ "output_dir": output_dir });
+        let mut checkpoint = ExecutionContext::new(&pipeline_id, "previous", input.clone());
+        checkpoint.stage_outputs.insert("a".to_string(), "A".to_string());
+
+        // "a" は前回の出力を引き継ぎ、"b" だけ実行する（エグゼキューターがないので手動実行のフォールバック）
+        let execution = runner.run_from(&pipeline_id, input, Some(checkpoint)).await.unwrap();
+        assert_eq!(execution.status, PipelineStatus::Completed);
+
+        let saved = ExecutionContext::load_checkpoint(&output_dir).unwrap();
+        assert_eq!(saved.execution_id, execution.execution_id);
+        // 終了した実行のコンテキストはメモリに残さず、チェックポイントから読み込む
+        assert!(runner.get_context(&execution.execution_id).is_none());
+        let loaded = runner.load_context(&execution.execution_id, None).unwrap();
+        assert_eq!(loaded.stage_outputs.len(), 2);
+        assert_eq!(saved.current_stage, 2);
+        assert_eq!(saved.stage_outputs.get("a").map(String::as_str), Some("A"));
+        assert!(saved.stage_outputs["b"].starts_with("[FALLBACK"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_stage_outputs_published_to_context() {
+        let executor = Arc::new(Mutex::new(PipelineExecutor::new()));
+        let runner = PipelineRunner::new(executor.clone(), Arc::new(Mutex::new(None)));
+        let orchestrator = Arc::new(Mutex::new(AgentOrchestrator::new()));
+        runner.set_orchestrator(orchestrator.clone());
+        orchestrator.lock().set_context_value(DEFAULT_WORKSPACE, "lang", ContextValue::Text("ja".into()), None, None);
+
+        let stages: Vec<CustomStage> = serde_json::from_value(serde_json::json!([
+            { "kind": "claude_code", "name": "a", "prompt": "translate to {{context.lang}}" },
+            { "kind": "claude_code", "name": "b", "prompt": "review: {{context.a}}" },
+        ])).unwrap();
+        let pipeline_id = executor.lock().register(PipelineRunner::create_custom_pipeline("context", &stages).unwrap());
+
+        // エグゼキューターがないのでプロンプトがそのまま出力になる
+        let execution = runner.run(&pipeline_id, serde_json::json!({})).await.unwrap();
+        let output = |name: &str| execution.stage_results.iter()
+            .find(|r| r.stage_name == name)
+            .and_then(|r| r.output.as_ref())
+            .and_then(|o| o["output"].as_str())
+            .unwrap()
+            .to_string();
+        assert!(output("a").ends_with("translate to ja"));
+        // bはaが公開した値をプロンプトに使う
+        let b = output("b");
+        assert!(b.contains("review: [FALLBACK"));
+        assert!(b.contains("translate to ja"));
+
+        // 実行が終わったら実行ごとのワークスペースは消え、既定ワークスペースは残る
+        let workspace = execution_workspace(&execution.execution_id);
+        assert!(orchestrator.lock().get_workspace_context(&workspace).values.is_empty());
+        assert!(orchestrator.lock().get_context_value(DEFAULT_WORKSPACE, "lang").is_some());
+    }
+
+    #[test]
+    fn test_usage_added_to_execution() {
+        let executor = Arc::new(Mutex::new(PipelineExecutor::new()));
+        let runner = PipelineRunner::new(executor.clone(), Arc::new(Mutex::new(None)));
+        let stages: Vec<CustomStage> = serde_json::from_value(serde_json::json!([
+            { "kind": "claude_code", "name": "a", "prompt": "hello" },
+        ])).unwrap();
+        let pipeline_id = executor.lock().register(PipelineRunner::create_custom_pipeline("usage", &stages).unwrap());
+        let execution_id = executor.lock().start_execution(&pipeline_id).unwrap().execution_id;
+
+        let request = UsageTotals { input_tokens: 100, output_tokens: 10, cost_usd: 0.5, requests: 1, ..Default::default() };
+        runner.add_usage(&execution_id, &request);
+        runner.emit_usage(&execution_id, TranslationProvider::Claude, TranslationUsage {
+            characters: 40,
+            character_count: None,
+            character_limit: None,
+            tokens: Some(request),
+        });
+        // 使用量のないリクエストは数えない
+        runner.add_usage(&execution_id, &UsageTotals::default());
+
+        let usage = runner.get_execution(&execution_id).unwrap().usage;
+        assert_eq!((usage.input_tokens, usage.output_tokens, usage.requests), (200, 20, 2));
+        assert!((usage.cost_usd - 1.0).abs() < 1e-9);
+    }
 }
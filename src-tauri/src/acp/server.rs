@@ -0,0 +1,377 @@
+//! Agent card server
+//!
+//! A minimal HTTP/1.1 server that publishes our own `AgentCard` at
+//! `/.well-known/agent.json` and accepts A2A `tasks/send` JSON-RPC requests on
+//! `POST /`, so other orchestrators can discover Re-Voice and hand it tasks.
+//!
+//! Tasks reach the local coding agent, so `POST /` requires the bearer token
+//! shared with paired agents, and requests sent by browsers (anything with an
+//! `Origin` header) are refused so a web page cannot drive the agent.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use super::agent::{AgentCard, Authentication, Transport};
+use super::discovery::WELL_KNOWN_PATH;
+use crate::log;
+
+/// Address used when none is configured
+pub const DEFAULT_SERVER_ADDR: &str = "127.0.0.1:41241";
+
+/// Largest request body accepted
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Task received from another agent
+#[derive(Debug, Clone)]
+pub struct InboundTask {
+    /// A2A task ID chosen by the caller
+    pub id: String,
+    pub session_id: Option<String>,
+    /// Text parts of the message, joined with newlines
+    pub text: String,
+    /// `params.metadata` of the request
+    pub metadata: Option<Value>,
+    pub peer: SocketAddr,
+}
+
+/// Runs an inbound task and returns the agent's answer
+pub type TaskHandler = Arc<dyn Fn(InboundTask) -> BoxFuture<'static, Result<String, String>> + Send + Sync>;
+
+/// Running server; stops when dropped
+pub struct AgentServer {
+    addr: SocketAddr,
+    card: AgentCard,
+    accept_loop: JoinHandle<()>,
+}
+
+impl AgentServer {
+    /// Bind `addr` and start serving
+    ///
+    /// The card's `url` is replaced with the address actually bound. Tasks must
+    /// carry `Authorization: Bearer <token>`; the card only advertises the scheme.
+    pub async fn start(addr: &str, mut card: AgentCard, token: &str, handler: TaskHandler) -> std::io::Result<Self> {
+        if token.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "agent server token must not be empty"));
+        }
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        card.url = format!("http://{}/", addr);
+        card.transport = Some(Transport::Http);
+        card.authentication = Some(Authentication::new(vec!["bearer".to_string()]));
+
+        let served = Arc::new(card.clone());
+        let token: Arc<str> = Arc::from(token);
+        let accept_loop = tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        log::warn("AgentServer", &format!("Accept failed: {}", e));
+                        continue;
+                    }
+                };
+                let card = served.clone();
+                let token = token.clone();
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_connection(stream, peer, &card, &token, &handler).await {
+                        log::debug("AgentServer", &format!("Connection from {} failed: {}", peer, e));
+                    }
+                });
+            }
+        });
+        log::info("AgentServer", &format!("Serving agent card at http://{}{}", addr, WELL_KNOWN_PATH));
+
+        Ok(Self { addr, card, accept_loop })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Card as served (with the bound URL)
+    pub fn card(&self) -> &AgentCard {
+        &self.card
+    }
+
+    /// Stop accepting connections and wait until the port is released
+    pub async fn stop(mut self) {
+        self.accept_loop.abort();
+        let _ = (&mut self.accept_loop).await;
+    }
+}
+
+impl Drop for AgentServer {
+    fn drop(&mut self) {
+        self.accept_loop.abort();
+    }
+}
+
+/// Parsed HTTP request (one per connection)
+struct Request {
+    method: String,
+    path: String,
+    /// `Origin` header (set by browsers, never by agents)
+    origin: Option<String>,
+    /// `Authorization` header
+    authorization: Option<String>,
+    body: String,
+}
+
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(None);
+    };
+    let method = method.to_string();
+    let path = target.split('?').next().unwrap_or("/").to_string();
+
+    let mut content_length = 0;
+    let mut origin = None;
+    let mut authorization = None;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let (name, value) = (name.trim(), value.trim());
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.to_string());
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "request body too large"));
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(Request {
+        method,
+        path,
+        origin,
+        authorization,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    }))
+}
+
+/// Whether the `Authorization` header carries `token` (compared in constant time)
+fn is_authorized(authorization: Option<&str>, token: &str) -> bool {
+    let Some(presented) = authorization.and_then(|value| value.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let (presented, token) = (presented.trim().as_bytes(), token.as_bytes());
+    presented.len() == token.len() && presented.iter().zip(token).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+async fn serve_connection(
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    card: &AgentCard,
+    token: &str,
+    handler: &TaskHandler,
+) -> std::io::Result<()> {
+    let Some(request) = read_request(&mut stream).await? else {
+        return Ok(());
+    };
+
+    let (status, body) = match (request.method.as_str(), request.path.as_str()) {
+        _ if request.origin.is_some() => {
+            log::warn("AgentServer", &format!("Refused browser request from {} (origin {:?})", peer, request.origin));
+            ("403 Forbidden", json!({ "error": "browser requests are not accepted" }))
+        }
+        ("GET", WELL_KNOWN_PATH) => ("200 OK", serde_json::to_value(card).unwrap_or(Value::Null)),
+        ("POST", "/") if !is_authorized(request.authorization.as_deref(), token) => {
+            log::warn("AgentServer", &format!("Refused task without a valid token from {}", peer));
+            ("401 Unauthorized", json!({ "error": "missing or invalid bearer token" }))
+        }
+        ("POST", "/") => ("200 OK", handle_rpc(&request.body, peer, handler).await),
+        ("GET", _) | ("POST", _) => ("404 Not Found", json!({ "error": "not found" })),
+        _ => ("405 Method Not Allowed", json!({ "error": "method not allowed" })),
+    };
+
+    let challenge = if status.starts_with("401") { "WWW-Authenticate: Bearer\r\n" } else { "" };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        challenge,
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Handle one JSON-RPC request and build the response object
+async fn handle_rpc(body: &str, peer: SocketAddr, handler: &TaskHandler) -> Value {
+    let request: Value = match serde_json::from_str(body) {
+        Ok(value) => value,
+        Err(e) => return rpc_error(Value::Null, -32700, &format!("Parse error: {}", e)),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+    match request.get("method").and_then(Value::as_str) {
+        Some("tasks/send") => {}
+        Some(method) => return rpc_error(id, -32601, &format!("Method not found: {}", method)),
+        None => return rpc_error(id, -32600, "Invalid request"),
+    }
+
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let text = params["message"]["parts"]
+        .as_array()
+        .map(|parts| {
+            parts
+                .iter()
+                .filter(|p| p["type"] == "text")
+                .filter_map(|p| p["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+    if text.trim().is_empty() {
+        return rpc_error(id, -32602, "Invalid params: message has no text parts");
+    }
+
+    let task = InboundTask {
+        id: params["id"].as_str().map(str::to_string).unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        session_id: params["sessionId"].as_str().map(str::to_string),
+        text,
+        metadata: params.get("metadata").cloned(),
+        peer,
+    };
+    let (task_id, session_id) = (task.id.clone(), task.session_id.clone());
+
+    let (state, text) = match handler(task).await {
+        Ok(output) => ("completed", output),
+        Err(error) => ("failed", error),
+    };
+    let parts = json!([{ "type": "text", "text": text }]);
+    let mut result = json!({
+        "id": task_id,
+        "sessionId": session_id,
+        "status": {
+            "state": state,
+            "message": { "role": "agent", "parts": parts },
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        },
+    });
+    if state == "completed" {
+        result["artifacts"] = json!([{ "index": 0, "parts": parts }]);
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "test-token";
+
+    fn echo_handler() -> TaskHandler {
+        Arc::new(|task: InboundTask| {
+            Box::pin(async move {
+                if task.text == "fail" {
+                    Err("agent unavailable".to_string())
+                } else {
+                    Ok(format!("echo: {}", task.text))
+                }
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_serves_card_and_tasks() {
+        let card = AgentCard::re_voice();
+        let server = AgentServer::start("127.0.0.1:0", card, TOKEN, echo_handler()).await.unwrap();
+        let base = format!("http://{}", server.local_addr());
+        let client = reqwest::Client::new();
+
+        let fetched = client.get(format!("{}{}", base, WELL_KNOWN_PATH)).send().await.unwrap().text().await.unwrap();
+        let fetched = crate::acp::discovery::parse_card(&fetched).unwrap();
+        assert_eq!(fetched.url, format!("{}/", base));
+        assert!(!serde_json::to_string(&fetched).unwrap().contains(TOKEN));
+        assert_eq!(fetched.name, server.card().name);
+
+        let send = |text: &str| {
+            json!({
+                "jsonrpc": "2.0", "id": 7, "method": "tasks/send",
+                "params": { "id": "task-1", "message": { "role": "user", "parts": [{ "type": "text", "text": text }] } },
+            })
+        };
+        let reply: Value = client.post(&base).bearer_auth(TOKEN).json(&send("hello")).send().await.unwrap().json().await.unwrap();
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["result"]["id"], "task-1");
+        assert_eq!(reply["result"]["status"]["state"], "completed");
+        assert_eq!(reply["result"]["artifacts"][0]["parts"][0]["text"], "echo: hello");
+
+        let reply: Value = client.post(&base).bearer_auth(TOKEN).json(&send("fail")).send().await.unwrap().json().await.unwrap();
+        assert_eq!(reply["result"]["status"]["state"], "failed");
+        assert!(reply["result"].get("artifacts").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unauthenticated_and_browser_requests() {
+        let called = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = called.clone();
+        let handler: TaskHandler = Arc::new(move |_task: InboundTask| {
+            flag.store(true, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(async { Ok("ran".to_string()) })
+        });
+        let server = AgentServer::start("127.0.0.1:0", AgentCard::re_voice(), TOKEN, handler).await.unwrap();
+        let base = format!("http://{}", server.local_addr());
+        let client = reqwest::Client::new();
+        let task = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "tasks/send",
+            "params": { "message": { "parts": [{ "type": "text", "text": "rm -rf ~" }] } },
+        });
+
+        let missing = client.post(&base).json(&task).send().await.unwrap();
+        assert_eq!(missing.status(), 401);
+        let wrong = client.post(&base).bearer_auth("guess").json(&task).send().await.unwrap();
+        assert_eq!(wrong.status(), 401);
+        // no-cors text/plain POST from a web page, even with the token
+        let browser = client.post(&base)
+            .bearer_auth(TOKEN)
+            .header("Origin", "https://evil.example")
+            .header("Content-Type", "text/plain")
+            .body(task.to_string())
+            .send().await.unwrap();
+        assert_eq!(browser.status(), 403);
+        let preflight = client.request(reqwest::Method::OPTIONS, &base).send().await.unwrap();
+        assert_eq!(preflight.status(), 405);
+        assert!(preflight.headers().get("access-control-allow-origin").is_none());
+        assert!(!called.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_rpc_errors() {
+        let peer: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        let handler = echo_handler();
+        assert_eq!(handle_rpc("{", peer, &handler).await["error"]["code"], -32700);
+        let unknown = json!({ "jsonrpc": "2.0", "id": 1, "method": "tasks/cancel" }).to_string();
+        assert_eq!(handle_rpc(&unknown, peer, &handler).await["error"]["code"], -32601);
+        let empty = json!({ "jsonrpc": "2.0", "id": 1, "method": "tasks/send", "params": { "message": { "parts": [] } } }).to_string();
+        assert_eq!(handle_rpc(&empty, peer, &handler).await["error"]["code"], -32602);
+    }
+}
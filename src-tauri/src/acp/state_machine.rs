@@ -3,10 +3,15 @@
 //! CLIモード（--print --output-format stream-json）用の状態管理。
 //! tmuxベースから移行し、JSONイベントで状態を明示的に検出する。
 
+use std::collections::VecDeque;
+use std::sync::Arc;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::log;
+
 /// Claude Code エージェントの状態
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -92,6 +97,29 @@ impl AgentState {
         Self::Completed { output }
     }
 
+    /// イベントが示す遷移先（不正な遷移の補正用）
+    pub fn for_event(event: &StateEvent) -> Self {
+        match event {
+            StateEvent::Initialized => Self::idle(),
+            StateEvent::TaskStarted { .. }
+            | StateEvent::ToolUseCompleted { .. }
+            | StateEvent::PermissionGranted { .. }
+            | StateEvent::InputReceived { .. } => Self::processing(None),
+            StateEvent::ToolUseStarted { tool_name } => Self::processing(Some(tool_name.clone())),
+            StateEvent::PermissionRequired { tool_name, tool_input, request_id } => {
+                Self::waiting_for_permission(tool_name.clone(), tool_input.clone(), request_id.clone())
+            }
+            StateEvent::PermissionDenied { reason, .. } => {
+                Self::error(format!("Permission denied: {}", reason), true)
+            }
+            StateEvent::InputRequired { question, options } => {
+                Self::waiting_for_input(question.clone(), options.clone())
+            }
+            StateEvent::ErrorOccurred { message, recoverable } => Self::error(message.clone(), *recoverable),
+            StateEvent::TaskCompleted { output } => Self::completed(output.clone()),
+        }
+    }
+
     /// 処理中かどうか
     pub fn is_processing(&self) -> bool {
         matches!(self, Self::Processing { .. })
@@ -172,54 +200,214 @@ pub enum StateEvent {
     TaskCompleted { output: String },
 }
 
+/// 状態遷移履歴の既定の保持件数
+pub const DEFAULT_HISTORY_LIMIT: usize = 200;
+
+/// 状態遷移の記録
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateTransition {
+    /// 遷移のきっかけになったイベント（強制設定の場合はNone）
+    pub event: Option<StateEvent>,
+    /// 遷移前の状態
+    pub from: AgentState,
+    /// 遷移後の状態
+    pub to: AgentState,
+    /// 遷移時刻
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub at: DateTime<Utc>,
+}
+
+/// 遷移表にないイベントを受け取ったときの扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidTransitionPolicy {
+    /// 警告を記録して現在の状態を維持する
+    #[default]
+    Keep,
+    /// 警告を記録し、イベントが示す状態に補正する
+    ForceCorrect,
+}
+
+/// 不正な遷移の記録（`executor:invalid_transition` イベント）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvalidTransition {
+    /// 受け取ったときの状態名
+    pub state: String,
+    /// 受け取ったイベント
+    pub event: StateEvent,
+    /// 補正後の状態名（補正しなかった場合はNone）
+    pub corrected_to: Option<String>,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub at: DateTime<Utc>,
+}
+
+/// ログ用のイベント名
+fn event_name(event: &StateEvent) -> &'static str {
+    match event {
+        StateEvent::Initialized => "initialized",
+        StateEvent::TaskStarted { .. } => "task_started",
+        StateEvent::ToolUseStarted { .. } => "tool_use_started",
+        StateEvent::ToolUseCompleted { .. } => "tool_use_completed",
+        StateEvent::PermissionRequired { .. } => "permission_required",
+        StateEvent::PermissionGranted { .. } => "permission_granted",
+        StateEvent::PermissionDenied { .. } => "permission_denied",
+        StateEvent::InputRequired { .. } => "input_required",
+        StateEvent::InputReceived { .. } => "input_received",
+        StateEvent::ErrorOccurred { .. } => "error_occurred",
+        StateEvent::TaskCompleted { .. } => "task_completed",
+    }
+}
+
+/// 状態遷移のリスナー
+///
+/// 状態マシンのロックを保持したまま呼ばれるため、リスナー内で状態マシンを操作してはいけない。
+pub type StateListener = Arc<dyn Fn(&StateTransition) + Send + Sync>;
+
+/// 登録済みリスナー
+#[derive(Clone, Default)]
+struct Listeners {
+    next_id: u64,
+    entries: Vec<(u64, StateListener)>,
+}
+
+impl std::fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Listeners").field("count", &self.entries.len()).finish()
+    }
+}
+
 /// 状態マシン
 #[derive(Debug, Clone)]
 pub struct StateMachine {
     /// 現在の状態
     current_state: AgentState,
-    /// 状態履歴（デバッグ用）
-    history: Vec<(AgentState, DateTime<Utc>)>,
+    /// 状態遷移履歴（デバッグ用、古いものから捨てる）
+    history: VecDeque<StateTransition>,
+    /// 履歴の最大件数
+    history_limit: usize,
+    /// 不正な遷移の扱い
+    policy: InvalidTransitionPolicy,
+    /// 直前の不正な遷移（取り出すまで保持）
+    last_invalid: Option<InvalidTransition>,
+    /// 不正な遷移の累計
+    invalid_count: usize,
+    /// 状態遷移のリスナー
+    listeners: Listeners,
 }
 
 impl StateMachine {
     /// 新しい状態マシンを作成
     pub fn new() -> Self {
-        let initial_state = AgentState::Initializing;
+        Self::with_history_limit(DEFAULT_HISTORY_LIMIT)
+    }
+
+    /// 履歴の最大件数を指定して作成
+    pub fn with_history_limit(history_limit: usize) -> Self {
         Self {
-            current_state: initial_state.clone(),
-            history: vec![(initial_state, Utc::now())],
+            current_state: AgentState::Initializing,
+            history: VecDeque::new(),
+            history_limit: history_limit.max(1),
+            policy: InvalidTransitionPolicy::default(),
+            last_invalid: None,
+            invalid_count: 0,
+            listeners: Listeners::default(),
         }
     }
 
+    /// 状態遷移のリスナーを登録し、解除用のIDを返す
+    pub fn subscribe<F>(&mut self, listener: F) -> u64
+    where
+        F: Fn(&StateTransition) + Send + Sync + 'static,
+    {
+        let id = self.listeners.next_id;
+        self.listeners.next_id += 1;
+        self.listeners.entries.push((id, Arc::new(listener)));
+        id
+    }
+
+    /// リスナーを解除
+    pub fn unsubscribe(&mut self, id: u64) -> bool {
+        let before = self.listeners.entries.len();
+        self.listeners.entries.retain(|(entry_id, _)| *entry_id != id);
+        self.listeners.entries.len() != before
+    }
+
+    /// 不正な遷移の扱いを設定
+    pub fn with_policy(mut self, policy: InvalidTransitionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
     /// 現在の状態を取得
     pub fn current_state(&self) -> &AgentState {
         &self.current_state
     }
 
     /// イベントを処理して状態を遷移
+    ///
+    /// 遷移表にないイベントは警告を記録し、ポリシーに従って状態を維持するか補正する。
     pub fn transition(&mut self, event: StateEvent) -> AgentState {
-        let new_state = self.apply_event(&event);
+        let new_state = match self.apply_event(&event) {
+            Some(state) => state,
+            None => {
+                let corrected = match self.policy {
+                    InvalidTransitionPolicy::Keep => None,
+                    InvalidTransitionPolicy::ForceCorrect => Some(AgentState::for_event(&event)),
+                };
+                let invalid = InvalidTransition {
+                    state: self.current_state.state_name().to_string(),
+                    event: event.clone(),
+                    corrected_to: corrected.as_ref().map(|s| s.state_name().to_string()),
+                    at: Utc::now(),
+                };
+                log::warn("StateMachine", &format!(
+                    "Invalid transition: {} + {:?} ({})",
+                    invalid.state,
+                    event_name(&event),
+                    invalid.corrected_to.as_deref().map(|s| format!("corrected to {}", s)).unwrap_or_else(|| "ignored".to_string())
+                ));
+                self.invalid_count += 1;
+                self.last_invalid = Some(invalid);
+                corrected.unwrap_or_else(|| self.current_state.clone())
+            }
+        };
+        self.record(Some(event), new_state);
+        self.current_state.clone()
+    }
 
-        // 履歴に追加（最大100件）
-        self.history.push((new_state.clone(), Utc::now()));
-        if self.history.len() > 100 {
-            self.history.remove(0);
+    /// 遷移を履歴に記録して現在の状態を更新
+    fn record(&mut self, event: Option<StateEvent>, new_state: AgentState) {
+        let from = std::mem::replace(&mut self.current_state, new_state);
+        let transition = StateTransition {
+            event,
+            from,
+            to: self.current_state.clone(),
+            at: Utc::now(),
+        };
+        for (_, listener) in &self.listeners.entries {
+            listener(&transition);
+        }
+        self.history.push_back(transition);
+        while self.history.len() > self.history_limit {
+            self.history.pop_front();
         }
-
-        self.current_state = new_state;
-        self.current_state.clone()
     }
 
-    /// イベントを適用して新しい状態を計算
-    fn apply_event(&self, event: &StateEvent) -> AgentState {
-        match (&self.current_state, event) {
+    /// イベントを適用して新しい状態を計算（遷移表にない組み合わせはNone）
+    fn apply_event(&self, event: &StateEvent) -> Option<AgentState> {
+        let next = match (&self.current_state, event) {
             // Initializingからの遷移
             (AgentState::Initializing, StateEvent::Initialized) => AgentState::idle(),
 
             // Idleからの遷移
+            (AgentState::Idle, StateEvent::Initialized) => self.current_state.clone(),
             (AgentState::Idle, StateEvent::TaskStarted { .. }) => AgentState::processing(None),
 
             // Processingからの遷移
+            (AgentState::Processing { .. }, StateEvent::Initialized) => {
+                // プロンプト送信後にinitイベントが届くことがある
+                self.current_state.clone()
+            }
             (AgentState::Processing { .. }, StateEvent::ToolUseStarted { tool_name }) => {
                 AgentState::processing(Some(tool_name.clone()))
             }
@@ -270,20 +458,30 @@ impl StateMachine {
                 AgentState::idle()
             }
 
-            // その他の遷移は状態維持
-            _ => self.current_state.clone(),
-        }
+            // 遷移表にない組み合わせ
+            _ => return None,
+        };
+        Some(next)
+    }
+
+    /// 直前の不正な遷移を取り出す
+    pub fn take_invalid(&mut self) -> Option<InvalidTransition> {
+        self.last_invalid.take()
     }
 
-    /// 状態履歴を取得
-    pub fn history(&self) -> &[(AgentState, DateTime<Utc>)] {
+    /// 検出した不正な遷移の累計
+    pub fn invalid_count(&self) -> usize {
+        self.invalid_count
+    }
+
+    /// 状態遷移履歴を取得（古い順）
+    pub fn history(&self) -> &VecDeque<StateTransition> {
         &self.history
     }
 
     /// 強制的に状態を設定（復旧用）
     pub fn force_state(&mut self, state: AgentState) {
-        self.history.push((state.clone(), Utc::now()));
-        self.current_state = state;
+        self.record(None, state);
     }
 }
 
@@ -373,4 +571,74 @@ mod tests {
             _ => panic!("Expected WaitingForPermission"),
         }
     }
+
+    #[test]
+    fn test_history_records_transitions() {
+        let mut sm = StateMachine::with_history_limit(2);
+        sm.transition(StateEvent::Initialized);
+        sm.transition(StateEvent::TaskStarted { prompt: "test".to_string() });
+        sm.transition(StateEvent::TaskCompleted { output: "done".to_string() });
+
+        let history: Vec<_> = sm.history().iter().collect();
+        assert_eq!(history.len(), 2);
+        assert!(matches!(history[0].event, Some(StateEvent::TaskStarted { .. })));
+        assert_eq!(history[0].from, AgentState::Idle);
+        assert!(matches!(history[1].from, AgentState::Processing { .. }));
+        assert!(matches!(history[1].to, AgentState::Completed { .. }));
+
+        sm.force_state(AgentState::idle());
+        let last = sm.history().back().unwrap();
+        assert!(last.event.is_none());
+        assert_eq!(last.to, AgentState::Idle);
+    }
+
+    #[test]
+    fn test_invalid_transition_kept() {
+        let mut sm = StateMachine::new();
+        sm.transition(StateEvent::Initialized);
+
+        // Idle中のツール使用はパーサーの誤分類
+        let state = sm.transition(StateEvent::ToolUseStarted { tool_name: "Read".to_string() });
+        assert_eq!(state, AgentState::Idle);
+        assert_eq!(sm.invalid_count(), 1);
+
+        let invalid = sm.take_invalid().unwrap();
+        assert_eq!(invalid.state, "idle");
+        assert!(invalid.corrected_to.is_none());
+        assert!(sm.take_invalid().is_none());
+    }
+
+    #[test]
+    fn test_invalid_transition_force_corrected() {
+        let mut sm = StateMachine::new().with_policy(InvalidTransitionPolicy::ForceCorrect);
+        sm.transition(StateEvent::Initialized);
+
+        let state = sm.transition(StateEvent::TaskCompleted { output: "done".to_string() });
+        assert!(matches!(state, AgentState::Completed { .. }));
+        assert_eq!(sm.take_invalid().unwrap().corrected_to.as_deref(), Some("completed"));
+    }
+
+    #[test]
+    fn test_listeners_notified() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let completed = Arc::new(AtomicUsize::new(0));
+        let counter = completed.clone();
+        let mut sm = StateMachine::new();
+        let id = sm.subscribe(move |transition| {
+            if matches!(transition.to, AgentState::Completed { .. }) {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        sm.transition(StateEvent::Initialized);
+        sm.transition(StateEvent::TaskStarted { prompt: "test".to_string() });
+        sm.transition(StateEvent::TaskCompleted { output: "done".to_string() });
+        assert_eq!(completed.load(Ordering::SeqCst), 1);
+
+        assert!(sm.unsubscribe(id));
+        sm.transition(StateEvent::TaskStarted { prompt: "again".to_string() });
+        sm.transition(StateEvent::TaskCompleted { output: "done".to_string() });
+        assert_eq!(completed.load(Ordering::SeqCst), 1);
+    }
 }
@@ -1,11 +1,14 @@
 //! Stream JSON Parser for Claude Code CLI
 //!
 //! `--print --output-format stream-json` の出力をパースする。
-//! 各行は独立したJSONオブジェクト。
+//! 通常は1行に1つのJSONオブジェクトだが、大きなメッセージが複数回の読み込みに
+//! 分かれて届くこともあるため、完全なJSONになるまでバッファに溜めてからパースする。
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Read};
+use std::time::Instant;
 use thiserror::Error;
 
 use crate::log;
@@ -93,6 +96,9 @@ pub enum StreamEvent {
         num_turns: Option<u32>,
         #[serde(default)]
         total_cost_usd: Option<f64>,
+        /// このリクエストのトークン数
+        #[serde(default)]
+        usage: Option<Usage>,
         #[serde(default)]
         permission_denials: Vec<Value>,
     },
@@ -146,6 +152,61 @@ pub struct Usage {
     pub cache_read_input_tokens: Option<u64>,
 }
 
+/// トークン数とコストの集計（リクエスト単位・セッション単位・パイプライン実行単位で使う）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+    pub cost_usd: f64,
+    /// 集計したリクエスト数
+    pub requests: u64,
+}
+
+impl UsageTotals {
+    /// resultイベントの使用量から1リクエスト分を作る（どちらもなければNone）
+    pub fn from_result(usage: Option<&Usage>, cost_usd: Option<f64>) -> Option<Self> {
+        if usage.is_none() && cost_usd.is_none() {
+            return None;
+        }
+        Some(Self {
+            input_tokens: usage.map_or(0, |u| u.input_tokens),
+            output_tokens: usage.map_or(0, |u| u.output_tokens),
+            cache_creation_input_tokens: usage.and_then(|u| u.cache_creation_input_tokens).unwrap_or(0),
+            cache_read_input_tokens: usage.and_then(|u| u.cache_read_input_tokens).unwrap_or(0),
+            cost_usd: cost_usd.unwrap_or(0.0),
+            requests: 1,
+        })
+    }
+
+    /// 加算
+    pub fn add(&mut self, other: &UsageTotals) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+        self.cost_usd += other.cost_usd;
+        self.requests += other.requests;
+    }
+
+    /// `earlier` の時点からの増分
+    pub fn since(&self, earlier: &UsageTotals) -> Self {
+        Self {
+            input_tokens: self.input_tokens.saturating_sub(earlier.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(earlier.output_tokens),
+            cache_creation_input_tokens: self.cache_creation_input_tokens.saturating_sub(earlier.cache_creation_input_tokens),
+            cache_read_input_tokens: self.cache_read_input_tokens.saturating_sub(earlier.cache_read_input_tokens),
+            cost_usd: (self.cost_usd - earlier.cost_usd).max(0.0),
+            requests: self.requests.saturating_sub(earlier.requests),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests == 0
+    }
+}
+
 /// エラー詳細
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorDetail {
@@ -161,49 +222,168 @@ pub enum ParsedEvent {
     StateChange(StateEvent),
     /// 生のテキスト出力（ストリーミング）
     TextOutput(String),
-    /// ツール実行情報
+    /// ツール実行情報（tool_useと対応するtool_resultをまとめたもの）
     ToolExecution {
+        /// tool_use ID
+        id: String,
         name: String,
         input: Value,
         result: Option<String>,
         is_error: bool,
+        /// tool_useからtool_resultまでの時間（対応するtool_useがなければNone）
+        duration_ms: Option<u64>,
     },
     /// 進捗情報
     Progress {
         message: String,
         percentage: Option<u8>,
     },
+    /// 1リクエスト分のトークン数・コスト（resultイベントから）
+    Usage(UsageTotals),
+}
+
+/// 結果待ちのツール
+#[derive(Debug, Clone)]
+struct PendingTool {
+    name: String,
+    input: Value,
+    started_at: Instant,
 }
 
+/// バッファの上限（これを超えたら壊れた断片とみなして捨てる）
+const MAX_BUFFER_BYTES: usize = 16 * 1024 * 1024;
+
 /// Stream JSON Parser
 pub struct StreamParser {
-    /// 現在処理中のツールID
-    current_tool_id: Option<String>,
-    /// 現在のツール名
-    current_tool_name: Option<String>,
+    /// 未完成のJSON断片
+    buffer: String,
+    /// 読み飛ばした壊れた断片の数
+    skipped: usize,
+    /// 結果待ちのツール（tool_use ID → 開始情報）
+    pending_tools: HashMap<String, PendingTool>,
+    /// 最後に受信したセッションID（init/resultイベントから取得）
+    session_id: Option<String>,
 }
 
 impl StreamParser {
     pub fn new() -> Self {
         Self {
-            current_tool_id: None,
-            current_tool_name: None,
+            buffer: String::new(),
+            skipped: 0,
+            pending_tools: HashMap::new(),
+            session_id: None,
         }
     }
 
-    /// 1行のJSONをパースしてイベントを生成
+    /// 未完成のJSON断片がバッファに残っているか
+    pub fn has_pending(&self) -> bool {
+        !self.buffer.trim().is_empty()
+    }
+
+    /// 読み飛ばした壊れた断片の数
+    pub fn skipped_fragments(&self) -> usize {
+        self.skipped
+    }
+
+    /// Claude CodeのセッションID（`--resume` に渡せる）
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// 1行をパースしてイベントを生成
+    ///
+    /// JSONが行の途中で終わっている場合は次の行と合わせてパースする。
     pub fn parse_line(&mut self, line: &str) -> Result<Vec<ParsedEvent>, ParseError> {
-        let line = line.trim();
-        if line.is_empty() {
+        if line.trim().is_empty() && !self.has_pending() {
             return Ok(vec![]);
         }
+        // 行区切りはJSONの文字列中には現れないので、空白として連結してよい
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+        self.drain_events()
+    }
+
+    /// 任意の位置で区切られた読み込み結果をパースしてイベントを生成
+    pub fn parse_chunk(&mut self, chunk: &str) -> Result<Vec<ParsedEvent>, ParseError> {
+        self.buffer.push_str(chunk);
+        self.drain_events()
+    }
 
-        let event: StreamEvent = serde_json::from_str(line)?;
+    /// バッファから完全なJSONを取り出してイベントに変換
+    fn drain_events(&mut self) -> Result<Vec<ParsedEvent>, ParseError> {
+        let mut events = Vec::new();
+        for value in self.drain_values() {
+            match serde_json::from_value::<StreamEvent>(value) {
+                Ok(event) => events.extend(self.process_event(&event)?),
+                Err(e) => {
+                    // 未知のイベントタイプなど。1件のために他のイベントを失わないよう読み飛ばす
+                    self.skipped += 1;
+                    log::warn("StreamParser", &format!("Skipping unrecognized event: {}", e));
+                }
+            }
+        }
 
-        let events = self.process_event(&event)?;
+        if self.buffer.len() > MAX_BUFFER_BYTES {
+            log::warn("StreamParser", &format!(
+                "Discarding {} bytes of incomplete JSON", self.buffer.len()
+            ));
+            self.buffer.clear();
+            self.skipped += 1;
+        }
         Ok(events)
     }
 
+    /// バッファから完全なJSON値を順に取り出す（未完成の末尾は残す）
+    ///
+    /// 壊れた断片は次の行頭の `{` まで読み飛ばして再同期する。
+    fn drain_values(&mut self) -> Vec<Value> {
+        let mut values = Vec::new();
+        loop {
+            let start = self.buffer.len() - self.buffer.trim_start().len();
+            if start == self.buffer.len() {
+                self.buffer.clear();
+                break;
+            }
+
+            let mut stream = serde_json::Deserializer::from_str(&self.buffer[start..]).into_iter::<Value>();
+            match stream.next() {
+                Some(Ok(value)) => {
+                    let end = start + stream.byte_offset();
+                    values.push(value);
+                    self.buffer.drain(..end);
+                }
+                // 続きが届くまで待つ
+                Some(Err(e)) if e.is_eof() => {
+                    self.buffer.drain(..start);
+                    break;
+                }
+                Some(Err(e)) => {
+                    self.skipped += 1;
+                    let resync = self.buffer[start + 1..].find("\n{").map(|i| start + 1 + i + 1);
+                    log::warn("StreamParser", &format!(
+                        "Malformed JSON fragment ({}), resyncing: {}",
+                        e,
+                        truncate_for_log(&self.buffer[start..], 100)
+                    ));
+                    match resync {
+                        Some(next) => {
+                            self.buffer.drain(..next);
+                        }
+                        None => {
+                            self.buffer.clear();
+                            break;
+                        }
+                    }
+                }
+                None => {
+                    self.buffer.clear();
+                    break;
+                }
+            }
+        }
+        values
+    }
+
     /// ストリームからイベントを読み込む
     pub fn parse_stream<R: Read>(
         &mut self,
@@ -228,6 +408,9 @@ impl StreamParser {
     fn process_event(&mut self, event: &StreamEvent) -> Result<Vec<ParsedEvent>, ParseError> {
         match event {
             StreamEvent::System { subtype, session_id, model, .. } => {
+                if session_id.is_some() {
+                    self.session_id = session_id.clone();
+                }
                 if subtype == "init" {
                     log::info("StreamParser", &format!(
                         "Initialized: session={:?}, model={:?}",
@@ -253,12 +436,19 @@ impl StreamParser {
                     }));
                 }
 
-                // テキストコンテンツを抽出
+                // テキスト・ツール使用を抽出
                 for block in &message.content {
-                    if let ContentBlock::Text { text } = block {
-                        if !text.is_empty() {
+                    match block {
+                        ContentBlock::Text { text } if !text.is_empty() => {
                             events.push(ParsedEvent::TextOutput(text.clone()));
                         }
+                        ContentBlock::ToolUse { id, name, input } => {
+                            events.extend(self.start_tool(id, name, input));
+                        }
+                        ContentBlock::ToolResult { tool_use_id, content, is_error } => {
+                            events.extend(self.finish_tool(tool_use_id, content, *is_error));
+                        }
+                        _ => {}
                     }
                 }
 
@@ -266,86 +456,31 @@ impl StreamParser {
             }
 
             StreamEvent::ToolUse { id, name, input } => {
-                self.current_tool_id = Some(id.clone());
-                self.current_tool_name = Some(name.clone());
-
-                log::info("StreamParser", &format!("Tool use: {} ({})", name, id));
-
-                Ok(vec![
-                    ParsedEvent::StateChange(StateEvent::ToolUseStarted {
-                        tool_name: name.clone(),
-                    }),
-                    ParsedEvent::ToolExecution {
-                        name: name.clone(),
-                        input: input.clone(),
-                        result: None,
-                        is_error: false,
-                    },
-                ])
+                Ok(self.start_tool(id, name, input))
             }
 
             StreamEvent::ToolResult { tool_use_id, content, is_error } => {
-                log::info("StreamParser", &format!(
-                    "Tool result for {}: error={}, len={}",
-                    tool_use_id, is_error, content.len()
-                ));
-
-                // 権限エラーかどうかチェック
-                if *is_error && self.is_permission_error(content) {
-                    // 権限エラーの場合
-                    let tool_name = self.current_tool_name.clone().unwrap_or_else(|| "unknown".to_string());
-                    let tool_input = serde_json::json!({});
-
-                    return Ok(vec![ParsedEvent::StateChange(
-                        StateEvent::PermissionRequired {
-                            tool_name,
-                            tool_input,
-                            request_id: tool_use_id.clone(),
-                        },
-                    )]);
-                }
-
-                // 通常のツール完了
-                let tool_name = self.current_tool_name.clone();
-
-                // ツール情報をクリア
-                if self.current_tool_id.as_deref() == Some(tool_use_id) {
-                    self.current_tool_id = None;
-                    self.current_tool_name = None;
-                }
-
-                let mut events = vec![ParsedEvent::StateChange(StateEvent::ToolUseCompleted {
-                    tool_name: tool_name.unwrap_or_else(|| "unknown".to_string()),
-                    success: !is_error,
-                })];
-
-                // エラーの場合
-                if *is_error {
-                    events.push(ParsedEvent::StateChange(StateEvent::ErrorOccurred {
-                        message: content.clone(),
-                        recoverable: true,
-                    }));
-                }
-
-                events.push(ParsedEvent::ToolExecution {
-                    name: "result".to_string(),
-                    input: serde_json::json!({}),
-                    result: Some(content.clone()),
-                    is_error: *is_error,
-                });
-
-                Ok(events)
+                Ok(self.finish_tool(tool_use_id, content, *is_error))
             }
 
-            StreamEvent::Result { subtype, result, is_error, session_id, cost_usd, duration_ms, permission_denials, .. } => {
+            StreamEvent::Result { subtype, result, is_error, session_id, cost_usd, total_cost_usd, duration_ms, usage, permission_denials, .. } => {
                 log::info("StreamParser", &format!(
                     "Result: subtype={:?}, session={:?}, cost={:?}, duration={:?}ms, is_error={}, denials={}",
                     subtype, session_id, cost_usd, duration_ms, is_error, permission_denials.len()
                 ));
+                if session_id.is_some() {
+                    self.session_id = session_id.clone();
+                }
 
                 // 結果テキストを取得
                 let output = result.clone().unwrap_or_default();
 
+                // 失敗したリクエストでも課金されるので、完了・エラーより先に使用量を通知
+                let mut events: Vec<ParsedEvent> = UsageTotals::from_result(usage.as_ref(), cost_usd.or(*total_cost_usd))
+                    .map(ParsedEvent::Usage)
+                    .into_iter()
+                    .collect();
+
                 // 権限拒否がある場合
                 if !permission_denials.is_empty() {
                     log::info("StreamParser", &format!("Permission denials: {:?}", permission_denials));
@@ -353,7 +488,7 @@ impl StreamParser {
 
                 // エラーの場合
                 if *is_error || subtype.as_deref() == Some("error") {
-                    return Ok(vec![
+                    events.extend([
                         ParsedEvent::StateChange(StateEvent::ErrorOccurred {
                             message: output.clone(),
                             recoverable: true,
@@ -363,9 +498,10 @@ impl StreamParser {
                             percentage: Some(0),
                         },
                     ]);
+                    return Ok(events);
                 }
 
-                Ok(vec![
+                events.extend([
                     ParsedEvent::StateChange(StateEvent::TaskCompleted {
                         output: output.clone(),
                     }),
@@ -373,7 +509,8 @@ impl StreamParser {
                         message: format!("Completed in {:?}ms", duration_ms),
                         percentage: Some(100),
                     },
-                ])
+                ]);
+                Ok(events)
             }
 
             StreamEvent::Error { error } => {
@@ -387,6 +524,71 @@ impl StreamParser {
         }
     }
 
+    /// ツール使用開始を記録
+    fn start_tool(&mut self, id: &str, name: &str, input: &Value) -> Vec<ParsedEvent> {
+        log::info("StreamParser", &format!("Tool use: {} ({})", name, id));
+
+        self.pending_tools.insert(id.to_string(), PendingTool {
+            name: name.to_string(),
+            input: input.clone(),
+            started_at: Instant::now(),
+        });
+
+        vec![ParsedEvent::StateChange(StateEvent::ToolUseStarted {
+            tool_name: name.to_string(),
+        })]
+    }
+
+    /// ツール結果を対応するtool_useとまとめる
+    fn finish_tool(&mut self, tool_use_id: &str, content: &str, is_error: bool) -> Vec<ParsedEvent> {
+        log::info("StreamParser", &format!(
+            "Tool result for {}: error={}, len={}",
+            tool_use_id, is_error, content.len()
+        ));
+
+        // 権限エラーの場合は結果待ちのまま（許可後に再実行される）
+        if is_error && self.is_permission_error(content) {
+            let (tool_name, tool_input) = self.pending_tools.get(tool_use_id)
+                .map(|tool| (tool.name.clone(), tool.input.clone()))
+                .unwrap_or_else(|| ("unknown".to_string(), serde_json::json!({})));
+
+            return vec![ParsedEvent::StateChange(StateEvent::PermissionRequired {
+                tool_name,
+                tool_input,
+                request_id: tool_use_id.to_string(),
+            })];
+        }
+
+        let pending = self.pending_tools.remove(tool_use_id);
+        if pending.is_none() {
+            log::warn("StreamParser", &format!("Tool result without matching tool_use: {}", tool_use_id));
+        }
+        let name = pending.as_ref().map(|tool| tool.name.clone()).unwrap_or_else(|| "unknown".to_string());
+
+        let mut events = vec![ParsedEvent::StateChange(StateEvent::ToolUseCompleted {
+            tool_name: name.clone(),
+            success: !is_error,
+        })];
+
+        if is_error {
+            events.push(ParsedEvent::StateChange(StateEvent::ErrorOccurred {
+                message: content.to_string(),
+                recoverable: true,
+            }));
+        }
+
+        events.push(ParsedEvent::ToolExecution {
+            id: tool_use_id.to_string(),
+            name,
+            input: pending.as_ref().map(|tool| tool.input.clone()).unwrap_or_else(|| serde_json::json!({})),
+            result: Some(content.to_string()),
+            is_error,
+            duration_ms: pending.map(|tool| tool.started_at.elapsed().as_millis() as u64),
+        });
+
+        events
+    }
+
     /// 権限エラーかどうかを判定
     fn is_permission_error(&self, content: &str) -> bool {
         // Claude Codeの権限エラーパターン
@@ -471,6 +673,18 @@ pub struct PermissionRequest {
     pub request_id: String,
 }
 
+/// ログ用にUTF-8境界で切り詰める
+fn truncate_for_log(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut boundary = max_bytes;
+    while boundary > 0 && !s.is_char_boundary(boundary) {
+        boundary -= 1;
+    }
+    &s[..boundary]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -487,6 +701,7 @@ mod tests {
             ParsedEvent::StateChange(StateEvent::Initialized) => {}
             _ => panic!("Expected Initialized event"),
         }
+        assert_eq!(parser.session_id(), Some("test-123"));
     }
 
     #[test]
@@ -523,6 +738,30 @@ mod tests {
         assert!(found);
     }
 
+    #[test]
+    fn test_parse_result_usage() {
+        let mut parser = StreamParser::new();
+        let line = r#"{"type":"result","subtype":"success","result":"Done!","total_cost_usd":0.012,"usage":{"input_tokens":120,"output_tokens":45,"cache_read_input_tokens":800}}"#;
+
+        let events = parser.parse_line(line).unwrap();
+        let ParsedEvent::Usage(usage) = &events[0] else {
+            panic!("usage should come before completion: {:?}", events);
+        };
+        assert_eq!((usage.input_tokens, usage.output_tokens, usage.cache_read_input_tokens), (120, 45, 800));
+        assert_eq!(usage.cache_creation_input_tokens, 0);
+        assert_eq!(usage.requests, 1);
+
+        let mut totals = UsageTotals::default();
+        totals.add(usage);
+        totals.add(usage);
+        assert_eq!(totals.since(usage).input_tokens, 120);
+        assert!((totals.cost_usd - 0.024).abs() < 1e-9);
+
+        // 使用量のないresultでは通知しない
+        let events = parser.parse_line(r#"{"type":"result","subtype":"success","result":"ok"}"#).unwrap();
+        assert!(!events.iter().any(|e| matches!(e, ParsedEvent::Usage(_))));
+    }
+
     #[test]
     fn test_parse_permission_request() {
         let content = r#"Bash requires approval
@@ -538,4 +777,63 @@ Do you want to proceed?
         assert_eq!(request.tool_name, "Bash");
         assert!(request.options.contains(&"Yes".to_string()));
     }
+
+    #[test]
+    fn test_buffers_split_json() {
+        let mut parser = StreamParser::new();
+        assert!(parser.parse_chunk(r#"{"type":"result","subtype":"succ"#).unwrap().is_empty());
+        assert!(parser.has_pending());
+
+        let events = parser.parse_chunk("ess\",\"result\":\"Done!\"}\n").unwrap();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ParsedEvent::StateChange(StateEvent::TaskCompleted { output }) if output == "Done!"
+        )));
+        assert!(!parser.has_pending());
+
+        // 整形された複数行のJSON
+        assert!(parser.parse_line("{").unwrap().is_empty());
+        let events = parser.parse_line(r#"  "type": "system", "subtype": "init", "session_id": "s-2" }"#).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(parser.session_id(), Some("s-2"));
+    }
+
+    #[test]
+    fn test_resyncs_after_malformed_fragment() {
+        let mut parser = StreamParser::new();
+        assert!(parser.parse_line(r#"{"type":"assistant","message":{"content":"#).unwrap().is_empty());
+        assert!(parser.parse_line("garbage").unwrap().is_empty());
+        let events = parser.parse_line(r#"{"type":"result","subtype":"success","result":"ok"}"#).unwrap();
+
+        assert!(events.iter().any(|e| matches!(e, ParsedEvent::StateChange(StateEvent::TaskCompleted { .. }))));
+        assert_eq!(parser.skipped_fragments(), 1);
+        assert!(!parser.has_pending());
+    }
+
+    #[test]
+    fn test_pairs_concurrent_tool_results() {
+        let mut parser = StreamParser::new();
+        parser.parse_line(r#"{"type":"tool_use","id":"t1","name":"Read","input":{"file_path":"/a"}}"#).unwrap();
+        parser.parse_line(r#"{"type":"tool_use","id":"t2","name":"Grep","input":{"pattern":"x"}}"#).unwrap();
+
+        // 先に開始したReadより後のGrepが先に終わる
+        let events = parser.parse_line(r#"{"type":"tool_result","tool_use_id":"t2","content":"match"}"#).unwrap();
+        let execution = events.iter().find_map(|e| match e {
+            ParsedEvent::ToolExecution { id, name, input, duration_ms, is_error, .. } => {
+                Some((id.clone(), name.clone(), input.clone(), *duration_ms, *is_error))
+            }
+            _ => None,
+        }).unwrap();
+        assert_eq!(execution.0, "t2");
+        assert_eq!(execution.1, "Grep");
+        assert_eq!(execution.2["pattern"], "x");
+        assert!(execution.3.is_some());
+        assert!(!execution.4);
+
+        let events = parser.parse_line(r#"{"type":"tool_result","tool_use_id":"t1","content":"text"}"#).unwrap();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ParsedEvent::StateChange(StateEvent::ToolUseCompleted { tool_name, success: true }) if tool_name == "Read"
+        )));
+    }
 }
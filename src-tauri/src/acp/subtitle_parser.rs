@@ -1,6 +1,6 @@
-//! VTT字幕パーサー
+//! VTT/SRT字幕パーサー
 //!
-//! WebVTT形式の字幕ファイルをパースし、翻訳処理用のデータ構造に変換する。
+//! WebVTT・SubRip形式の字幕ファイルをパースし、翻訳処理用のデータ構造に変換する。
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -29,6 +29,9 @@ pub struct SubtitleSegment {
     pub end_ms: u64,
     /// 字幕テキスト
     pub text: String,
+    /// 話者ラベル（VTTの `<v 話者>` タグ、または話者分離した場合）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
 }
 
 impl SubtitleSegment {
@@ -39,6 +42,7 @@ impl SubtitleSegment {
             start_ms,
             end_ms,
             text,
+            speaker: None,
         }
     }
 
@@ -48,6 +52,55 @@ impl SubtitleSegment {
     }
 }
 
+/// 字幕ファイル形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Vtt,
+    Srt,
+}
+
+impl SubtitleFormat {
+    /// 拡張子から形式を判定（`.srt` 以外はVTT）
+    pub fn from_path(path: &str) -> Self {
+        let is_srt = std::path::Path::new(path)
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("srt"))
+            .unwrap_or(false);
+        if is_srt { SubtitleFormat::Srt } else { SubtitleFormat::Vtt }
+    }
+
+    /// ファイル拡張子
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Vtt => "vtt",
+            SubtitleFormat::Srt => "srt",
+        }
+    }
+
+    /// この形式としてパース
+    pub fn parse(&self, content: &str) -> Result<Vec<SubtitleSegment>, ParseError> {
+        match self {
+            SubtitleFormat::Vtt => VttParser::parse(content),
+            SubtitleFormat::Srt => SrtParser::parse(content),
+        }
+    }
+
+    /// 翻訳済みテキストからこの形式の字幕を再構築
+    pub fn rebuild(&self, original: &[SubtitleSegment], translated: &[String]) -> String {
+        match self {
+            SubtitleFormat::Vtt => VttParser::rebuild_vtt(original, translated),
+            SubtitleFormat::Srt => SrtParser::rebuild_srt(original, translated),
+        }
+    }
+}
+
+/// 字幕ファイルを拡張子で形式を判定してパース
+pub fn parse_subtitle_file(path: &str) -> Result<Vec<SubtitleSegment>, ParseError> {
+    let content = std::fs::read_to_string(path)?;
+    SubtitleFormat::from_path(path).parse(&content)
+}
+
 /// VTTパーサー
 pub struct VttParser;
 
@@ -84,6 +137,7 @@ impl VttParser {
 
                 // テキストを収集
                 let mut text_lines = Vec::new();
+                let mut speaker = None;
                 i += 1;
 
                 while i < lines.len() {
@@ -91,6 +145,9 @@ impl VttParser {
                     if text_line.is_empty() || text_line.contains("-->") {
                         break;
                     }
+                    if speaker.is_none() {
+                        speaker = Self::voice_label(text_line);
+                    }
                     // タグを除去
                     let clean_text = Self::strip_vtt_tags(text_line);
                     if !clean_text.is_empty() {
@@ -101,7 +158,9 @@ impl VttParser {
 
                 if !text_lines.is_empty() {
                     let text = text_lines.join("\n");
-                    segments.push(SubtitleSegment::new(index, start_ms, end_ms, text));
+                    let mut segment = SubtitleSegment::new(index, start_ms, end_ms, text);
+                    segment.speaker = speaker;
+                    segments.push(segment);
                     index += 1;
                 }
 
@@ -179,15 +238,24 @@ impl VttParser {
         Ok(seconds * 1000 + millis)
     }
 
+    /// 話者タグ（`<v 話者>` / `<v.クラス 話者>`）のラベル
+    fn voice_label(text: &str) -> Option<String> {
+        let re = regex::Regex::new(r"<v(?:\.[^\s>]+)*\s+([^>]+)>").ok()?;
+        re.captures(text)
+            .map(|c| c[1].trim().to_string())
+            .filter(|label| !label.is_empty())
+    }
+
     /// VTTタグを除去
     fn strip_vtt_tags(text: &str) -> String {
         let mut result = text.to_string();
-        // <b>, </b>, <i>, </i>, <u>, </u>, <c.color>, etc.
+        // <b>, </b>, <i>, </i>, <u>, </u>, <c.color>, <v 話者>, etc.
         let tag_patterns = [
             (r"</?b>", ""),
             (r"</?i>", ""),
             (r"</?u>", ""),
             (r"</?c[^>]*>", ""),
+            (r"</?v[^>]*>", ""),
             (r"<\d+:\d+:\d+\.?\d*>", ""), // タイミングタグ
             (r"</?\w+>", ""),              // その他のタグ
         ];
@@ -273,12 +341,85 @@ impl VttParser {
             .enumerate()
             .map(|(i, s)| {
                 let text = translated_texts.get(i).cloned().unwrap_or_else(|| s.text.clone());
-                SubtitleSegment::new(s.index, s.start_ms, s.end_ms, text)
+                SubtitleSegment { text, ..s.clone() }
             })
             .collect()
     }
 }
 
+/// SRTパーサー
+pub struct SrtParser;
+
+impl SrtParser {
+    /// SRTコンテンツをパース
+    ///
+    /// 空行区切りのブロックごとに連番・タイムスタンプ・テキストを読む。
+    /// 連番は振り直し、`<i>` などのタグと `{\an8}` などの位置指定は除去する。
+    pub fn parse(content: &str) -> Result<Vec<SubtitleSegment>, ParseError> {
+        let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+        let position_tags = regex::Regex::new(r"\{\\[^}]*\}").unwrap();
+        let mut segments = Vec::new();
+        let mut index: u32 = 0;
+
+        let mut lines = content.lines().peekable();
+        while let Some(line) = lines.next() {
+            let line = line.trim();
+            if !line.contains("-->") {
+                continue;
+            }
+            let (start_ms, end_ms) = VttParser::parse_timestamp(&line.replace(',', "."))?;
+
+            let mut text_lines = Vec::new();
+            while let Some(text_line) = lines.next_if(|l| !l.trim().is_empty() && !l.contains("-->")) {
+                let clean_text = VttParser::strip_vtt_tags(&position_tags.replace_all(text_line, ""));
+                if !clean_text.is_empty() {
+                    text_lines.push(clean_text);
+                }
+            }
+
+            // 空行なしで続く次のブロックの連番を本文に含めない
+            let next_is_cue = lines.peek().map_or(false, |l| l.contains("-->"));
+            let last_is_number = text_lines.last().map_or(false, |l| l.chars().all(|c| c.is_ascii_digit()));
+            if next_is_cue && last_is_number {
+                text_lines.pop();
+            }
+
+            if !text_lines.is_empty() {
+                segments.push(SubtitleSegment::new(index, start_ms, end_ms, text_lines.join("\n")));
+                index += 1;
+            }
+        }
+
+        if segments.is_empty() && !content.trim().is_empty() && !content.contains("-->") {
+            return Err(ParseError::InvalidFormat("No SRT cues found".to_string()));
+        }
+        Ok(segments)
+    }
+
+    /// 翻訳済みテキストからSRTを再構築
+    pub fn rebuild_srt(original: &[SubtitleSegment], translated: &[String]) -> String {
+        let mut srt = String::new();
+
+        for (i, segment) in original.iter().enumerate() {
+            let translated_text = translated.get(i).unwrap_or(&segment.text);
+            srt.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                Self::format_time(segment.start_ms),
+                Self::format_time(segment.end_ms),
+                translated_text
+            ));
+        }
+
+        srt
+    }
+
+    /// ミリ秒をSRT時刻形式（HH:MM:SS,mmm）に変換
+    fn format_time(ms: u64) -> String {
+        VttParser::format_time(ms).replace('.', ",")
+    }
+}
+
 /// 翻訳テキストをパースして各セグメントに分割
 /// 形式: "[0] テキスト\n\n[1] テキスト..."
 pub fn parse_translated_text(text: &str) -> Vec<String> {
@@ -310,6 +451,50 @@ pub fn parse_translated_text(text: &str) -> Vec<String> {
     translations
 }
 
+/// 番号付きテキストをインデックスを保ったままパース
+/// 形式: "[0] テキスト\n\n[1] テキスト..."（番号の無い先頭行は無視）
+pub fn parse_indexed_text(text: &str) -> Vec<(usize, String)> {
+    let re = regex::Regex::new(r"^\s*\[(\d+)\]\s*").unwrap();
+    let mut entries: Vec<(usize, String)> = Vec::new();
+
+    for line in text.lines() {
+        if let Some(caps) = re.captures(line) {
+            let index = caps[1].parse().unwrap_or(0);
+            entries.push((index, re.replace(line, "").trim().to_string()));
+        } else if let Some(last) = entries.last_mut() {
+            let line = line.trim();
+            if !line.is_empty() {
+                if !last.1.is_empty() {
+                    last.1.push(' ');
+                }
+                last.1.push_str(line);
+            }
+        }
+    }
+
+    entries
+}
+
+/// セグメントごとの翻訳テキストを番号付きフォーマットに戻す
+/// （`parse_translated_text` の逆変換）
+pub fn format_translated_text(translations: &[String]) -> String {
+    translations
+        .iter()
+        .enumerate()
+        .map(|(i, t)| format!("[{}] {}", i, t))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// (番号, テキスト) の組を番号付きフォーマットに変換
+pub fn format_indexed_text(entries: &[(usize, String)]) -> String {
+    entries
+        .iter()
+        .map(|(i, t)| format!("[{}] {}", i, t))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,6 +518,20 @@ This is a test.
         assert_eq!(segments[1].text, "This is a test.");
     }
 
+    #[test]
+    fn test_parse_voice_tags() {
+        let vtt = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\n<v Alice>Hi there</v>\n\n\
+                   00:00:02.000 --> 00:00:03.000\n<v.loud Bob Smith>Hello!\n\n\
+                   00:00:03.000 --> 00:00:04.000\nNo voice\n";
+
+        let segments = VttParser::parse(vtt).unwrap();
+        assert_eq!(segments[0].text, "Hi there");
+        assert_eq!(segments[0].speaker.as_deref(), Some("Alice"));
+        assert_eq!(segments[1].text, "Hello!");
+        assert_eq!(segments[1].speaker.as_deref(), Some("Bob Smith"));
+        assert_eq!(segments[2].speaker, None);
+    }
+
     #[test]
     fn test_parse_timestamp() {
         let (start, end) = VttParser::parse_timestamp("00:01:30.500 --> 00:02:45.250").unwrap();
@@ -392,4 +591,49 @@ This is a test.
         assert_eq!(translations[0], "こんにちは");
         assert_eq!(translations[1], "世界");
     }
+
+    #[test]
+    fn test_format_translated_text_roundtrip() {
+        let texts = vec!["こんにちは".to_string(), "世界".to_string()];
+        let formatted = format_translated_text(&texts);
+        assert_eq!(formatted, "[0] こんにちは\n\n[1] 世界");
+        assert_eq!(parse_translated_text(&formatted), texts);
+    }
+
+    #[test]
+    fn test_parse_indexed_text() {
+        let text = "翻訳結果:\n[3] こんにちは\n続き\n\n[5] 世界";
+        let entries = parse_indexed_text(text);
+        assert_eq!(entries, vec![
+            (3, "こんにちは 続き".to_string()),
+            (5, "世界".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_srt() {
+        let srt = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,500\r\n<i>Hello,</i> world\r\n\r\n\
+                   2\r\n00:00:03,000 --> 00:00:04,000\r\n{\\an8}Bye\r\nnow\r\n3\r\n00:00:05,000 --> 00:00:06,000\r\nEnd\r\n";
+        let segments = SrtParser::parse(srt).unwrap();
+        assert_eq!(segments.len(), 3);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (1000, 2500));
+        assert_eq!(segments[0].text, "Hello, world");
+        assert_eq!(segments[1].text, "Bye\nnow");
+        assert_eq!(segments[2].index, 2);
+        assert!(SrtParser::parse("not a subtitle").is_err());
+    }
+
+    #[test]
+    fn test_rebuild_srt() {
+        let segments = vec![
+            SubtitleSegment::new(0, 1000, 2500, "Hello".to_string()),
+            SubtitleSegment::new(1, 3_723_004, 3_724_000, "Bye".to_string()),
+        ];
+        let srt = SubtitleFormat::Srt.rebuild(&segments, &["こんにちは".to_string()]);
+        assert_eq!(srt, "1\n00:00:01,000 --> 00:00:02,500\nこんにちは\n\n2\n01:02:03,004 --> 01:02:04,000\nBye\n\n");
+
+        // 再構築したSRTはそのままパースできる
+        let reparsed = SubtitleFormat::from_path("out/translated.ja.SRT").parse(&srt).unwrap();
+        assert_eq!(reparsed[1].start_ms, 3_723_004);
+    }
 }
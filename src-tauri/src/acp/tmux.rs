@@ -3,9 +3,11 @@
 //! ACP v3: Broadcast機能追加
 
 use std::collections::HashMap;
-use std::process::Command;
+use parking_lot::Mutex;
 use thiserror::Error;
 
+use crate::output_log::{diff_lines, OutputLog, OutputSlice};
+use crate::process_env;
 use super::parser::OutputParser;
 use super::message::CapabilityFilter;
 
@@ -20,6 +22,8 @@ pub enum TmuxError {
     AgentNotFound(String),
     #[error("Invalid pane ID")]
     InvalidPaneId,
+    #[error("tmux is not available on this platform")]
+    Unsupported,
 }
 
 /// エージェントの種類
@@ -57,11 +61,23 @@ pub struct PaneInfo {
     pub status: AgentStatus,
 }
 
+/// ペインの前回キャプチャと出力ログ
+#[derive(Default)]
+struct PaneCapture {
+    /// 前回キャプチャした画面（次のキャプチャはこの先頭行から始まる）
+    screen: String,
+    /// 前回キャプチャしたときのスクロールバックの行数（未キャプチャならNone）
+    history_size: Option<usize>,
+    log: OutputLog,
+}
+
 /// tmuxベースのオーケストレーター
 pub struct TmuxOrchestrator {
     session_name: String,
     panes: HashMap<String, PaneInfo>,
     parser: OutputParser,
+    /// ペインごとの前回キャプチャと出力ログ（カーソル読み取り用）
+    capture_logs: Mutex<HashMap<String, PaneCapture>>,
 }
 
 impl TmuxOrchestrator {
@@ -70,23 +86,29 @@ impl TmuxOrchestrator {
             session_name: session_name.to_string(),
             panes: HashMap::new(),
             parser: OutputParser::new(),
+            capture_logs: Mutex::new(HashMap::new()),
         }
     }
 
     /// tmuxセッションを作成
     pub fn create_session(&mut self) -> Result<(), TmuxError> {
+        // Windowsやtmux未インストールの環境では使えない
+        if !crate::platform::tmux_available() {
+            return Err(TmuxError::Unsupported);
+        }
+
         // 既存のセッションがあれば削除
-        let _ = Command::new("tmux")
+        let _ = process_env::command("tmux")
             .args(["kill-session", "-t", &self.session_name])
             .output();
 
         // グローバル履歴サイズを設定
-        let _ = Command::new("tmux")
+        let _ = process_env::command("tmux")
             .args(["set-option", "-g", "history-limit", "50000"])
             .output();
 
         // 新しいセッションを作成
-        let output = Command::new("tmux")
+        let output = process_env::command("tmux")
             .args(["new-session", "-d", "-s", &self.session_name, "-x", "200", "-y", "50"])
             .output()
             .map_err(|e| TmuxError::CommandFailed(e.to_string()))?;
@@ -97,7 +119,7 @@ impl TmuxOrchestrator {
         }
 
         // セッションの履歴サイズも設定
-        let _ = Command::new("tmux")
+        let _ = process_env::command("tmux")
             .args(["set-option", "-t", &self.session_name, "history-limit", "50000"])
             .output();
 
@@ -122,7 +144,7 @@ impl TmuxOrchestrator {
         capabilities: Vec<String>,
     ) -> Result<String, TmuxError> {
         // セッション名だけで参照（最初のウィンドウが使われる）
-        let output = Command::new("tmux")
+        let output = process_env::command("tmux")
             .args([
                 "split-window",
                 "-t", &self.session_name,
@@ -141,7 +163,7 @@ impl TmuxOrchestrator {
         eprintln!("[TmuxOrchestrator] Created pane: {} for agent: {}", pane_id, agent_id);
 
         // ペインレイアウトを調整
-        let _ = Command::new("tmux")
+        let _ = process_env::command("tmux")
             .args(["select-layout", "-t", &self.session_name, "tiled"])
             .output();
 
@@ -174,7 +196,7 @@ impl TmuxOrchestrator {
 
         // -l フラグでリテラルモード（特殊文字をエスケープ）
         // 複数行テキストも一括送信
-        let output1 = Command::new("tmux")
+        let output1 = process_env::command("tmux")
             .args(["send-keys", "-t", pane_id, "-l", text])
             .output()
             .map_err(|e| TmuxError::CommandFailed(e.to_string()))?;
@@ -185,7 +207,7 @@ impl TmuxOrchestrator {
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         // Enterを送信（最後に1回だけ）
-        let output2 = Command::new("tmux")
+        let output2 = process_env::command("tmux")
             .args(["send-keys", "-t", pane_id, "Enter"])
             .output()
             .map_err(|e| TmuxError::CommandFailed(e.to_string()))?;
@@ -197,7 +219,7 @@ impl TmuxOrchestrator {
 
     /// ペインの画面内容をキャプチャ（ANSIエスケープシーケンス付き）
     pub fn capture_pane(&self, pane_id: &str) -> Result<String, TmuxError> {
-        let output = Command::new("tmux")
+        let output = process_env::command("tmux")
             .args(["capture-pane", "-t", pane_id, "-p", "-e"])
             .output()
             .map_err(|e| TmuxError::CommandFailed(e.to_string()))?;
@@ -209,7 +231,7 @@ impl TmuxOrchestrator {
     pub fn capture_pane_plain(&self, pane_id: &str) -> Result<String, TmuxError> {
         // -S - でスクロールバックの先頭から全行取得
         // -E - で履歴の最後まで取得
-        let output = Command::new("tmux")
+        let output = process_env::command("tmux")
             .args(["capture-pane", "-t", pane_id, "-p", "-S", "-", "-E", "-"])
             .output()
             .map_err(|e| TmuxError::CommandFailed(e.to_string()))?;
@@ -219,15 +241,85 @@ impl TmuxOrchestrator {
         Ok(content)
     }
 
-    /// エージェントの状態を検出（OutputParserを使用）
+    /// ペインのスクロールバックの行数
+    fn history_size(&self, pane_id: &str) -> Result<usize, TmuxError> {
+        let output = process_env::command("tmux")
+            .args(["display-message", "-p", "-t", pane_id, "#{history_size}"])
+            .output()
+            .map_err(|e| TmuxError::CommandFailed(e.to_string()))?;
+
+        String::from_utf8_lossy(&output.stdout).trim().parse()
+            .map_err(|_| TmuxError::CommandFailed(format!("Failed to read history size of {}", pane_id)))
+    }
+
+    /// 前回の画面の先頭行から現在の画面までをキャプチャして出力ログに記録し、`(キャプチャ内容, カーソル)` を返す
+    ///
+    /// スクロールバック全体（`-S -`）を取得するのは初回と履歴が消えたときだけ。
+    /// 履歴が上限（history-limit）に達すると増えた行数がわからないため、画面全体を新しい出力として記録することがある。
+    pub fn capture_pane_incremental(&self, pane_id: &str) -> Result<(String, u64), TmuxError> {
+        let history_size = self.history_size(pane_id)?;
+        let previous = self.capture_logs.lock().get(pane_id).and_then(|capture| capture.history_size);
+        let (start, scrolled) = match previous {
+            Some(previous) if history_size >= previous => {
+                let scrolled = history_size - previous;
+                ((-(scrolled as i64)).to_string(), scrolled)
+            }
+            _ => ("-".to_string(), history_size),
+        };
+
+        let output = process_env::command("tmux")
+            .args(["capture-pane", "-t", pane_id, "-p", "-S", &start, "-E", "-"])
+            .output()
+            .map_err(|e| TmuxError::CommandFailed(e.to_string()))?;
+
+        let content = String::from_utf8_lossy(&output.stdout).to_string();
+        let seq = self.record_window(pane_id, &content, scrolled, history_size);
+        Ok((content, seq))
+    }
+
+    /// 前回の画面の先頭行から始まるキャプチャを差分として出力ログに記録し、現在のカーソルを返す
+    ///
+    /// 先頭の `scrolled` 行は前回からスクロールバックに移った行で、残りが現在の画面。
+    fn record_window(&self, pane_id: &str, content: &str, scrolled: usize, history_size: usize) -> u64 {
+        let mut logs = self.capture_logs.lock();
+        let capture = logs.entry(pane_id.to_string()).or_default();
+        if let Some(text) = diff_lines(&capture.screen, content) {
+            capture.log.push(text);
+        }
+        capture.screen = content.lines().skip(scrolled).collect::<Vec<_>>().join("\n");
+        capture.history_size = Some(history_size);
+        capture.log.next_seq()
+    }
+
+    /// 出力ログのカーソル `seq` 以降の出力（キャプチャはしない）
+    pub fn output_since(&self, pane_id: &str, seq: u64) -> OutputSlice {
+        self.capture_logs.lock().get(pane_id).map(|capture| capture.log.since(seq)).unwrap_or_default()
+    }
+
+    /// ペインをキャプチャし、カーソル `seq` 以降に増えた出力を取得
+    pub fn capture_pane_since(&self, pane_id: &str, seq: u64) -> Result<OutputSlice, TmuxError> {
+        self.capture_pane_incremental(pane_id)?;
+        Ok(self.output_since(pane_id, seq))
+    }
+
+    /// エージェントの状態を検出（ペインのエージェント種別のルールで判定）
     pub fn detect_status(&self, pane_id: &str) -> AgentStatus {
         if let Ok(content) = self.capture_pane_plain(pane_id) {
-            self.parser.parse(&content)
+            self.detect_status_in(pane_id, &content)
         } else {
             AgentStatus::Unknown
         }
     }
 
+    /// キャプチャ済みの内容からペインの状態を検出（ペインのエージェント種別のルールで判定）
+    pub fn detect_status_in(&self, pane_id: &str, content: &str) -> AgentStatus {
+        let agent_type = self.panes.values()
+            .find(|pane| pane.pane_id == pane_id)
+            .map(|pane| pane.agent_type.clone())
+            .unwrap_or(AgentType::ClaudeCode);
+        self.parser.parse_for(&agent_type, content)
+    }
+
     /// エージェントの状態を検出（生のコンテンツから）
     pub fn detect_status_from_content(&self, content: &str) -> AgentStatus {
         self.parser.parse(content)
@@ -253,7 +345,8 @@ impl TmuxOrchestrator {
     /// エージェントを終了
     pub fn kill_agent(&mut self, agent_id: &str) -> Result<(), TmuxError> {
         if let Some(pane) = self.panes.remove(agent_id) {
-            Command::new("tmux")
+            self.capture_logs.lock().remove(&pane.pane_id);
+            process_env::command("tmux")
                 .args(["kill-pane", "-t", &pane.pane_id])
                 .output()
                 .ok();
@@ -263,17 +356,18 @@ impl TmuxOrchestrator {
 
     /// セッションを終了
     pub fn destroy_session(&mut self) -> Result<(), TmuxError> {
-        Command::new("tmux")
+        process_env::command("tmux")
             .args(["kill-session", "-t", &self.session_name])
             .output()
             .ok();
         self.panes.clear();
+        self.capture_logs.lock().clear();
         Ok(())
     }
 
     /// 最初のペインIDを取得
     fn get_first_pane_id(&self) -> Result<String, TmuxError> {
-        let output = Command::new("tmux")
+        let output = process_env::command("tmux")
             .args(["list-panes", "-t", &self.session_name, "-F", "#{pane_id}"])
             .output()
             .map_err(|e| TmuxError::CommandFailed(e.to_string()))?;
@@ -578,7 +672,7 @@ impl TmuxOrchestrator {
     pub fn send_key(&self, pane_id: &str, key: &str) -> Result<(), TmuxError> {
         crate::log::info("send_key", &format!("Sending key: {:?}", key));
 
-        let output = Command::new("tmux")
+        let output = process_env::command("tmux")
             .args(["send-keys", "-t", pane_id, key])
             .output()
             .map_err(|e| TmuxError::CommandFailed(e.to_string()))?;
@@ -643,6 +737,30 @@ fn extract_option_number(line: &str) -> Option<u32> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_record_capture_cursor() {
+        let orch = TmuxOrchestrator::new("test-revoice-cursor");
+        assert_eq!(orch.record_window("%1", "$ ls", 0, 0), 1);
+        assert_eq!(orch.record_window("%1", "$ ls", 0, 0), 1);
+        let cursor = orch.record_window("%1", "$ ls\nsrc\n$ ", 0, 0);
+
+        assert_eq!(orch.output_since("%1", 1).chunks, vec!["src\n$".to_string()]);
+        assert!(orch.output_since("%1", cursor).chunks.is_empty());
+    }
+
+    #[test]
+    fn test_record_window_after_scroll() {
+        // 2行の画面で、前回の画面の2行がスクロールバックに移った
+        let orch = TmuxOrchestrator::new("test-revoice-scroll");
+        orch.record_window("%1", "$ ls\nsrc", 0, 0);
+        let cursor = orch.record_window("%1", "$ ls\nsrc\ndocs\n$ ", 2, 2);
+        assert_eq!(orch.output_since("%1", 1).text(), "docs\n$");
+
+        // スクロールしていなければ画面の差分だけ
+        orch.record_window("%1", "docs\n$ pwd", 0, 2);
+        assert_eq!(orch.output_since("%1", cursor).text(), "$ pwd");
+    }
+
     #[test]
     fn test_create_session() {
         let mut orch = TmuxOrchestrator::new("test-revoice");
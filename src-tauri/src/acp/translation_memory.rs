@@ -0,0 +1,146 @@
+//! 翻訳メモリ
+//!
+//! 原文セグメント → 訳文をキャッシュし、同じ動画や更新された動画を再実行したときは
+//! 新しいセグメント・変更されたセグメントだけを翻訳プロバイダーに送る。
+//! 訳文は翻訳先・プロバイダー・文体・用語集で変わるため、それらをまとめた
+//! コンテキストごとに保存する。
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// 保存ファイル名（データディレクトリ直下）
+pub const TRANSLATION_MEMORY_FILE: &str = "translation_memory.json";
+
+/// 翻訳メモリの件数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryStats {
+    /// コンテキスト数
+    pub contexts: usize,
+    /// 保存している訳文の数
+    pub entries: usize,
+}
+
+/// 原文 → 訳文のキャッシュ
+#[derive(Debug, Default)]
+pub struct TranslationMemory {
+    /// 保存先（Noneならメモリのみ）
+    path: Option<PathBuf>,
+    /// コンテキスト -> 正規化した原文 -> 訳文
+    contexts: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl TranslationMemory {
+    /// メモリのみのキャッシュを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ファイルから読み込む（存在しなければ空）
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let contexts = if path.exists() {
+            let content = std::fs::read_to_string(path)?;
+            serde_json::from_str(&content)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            contexts,
+        })
+    }
+
+    /// ファイルに保存
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(&self.contexts)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)
+    }
+
+    /// キャッシュ済みの訳文
+    pub fn lookup(&self, context: &str, source: &str) -> Option<&str> {
+        self.contexts.get(context)?.get(&normalize(source)).map(|s| s.as_str())
+    }
+
+    /// 訳文を記録
+    pub fn insert(&mut self, context: &str, source: &str, translation: &str) {
+        let source = normalize(source);
+        if source.is_empty() {
+            return;
+        }
+        self.contexts
+            .entry(context.to_string())
+            .or_default()
+            .insert(source, translation.to_string());
+    }
+
+    /// 件数
+    pub fn stats(&self) -> MemoryStats {
+        MemoryStats {
+            contexts: self.contexts.len(),
+            entries: self.contexts.values().map(|c| c.len()).sum(),
+        }
+    }
+
+    /// すべて削除
+    pub fn clear(&mut self) {
+        self.contexts.clear();
+    }
+}
+
+/// キャッシュのコンテキスト（訳文に影響する設定と用語集のプロンプト）
+pub fn context_key(settings: &[&str], glossary_prompt: &str) -> String {
+    format!("{}|{:016x}", settings.join("|"), fnv1a(glossary_prompt))
+}
+
+/// 空白の違いは同じ原文として扱う
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 保存するキーに使うので、ビルド間で変わらないハッシュにする
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_by_context() {
+        let mut memory = TranslationMemory::new();
+        let context = context_key(&["claude", "ja"], "");
+        memory.insert(&context, "Hello  world\n", "こんにちは世界");
+
+        assert_eq!(memory.lookup(&context, "Hello world"), Some("こんにちは世界"));
+        assert_eq!(memory.lookup(&context_key(&["claude", "ko"], ""), "Hello world"), None);
+        assert_eq!(memory.lookup(&context_key(&["claude", "ja"], "用語集"), "Hello world"), None);
+        assert_eq!(memory.stats(), MemoryStats { contexts: 1, entries: 1 });
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("re-voice-tm-{}", uuid::Uuid::new_v4()))
+            .join(TRANSLATION_MEMORY_FILE);
+        let mut memory = TranslationMemory::load(&path).unwrap();
+        memory.insert("ctx", "Hi", "やあ");
+        memory.save().unwrap();
+
+        let loaded = TranslationMemory::load(&path).unwrap();
+        assert_eq!(loaded.lookup("ctx", "Hi"), Some("やあ"));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
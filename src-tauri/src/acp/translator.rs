@@ -0,0 +1,358 @@
+//! Translator trait
+//!
+//! Provider-agnostic interface for the translate stage. The runner handles
+//! chunking, repair of missing segments and glossary verification; providers
+//! only turn a batch of numbered segments into translations.
+//!
+//! Implementations live in `translators/`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use super::cli_agent::CliAgentKind;
+use super::executor::ClaudeCodeExecutor;
+use super::glossary::Glossary;
+use super::stream_parser::UsageTotals;
+use super::message::AgentAddress;
+use super::translators::{ClaudeTranslator, CliAgentTranslator, DeeplTranslator, OllamaTranslator, OpenAiTranslator};
+use super::validation::validate_translation;
+use crate::secrets::{SecretStore, DEEPL_API_KEY, OPENAI_API_KEY};
+
+/// Translator errors
+#[derive(Debug, Error)]
+pub enum TranslatorError {
+    #[error("Translator not available: {0}")]
+    NotAvailable(String),
+
+    #[error("Missing API key: {0}")]
+    MissingApiKey(String),
+
+    #[error("Request failed: {0}")]
+    Request(String),
+
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Translation provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationProvider {
+    /// Claude Code (CLI executor)
+    #[default]
+    Claude,
+    /// DeepL API
+    Deepl,
+    /// OpenAI Chat Completions API
+    Openai,
+    /// Local Ollama server (offline)
+    Ollama,
+    /// OpenAI Codex CLI
+    Codex,
+    /// Gemini CLI
+    Gemini,
+}
+
+impl TranslationProvider {
+    /// Provider for a translate stage pointed at an agent address
+    /// (`claude-code@...`, `codex@...` or `gemini@...`)
+    pub fn from_address(address: &AgentAddress) -> Option<Self> {
+        if address.id.split('@').next() == Some("claude-code") {
+            return Some(Self::Claude);
+        }
+        CliAgentKind::from_address(address).map(|kind| match kind {
+            CliAgentKind::Codex => Self::Codex,
+            CliAgentKind::Gemini => Self::Gemini,
+        })
+    }
+}
+
+/// Speech style (register) of the translated dialogue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeechStyle {
+    /// です・ます調
+    Polite,
+    /// Casual spoken Japanese
+    Casual,
+    /// Documentary-style narration (だ・である調)
+    Narration,
+}
+
+impl SpeechStyle {
+    /// Instruction injected into LLM prompts
+    pub fn prompt_instruction(&self) -> &'static str {
+        match self {
+            SpeechStyle::Polite => "です・ます調の丁寧な話し言葉で統一",
+            SpeechStyle::Casual => "友人同士のようなくだけた話し言葉（タメ口）で統一",
+            SpeechStyle::Narration => "ドキュメンタリーのナレーションのような、だ・である調の落ち着いた語り口で統一",
+        }
+    }
+
+    /// DeepL `formality` parameter, if the style maps to one
+    pub fn deepl_formality(&self) -> Option<&'static str> {
+        match self {
+            SpeechStyle::Polite => Some("prefer_more"),
+            SpeechStyle::Casual => Some("prefer_less"),
+            SpeechStyle::Narration => None,
+        }
+    }
+}
+
+/// Options passed to every translation request
+#[derive(Debug, Clone, Default)]
+pub struct TranslateOptions {
+    /// Source language code (auto-detect if None)
+    pub source_lang: Option<String>,
+    /// Target language code
+    pub target_lang: String,
+    /// Glossary to inject into the request
+    pub glossary: Glossary,
+    /// Speech style (provider default if None)
+    pub style: Option<SpeechStyle>,
+    /// Maximum spoken characters per segment index (from the segment duration)
+    pub max_chars: BTreeMap<usize, usize>,
+}
+
+/// Provider usage for a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationUsage {
+    /// Characters sent by this translator
+    pub characters: u64,
+    /// Characters used in the current billing period, if known
+    pub character_count: Option<u64>,
+    /// Character limit for the current billing period, if known
+    pub character_limit: Option<u64>,
+    /// Tokens and cost, for providers that report them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<UsageTotals>,
+}
+
+/// Translation provider interface
+#[async_trait]
+pub trait Translator: Send + Sync {
+    /// Provider identifier
+    fn provider(&self) -> TranslationProvider;
+
+    /// Whether the runner should split long inputs into overlapping chunks.
+    /// Providers that batch internally (DeepL) return false.
+    fn uses_chunking(&self) -> bool {
+        true
+    }
+
+    /// Whether the provider follows the per-segment `max_chars` budget.
+    /// Machine translation (DeepL) ignores it, so re-requesting is pointless.
+    fn honors_length_limits(&self) -> bool {
+        true
+    }
+
+    /// Upper bound on segments per request (e.g. limited context window)
+    fn max_chunk_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Verify the provider is reachable before the run starts
+    async fn check_available(&self) -> Result<(), TranslatorError> {
+        Ok(())
+    }
+
+    /// Translate numbered segments.
+    ///
+    /// Returns a map of segment index to translation. Segments the provider
+    /// failed to return (or returned twice) are simply absent; the runner
+    /// re-requests them.
+    async fn translate_segments(
+        &self,
+        segments: &[(usize, String)],
+        options: &TranslateOptions,
+    ) -> Result<BTreeMap<usize, String>, TranslatorError>;
+
+    /// Run a free-form prompt and return the raw answer (e.g. content checks).
+    /// Providers without a general-purpose model (DeepL) return `NotAvailable`.
+    async fn complete(&self, _prompt: &str) -> Result<String, TranslatorError> {
+        Err(TranslatorError::NotAvailable(format!(
+            "{:?} cannot run free-form prompts", self.provider()
+        )))
+    }
+
+    /// Usage report after the run, if the provider tracks it
+    async fn usage(&self) -> Option<TranslationUsage> {
+        None
+    }
+}
+
+/// Create a translator for the given provider
+pub fn create_translator(
+    provider: TranslationProvider,
+    model: Option<&str>,
+    executor: Arc<RwLock<Option<ClaudeCodeExecutor>>>,
+    secrets: &SecretStore,
+) -> Result<Box<dyn Translator>, TranslatorError> {
+    match provider {
+        TranslationProvider::Claude => Ok(Box::new(ClaudeTranslator::new(executor))),
+        TranslationProvider::Deepl => {
+            let key = secrets.get(DEEPL_API_KEY)
+                .ok_or_else(|| TranslatorError::MissingApiKey(DEEPL_API_KEY.to_string()))?;
+            Ok(Box::new(DeeplTranslator::new(&key)?))
+        }
+        TranslationProvider::Openai => {
+            let key = secrets.get(OPENAI_API_KEY)
+                .ok_or_else(|| TranslatorError::MissingApiKey(OPENAI_API_KEY.to_string()))?;
+            Ok(Box::new(OpenAiTranslator::new(&key, model)))
+        }
+        TranslationProvider::Ollama => Ok(Box::new(OllamaTranslator::new(model))),
+        TranslationProvider::Codex => Ok(Box::new(CliAgentTranslator::new(CliAgentKind::Codex, model))),
+        TranslationProvider::Gemini => Ok(Box::new(CliAgentTranslator::new(CliAgentKind::Gemini, model))),
+    }
+}
+
+/// Primary language subtag, lowercased ("ja-JP" and "ja_JP" become "ja")
+pub fn primary_language(code: &str) -> String {
+    code.split(['-', '_']).next().unwrap_or(code).to_ascii_lowercase()
+}
+
+/// Display name of a language code for prompts (unknown codes are returned as-is)
+pub fn language_name(code: &str) -> &str {
+    match primary_language(code).as_str() {
+        "ja" => "日本語",
+        "en" => "英語",
+        "zh" => "中国語",
+        "ko" => "韓国語",
+        "fr" => "フランス語",
+        "de" => "ドイツ語",
+        "es" => "スペイン語",
+        "pt" => "ポルトガル語",
+        "it" => "イタリア語",
+        "ru" => "ロシア語",
+        _ => code,
+    }
+}
+
+/// Build the numbered-segment prompt shared by LLM-based translators
+pub fn build_translation_prompt(segments: &[(usize, String)], options: &TranslateOptions) -> String {
+    let language = language_name(&options.target_lang);
+    let text = segments
+        .iter()
+        .map(|(index, text)| format!("[{}] {}", index, text))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let mut prompt = format!(
+        "以下の字幕テキストを{}に翻訳してください。\n\
+         翻訳結果のみを出力してください。各セグメントの番号を維持してください。\n\n{}\n\n",
+        language, text
+    );
+
+    let glossary = options.glossary.to_prompt_section();
+    if !glossary.is_empty() {
+        prompt.push_str(&glossary);
+        prompt.push('\n');
+    }
+
+    let limits: Vec<String> = segments
+        .iter()
+        .filter_map(|(index, _)| options.max_chars.get(index).map(|max| format!("[{}] {}文字", index, max)))
+        .collect();
+    if !limits.is_empty() {
+        prompt.push_str(&format!(
+            "【文字数の上限】\n\
+             音声が元の尺に収まるよう、各セグメントは以下の文字数以内にしてください（意訳・省略可）。\n{}\n\n",
+            limits.join("\n")
+        ));
+    }
+
+    prompt.push_str(&format!(
+        "【翻訳ルール】\n\
+         1. 自然な{}に翻訳\n\
+         2. 短すぎず長すぎない、適切な長さに\n\
+         3. 番号付きフォーマットを維持: [0] テキスト\n",
+        language
+    ));
+    if let Some(style) = options.style {
+        prompt.push_str(&format!("4. 文体: {}\n", style.prompt_instruction()));
+    }
+
+    prompt.push_str("\n翻訳結果:");
+    prompt
+}
+
+/// Parse numbered LLM output, keeping only indices that were requested exactly once
+pub fn parse_numbered_output(segments: &[(usize, String)], output: &str) -> BTreeMap<usize, String> {
+    let expected: Vec<usize> = segments.iter().map(|(index, _)| *index).collect();
+    let validation = validate_translation(&expected, output);
+
+    if !validation.leaked.is_empty() || !validation.unexpected.is_empty() {
+        crate::log::warn("Translator", &format!(
+            "Dropped {} leaked lines and unexpected indices {:?}",
+            validation.leaked.len(), validation.unexpected
+        ));
+    }
+
+    validation.translations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acp::glossary::GlossaryEntry;
+
+    #[test]
+    fn test_build_translation_prompt() {
+        let segments = vec![(0, "Hello".to_string()), (1, "World".to_string())];
+        let options = TranslateOptions {
+            target_lang: "ja".to_string(),
+            glossary: Glossary::new(vec![GlossaryEntry::new("World", "世界")]),
+            ..Default::default()
+        };
+
+        let prompt = build_translation_prompt(&segments, &options);
+        assert!(prompt.contains("日本語に翻訳"));
+        assert!(prompt.contains("[0] Hello\n\n[1] World"));
+        assert!(prompt.contains("World → 世界"));
+        assert!(!prompt.contains("文体"));
+        assert!(prompt.ends_with("翻訳結果:"));
+
+        let options = TranslateOptions { style: Some(SpeechStyle::Polite), ..options };
+        assert!(build_translation_prompt(&segments, &options).contains("4. 文体: です・ます調"));
+
+        let options = TranslateOptions { max_chars: [(1, 6)].into(), ..options };
+        assert!(build_translation_prompt(&segments, &options).contains("【文字数の上限】\n音声が"));
+        assert!(build_translation_prompt(&segments, &options).contains("[1] 6文字"));
+    }
+
+    #[test]
+    fn test_primary_language() {
+        assert_eq!(primary_language("ja"), "ja");
+        assert_eq!(primary_language("ja-JP"), "ja");
+        assert_eq!(primary_language("JA_jp"), "ja");
+    }
+
+    #[test]
+    fn test_parse_numbered_output() {
+        let segments = vec![(3, "a".to_string()), (4, "b".to_string())];
+        let parsed = parse_numbered_output(&segments, "翻訳結果:\n[3] あ\n[4] い\n[4] う\n[9] x");
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[&3], "あ");
+    }
+
+    #[test]
+    fn test_language_name() {
+        assert_eq!(language_name("en-US"), "英語");
+        assert_eq!(language_name("sv"), "sv");
+    }
+
+    #[test]
+    fn test_provider_from_address() {
+        let provider = |s: &str| TranslationProvider::from_address(&AgentAddress::parse(s).unwrap());
+        assert_eq!(provider("claude-code@localhost/cli"), Some(TranslationProvider::Claude));
+        assert_eq!(provider("codex@localhost"), Some(TranslationProvider::Codex));
+        assert_eq!(provider("gemini"), Some(TranslationProvider::Gemini));
+        assert_eq!(provider("rust-direct"), None);
+        assert_eq!(serde_json::to_value(TranslationProvider::Gemini).unwrap(), "gemini");
+    }
+}
@@ -0,0 +1,76 @@
+//! Claude Code translator (CLI executor)
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use tokio::sync::RwLock;
+
+use crate::acp::executor::ClaudeCodeExecutor;
+use crate::acp::stream_parser::UsageTotals;
+use crate::acp::translator::{
+    build_translation_prompt, parse_numbered_output, TranslateOptions, TranslationProvider,
+    TranslationUsage, Translator, TranslatorError,
+};
+
+/// Translator backed by the shared Claude Code executor
+pub struct ClaudeTranslator {
+    executor: Arc<RwLock<Option<ClaudeCodeExecutor>>>,
+    /// Characters sent and tokens/cost spent by this translator's requests
+    spent: Mutex<(u64, UsageTotals)>,
+}
+
+impl ClaudeTranslator {
+    pub fn new(executor: Arc<RwLock<Option<ClaudeCodeExecutor>>>) -> Self {
+        Self { executor, spent: Mutex::new((0, UsageTotals::default())) }
+    }
+}
+
+#[async_trait]
+impl Translator for ClaudeTranslator {
+    fn provider(&self) -> TranslationProvider {
+        TranslationProvider::Claude
+    }
+
+    async fn check_available(&self) -> Result<(), TranslatorError> {
+        if self.executor.read().await.is_none() {
+            return Err(TranslatorError::NotAvailable("Claude Code executor is not started".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn translate_segments(
+        &self,
+        segments: &[(usize, String)],
+        options: &TranslateOptions,
+    ) -> Result<BTreeMap<usize, String>, TranslatorError> {
+        let prompt = build_translation_prompt(segments, options);
+        let output = self.complete(&prompt).await?;
+        Ok(parse_numbered_output(segments, &output))
+    }
+
+    async fn complete(&self, prompt: &str) -> Result<String, TranslatorError> {
+        let mut guard = self.executor.write().await;
+        let executor = guard.as_mut()
+            .ok_or_else(|| TranslatorError::NotAvailable("Claude Code executor is not started".to_string()))?;
+
+        let result = executor.execute(prompt).await;
+        {
+            let mut spent = self.spent.lock();
+            spent.0 += prompt.chars().count() as u64;
+            spent.1.add(&executor.last_usage());
+        }
+        result.map_err(|e| TranslatorError::Request(e.to_string()))
+    }
+
+    async fn usage(&self) -> Option<TranslationUsage> {
+        let (characters, tokens) = *self.spent.lock();
+        Some(TranslationUsage {
+            characters,
+            character_count: None,
+            character_limit: None,
+            tokens: Some(tokens),
+        })
+    }
+}
@@ -0,0 +1,75 @@
+//! Codex CLI / Gemini CLI translator
+//!
+//! Each request runs the CLI once in non-interactive mode, so no executor
+//! has to be started beforehand.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+
+use crate::acp::cli_agent::{CliAgentExecutor, CliAgentKind, CliAgentOptions};
+use crate::acp::translator::{
+    build_translation_prompt, parse_numbered_output, TranslateOptions, TranslationProvider,
+    TranslationUsage, Translator, TranslatorError,
+};
+
+/// Translator backed by a non-Anthropic CLI agent
+pub struct CliAgentTranslator {
+    executor: tokio::sync::Mutex<CliAgentExecutor>,
+    kind: CliAgentKind,
+    /// Characters sent by this translator's requests (tokens are counted by the executor)
+    characters: AtomicU64,
+}
+
+impl CliAgentTranslator {
+    pub fn new(kind: CliAgentKind, model: Option<&str>) -> Self {
+        let options = CliAgentOptions { model: model.map(str::to_string), ..Default::default() };
+        Self {
+            executor: tokio::sync::Mutex::new(CliAgentExecutor::new(kind, options)),
+            kind,
+            characters: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for CliAgentTranslator {
+    fn provider(&self) -> TranslationProvider {
+        match self.kind {
+            CliAgentKind::Codex => TranslationProvider::Codex,
+            CliAgentKind::Gemini => TranslationProvider::Gemini,
+        }
+    }
+
+    async fn check_available(&self) -> Result<(), TranslatorError> {
+        self.executor.lock().await.check_available().await
+            .map(|_| ())
+            .map_err(|e| TranslatorError::NotAvailable(e.to_string()))
+    }
+
+    async fn translate_segments(
+        &self,
+        segments: &[(usize, String)],
+        options: &TranslateOptions,
+    ) -> Result<BTreeMap<usize, String>, TranslatorError> {
+        let prompt = build_translation_prompt(segments, options);
+        let output = self.complete(&prompt).await?;
+        Ok(parse_numbered_output(segments, &output))
+    }
+
+    async fn complete(&self, prompt: &str) -> Result<String, TranslatorError> {
+        self.characters.fetch_add(prompt.chars().count() as u64, Ordering::Relaxed);
+        self.executor.lock().await.execute(prompt).await
+            .map_err(|e| TranslatorError::Request(e.to_string()))
+    }
+
+    async fn usage(&self) -> Option<TranslationUsage> {
+        Some(TranslationUsage {
+            characters: self.characters.load(Ordering::Relaxed),
+            character_count: None,
+            character_limit: None,
+            tokens: Some(self.executor.lock().await.usage()),
+        })
+    }
+}
@@ -0,0 +1,73 @@
+//! DeepL translator
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+
+use crate::acp::translator::{
+    TranslateOptions, TranslationProvider, TranslationUsage, Translator, TranslatorError,
+};
+use crate::deepl::DeeplClient;
+
+/// Translator backed by the DeepL API.
+///
+/// DeepL translates each text independently, so the glossary is not sent (the
+/// runner still verifies it afterwards). Polite/casual styles map to `formality`.
+pub struct DeeplTranslator {
+    client: DeeplClient,
+}
+
+impl DeeplTranslator {
+    pub fn new(api_key: &str) -> Result<Self, TranslatorError> {
+        let client = DeeplClient::new(api_key)
+            .map_err(|e| TranslatorError::MissingApiKey(e.to_string()))?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl Translator for DeeplTranslator {
+    fn provider(&self) -> TranslationProvider {
+        TranslationProvider::Deepl
+    }
+
+    fn uses_chunking(&self) -> bool {
+        false
+    }
+
+    fn honors_length_limits(&self) -> bool {
+        false
+    }
+
+    async fn translate_segments(
+        &self,
+        segments: &[(usize, String)],
+        options: &TranslateOptions,
+    ) -> Result<BTreeMap<usize, String>, TranslatorError> {
+        let texts: Vec<String> = segments.iter().map(|(_, text)| text.clone()).collect();
+        let translated = self.client
+            .translate(
+                &texts,
+                options.source_lang.as_deref(),
+                &options.target_lang,
+                options.style.and_then(|s| s.deepl_formality()),
+            )
+            .await
+            .map_err(|e| TranslatorError::Request(e.to_string()))?;
+
+        Ok(segments.iter().map(|(index, _)| *index).zip(translated).collect())
+    }
+
+    async fn usage(&self) -> Option<TranslationUsage> {
+        let usage = self.client.usage().await
+            .map_err(|e| crate::log::warn("DeeplTranslator", &format!("Failed to get usage: {}", e)))
+            .ok();
+
+        Some(TranslationUsage {
+            characters: self.client.characters_sent(),
+            character_count: usage.as_ref().map(|u| u.character_count),
+            character_limit: usage.as_ref().map(|u| u.character_limit),
+            tokens: None,
+        })
+    }
+}
@@ -0,0 +1,13 @@
+//! Translator implementations
+
+pub mod claude;
+pub mod cli_agent;
+pub mod deepl;
+pub mod ollama;
+pub mod openai;
+
+pub use claude::ClaudeTranslator;
+pub use cli_agent::CliAgentTranslator;
+pub use deepl::DeeplTranslator;
+pub use ollama::OllamaTranslator;
+pub use openai::OpenAiTranslator;
@@ -0,0 +1,229 @@
+//! Ollama translator (local LLM over HTTP)
+//!
+//! Lets privacy-sensitive users translate fully offline. The model's context
+//! length is read from `/api/show` during the availability check and used to
+//! size chunks so each request fits the local context window.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::acp::translator::{
+    build_translation_prompt, parse_numbered_output, TranslateOptions, TranslationProvider,
+    Translator, TranslatorError,
+};
+
+/// Default Ollama endpoint
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Default model (multilingual, runs on consumer GPUs)
+pub const DEFAULT_OLLAMA_MODEL: &str = "qwen2.5:7b";
+
+/// Context window used when the model does not report one
+const DEFAULT_NUM_CTX: u32 = 4096;
+
+/// Upper bound on the context window we request (memory use grows with it)
+const MAX_NUM_CTX: u32 = 8192;
+
+/// Rough token estimate per numbered segment (source + translation)
+const TOKENS_PER_SEGMENT: u32 = 60;
+
+/// Tokens reserved for instructions and glossary
+const PROMPT_OVERHEAD_TOKENS: u32 = 400;
+
+/// Installed model entry from `/api/tags`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShowResponse {
+    #[serde(default)]
+    model_info: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GenerateResponse {
+    response: String,
+}
+
+/// Translator backed by a local Ollama server
+pub struct OllamaTranslator {
+    base_url: String,
+    model: String,
+    client: reqwest::Client,
+    /// Context window to request (updated by `check_available`)
+    num_ctx: AtomicU32,
+}
+
+impl OllamaTranslator {
+    pub fn new(model: Option<&str>) -> Self {
+        Self::with_url(DEFAULT_OLLAMA_URL, model)
+    }
+
+    pub fn with_url(base_url: &str, model: Option<&str>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.unwrap_or(DEFAULT_OLLAMA_MODEL).to_string(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(600))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            num_ctx: AtomicU32::new(DEFAULT_NUM_CTX),
+        }
+    }
+
+    /// List installed models
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>, TranslatorError> {
+        let resp = self.client
+            .get(format!("{}/api/tags", self.base_url))
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+            .map_err(|e| TranslatorError::NotAvailable(format!("Ollama is not running: {}", e)))?;
+
+        let tags: TagsResponse = resp.json().await
+            .map_err(|e| TranslatorError::InvalidResponse(e.to_string()))?;
+        Ok(tags.models)
+    }
+
+    /// Context length reported by the model, if any
+    async fn model_context_length(&self) -> Option<u32> {
+        let resp = self.client
+            .post(format!("{}/api/show", self.base_url))
+            .json(&serde_json::json!({ "model": self.model }))
+            .send()
+            .await
+            .ok()?;
+        let show: ShowResponse = resp.json().await.ok()?;
+        context_length_from_info(&show.model_info)
+    }
+}
+
+/// Find `<arch>.context_length` in `/api/show` model_info
+fn context_length_from_info(info: &BTreeMap<String, serde_json::Value>) -> Option<u32> {
+    info.iter()
+        .find(|(key, _)| key.ends_with(".context_length"))
+        .and_then(|(_, value)| value.as_u64())
+        .map(|v| v.min(u32::MAX as u64) as u32)
+}
+
+/// Whether an installed model name matches the requested one (`name` implies `name:latest`)
+fn model_matches(installed: &str, requested: &str) -> bool {
+    installed == requested || (!requested.contains(':') && installed == format!("{}:latest", requested))
+}
+
+/// Segments per request that fit the context window
+pub fn chunk_size_for_context(num_ctx: u32) -> usize {
+    (num_ctx.saturating_sub(PROMPT_OVERHEAD_TOKENS) / TOKENS_PER_SEGMENT).max(5) as usize
+}
+
+#[async_trait]
+impl Translator for OllamaTranslator {
+    fn provider(&self) -> TranslationProvider {
+        TranslationProvider::Ollama
+    }
+
+    fn max_chunk_size(&self) -> Option<usize> {
+        Some(chunk_size_for_context(self.num_ctx.load(Ordering::SeqCst)))
+    }
+
+    async fn check_available(&self) -> Result<(), TranslatorError> {
+        let models = self.list_models().await?;
+        if !models.iter().any(|m| model_matches(&m.name, &self.model)) {
+            return Err(TranslatorError::NotAvailable(format!(
+                "Ollama model '{}' is not installed (run `ollama pull {}`)",
+                self.model, self.model
+            )));
+        }
+
+        let num_ctx = self.model_context_length().await
+            .unwrap_or(DEFAULT_NUM_CTX)
+            .min(MAX_NUM_CTX);
+        self.num_ctx.store(num_ctx, Ordering::SeqCst);
+
+        crate::log::info("OllamaTranslator", &format!(
+            "Using model {} (num_ctx={}, {} segments per chunk)",
+            self.model, num_ctx, chunk_size_for_context(num_ctx)
+        ));
+        Ok(())
+    }
+
+    async fn translate_segments(
+        &self,
+        segments: &[(usize, String)],
+        options: &TranslateOptions,
+    ) -> Result<BTreeMap<usize, String>, TranslatorError> {
+        let prompt = build_translation_prompt(segments, options);
+        let output = self.complete(&prompt).await?;
+        Ok(parse_numbered_output(segments, &output))
+    }
+
+    async fn complete(&self, prompt: &str) -> Result<String, TranslatorError> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "prompt": prompt,
+            "stream": false,
+            "options": {
+                "num_ctx": self.num_ctx.load(Ordering::SeqCst),
+                "temperature": 0.2,
+            },
+        });
+
+        let resp = self.client
+            .post(format!("{}/api/generate", self.base_url))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| TranslatorError::Request(e.to_string()))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(TranslatorError::Request(format!("{}: {}", status, text)));
+        }
+
+        let generated: GenerateResponse = resp.json().await
+            .map_err(|e| TranslatorError::InvalidResponse(e.to_string()))?;
+        Ok(generated.response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_size_for_context() {
+        assert_eq!(chunk_size_for_context(4096), 61);
+        assert_eq!(chunk_size_for_context(512), 5);
+    }
+
+    #[test]
+    fn test_model_matches() {
+        assert!(model_matches("llama3:latest", "llama3"));
+        assert!(model_matches("qwen2.5:7b", "qwen2.5:7b"));
+        assert!(!model_matches("qwen2.5:14b", "qwen2.5:7b"));
+    }
+
+    #[test]
+    fn test_context_length_from_info() {
+        let info: BTreeMap<String, serde_json::Value> = serde_json::from_str(
+            r#"{"general.architecture":"qwen2","qwen2.context_length":32768}"#,
+        ).unwrap();
+        assert_eq!(context_length_from_info(&info), Some(32768));
+        assert_eq!(context_length_from_info(&BTreeMap::new()), None);
+    }
+}
@@ -0,0 +1,132 @@
+//! OpenAI translator (Chat Completions API)
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::acp::translator::{
+    build_translation_prompt, parse_numbered_output, TranslateOptions, TranslationProvider,
+    TranslationUsage, Translator, TranslatorError,
+};
+
+/// Default model
+pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+/// Translator backed by the OpenAI Chat Completions API
+pub struct OpenAiTranslator {
+    api_key: String,
+    model: String,
+    base_url: String,
+    client: reqwest::Client,
+    /// Characters sent in prompts
+    characters_sent: AtomicU64,
+}
+
+impl OpenAiTranslator {
+    pub fn new(api_key: &str, model: Option<&str>) -> Self {
+        Self {
+            api_key: api_key.trim().to_string(),
+            model: model.unwrap_or(DEFAULT_OPENAI_MODEL).to_string(),
+            base_url: OPENAI_BASE_URL.to_string(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(180))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            characters_sent: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for OpenAiTranslator {
+    fn provider(&self) -> TranslationProvider {
+        TranslationProvider::Openai
+    }
+
+    async fn translate_segments(
+        &self,
+        segments: &[(usize, String)],
+        options: &TranslateOptions,
+    ) -> Result<BTreeMap<usize, String>, TranslatorError> {
+        let prompt = build_translation_prompt(segments, options);
+        let output = self.complete(&prompt).await?;
+        Ok(parse_numbered_output(segments, &output))
+    }
+
+    async fn complete(&self, prompt: &str) -> Result<String, TranslatorError> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "temperature": 0.2,
+        });
+
+        let resp = self.client
+            .post(format!("{}/chat/completions", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| TranslatorError::Request(e.to_string()))?;
+
+        let status = resp.status();
+        let text = resp.text().await.map_err(|e| TranslatorError::Request(e.to_string()))?;
+        if !status.is_success() {
+            return Err(TranslatorError::Request(format!("{}: {}", status, text)));
+        }
+
+        let response: ChatResponse = serde_json::from_str(&text)
+            .map_err(|e| TranslatorError::InvalidResponse(e.to_string()))?;
+        let output = response.choices
+            .into_iter()
+            .next()
+            .and_then(|c| c.message.content)
+            .ok_or_else(|| TranslatorError::InvalidResponse("No choices in response".to_string()))?;
+
+        self.characters_sent.fetch_add(prompt.chars().count() as u64, Ordering::SeqCst);
+        Ok(output)
+    }
+
+    async fn usage(&self) -> Option<TranslationUsage> {
+        Some(TranslationUsage {
+            characters: self.characters_sent.load(Ordering::SeqCst),
+            character_count: None,
+            character_limit: None,
+            tokens: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chat_response() {
+        let json = r#"{"choices":[{"message":{"role":"assistant","content":"[0] こんにちは"}}]}"#;
+        let response: ChatResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.choices[0].message.content.as_deref(), Some("[0] こんにちは"));
+
+        let translator = OpenAiTranslator::new("key", None);
+        assert_eq!(translator.model, DEFAULT_OPENAI_MODEL);
+    }
+}
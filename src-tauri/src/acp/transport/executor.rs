@@ -0,0 +1,117 @@
+//! CLI executor transport
+
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::acp::agent::Transport;
+use crate::acp::executor::ClaudeCodeExecutor;
+use crate::acp::message::{ACPEnvelope, MessageType};
+use super::{reply_to, Subscribers, TransportError, TransportEvent, TransportHandle, TransportListener};
+
+/// `TransportHandle` for the CLI executor (`claude --print`)
+///
+/// `send` runs the prompt as a task in the background; the final result is
+/// delivered to subscribers as an `Output` event. Envelopes sent with
+/// `send_envelope` are answered with a `Response` (or `Error`) message
+/// correlated to the request instead.
+pub struct ExecutorHandle {
+    executor: Arc<RwLock<Option<ClaudeCodeExecutor>>>,
+    subscribers: Arc<Subscribers>,
+}
+
+impl ExecutorHandle {
+    pub fn new(executor: Arc<RwLock<Option<ClaudeCodeExecutor>>>) -> Self {
+        Self {
+            executor,
+            subscribers: Arc::new(Subscribers::new()),
+        }
+    }
+
+    /// Whether an executor is present (a locked executor is busy, so it counts as present)
+    fn has_executor(&self) -> bool {
+        self.executor.try_read().map(|guard| guard.is_some()).unwrap_or(true)
+    }
+
+    /// Run the prompt in the background and report the result
+    fn run(&self, prompt: &str, request: Option<ACPEnvelope>) -> Result<(), TransportError> {
+        if !self.has_executor() {
+            return Err(TransportError::NotConnected("Executor not started".to_string()));
+        }
+
+        let executor = self.executor.clone();
+        let subscribers = self.subscribers.clone();
+        let prompt = prompt.to_string();
+        tauri::async_runtime::spawn(async move {
+            let mut guard = executor.write().await;
+            let result = match guard.as_mut() {
+                Some(executor) => executor.execute(&prompt).await.map_err(|e| e.to_string()),
+                None => Err("Executor not started".to_string()),
+            };
+            let event = match (request, result) {
+                (Some(request), Ok(text)) => TransportEvent::Message {
+                    envelope: Box::new(reply_to(&request, MessageType::Response, &text)),
+                },
+                (Some(request), Err(message)) => TransportEvent::Message {
+                    envelope: Box::new(reply_to(&request, MessageType::Error, &message)),
+                },
+                (None, Ok(text)) => TransportEvent::Output { text },
+                (None, Err(message)) => TransportEvent::Error { message },
+            };
+            subscribers.notify(&event);
+        });
+        Ok(())
+    }
+}
+
+impl TransportHandle for ExecutorHandle {
+    fn kind(&self) -> Transport {
+        Transport::Stdio
+    }
+
+    fn send(&self, content: &str) -> Result<(), TransportError> {
+        self.run(content, None)
+    }
+
+    fn send_envelope(&self, envelope: &ACPEnvelope) -> Result<(), TransportError> {
+        self.run(&envelope.message.payload.content, Some(envelope.clone()))
+    }
+
+    fn subscribe(&self, listener: TransportListener) -> u64 {
+        self.subscribers.add(listener)
+    }
+
+    fn unsubscribe(&self, id: u64) -> bool {
+        self.subscribers.remove(id)
+    }
+
+    fn close(&self) -> Result<(), TransportError> {
+        let executor = self.executor.clone();
+        let subscribers = self.subscribers.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut guard = executor.write().await;
+            if let Some(ref mut executor) = *guard {
+                let _ = executor.stop().await;
+            }
+            *guard = None;
+            subscribers.notify(&TransportEvent::Closed);
+        });
+        Ok(())
+    }
+
+    fn is_open(&self) -> bool {
+        self.has_executor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send_without_executor() {
+        let handle = ExecutorHandle::new(Arc::new(RwLock::new(None)));
+        assert!(!handle.is_open());
+        assert!(matches!(handle.send("hi"), Err(TransportError::NotConnected(_))));
+    }
+}
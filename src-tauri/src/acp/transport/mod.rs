@@ -1,4 +1,219 @@
 //! Transport implementations
+//!
+//! Every backend an agent can be reached through (legacy PTY, tmux pane,
+//! CLI executor, stdio process, ...) implements `TransportHandle`. Handles are attached to
+//! agents in the registry so the orchestrator can send messages uniformly.
 
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use thiserror::Error;
+
+use super::agent::Transport;
+use super::message::{ACPEnvelope, ACPMessageV3, MessageType};
+
+pub mod executor;
 pub mod pty;
+pub mod stdio;
+pub mod tmux;
+pub mod websocket;
+
+/// Sender address used for envelopes built from plain text
+pub const LOCAL_SENDER: &str = "re-voice";
+
+/// Transport error types
+#[derive(Debug, Error)]
+pub enum TransportError {
+    #[error("Not connected: {0}")]
+    NotConnected(String),
+
+    #[error("Send failed: {0}")]
+    SendFailed(String),
+
+    #[error("Failed to spawn agent process: {0}")]
+    SpawnFailed(String),
+
+    #[error("Transport closed")]
+    Closed,
+}
+
+/// Event received from a transport
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransportEvent {
+    /// Output from the agent
+    Output { text: String },
+    /// ACP envelope received from the agent
+    Message { envelope: Box<ACPEnvelope> },
+    /// Error reported by the backend
+    Error { message: String },
+    /// The transport was closed
+    Closed,
+}
+
+/// Callback invoked for every transport event
+pub type TransportListener = Arc<dyn Fn(&TransportEvent) + Send + Sync>;
+
+/// Uniform handle to a backend that can deliver messages to an agent
+pub trait TransportHandle: Send + Sync {
+    /// Transport type (matches `AgentCard.transport`)
+    fn kind(&self) -> Transport;
+
+    /// Send a message to the agent
+    fn send(&self, content: &str) -> Result<(), TransportError>;
+
+    /// Deliver an ACP envelope
+    ///
+    /// Text-based backends (PTY, tmux pane, CLI executor) only understand plain
+    /// prompts, so by default just the payload content is sent. Transports that
+    /// speak ACP override this to forward the whole envelope.
+    fn send_envelope(&self, envelope: &ACPEnvelope) -> Result<(), TransportError> {
+        self.send(&envelope.message.payload.content)
+    }
+
+    /// Register a listener for agent output; returns an id for `unsubscribe`
+    fn subscribe(&self, listener: TransportListener) -> u64;
+
+    /// Remove a listener
+    fn unsubscribe(&self, id: u64) -> bool;
+
+    /// Close the transport (the agent can no longer be messaged)
+    fn close(&self) -> Result<(), TransportError>;
+
+    /// Whether messages can currently be sent
+    fn is_open(&self) -> bool;
+}
+
+/// Build the JSON frame sent to an ACP-speaking agent
+///
+/// Content that is already an ACP envelope is forwarded as-is; plain text
+/// is wrapped in a prompt envelope addressed to the agent.
+pub fn encode_frame(agent_id: &str, content: &str) -> Result<String, TransportError> {
+    let envelope = match ACPEnvelope::from_json(content.trim()) {
+        Ok(envelope) => envelope,
+        Err(_) => ACPEnvelope::new(ACPMessageV3::prompt(LOCAL_SENDER, agent_id, content)),
+    };
+    envelope_frame(&envelope)
+}
+
+/// Serialize an envelope into a frame for ACP-speaking transports
+pub fn envelope_frame(envelope: &ACPEnvelope) -> Result<String, TransportError> {
+    envelope
+        .to_json()
+        .map_err(|e| TransportError::SendFailed(e.to_string()))
+}
+
+/// Build a reply to `request` correlated by its message ID
+///
+/// Used by text-based transports to report the agent's answer as an ACP message.
+/// `message_type` is `Response`, `Stream` or `Error`.
+pub fn reply_to(request: &ACPEnvelope, message_type: MessageType, content: &str) -> ACPEnvelope {
+    let from = request
+        .message
+        .to
+        .recipients()
+        .first()
+        .map(|a| a.to_address_string())
+        .unwrap_or_else(|| LOCAL_SENDER.to_string());
+    let to = request.message.from.to_address_string();
+    let correlation_id = request.message.id.clone();
+    let message = match message_type {
+        MessageType::Stream => ACPMessageV3::stream(from, to, content, correlation_id),
+        MessageType::Error => ACPMessageV3::error(from, to, content).with_correlation_id(correlation_id),
+        _ => ACPMessageV3::response(from, to, content, correlation_id),
+    };
+    ACPEnvelope::new(message)
+}
+
+/// Turn one received frame into an event (blank frames are skipped)
+pub fn decode_frame(frame: &str) -> Option<TransportEvent> {
+    let frame = frame.trim();
+    if frame.is_empty() {
+        return None;
+    }
+    Some(match ACPEnvelope::from_json(frame) {
+        Ok(envelope) => TransportEvent::Message { envelope: Box::new(envelope) },
+        Err(_) => TransportEvent::Output { text: frame.to_string() },
+    })
+}
+
+/// Listener list shared by transport implementations
+#[derive(Default)]
+pub struct Subscribers {
+    next_id: RwLock<u64>,
+    entries: RwLock<Vec<(u64, TransportListener)>>,
+}
+
+impl Subscribers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, listener: TransportListener) -> u64 {
+        let mut next_id = self.next_id.write();
+        let id = *next_id;
+        *next_id += 1;
+        self.entries.write().push((id, listener));
+        id
+    }
+
+    pub fn remove(&self, id: u64) -> bool {
+        let mut entries = self.entries.write();
+        let before = entries.len();
+        entries.retain(|(entry_id, _)| *entry_id != id);
+        entries.len() != before
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().is_empty()
+    }
+
+    /// Deliver an event to every listener
+    pub fn notify(&self, event: &TransportEvent) {
+        // Clone the list so listeners can (un)subscribe from the callback
+        let listeners: Vec<TransportListener> = self.entries.read().iter().map(|(_, l)| l.clone()).collect();
+        for listener in listeners {
+            listener(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribers() {
+        let subscribers = Subscribers::new();
+        let received = Arc::new(RwLock::new(Vec::new()));
+        let sink = received.clone();
+        let id = subscribers.add(Arc::new(move |event| {
+            if let TransportEvent::Output { text } = event {
+                sink.write().push(text.clone());
+            }
+        }));
+
+        subscribers.notify(&TransportEvent::Output { text: "hello".into() });
+        assert!(subscribers.remove(id));
+        subscribers.notify(&TransportEvent::Output { text: "ignored".into() });
+
+        assert_eq!(*received.read(), vec!["hello".to_string()]);
+        assert!(subscribers.is_empty());
+    }
+
+    #[test]
+    fn test_decode_frame() {
+        assert!(decode_frame("   ").is_none());
+        assert!(matches!(
+            decode_frame("not json"),
+            Some(TransportEvent::Output { text }) if text == "not json"
+        ));
 
+        let json = ACPEnvelope::new(ACPMessageV3::prompt("a", "b", "hi")).to_json().unwrap();
+        match decode_frame(&json) {
+            Some(TransportEvent::Message { envelope }) => assert_eq!(envelope.message.payload.content, "hi"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+}
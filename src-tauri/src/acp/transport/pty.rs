@@ -1,9 +1,14 @@
 //! PTY Transport for ACP messages
 
+use std::sync::Arc;
+
 use anyhow::Result;
+use parking_lot::Mutex;
 
-use crate::pty::PtyManager;
-use crate::acp::message::{ACPFrame, ACPMessage};
+use crate::pty::{PtyEvent, PtyManager};
+use crate::acp::agent::Transport;
+use crate::acp::message::{ACPEnvelope, ACPFrame, ACPMessage, MessageType};
+use super::{reply_to, Subscribers, TransportError, TransportEvent, TransportHandle, TransportListener};
 
 /// PTY-based transport for ACP messages
 pub struct PtyTransport {
@@ -93,6 +98,101 @@ impl Default for PtyTransport {
     }
 }
 
+/// `TransportHandle` for the shared legacy PTY session
+///
+/// Output reaches subscribers through `publish`, which the PTY event callback calls.
+/// For an envelope sent with `send_envelope`, the output until the next input prompt
+/// is collected and delivered as a `Response` message correlated to the request.
+pub struct PtyHandle {
+    pty: Arc<Mutex<PtyManager>>,
+    subscribers: Arc<Subscribers>,
+    /// Request awaiting its reply and the output received since it was sent
+    pending: Mutex<Option<(ACPEnvelope, String)>>,
+}
+
+impl PtyHandle {
+    pub fn new(pty: Arc<Mutex<PtyManager>>, subscribers: Arc<Subscribers>) -> Self {
+        Self {
+            pty,
+            subscribers,
+            pending: Mutex::new(None),
+        }
+    }
+
+    /// Forward a PTY event to subscribers
+    pub fn publish(&self, event: &PtyEvent) {
+        match event {
+            PtyEvent::Output(text) => {
+                if let Some((_, output)) = self.pending.lock().as_mut() {
+                    output.push_str(text);
+                }
+                self.subscribers.notify(&TransportEvent::Output { text: text.clone() });
+            }
+            PtyEvent::Prompt => {
+                // The agent is waiting for input again: its answer is complete
+                if let Some((request, output)) = self.pending.lock().take() {
+                    let reply = reply_to(&request, MessageType::Response, output.trim());
+                    self.subscribers.notify(&TransportEvent::Message { envelope: Box::new(reply) });
+                }
+            }
+            PtyEvent::Error(message) => self.subscribers.notify(&TransportEvent::Error { message: message.clone() }),
+            PtyEvent::Exited(_) => {
+                if let Some((request, _)) = self.pending.lock().take() {
+                    let reply = reply_to(&request, MessageType::Error, "PTY process exited");
+                    self.subscribers.notify(&TransportEvent::Message { envelope: Box::new(reply) });
+                }
+                self.subscribers.notify(&TransportEvent::Closed);
+            }
+            PtyEvent::RawOutput(_)
+            | PtyEvent::InputRequired { .. }
+            | PtyEvent::AutoResponse(_)
+            | PtyEvent::Timeout(_) => {}
+        }
+    }
+}
+
+impl TransportHandle for PtyHandle {
+    fn kind(&self) -> Transport {
+        Transport::Pty
+    }
+
+    fn send(&self, content: &str) -> Result<(), TransportError> {
+        let pty = self.pty.lock();
+        if !pty.is_running() {
+            return Err(TransportError::NotConnected("PTY is not running".to_string()));
+        }
+        pty.send_message(content).map_err(|e| TransportError::SendFailed(e.to_string()))
+    }
+
+    fn send_envelope(&self, envelope: &ACPEnvelope) -> Result<(), TransportError> {
+        // A newer request supersedes one still waiting (the PTY answers one prompt at a time)
+        *self.pending.lock() = Some((envelope.clone(), String::new()));
+        let result = self.send(&envelope.message.payload.content);
+        if result.is_err() {
+            *self.pending.lock() = None;
+        }
+        result
+    }
+
+    fn subscribe(&self, listener: TransportListener) -> u64 {
+        self.subscribers.add(listener)
+    }
+
+    fn unsubscribe(&self, id: u64) -> bool {
+        self.subscribers.remove(id)
+    }
+
+    fn close(&self) -> Result<(), TransportError> {
+        self.pty.lock().stop_background_reader();
+        self.subscribers.notify(&TransportEvent::Closed);
+        Ok(())
+    }
+
+    fn is_open(&self) -> bool {
+        self.pty.lock().is_running()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,4 +202,11 @@ mod tests {
         let transport = PtyTransport::new();
         assert!(!transport.is_running());
     }
+
+    #[test]
+    fn test_pty_handle_not_running() {
+        let handle = PtyHandle::new(Arc::new(Mutex::new(PtyManager::new())), Arc::new(Subscribers::new()));
+        assert!(!handle.is_open());
+        assert!(matches!(handle.send("hello"), Err(TransportError::NotConnected(_))));
+    }
 }
@@ -0,0 +1,199 @@
+//! Stdio transport
+//!
+//! Spawns an agent process and exchanges ACP envelopes with it as
+//! newline-delimited JSON: one `ACPEnvelope` per line on stdin/stdout.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::acp::agent::Transport;
+use crate::acp::message::ACPEnvelope;
+use super::{decode_frame, encode_frame, envelope_frame, Subscribers, TransportError, TransportEvent, TransportHandle, TransportListener};
+
+/// How to launch a stdio agent process
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StdioCommand {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+}
+
+/// `TransportHandle` for an agent process speaking ACP over stdin/stdout
+///
+/// Stdout lines that parse as `ACPEnvelope` are delivered as
+/// `TransportEvent::Message`; anything else is passed through as `Output`.
+/// Stderr lines are reported as `Error` events.
+pub struct StdioTransport {
+    agent_id: String,
+    child: Mutex<Child>,
+    stdin: Mutex<Option<ChildStdin>>,
+    subscribers: Arc<Subscribers>,
+    closed: Arc<AtomicBool>,
+}
+
+impl StdioTransport {
+    /// Spawn the agent process and start reading its output
+    pub fn spawn(agent_id: impl Into<String>, command: &StdioCommand) -> Result<Self, TransportError> {
+        let mut cmd = Command::new(&command.command);
+        cmd.args(&command.args)
+            .envs(&command.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(ref dir) = command.working_dir {
+            cmd.current_dir(dir);
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| TransportError::SpawnFailed(format!("{}: {}", command.command, e)))?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let subscribers = Arc::new(Subscribers::new());
+        let closed = Arc::new(AtomicBool::new(false));
+
+        if let Some(stdout) = stdout {
+            let subscribers = subscribers.clone();
+            let closed = closed.clone();
+            thread::spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    if let Some(event) = decode_frame(&line) {
+                        subscribers.notify(&event);
+                    }
+                }
+                // EOF: the process exited or closed stdout
+                if !closed.swap(true, Ordering::SeqCst) {
+                    subscribers.notify(&TransportEvent::Closed);
+                }
+            });
+        }
+
+        if let Some(stderr) = stderr {
+            let subscribers = subscribers.clone();
+            thread::spawn(move || {
+                for line in BufReader::new(stderr).lines() {
+                    let Ok(line) = line else { break };
+                    if !line.trim().is_empty() {
+                        subscribers.notify(&TransportEvent::Error { message: line });
+                    }
+                }
+            });
+        }
+
+        Ok(Self {
+            agent_id: agent_id.into(),
+            child: Mutex::new(child),
+            stdin: Mutex::new(stdin),
+            subscribers,
+            closed,
+        })
+    }
+
+    /// Write one newline-delimited frame to the agent's stdin
+    fn write_frame(&self, frame: &str) -> Result<(), TransportError> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(TransportError::Closed);
+        }
+        let mut stdin = self.stdin.lock();
+        let stdin = stdin.as_mut().ok_or(TransportError::Closed)?;
+        writeln!(stdin, "{}", frame)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| TransportError::SendFailed(e.to_string()))
+    }
+}
+
+impl TransportHandle for StdioTransport {
+    fn kind(&self) -> Transport {
+        Transport::Stdio
+    }
+
+    fn send(&self, content: &str) -> Result<(), TransportError> {
+        self.write_frame(&encode_frame(&self.agent_id, content)?)
+    }
+
+    fn send_envelope(&self, envelope: &ACPEnvelope) -> Result<(), TransportError> {
+        self.write_frame(&envelope_frame(envelope)?)
+    }
+
+    fn subscribe(&self, listener: TransportListener) -> u64 {
+        self.subscribers.add(listener)
+    }
+
+    fn unsubscribe(&self, id: u64) -> bool {
+        self.subscribers.remove(id)
+    }
+
+    fn close(&self) -> Result<(), TransportError> {
+        // Dropping stdin lets well-behaved agents exit on EOF
+        self.stdin.lock().take();
+        let mut child = self.child.lock();
+        if child.try_wait().ok().flatten().is_none() {
+            let _ = child.kill();
+        }
+        let _ = child.wait();
+        if !self.closed.swap(true, Ordering::SeqCst) {
+            self.subscribers.notify(&TransportEvent::Closed);
+        }
+        Ok(())
+    }
+
+    fn is_open(&self) -> bool {
+        !self.closed.load(Ordering::SeqCst) && self.child.lock().try_wait().ok().flatten().is_none()
+    }
+}
+
+impl Drop for StdioTransport {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_round_trip_through_cat() {
+        // `cat` echoes every frame back, so the agent "replies" with our own envelope
+        let transport = StdioTransport::spawn("echo-agent", &StdioCommand { command: "cat".into(), ..Default::default() }).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        transport.subscribe(Arc::new(move |event| {
+            let _ = tx.lock().send(event.clone());
+        }));
+
+        assert!(transport.is_open());
+        transport.send("hello").unwrap();
+
+        match rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+            TransportEvent::Message { envelope } => {
+                assert_eq!(envelope.message.payload.content, "hello");
+                assert_eq!(envelope.message.from.id, crate::acp::transport::LOCAL_SENDER);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        transport.close().unwrap();
+        assert!(!transport.is_open());
+        assert!(matches!(transport.send("again"), Err(TransportError::Closed)));
+    }
+}
@@ -0,0 +1,139 @@
+//! tmux pane transport
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use crate::acp::agent::Transport;
+use crate::acp::tmux::TmuxOrchestrator;
+use crate::output_log::diff_lines;
+use super::{Subscribers, TransportError, TransportEvent, TransportHandle, TransportListener};
+
+/// Pane capture interval while someone is subscribed
+const CAPTURE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// `TransportHandle` for an agent running in a tmux pane
+///
+/// tmux has no output stream, so subscribers are fed by capturing the pane
+/// periodically (only while at least one listener is registered).
+pub struct TmuxPaneHandle {
+    orchestrator: Arc<Mutex<Option<TmuxOrchestrator>>>,
+    agent_id: String,
+    subscribers: Arc<Subscribers>,
+    capturing: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+}
+
+impl TmuxPaneHandle {
+    pub fn new(orchestrator: Arc<Mutex<Option<TmuxOrchestrator>>>, agent_id: impl Into<String>) -> Self {
+        Self {
+            orchestrator,
+            agent_id: agent_id.into(),
+            subscribers: Arc::new(Subscribers::new()),
+            capturing: Arc::new(AtomicBool::new(false)),
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn capture(orchestrator: &Mutex<Option<TmuxOrchestrator>>, agent_id: &str) -> Option<String> {
+        let guard = orchestrator.lock();
+        let orch = guard.as_ref()?;
+        let pane_id = orch.get_pane_id(agent_id)?;
+        orch.capture_pane_plain(pane_id).ok()
+    }
+
+    /// Start the capture thread if it is not running yet
+    fn start_capture(&self) {
+        if self.capturing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let orchestrator = self.orchestrator.clone();
+        let agent_id = self.agent_id.clone();
+        let subscribers = self.subscribers.clone();
+        let capturing = self.capturing.clone();
+        let closed = self.closed.clone();
+
+        thread::spawn(move || {
+            let mut previous = Self::capture(&orchestrator, &agent_id).unwrap_or_default();
+            while !closed.load(Ordering::SeqCst) && !subscribers.is_empty() {
+                thread::sleep(CAPTURE_INTERVAL);
+                let Some(current) = Self::capture(&orchestrator, &agent_id) else {
+                    continue;
+                };
+                if let Some(text) = diff_lines(&previous, &current) {
+                    subscribers.notify(&TransportEvent::Output { text });
+                }
+                previous = current;
+            }
+            capturing.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+impl TransportHandle for TmuxPaneHandle {
+    fn kind(&self) -> Transport {
+        Transport::Pty
+    }
+
+    fn send(&self, content: &str) -> Result<(), TransportError> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(TransportError::Closed);
+        }
+        let guard = self.orchestrator.lock();
+        let orch = guard
+            .as_ref()
+            .ok_or_else(|| TransportError::NotConnected("tmux session not created".to_string()))?;
+        let pane_id = orch
+            .get_pane_id(&self.agent_id)
+            .ok_or_else(|| TransportError::NotConnected(format!("Agent not found: {}", self.agent_id)))?;
+        orch.send_keys(pane_id, content)
+            .map_err(|e| TransportError::SendFailed(e.to_string()))
+    }
+
+    fn subscribe(&self, listener: TransportListener) -> u64 {
+        let id = self.subscribers.add(listener);
+        self.start_capture();
+        id
+    }
+
+    fn unsubscribe(&self, id: u64) -> bool {
+        self.subscribers.remove(id)
+    }
+
+    fn close(&self) -> Result<(), TransportError> {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        if let Some(ref mut orch) = *self.orchestrator.lock() {
+            // The pane may already be gone
+            let _ = orch.kill_agent(&self.agent_id);
+        }
+        self.subscribers.notify(&TransportEvent::Closed);
+        Ok(())
+    }
+
+    fn is_open(&self) -> bool {
+        !self.closed.load(Ordering::SeqCst)
+            && self
+                .orchestrator
+                .lock()
+                .as_ref()
+                .is_some_and(|orch| orch.get_pane_id(&self.agent_id).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send_without_session() {
+        let handle = TmuxPaneHandle::new(Arc::new(Mutex::new(None)), "agent-1");
+        assert!(!handle.is_open());
+        assert!(matches!(handle.send("hi"), Err(TransportError::NotConnected(_))));
+    }
+}
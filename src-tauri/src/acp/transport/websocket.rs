@@ -0,0 +1,381 @@
+//! WebSocket transport
+//!
+//! Connects to a remote A2A-compatible agent and exchanges ACP envelopes as
+//! text frames. The connection is kept alive with `MessageType::Heartbeat`
+//! envelopes and WebSocket pings, and re-established automatically when it
+//! drops. Any frame from the remote, including the pong, counts as liveness.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use crate::acp::agent::Transport;
+use crate::acp::message::{ACPEnvelope, ACPMessageV3, MessageType};
+use super::{
+    decode_frame, encode_frame, envelope_frame, Subscribers, TransportError, TransportEvent, TransportHandle,
+    TransportListener, LOCAL_SENDER,
+};
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Heartbeat intervals without any frame (or pong) before the connection is considered dead
+const HEARTBEAT_MISSES: u32 = 3;
+
+/// Connection settings
+#[derive(Debug, Clone)]
+pub struct WebSocketConfig {
+    /// Interval between heartbeat envelopes
+    pub heartbeat_interval: Duration,
+    /// Delay between reconnect attempts
+    pub reconnect_delay: Duration,
+    /// Consecutive failed reconnects before giving up (None = retry forever)
+    pub max_reconnect_attempts: Option<u32>,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(15),
+            reconnect_delay: Duration::from_secs(3),
+            max_reconnect_attempts: Some(10),
+        }
+    }
+}
+
+/// Why a connected session ended
+enum SessionEnd {
+    /// `close()` was called
+    Closed,
+    /// The connection dropped and should be re-established
+    Lost(String),
+}
+
+/// State shared between the handle and its connection task
+struct Connection {
+    agent_id: String,
+    url: String,
+    config: WebSocketConfig,
+    subscribers: Arc<Subscribers>,
+    connected: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Connection {
+    /// Mark the transport closed and notify listeners (once)
+    fn shut_down(&self) {
+        self.connected.store(false, Ordering::SeqCst);
+        if !self.closed.swap(true, Ordering::SeqCst) {
+            self.subscribers.notify(&TransportEvent::Closed);
+        }
+    }
+
+    /// Drive the connection until `close()` is called or reconnecting gives up
+    async fn run(self, mut ws: WsStream, mut outgoing: UnboundedReceiver<String>) {
+        loop {
+            let reason = match self.session(&mut ws, &mut outgoing).await {
+                SessionEnd::Closed => break,
+                SessionEnd::Lost(reason) => reason,
+            };
+            self.connected.store(false, Ordering::SeqCst);
+            self.subscribers.notify(&TransportEvent::Error {
+                message: format!("Connection to {} lost: {}", self.url, reason),
+            });
+
+            match self.reconnect().await {
+                Some(stream) => ws = stream,
+                None => break,
+            }
+        }
+        self.shut_down();
+    }
+
+    /// Exchange frames over an established connection
+    async fn session(&self, ws: &mut WsStream, outgoing: &mut UnboundedReceiver<String>) -> SessionEnd {
+        let mut heartbeat = tokio::time::interval(self.config.heartbeat_interval);
+        heartbeat.tick().await;
+        let mut last_seen = Instant::now();
+
+        loop {
+            tokio::select! {
+                frame = outgoing.recv() => match frame {
+                    Some(frame) => {
+                        if let Err(e) = ws.send(Message::Text(frame)).await {
+                            return SessionEnd::Lost(e.to_string());
+                        }
+                    }
+                    None => {
+                        let _ = ws.close(None).await;
+                        return SessionEnd::Closed;
+                    }
+                },
+                _ = heartbeat.tick() => {
+                    if last_seen.elapsed() > self.config.heartbeat_interval * HEARTBEAT_MISSES {
+                        return SessionEnd::Lost("heartbeat timeout".to_string());
+                    }
+                    // Remotes that ignore ACP heartbeats still answer the ping
+                    if let Err(e) = ws.send(Message::Ping(Vec::new())).await {
+                        return SessionEnd::Lost(e.to_string());
+                    }
+                    let beat = ACPEnvelope::new(ACPMessageV3::heartbeat(LOCAL_SENDER, &self.agent_id));
+                    let Ok(json) = beat.to_json() else { continue };
+                    if let Err(e) = ws.send(Message::Text(json)).await {
+                        return SessionEnd::Lost(e.to_string());
+                    }
+                },
+                incoming = ws.next() => {
+                    last_seen = Instant::now();
+                    match incoming {
+                        Some(Ok(Message::Text(text))) => self.deliver(&text),
+                        Some(Ok(Message::Binary(bytes))) => self.deliver(&String::from_utf8_lossy(&bytes)),
+                        Some(Ok(Message::Close(_))) => return SessionEnd::Lost("closed by remote".to_string()),
+                        // Pongs only refresh last_seen; pings are answered by tungstenite itself
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => return SessionEnd::Lost(e.to_string()),
+                        None => return SessionEnd::Lost("stream ended".to_string()),
+                    }
+                },
+            }
+        }
+    }
+
+    /// Forward a received frame to listeners (heartbeats only count as liveness)
+    fn deliver(&self, frame: &str) {
+        match decode_frame(frame) {
+            Some(TransportEvent::Message { envelope }) if envelope.message.message_type == MessageType::Heartbeat => {}
+            Some(event) => self.subscribers.notify(&event),
+            None => {}
+        }
+    }
+
+    /// Retry the connection; None if closed meanwhile or out of attempts
+    async fn reconnect(&self) -> Option<WsStream> {
+        let mut attempts = 0;
+        loop {
+            if self.closed.load(Ordering::SeqCst) {
+                return None;
+            }
+            if self.config.max_reconnect_attempts.is_some_and(|max| attempts >= max) {
+                self.subscribers.notify(&TransportEvent::Error {
+                    message: format!("Giving up on {} after {} reconnect attempts", self.url, attempts),
+                });
+                return None;
+            }
+            attempts += 1;
+            tokio::time::sleep(self.config.reconnect_delay).await;
+
+            match connect_async(self.url.as_str()).await {
+                Ok((ws, _)) => {
+                    self.connected.store(true, Ordering::SeqCst);
+                    return Some(ws);
+                }
+                Err(e) => self.subscribers.notify(&TransportEvent::Error {
+                    message: format!("Reconnect to {} failed: {}", self.url, e),
+                }),
+            }
+        }
+    }
+}
+
+/// `TransportHandle` for a remote agent reachable over WebSocket
+///
+/// Messages sent while a reconnect is in progress are queued and delivered
+/// once the connection is back.
+pub struct WebSocketTransport {
+    agent_id: String,
+    outgoing: Mutex<Option<UnboundedSender<String>>>,
+    subscribers: Arc<Subscribers>,
+    connected: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+}
+
+impl WebSocketTransport {
+    /// Connect to `url` (ws:// or wss://) and keep the connection alive in the background
+    pub async fn connect(
+        agent_id: impl Into<String>,
+        url: impl Into<String>,
+        config: WebSocketConfig,
+    ) -> Result<Self, TransportError> {
+        let agent_id = agent_id.into();
+        let url = url.into();
+        if !(url.starts_with("ws://") || url.starts_with("wss://")) {
+            return Err(TransportError::NotConnected(format!("Invalid WebSocket URL: {}", url)));
+        }
+
+        let (ws, _) = connect_async(url.as_str())
+            .await
+            .map_err(|e| TransportError::NotConnected(format!("{}: {}", url, e)))?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let subscribers = Arc::new(Subscribers::new());
+        let connected = Arc::new(AtomicBool::new(true));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let connection = Connection {
+            agent_id: agent_id.clone(),
+            url,
+            config,
+            subscribers: subscribers.clone(),
+            connected: connected.clone(),
+            closed: closed.clone(),
+        };
+        tauri::async_runtime::spawn(connection.run(ws, rx));
+
+        Ok(Self {
+            agent_id,
+            outgoing: Mutex::new(Some(tx)),
+            subscribers,
+            connected,
+            closed,
+        })
+    }
+
+    /// Queue one text frame for the connection task
+    fn send_frame(&self, frame: String) -> Result<(), TransportError> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(TransportError::Closed);
+        }
+        self.outgoing
+            .lock()
+            .as_ref()
+            .ok_or(TransportError::Closed)?
+            .send(frame)
+            .map_err(|_| TransportError::Closed)
+    }
+}
+
+impl TransportHandle for WebSocketTransport {
+    fn kind(&self) -> Transport {
+        Transport::WebSocket
+    }
+
+    fn send(&self, content: &str) -> Result<(), TransportError> {
+        self.send_frame(encode_frame(&self.agent_id, content)?)
+    }
+
+    fn send_envelope(&self, envelope: &ACPEnvelope) -> Result<(), TransportError> {
+        self.send_frame(envelope_frame(envelope)?)
+    }
+
+    fn subscribe(&self, listener: TransportListener) -> u64 {
+        self.subscribers.add(listener)
+    }
+
+    fn unsubscribe(&self, id: u64) -> bool {
+        self.subscribers.remove(id)
+    }
+
+    fn close(&self) -> Result<(), TransportError> {
+        // Dropping the sender ends the session with a close frame
+        self.outgoing.lock().take();
+        self.connected.store(false, Ordering::SeqCst);
+        if !self.closed.swap(true, Ordering::SeqCst) {
+            self.subscribers.notify(&TransportEvent::Closed);
+        }
+        Ok(())
+    }
+
+    fn is_open(&self) -> bool {
+        !self.closed.load(Ordering::SeqCst) && self.connected.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for WebSocketTransport {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Echo server that also reports every heartbeat it receives
+    async fn echo_server(heartbeats: UnboundedSender<()>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(message)) = ws.next().await {
+                let Message::Text(text) = message else { continue };
+                let envelope = ACPEnvelope::from_json(&text).unwrap();
+                if envelope.message.message_type == MessageType::Heartbeat {
+                    let _ = heartbeats.send(());
+                } else {
+                    ws.send(Message::Text(text)).await.unwrap();
+                }
+            }
+        });
+        format!("ws://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_and_heartbeat() {
+        let (beat_tx, mut beat_rx) = mpsc::unbounded_channel();
+        let url = echo_server(beat_tx).await;
+        let config = WebSocketConfig {
+            heartbeat_interval: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let transport = WebSocketTransport::connect("remote-1", &url, config).await.unwrap();
+        assert!(transport.is_open());
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        transport.subscribe(Arc::new(move |event| {
+            let _ = tx.send(event.clone());
+        }));
+        transport.send("hello").unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        match event {
+            TransportEvent::Message { envelope } => assert_eq!(envelope.message.payload.content, "hello"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        tokio::time::timeout(Duration::from_secs(5), beat_rx.recv()).await.unwrap().unwrap();
+
+        transport.close().unwrap();
+        assert!(!transport.is_open());
+        assert!(matches!(transport.send("again"), Err(TransportError::Closed)));
+    }
+
+    #[tokio::test]
+    async fn test_quiet_remote_stays_connected() {
+        // Reads (and so answers pings) but never sends anything itself
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let config = WebSocketConfig {
+            heartbeat_interval: Duration::from_millis(30),
+            ..Default::default()
+        };
+        let transport = WebSocketTransport::connect("remote-1", &url, config).await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        transport.subscribe(Arc::new(move |event| {
+            let _ = tx.send(event.clone());
+        }));
+
+        tokio::time::sleep(Duration::from_millis(30 * HEARTBEAT_MISSES as u64 * 4)).await;
+        assert!(transport.is_open());
+        assert!(rx.try_recv().is_err());
+        transport.close().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_websocket_url() {
+        let result = WebSocketTransport::connect("remote-1", "http://localhost", WebSocketConfig::default()).await;
+        assert!(matches!(result, Err(TransportError::NotConnected(_))));
+    }
+}
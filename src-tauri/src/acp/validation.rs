@@ -0,0 +1,212 @@
+//! 翻訳出力の検証
+//!
+//! 翻訳結果の `[N]` 番号が原文のセグメントと1対1で対応しているかを確認する。
+//! 欠落・重複した番号は訳文から除き（再翻訳の対象）、番号の外に漏れた説明文などは取り除く。
+//! 検証ステージ用に、翻訳済みテキスト全体の欠落・未翻訳セグメントも検出する。
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+/// 検証結果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranslationValidation {
+    /// 正しく1回だけ出現した番号の訳文
+    pub translations: BTreeMap<usize, String>,
+    /// 欠落した番号（空の訳文を含む）
+    pub missing: Vec<usize>,
+    /// 重複した番号
+    pub duplicated: Vec<usize>,
+    /// 原文に存在しない番号
+    pub unexpected: Vec<usize>,
+    /// 番号の外に漏れたテキスト
+    pub leaked: Vec<String>,
+}
+
+/// 翻訳出力を検証する
+///
+/// `expected` は原文のセグメント番号。空行の後に番号なしで続くテキストや、
+/// 最初の番号より前のテキストは漏れたテキストとして扱う。
+pub fn validate_translation(expected: &[usize], output: &str) -> TranslationValidation {
+    let re = regex::Regex::new(r"^\s*\[(\d+)\]\s*").unwrap();
+    let expected_set: HashSet<usize> = expected.iter().copied().collect();
+
+    let mut entries: Vec<(usize, String)> = Vec::new();
+    let mut leaked: Vec<String> = Vec::new();
+    // 直前の行が空行だったか（空行を挟んだ番号なしテキストは訳文の続きではない）
+    let mut after_blank = true;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(caps) = re.captures(line) {
+            let index = caps[1].parse().unwrap_or(usize::MAX);
+            entries.push((index, re.replace(line, "").trim().to_string()));
+            after_blank = false;
+        } else if trimmed.is_empty() {
+            after_blank = true;
+        } else if after_blank || entries.is_empty() {
+            leaked.push(trimmed.to_string());
+        } else if let Some(last) = entries.last_mut() {
+            if !last.1.is_empty() {
+                last.1.push(' ');
+            }
+            last.1.push_str(trimmed);
+        }
+    }
+
+    let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+    for (index, _) in &entries {
+        *counts.entry(*index).or_default() += 1;
+    }
+
+    let mut result = TranslationValidation {
+        leaked,
+        ..Default::default()
+    };
+
+    for (index, text) in entries {
+        if !expected_set.contains(&index) {
+            if !result.unexpected.contains(&index) {
+                result.unexpected.push(index);
+            }
+        } else if counts[&index] > 1 {
+            if !result.duplicated.contains(&index) {
+                result.duplicated.push(index);
+            }
+        } else if !text.is_empty() {
+            result.translations.insert(index, text);
+        }
+    }
+
+    result.missing = expected
+        .iter()
+        .copied()
+        .filter(|i| !result.translations.contains_key(i) && !result.duplicated.contains(i))
+        .collect();
+
+    result
+}
+
+/// 翻訳済みテキストの検証レポート（`verification_report.<lang>.json`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerificationReport {
+    /// 原文のセグメント数
+    pub segment_count: usize,
+    /// 訳文のあるセグメント数
+    pub translated_count: usize,
+    /// 訳文が欠落・空の番号
+    pub missing: Vec<usize>,
+    /// 明らかに翻訳されていない番号
+    pub untranslated: Vec<usize>,
+    /// 再翻訳で修復した番号
+    pub repaired: Vec<usize>,
+    /// 修復できずに残った番号
+    pub unresolved: Vec<usize>,
+}
+
+impl VerificationReport {
+    /// 再翻訳が必要な番号（欠落と未翻訳）
+    pub fn broken(&self) -> Vec<usize> {
+        let mut broken: Vec<usize> = self.missing.iter().chain(&self.untranslated).copied().collect();
+        broken.sort_unstable();
+        broken
+    }
+}
+
+/// 翻訳済みテキストを原文と突き合わせて検証する
+pub fn verify_translation(
+    sources: &[(usize, String)],
+    translations: &BTreeMap<usize, String>,
+    target_lang: &str,
+) -> VerificationReport {
+    let mut report = VerificationReport {
+        segment_count: sources.len(),
+        ..Default::default()
+    };
+
+    for (index, source) in sources {
+        match translations.get(index).map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            None => report.missing.push(*index),
+            Some(text) => {
+                report.translated_count += 1;
+                if is_untranslated(source, text, target_lang) {
+                    report.untranslated.push(*index);
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// 明らかに翻訳されていないか（原文のまま、または翻訳先の文字を含まない）
+fn is_untranslated(source: &str, translation: &str, target_lang: &str) -> bool {
+    if !source.chars().any(char::is_alphabetic) {
+        return false;
+    }
+    let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    if normalize(source) == normalize(translation) {
+        return true;
+    }
+
+    let contains = |ranges: &[(char, char)]| {
+        translation.chars().any(|c| ranges.iter().any(|&(lo, hi)| c >= lo && c <= hi))
+    };
+    match super::translator::primary_language(target_lang).as_str() {
+        // ひらがな・カタカナ・漢字
+        "ja" => !contains(&[('\u{3040}', '\u{30ff}'), ('\u{4e00}', '\u{9fff}')]),
+        "zh" => !contains(&[('\u{4e00}', '\u{9fff}')]),
+        "ko" => !contains(&[('\u{ac00}', '\u{d7af}')]),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_output() {
+        let validation = validate_translation(&[0, 1], "[0] こんにちは\n\n[1] 世界");
+        assert!(validation.missing.is_empty());
+        assert!(validation.leaked.is_empty());
+        assert_eq!(validation.translations[&1], "世界");
+    }
+
+    #[test]
+    fn test_detects_missing_duplicated_and_leaked() {
+        let output = "翻訳結果:\n[0] こんにちは\n[0] やあ\n\n[2] 余分\n[3] \n\n以上です。";
+        let validation = validate_translation(&[0, 1, 3], output);
+
+        assert_eq!(validation.duplicated, vec![0]);
+        assert_eq!(validation.missing, vec![1, 3]);
+        assert_eq!(validation.unexpected, vec![2]);
+        assert_eq!(validation.leaked, vec!["翻訳結果:".to_string(), "以上です。".to_string()]);
+    }
+
+    #[test]
+    fn test_multiline_segment_is_kept() {
+        let validation = validate_translation(&[0], "[0] 一行目\n二行目");
+        assert_eq!(validation.translations[&0], "一行目 二行目");
+        assert!(validation.leaked.is_empty());
+    }
+
+    #[test]
+    fn test_verify_translation() {
+        let sources: Vec<(usize, String)> = ["Hello", "Good morning", "See you", "42"]
+            .iter().enumerate().map(|(i, s)| (i, s.to_string())).collect();
+        let translations: BTreeMap<usize, String> = [(0, "こんにちは"), (1, "good  morning"), (3, "42")]
+            .into_iter().map(|(i, t)| (i, t.to_string())).collect();
+
+        let report = verify_translation(&sources, &translations, "ja");
+        assert_eq!(report.segment_count, 4);
+        assert_eq!(report.translated_count, 3);
+        assert_eq!(report.missing, vec![2]);
+        assert_eq!(report.untranslated, vec![1]);
+        assert_eq!(report.broken(), vec![1, 2]);
+
+        // 英語への翻訳では文字種を見ない
+        let report = verify_translation(&sources[..1], &[(0, "Hi".to_string())].into(), "en");
+        assert!(report.broken().is_empty());
+    }
+}
@@ -0,0 +1,305 @@
+//! アプリデータのエクスポート/インポート
+//!
+//! パイプライン定義、Ask自動応答ポリシー、アプリ設定、データディレクトリ内の設定ファイル
+//! （用語集・翻訳メモリ・権限ルール・プリセット）を1つのアーカイブ（JSON）にまとめ、
+//! 別のマシンに持ち込めるようにする。実行状態・履歴・認証情報は含めない。
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+use thiserror::Error;
+
+use crate::acp::ask::AutoAnswerPolicy;
+use crate::acp::pipeline::PipelineDefinition;
+use crate::settings::Settings;
+
+/// アーカイブ形式のバージョン
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// アーカイブに含める設定ファイル（`<data_dir>/<name>.json`）
+///
+/// 用語集・翻訳メモリ・権限ルール・翻訳プリセット。これ以外のファイルは読み書きしない。
+pub const EXPORTED_STORES: &[&str] = &["glossaries", "translation_memory", "permission_policies", "presets"];
+
+/// アプリデータエラー
+#[derive(Debug, Error)]
+pub enum AppDataError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Unsupported bundle version: {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Invalid store name: {0}")]
+    InvalidStoreName(String),
+}
+
+/// エクスポートアーカイブ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppDataBundle {
+    /// アーカイブ形式のバージョン
+    pub format_version: u32,
+    /// エクスポートしたアプリのバージョン
+    pub app_version: String,
+    /// エクスポート日時
+    pub exported_at: DateTime<Utc>,
+    /// パイプライン定義
+    #[serde(default)]
+    pub pipelines: Vec<PipelineDefinition>,
+    /// Ask Tool 自動応答ポリシー
+    #[serde(default)]
+    pub ask_policies: Vec<AutoAnswerPolicy>,
+    /// アプリ設定（エージェントサーバーのトークンは除く）
+    #[serde(default)]
+    pub settings: Option<Value>,
+    /// データディレクトリ内の設定ファイル（ファイル名（拡張子なし） -> 内容）
+    #[serde(default)]
+    pub stores: BTreeMap<String, Value>,
+}
+
+impl AppDataBundle {
+    /// 空のアーカイブを作成
+    pub fn new() -> Self {
+        Self {
+            format_version: BUNDLE_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: Utc::now(),
+            pipelines: Vec::new(),
+            ask_policies: Vec::new(),
+            settings: None,
+            stores: BTreeMap::new(),
+        }
+    }
+
+    /// ファイルに書き出す
+    pub fn save(&self, path: &Path) -> Result<(), AppDataError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// ファイルから読み込む
+    pub fn load(path: &Path) -> Result<Self, AppDataError> {
+        let content = std::fs::read_to_string(path)?;
+        let bundle: Self = serde_json::from_str(&content)?;
+        if bundle.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(AppDataError::UnsupportedVersion(bundle.format_version));
+        }
+        Ok(bundle)
+    }
+}
+
+impl Default for AppDataBundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// インポート結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// 登録したパイプライン数
+    pub pipelines: usize,
+    /// 取り込んだポリシー数
+    pub ask_policies: usize,
+    /// アプリ設定を更新したか
+    pub settings: bool,
+    /// 書き込んだ設定ファイル
+    pub stores: Vec<String>,
+}
+
+/// アプリのデータディレクトリ（取得できない場合はカレントの `data/`）
+pub fn data_dir<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("data"))
+}
+
+/// アーカイブに含める設定ファイルか
+fn is_valid_store_name(name: &str) -> bool {
+    EXPORTED_STORES.contains(&name)
+}
+
+/// データディレクトリ内の対象の設定ファイル（`EXPORTED_STORES`）を読み込む
+pub fn read_stores(dir: &Path) -> Result<BTreeMap<String, Value>, AppDataError> {
+    let mut stores = BTreeMap::new();
+
+    for name in EXPORTED_STORES {
+        let path = dir.join(format!("{}.json", name));
+        if !path.is_file() {
+            continue;
+        }
+        match std::fs::read_to_string(&path).map(|c| serde_json::from_str::<Value>(&c)) {
+            Ok(Ok(value)) => {
+                stores.insert(name.to_string(), value);
+            }
+            _ => {
+                crate::log::warn("AppData", &format!("Skipping unreadable store: {}", path.display()));
+            }
+        }
+    }
+
+    Ok(stores)
+}
+
+/// アーカイブに含めるアプリ設定（エージェントサーバーのトークンは除く）
+pub fn exported_settings(settings: &Settings) -> Result<Value, AppDataError> {
+    let mut value = serde_json::to_value(settings)?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.remove("agent_server_token");
+    }
+    Ok(value)
+}
+
+/// 設定ファイルをデータディレクトリに書き込む（同名ファイルは上書き）
+pub fn write_stores(dir: &Path, stores: &BTreeMap<String, Value>) -> Result<Vec<String>, AppDataError> {
+    // 1件でも不正な名前があれば何も書き込まない
+    if let Some(bad) = stores.keys().find(|name| !is_valid_store_name(name)) {
+        return Err(AppDataError::InvalidStoreName(bad.clone()));
+    }
+
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for (name, value) in stores {
+        let path = dir.join(format!("{}.json", name));
+        std::fs::write(&path, serde_json::to_string_pretty(value)?)?;
+        written.push(name.clone());
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("re-voice-appdata-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let dir = temp_dir("bundle");
+        let mut bundle = AppDataBundle::new();
+        bundle.pipelines.push(PipelineDefinition::new("custom"));
+        bundle.stores.insert("glossary".to_string(), serde_json::json!({ "AI": "人工知能" }));
+
+        let path = dir.join("export.revoice.json");
+        bundle.save(&path).unwrap();
+        let loaded = AppDataBundle::load(&path).unwrap();
+
+        assert_eq!(loaded.pipelines.len(), 1);
+        assert_eq!(loaded.pipelines[0].name, "custom");
+        assert_eq!(loaded.stores["glossary"]["AI"], "人工知能");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_stores_roundtrip() {
+        let dir = temp_dir("stores");
+        let mut stores = BTreeMap::new();
+        stores.insert("glossaries".to_string(), serde_json::json!({ "default": [] }));
+        assert_eq!(write_stores(&dir, &stores).unwrap(), vec!["glossaries".to_string()]);
+
+        // 対象外のファイル（実行状態など）は読み込まない
+        std::fs::write(dir.join("executor_state.json"), "{}").unwrap();
+        let read = read_stores(&dir).unwrap();
+        assert_eq!(read.keys().collect::<Vec<_>>(), vec!["glossaries"]);
+        assert!(read["glossaries"]["default"].is_array());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_exported_stores_match_files() {
+        use crate::acp::glossary::GLOSSARY_FILE;
+        use crate::acp::permission::POLICY_FILE;
+        use crate::acp::runner::PRESETS_FILE;
+        use crate::acp::translation_memory::TRANSLATION_MEMORY_FILE;
+
+        let files: Vec<String> = EXPORTED_STORES.iter().map(|name| format!("{}.json", name)).collect();
+        assert_eq!(files, vec![GLOSSARY_FILE, TRANSLATION_MEMORY_FILE, POLICY_FILE, PRESETS_FILE]);
+
+        let settings = Settings { agent_server_token: Some("secret".to_string()), ..Default::default() };
+        let exported = exported_settings(&settings).unwrap();
+        assert!(exported.get("agent_server_token").is_none());
+        assert_eq!(exported["default_speaker"], settings.default_speaker);
+    }
+
+    #[test]
+    fn test_policies_and_presets_roundtrip() {
+        use crate::acp::ask::{AskToolHandler, ASK_POLICY_FILE};
+        use crate::acp::runner::{TranslationOptions, PRESETS_FILE};
+
+        let source = temp_dir("source");
+        let target = temp_dir("target");
+
+        let policy = AutoAnswerPolicy {
+            resource_pattern: "^/work/".to_string(),
+            action: "write".to_string(),
+            auto_answer: "1".to_string(),
+            always: true,
+        };
+        let handler = AskToolHandler::new();
+        handler.set_policies(vec![policy]);
+        let mut presets = BTreeMap::new();
+        presets.insert("lecture".to_string(), TranslationOptions { project: Some("course".to_string()), ..Default::default() });
+        TranslationOptions::save_presets(&source.join(PRESETS_FILE), &presets).unwrap();
+
+        let mut bundle = AppDataBundle::new();
+        bundle.ask_policies = handler.policies();
+        bundle.stores = read_stores(&source).unwrap();
+        let path = source.join("export.revoice.json");
+        bundle.save(&path).unwrap();
+
+        // 別のマシンで取り込み、再起動後に読み込む
+        let loaded = AppDataBundle::load(&path).unwrap();
+        write_stores(&target, &loaded.stores).unwrap();
+        let imported = AskToolHandler::new();
+        imported.set_policies(loaded.ask_policies);
+        imported.save_policies(&target.join(ASK_POLICY_FILE)).unwrap();
+
+        let restarted = AskToolHandler::new();
+        assert_eq!(restarted.load_policies(&target.join(ASK_POLICY_FILE)).unwrap(), 1);
+        assert_eq!(restarted.policies()[0].resource_pattern, "^/work/");
+        let restored = TranslationOptions::load_presets(&target.join(PRESETS_FILE)).unwrap();
+        assert_eq!(restored["lecture"].project.as_deref(), Some("course"));
+
+        let _ = std::fs::remove_dir_all(source);
+        let _ = std::fs::remove_dir_all(target);
+    }
+
+    #[test]
+    fn test_rejects_invalid_store_name() {
+        let dir = temp_dir("invalid");
+        let mut stores = BTreeMap::new();
+        stores.insert("../escape".to_string(), Value::Null);
+        assert!(matches!(write_stores(&dir, &stores), Err(AppDataError::InvalidStoreName(_))));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let dir = temp_dir("version");
+        let mut bundle = AppDataBundle::new();
+        bundle.format_version = BUNDLE_FORMAT_VERSION + 1;
+        let path = dir.join("future.json");
+        bundle.save(&path).unwrap();
+        assert!(matches!(AppDataBundle::load(&path), Err(AppDataError::UnsupportedVersion(_))));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
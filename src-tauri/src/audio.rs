@@ -0,0 +1,119 @@
+//! 音声の試聴再生
+//!
+//! 合成したセグメントや組み立てた吹替トラックをアプリ内で再生する。
+//! rodioの出力ストリームはスレッド間で移動できないため、再生ごとに専用スレッドを立て、
+//! 停止用に `Sink` だけを保持する。
+
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use rodio::{Decoder, OutputStream, Sink};
+use thiserror::Error;
+
+/// 再生エラー
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Unsupported audio file: {0}")]
+    Decode(String),
+
+    #[error("Audio output device error: {0}")]
+    Device(String),
+}
+
+/// 音声ファイルを開いてデコーダーを作る
+fn open_source(path: &str) -> Result<Decoder<BufReader<File>>, AudioError> {
+    let file = File::open(path)?;
+    Decoder::new(BufReader::new(file)).map_err(|e| AudioError::Decode(e.to_string()))
+}
+
+/// 試聴プレイヤー（同時に再生するのは1つだけ）
+#[derive(Default)]
+pub struct AudioPlayer {
+    current: Mutex<Option<Arc<Sink>>>,
+}
+
+impl AudioPlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 再生を開始する（再生中のものは止める）
+    pub fn play(&self, path: &str) -> Result<(), AudioError> {
+        self.stop();
+        let source = open_source(path)?;
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let (_stream, handle) = match OutputStream::try_default() {
+                Ok(output) => output,
+                Err(e) => {
+                    let _ = tx.send(Err(AudioError::Device(e.to_string())));
+                    return;
+                }
+            };
+            let sink = match Sink::try_new(&handle) {
+                Ok(sink) => Arc::new(sink),
+                Err(e) => {
+                    let _ = tx.send(Err(AudioError::Device(e.to_string())));
+                    return;
+                }
+            };
+            sink.append(source);
+            let _ = tx.send(Ok(sink.clone()));
+            // 再生が終わる（または停止される）までストリームを保持する
+            sink.sleep_until_end();
+        });
+
+        let sink = rx
+            .recv()
+            .map_err(|e| AudioError::Device(e.to_string()))??;
+        crate::log::info("AudioPlayer", &format!("Playing: {}", path));
+        *self.current.lock() = Some(sink);
+        Ok(())
+    }
+
+    /// 再生を停止する
+    pub fn stop(&self) {
+        if let Some(sink) = self.current.lock().take() {
+            sink.stop();
+        }
+    }
+
+    /// 再生中か
+    pub fn is_playing(&self) -> bool {
+        self.current.lock().as_ref().map(|sink| !sink.empty()).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeline::PcmAudio;
+
+    #[test]
+    fn test_open_source() {
+        let dir = std::env::temp_dir();
+        let wav = dir.join(format!("re-voice-audio-{}.wav", uuid::Uuid::new_v4()));
+        PcmAudio { sample_rate: 24_000, samples: vec![0; 240] }.write_wav(&wav).unwrap();
+        assert!(open_source(&wav.to_string_lossy()).is_ok());
+
+        let text = dir.join(format!("re-voice-audio-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&text, "not audio").unwrap();
+        assert!(matches!(open_source(&text.to_string_lossy()), Err(AudioError::Decode(_))));
+
+        let _ = std::fs::remove_file(wav);
+        let _ = std::fs::remove_file(text);
+    }
+
+    #[test]
+    fn test_play_missing_file() {
+        let player = AudioPlayer::new();
+        assert!(matches!(player.play("/nonexistent/audio.wav"), Err(AudioError::Io(_))));
+        assert!(!player.is_playing());
+    }
+}
@@ -0,0 +1,96 @@
+//! チャプター分割
+//!
+//! 長い配信をチャプターごとの短い吹替動画として公開できるよう、
+//! 吹替トラック・翻訳済み字幕をチャプターの範囲で切り出す。
+
+use serde::{Deserialize, Serialize};
+
+use crate::acp::subtitle_parser::SubtitleSegment;
+use crate::timeline::PcmAudio;
+
+/// チャプター
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub title: String,
+    /// 開始時刻（ミリ秒）
+    pub start_ms: u64,
+    /// 終了時刻（ミリ秒）
+    pub end_ms: u64,
+}
+
+impl Chapter {
+    /// 出力ディレクトリ名（`01_タイトル`）
+    pub fn dir_name(&self, number: usize) -> String {
+        let title: String = self
+            .title
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        let title = title.trim_matches('_');
+        if title.is_empty() {
+            format!("{:02}", number)
+        } else {
+            format!("{:02}_{}", number, title.chars().take(40).collect::<String>())
+        }
+    }
+}
+
+/// チャプター内に始まるセグメントを切り出し、チャプターの先頭を0とした時刻に直す
+pub fn slice_segments(segments: &[SubtitleSegment], chapter: &Chapter) -> Vec<SubtitleSegment> {
+    segments
+        .iter()
+        .filter(|s| s.start_ms >= chapter.start_ms && s.start_ms < chapter.end_ms)
+        .enumerate()
+        .map(|(i, s)| {
+            SubtitleSegment::new(
+                i as u32 + 1,
+                s.start_ms - chapter.start_ms,
+                s.end_ms.min(chapter.end_ms) - chapter.start_ms,
+                s.text.clone(),
+            )
+        })
+        .collect()
+}
+
+/// 音声からチャプターの範囲を切り出す
+pub fn slice_audio(audio: &PcmAudio, chapter: &Chapter) -> PcmAudio {
+    let to_sample = |ms: u64| ((ms * audio.sample_rate as u64 / 1000) as usize).min(audio.samples.len());
+    let start = to_sample(chapter.start_ms);
+    let end = to_sample(chapter.end_ms).max(start);
+    PcmAudio {
+        sample_rate: audio.sample_rate,
+        samples: audio.samples[start..end].to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(start_ms: u64, end_ms: u64) -> Chapter {
+        Chapter { title: "Part 1: Intro".to_string(), start_ms, end_ms }
+    }
+
+    #[test]
+    fn test_slice_segments() {
+        let segments = vec![
+            SubtitleSegment::new(1, 0, 900, "a".to_string()),
+            SubtitleSegment::new(2, 1000, 2500, "b".to_string()),
+            SubtitleSegment::new(3, 2000, 3000, "c".to_string()),
+        ];
+        let sliced = slice_segments(&segments, &chapter(1000, 2200));
+        assert_eq!(sliced.len(), 2);
+        assert_eq!((sliced[0].index, sliced[0].start_ms, sliced[0].end_ms), (1, 0, 1200));
+        assert_eq!((sliced[1].start_ms, sliced[1].end_ms), (1000, 1200));
+    }
+
+    #[test]
+    fn test_slice_audio_and_dir_name() {
+        let audio = PcmAudio { sample_rate: 1000, samples: (0..100).collect() };
+        let sliced = slice_audio(&audio, &chapter(10, 20));
+        assert_eq!(sliced.samples, (10..20).collect::<Vec<i16>>());
+        assert_eq!(slice_audio(&audio, &chapter(90, 200)).samples.len(), 10);
+
+        assert_eq!(chapter(0, 1).dir_name(3), "03_Part_1__Intro");
+    }
+}
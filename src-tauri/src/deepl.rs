@@ -0,0 +1,254 @@
+//! DeepL API クライアント
+//!
+//! 単純な内容の字幕をClaudeの代わりにDeepLで翻訳する。
+//! リクエスト間隔の制限と429応答時のリトライ、使用文字数の集計を行う。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 1リクエストあたりの最大テキスト数（DeepL APIの上限）
+const MAX_TEXTS_PER_REQUEST: usize = 50;
+
+/// リクエスト間の最小間隔
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 429応答時の最大リトライ回数
+const MAX_RETRIES: u32 = 3;
+
+/// DeepL APIエラー
+#[derive(Debug, Error)]
+pub enum DeeplError {
+    #[error("HTTP request failed: {0}")]
+    HttpError(String),
+
+    #[error("DeepL API key is not configured")]
+    MissingApiKey,
+
+    #[error("DeepL API rejected the request ({status}): {message}")]
+    ApiError { status: u16, message: String },
+
+    #[error("DeepL character quota exceeded")]
+    QuotaExceeded,
+
+    #[error("Rate limited by DeepL after {0} retries")]
+    RateLimited(u32),
+
+    #[error("Unexpected response: expected {expected} translations, got {actual}")]
+    CountMismatch { expected: usize, actual: usize },
+}
+
+/// 使用量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeeplUsage {
+    /// 今期の使用文字数
+    pub character_count: u64,
+    /// 今期の上限文字数
+    pub character_limit: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranslateResponse {
+    translations: Vec<Translation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Translation {
+    text: String,
+}
+
+/// DeepL API クライアント
+pub struct DeeplClient {
+    api_key: String,
+    base_url: String,
+    client: reqwest::Client,
+    /// 最後にリクエストした時刻
+    last_request: Mutex<Option<Instant>>,
+    /// このクライアントで送信した文字数
+    characters_sent: AtomicU64,
+}
+
+impl DeeplClient {
+    /// 新しいクライアントを作成（Freeプランのキーは `:fx` で終わる）
+    pub fn new(api_key: &str) -> Result<Self, DeeplError> {
+        if api_key.trim().is_empty() {
+            return Err(DeeplError::MissingApiKey);
+        }
+
+        let base_url = if api_key.ends_with(":fx") {
+            "https://api-free.deepl.com"
+        } else {
+            "https://api.deepl.com"
+        };
+
+        Ok(Self {
+            api_key: api_key.trim().to_string(),
+            base_url: base_url.to_string(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(60))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            last_request: Mutex::new(None),
+            characters_sent: AtomicU64::new(0),
+        })
+    }
+
+    /// このクライアントで送信した文字数
+    pub fn characters_sent(&self) -> u64 {
+        self.characters_sent.load(Ordering::SeqCst)
+    }
+
+    /// テキストを翻訳（入力と同じ順序で返す）
+    ///
+    /// `formality` は `prefer_more` / `prefer_less`（非対応の言語では無視される）。
+    pub async fn translate(
+        &self,
+        texts: &[String],
+        source_lang: Option<&str>,
+        target_lang: &str,
+        formality: Option<&str>,
+    ) -> Result<Vec<String>, DeeplError> {
+        let mut results = Vec::with_capacity(texts.len());
+
+        for batch in texts.chunks(MAX_TEXTS_PER_REQUEST) {
+            let mut form: Vec<(&str, String)> = batch.iter().map(|t| ("text", t.clone())).collect();
+            form.push(("target_lang", deepl_target_lang(target_lang)));
+            if let Some(source) = source_lang {
+                form.push(("source_lang", deepl_source_lang(source)));
+            }
+            if let Some(formality) = formality {
+                form.push(("formality", formality.to_string()));
+            }
+
+            let body = self.post_with_retry("/v2/translate", &form).await?;
+            let response: TranslateResponse = serde_json::from_str(&body)
+                .map_err(|e| DeeplError::HttpError(e.to_string()))?;
+
+            if response.translations.len() != batch.len() {
+                return Err(DeeplError::CountMismatch {
+                    expected: batch.len(),
+                    actual: response.translations.len(),
+                });
+            }
+
+            let chars: usize = batch.iter().map(|t| t.chars().count()).sum();
+            self.characters_sent.fetch_add(chars as u64, Ordering::SeqCst);
+            results.extend(response.translations.into_iter().map(|t| t.text));
+        }
+
+        Ok(results)
+    }
+
+    /// 使用量を取得
+    pub async fn usage(&self) -> Result<DeeplUsage, DeeplError> {
+        self.wait_for_slot().await;
+        let resp = self.client
+            .get(format!("{}/v2/usage", self.base_url))
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .send()
+            .await
+            .map_err(|e| DeeplError::HttpError(e.to_string()))?;
+
+        let status = resp.status().as_u16();
+        let body = resp.text().await.map_err(|e| DeeplError::HttpError(e.to_string()))?;
+        if status != 200 {
+            return Err(DeeplError::ApiError { status, message: body });
+        }
+
+        serde_json::from_str(&body).map_err(|e| DeeplError::HttpError(e.to_string()))
+    }
+
+    /// 最小間隔を守ってPOSTし、429応答ならバックオフしてリトライ
+    async fn post_with_retry(&self, path: &str, form: &[(&str, String)]) -> Result<String, DeeplError> {
+        let mut backoff = Duration::from_secs(1);
+
+        for attempt in 0..=MAX_RETRIES {
+            self.wait_for_slot().await;
+
+            let resp = self.client
+                .post(format!("{}{}", self.base_url, path))
+                .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+                .form(form)
+                .send()
+                .await
+                .map_err(|e| DeeplError::HttpError(e.to_string()))?;
+
+            let status = resp.status().as_u16();
+            let body = resp.text().await.map_err(|e| DeeplError::HttpError(e.to_string()))?;
+
+            match status {
+                200 => return Ok(body),
+                429 if attempt < MAX_RETRIES => {
+                    crate::log::warn("DeeplClient", &format!(
+                        "Rate limited, retrying in {:?} (attempt {})", backoff, attempt + 1
+                    ));
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                429 => return Err(DeeplError::RateLimited(MAX_RETRIES)),
+                456 => return Err(DeeplError::QuotaExceeded),
+                _ => return Err(DeeplError::ApiError { status, message: body }),
+            }
+        }
+
+        Err(DeeplError::RateLimited(MAX_RETRIES))
+    }
+
+    /// 前回のリクエストから最小間隔が経つまで待つ
+    async fn wait_for_slot(&self) {
+        let wait = {
+            let mut last = self.last_request.lock();
+            let now = Instant::now();
+            let wait = last
+                .map(|t| MIN_REQUEST_INTERVAL.saturating_sub(now.duration_since(t)))
+                .unwrap_or_default();
+            *last = Some(now + wait);
+            wait
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// 言語コードをDeepLの翻訳先コードに変換
+///
+/// 英語・ポルトガル語は地域指定が必須のため既定の地域を補う。
+pub fn deepl_target_lang(lang: &str) -> String {
+    match lang.to_lowercase().as_str() {
+        "en" => "EN-US".to_string(),
+        "pt" => "PT-BR".to_string(),
+        other => other.to_uppercase(),
+    }
+}
+
+/// 言語コードをDeepLの翻訳元コードに変換（地域指定は不可）
+fn deepl_source_lang(lang: &str) -> String {
+    lang.split('-').next().unwrap_or(lang).to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_codes() {
+        assert_eq!(deepl_target_lang("ja"), "JA");
+        assert_eq!(deepl_target_lang("en"), "EN-US");
+        assert_eq!(deepl_target_lang("en-gb"), "EN-GB");
+        assert_eq!(deepl_source_lang("en-US"), "EN");
+    }
+
+    #[test]
+    fn test_endpoint_and_missing_key() {
+        let free = DeeplClient::new("abc:fx").unwrap();
+        assert_eq!(free.base_url, "https://api-free.deepl.com");
+        let pro = DeeplClient::new("abc").unwrap();
+        assert_eq!(pro.base_url, "https://api.deepl.com");
+        assert!(matches!(DeeplClient::new("  "), Err(DeeplError::MissingApiKey)));
+    }
+}
@@ -0,0 +1,200 @@
+//! 話者分離
+//!
+//! 元音声を外部の話者分離コマンド（pyannote等）にかけてRTTMを得て、
+//! 字幕セグメントに話者ラベルを付け、話者ごとに別のVOICEVOX話者を割り当てる。
+//! インタビューなど複数人の動画を複数の声で吹き替えるために使う。
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::acp::subtitle_parser::SubtitleSegment;
+use crate::process_env;
+
+/// 話者分離エラー
+#[derive(Debug, Error)]
+pub enum DiarizationError {
+    #[error("Diarization command not found: {0}")]
+    NotFound(String),
+
+    #[error("Diarization failed: {0}")]
+    Failed(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 話者分離の設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiarizationConfig {
+    /// 実行するコマンド（RTTMを出力するもの）
+    #[serde(default = "default_command")]
+    pub command: String,
+    /// 引数（`{audio}` は入力音声、`{output}` は出力RTTMのパスに置換）
+    #[serde(default = "default_args")]
+    pub args: Vec<String>,
+    /// 自動で割り当てるVOICEVOX話者ID（登場順に使い、足りなければ繰り返す）
+    #[serde(default = "default_voices")]
+    pub voices: Vec<i32>,
+    /// 話者ラベルごとの固定の割り当て
+    #[serde(default)]
+    pub speakers: BTreeMap<String, i32>,
+}
+
+fn default_command() -> String {
+    "pyannote-diarize".to_string()
+}
+
+fn default_args() -> Vec<String> {
+    vec!["{audio}".to_string(), "--output".to_string(), "{output}".to_string()]
+}
+
+fn default_voices() -> Vec<i32> {
+    // 四国めたん、ずんだもん、春日部つむぎ、青山龍星
+    vec![2, 3, 8, 13]
+}
+
+impl Default for DiarizationConfig {
+    fn default() -> Self {
+        Self {
+            command: default_command(),
+            args: default_args(),
+            voices: default_voices(),
+            speakers: BTreeMap::new(),
+        }
+    }
+}
+
+/// 話者の発話区間
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeakerTurn {
+    pub speaker: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+impl DiarizationConfig {
+    /// 話者分離を実行してRTTMを読み込む（ブロッキング）
+    pub fn run(&self, audio: &str, rttm_path: &str) -> Result<Vec<SpeakerTurn>, DiarizationError> {
+        let args: Vec<String> = self
+            .args
+            .iter()
+            .map(|a| a.replace("{audio}", audio).replace("{output}", rttm_path))
+            .collect();
+
+        crate::log::info("Diarization", &format!("{} {}", self.command, args.join(" ")));
+        let output = process_env::command(&self.command)
+            .args(&args)
+            .output()
+            .map_err(|e| DiarizationError::NotFound(format!("{}: {}", self.command, e)))?;
+        if !output.status.success() {
+            return Err(DiarizationError::Failed(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+        }
+
+        Ok(parse_rttm(&std::fs::read_to_string(rttm_path)?))
+    }
+
+    /// 話者ラベルにVOICEVOX話者を割り当てる
+    pub fn assign_voices(&self, segments: &[SubtitleSegment]) -> BTreeMap<String, i32> {
+        let mut assigned = BTreeMap::new();
+        let mut next = 0;
+        for label in segments.iter().filter_map(|s| s.speaker.as_ref()) {
+            if assigned.contains_key(label) {
+                continue;
+            }
+            let voice = match self.speakers.get(label) {
+                Some(voice) => *voice,
+                None if self.voices.is_empty() => continue,
+                None => {
+                    let voice = self.voices[next % self.voices.len()];
+                    next += 1;
+                    voice
+                }
+            };
+            assigned.insert(label.clone(), voice);
+        }
+        assigned
+    }
+}
+
+/// RTTM（`SPEAKER <file> <ch> <start> <dur> <NA> <NA> <label> ...`）を読み込む
+pub fn parse_rttm(text: &str) -> Vec<SpeakerTurn> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 8 || fields[0] != "SPEAKER" {
+                return None;
+            }
+            let start: f64 = fields[3].parse().ok()?;
+            let duration: f64 = fields[4].parse().ok()?;
+            Some(SpeakerTurn {
+                speaker: fields[7].to_string(),
+                start_ms: (start * 1000.0).round() as u64,
+                end_ms: ((start + duration) * 1000.0).round() as u64,
+            })
+        })
+        .collect()
+}
+
+/// 各セグメントに最も長く重なる話者のラベルを付ける
+pub fn label_segments(segments: &mut [SubtitleSegment], turns: &[SpeakerTurn]) {
+    for segment in segments.iter_mut() {
+        let mut overlaps: BTreeMap<&str, u64> = BTreeMap::new();
+        for turn in turns {
+            let start = segment.start_ms.max(turn.start_ms);
+            let end = segment.end_ms.min(turn.end_ms);
+            if end > start {
+                *overlaps.entry(turn.speaker.as_str()).or_default() += end - start;
+            }
+        }
+        segment.speaker = overlaps
+            .into_iter()
+            .max_by_key(|(_, overlap)| *overlap)
+            .map(|(speaker, _)| speaker.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rttm_and_label() {
+        let rttm = "SPEAKER audio 1 0.000 2.500 <NA> <NA> SPEAKER_00 <NA> <NA>\n\
+                    SPEAKER audio 1 2.500 3.000 <NA> <NA> SPEAKER_01 <NA> <NA>\n";
+        let turns = parse_rttm(rttm);
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[1].end_ms, 5500);
+
+        let mut segments = vec![
+            SubtitleSegment::new(1, 0, 2000, "Hi".to_string()),
+            SubtitleSegment::new(2, 2000, 5000, "Hello".to_string()),
+            SubtitleSegment::new(3, 9000, 9500, "...".to_string()),
+        ];
+        label_segments(&mut segments, &turns);
+        assert_eq!(segments[0].speaker.as_deref(), Some("SPEAKER_00"));
+        assert_eq!(segments[1].speaker.as_deref(), Some("SPEAKER_01"));
+        assert_eq!(segments[2].speaker, None);
+    }
+
+    #[test]
+    fn test_assign_voices() {
+        let config = DiarizationConfig {
+            voices: vec![2, 3],
+            speakers: [("SPEAKER_02".to_string(), 13)].into(),
+            ..Default::default()
+        };
+        let mut segments: Vec<SubtitleSegment> = (0..4)
+            .map(|i| SubtitleSegment::new(i, 0, 1, String::new()))
+            .collect();
+        for (segment, label) in segments.iter_mut().zip(["SPEAKER_01", "SPEAKER_02", "SPEAKER_00", "SPEAKER_01"]) {
+            segment.speaker = Some(label.to_string());
+        }
+
+        let voices = config.assign_voices(&segments);
+        assert_eq!(voices["SPEAKER_01"], 2);
+        assert_eq!(voices["SPEAKER_02"], 13);
+        assert_eq!(voices["SPEAKER_00"], 3);
+    }
+}
@@ -0,0 +1,181 @@
+//! イベントバス
+//!
+//! フロントエンドに送るTauriイベントをチャンネルごとに直近N件だけ記録する。
+//! 後からマウントした画面やリロードした画面は `get_event_history` で
+//! 取りこぼしたイベントを取得して状態を復元できる。
+
+use std::collections::{HashMap, VecDeque};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// チャンネルごとに保持するイベント数
+const DEFAULT_CHANNEL_CAPACITY: usize = 500;
+
+/// 記録したイベント
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventRecord {
+    /// 全チャンネル共通の連番（`since` で続きを取得する）
+    pub seq: u64,
+    /// イベント名（例: `pipeline:progress`）
+    pub channel: String,
+    /// 記録時刻（UNIXミリ秒）
+    pub timestamp: i64,
+    pub payload: Value,
+}
+
+/// チャンネルごとのリングバッファ
+pub struct EventBus {
+    channels: HashMap<String, VecDeque<EventRecord>>,
+    capacity: usize,
+    next_seq: u64,
+}
+
+impl EventBus {
+    /// イベントバスを作成
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            channels: HashMap::new(),
+            capacity: capacity.max(1),
+            next_seq: 1,
+        }
+    }
+
+    /// イベントを記録し、連番を返す
+    pub fn record(&mut self, channel: &str, payload: Value) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        let buffer = self.channels.entry(channel.to_string()).or_default();
+        buffer.push_back(EventRecord {
+            seq,
+            channel: channel.to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            payload,
+        });
+        while buffer.len() > self.capacity {
+            buffer.pop_front();
+        }
+        seq
+    }
+
+    /// `since` より後のイベント（連番順）
+    ///
+    /// `channel` が `tmux:*` のように `*` で終わる場合は前方一致、
+    /// 指定しなければ全チャンネル。
+    pub fn history(&self, channel: Option<&str>, since: Option<u64>) -> Vec<EventRecord> {
+        let since = since.unwrap_or(0);
+        let mut records: Vec<EventRecord> = self
+            .channels
+            .iter()
+            .filter(|(name, _)| channel.map_or(true, |c| channel_matches(c, name)))
+            .flat_map(|(_, buffer)| buffer.iter().filter(|r| r.seq > since).cloned())
+            .collect();
+        records.sort_by_key(|r| r.seq);
+        records
+    }
+
+    /// 記録しているチャンネル名
+    pub fn channels(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.channels.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// 最後に記録したイベントの連番（まだなければ0）
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHANNEL_CAPACITY)
+    }
+}
+
+fn channel_matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    }
+}
+
+// グローバルイベントバス
+lazy_static::lazy_static! {
+    static ref GLOBAL_EVENT_BUS: Mutex<EventBus> = Mutex::new(EventBus::default());
+}
+
+/// イベントを記録する
+pub fn record<S: Serialize>(channel: &str, payload: &S) -> u64 {
+    let payload = serde_json::to_value(payload).unwrap_or(Value::Null);
+    GLOBAL_EVENT_BUS.lock().record(channel, payload)
+}
+
+/// 記録済みのイベント（連番順）
+pub fn history(channel: Option<&str>, since: Option<u64>) -> Vec<EventRecord> {
+    GLOBAL_EVENT_BUS.lock().history(channel, since)
+}
+
+/// 記録しているチャンネル名
+pub fn channels() -> Vec<String> {
+    GLOBAL_EVENT_BUS.lock().channels()
+}
+
+/// 最後に記録したイベントの連番
+pub fn last_seq() -> u64 {
+    GLOBAL_EVENT_BUS.lock().last_seq()
+}
+
+/// イベントバスに記録してからフロントエンドに送信する
+pub trait RecordedEmit {
+    fn emit_recorded<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()>;
+}
+
+impl<R: Runtime> RecordedEmit for AppHandle<R> {
+    fn emit_recorded<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()> {
+        record(event, &payload);
+        self.emit(event, payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_since_and_channel() {
+        let mut bus = EventBus::new(10);
+        bus.record("pipeline:progress", serde_json::json!({ "stage": "download" }));
+        let seq = bus.record("tmux:status_changed", serde_json::json!("idle"));
+        bus.record("tmux:question", serde_json::json!("y/n"));
+        bus.record("pipeline:progress", serde_json::json!({ "stage": "translate" }));
+
+        assert_eq!(bus.history(None, None).len(), 4);
+        assert_eq!(bus.history(Some("pipeline:progress"), None).len(), 2);
+
+        let tmux = bus.history(Some("tmux:*"), None);
+        assert_eq!(tmux.iter().map(|r| r.channel.as_str()).collect::<Vec<_>>(), ["tmux:status_changed", "tmux:question"]);
+
+        let after = bus.history(None, Some(seq));
+        assert_eq!(after.iter().map(|r| r.seq).collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(bus.last_seq(), 4);
+    }
+
+    #[test]
+    fn test_capacity_per_channel() {
+        let mut bus = EventBus::new(2);
+        for i in 0..5 {
+            bus.record("pty-output", serde_json::json!(i));
+        }
+        bus.record("pty-prompt", Value::Null);
+
+        let output = bus.history(Some("pty-output"), None);
+        assert_eq!(output.iter().map(|r| r.payload.clone()).collect::<Vec<_>>(), [serde_json::json!(3), serde_json::json!(4)]);
+        assert_eq!(bus.history(Some("pty-prompt"), None).len(), 1);
+        assert_eq!(bus.channels(), ["pty-output", "pty-prompt"]);
+    }
+}
@@ -0,0 +1,376 @@
+//! 吹替動画の書き出し
+//!
+//! パイプラインの出力（吹替トラック `dub.<lang>.wav`・翻訳済み字幕）と元動画から
+//! 最終的なMP4を作る。元動画が未取得ならyt-dlpでダウンロードする。
+//! チャプターが分かる場合はチャプターごとに吹替トラック・字幕・動画を書き出せる。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::acp::runner::ExecutionContext;
+use crate::acp::subtitle_parser::VttParser;
+use crate::chapters::{self, Chapter};
+use crate::ffmpeg::{Ffmpeg, FfmpegError};
+use crate::mux::{self, MuxOptions};
+use crate::timeline::{PcmAudio, TimelineError};
+use crate::youtube::{YoutubeDownloader, YtdlpCookies};
+
+/// 書き出しエラー
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Missing input: {0}")]
+    MissingInput(String),
+
+    #[error("Video download failed: {0}")]
+    Download(String),
+
+    #[error("No chapters found")]
+    NoChapters,
+
+    #[error("Subtitle error: {0}")]
+    Subtitle(String),
+
+    #[error(transparent)]
+    Ffmpeg(#[from] FfmpegError),
+
+    #[error(transparent)]
+    Audio(#[from] TimelineError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 書き出しオプション
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportOptions {
+    /// 多重化オプション（元音声の扱いなど）
+    #[serde(flatten)]
+    pub mux: MuxOptions,
+    /// 書き出す言語（省略時は実行時の翻訳先）
+    #[serde(default)]
+    pub target_lang: Option<String>,
+    /// 翻訳済み字幕をソフトサブとして含める
+    #[serde(default)]
+    pub include_subtitles: bool,
+    /// 元動画のパス（省略時はYouTubeからダウンロード）
+    #[serde(default)]
+    pub video_path: Option<String>,
+    /// 出力先（省略時は `<output_dir>/dubbed.<lang>.mp4`）
+    #[serde(default)]
+    pub output_path: Option<String>,
+    /// 元動画・チャプターをyt-dlpで取得するときのcookie
+    #[serde(default)]
+    pub cookies: YtdlpCookies,
+}
+
+/// チャプター分割の書き出しオプション
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChapterExportOptions {
+    #[serde(flatten)]
+    pub export: ExportOptions,
+    /// チャプターごとの動画も書き出す（省略時は音声と字幕のみ）
+    #[serde(default)]
+    pub include_video: bool,
+}
+
+/// チャプターごとの出力
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChapterOutput {
+    pub chapter: Chapter,
+    /// 出力ディレクトリ
+    pub dir: String,
+    pub audio_path: String,
+    pub subtitle_path: Option<String>,
+    pub video_path: Option<String>,
+}
+
+/// 書き出しの進捗（`export:progress` イベント）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportProgress {
+    pub execution_id: String,
+    /// フェーズ（"download" / "mux" / "done"）
+    pub phase: String,
+    /// フェーズ内の進捗率
+    pub percent: u8,
+    pub message: String,
+}
+
+/// 吹替動画を書き出す（ブロッキング）。出力ファイルのパスを返す
+pub fn export_dubbed_video<F>(
+    context: &ExecutionContext,
+    options: &ExportOptions,
+    mut on_progress: F,
+) -> Result<String, ExportError>
+where
+    F: FnMut(ExportProgress),
+{
+    let mut emit = |phase: &str, percent: u8, message: String| {
+        on_progress(ExportProgress {
+            execution_id: context.execution_id.clone(),
+            phase: phase.to_string(),
+            percent,
+            message,
+        });
+    };
+
+    let source = ExportSource::resolve(context, options)?;
+
+    let ffmpeg = Ffmpeg::new();
+    ffmpeg.check_available()?;
+    let video_path = source.video(context, options, &mut emit)?;
+
+    // 多重化
+    let output_path = options.output_path.clone()
+        .unwrap_or_else(|| format!("{}/dubbed.{}.mp4", source.output_dir, source.target_lang));
+    let command = mux::build_mux_command(&video_path, &source.dub_path, source.subtitles.as_deref(), &options.mux, &output_path);
+    let total_ms = ffmpeg.probe_duration_ms(&video_path).ok();
+
+    let label = if options.mux.burn_in.is_some() {
+        "字幕を焼き込んで再エンコード中"
+    } else {
+        "吹替音声を多重化中"
+    };
+    emit("mux", 0, label.to_string());
+    ffmpeg.run(&command, total_ms, |progress| {
+        if let Some(percent) = progress.percent {
+            emit("mux", percent, format!("{} ({}%)", label, percent));
+        }
+    })?;
+
+    crate::log::info("Export", &format!("Dubbed video written: {}", output_path));
+    emit("done", 100, output_path.clone());
+    Ok(output_path)
+}
+
+/// チャプターごとに吹替トラック・字幕（・動画）を書き出す（ブロッキング）
+///
+/// 出力先は `<output_dir>/chapters/<番号>_<タイトル>/`。
+/// `chapters` を省略した場合はyt-dlpで元動画のチャプターを取得する。
+pub fn export_chapters<F>(
+    context: &ExecutionContext,
+    chapters: Option<Vec<Chapter>>,
+    options: &ChapterExportOptions,
+    mut on_progress: F,
+) -> Result<Vec<ChapterOutput>, ExportError>
+where
+    F: FnMut(ExportProgress),
+{
+    let mut emit = |phase: &str, percent: u8, message: String| {
+        on_progress(ExportProgress {
+            execution_id: context.execution_id.clone(),
+            phase: phase.to_string(),
+            percent,
+            message,
+        });
+    };
+
+    let source = ExportSource::resolve(context, &options.export)?;
+    let chapters = match chapters {
+        Some(chapters) => chapters,
+        None => {
+            let url = context.input["youtube_url"].as_str()
+                .ok_or_else(|| ExportError::MissingInput("chapters or youtube_url".to_string()))?;
+            YoutubeDownloader::new()
+                .with_cookies(options.export.cookies.clone())
+                .fetch_chapters(url)
+                .map_err(|e| ExportError::Download(e.to_string()))?
+        }
+    };
+    if chapters.is_empty() {
+        return Err(ExportError::NoChapters);
+    }
+
+    let dub = PcmAudio::read_wav(Path::new(&source.dub_path))?;
+    let segments = match source.subtitles {
+        Some(ref path) => Some(VttParser::parse_file(path).map_err(|e| ExportError::Subtitle(e.to_string()))?),
+        None => None,
+    };
+
+    let ffmpeg = Ffmpeg::new();
+    let video_path = if options.include_video {
+        ffmpeg.check_available()?;
+        Some(source.video(context, &options.export, &mut emit)?)
+    } else {
+        None
+    };
+
+    let mut outputs = Vec::new();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let percent = (i * 100 / chapters.len()) as u8;
+        emit("chapter", percent, format!("チャプター {}/{}: {}", i + 1, chapters.len(), chapter.title));
+
+        let dir = Path::new(&source.output_dir).join("chapters").join(chapter.dir_name(i + 1));
+        std::fs::create_dir_all(&dir)?;
+
+        let audio_path = dir.join(format!("dub.{}.wav", source.target_lang)).to_string_lossy().to_string();
+        chapters::slice_audio(&dub, chapter).write_wav(Path::new(&audio_path))?;
+
+        let subtitle_path = match segments {
+            Some(ref segments) => {
+                let path = dir.join(format!("translated.{}.vtt", source.target_lang)).to_string_lossy().to_string();
+                std::fs::write(&path, VttParser::rebuild_vtt(&chapters::slice_segments(segments, chapter), &[]))?;
+                Some(path)
+            }
+            None => None,
+        };
+
+        let chapter_video = match video_path {
+            Some(ref video) => {
+                let path = dir.join(format!("dubbed.{}.mp4", source.target_lang)).to_string_lossy().to_string();
+                let command = mux::build_chapter_mux_command(
+                    video,
+                    chapter,
+                    &audio_path,
+                    subtitle_path.as_deref(),
+                    &options.export.mux,
+                    &path,
+                );
+                ffmpeg.run(&command, Some(chapter.end_ms.saturating_sub(chapter.start_ms)), |_| {})?;
+                Some(path)
+            }
+            None => None,
+        };
+
+        outputs.push(ChapterOutput {
+            chapter: chapter.clone(),
+            dir: dir.to_string_lossy().to_string(),
+            audio_path,
+            subtitle_path,
+            video_path: chapter_video,
+        });
+    }
+
+    crate::log::info("Export", &format!("Exported {} chapters", outputs.len()));
+    emit("done", 100, format!("{} チャプターを書き出しました", outputs.len()));
+    Ok(outputs)
+}
+
+/// 書き出しに使うパイプラインの出力
+struct ExportSource {
+    output_dir: String,
+    target_lang: String,
+    dub_path: String,
+    subtitles: Option<String>,
+}
+
+impl ExportSource {
+    fn resolve(context: &ExecutionContext, options: &ExportOptions) -> Result<Self, ExportError> {
+        let input = &context.input;
+        let output_dir = input["output_dir"].as_str()
+            .ok_or_else(|| ExportError::MissingInput("output_dir".to_string()))?;
+        let target_lang = options.target_lang.as_deref()
+            .or(input["target_lang"].as_str())
+            .unwrap_or("ja");
+
+        let dub_path = format!("{}/dub.{}.wav", output_dir, target_lang);
+        if !Path::new(&dub_path).exists() {
+            return Err(ExportError::MissingInput(format!("dub track {}", dub_path)));
+        }
+
+        // 焼き込みを指定した場合は字幕を含める
+        let subtitles = if options.include_subtitles || options.mux.burn_in.is_some() {
+            let path = format!("{}/translated.{}.vtt", output_dir, target_lang);
+            if !Path::new(&path).exists() {
+                return Err(ExportError::MissingInput(format!("subtitles {}", path)));
+            }
+            Some(path)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            output_dir: output_dir.to_string(),
+            target_lang: target_lang.to_string(),
+            dub_path,
+            subtitles,
+        })
+    }
+
+    /// 元動画のパス（未取得ならダウンロード）
+    fn video<F>(&self, context: &ExecutionContext, options: &ExportOptions, emit: &mut F) -> Result<String, ExportError>
+    where
+        F: FnMut(&str, u8, String),
+    {
+        let video_path = match options.video_path {
+            Some(ref path) => path.clone(),
+            None => {
+                let url = context.input["youtube_url"].as_str()
+                    .ok_or_else(|| ExportError::MissingInput("video_path or youtube_url".to_string()))?;
+                emit("download", 0, "元動画をダウンロード中".to_string());
+                YoutubeDownloader::new()
+                    .with_cookies(options.cookies.clone())
+                    .download_video(url, &self.output_dir, |percent| {
+                        emit("download", percent as u8, format!("元動画をダウンロード中 ({:.1}%)", percent));
+                    })
+                    .map_err(|e| ExportError::Download(e.to_string()))?
+            }
+        };
+        emit("download", 100, format!("元動画: {}", video_path));
+        Ok(video_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_flatten_mux() {
+        let options: ExportOptions = serde_json::from_str(
+            r#"{"original_audio":{"mode":"duck"},"include_subtitles":true,"target_lang":"en"}"#,
+        ).unwrap();
+        assert!(matches!(options.mux.original_audio, mux::OriginalAudio::Duck(_)));
+        assert_eq!(options.mux.audio_bitrate, "192k");
+        assert!(options.include_subtitles);
+        assert_eq!(options.target_lang.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_export_chapter_audio_and_subtitles() {
+        let dir = std::env::temp_dir().join(format!("re-voice-export-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        PcmAudio { sample_rate: 1000, samples: vec![1; 4000] }
+            .write_wav(&dir.join("dub.ja.wav"))
+            .unwrap();
+        std::fs::write(
+            dir.join("translated.ja.vtt"),
+            "WEBVTT\n\n00:00:00.500 --> 00:00:01.500\nこんにちは\n\n00:00:02.500 --> 00:00:03.000\nさようなら\n",
+        ).unwrap();
+
+        let context = ExecutionContext::new(
+            "pipeline",
+            "exec",
+            serde_json::json!({ "output_dir": dir.to_string_lossy(), "target_lang": "ja" }),
+        );
+        let chapters = vec![
+            Chapter { title: "Intro".to_string(), start_ms: 0, end_ms: 2000 },
+            Chapter { title: "Outro".to_string(), start_ms: 2000, end_ms: 4000 },
+        ];
+        let options = ChapterExportOptions {
+            export: ExportOptions { include_subtitles: true, ..Default::default() },
+            include_video: false,
+        };
+        let outputs = export_chapters(&context, Some(chapters), &options, |_| {}).unwrap();
+
+        assert_eq!(outputs.len(), 2);
+        let audio = PcmAudio::read_wav(Path::new(&outputs[1].audio_path)).unwrap();
+        assert_eq!(audio.duration_ms(), 2000);
+        let vtt = std::fs::read_to_string(outputs[1].subtitle_path.as_ref().unwrap()).unwrap();
+        assert!(vtt.contains("00:00:00.500 --> 00:00:01.000\nさようなら"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_missing_dub_track() {
+        let dir = std::env::temp_dir().join(format!("re-voice-export-{}", uuid::Uuid::new_v4()));
+        let context = ExecutionContext::new(
+            "pipeline",
+            "exec",
+            serde_json::json!({ "output_dir": dir.to_string_lossy(), "target_lang": "ja" }),
+        );
+        let result = export_dubbed_video(&context, &ExportOptions::default(), |_| {});
+        assert!(matches!(result, Err(ExportError::MissingInput(_))));
+    }
+}
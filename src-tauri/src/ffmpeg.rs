@@ -0,0 +1,351 @@
+//! ffmpeg連携
+//!
+//! ffmpeg/ffprobeの存在確認、型付きのコマンドビルダー、`-progress` 出力の解析を行う。
+//! 音声トラックの組み立てや動画への多重化などのステージはこのモジュール経由でffmpegを呼ぶ。
+
+use std::io::{BufRead, BufReader, Read};
+use std::process::Stdio;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::process_env;
+
+/// ffmpegエラー
+#[derive(Debug, Error)]
+pub enum FfmpegError {
+    #[error("ffmpeg is not installed: {0}")]
+    NotFound(String),
+
+    #[error("ffmpeg failed (exit code {code:?}): {stderr}")]
+    Failed { code: Option<i32>, stderr: String },
+
+    #[error("Invalid ffprobe output: {0}")]
+    InvalidOutput(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 進捗（`-progress pipe:1` の1ブロック分）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FfmpegProgress {
+    /// 出力済みの時間（ミリ秒）
+    pub out_time_ms: u64,
+    /// 処理速度（等倍=1.0）
+    pub speed: Option<f64>,
+    /// 進捗率（全体の長さが分かる場合）
+    pub percent: Option<u8>,
+    /// 完了したか
+    pub done: bool,
+}
+
+/// `-progress` 出力のパーサー
+///
+/// `key=value` の行を受け取り、`progress=continue|end` でブロックが終わるたびに進捗を返す。
+#[derive(Debug, Default)]
+pub struct ProgressParser {
+    total_ms: Option<u64>,
+    current: FfmpegProgress,
+}
+
+impl ProgressParser {
+    /// 全体の長さ（ミリ秒、分かれば）を指定して作成
+    pub fn new(total_ms: Option<u64>) -> Self {
+        Self {
+            total_ms: total_ms.filter(|t| *t > 0),
+            current: FfmpegProgress::default(),
+        }
+    }
+
+    /// 1行を処理する
+    pub fn feed(&mut self, line: &str) -> Option<FfmpegProgress> {
+        let (key, value) = line.trim().split_once('=')?;
+        match key {
+            // out_time_ms も実際はマイクロ秒
+            "out_time_us" | "out_time_ms" => {
+                if let Ok(us) = value.parse::<u64>() {
+                    self.current.out_time_ms = us / 1000;
+                }
+            }
+            "speed" => {
+                self.current.speed = value.trim_end_matches('x').trim().parse().ok();
+            }
+            "progress" => {
+                self.current.done = value == "end";
+                self.current.percent = if self.current.done {
+                    Some(100)
+                } else {
+                    self.total_ms.map(|total| (self.current.out_time_ms * 100 / total).min(99) as u8)
+                };
+                return Some(self.current.clone());
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// ffmpegコマンドビルダー
+#[derive(Debug, Clone, Default)]
+pub struct FfmpegCommand {
+    inputs: Vec<(Vec<String>, String)>,
+    filter_complex: Option<String>,
+    output_args: Vec<String>,
+    output: Option<String>,
+}
+
+impl FfmpegCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 入力ファイルを追加
+    pub fn input(self, path: &str) -> Self {
+        self.input_with_args(&[], path)
+    }
+
+    /// 入力オプション付きで入力ファイルを追加（`-f lavfi` など）
+    pub fn input_with_args(mut self, args: &[&str], path: &str) -> Self {
+        self.inputs.push((args.iter().map(|a| a.to_string()).collect(), path.to_string()));
+        self
+    }
+
+    /// `-filter_complex` を設定
+    pub fn filter_complex(mut self, graph: &str) -> Self {
+        self.filter_complex = Some(graph.to_string());
+        self
+    }
+
+    /// `-map` を追加
+    pub fn map(self, stream: &str) -> Self {
+        self.arg("-map").arg(stream)
+    }
+
+    /// 音声コーデック
+    pub fn audio_codec(self, codec: &str) -> Self {
+        self.arg("-c:a").arg(codec)
+    }
+
+    /// 映像コーデック
+    pub fn video_codec(self, codec: &str) -> Self {
+        self.arg("-c:v").arg(codec)
+    }
+
+    /// 出力オプションを追加
+    pub fn arg(mut self, arg: &str) -> Self {
+        self.output_args.push(arg.to_string());
+        self
+    }
+
+    /// 出力ファイル
+    pub fn output(mut self, path: &str) -> Self {
+        self.output = Some(path.to_string());
+        self
+    }
+
+    /// ffmpegに渡す引数（上書き・進捗出力を含む）
+    pub fn build_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = ["-hide_banner", "-nostdin", "-y", "-loglevel", "error", "-progress", "pipe:1", "-nostats"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        for (input_args, path) in &self.inputs {
+            args.extend(input_args.iter().cloned());
+            args.push("-i".to_string());
+            args.push(path.clone());
+        }
+        if let Some(ref graph) = self.filter_complex {
+            args.push("-filter_complex".to_string());
+            args.push(graph.clone());
+        }
+        args.extend(self.output_args.iter().cloned());
+        if let Some(ref output) = self.output {
+            args.push(output.clone());
+        }
+        args
+    }
+}
+
+/// テンポを変える `atempo` フィルター（1段あたり0.5〜2.0のため範囲外は連結する）
+pub fn atempo_filter(tempo: f64) -> String {
+    let mut remaining = tempo;
+    let mut stages = Vec::new();
+    while remaining > 2.0 {
+        stages.push("atempo=2.0".to_string());
+        remaining /= 2.0;
+    }
+    while remaining < 0.5 {
+        stages.push("atempo=0.5".to_string());
+        remaining /= 0.5;
+    }
+    stages.push(format!("atempo={:.4}", remaining));
+    stages.join(",")
+}
+
+/// ffmpeg/ffprobe
+pub struct Ffmpeg {
+    ffmpeg_path: String,
+    ffprobe_path: String,
+}
+
+impl Ffmpeg {
+    pub fn new() -> Self {
+        Self::with_paths("ffmpeg", "ffprobe")
+    }
+
+    /// パスを指定して作成
+    pub fn with_paths(ffmpeg_path: &str, ffprobe_path: &str) -> Self {
+        Self {
+            ffmpeg_path: ffmpeg_path.to_string(),
+            ffprobe_path: ffprobe_path.to_string(),
+        }
+    }
+
+    /// ffmpegがインストールされているか確認し、バージョン行を返す
+    pub fn check_available(&self) -> Result<String, FfmpegError> {
+        let output = process_env::command(&self.ffmpeg_path)
+            .arg("-version")
+            .output()
+            .map_err(|e| FfmpegError::NotFound(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(FfmpegError::NotFound(format!("{} -version failed", self.ffmpeg_path)));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// メディアの長さ（ミリ秒）
+    pub fn probe_duration_ms(&self, path: &str) -> Result<u64, FfmpegError> {
+        let output = process_env::command(&self.ffprobe_path)
+            .args(["-v", "error", "-show_entries", "format=duration", "-of", "default=nw=1:nk=1", path])
+            .output()
+            .map_err(|e| FfmpegError::NotFound(e.to_string()))?;
+
+        if !output.status.success() {
+            return Err(FfmpegError::Failed {
+                code: output.status.code(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let seconds: f64 = text.parse().map_err(|_| FfmpegError::InvalidOutput(text.clone()))?;
+        Ok((seconds * 1000.0).round() as u64)
+    }
+
+    /// 音声のピッチを保ったまま再生速度を変える（`tempo` > 1.0 で短くなる）
+    pub fn time_stretch(&self, input: &str, output: &str, tempo: f64) -> Result<(), FfmpegError> {
+        let command = FfmpegCommand::new()
+            .input(input)
+            .arg("-filter:a")
+            .arg(&atempo_filter(tempo))
+            .output(output);
+        self.run(&command, None, |_| {})
+    }
+
+    /// コマンドを実行し、進捗ブロックごとにコールバックを呼ぶ（ブロッキング）
+    pub fn run<F>(&self, command: &FfmpegCommand, total_ms: Option<u64>, mut on_progress: F) -> Result<(), FfmpegError>
+    where
+        F: FnMut(&FfmpegProgress),
+    {
+        let args = command.build_args();
+        crate::log::info("Ffmpeg", &format!("{} {}", self.ffmpeg_path, args.join(" ")));
+
+        let mut child = process_env::command(&self.ffmpeg_path)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| FfmpegError::NotFound(e.to_string()))?;
+
+        // stderrはパイプが詰まらないよう別スレッドで読む
+        let mut stderr_pipe = child.stderr.take();
+        let stderr_reader = std::thread::spawn(move || {
+            let mut buf = String::new();
+            if let Some(ref mut pipe) = stderr_pipe {
+                let _ = pipe.read_to_string(&mut buf);
+            }
+            buf
+        });
+
+        let mut parser = ProgressParser::new(total_ms);
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some(progress) = parser.feed(&line) {
+                    on_progress(&progress);
+                }
+            }
+        }
+
+        let status = child.wait()?;
+        let stderr = stderr_reader.join().unwrap_or_default();
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(FfmpegError::Failed {
+                code: status.code(),
+                stderr: stderr.trim().to_string(),
+            })
+        }
+    }
+}
+
+impl Default for Ffmpeg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_args() {
+        let args = FfmpegCommand::new()
+            .input("video.mp4")
+            .input_with_args(&["-f", "lavfi"], "anullsrc")
+            .filter_complex("[0:a][1:a]amix=inputs=2[a]")
+            .map("[a]")
+            .audio_codec("aac")
+            .output("out.mp4")
+            .build_args();
+
+        let joined = args.join(" ");
+        assert!(joined.starts_with("-hide_banner -nostdin -y"));
+        assert!(joined.contains("-progress pipe:1"));
+        assert!(joined.contains("-i video.mp4 -f lavfi -i anullsrc -filter_complex [0:a][1:a]amix=inputs=2[a] -map [a] -c:a aac out.mp4"));
+    }
+
+    #[test]
+    fn test_atempo_filter() {
+        assert_eq!(atempo_filter(1.25), "atempo=1.2500");
+        assert_eq!(atempo_filter(3.0), "atempo=2.0,atempo=1.5000");
+        assert_eq!(atempo_filter(0.4), "atempo=0.5,atempo=0.8000");
+    }
+
+    #[test]
+    fn test_progress_parser() {
+        let mut parser = ProgressParser::new(Some(10_000));
+        assert!(parser.feed("out_time_us=2500000").is_none());
+        assert!(parser.feed("speed=2.5x").is_none());
+
+        let progress = parser.feed("progress=continue").unwrap();
+        assert_eq!(progress.out_time_ms, 2500);
+        assert_eq!(progress.percent, Some(25));
+        assert_eq!(progress.speed, Some(2.5));
+
+        let end = parser.feed("progress=end").unwrap();
+        assert!(end.done);
+        assert_eq!(end.percent, Some(100));
+    }
+}
@@ -1,42 +1,119 @@
 mod acp;
+mod appdata;
+mod audio;
+mod chapters;
+mod deepl;
+mod diarization;
+mod events;
+mod export;
+mod ffmpeg;
+mod live;
 mod log;
+mod mux;
+mod output_log;
+mod platform;
+mod preflight;
+mod process_env;
 mod pty;
+mod secrets;
+mod settings;
+mod timeline;
+mod transcript;
+mod tts;
 mod voicevox;
+mod watcher;
+mod whisper;
 mod youtube;
 
 use chrono;
 use parking_lot::Mutex;
-use pty::{PtyEvent, PtyManager};
+use pty::{AutoResponseRecord, PtyEvent, PtyExit, PtyManager, PtyWatchdog};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager, State};
+use tauri::{AppHandle, Manager, State};
 use tokio::sync::RwLock;
 
 use acp::{
-    AgentCard, AgentOrchestrator, DiscoveryQuery, OrchestratorStats, SharedContext, TaskState,
-    Transport, StatusPoller, PollerConfig, CapabilityFilter,
+    AgentCard, AgentOrchestrator, CorrelatedReply, DiscoveryQuery, OrchestratorStats, SharedContext, TaskState,
+    ContextItem, ContextValue, DEFAULT_WORKSPACE, execution_workspace,
+    Skill, Transport, StatusPoller, PollerConfig, CapabilityFilter,
     PipelineDefinition, PipelineExecution, PipelineExecutor, PipelineStage, AgentAddress,
     AskToolHandler, HumanAnswer, ParsedQuestion,
-    ClaudeCodeExecutor, ExecutorOptions, AgentState,
+    ClaudeCodeExecutor, ExecutorOptions, AgentState, InvalidTransitionPolicy, StateTransition, UsageTotals,
 };
-use acp::permission::PermissionDecision;
+use acp::executor::{ExecutorCanceller, ExecutorSession, ExecutorSnapshot, EXECUTOR_STATE_FILE};
+use acp::permission::{
+    PermissionDecision, PermissionRequest, PermissionResponder, PermissionRule, PolicyStore, RuleAction, POLICY_FILE,
+};
+use acp::discovery::fetch_agent_card;
+use acp::server::{AgentServer, InboundTask, TaskHandler, DEFAULT_SERVER_ADDR};
+use acp::registry::{validate_card, CardDirectory, CardWatcher, RegistryStore, AGENTS_DIR, REGISTRY_FILE};
+use acp::transport::{Subscribers, LOCAL_SENDER};
+use acp::transport::executor::ExecutorHandle;
+use acp::transport::pty::PtyHandle;
+use acp::transport::stdio::{StdioCommand, StdioTransport};
+use acp::transport::tmux::TmuxPaneHandle;
+use acp::transport::websocket::{WebSocketConfig, WebSocketTransport};
 use acp::tmux::{TmuxOrchestrator, AgentType as TmuxAgentType};
-use acp::runner::{PipelineRunner, ExecutionContext, ProgressPayload};
+use acp::glossary::{Glossary, GlossaryEntry, GLOSSARY_FILE};
+use acp::translation_memory::{MemoryStats, TRANSLATION_MEMORY_FILE};
+use acp::history::{HistoryEntry, MessageHistory, HISTORY_FILE};
+use settings::Settings;
+use log::{LogFilter, LogLevel, LogRecord};
+use acp::pipeline::{PipelineStore, PIPELINES_FILE};
+use acp::review::{ReviewSegment, ReviewSession};
+use acp::translator::Translator;
+use acp::translators::ollama::{OllamaModel, OllamaTranslator};
+use acp::ask::ASK_POLICY_FILE;
+use acp::runner::{BatchVideoStatus, CustomStage, PipelineRunner, ExecutionContext, ProgressPayload, TranslationOptions, BUILTIN_PIPELINE_NAMES, PRESETS_FILE};
 use acp::subtitle_parser::{VttParser, SubtitleSegment};
-use voicevox::{VoicevoxClient, VoicevoxError, Speaker, SynthesisOptions};
-use youtube::{YoutubeDownloader, SubtitleDownloadResult, YoutubeError};
+use appdata::{AppDataBundle, ImportSummary};
+use deepl::{DeeplClient, DeeplUsage};
+use preflight::{DiskSpaceReport, EnvironmentReport};
+use secrets::{SecretStore, DEEPL_API_KEY};
+use voicevox::{AudioQuery, SynthesisManifest, VoicevoxClient, VoicevoxError, Speaker, SynthesisOptions};
+use watcher::{FolderWatcher, WatchConfig};
+use output_log::OutputSlice;
+use events::{EventRecord, RecordedEmit};
+use youtube::{DownloadProgressEvent, MediaFormat, VideoMetadata, YoutubeDownloader, SubtitleDownloadResult, YoutubeError, YtdlpCookies};
 
 /// Application state
 pub struct AppState {
     pty: Arc<Mutex<PtyManager>>,
+    /// レガシーPTYのトランスポート（出力の購読者と応答待ちのリクエストを持つ）
+    pty_transport: Arc<PtyHandle>,
     orchestrator: Arc<Mutex<AgentOrchestrator>>,
     tmux_orchestrator: Arc<Mutex<Option<TmuxOrchestrator>>>,
     status_poller: Arc<Mutex<Option<StatusPoller>>>,
     pipeline_executor: Arc<Mutex<PipelineExecutor>>,
     pipeline_runner: Arc<PipelineRunner>,
-    voicevox_client: Arc<Mutex<VoicevoxClient>>,
+    /// VOICEVOXクライアント（コネクションプールを共有し、同時に呼び出せる。設定変更で作り直す）
+    voicevox_client: Arc<parking_lot::RwLock<VoicevoxClient>>,
     app_handle: Arc<Mutex<Option<AppHandle>>>,
     /// CLI-based Claude Code executor (async-aware)
     cli_executor: Arc<RwLock<Option<ClaudeCodeExecutor>>>,
+    /// エグゼキューターの権限要求への回答先（実行中でもエグゼキューターをロックせずに回答する）
+    permission_responder: PermissionResponder,
+    /// エグゼキューターの中止要求（実行中でもエグゼキューターをロックせずに停止する）
+    executor_canceller: ExecutorCanceller,
+    /// エグゼキューターの最新の状態（起動中のみSome。実行中でもロックせずに読む）
+    executor_state: Arc<Mutex<Option<tokio::sync::watch::Receiver<AgentState>>>>,
+    /// エグゼキューターの権限ルール（`permission_policies.json`）
+    permission_policies: Arc<Mutex<PolicyStore>>,
+    /// エグゼキューターの起動・再開からの使用量（実行中でも読めるよう共有する）
+    executor_usage: Arc<Mutex<UsageTotals>>,
+    /// 監視フォルダ
+    folder_watcher: Arc<Mutex<Option<FolderWatcher>>>,
+    card_watcher: Arc<Mutex<Option<CardWatcher>>>,
+    /// 実行時に登録したエージェントカード（再起動後に復元する）
+    registry_store: Arc<Mutex<RegistryStore>>,
+    /// 自分のエージェントカードを公開するHTTPサーバー（開いていればSome）
+    agent_server: Arc<Mutex<Option<AgentServer>>>,
+    /// PTYの応答監視
+    pty_watchdog: Arc<Mutex<Option<PtyWatchdog>>>,
+    /// 試聴プレイヤー
+    audio_player: Arc<audio::AudioPlayer>,
+    /// ライブ吹替セッション
+    live_manager: Arc<live::LiveManager>,
 }
 
 impl AppState {
@@ -45,26 +122,55 @@ impl AppState {
         let tmux_orchestrator: Arc<Mutex<Option<TmuxOrchestrator>>> = Arc::new(Mutex::new(None));
         let executor = pipeline_executor.clone();
         let cli_executor: Arc<RwLock<Option<ClaudeCodeExecutor>>> = Arc::new(RwLock::new(None));
+        let pty = Arc::new(Mutex::new(PtyManager::new()));
+        let pty_transport = Arc::new(PtyHandle::new(pty.clone(), Arc::new(Subscribers::new())));
+
+        // CLIエグゼキューター・tmux・PTYをPipelineRunnerに注入（ステージのエージェントアドレスで使い分ける）
+        let pipeline_runner = Arc::new(
+            PipelineRunner::with_cli_executor(executor, cli_executor.clone())
+                .with_agent_backends(tmux_orchestrator.clone(), pty_transport.clone()),
+        );
 
-        // CLIエグゼキューターをPipelineRunnerに注入
-        let pipeline_runner = Arc::new(PipelineRunner::with_cli_executor(
-            executor,
-            cli_executor.clone(),
-        ));
+        // スキル指定のステージはオーケストレーターでエージェントを選ぶ
+        let orchestrator = Arc::new(Mutex::new(AgentOrchestrator::new()));
+        pipeline_runner.set_orchestrator(orchestrator.clone());
 
         Self {
-            pty: Arc::new(Mutex::new(PtyManager::new())),
-            orchestrator: Arc::new(Mutex::new(AgentOrchestrator::new())),
+            pty_transport,
+            pty,
+            orchestrator,
             tmux_orchestrator,
             status_poller: Arc::new(Mutex::new(None)),
             pipeline_executor,
             pipeline_runner,
-            voicevox_client: Arc::new(Mutex::new(VoicevoxClient::new())),
+            voicevox_client: Arc::new(parking_lot::RwLock::new(VoicevoxClient::new())),
             app_handle: Arc::new(Mutex::new(None)),
             cli_executor,
+            permission_responder: PermissionResponder::new(),
+            executor_canceller: ExecutorCanceller::new(),
+            executor_state: Arc::new(Mutex::new(None)),
+            permission_policies: Arc::new(Mutex::new(PolicyStore::default())),
+            executor_usage: Arc::new(Mutex::new(UsageTotals::default())),
+            folder_watcher: Arc::new(Mutex::new(None)),
+            card_watcher: Arc::new(Mutex::new(None)),
+            registry_store: Arc::new(Mutex::new(RegistryStore::default())),
+            agent_server: Arc::new(Mutex::new(None)),
+            pty_watchdog: Arc::new(Mutex::new(None)),
+            audio_player: Arc::new(audio::AudioPlayer::new()),
+            live_manager: Arc::new(live::LiveManager::new()),
         }
     }
 
+    /// VOICEVOXクライアント
+    fn voicevox(&self) -> VoicevoxClient {
+        self.voicevox_client.read().clone()
+    }
+
+    /// 設定（VOICEVOXのURLなど）を反映したクライアントに作り直す
+    fn apply_settings(&self) {
+        *self.voicevox_client.write() = VoicevoxClient::new();
+    }
+
     /// AppHandleを設定（初期化時に呼ぶ）
     pub fn set_app_handle(&self, handle: AppHandle) {
         *self.app_handle.lock() = Some(handle.clone());
@@ -84,64 +190,162 @@ impl Default for AppState {
 // Legacy PTY Commands (for backwards compatibility)
 // ============================================================================
 
-/// Claude Codeを起動
-#[tauri::command]
-fn spawn_claude(state: State<AppState>, app_handle: AppHandle) -> Result<String, String> {
-    // AppHandleを保存
-    state.set_app_handle(app_handle.clone());
-
-    let mut pty = state.pty.lock();
-
-    // イベントコールバックを設定
-    let handle = app_handle.clone();
+/// PTYイベントをフロントエンドとトランスポートの購読者に送るコールバックを設定
+fn attach_pty_events(state: &AppState, pty: &mut PtyManager, handle: AppHandle) {
+    let transport = state.pty_transport.clone();
     pty.set_event_callback(move |event| {
-        let now = chrono::Local::now();
-        let ts = now.format("%H:%M:%S%.3f");
+        transport.publish(&event);
 
         match event {
             PtyEvent::Output(text) => {
-                eprintln!("[{}] [PTY OUTPUT EVENT] {} bytes", ts, text.len());
-                eprintln!("[{}] [PTY OUTPUT CONTENT] {:?}", ts, text);
+                log::debug("Pty", &format!("Output {} bytes: {:?}", text.len(), text));
                 // フロントエンドにイベントを送信
-                if let Err(e) = handle.emit("pty-output", &text) {
-                    eprintln!("[{}] [PTY EMIT ERROR] {}", ts, e);
+                if let Err(e) = handle.emit_recorded("pty-output", &text) {
+                    log::error("Pty", &format!("Failed to emit output: {}", e));
                 }
             }
+            PtyEvent::RawOutput(raw) => {
+                let _ = handle.emit_recorded("pty-raw-output", &raw);
+            }
             PtyEvent::Prompt => {
-                eprintln!("[{}] [PTY PROMPT EVENT]", ts);
-                let _ = handle.emit("pty-prompt", ());
+                log::debug("Pty", "Prompt");
+                let _ = handle.emit_recorded("pty-prompt", ());
             }
             PtyEvent::Error(msg) => {
-                eprintln!("[{}] [PTY ERROR EVENT] {}", ts, msg);
-                let _ = handle.emit("pty-error", &msg);
+                log::error("Pty", &msg);
+                let _ = handle.emit_recorded("pty-error", &msg);
             }
             PtyEvent::InputRequired { prompt_type, context } => {
-                eprintln!("[{}] [PTY INPUT REQUIRED EVENT] {:?}", ts, prompt_type);
+                log::info("Pty", &format!("Input required: {:?}", prompt_type));
                 // フロントエンドに入力要求イベントを送信
                 let payload = serde_json::json!({
                     "promptType": prompt_type,
                     "context": context,
                 });
-                let _ = handle.emit("pty-input-required", &payload);
+                let _ = handle.emit_recorded("pty-input-required", &payload);
+            }
+            PtyEvent::AutoResponse(record) => {
+                let _ = handle.emit_recorded("pty-auto-response", &record);
+            }
+            PtyEvent::Timeout(timeout) => {
+                let _ = handle.emit_recorded("pty-timeout", &timeout);
+            }
+            PtyEvent::Exited(exit) => {
+                log::info("Pty", &format!("Exited: {:?}", exit));
+                let _ = handle.emit_recorded("pty-exited", &exit);
             }
         }
     });
+}
+
+/// Claude Codeを起動
+#[tauri::command]
+fn spawn_claude(state: State<AppState>, app_handle: AppHandle) -> Result<String, String> {
+    // AppHandleを保存
+    state.set_app_handle(app_handle.clone());
+
+    let mut pty = state.pty.lock();
+
+    // イベントコールバックを設定
+    attach_pty_events(&state, &mut pty, app_handle);
 
     pty.spawn_claude_code().map_err(|e| e.to_string())?;
     Ok("Claude Code started".to_string())
 }
 
+/// 任意のCLIエージェント（codex・gemini・aiderなど）をPTYで起動
+///
+/// 出力・プロンプト・終了はClaude Codeと同じ `pty-*` イベントで通知する。
+#[tauri::command]
+fn spawn_agent_pty(
+    state: State<AppState>,
+    app_handle: AppHandle,
+    command: String,
+    args: Option<Vec<String>>,
+    env: Option<std::collections::HashMap<String, String>>,
+    cwd: Option<String>,
+) -> Result<String, String> {
+    state.set_app_handle(app_handle.clone());
+
+    let mut pty = state.pty.lock();
+    attach_pty_events(&state, &mut pty, app_handle);
+
+    pty.spawn_agent_pty(
+        &command,
+        &args.unwrap_or_default(),
+        &env.unwrap_or_default(),
+        cwd.as_deref(),
+    )
+    .map_err(|e| e.to_string())?;
+    log::info("spawn_agent_pty", &format!("Started {}", command));
+    Ok(format!("{} started", command))
+}
+
+/// Claude Codeを終了（リーダースレッドを止めてバッファをクリアし、`pty-exited` を送信）
+#[tauri::command]
+fn kill_claude(state: State<AppState>) -> Result<PtyExit, String> {
+    let mut pty = state.pty.lock();
+    pty.shutdown().map_err(|e| e.to_string())
+}
+
+/// PTYの確認プロンプトへの自動応答の監査ログを取得（古い順）
+#[tauri::command]
+fn get_auto_response_audit(state: State<AppState>) -> Vec<AutoResponseRecord> {
+    state.pty.lock().auto_response_audit()
+}
+
+/// 記録済みのセッション一覧（新しい順）
+#[tauri::command]
+fn list_transcripts() -> Result<Vec<transcript::TranscriptSummary>, String> {
+    transcript::list().map_err(|e| e.to_string())
+}
+
+/// セッションの記録をMarkdownまたはHTMLで書き出す
+///
+/// `output_path` を指定するとファイルにも保存する。書き出した内容を返す。
+#[tauri::command]
+fn export_transcript(
+    session_id: String,
+    format: transcript::TranscriptFormat,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let content = transcript::export(&session_id, format).map_err(|e| e.to_string())?;
+    if let Some(path) = output_path {
+        std::fs::write(&path, &content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+    Ok(content)
+}
+
+/// rawモードを切り替え（有効にすると加工前の出力を `pty-raw-output` でも送信）
+#[tauri::command]
+fn set_pty_raw_mode(state: State<AppState>, enabled: bool) {
+    state.pty.lock().set_raw_mode(enabled);
+}
+
+/// 最後に起動したエージェント（既定はClaude Code）を再起動（起動していなければそのまま起動）
+#[tauri::command]
+fn restart_claude(state: State<AppState>, app_handle: AppHandle) -> Result<String, String> {
+    state.set_app_handle(app_handle.clone());
+
+    let mut pty = state.pty.lock();
+    if pty.is_running() {
+        pty.shutdown().map_err(|e| e.to_string())?;
+    }
+
+    attach_pty_events(&state, &mut pty, app_handle);
+    pty.respawn().map_err(|e| e.to_string())?;
+    Ok("Claude Code restarted".to_string())
+}
+
 /// Claude Codeにメッセージを送信
 #[tauri::command]
 fn send_to_claude(state: State<AppState>, message: String) -> Result<String, String> {
-    let now = chrono::Local::now();
-    eprintln!("[{}] [send_to_claude] called with {} bytes", now.format("%H:%M:%S%.3f"), message.len());
+    log::debug("send_to_claude", &format!("Sending {} bytes", message.len()));
 
     let pty = state.pty.lock();
     pty.send_message(&message).map_err(|e| e.to_string())?;
 
-    let now = chrono::Local::now();
-    eprintln!("[{}] [send_to_claude] completed", now.format("%H:%M:%S%.3f"));
+    log::debug("send_to_claude", "Completed");
     Ok("Message sent".to_string())
 }
 
@@ -152,11 +356,17 @@ fn read_from_claude(state: State<AppState>) -> Result<String, String> {
     Ok(pty.get_output())
 }
 
+/// Claude Codeの出力をカーソル `seq` 以降だけ取得（次回は `nextSeq` を渡す）
+#[tauri::command]
+fn read_from_claude_since(state: State<AppState>, seq: u64) -> OutputSlice {
+    let pty = state.pty.lock();
+    pty.get_output_since(seq)
+}
+
 /// PTYテスト: 送信直後に読み取り
 #[tauri::command]
 fn pty_test_roundtrip(state: State<AppState>, message: String) -> Result<String, String> {
-    let now = chrono::Local::now();
-    eprintln!("[{}] [pty_test_roundtrip] Starting", now.format("%H:%M:%S%.3f"));
+    log::debug("pty_test_roundtrip", "Starting");
 
     let pty = state.pty.lock();
 
@@ -171,8 +381,7 @@ fn pty_test_roundtrip(state: State<AppState>, message: String) -> Result<String,
     let pty = state.pty.lock();
     let output = pty.get_output();
 
-    let now = chrono::Local::now();
-    eprintln!("[{}] [pty_test_roundtrip] Output: {} chars", now.format("%H:%M:%S%.3f"), output.len());
+    log::debug("pty_test_roundtrip", &format!("Output: {} chars", output.len()));
 
     Ok(output)
 }
@@ -205,6 +414,12 @@ fn get_child_pid(state: State<AppState>) -> Option<u32> {
     pty.child_pid()
 }
 
+/// プラットフォームで使える機能（PTY・ConPTY・tmux・シェル）を取得
+#[tauri::command]
+fn get_platform_capabilities() -> platform::PlatformCapabilities {
+    platform::capabilities()
+}
+
 /// テスト用: 汎用コマンドを実行
 #[tauri::command]
 fn execute_command(state: State<AppState>, command: String) -> Result<String, String> {
@@ -213,13 +428,7 @@ fn execute_command(state: State<AppState>, command: String) -> Result<String, St
     if !pty.is_running() {
         drop(pty);
 
-        let path = std::env::var("PATH").unwrap_or_default();
-        let extended_path = format!("/opt/homebrew/bin:/usr/local/bin:{}", path);
-
-        let output = std::process::Command::new("sh")
-            .arg("-c")
-            .arg(&command)
-            .env("PATH", &extended_path)
+        let output = process_env::shell_command(&command)
             .output()
             .map_err(|e| e.to_string())?;
 
@@ -241,29 +450,111 @@ fn execute_command(state: State<AppState>, command: String) -> Result<String, St
 // ACP Commands
 // ============================================================================
 
+/// AgentCardのJSONを読み、登録できるか検証する
+fn parse_agent_card(json: serde_json::Value) -> Result<AgentCard, String> {
+    let card: AgentCard = serde_json::from_value(json).map_err(|e| format!("Invalid agent card: {}", e))?;
+    validate_card(&card)?;
+    Ok(card)
+}
+
+/// PTYで動くエージェントならレガシーPTY経由でメッセージを送れるようにする
+fn attach_pty_transport(state: &AppState, orchestrator: &AgentOrchestrator, card: &AgentCard) -> Result<(), String> {
+    if card.transport != Some(Transport::Pty) {
+        return Ok(());
+    }
+    let agent_id = card.id.clone().unwrap_or_else(|| card.name.clone());
+    orchestrator
+        .attach_transport(&agent_id, state.pty_transport.clone())
+        .map_err(|e| e.to_string())
+}
+
 /// ACP: エージェントを登録
+///
+/// `card` を渡すとAgentCardのJSONをそのまま登録する（protocolVersionとスキルを検証）。
+/// 登録したエージェントは再起動後も復元される。
 #[tauri::command]
 fn acp_register_agent(
     state: State<AppState>,
-    agent_type: String,
-    instance_id: String,
+    agent_type: Option<String>,
+    instance_id: Option<String>,
+    card: Option<serde_json::Value>,
 ) -> Result<String, String> {
-    let orchestrator = state.orchestrator.lock();
-
-    // Create agent card based on type
-    let card = match agent_type.as_str() {
-        "claude-code" => AgentCard::claude_code(&instance_id),
-        _ => return Err(format!("Unknown agent type: {}", agent_type)),
+    let card = match (card, agent_type) {
+        (Some(json), _) => parse_agent_card(json)?,
+        (None, Some(agent_type)) => {
+            let instance_id = instance_id.ok_or("instanceId is required")?;
+            match agent_type.as_str() {
+                "claude-code" => AgentCard::claude_code(&instance_id),
+                "codex" => AgentCard::codex(&instance_id),
+                _ => return Err(format!("Unknown agent type: {}", agent_type)),
+            }
+        }
+        (None, None) => return Err("agentType or card is required".to_string()),
     };
 
     let agent_id = card.id.clone().unwrap_or_else(|| card.name.clone());
+    let orchestrator = state.orchestrator.lock();
     orchestrator
-        .register_agent_card(card)
+        .register_agent_card(card.clone())
         .map_err(|e| e.to_string())?;
+    attach_pty_transport(&state, &orchestrator, &card)?;
 
+    if let Err(e) = state.registry_store.lock().insert(card) {
+        log::warn("ACP", &format!("Failed to save agent registry: {}", e));
+    }
     Ok(agent_id)
 }
 
+/// ACP: 登録済みエージェントのカードを置き換える
+#[tauri::command]
+fn acp_update_agent(
+    state: State<AppState>,
+    agent_id: String,
+    card: serde_json::Value,
+) -> Result<AgentCard, String> {
+    let mut card = parse_agent_card(card)?;
+    match card.id {
+        Some(ref id) if *id != agent_id => {
+            return Err(format!("Card id {} does not match {}", id, agent_id));
+        }
+        Some(_) => {}
+        None => card.id = Some(agent_id.clone()),
+    }
+
+    state
+        .orchestrator
+        .lock()
+        .update_agent_card(card.clone())
+        .map_err(|e| e.to_string())?;
+
+    // カードディレクトリやリモートのカードは元の場所から読み直すので保存しない
+    let mut store = state.registry_store.lock();
+    if store.contains(&agent_id) {
+        if let Err(e) = store.insert(card.clone()) {
+            log::warn("ACP", &format!("Failed to save agent registry: {}", e));
+        }
+    }
+    Ok(card)
+}
+
+/// ACP: エージェントの登録を解除（stdio・WebSocketのトランスポートは閉じる）
+#[tauri::command]
+fn acp_unregister_agent(state: State<AppState>, agent_id: String) -> Result<(), String> {
+    let orchestrator = state.orchestrator.lock();
+    let transport = orchestrator.transport(&agent_id);
+    orchestrator.unregister_agent(&agent_id).map_err(|e| e.to_string())?;
+    drop(orchestrator);
+
+    // レガシーPTYなど共有のトランスポートは閉じない
+    if let Some(transport) = transport.filter(|t| matches!(t.kind(), Transport::Stdio | Transport::WebSocket)) {
+        let _ = transport.close();
+    }
+    if let Err(e) = state.registry_store.lock().remove(&agent_id) {
+        log::warn("ACP", &format!("Failed to save agent registry: {}", e));
+    }
+    Ok(())
+}
+
 /// ACP: エージェントを発見
 #[tauri::command]
 fn acp_discover_agents(
@@ -313,28 +604,294 @@ fn acp_get_agent(state: State<AppState>, agent_id: String) -> Option<AgentCard>
 }
 
 /// ACP: メッセージを送信（イベント駆動）
-/// メッセージを送信し、応答は "pty-output" イベントで通知される
+///
+/// 宛先のエージェントのトランスポート（PTY・tmuxペイン・CLIエグゼキューター・stdio・WebSocket）に
+/// ACPエンベロープとして送り、メッセージIDを返す。応答は各トランスポートのイベントで通知される。
 #[tauri::command]
 fn acp_send_message(
     state: State<AppState>,
     to: String,
     content: String,
-    _from: String,
+    from: Option<String>,
 ) -> Result<String, String> {
-    let now = chrono::Local::now();
-    eprintln!("[{}] [acp_send_message] Sending to {}: {:?}", now.format("%H:%M:%S%.3f"), to, content);
+    log::debug("acp_send_message", &format!("Sending to {}: {:?}", to, content));
 
-    let pty = state.pty.lock();
+    let from = from.unwrap_or_else(|| LOCAL_SENDER.to_string());
+    let orchestrator = state.orchestrator.lock();
+    orchestrator.route(&from, &to, &content).map_err(|e| e.to_string())
+}
 
-    if pty.is_running() {
-        // メッセージ送信（応答はイベントで通知）
-        pty.send_message(&content).map_err(|e| e.to_string())?;
-        Ok(format!("Message sent to {}. Response will arrive via pty-output events.", to))
+/// ACP: メッセージを送信し、対応する応答（`correlation_id` が一致するResponse）を待つ
+///
+/// `timeout_ms` を省略すると設定の `executor_timeout_secs`。Streamメッセージは `chunks` にまとめて返す。
+#[tauri::command]
+async fn acp_send_and_wait(
+    state: State<'_, AppState>,
+    to: String,
+    content: String,
+    from: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<CorrelatedReply, String> {
+    let from = from.unwrap_or_else(|| LOCAL_SENDER.to_string());
+    let timeout = timeout_ms
+        .map(std::time::Duration::from_millis)
+        .unwrap_or_else(|| std::time::Duration::from_secs(settings::current().executor_timeout_secs));
+
+    // 応答待ちの間はオーケストレーターのロックを持たない
+    let pending = state.orchestrator.lock().request(&from, &to, &content).map_err(|e| e.to_string())?;
+    log::debug("acp_send_and_wait", &format!("Waiting for reply to {} from {}", pending.message_id(), to));
+    pending.wait(timeout).await.map_err(|e| e.to_string())
+}
+
+/// ACP: 条件に合うエージェントのうち最も空いているものに送り、応答を待つ
+///
+/// 待機中のエージェントを優先し、次に応答待ちの少ないもの、同じなら最後に送ってから長いもの（順番）を選ぶ。
+#[tauri::command]
+async fn acp_dispatch(
+    state: State<'_, AppState>,
+    content: String,
+    filter: Option<CapabilityFilter>,
+    from: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<CorrelatedReply, String> {
+    let from = from.unwrap_or_else(|| LOCAL_SENDER.to_string());
+    let timeout = timeout_ms
+        .map(std::time::Duration::from_millis)
+        .unwrap_or_else(|| std::time::Duration::from_secs(settings::current().executor_timeout_secs));
+
+    let pending = state
+        .orchestrator
+        .lock()
+        .dispatch(&from, &filter.unwrap_or_default(), &content)
+        .map_err(|e| e.to_string())?;
+    log::debug("acp_dispatch", &format!("Dispatched {} to {}", pending.message_id(), pending.agent_id()));
+    pending.wait(timeout).await.map_err(|e| e.to_string())
+}
+
+/// ACP: 送受信したメッセージの履歴（古い順、既定は最新200件）
+///
+/// `agent_id` でエージェント、`since`（RFC 3339）で記録日時を絞り込む。
+#[tauri::command]
+fn acp_get_history(
+    state: State<AppState>,
+    agent_id: Option<String>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    limit: Option<usize>,
+) -> Vec<HistoryEntry> {
+    state.orchestrator.lock().history(agent_id.as_deref(), since, limit)
+}
+
+/// ACP: 本文・メッセージID・相関ID・アドレスに `query` を含むメッセージを検索（大文字小文字を区別しない）
+#[tauri::command]
+fn acp_search_history(state: State<AppState>, query: String, limit: Option<usize>) -> Vec<HistoryEntry> {
+    state.orchestrator.lock().search_history(&query, limit)
+}
+
+/// ACP: エージェントの出力を購読する（"acp:agent_output" イベントで通知、解除用のIDを返す）
+#[tauri::command]
+fn acp_subscribe_agent(state: State<AppState>, app_handle: AppHandle, agent_id: String) -> Result<u64, String> {
+    let transport = state.orchestrator.lock().transport(&agent_id)
+        .ok_or_else(|| format!("No transport attached to agent: {}", agent_id))?;
+
+    let id = agent_id.clone();
+    let subscription = transport.subscribe(Arc::new(move |event| {
+        let payload = serde_json::json!({ "agentId": id, "event": event });
+        let _ = app_handle.emit_recorded("acp:agent_output", payload);
+    }));
+    log::info("acp_subscribe_agent", &format!("Subscribed to {} ({:?})", agent_id, transport.kind()));
+    Ok(subscription)
+}
+
+/// ACP: エージェント出力の購読を解除
+#[tauri::command]
+fn acp_unsubscribe_agent(state: State<AppState>, agent_id: String, subscription: u64) -> bool {
+    state.orchestrator.lock().transport(&agent_id)
+        .is_some_and(|transport| transport.unsubscribe(subscription))
+}
+
+/// ACP: エージェントのトランスポートを閉じる
+#[tauri::command]
+fn acp_close_agent(state: State<AppState>, agent_id: String) -> Result<(), String> {
+    let transport = state.orchestrator.lock().registry().detach_transport(&agent_id)
+        .ok_or_else(|| format!("No transport attached to agent: {}", agent_id))?;
+    transport.close().map_err(|e| e.to_string())
+}
+
+/// ACP: 任意のエージェントプロセスを起動し、stdio（改行区切りのACPエンベロープ）で接続する
+#[tauri::command]
+fn acp_spawn_stdio_agent(
+    state: State<AppState>,
+    agent_id: String,
+    command: StdioCommand,
+    capabilities: Option<Vec<String>>,
+) -> Result<String, String> {
+    let transport = StdioTransport::spawn(&agent_id, &command).map_err(|e| e.to_string())?;
+
+    let card = AgentCard::new(&agent_id, format!("stdio://{}", command.command))
+        .with_id(&agent_id)
+        .with_transport(Transport::Stdio)
+        .with_skills(capabilities.unwrap_or_default().iter().map(|c| Skill::new(c, c)).collect());
+    let orchestrator = state.orchestrator.lock();
+    orchestrator.registry().upsert(card);
+
+    // 既存のトランスポートがあれば閉じてから差し替える
+    if let Some(previous) = orchestrator.registry().detach_transport(&agent_id) {
+        let _ = previous.close();
+    }
+    orchestrator
+        .attach_transport(&agent_id, Arc::new(transport))
+        .map_err(|e| e.to_string())?;
+    Ok(agent_id)
+}
+
+/// ACP: リモートエージェントにWebSocketで接続し、`Transport::WebSocket` のエージェントとして登録
+#[tauri::command]
+async fn acp_connect_remote(
+    state: State<'_, AppState>,
+    url: String,
+    agent_id: Option<String>,
+    capabilities: Option<Vec<String>>,
+) -> Result<String, String> {
+    let agent_id = agent_id.unwrap_or_else(|| url.clone());
+    let transport = WebSocketTransport::connect(&agent_id, &url, WebSocketConfig::default())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let card = AgentCard::new(&agent_id, &url)
+        .with_id(&agent_id)
+        .with_transport(Transport::WebSocket)
+        .with_skills(capabilities.unwrap_or_default().iter().map(|c| Skill::new(c, c)).collect());
+    let orchestrator = state.orchestrator.lock();
+    orchestrator.registry().upsert(card);
+    if let Some(previous) = orchestrator.registry().detach_transport(&agent_id) {
+        let _ = previous.close();
+    }
+    orchestrator
+        .attach_transport(&agent_id, Arc::new(transport))
+        .map_err(|e| e.to_string())?;
+    Ok(agent_id)
+}
+
+/// ACP: `<url>/.well-known/agent.json` からエージェントカードを取得して登録
+///
+/// `connect` が true でカードのエンドポイントがWebSocketなら、そのまま接続する。
+#[tauri::command]
+async fn acp_discover_remote(
+    state: State<'_, AppState>,
+    url: String,
+    connect: Option<bool>,
+) -> Result<AgentCard, String> {
+    let card = fetch_agent_card(&url).await.map_err(|e| e.to_string())?;
+    let agent_id = card.id.clone().unwrap_or_else(|| card.name.clone());
+
+    let transport = if connect.unwrap_or(false) && card.transport == Some(Transport::WebSocket) {
+        let transport = WebSocketTransport::connect(&agent_id, &card.url, WebSocketConfig::default())
+            .await
+            .map_err(|e| e.to_string())?;
+        Some(transport)
     } else {
-        Err(format!("Agent {} is not running", to))
+        None
+    };
+
+    let orchestrator = state.orchestrator.lock();
+    orchestrator.registry().upsert(card.clone());
+    if let Some(transport) = transport {
+        if let Some(previous) = orchestrator.registry().detach_transport(&agent_id) {
+            let _ = previous.close();
+        }
+        orchestrator
+            .attach_transport(&agent_id, Arc::new(transport))
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(card)
+}
+
+/// 他のエージェントから受けたA2Aタスクをエージェントに渡し、応答を待つ
+///
+/// 宛先は `metadata.agentId`（省略時はCLIエグゼキューター）。
+fn inbound_task_handler(app_handle: AppHandle) -> TaskHandler {
+    Arc::new(move |task: InboundTask| {
+        let app_handle = app_handle.clone();
+        Box::pin(async move {
+            let to = task
+                .metadata
+                .as_ref()
+                .and_then(|m| m["agentId"].as_str().map(str::to_string))
+                .or_else(|| cli_agent_card().id)
+                .unwrap_or_default();
+            log::info("AgentServer", &format!("Task {} from {} -> {}", task.id, task.peer, to));
+            let _ = app_handle.emit_recorded(
+                "acp:inbound_task",
+                serde_json::json!({ "taskId": task.id, "peer": task.peer.to_string(), "agentId": to, "text": task.text }),
+            );
+
+            let from = format!("a2a@{}", task.peer.ip());
+            let timeout = std::time::Duration::from_secs(settings::current().executor_timeout_secs);
+            let state = app_handle.state::<AppState>();
+            let pending = state.orchestrator.lock().request(&from, &to, &task.text).map_err(|e| e.to_string())?;
+            let reply = pending.wait(timeout).await.map_err(|e| e.to_string())?;
+            Ok(reply.response.message.payload.content)
+        })
+    })
+}
+
+/// ACP: 自分のエージェントカードを `/.well-known/agent.json` で公開し、A2Aタスクを受け付ける（公開したカードを返す）
+///
+/// `addr` 省略時は設定の `agent_server_addr`、それもなければ `127.0.0.1:41241`。
+#[tauri::command]
+async fn acp_start_agent_server(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    addr: Option<String>,
+) -> Result<AgentCard, String> {
+    let addr = addr
+        .or_else(|| settings::current().agent_server_addr)
+        .unwrap_or_else(|| DEFAULT_SERVER_ADDR.to_string());
+
+    // 開き直すときは先に閉じてポートを空ける
+    let previous = state.agent_server.lock().take();
+    if let Some(previous) = previous {
+        previous.stop().await;
+    }
+    let token = settings::agent_server_token().map_err(|e| e.to_string())?;
+    let server = AgentServer::start(&addr, AgentCard::re_voice(), &token, inbound_task_handler(app_handle))
+        .await
+        .map_err(|e| format!("Failed to start agent server on {}: {}", addr, e))?;
+    let card = server.card().clone();
+    *state.agent_server.lock() = Some(server);
+    Ok(card)
+}
+
+/// ACP: エージェントカードサーバーのトークン（ペアリングするエージェントに渡す。なければ生成する）
+#[tauri::command]
+fn acp_get_agent_server_token() -> Result<String, String> {
+    settings::agent_server_token().map_err(|e| e.to_string())
+}
+
+/// ACP: エージェントカードサーバーを閉じる（開いていなければfalse）
+#[tauri::command]
+async fn acp_stop_agent_server(state: State<'_, AppState>) -> Result<bool, String> {
+    let server = state.agent_server.lock().take();
+    match server {
+        Some(server) => {
+            server.stop().await;
+            Ok(true)
+        }
+        None => Ok(false),
     }
 }
 
+/// ACP: リモートエージェントとの接続を切り、レジストリから削除
+#[tauri::command]
+fn acp_disconnect_remote(state: State<AppState>, agent_id: String) -> Result<(), String> {
+    let orchestrator = state.orchestrator.lock();
+    let transport = orchestrator.transport(&agent_id)
+        .filter(|t| t.kind() == Transport::WebSocket)
+        .ok_or_else(|| format!("Not a remote agent: {}", agent_id))?;
+    transport.close().map_err(|e| e.to_string())?;
+    orchestrator.unregister_agent(&agent_id).map_err(|e| e.to_string())
+}
+
 /// ACP: 現在のレスポンスを取得
 #[tauri::command]
 fn acp_get_response(state: State<AppState>) -> Result<String, String> {
@@ -342,23 +899,20 @@ fn acp_get_response(state: State<AppState>) -> Result<String, String> {
     Ok(pty.get_response())
 }
 
-/// ACP: ブロードキャスト（簡易実装）
+/// ACP: ブロードキャスト（トランスポートが接続されたエージェントのみ、送信先IDを返す）
 #[tauri::command]
 fn acp_broadcast(
     state: State<AppState>,
     content: String,
-    _capabilities: Option<Vec<String>>,
+    capabilities: Option<Vec<String>>,
     _from: String,
 ) -> Result<Vec<String>, String> {
-    // For now, just send to the legacy PTY if running
-    let pty = state.pty.lock();
-
-    if pty.is_running() {
-        pty.send_message(&content).map_err(|e| e.to_string())?;
-        Ok(vec!["Message broadcasted".to_string()])
-    } else {
-        Ok(vec![])
+    let mut query = DiscoveryQuery::new();
+    if let Some(caps) = capabilities {
+        query = query.with_capabilities(caps);
     }
+    let orchestrator = state.orchestrator.lock();
+    Ok(orchestrator.broadcast(&query, &content))
 }
 
 /// ACP: タスク状態を取得
@@ -375,11 +929,72 @@ fn acp_stats(state: State<AppState>) -> OrchestratorStats {
     orchestrator.stats()
 }
 
+/// 共有コンテキストのワークスペース（`execution_id` 指定時はその実行の名前空間）
+fn context_workspace(workspace: Option<String>, execution_id: Option<String>) -> String {
+    match execution_id {
+        Some(id) => execution_workspace(&id),
+        None => workspace.unwrap_or_else(|| DEFAULT_WORKSPACE.to_string()),
+    }
+}
+
 /// ACP: 共有コンテキストを取得
 #[tauri::command]
-fn acp_get_context(state: State<AppState>) -> SharedContext {
+fn acp_get_context(state: State<AppState>, workspace: Option<String>, execution_id: Option<String>) -> SharedContext {
+    let orchestrator = state.orchestrator.lock();
+    orchestrator.get_workspace_context(&context_workspace(workspace, execution_id))
+}
+
+/// ACP: 共有コンテキストに値を設定（`ttl_secs` 秒後に消える）
+#[tauri::command]
+fn acp_context_set(
+    state: State<AppState>,
+    key: String,
+    value: ContextValue,
+    workspace: Option<String>,
+    execution_id: Option<String>,
+    ttl_secs: Option<u64>,
+    updated_by: Option<String>,
+) -> Result<ContextItem, String> {
+    if key.trim().is_empty() {
+        return Err("Key must not be empty".to_string());
+    }
+    let ttl = ttl_secs.map(|secs| chrono::Duration::seconds(secs as i64));
+    let workspace = context_workspace(workspace, execution_id);
+    let orchestrator = state.orchestrator.lock();
+    Ok(orchestrator.set_context_value(&workspace, &key, value, ttl, updated_by))
+}
+
+/// ACP: 共有コンテキストの値を取得
+#[tauri::command]
+fn acp_context_get(
+    state: State<AppState>,
+    key: String,
+    workspace: Option<String>,
+    execution_id: Option<String>,
+) -> Option<ContextItem> {
+    let workspace = context_workspace(workspace, execution_id);
+    let orchestrator = state.orchestrator.lock();
+    orchestrator.get_context_value(&workspace, &key)
+}
+
+/// ACP: 共有コンテキストの値を削除
+#[tauri::command]
+fn acp_context_delete(
+    state: State<AppState>,
+    key: String,
+    workspace: Option<String>,
+    execution_id: Option<String>,
+) -> bool {
+    let workspace = context_workspace(workspace, execution_id);
+    let orchestrator = state.orchestrator.lock();
+    orchestrator.delete_context_value(&workspace, &key)
+}
+
+/// ACP: ワークスペースの値をすべて削除（削除した件数を返す）
+#[tauri::command]
+fn acp_context_clear(state: State<AppState>, workspace: Option<String>, execution_id: Option<String>) -> usize {
     let orchestrator = state.orchestrator.lock();
-    orchestrator.get_shared_context()
+    orchestrator.clear_workspace(&context_workspace(workspace, execution_id))
 }
 
 // ============================================================================
@@ -394,35 +1009,77 @@ fn check_ytdlp_available() -> Result<(), String> {
 }
 
 /// 字幕をダウンロード（Rust版）
+///
+/// メンバー限定・年齢制限のある動画は `cookies` でブラウザかcookies.txtを指定する。
 #[tauri::command]
-fn youtube_download_subtitle(
+async fn youtube_download_subtitle(
     url: String,
     output_dir: String,
     lang: String,
+    cookies: Option<YtdlpCookies>,
 ) -> Result<SubtitleDownloadResult, String> {
-    let downloader = YoutubeDownloader::new();
-    downloader.download_subtitle(&url, &output_dir, &lang)
+    tokio::task::spawn_blocking(move || {
+        let downloader = YoutubeDownloader::new().with_cookies(cookies.unwrap_or_default());
+        downloader.download_subtitle(&url, &output_dir, &lang)
+    })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// 動画（MP4）または音声をダウンロードし、保存したファイルのパスを返す
+///
+/// 進捗は `youtube:download_progress` イベントで通知する。
+#[tauri::command]
+async fn youtube_download_media(
+    app_handle: AppHandle,
+    url: String,
+    format: Option<MediaFormat>,
+    output_dir: String,
+    cookies: Option<YtdlpCookies>,
+) -> Result<String, String> {
+    let format = format.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let downloader = YoutubeDownloader::new().with_cookies(cookies.unwrap_or_default());
+        downloader.download_media(&url, format, &output_dir, |progress| {
+            let _ = app_handle.emit_recorded("youtube:download_progress", DownloadProgressEvent {
+                url: url.clone(),
+                format,
+                progress: progress.clone(),
+            });
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// 動画情報（タイトル・長さ・チャンネル・チャプター・字幕言語）を取得
+#[tauri::command]
+async fn youtube_get_metadata(url: String, cookies: Option<YtdlpCookies>) -> Result<VideoMetadata, String> {
+    tokio::task::spawn_blocking(move || {
+        YoutubeDownloader::new().with_cookies(cookies.unwrap_or_default()).fetch_metadata(&url)
+    })
+        .await
+        .map_err(|e| e.to_string())?
         .map_err(|e| e.to_string())
 }
 
 /// 利用可能な字幕言語一覧を取得
 #[tauri::command]
-fn youtube_list_subs(url: String) -> Result<Vec<String>, String> {
-    let downloader = YoutubeDownloader::new();
-    downloader.list_available_subs(&url)
+async fn youtube_list_subs(url: String, cookies: Option<YtdlpCookies>) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || {
+        YoutubeDownloader::new().with_cookies(cookies.unwrap_or_default()).list_available_subs(&url)
+    })
+        .await
+        .map_err(|e| e.to_string())?
         .map_err(|e| e.to_string())
 }
 
 /// 字幕情報を取得（レガシー）
 #[tauri::command]
 fn get_available_subtitles(url: String) -> Result<String, String> {
-    let path = std::env::var("PATH").unwrap_or_default();
-    let extended_path = format!("/opt/homebrew/bin:/usr/local/bin:{}", path);
-
-    let output = std::process::Command::new("sh")
-        .arg("-c")
-        .arg(format!("yt-dlp --list-subs \"{}\"", url))
-        .env("PATH", &extended_path)
+    let output = process_env::shell_command(&format!("yt-dlp --list-subs \"{}\"", url))
         .output()
         .map_err(|e| e.to_string())?;
 
@@ -439,16 +1096,11 @@ fn get_available_subtitles(url: String) -> Result<String, String> {
 /// 字幕をダウンロード（レガシー）
 #[tauri::command]
 fn download_subtitles(url: String, lang: String, output_path: String) -> Result<String, String> {
-    let path = std::env::var("PATH").unwrap_or_default();
-    let extended_path = format!("/opt/homebrew/bin:/usr/local/bin:{}", path);
-
-    let output = std::process::Command::new("sh")
-        .arg("-c")
-        .arg(format!(
-            "yt-dlp --write-subs --sub-lang {} --skip-download --sub-format vtt --output \"{}\" \"{}\"",
-            lang, output_path, url
-        ))
-        .env("PATH", &extended_path)
+    let script = format!(
+        "yt-dlp --write-subs --sub-lang {} --skip-download --sub-format vtt --output \"{}\" \"{}\"",
+        lang, output_path, url
+    );
+    let output = process_env::shell_command(&script)
         .output()
         .map_err(|e| e.to_string())?;
 
@@ -465,16 +1117,11 @@ fn download_subtitles(url: String, lang: String, output_path: String) -> Result<
 /// 自動生成字幕をダウンロード（手動字幕がない場合・レガシー）
 #[tauri::command]
 fn download_auto_subtitles(url: String, lang: String, output_path: String) -> Result<String, String> {
-    let path = std::env::var("PATH").unwrap_or_default();
-    let extended_path = format!("/opt/homebrew/bin:/usr/local/bin:{}", path);
-
-    let output = std::process::Command::new("sh")
-        .arg("-c")
-        .arg(format!(
-            "yt-dlp --write-auto-subs --sub-lang {} --skip-download --sub-format vtt --output \"{}\" \"{}\"",
-            lang, output_path, url
-        ))
-        .env("PATH", &extended_path)
+    let script = format!(
+        "yt-dlp --write-auto-subs --sub-lang {} --skip-download --sub-format vtt --output \"{}\" \"{}\"",
+        lang, output_path, url
+    );
+    let output = process_env::shell_command(&script)
         .output()
         .map_err(|e| e.to_string())?;
 
@@ -488,11 +1135,155 @@ fn download_auto_subtitles(url: String, lang: String, output_path: String) -> Re
     }
 }
 
-// ============================================================================
-// tmux Test Commands (ACP v2 PoC)
-// ============================================================================
-
-/// tmuxセッションを作成
+/// 出力先の空き容量を確認
+///
+/// `required_bytes` を省略した場合は字幕ステージ分の見積もりで確認する。
+#[tauri::command]
+fn check_disk_space(path: String, required_bytes: Option<u64>) -> Result<DiskSpaceReport, String> {
+    let required = required_bytes.unwrap_or(preflight::SUBTITLE_STAGE_BYTES);
+    preflight::check_disk_space(std::path::Path::new(&path), required)
+        .map_err(|e| e.to_string())
+}
+
+/// 外部ツール（claude・yt-dlp・tmux・ffmpeg・VOICEVOX Engine）が使えるか確認する
+///
+/// ツールごとにバージョンと、使えない場合は対処方法を返す。
+#[tauri::command]
+async fn check_environment() -> EnvironmentReport {
+    preflight::check_environment().await
+}
+
+/// 字幕ファイルから音声合成に必要な容量を見積もる（バイト）
+#[tauri::command]
+fn estimate_synthesis_space(vtt_path: String) -> Result<u64, String> {
+    let segments = VttParser::parse_file(&vtt_path).map_err(|e| e.to_string())?;
+    Ok(preflight::estimate_synthesis_bytes(&segments))
+}
+
+/// ffmpegがインストールされているか確認（バージョンを返す）
+#[tauri::command]
+fn ffmpeg_check_available() -> Result<String, String> {
+    ffmpeg::Ffmpeg::new().check_available().map_err(|e| e.to_string())
+}
+
+/// 音声ファイルを再生（セグメントや吹替トラックの試聴）
+#[tauri::command]
+fn audio_play(state: State<AppState>, path: String) -> Result<(), String> {
+    state.audio_player.play(&path).map_err(|e| e.to_string())
+}
+
+/// 試聴中の音声を停止
+#[tauri::command]
+fn audio_stop(state: State<AppState>) {
+    state.audio_player.stop();
+}
+
+/// 波形表示用のピークデータを取得（既定は10msごと）
+#[tauri::command]
+async fn audio_waveform(path: String, bin_ms: Option<u32>) -> Result<timeline::Waveform, String> {
+    tokio::task::spawn_blocking(move || {
+        timeline::PcmAudio::read_wav(std::path::Path::new(&path))
+            .map(|audio| audio.waveform(bin_ms.unwrap_or(10)))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// ライブ配信の吹替を開始（セッションIDを返す）
+///
+/// 翻訳・合成した字幕は `live:audio_chunk`、終了は `live:stopped` イベントで通知する。
+#[tauri::command]
+async fn live_start(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    url: String,
+    options: live::LiveOptions,
+) -> Result<String, String> {
+    let translator = state.pipeline_runner
+        .create_translator(options.provider, options.model.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+    state.live_manager.start(&url, options, translator, app_handle)
+}
+
+/// ライブ配信の吹替を停止
+#[tauri::command]
+fn live_stop(state: State<AppState>, session_id: String) -> Result<(), String> {
+    if state.live_manager.stop(&session_id) {
+        Ok(())
+    } else {
+        Err(format!("Live session not found: {}", session_id))
+    }
+}
+
+/// 実行中のライブ吹替セッション（ID・出力先）
+#[tauri::command]
+fn live_list(state: State<AppState>) -> Vec<(String, String)> {
+    state.live_manager.list()
+}
+
+/// チャプターごとに吹替トラック・字幕・動画を書き出す
+///
+/// `chapters` を省略した場合は元動画のチャプターを使う。進捗は `export:progress` イベントで通知。
+/// 終了した実行はチェックポイント（アプリ再起動後は `output_dir` の `pipeline_state.json`）から読み込む。
+#[tauri::command]
+async fn export_chapters(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    execution_id: String,
+    output_dir: Option<String>,
+    chapters: Option<Vec<chapters::Chapter>>,
+    options: Option<export::ChapterExportOptions>,
+) -> Result<Vec<export::ChapterOutput>, String> {
+    let context = state.pipeline_runner
+        .load_context(&execution_id, output_dir.as_deref())
+        .map_err(|e| e.to_string())?;
+    let options = options.unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || {
+        export::export_chapters(&context, chapters, &options, |progress| {
+            let _ = app_handle.emit_recorded("export:progress", &progress);
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// 吹替動画（MP4）を書き出す
+///
+/// 元動画が未取得ならダウンロードし、吹替トラック（と字幕）を多重化する。
+/// 進捗は `export:progress` イベントで通知し、出力ファイルのパスを返す。
+/// 終了した実行はチェックポイント（アプリ再起動後は `output_dir` の `pipeline_state.json`）から読み込む。
+#[tauri::command]
+async fn export_dubbed_video(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    execution_id: String,
+    output_dir: Option<String>,
+    options: Option<export::ExportOptions>,
+) -> Result<String, String> {
+    let context = state.pipeline_runner
+        .load_context(&execution_id, output_dir.as_deref())
+        .map_err(|e| e.to_string())?;
+    let options = options.unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || {
+        export::export_dubbed_video(&context, &options, |progress| {
+            let _ = app_handle.emit_recorded("export:progress", &progress);
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// tmux Test Commands (ACP v2 PoC)
+// ============================================================================
+
+/// tmuxセッションを作成
 #[tauri::command]
 fn tmux_create_session(state: State<AppState>) -> Result<String, String> {
     let mut tmux = state.tmux_orchestrator.lock();
@@ -517,7 +1308,19 @@ fn tmux_spawn_agent(
             "codex" => TmuxAgentType::Codex,
             _ => TmuxAgentType::GenericShell,
         };
-        let pane_id = orch.spawn_agent(&agent_id, atype, capabilities)
+        let pane_id = orch.spawn_agent(&agent_id, atype, capabilities.clone())
+            .map_err(|e| e.to_string())?;
+
+        // ACPレジストリに登録し、ペイン経由でメッセージを送れるようにする
+        let card = AgentCard::new(&agent_id, format!("tmux://{}", pane_id))
+            .with_id(&agent_id)
+            .with_transport(Transport::Pty)
+            .with_skills(capabilities.iter().map(|c| Skill::new(c, c)).collect());
+        let orchestrator = state.orchestrator.lock();
+        orchestrator.registry().upsert(card);
+        let transport = TmuxPaneHandle::new(state.tmux_orchestrator.clone(), &agent_id);
+        orchestrator
+            .attach_transport(&agent_id, Arc::new(transport))
             .map_err(|e| e.to_string())?;
         Ok(pane_id)
     } else {
@@ -525,6 +1328,16 @@ fn tmux_spawn_agent(
     }
 }
 
+/// tmuxペインの出力をカーソル `seq` 以降だけ取得
+#[tauri::command]
+fn tmux_capture_since(state: State<AppState>, agent_id: String, seq: u64) -> Result<OutputSlice, String> {
+    let tmux = state.tmux_orchestrator.lock();
+    let orch = tmux.as_ref().ok_or("Session not created")?;
+    let pane_id = orch.get_pane_id(&agent_id)
+        .ok_or_else(|| format!("Agent not found: {}", agent_id))?;
+    orch.capture_pane_since(pane_id, seq).map_err(|e| e.to_string())
+}
+
 /// tmuxペインの内容を取得
 #[tauri::command]
 fn tmux_capture_pane(state: State<AppState>, agent_id: String) -> Result<String, String> {
@@ -634,7 +1447,9 @@ fn tmux_start_polling(
         ..Default::default()
     });
 
-    let mut poller = StatusPoller::new(config);
+    // 検出した状態をレジストリに反映し、負荷に応じた振り分けに使う
+    let registry = state.orchestrator.lock().registry();
+    let mut poller = StatusPoller::new(config).with_registry(registry);
     let orch = state.tmux_orchestrator.clone();
 
     poller.start(app_handle, orch).map_err(|e| e.to_string())?;
@@ -645,7 +1460,7 @@ fn tmux_start_polling(
         *p = Some(poller);
     }
 
-    eprintln!("[tmux_start_polling] Polling started");
+    log::info("tmux_start_polling", "Polling started");
     Ok(())
 }
 
@@ -658,7 +1473,7 @@ fn tmux_stop_polling(state: State<AppState>) -> Result<(), String> {
     }
     *poller = None;
 
-    eprintln!("[tmux_stop_polling] Polling stopped");
+    log::info("tmux_stop_polling", "Polling stopped");
     Ok(())
 }
 
@@ -811,6 +1626,39 @@ fn acp_list_pipelines(state: State<AppState>) -> Vec<serde_json::Value> {
     }).collect()
 }
 
+/// パイプライン定義をアプリデータに保存（終了した実行履歴も合わせて保存）
+#[tauri::command]
+fn acp_save_pipeline(
+    state: State<AppState>,
+    app_handle: AppHandle,
+    pipeline_id: String,
+) -> Result<(), String> {
+    let executor = state.pipeline_executor.lock();
+    let pipeline = executor.get_pipeline(&pipeline_id)
+        .ok_or_else(|| format!("Pipeline not found: {}", pipeline_id))?;
+
+    let path = appdata::data_dir(&app_handle).join(PIPELINES_FILE);
+    let mut store = PipelineStore::load(&path).map_err(|e| e.to_string())?;
+    store.upsert(pipeline);
+    store.record_history(executor.finished_executions());
+    store.save().map_err(|e| e.to_string())?;
+
+    log::info("acp_save_pipeline", &format!("Pipeline saved: {}", pipeline_id));
+    Ok(())
+}
+
+/// 保存済みのパイプライン定義と実行履歴を読み込んで登録
+#[tauri::command]
+fn acp_load_pipelines(
+    state: State<AppState>,
+    app_handle: AppHandle,
+) -> Result<Vec<PipelineDefinition>, String> {
+    let path = appdata::data_dir(&app_handle).join(PIPELINES_FILE);
+    let store = PipelineStore::load(&path).map_err(|e| e.to_string())?;
+    store.restore_into(&state.pipeline_executor.lock());
+    Ok(store.pipelines)
+}
+
 /// アクティブなパイプライン実行一覧を取得
 #[tauri::command]
 fn acp_list_active_executions(state: State<AppState>) -> Vec<PipelineExecution> {
@@ -973,10 +1821,8 @@ async fn run_subtitle_pipeline(
     youtube_url: String,
     subtitle_lang: String,
     output_dir: String,
+    options: Option<TranslationOptions>,
 ) -> Result<String, String> {
-    eprintln!("[run_subtitle_pipeline] ===== STARTING =====");
-    eprintln!("[run_subtitle_pipeline] url={}, lang={}, dir={}", youtube_url, subtitle_lang, output_dir);
-
     log::info("run_subtitle_pipeline", &format!(
         "Starting pipeline: url={}, lang={}, dir={}",
         youtube_url, subtitle_lang, output_dir
@@ -990,153 +1836,794 @@ async fn run_subtitle_pipeline(
     let url = youtube_url.clone();
     let lang = subtitle_lang.clone();
     let dir = output_dir.clone();
+    let options = options.unwrap_or_default();
 
     // バックグラウンドでパイプラインを実行
     tokio::spawn(async move {
-        eprintln!("[run_subtitle_pipeline] Background task started");
-        match runner.run_subtitle_pipeline(&url, &lang, &dir).await {
+        match runner.run_subtitle_pipeline(&url, &lang, &dir, &options).await {
             Ok(exec) => {
-                eprintln!("[run_subtitle_pipeline] Pipeline completed: {}", exec.execution_id);
                 log::info("run_subtitle_pipeline", &format!(
                     "Pipeline completed: {} with status {:?}",
                     exec.execution_id, exec.status
                 ));
             }
             Err(e) => {
-                eprintln!("[run_subtitle_pipeline] Pipeline FAILED: {}", e);
                 log::error("run_subtitle_pipeline", &format!("Pipeline failed: {}", e));
             }
         }
     });
-
-    eprintln!("[run_subtitle_pipeline] Returning 'started'");
     Ok("started".to_string())
 }
 
-/// パイプライン実行状態を取得
+/// プレイリスト・複数URLの字幕翻訳パイプラインを動画ごとに順に実行（非同期・バックグラウンド）
+///
+/// 進捗は `pipeline:batch_progress` イベントで通知する。
 #[tauri::command]
-fn get_pipeline_execution(
-    state: State<AppState>,
-    execution_id: String,
-) -> Option<PipelineExecution> {
-    state.pipeline_runner.get_execution(&execution_id)
-}
+async fn run_batch_pipeline(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    urls: Vec<String>,
+    subtitle_lang: String,
+    output_dir: String,
+    options: Option<TranslationOptions>,
+) -> Result<String, String> {
+    if urls.iter().all(|u| u.trim().is_empty()) {
+        return Err("URLが指定されていません".to_string());
+    }
+    log::info("run_batch_pipeline", &format!("Starting batch: {} urls, dir={}", urls.len(), output_dir));
 
-/// アクティブなパイプライン実行一覧を取得
-#[tauri::command]
-fn list_active_pipeline_executions(state: State<AppState>) -> Vec<PipelineExecution> {
-    state.pipeline_runner.get_active_executions()
+    state.pipeline_runner.set_app_handle(app_handle);
+    let runner = state.pipeline_runner.clone();
+    let urls: Vec<String> = urls.into_iter().filter(|u| !u.trim().is_empty()).collect();
+    let options = options.unwrap_or_default();
+
+    tokio::spawn(async move {
+        match runner.run_batch_pipeline(&urls, &subtitle_lang, &output_dir, &options).await {
+            Ok(videos) => log::info("run_batch_pipeline", &format!(
+                "Batch finished: {} videos, {} failed",
+                videos.len(),
+                videos.iter().filter(|v| v.status == BatchVideoStatus::Failed).count()
+            )),
+            Err(e) => log::error("run_batch_pipeline", &format!("Batch failed: {}", e)),
+        }
+    });
+
+    Ok("started".to_string())
 }
 
-/// パイプライン実行をキャンセル
+/// 失敗した字幕翻訳パイプラインを完了済みステージの続きから再実行（非同期・バックグラウンド）
+///
+/// 戻り値は再実行の新しい実行ID。進捗イベントはこのIDで通知される。
 #[tauri::command]
-fn cancel_pipeline_execution(
-    state: State<AppState>,
+async fn run_subtitle_pipeline_resume(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
     execution_id: String,
-) -> Result<PipelineExecution, String> {
-    state.pipeline_runner.cancel_execution(&execution_id)
-        .map_err(|e| e.to_string())
-}
+    output_dir: Option<String>,
+) -> Result<String, String> {
+    log::info("run_subtitle_pipeline_resume", &format!("Resuming execution: {}", execution_id));
 
-// ============================================================================
-// Ask Tool Commands (ACP v3)
-// ============================================================================
+    state.pipeline_runner.set_app_handle(app_handle);
+    let runner = state.pipeline_runner.clone();
 
-/// 保留中の質問一覧を取得
-#[tauri::command]
-fn acp_get_pending_questions(state: State<AppState>) -> Vec<(String, ParsedQuestion)> {
-    state.pipeline_runner.ask_handler().get_pending_questions()
-}
+    let (pipeline_id, new_execution_id) = runner
+        .start_resume_subtitle_pipeline(&execution_id, output_dir.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
 
-/// 質問に回答する
-#[tauri::command]
-fn acp_submit_answer(
-    state: State<AppState>,
-    question_id: String,
-    answer: String,
-    remember_choice: bool,
-) -> Result<(), String> {
-    let human_answer = HumanAnswer {
-        question_id,
-        answer,
-        remember_choice,
-    };
-    state.pipeline_runner.ask_handler().submit_answer(human_answer)
-        .map_err(|e| e.to_string())
-}
+    let spawned_id = new_execution_id.clone();
+    tokio::spawn(async move {
+        match runner.run_started(&pipeline_id, &spawned_id).await {
+            Ok(exec) => log::info("run_subtitle_pipeline_resume", &format!(
+                "Pipeline completed: {} with status {:?}",
+                exec.execution_id, exec.status
+            )),
+            Err(e) => log::error("run_subtitle_pipeline_resume", &format!("Pipeline failed: {}", e)),
+        }
+    });
 
-// ============================================================================
-// CLI Executor Commands (v3 - stream-json based)
-// ============================================================================
+    Ok(new_execution_id)
+}
 
-/// CLIエグゼキューターを起動
+/// 登録済みのパイプライン（テンプレート）を入力付きで実行（非同期・バックグラウンド）
+///
+/// 入力にない項目はパイプラインの既定入力で補い、ステージの `{{key}}` を入力の値で置き換える。
 #[tauri::command]
-async fn executor_start(
+async fn run_registered_pipeline(
     state: State<'_, AppState>,
     app_handle: AppHandle,
-    working_dir: Option<String>,
-    allowed_tools: Option<Vec<String>>,
-    session_id: Option<String>,
+    pipeline_id: String,
+    input: serde_json::Value,
 ) -> Result<String, String> {
-    log::info("executor_start", "Starting CLI executor");
-
-    let options = ExecutorOptions {
-        working_dir,
-        allowed_tools: allowed_tools.unwrap_or_default(),
-        session_id,
-        ..Default::default()
-    };
+    if state.pipeline_executor.lock().get_pipeline(&pipeline_id).is_none() {
+        return Err(format!("Pipeline not found: {}", pipeline_id));
+    }
 
-    let cli_executor = state.cli_executor.clone();
+    state.pipeline_runner.set_app_handle(app_handle);
 
-    // 非同期でエグゼキューターを作成・保存
-    let mut guard = cli_executor.write().await;
+    let runner = state.pipeline_runner.clone();
+    tokio::spawn(async move {
+        match runner.run(&pipeline_id, input).await {
+            Ok(exec) => {
+                log::info("run_registered_pipeline", &format!(
+                    "Pipeline completed: {} with status {:?}",
+                    exec.execution_id, exec.status
+                ));
+            }
+            Err(e) => {
+                log::error("run_registered_pipeline", &format!("Pipeline failed: {}", e));
+            }
+        }
+    });
 
-    let mut executor = ClaudeCodeExecutor::new(options);
-    executor.set_app_handle(app_handle);
+    Ok("started".to_string())
+}
 
-    // 起動
-    executor.start().await
-        .map_err(|e| format!("Failed to start executor: {}", e))?;
+/// ユーザー定義のステージ列でパイプラインを実行（非同期・バックグラウンド）
+///
+/// Rust直接実行ステージは名前とパラメータ、Claude Codeステージはプロンプトテンプレートで指定する。
+/// 作成したパイプラインのIDを返す（`acp_save_pipeline` で保存できる）。
+/// 同じ名前で実行し直すと、実行中でなければ前回のパイプラインの定義を同じIDのまま置き換える。
+#[tauri::command]
+async fn run_custom_pipeline(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    name: String,
+    stages: Vec<CustomStage>,
+    input: serde_json::Value,
+) -> Result<String, String> {
+    let pipeline_id = state.pipeline_runner.register_custom_pipeline(&name, &stages).map_err(|e| e.to_string())?;
 
-    let session_id = executor.session_id()
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    state.pipeline_runner.set_app_handle(app_handle);
 
-    *guard = Some(executor);
+    let runner = state.pipeline_runner.clone();
+    let id = pipeline_id.clone();
+    tokio::spawn(async move {
+        match runner.run(&id, input).await {
+            Ok(exec) => {
+                log::info("run_custom_pipeline", &format!(
+                    "Pipeline completed: {} with status {:?}",
+                    exec.execution_id, exec.status
+                ));
+            }
+            Err(e) => {
+                log::error("run_custom_pipeline", &format!("Pipeline failed: {}", e));
+            }
+        }
+    });
 
-    log::info("executor_start", &format!("CLI executor started, session: {}", session_id));
-    Ok(session_id)
+    Ok(pipeline_id)
 }
 
-/// CLIエグゼキューターでタスクを実行
+/// ローカル字幕ファイル（VTT/SRT）の翻訳パイプラインを実行（非同期・バックグラウンド）
+///
+/// 字幕ダウンロードの代わりにファイルを取り込み、解析・翻訳・音声生成を行う。
 #[tauri::command]
-async fn executor_execute(
+async fn run_local_subtitle_pipeline(
     state: State<'_, AppState>,
-    prompt: String,
+    app_handle: AppHandle,
+    subtitle_path: String,
+    output_dir: String,
+    options: Option<TranslationOptions>,
 ) -> Result<String, String> {
-    log::info("executor_execute", &format!("Executing task ({} chars)", prompt.len()));
+    if !std::path::Path::new(&subtitle_path).is_file() {
+        return Err(format!("Subtitle file not found: {}", subtitle_path));
+    }
 
-    let cli_executor = state.cli_executor.clone();
+    state.pipeline_runner.set_app_handle(app_handle);
 
-    let mut guard = cli_executor.write().await;
+    let runner = state.pipeline_runner.clone();
+    let options = options.unwrap_or_default();
 
-    if let Some(ref mut executor) = *guard {
-        executor.execute(&prompt).await
-            .map_err(|e| format!("Execution failed: {}", e))
-    } else {
-        Err("Executor not started".to_string())
-    }
+    tokio::spawn(async move {
+        match runner.run_local_subtitle_pipeline(&subtitle_path, &output_dir, &options).await {
+            Ok(exec) => {
+                log::info("run_local_subtitle_pipeline", &format!(
+                    "Pipeline completed: {} with status {:?}",
+                    exec.execution_id, exec.status
+                ));
+            }
+            Err(e) => {
+                log::error("run_local_subtitle_pipeline", &format!("Pipeline failed: {}", e));
+            }
+        }
+    });
+
+    Ok("started".to_string())
 }
 
-/// CLIエグゼキューターを停止
+/// ローカル動画＋字幕ファイルの吹替パイプラインを実行（非同期・バックグラウンド）
+///
+/// 字幕（VTT/SRT）を翻訳・音声合成し、元動画に多重化した `dubbed.<lang>.mp4` を書き出す。
 #[tauri::command]
-async fn executor_stop(state: State<'_, AppState>) -> Result<(), String> {
-    log::info("executor_stop", "Stopping CLI executor");
+async fn run_local_video_pipeline(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    video_path: String,
+    subtitle_path: String,
+    output_dir: String,
+    options: Option<TranslationOptions>,
+) -> Result<String, String> {
+    if !std::path::Path::new(&video_path).is_file() {
+        return Err(format!("Video not found: {}", video_path));
+    }
+    if !std::path::Path::new(&subtitle_path).is_file() {
+        return Err(format!("Subtitle file not found: {}", subtitle_path));
+    }
 
-    let cli_executor = state.cli_executor.clone();
+    state.pipeline_runner.set_app_handle(app_handle);
 
-    let mut guard = cli_executor.write().await;
+    let runner = state.pipeline_runner.clone();
+    let options = options.unwrap_or_default();
+
+    tokio::spawn(async move {
+        match runner.run_local_video_pipeline(&video_path, &subtitle_path, &output_dir, &options).await {
+            Ok(exec) => {
+                log::info("run_local_video_pipeline", &format!(
+                    "Pipeline completed: {} with status {:?}",
+                    exec.execution_id, exec.status
+                ));
+            }
+            Err(e) => {
+                log::error("run_local_video_pipeline", &format!("Pipeline failed: {}", e));
+            }
+        }
+    });
+
+    Ok("started".to_string())
+}
+
+/// パイプライン実行状態を取得
+#[tauri::command]
+fn get_pipeline_execution(
+    state: State<AppState>,
+    execution_id: String,
+) -> Option<PipelineExecution> {
+    state.pipeline_runner.get_execution(&execution_id)
+}
+
+/// アクティブなパイプライン実行一覧を取得
+#[tauri::command]
+fn list_active_pipeline_executions(state: State<AppState>) -> Vec<PipelineExecution> {
+    state.pipeline_runner.get_active_executions()
+}
+
+/// パイプライン実行を一時停止
+#[tauri::command]
+fn pause_pipeline_execution(
+    state: State<AppState>,
+    execution_id: String,
+) -> Result<PipelineExecution, String> {
+    state.pipeline_runner.pause_execution(&execution_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 一時停止したパイプライン実行を再開
+#[tauri::command]
+fn resume_pipeline_execution(
+    state: State<AppState>,
+    execution_id: String,
+) -> Result<PipelineExecution, String> {
+    state.pipeline_runner.resume_execution(&execution_id)
+        .map_err(|e| e.to_string())
+}
+
+/// パイプライン実行をキャンセル
+#[tauri::command]
+fn cancel_pipeline_execution(
+    state: State<AppState>,
+    execution_id: String,
+) -> Result<PipelineExecution, String> {
+    state.pipeline_runner.cancel_execution(&execution_id)
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Watch Folder Commands
+// ============================================================================
+
+/// 監視フォルダを開始
+#[tauri::command]
+fn watcher_start(
+    state: State<AppState>,
+    app_handle: AppHandle,
+    config: WatchConfig,
+) -> Result<(), String> {
+    let mut guard = state.folder_watcher.lock();
+    if let Some(ref w) = *guard {
+        if w.is_running() {
+            return Err("Watcher is already running".to_string());
+        }
+    }
+
+    state.pipeline_runner.set_app_handle(app_handle.clone());
+
+    let mut watcher = FolderWatcher::new(config);
+    watcher.start(app_handle, state.pipeline_runner.clone())?;
+    log::info("watcher_start", &format!("Watching folder: {}", watcher.config().folder));
+
+    *guard = Some(watcher);
+    Ok(())
+}
+
+/// 監視フォルダを停止
+#[tauri::command]
+fn watcher_stop(state: State<AppState>) -> Result<(), String> {
+    let mut guard = state.folder_watcher.lock();
+    if let Some(ref mut w) = *guard {
+        w.stop()?;
+    }
+    *guard = None;
+
+    log::info("watcher_stop", "Folder watcher stopped");
+    Ok(())
+}
+
+/// 監視中の設定を取得（停止中はNone）
+#[tauri::command]
+fn watcher_status(state: State<AppState>) -> Option<WatchConfig> {
+    let guard = state.folder_watcher.lock();
+    guard.as_ref()
+        .filter(|w| w.is_running())
+        .map(|w| w.config().clone())
+}
+
+// ============================================================================
+// Settings Commands
+// ============================================================================
+
+/// アプリ設定を取得
+#[tauri::command]
+fn get_settings() -> Settings {
+    settings::current()
+}
+
+/// アプリ設定を更新して保存（`patch` に含まれるキーだけを変更）
+#[tauri::command]
+fn update_settings(state: State<AppState>, patch: serde_json::Value) -> Result<Settings, String> {
+    let updated = settings::update(&patch).map_err(|e| e.to_string())?;
+    state.apply_settings();
+    log::info("update_settings", &format!("Updated settings: {}", patch));
+    Ok(updated)
+}
+
+// ============================================================================
+// Log Commands
+// ============================================================================
+
+/// 直近のログを取得（レベル・ターゲット・文字列・連番で絞り込み、古い順）
+#[tauri::command]
+fn get_recent_logs(filter: Option<LogFilter>) -> Vec<LogRecord> {
+    log::recent_logs(&filter.unwrap_or_default())
+}
+
+/// ログレベルを変更（`target` を指定するとそのターゲットだけ）
+#[tauri::command]
+fn set_log_level(level: String, target: Option<String>) -> Result<(), String> {
+    let level: LogLevel = level.parse()?;
+    log::set_level(target.as_deref(), level);
+    log::info("set_log_level", &format!("{:?} -> {:?}", target.as_deref().unwrap_or("*"), level));
+    Ok(())
+}
+
+// ============================================================================
+// Event History Commands
+// ============================================================================
+
+/// 記録済みのイベントを取得（`since` より後の連番、`tmux:*` のような前方一致も可）
+#[tauri::command]
+fn get_event_history(channel: Option<String>, since: Option<u64>) -> Vec<EventRecord> {
+    events::history(channel.as_deref(), since)
+}
+
+/// 記録しているチャンネル名と最後の連番を取得
+#[tauri::command]
+fn get_event_channels() -> serde_json::Value {
+    serde_json::json!({
+        "channels": events::channels(),
+        "lastSeq": events::last_seq(),
+    })
+}
+
+// ============================================================================
+// Glossary Commands
+// ============================================================================
+
+/// 用語集が登録されているプロジェクト一覧を取得
+#[tauri::command]
+fn glossary_list_projects(state: State<AppState>) -> Vec<String> {
+    state.pipeline_runner.glossaries().lock().projects()
+}
+
+/// プロジェクトの用語集を取得
+#[tauri::command]
+fn glossary_get(state: State<AppState>, project: String) -> Glossary {
+    state.pipeline_runner.glossaries().lock().get(&project)
+}
+
+/// プロジェクトの用語集を保存（既存のエントリは置き換え）
+#[tauri::command]
+fn glossary_set(
+    state: State<AppState>,
+    project: String,
+    entries: Vec<GlossaryEntry>,
+) -> Result<(), String> {
+    let glossaries = state.pipeline_runner.glossaries();
+    let mut store = glossaries.lock();
+    store.set(&project, Glossary::new(entries));
+    store.save().map_err(|e| e.to_string())?;
+
+    log::info("glossary_set", &format!("Saved glossary for project: {}", project));
+    Ok(())
+}
+
+/// プロジェクトの用語集を削除
+#[tauri::command]
+fn glossary_remove(state: State<AppState>, project: String) -> Result<bool, String> {
+    let glossaries = state.pipeline_runner.glossaries();
+    let mut store = glossaries.lock();
+    let removed = store.remove(&project);
+    store.save().map_err(|e| e.to_string())?;
+    Ok(removed)
+}
+
+/// 翻訳メモリの件数を取得
+#[tauri::command]
+fn translation_memory_stats(state: State<AppState>) -> MemoryStats {
+    state.pipeline_runner.translation_memory_stats()
+}
+
+/// 翻訳メモリを削除（次回の実行は全セグメントを翻訳し直す）
+#[tauri::command]
+fn translation_memory_clear(state: State<AppState>) -> Result<(), String> {
+    state.pipeline_runner.clear_translation_memory().map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Translation Provider Commands
+// ============================================================================
+
+/// 保存されているシークレット名一覧（値は返さない）
+#[tauri::command]
+fn secrets_list(state: State<AppState>) -> Vec<String> {
+    state.pipeline_runner.secrets().lock().names()
+}
+
+/// シークレットを保存
+#[tauri::command]
+fn secrets_set(state: State<AppState>, name: String, value: String) -> Result<(), String> {
+    let secrets = state.pipeline_runner.secrets();
+    let mut store = secrets.lock();
+    store.set(&name, &value);
+    store.save().map_err(|e| e.to_string())?;
+
+    log::info("secrets_set", &format!("Saved secret: {}", name));
+    Ok(())
+}
+
+/// シークレットを削除
+#[tauri::command]
+fn secrets_remove(state: State<AppState>, name: String) -> Result<bool, String> {
+    let secrets = state.pipeline_runner.secrets();
+    let mut store = secrets.lock();
+    let removed = store.remove(&name);
+    store.save().map_err(|e| e.to_string())?;
+    Ok(removed)
+}
+
+/// DeepLの今期の使用量を取得
+#[tauri::command]
+async fn deepl_get_usage(state: State<'_, AppState>) -> Result<DeeplUsage, String> {
+    let api_key = state.pipeline_runner.secrets().lock().get(DEEPL_API_KEY).unwrap_or_default();
+    let client = DeeplClient::new(&api_key).map_err(|e| e.to_string())?;
+    client.usage().await.map_err(|e| e.to_string())
+}
+
+/// ローカルのOllamaにインストール済みのモデル一覧を取得
+#[tauri::command]
+async fn ollama_list_models() -> Result<Vec<OllamaModel>, String> {
+    OllamaTranslator::new(None).list_models().await.map_err(|e| e.to_string())
+}
+
+/// Ollamaでモデルが利用可能か確認
+#[tauri::command]
+async fn ollama_check_model(model: Option<String>) -> Result<(), String> {
+    OllamaTranslator::new(model.as_deref()).check_available().await.map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Ask Tool Commands (ACP v3)
+// ============================================================================
+
+/// 保留中の質問一覧を取得
+#[tauri::command]
+fn acp_get_pending_questions(state: State<AppState>) -> Vec<(String, ParsedQuestion)> {
+    state.pipeline_runner.ask_handler().get_pending_questions()
+}
+
+/// 質問に回答する
+#[tauri::command]
+fn acp_submit_answer(
+    state: State<AppState>,
+    question_id: String,
+    answer: String,
+    remember_choice: bool,
+) -> Result<(), String> {
+    let human_answer = HumanAnswer {
+        question_id,
+        answer,
+        remember_choice,
+    };
+    state.pipeline_runner.ask_handler().submit_answer(human_answer)
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Translation Review Commands
+// ============================================================================
+
+/// レビュー待ちのレビューID一覧を取得
+///
+/// レビューIDは実行ID（2言語目以降は `<実行ID>:<言語>`）。他のレビューコマンドの `execution_id` に渡す。
+#[tauri::command]
+fn review_list_pending(state: State<AppState>) -> Vec<String> {
+    state.pipeline_runner.reviews().pending_ids()
+}
+
+/// レビューセッションを取得
+#[tauri::command]
+fn review_get_session(state: State<AppState>, execution_id: String) -> Option<ReviewSession> {
+    state.pipeline_runner.reviews().get(&execution_id)
+}
+
+/// セグメントの訳文を編集
+#[tauri::command]
+fn review_update_segment(
+    state: State<AppState>,
+    execution_id: String,
+    index: usize,
+    translation: String,
+) -> Result<ReviewSegment, String> {
+    state.pipeline_runner.reviews()
+        .update_segment(&execution_id, index, &translation)
+        .map_err(|e| e.to_string())
+}
+
+/// レビューを承認してパイプラインを再開
+#[tauri::command]
+fn review_approve(state: State<AppState>, execution_id: String) -> Result<(), String> {
+    state.pipeline_runner.reviews().approve(&execution_id).map_err(|e| e.to_string())
+}
+
+/// レビューを却下してパイプラインを中止
+#[tauri::command]
+fn review_reject(state: State<AppState>, execution_id: String, reason: Option<String>) -> Result<(), String> {
+    state.pipeline_runner.reviews()
+        .reject(&execution_id, reason.as_deref().unwrap_or(""))
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// CLI Executor Commands (v3 - stream-json based)
+// ============================================================================
+
+/// CLIエグゼキューターとしてレジストリに登録するカード
+fn cli_agent_card() -> AgentCard {
+    AgentCard::claude_code("cli").with_transport(Transport::Stdio)
+}
+
+/// CLIエグゼキューターを起動
+///
+/// `model`・`max_turns`・`extra_args` はそのままClaude Codeのコマンドラインに渡す。
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn executor_start(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    working_dir: Option<String>,
+    allowed_tools: Option<Vec<String>>,
+    session_id: Option<String>,
+    transition_policy: Option<InvalidTransitionPolicy>,
+    model: Option<String>,
+    max_turns: Option<u32>,
+    extra_args: Option<Vec<String>>,
+) -> Result<String, String> {
+    log::info("executor_start", &format!("Starting CLI executor (model: {:?})", model));
+
+    let options = ExecutorOptions {
+        working_dir,
+        allowed_tools: allowed_tools.unwrap_or_default(),
+        session_id,
+        transition_policy: transition_policy.unwrap_or_default(),
+        model,
+        max_turns,
+        extra_args: extra_args.unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let cli_executor = state.cli_executor.clone();
+
+    // 非同期でエグゼキューターを作成・保存
+    let mut guard = cli_executor.write().await;
+
+    let mut executor = ClaudeCodeExecutor::new(options);
+    executor.set_state_path(appdata::data_dir(&app_handle).join(EXECUTOR_STATE_FILE));
+    executor.set_app_handle(app_handle);
+    executor.set_permission_responder(state.permission_responder.clone());
+    executor.set_permission_policies(state.permission_policies.clone());
+    executor.set_canceller(state.executor_canceller.clone());
+    *state.executor_usage.lock() = UsageTotals::default();
+    executor.set_usage_counter(state.executor_usage.clone());
+
+    // 状態マシンの状態をレジストリに反映し、負荷に応じた振り分けに使う
+    let registry = state.orchestrator.lock().registry();
+    let cli_agent_id = cli_agent_card().id.unwrap_or_default();
+    executor.on_state_change(move |transition| {
+        let _ = registry.set_status(&cli_agent_id, acp::registry::AgentStatus::from_executor(&transition.to));
+    });
+
+    // 起動
+    executor.start().await
+        .map_err(|e| format!("Failed to start executor: {}", e))?;
+
+    let session_id = executor.session_id()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    *state.executor_state.lock() = Some(executor.subscribe_state());
+    *guard = Some(executor);
+
+    // ACPレジストリに登録し、エグゼキューター経由でメッセージを送れるようにする
+    {
+        let card = cli_agent_card();
+        let agent_id = card.id.clone().unwrap_or_else(|| card.name.clone());
+        let orchestrator = state.orchestrator.lock();
+        orchestrator.registry().upsert(card);
+        orchestrator
+            .attach_transport(&agent_id, Arc::new(ExecutorHandle::new(cli_executor.clone())))
+            .map_err(|e| e.to_string())?;
+    }
+
+    log::info("executor_start", &format!("CLI executor started, session: {}", session_id));
+    Ok(session_id)
+}
+
+/// 前回終了時に保存されたエグゼキューター状態を取得（なければNone）
+#[tauri::command]
+fn executor_get_saved_state(app_handle: AppHandle) -> Result<Option<ExecutorSnapshot>, String> {
+    ExecutorSnapshot::load(&appdata::data_dir(&app_handle).join(EXECUTOR_STATE_FILE))
+        .map_err(|e| e.to_string())
+}
+
+/// 記録に残っているCLIエグゼキューターの過去のセッション（新しい順）
+#[tauri::command]
+fn executor_list_sessions() -> Result<Vec<ExecutorSession>, String> {
+    acp::executor::list_sessions().map_err(|e| e.to_string())
+}
+
+/// 保存された状態または過去のセッションからCLIエグゼキューターを再開
+///
+/// 同じセッション（`--resume`）・作業ディレクトリで起動し、再開前の状態を返す。
+/// `session_id` を省略すると前回終了時のセッションを再開する。
+/// `is_interrupted` の場合は実行中だったタスクを再送する必要がある。
+#[tauri::command]
+async fn executor_resume(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    session_id: Option<String>,
+) -> Result<ExecutorSnapshot, String> {
+    let path = appdata::data_dir(&app_handle).join(EXECUTOR_STATE_FILE);
+    let saved = ExecutorSnapshot::load(&path).map_err(|e| e.to_string())?;
+    let snapshot = match session_id {
+        // 保存された状態と同じセッションなら中断中のタスクや事前許可ツールも引き継ぐ
+        Some(id) if saved.as_ref().and_then(|s| s.session_id.as_deref()) != Some(id.as_str()) => {
+            let sessions = acp::executor::list_sessions().map_err(|e| e.to_string())?;
+            let session = sessions.iter()
+                .find(|s| s.session_id == id)
+                .ok_or_else(|| format!("Session not found: {}", id))?;
+            ExecutorSnapshot::for_session(session)
+        }
+        _ => saved.ok_or("No saved executor state")?,
+    };
+
+    let cli_executor = state.cli_executor.clone();
+    let mut guard = cli_executor.write().await;
+    if guard.is_some() {
+        return Err("Executor already running".to_string());
+    }
+
+    let mut executor = ClaudeCodeExecutor::new(snapshot.resume_options());
+    executor.restore(&snapshot);
+    executor.set_state_path(path);
+    executor.set_app_handle(app_handle);
+    executor.set_permission_responder(state.permission_responder.clone());
+    executor.set_permission_policies(state.permission_policies.clone());
+    executor.set_canceller(state.executor_canceller.clone());
+    *state.executor_usage.lock() = UsageTotals::default();
+    executor.set_usage_counter(state.executor_usage.clone());
+    executor.start().await
+        .map_err(|e| format!("Failed to resume executor: {}", e))?;
+
+    *state.executor_state.lock() = Some(executor.subscribe_state());
+    *guard = Some(executor);
+
+    log::info("executor_resume", &format!(
+        "CLI executor resumed: session={:?}, previous state={}, interrupted={}",
+        snapshot.session_id, snapshot.state.state_name(), snapshot.is_interrupted()
+    ));
+    Ok(snapshot)
+}
+
+/// CLIエグゼキューターでタスクを実行
+#[tauri::command]
+async fn executor_execute(
+    state: State<'_, AppState>,
+    prompt: String,
+) -> Result<String, String> {
+    log::info("executor_execute", &format!("Executing task ({} chars)", prompt.len()));
+
+    let cli_executor = state.cli_executor.clone();
+
+    let mut guard = cli_executor.write().await;
+
+    if let Some(ref mut executor) = *guard {
+        executor.execute(&prompt).await
+            .map_err(|e| format!("Execution failed: {}", e))
+    } else {
+        Err("Executor not started".to_string())
+    }
+}
+
+/// CLIエグゼキューターでタスクを実行（完了を待たずにタスクIDを返す）
+///
+/// 出力は `executor:output`、終了は `executor:stream_completed` でタスクID付きで通知する。
+#[tauri::command]
+async fn executor_execute_streaming(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    prompt: String,
+) -> Result<String, String> {
+    if state.cli_executor.read().await.is_none() {
+        return Err("Executor not started".to_string());
+    }
+
+    let task_id = uuid::Uuid::new_v4().to_string();
+    log::info("executor_execute_streaming", &format!("Executing task {} ({} chars)", task_id, prompt.len()));
+
+    let cli_executor = state.cli_executor.clone();
+    let id = task_id.clone();
+    tokio::spawn(async move {
+        let result = {
+            let mut guard = cli_executor.write().await;
+            match *guard {
+                Some(ref mut executor) => executor.execute_streaming(&prompt, &id).await
+                    .map_err(|e| format!("Execution failed: {}", e)),
+                None => Err("Executor not started".to_string()),
+            }
+        };
+        let payload = match result {
+            Ok(output) => serde_json::json!({ "task_id": id, "output": output }),
+            Err(error) => {
+                log::error("executor_execute_streaming", &format!("Task {} failed: {}", id, error));
+                serde_json::json!({ "task_id": id, "error": error })
+            }
+        };
+        let _ = app_handle.emit_recorded("executor:stream_completed", &payload);
+    });
+
+    Ok(task_id)
+}
+
+/// CLIエグゼキューターを停止
+#[tauri::command]
+async fn executor_stop(state: State<'_, AppState>) -> Result<(), String> {
+    log::info("executor_stop", "Stopping CLI executor");
+
+    // 実行中のタスクが書き込みロックを持っているので、先に中止させてロックを手放させる
+    state.executor_canceller.cancel();
+
+    let cli_executor = state.cli_executor.clone();
+
+    let mut guard = cli_executor.write().await;
 
     if let Some(ref mut executor) = *guard {
         executor.stop().await
@@ -1144,6 +2631,7 @@ async fn executor_stop(state: State<'_, AppState>) -> Result<(), String> {
     }
 
     *guard = None;
+    *state.executor_state.lock() = None;
 
     log::info("executor_stop", "CLI executor stopped");
     Ok(())
@@ -1151,12 +2639,39 @@ async fn executor_stop(state: State<'_, AppState>) -> Result<(), String> {
 
 /// CLIエグゼキューターの状態を取得
 #[tauri::command]
-async fn executor_get_state(state: State<'_, AppState>) -> Result<AgentState, String> {
+fn executor_get_state(state: State<'_, AppState>) -> Result<AgentState, String> {
+    let current = state.executor_state.lock();
+    let rx = current.as_ref().ok_or("Executor not started")?;
+    let agent_state = rx.borrow().clone();
+    Ok(agent_state)
+}
+
+/// CLIエグゼキューターの状態遷移履歴を取得（古い順）
+///
+/// `session` を指定した場合は起動中のセッションと一致するときだけ返す。
+#[tauri::command]
+async fn executor_get_state_history(
+    state: State<'_, AppState>,
+    session: Option<String>,
+) -> Result<Vec<StateTransition>, String> {
     let cli_executor = state.cli_executor.clone();
     let guard = cli_executor.read().await;
 
     let executor = guard.as_ref().ok_or("Executor not started")?;
-    Ok(executor.current_state())
+    if let (Some(requested), Some(current)) = (session.as_deref(), executor.session_id()) {
+        if requested != current {
+            return Err(format!("Session not found: {}", requested));
+        }
+    }
+    Ok(executor.state_history())
+}
+
+/// CLIエグゼキューターの起動・再開からのトークン数・コストの合計
+///
+/// パイプライン実行ごとの合計は `PipelineExecution.usage` にある。
+#[tauri::command]
+fn executor_get_usage(state: State<'_, AppState>) -> UsageTotals {
+    *state.executor_usage.lock()
 }
 
 /// 権限要求に回答
@@ -1175,13 +2690,9 @@ async fn executor_submit_permission(
         }
     };
 
-    let cli_executor = state.cli_executor.clone();
-    let guard = cli_executor.read().await;
-
-    if let Some(ref executor) = *guard {
-        executor.submit_permission_response(&request_id, decision).await
-            .map_err(|e| format!("Failed to submit permission: {}", e))?;
-    }
+    // 実行中はエグゼキューターが書き込みロックを持っているので、共有の回答先に渡す
+    state.permission_responder.submit(&request_id, decision)
+        .map_err(|e| format!("Failed to submit permission: {}", e))?;
 
     log::info("executor_submit_permission", &format!(
         "Permission response: request_id={}, allow={}, always={}",
@@ -1191,6 +2702,41 @@ async fn executor_submit_permission(
     Ok(())
 }
 
+/// CLIエグゼキューターの回答待ちの権限要求（古い順）
+#[tauri::command]
+fn executor_pending_permissions(state: State<'_, AppState>) -> Vec<PermissionRequest> {
+    state.permission_responder.pending()
+}
+
+/// CLIエグゼキューターの権限ルール一覧
+#[tauri::command]
+fn executor_list_permission_rules(state: State<'_, AppState>) -> Vec<PermissionRule> {
+    state.permission_policies.lock().rules().to_vec()
+}
+
+/// CLIエグゼキューターの権限ルールを追加（条件が同じルールは置き換え）
+#[tauri::command]
+fn executor_add_permission_rule(
+    state: State<'_, AppState>,
+    tool: String,
+    path: Option<String>,
+    command: Option<String>,
+    action: RuleAction,
+) -> Result<PermissionRule, String> {
+    let mut rule = PermissionRule::new(tool, action);
+    rule.path = path;
+    rule.command = command;
+    let rule = state.permission_policies.lock().add(rule)?;
+    log::info("executor_add_permission_rule", &format!("Permission rule added: {} {:?}", rule.tool, rule.action));
+    Ok(rule)
+}
+
+/// CLIエグゼキューターの権限ルールを削除（存在したらtrue）
+#[tauri::command]
+fn executor_remove_permission_rule(state: State<'_, AppState>, rule_id: String) -> Result<bool, String> {
+    state.permission_policies.lock().remove(&rule_id)
+}
+
 /// CLIエグゼキューターが起動しているか確認
 #[tauri::command]
 async fn executor_is_running(state: State<'_, AppState>) -> Result<bool, String> {
@@ -1205,44 +2751,43 @@ async fn executor_is_running(state: State<'_, AppState>) -> Result<bool, String>
 
 /// VOICEVOX Engineが起動しているか確認
 #[tauri::command]
-fn voicevox_is_running(state: State<AppState>) -> bool {
-    let client = state.voicevox_client.lock();
-    client.is_running()
+async fn voicevox_is_running(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.voicevox().is_running().await)
 }
 
 /// VOICEVOXのバージョンを取得
 #[tauri::command]
-fn voicevox_get_version(state: State<AppState>) -> Result<String, String> {
-    let client = state.voicevox_client.lock();
-    client.get_version()
+async fn voicevox_get_version(state: State<'_, AppState>) -> Result<String, String> {
+    state.voicevox().get_version()
+        .await
         .map_err(|e| e.to_string())
 }
 
 /// VOICEVOX話者一覧を取得
 #[tauri::command]
-fn voicevox_get_speakers(state: State<AppState>) -> Result<Vec<Speaker>, String> {
-    let client = state.voicevox_client.lock();
-    client.get_speakers()
+async fn voicevox_get_speakers(state: State<'_, AppState>) -> Result<Vec<Speaker>, String> {
+    state.voicevox().get_speakers()
+        .await
         .map_err(|e| e.to_string())
 }
 
 /// テキストから音声を合成
 #[tauri::command]
-fn voicevox_synthesize(
-    state: State<AppState>,
+async fn voicevox_synthesize(
+    state: State<'_, AppState>,
     text: String,
     speaker: i32,
     output_path: String,
 ) -> Result<String, String> {
-    let client = state.voicevox_client.lock();
-    client.text_to_speech(&text, speaker, &output_path)
+    state.voicevox().text_to_speech(&text, speaker, &output_path)
+        .await
         .map_err(|e| e.to_string())
 }
 
 /// オプション付きでテキストから音声を合成
 #[tauri::command]
-fn voicevox_synthesize_with_options(
-    state: State<AppState>,
+async fn voicevox_synthesize_with_options(
+    state: State<'_, AppState>,
     text: String,
     speaker: i32,
     speed_scale: Option<f64>,
@@ -1251,7 +2796,6 @@ fn voicevox_synthesize_with_options(
     volume_scale: Option<f64>,
     output_path: String,
 ) -> Result<String, String> {
-    let client = state.voicevox_client.lock();
     let options = SynthesisOptions {
         speaker,
         speed_scale: speed_scale.unwrap_or(1.0),
@@ -1259,10 +2803,206 @@ fn voicevox_synthesize_with_options(
         intonation_scale: intonation_scale.unwrap_or(1.0),
         volume_scale: volume_scale.unwrap_or(1.0),
     };
-    client.text_to_speech_with_options(&text, options, &output_path)
+    state.voicevox().text_to_speech_with_options(&text, options, &output_path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 話者の試聴用クリップを合成し、WAVのパスを返す（`audio_play` で再生する）
+#[tauri::command]
+async fn voicevox_preview_speaker(
+    state: State<'_, AppState>,
+    speaker_id: i32,
+    sample_text: Option<String>,
+) -> Result<String, String> {
+    state.voicevox().preview_speaker(speaker_id, sample_text.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 複数テキストを一括合成
+///
+/// 1件終わるごとに `voicevox:progress` イベントを送り、生成したファイルの一覧を返す。
+#[tauri::command]
+async fn voicevox_synthesize_batch(
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+    texts: Vec<String>,
+    speaker: i32,
+    output_dir: String,
+) -> Result<SynthesisManifest, String> {
+    if !state.voicevox().is_running().await {
+        return Err("VOICEVOX Engine not running".to_string());
+    }
+    let manifest = state.voicevox()
+        .synthesize_batch(&texts, speaker, &output_dir, |progress| {
+            let _ = app_handle.emit_recorded("voicevox:progress", progress);
+        })
+        .await;
+    Ok(manifest)
+}
+
+/// テキストのAudioQueryを取得（モーラごとの音高・長さを編集する場合）
+#[tauri::command]
+async fn voicevox_audio_query(
+    state: State<'_, AppState>,
+    text: String,
+    speaker: i32,
+) -> Result<AudioQuery, String> {
+    state.voicevox().get_audio_query(&text, speaker)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 編集済みのAudioQueryから音声を合成
+#[tauri::command]
+async fn voicevox_synthesize_from_query(
+    state: State<'_, AppState>,
+    query: AudioQuery,
+    speaker: i32,
+    output_path: String,
+) -> Result<String, String> {
+    state.voicevox().synthesize_from_query(&query, speaker, &output_path)
+        .await
         .map_err(|e| e.to_string())
 }
 
+// ============================================================================
+// Translation Preset Commands
+// ============================================================================
+
+/// 保存済みの翻訳オプションのプリセット一覧（名前 -> オプション）
+#[tauri::command]
+fn list_translation_presets(
+    app_handle: AppHandle,
+) -> Result<std::collections::BTreeMap<String, TranslationOptions>, String> {
+    TranslationOptions::load_presets(&appdata::data_dir(&app_handle).join(PRESETS_FILE))
+        .map_err(|e| e.to_string())
+}
+
+/// 翻訳オプションをプリセットとして保存（同名は上書き）
+#[tauri::command]
+fn save_translation_preset(
+    app_handle: AppHandle,
+    name: String,
+    options: TranslationOptions,
+) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Preset name is empty".to_string());
+    }
+    options.validate().map_err(|e| e.to_string())?;
+
+    let path = appdata::data_dir(&app_handle).join(PRESETS_FILE);
+    let mut presets = TranslationOptions::load_presets(&path).map_err(|e| e.to_string())?;
+    presets.insert(name, options);
+    TranslationOptions::save_presets(&path, &presets).map_err(|e| e.to_string())
+}
+
+/// プリセットを削除（存在しなければfalse）
+#[tauri::command]
+fn delete_translation_preset(app_handle: AppHandle, name: String) -> Result<bool, String> {
+    let path = appdata::data_dir(&app_handle).join(PRESETS_FILE);
+    let mut presets = TranslationOptions::load_presets(&path).map_err(|e| e.to_string())?;
+    if presets.remove(&name).is_none() {
+        return Ok(false);
+    }
+    TranslationOptions::save_presets(&path, &presets).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+// ============================================================================
+// App Data Export/Import Commands
+// ============================================================================
+
+/// パイプライン・ポリシー・アプリ設定・設定ファイルを1つのアーカイブに書き出す
+#[tauri::command]
+fn export_app_data(
+    state: State<AppState>,
+    app_handle: AppHandle,
+    path: String,
+) -> Result<AppDataBundle, String> {
+    let mut bundle = AppDataBundle::new();
+
+    bundle.pipelines = {
+        let executor = state.pipeline_executor.lock();
+        executor.list_pipelines()
+            .into_iter()
+            .filter(|p| !BUILTIN_PIPELINE_NAMES.contains(&p.name.as_str()))
+            .collect()
+    };
+    bundle.ask_policies = state.pipeline_runner.ask_handler().policies();
+    bundle.settings = Some(appdata::exported_settings(&settings::current()).map_err(|e| e.to_string())?);
+    bundle.stores = appdata::read_stores(&appdata::data_dir(&app_handle))
+        .map_err(|e| e.to_string())?;
+
+    bundle.save(std::path::Path::new(&path)).map_err(|e| e.to_string())?;
+
+    log::info("export_app_data", &format!(
+        "Exported {} pipelines, {} policies, {} stores to {}",
+        bundle.pipelines.len(), bundle.ask_policies.len(), bundle.stores.len(), path
+    ));
+    Ok(bundle)
+}
+
+/// アーカイブを読み込んで取り込む
+///
+/// `replace_policies` がtrueの場合はAsk自動応答ポリシーを置き換え、falseなら追加する。
+/// 取り込んだパイプライン・ポリシー・設定は保存し、再起動後も使われる。
+#[tauri::command]
+fn import_app_data(
+    state: State<AppState>,
+    app_handle: AppHandle,
+    path: String,
+    replace_policies: bool,
+) -> Result<ImportSummary, String> {
+    let bundle = AppDataBundle::load(std::path::Path::new(&path)).map_err(|e| e.to_string())?;
+
+    // 設定ファイルを先に書き込む（名前が不正なら何も取り込まない）
+    let data_dir = appdata::data_dir(&app_handle);
+    let stores = appdata::write_stores(&data_dir, &bundle.stores)
+        .map_err(|e| e.to_string())?;
+
+    // 用語集ファイルが上書きされた可能性があるため読み込み直す
+    state.pipeline_runner.load_glossaries(&data_dir.join(GLOSSARY_FILE))
+        .map_err(|e| e.to_string())?;
+    state.pipeline_runner.load_translation_memory(&data_dir.join(TRANSLATION_MEMORY_FILE))
+        .map_err(|e| e.to_string())?;
+    *state.permission_policies.lock() = PolicyStore::load(&data_dir.join(POLICY_FILE))?;
+
+    if let Some(ref settings) = bundle.settings {
+        settings::update(settings).map_err(|e| e.to_string())?;
+    }
+
+    if !bundle.pipelines.is_empty() {
+        let mut store = PipelineStore::load(&data_dir.join(PIPELINES_FILE)).map_err(|e| e.to_string())?;
+        let executor = state.pipeline_executor.lock();
+        for pipeline in &bundle.pipelines {
+            executor.register(pipeline.clone());
+            store.upsert(pipeline.clone());
+        }
+        store.save().map_err(|e| e.to_string())?;
+    }
+
+    let ask_handler = state.pipeline_runner.ask_handler();
+    if replace_policies {
+        ask_handler.set_policies(bundle.ask_policies.clone());
+    } else {
+        for policy in &bundle.ask_policies {
+            ask_handler.add_policy(policy.clone());
+        }
+    }
+    ask_handler.save_policies(&data_dir.join(ASK_POLICY_FILE))?;
+
+    let summary = ImportSummary {
+        pipelines: bundle.pipelines.len(),
+        ask_policies: bundle.ask_policies.len(),
+        settings: bundle.settings.is_some(),
+        stores,
+    };
+    log::info("import_app_data", &format!("Imported from {}: {:?}", path, summary));
+    Ok(summary)
+}
+
 // ============================================================================
 // Application Entry Point
 // ============================================================================
@@ -1279,6 +3019,7 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
         .manage(AppState::new())
         .setup(move |app| {
             // タイトルバーに起動時刻を表示
@@ -1286,6 +3027,154 @@ pub fn run() {
                 let title = format!("Re-Voice [{}]", start_time);
                 window.set_title(&title).ok();
             }
+
+            // 設定を読み込む（読み込めなければ既定値のまま）
+            let settings_path = settings::settings_path(app.handle());
+            match settings::init(&settings_path) {
+                Ok(_) => app.state::<AppState>().apply_settings(),
+                Err(e) => log::error("APP", &format!("Failed to load settings from {}: {}", settings_path.display(), e)),
+            }
+
+            // 用語集を読み込む
+            let glossary_path = appdata::data_dir(app.handle()).join(GLOSSARY_FILE);
+            let state = app.state::<AppState>();
+            if let Err(e) = state.pipeline_runner.load_glossaries(&glossary_path) {
+                log::error("APP", &format!("Failed to load glossaries: {}", e));
+            }
+
+            // 翻訳メモリを読み込む
+            let memory_path = appdata::data_dir(app.handle()).join(TRANSLATION_MEMORY_FILE);
+            if let Err(e) = state.pipeline_runner.load_translation_memory(&memory_path) {
+                log::error("APP", &format!("Failed to load translation memory: {}", e));
+            }
+
+            // 保存済みのパイプラインを読み込む
+            let pipelines_path = appdata::data_dir(app.handle()).join(PIPELINES_FILE);
+            match PipelineStore::load(&pipelines_path) {
+                Ok(store) => {
+                    let count = store.restore_into(&state.pipeline_executor.lock());
+                    log::info("APP", &format!("Loaded {} saved pipelines", count));
+                }
+                Err(e) => log::error("APP", &format!("Failed to load pipelines: {}", e)),
+            }
+
+            // エージェント間のメッセージ履歴を読み込む
+            let history_path = appdata::data_dir(app.handle()).join(HISTORY_FILE);
+            match MessageHistory::load(&history_path) {
+                Ok(history) => state.orchestrator.lock().set_history(history),
+                Err(e) => log::error("APP", &format!("Failed to load message history: {}", e)),
+            }
+
+            // 実行時に登録したエージェントを復元する
+            let registry_path = appdata::data_dir(app.handle()).join(REGISTRY_FILE);
+            match RegistryStore::load(&registry_path) {
+                Ok(store) => {
+                    let orchestrator = state.orchestrator.lock();
+                    for card in store.cards() {
+                        let restored = orchestrator
+                            .register_agent_card(card.clone())
+                            .map_err(|e| e.to_string())
+                            .and_then(|_| attach_pty_transport(&state, &orchestrator, card));
+                        if let Err(e) = restored {
+                            log::warn("APP", &format!("Failed to restore agent {}: {}", card.name, e));
+                        }
+                    }
+                    log::info("APP", &format!("Restored {} registered agents", store.cards().len()));
+                    drop(orchestrator);
+                    *state.registry_store.lock() = store;
+                }
+                Err(e) => log::error("APP", &format!("Failed to load agent registry: {}", e)),
+            }
+
+            // エグゼキューターの権限ルールを読み込む
+            let policy_path = appdata::data_dir(app.handle()).join(POLICY_FILE);
+            match PolicyStore::load(&policy_path) {
+                Ok(store) => {
+                    log::info("APP", &format!("Loaded {} permission rules", store.rules().len()));
+                    *state.permission_policies.lock() = store;
+                }
+                Err(e) => log::error("APP", &format!("Failed to load permission rules: {}", e)),
+            }
+
+            // Ask自動応答ポリシーを読み込む（保存されていなければ既定のまま）
+            let ask_policy_path = appdata::data_dir(app.handle()).join(ASK_POLICY_FILE);
+            match state.pipeline_runner.ask_handler().load_policies(&ask_policy_path) {
+                Ok(count) => log::info("APP", &format!("Loaded {} ask policies", count)),
+                Err(e) => log::error("APP", &format!("Failed to load ask policies: {}", e)),
+            }
+
+            // PTY・エグゼキューターのやり取りを記録する
+            transcript::init(&appdata::data_dir(app.handle()).join(transcript::TRANSCRIPTS_DIR));
+
+            // APIキーを読み込む
+            let secrets_path = SecretStore::path_in(&appdata::data_dir(app.handle()));
+            if let Err(e) = state.pipeline_runner.load_secrets(&secrets_path) {
+                log::error("APP", &format!("Failed to load secrets: {}", e));
+            }
+
+            // 組み込みのパイプラインテンプレートを登録する
+            let template_id = state.pipeline_runner.register_templates();
+            log::info("APP", &format!("Registered local video pipeline template: {}", template_id));
+
+            // 共有コンテキストの変更をフロントエンドに通知し、期限切れのキーを定期的に消す
+            let handle = app.handle().clone();
+            state.orchestrator.lock().subscribe_context(move |change| {
+                let _ = handle.emit_recorded("acp:context_changed", change);
+            });
+
+            // agents/ ディレクトリのエージェントカードを監視して登録・更新・削除する
+            let agents_dir = appdata::data_dir(app.handle()).join(AGENTS_DIR);
+            if let Err(e) = std::fs::create_dir_all(&agents_dir) {
+                log::error("APP", &format!("Failed to create {}: {}", agents_dir.display(), e));
+            }
+            let handle = app.handle().clone();
+            let registry = state.orchestrator.lock().registry();
+            let watcher = CardWatcher::start(
+                CardDirectory::new(agents_dir),
+                registry,
+                std::time::Duration::from_secs(2),
+                move |report| {
+                    let _ = handle.emit_recorded("acp:agents_changed", report);
+                },
+            );
+            *state.card_watcher.lock() = Some(watcher);
+
+            *state.pty_watchdog.lock() = Some(PtyWatchdog::start(
+                state.pty.clone(),
+                std::time::Duration::from_secs(1),
+            ));
+
+            // 設定されていればエージェントカードサーバーを開く
+            if let Some(addr) = settings::current().agent_server_addr {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let token = match settings::agent_server_token() {
+                        Ok(token) => token,
+                        Err(e) => {
+                            log::error("APP", &format!("Failed to create agent server token: {}", e));
+                            return;
+                        }
+                    };
+                    match AgentServer::start(&addr, AgentCard::re_voice(), &token, inbound_task_handler(handle.clone())).await {
+                        Ok(server) => *handle.state::<AppState>().agent_server.lock() = Some(server),
+                        Err(e) => log::error("APP", &format!("Failed to start agent server on {}: {}", addr, e)),
+                    }
+                });
+            }
+
+            let orchestrator = state.orchestrator.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+                loop {
+                    interval.tick().await;
+                    let orchestrator = orchestrator.lock();
+                    orchestrator.purge_expired_context();
+                    // 閉じていたトランスポートが開いたら溜まっていたメッセージを送る
+                    for (agent_id, message_id, e) in orchestrator.flush_queues() {
+                        log::warn("Orchestrator", &format!("Failed to deliver {} to {}: {}", message_id, agent_id, e));
+                    }
+                }
+            });
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -1293,34 +3182,78 @@ pub fn run() {
             spawn_claude,
             send_to_claude,
             read_from_claude,
+            read_from_claude_since,
             get_claude_response,
             is_claude_running,
             is_child_alive,
+            spawn_agent_pty,
+            get_platform_capabilities,
+            kill_claude,
+            restart_claude,
+            set_pty_raw_mode,
+            get_auto_response_audit,
+            list_transcripts,
+            export_transcript,
             get_child_pid,
             execute_command,
             pty_test_roundtrip,
             // ACP commands
             acp_register_agent,
+            acp_update_agent,
+            acp_unregister_agent,
             acp_discover_agents,
             acp_list_agents,
             acp_get_agent,
             acp_send_message,
+            acp_send_and_wait,
+            acp_dispatch,
+            acp_get_history,
+            acp_search_history,
+            acp_subscribe_agent,
+            acp_unsubscribe_agent,
+            acp_close_agent,
+            acp_spawn_stdio_agent,
+            acp_connect_remote,
+            acp_disconnect_remote,
+            acp_start_agent_server,
+            acp_stop_agent_server,
+            acp_get_agent_server_token,
+            acp_discover_remote,
             acp_get_response,
             acp_broadcast,
             acp_get_task,
             acp_stats,
             acp_get_context,
+            acp_context_set,
+            acp_context_get,
+            acp_context_delete,
+            acp_context_clear,
             // YouTube/Subtitle commands
             check_ytdlp_available,
             youtube_download_subtitle,
             youtube_list_subs,
+            youtube_download_media,
+            youtube_get_metadata,
             get_available_subtitles,
             download_subtitles,
             download_auto_subtitles,
+            check_disk_space,
+            check_environment,
+            estimate_synthesis_space,
+            ffmpeg_check_available,
+            audio_play,
+            audio_stop,
+            audio_waveform,
+            export_dubbed_video,
+            export_chapters,
+            live_start,
+            live_stop,
+            live_list,
             // tmux test commands (ACP v2 PoC)
             tmux_create_session,
             tmux_spawn_agent,
             tmux_capture_pane,
+            tmux_capture_since,
             tmux_send_message,
             tmux_get_status,
             tmux_list_agents,
@@ -1338,24 +3271,74 @@ pub fn run() {
             acp_cancel_pipeline,
             acp_list_pipelines,
             acp_list_active_executions,
+            acp_save_pipeline,
+            acp_load_pipelines,
             acp_broadcast_v3,
             acp_broadcast_to_idle,
             acp_discover_agents_v3,
             acp_stats_v3,
             // Pipeline Runner commands (Phase 3)
             run_subtitle_pipeline,
+            run_batch_pipeline,
+            run_subtitle_pipeline_resume,
+            run_local_subtitle_pipeline,
+            run_local_video_pipeline,
+            run_registered_pipeline,
+            run_custom_pipeline,
             get_pipeline_execution,
             list_active_pipeline_executions,
+            pause_pipeline_execution,
+            resume_pipeline_execution,
             cancel_pipeline_execution,
+            // Watch folder commands
+            watcher_start,
+            watcher_stop,
+            watcher_status,
+            // Glossary commands
+            glossary_list_projects,
+            glossary_get,
+            glossary_set,
+            glossary_remove,
+            translation_memory_stats,
+            get_settings,
+            update_settings,
+            get_recent_logs,
+            set_log_level,
+            get_event_history,
+            get_event_channels,
+            translation_memory_clear,
+            // Translation provider commands
+            secrets_list,
+            secrets_set,
+            secrets_remove,
+            deepl_get_usage,
+            ollama_list_models,
+            ollama_check_model,
             // Ask Tool commands (ACP v3)
             acp_get_pending_questions,
             acp_submit_answer,
+            // Translation review commands
+            review_list_pending,
+            review_get_session,
+            review_update_segment,
+            review_approve,
+            review_reject,
             // CLI Executor commands (v3 - stream-json based)
             executor_start,
             executor_execute,
+            executor_execute_streaming,
             executor_stop,
             executor_get_state,
+            executor_get_state_history,
+            executor_get_saved_state,
+            executor_resume,
+            executor_list_sessions,
+            executor_get_usage,
             executor_submit_permission,
+            executor_pending_permissions,
+            executor_list_permission_rules,
+            executor_add_permission_rule,
+            executor_remove_permission_rule,
             executor_is_running,
             // VOICEVOX commands
             voicevox_is_running,
@@ -1363,6 +3346,17 @@ pub fn run() {
             voicevox_get_speakers,
             voicevox_synthesize,
             voicevox_synthesize_with_options,
+            voicevox_synthesize_batch,
+            voicevox_preview_speaker,
+            voicevox_audio_query,
+            voicevox_synthesize_from_query,
+            // Translation presets
+            list_translation_presets,
+            save_translation_preset,
+            delete_translation_preset,
+            // App data export/import
+            export_app_data,
+            import_app_data,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,436 @@
+//! ライブ配信の吹替
+//!
+//! 伸びていく字幕ファイル（yt-dlpのライブ字幕や、字幕WebSocketを書き出す外部ツールの出力）を
+//! 追いかけ、数件ずつ翻訳・音声合成して `live:audio_chunk` イベントで順に通知する。
+//! 遅延を抑えるため、バッチは件数か待ち時間のどちらかに達したら送る。
+
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::process::{Child, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::acp::subtitle_parser::{SubtitleSegment, VttParser};
+use crate::acp::translator::{primary_language, TranslateOptions, TranslationProvider, Translator};
+use crate::events::RecordedEmit;
+use crate::process_env;
+use crate::youtube::YtdlpCookies;
+use crate::voicevox::VoicevoxClient;
+
+/// ライブ吹替の設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveOptions {
+    /// 出力先（字幕・合成音声）
+    pub output_dir: String,
+    /// 字幕の言語
+    #[serde(default = "default_source_lang")]
+    pub source_lang: String,
+    /// 翻訳先の言語
+    #[serde(default = "default_target_lang")]
+    pub target_lang: String,
+    /// 翻訳プロバイダー
+    #[serde(default)]
+    pub provider: TranslationProvider,
+    /// LLMプロバイダーのモデル名
+    #[serde(default)]
+    pub model: Option<String>,
+    /// VOICEVOX話者ID
+    #[serde(default = "default_speaker")]
+    pub speaker: i32,
+    /// 1回に翻訳する字幕の件数
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// 件数に満たなくても送るまでの待ち時間（ミリ秒）
+    #[serde(default = "default_max_wait_ms")]
+    pub max_wait_ms: u64,
+    /// 字幕ファイルを確認する間隔（ミリ秒）
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// 外部ツールが書き出す字幕ファイル（指定時はyt-dlpを起動しない）
+    #[serde(default)]
+    pub caption_file: Option<String>,
+    /// yt-dlpに渡すcookie（メンバー限定配信など）
+    #[serde(default)]
+    pub cookies: YtdlpCookies,
+}
+
+fn default_source_lang() -> String {
+    "en".to_string()
+}
+
+fn default_target_lang() -> String {
+    "ja".to_string()
+}
+
+fn default_speaker() -> i32 {
+    1
+}
+
+fn default_batch_size() -> usize {
+    3
+}
+
+fn default_max_wait_ms() -> u64 {
+    2000
+}
+
+fn default_poll_interval_ms() -> u64 {
+    500
+}
+
+/// 合成済みの音声チャンク（`live:audio_chunk` イベント）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveChunk {
+    pub session_id: String,
+    /// 連番
+    pub index: u32,
+    /// 配信内の時刻（ミリ秒）
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub source: String,
+    pub translation: String,
+    /// 合成した音声（VOICEVOXが使えなければNone）
+    pub audio_path: Option<String>,
+}
+
+/// 翻訳に失敗したバッチ（`live:translation_failed` イベント、セッションは続行）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveTranslationFailed {
+    pub session_id: String,
+    /// 翻訳できなかった字幕の連番
+    pub indices: Vec<u32>,
+    pub error: String,
+}
+
+/// セッション終了（`live:stopped` イベント）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveStopped {
+    pub session_id: String,
+    /// 異常終了の場合の理由
+    pub error: Option<String>,
+}
+
+/// 伸びていくVTTから新しい字幕だけを取り出す
+///
+/// 自動字幕は前の行を含んだまま流れてくるため、出力済みの行と時刻より前の字幕は捨てる。
+#[derive(Debug, Default)]
+pub struct CaptionTail {
+    pending: String,
+    last_end_ms: u64,
+    last_line: String,
+    next_index: u32,
+}
+
+impl CaptionTail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追記されたテキストを渡し、確定した新しい字幕を返す
+    pub fn push(&mut self, appended: &str) -> Vec<SubtitleSegment> {
+        self.pending.push_str(&appended.replace("\r\n", "\n"));
+
+        // 空行で終わっていない最後のブロックは書き込み途中の可能性がある
+        let Some(boundary) = self.pending.rfind("\n\n") else {
+            return Vec::new();
+        };
+        let complete: String = self.pending.drain(..boundary + 2).collect();
+        let Ok(segments) = VttParser::parse(&format!("WEBVTT\n\n{}", complete)) else {
+            return Vec::new();
+        };
+
+        let mut fresh = Vec::new();
+        for segment in segments {
+            if segment.start_ms < self.last_end_ms && segment.end_ms <= self.last_end_ms {
+                continue;
+            }
+            let lines: Vec<&str> = segment
+                .text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .skip_while(|line| *line == self.last_line)
+                .collect();
+            if lines.is_empty() {
+                continue;
+            }
+
+            self.last_line = lines[lines.len() - 1].to_string();
+            self.last_end_ms = self.last_end_ms.max(segment.end_ms);
+            fresh.push(SubtitleSegment::new(self.next_index, segment.start_ms, segment.end_ms, lines.join(" ")));
+            self.next_index += 1;
+        }
+        fresh
+    }
+}
+
+/// 字幕を件数・待ち時間でまとめる
+#[derive(Debug)]
+struct Batcher {
+    size: usize,
+    max_wait: Duration,
+    items: Vec<SubtitleSegment>,
+    since: Option<Instant>,
+}
+
+impl Batcher {
+    fn new(size: usize, max_wait: Duration) -> Self {
+        Self { size: size.max(1), max_wait, items: Vec::new(), since: None }
+    }
+
+    fn extend(&mut self, segments: Vec<SubtitleSegment>) {
+        if !segments.is_empty() && self.since.is_none() {
+            self.since = Some(Instant::now());
+        }
+        self.items.extend(segments);
+    }
+
+    /// 送るべきバッチがあれば取り出す
+    fn take_ready(&mut self, now: Instant) -> Option<Vec<SubtitleSegment>> {
+        let waited = self.since.map(|since| now.duration_since(since) >= self.max_wait).unwrap_or(false);
+        if self.items.len() < self.size && !waited {
+            return None;
+        }
+        self.since = None;
+        Some(std::mem::take(&mut self.items))
+    }
+}
+
+/// 実行中のセッション
+struct LiveSession {
+    stop: Arc<AtomicBool>,
+    options: LiveOptions,
+}
+
+/// ライブ吹替セッションの管理
+#[derive(Default)]
+pub struct LiveManager {
+    sessions: Mutex<HashMap<String, LiveSession>>,
+}
+
+impl LiveManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// セッションを開始し、セッションIDを返す
+    pub fn start(
+        self: &Arc<Self>,
+        url: &str,
+        options: LiveOptions,
+        translator: Box<dyn Translator>,
+        app_handle: AppHandle,
+    ) -> Result<String, String> {
+        std::fs::create_dir_all(format!("{}/live", options.output_dir)).map_err(|e| e.to_string())?;
+
+        // 字幕ファイル（外部ツールの出力か、yt-dlpに書かせる）
+        let (caption_file, child) = match options.caption_file {
+            Some(ref path) => (path.clone(), None),
+            None => {
+                let child = spawn_caption_downloader(url, &options)?;
+                let path = format!("{}/live/captions.{}.vtt", options.output_dir, options.source_lang);
+                (path, Some(child))
+            }
+        };
+
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let stop = Arc::new(AtomicBool::new(false));
+        self.sessions.lock().insert(session_id.clone(), LiveSession { stop: stop.clone(), options: options.clone() });
+
+        crate::log::info("Live", &format!("Session {} started: {}", session_id, url));
+
+        let manager = self.clone();
+        let id = session_id.clone();
+        tauri::async_runtime::spawn(async move {
+            let result = run_session(&id, &caption_file, &options, translator.as_ref(), &stop, &app_handle).await;
+            if let Some(mut child) = child {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            manager.sessions.lock().remove(&id);
+
+            let error = result.err();
+            if let Some(ref e) = error {
+                crate::log::error("Live", &format!("Session {} failed: {}", id, e));
+            }
+            let _ = app_handle.emit_recorded("live:stopped", LiveStopped { session_id: id, error });
+        });
+
+        Ok(session_id)
+    }
+
+    /// セッションを停止する
+    pub fn stop(&self, session_id: &str) -> bool {
+        match self.sessions.lock().get(session_id) {
+            Some(session) => {
+                session.stop.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 実行中のセッション（ID・出力先）
+    pub fn list(&self) -> Vec<(String, String)> {
+        self.sessions
+            .lock()
+            .iter()
+            .map(|(id, session)| (id.clone(), session.options.output_dir.clone()))
+            .collect()
+    }
+}
+
+/// yt-dlpでライブ字幕の書き出しを始める
+fn spawn_caption_downloader(url: &str, options: &LiveOptions) -> Result<Child, String> {
+    let output = format!("{}/live/captions", options.output_dir);
+    process_env::command(&crate::settings::current().ytdlp_path)
+        .args(options.cookies.args())
+        .args([
+            "--skip-download",
+            "--live-from-start",
+            "--write-subs",
+            "--write-auto-subs",
+            "--sub-format", "vtt",
+            "--sub-langs", &options.source_lang,
+            "-o", &output,
+            url,
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("yt-dlpを起動できません: {}", e))
+}
+
+/// 字幕ファイルを追いかけて翻訳・合成する
+async fn run_session(
+    session_id: &str,
+    caption_file: &str,
+    options: &LiveOptions,
+    translator: &dyn Translator,
+    stop: &AtomicBool,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
+    let mut tail = CaptionTail::new();
+    let mut batcher = Batcher::new(options.batch_size, Duration::from_millis(options.max_wait_ms));
+    let mut offset = 0u64;
+    let translate_options = TranslateOptions {
+        source_lang: Some(options.source_lang.clone()),
+        target_lang: options.target_lang.clone(),
+        ..Default::default()
+    };
+    let voicevox = VoicevoxClient::new();
+    let voicevox_ready = primary_language(&options.target_lang) == "ja" && voicevox.is_running().await;
+
+    while !stop.load(Ordering::SeqCst) {
+        if let Some(appended) = read_appended(caption_file, &mut offset) {
+            batcher.extend(tail.push(&appended));
+        }
+
+        if let Some(batch) = batcher.take_ready(Instant::now()) {
+            let sources: Vec<(usize, String)> = batch.iter().map(|s| (s.index as usize, s.text.clone())).collect();
+            // 一時的な失敗でセッションを終わらせず、このバッチだけ飛ばす
+            let translations = match translator.translate_segments(&sources, &translate_options).await {
+                Ok(translations) => translations,
+                Err(e) => {
+                    crate::log::warn("Live", &format!("Session {}: translation failed: {}", session_id, e));
+                    let _ = app_handle.emit_recorded("live:translation_failed", LiveTranslationFailed {
+                        session_id: session_id.to_string(),
+                        indices: batch.iter().map(|s| s.index).collect(),
+                        error: e.to_string(),
+                    });
+                    Default::default()
+                }
+            };
+
+            for segment in batch {
+                let Some(translation) = translations.get(&(segment.index as usize)).cloned() else {
+                    continue;
+                };
+                let audio_path = if voicevox_ready {
+                    synthesize(&voicevox, &translation, options.speaker, &format!("{}/live/chunk_{:05}.wav", options.output_dir, segment.index)).await
+                } else {
+                    None
+                };
+                let _ = app_handle.emit_recorded("live:audio_chunk", LiveChunk {
+                    session_id: session_id.to_string(),
+                    index: segment.index,
+                    start_ms: segment.start_ms,
+                    end_ms: segment.end_ms,
+                    source: segment.text,
+                    translation,
+                    audio_path,
+                });
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(options.poll_interval_ms)).await;
+    }
+
+    crate::log::info("Live", &format!("Session {} stopped", session_id));
+    Ok(())
+}
+
+/// 前回の位置以降に追記された行を読む（書き込み途中の行は次回に回す）
+fn read_appended(path: &str, offset: &mut u64) -> Option<String> {
+    let mut file = std::fs::File::open(path).ok()?;
+    file.seek(SeekFrom::Start(*offset)).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+
+    let end = bytes.iter().rposition(|b| *b == b'\n')? + 1;
+    *offset += end as u64;
+    Some(String::from_utf8_lossy(&bytes[..end]).to_string())
+}
+
+/// 1件を合成する（失敗した場合はNone）
+async fn synthesize(client: &VoicevoxClient, text: &str, speaker: i32, path: &str) -> Option<String> {
+    client.text_to_speech(text, speaker, path)
+        .await
+        .map_err(|e| crate::log::warn("Live", &format!("VOICEVOX error: {}", e)))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caption_tail_rolling_captions() {
+        let mut tail = CaptionTail::new();
+        let first = tail.push("WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nhello everyone\n\n00:00:02.000 --> 00:00:03");
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].text, "hello everyone");
+
+        // 書き込み途中のブロックは続きが来てから確定する
+        let second = tail.push(".000\nhello everyone\nwelcome back\n\n");
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].text, "welcome back");
+        assert_eq!(second[0].index, 1);
+
+        // 既に出力した時刻の字幕は捨てる
+        assert!(tail.push("00:00:01.000 --> 00:00:02.000\nhello everyone\n\n").is_empty());
+    }
+
+    #[test]
+    fn test_batcher() {
+        let mut batcher = Batcher::new(2, Duration::from_millis(100));
+        let now = Instant::now();
+        assert!(batcher.take_ready(now).is_none());
+
+        batcher.extend(vec![SubtitleSegment::new(0, 0, 1, "a".to_string())]);
+        assert!(batcher.take_ready(Instant::now()).is_none());
+        let later = Instant::now() + Duration::from_millis(150);
+        assert_eq!(batcher.take_ready(later).unwrap().len(), 1);
+
+        batcher.extend(vec![
+            SubtitleSegment::new(1, 0, 1, "b".to_string()),
+            SubtitleSegment::new(2, 0, 1, "c".to_string()),
+        ]);
+        assert_eq!(batcher.take_ready(Instant::now()).unwrap().len(), 2);
+    }
+}
@@ -1,59 +1,194 @@
 //! ログユーティリティ
 //!
-//! ログをファイルに出力し、デバッグしやすくする。
+//! レベル・ターゲット（モジュール名）付きのログを標準エラー出力とファイルに出力する。
+//! ファイルが一定サイズを超えたらアーカイブに移して新しいファイルに切り替え、
+//! 直近のログはメモリにも保持してアプリ内のログビューアから取得できるようにする。
 
+use std::collections::{HashMap, VecDeque};
 use std::fs::{self, File, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+/// ファイルを切り替えるサイズ（バイト）
+const DEFAULT_MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// メモリに保持するログの件数
+const DEFAULT_MAX_RECORDS: usize = 2000;
+
+/// `get_recent_logs` で件数を指定しなかったときに返す件数
+const DEFAULT_QUERY_LIMIT: usize = 200;
+
+/// ログレベル
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(format!("Unknown log level: {}", s)),
+        }
+    }
+}
+
+/// ログ1件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    /// 連番（`since_seq` で続きを取得する）
+    pub seq: u64,
+    pub timestamp: String,
+    pub level: LogLevel,
+    /// 出力元（モジュール名・コマンド名）
+    pub target: String,
+    pub message: String,
+}
+
+/// ログの絞り込み条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LogFilter {
+    /// このレベル以上
+    pub level: Option<LogLevel>,
+    /// ターゲットに含まれる文字列（大文字小文字は区別しない）
+    pub target: Option<String>,
+    /// メッセージに含まれる文字列（大文字小文字は区別しない）
+    pub contains: Option<String>,
+    /// この連番より後
+    pub since_seq: Option<u64>,
+    /// 最大件数（新しいものから数える）
+    pub limit: Option<usize>,
+}
+
+impl LogFilter {
+    fn matches(&self, record: &LogRecord) -> bool {
+        if self.level.is_some_and(|level| record.level < level) {
+            return false;
+        }
+        if self.since_seq.is_some_and(|seq| record.seq <= seq) {
+            return false;
+        }
+        if let Some(ref target) = self.target {
+            if !record.target.to_lowercase().contains(&target.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(ref contains) = self.contains {
+            if !record.message.to_lowercase().contains(&contains.to_lowercase()) {
+                return false;
+            }
+        }
+        true
+    }
+}
 
 /// ロガー
 pub struct Logger {
     log_dir: PathBuf,
     current_log: PathBuf,
-    file: Option<Mutex<File>>,
+    file: Option<File>,
+    /// 現在のファイルに書き込んだバイト数
+    file_bytes: u64,
+    max_file_bytes: u64,
+    /// 既定のレベル
+    level: LogLevel,
+    /// ターゲットごとのレベル
+    target_levels: HashMap<String, LogLevel>,
+    /// 直近のログ
+    recent: VecDeque<LogRecord>,
+    max_records: usize,
+    next_seq: u64,
 }
 
 impl Logger {
     /// ロガーを作成
     pub fn new() -> Self {
-        let log_dir = PathBuf::from("logs");
+        Self::with_dir("logs")
+    }
+
+    /// 出力先ディレクトリを指定して作成
+    pub fn with_dir(log_dir: impl AsRef<Path>) -> Self {
+        let log_dir = log_dir.as_ref().to_path_buf();
         let current_log = log_dir.join("current.log");
 
         Self {
             log_dir,
             current_log,
             file: None,
+            file_bytes: 0,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            level: if cfg!(debug_assertions) { LogLevel::Debug } else { LogLevel::Info },
+            target_levels: HashMap::new(),
+            recent: VecDeque::new(),
+            max_records: DEFAULT_MAX_RECORDS,
+            next_seq: 1,
         }
     }
 
+    /// ファイルを切り替えるサイズを設定
+    pub fn with_max_file_bytes(mut self, bytes: u64) -> Self {
+        self.max_file_bytes = bytes;
+        self
+    }
+
     /// ログを初期化
     pub fn init(&mut self) -> std::io::Result<()> {
         // logsディレクトリを作成
         fs::create_dir_all(&self.log_dir)?;
 
         // 古いログをアーカイブ
-        self.archive_old_log()?;
+        self.archive_current_log()?;
 
         // 新しいログファイルを作成
+        self.open_file()?;
+
+        // 起動ログ
+        self.info("LOGGER", "Log initialized");
+
+        Ok(())
+    }
+
+    /// 新しいログファイルを開く
+    fn open_file(&mut self) -> std::io::Result<()> {
         let file = OpenOptions::new()
             .create(true)
             .write(true)
             .truncate(true)
             .open(&self.current_log)?;
 
-        self.file = Some(Mutex::new(file));
-
-        // 起動ログ
-        self.log("LOGGER", "Log initialized");
-
+        self.file = Some(file);
+        self.file_bytes = 0;
         Ok(())
     }
 
-    /// 古いログをアーカイブ
-    fn archive_old_log(&self) -> std::io::Result<()> {
+    /// 現在のログをアーカイブ
+    fn archive_current_log(&self) -> std::io::Result<()> {
         if !self.current_log.exists() {
             return Ok(());
         }
@@ -62,10 +197,14 @@ impl Logger {
         let archive_dir = self.log_dir.join("archive");
         fs::create_dir_all(&archive_dir)?;
 
-        // タイムスタンプ付きファイル名
+        // タイムスタンプ付きファイル名（同じ秒に切り替えても上書きしない）
         let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-        let archived_name = format!("{}.log", timestamp);
-        let archived_path = archive_dir.join(archived_name);
+        let mut archived_path = archive_dir.join(format!("{}.log", timestamp));
+        let mut n = 1;
+        while archived_path.exists() {
+            archived_path = archive_dir.join(format!("{}_{}.log", timestamp, n));
+            n += 1;
+        }
 
         // 移動
         fs::rename(&self.current_log, &archived_path)?;
@@ -76,8 +215,18 @@ impl Logger {
         Ok(())
     }
 
+    /// サイズを超えたらファイルを切り替える
+    fn rotate_if_needed(&mut self) -> std::io::Result<()> {
+        if self.file.is_none() || self.file_bytes < self.max_file_bytes {
+            return Ok(());
+        }
+        self.file = None;
+        self.archive_current_log()?;
+        self.open_file()
+    }
+
     /// 古いアーカイブを削除
-    fn cleanup_old_archives(&self, archive_dir: &PathBuf) -> std::io::Result<()> {
+    fn cleanup_old_archives(&self, archive_dir: &Path) -> std::io::Result<()> {
         let now = std::time::SystemTime::now();
         let seven_days = std::time::Duration::from_secs(7 * 24 * 60 * 60);
 
@@ -99,41 +248,97 @@ impl Logger {
         Ok(())
     }
 
+    /// ターゲットに適用されるレベル
+    fn level_for(&self, target: &str) -> LogLevel {
+        self.target_levels.get(target).copied().unwrap_or(self.level)
+    }
+
+    /// レベルを設定（ターゲットを指定しなければ既定のレベル）
+    pub fn set_level(&mut self, target: Option<&str>, level: LogLevel) {
+        match target {
+            Some(target) => {
+                self.target_levels.insert(target.to_string(), level);
+            }
+            None => self.level = level,
+        }
+    }
+
     /// ログを出力
-    pub fn log(&self, tag: &str, message: &str) {
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
-        let log_line = format!("[{}] [{}] {}\n", timestamp, tag, message);
+    pub fn log(&mut self, level: LogLevel, target: &str, message: &str) {
+        if level < self.level_for(target) {
+            return;
+        }
+
+        let record = LogRecord {
+            seq: self.next_seq,
+            timestamp: Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+            level,
+            target: target.to_string(),
+            message: message.to_string(),
+        };
+        self.next_seq += 1;
+
+        let log_line = format!(
+            "[{}] [{}/{}] {}\n",
+            record.timestamp,
+            level.as_str(),
+            record.target,
+            record.message
+        );
 
         // 標準エラー出力にも出力
         eprint!("{}", log_line);
 
         // ファイルに出力
-        if let Some(ref file_mutex) = self.file {
-            if let Ok(mut file) = file_mutex.lock() {
-                let _ = file.write_all(log_line.as_bytes());
+        if let Some(ref mut file) = self.file {
+            if file.write_all(log_line.as_bytes()).is_ok() {
                 let _ = file.flush();
+                self.file_bytes += log_line.len() as u64;
             }
         }
+        if let Err(e) = self.rotate_if_needed() {
+            eprintln!("[LOGGER] Failed to rotate log: {}", e);
+        }
+
+        self.recent.push_back(record);
+        while self.recent.len() > self.max_records {
+            self.recent.pop_front();
+        }
+    }
+
+    /// 条件に合う直近のログ（古い順）
+    pub fn recent(&self, filter: &LogFilter) -> Vec<LogRecord> {
+        let limit = filter.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+        let mut records: Vec<LogRecord> = self
+            .recent
+            .iter()
+            .rev()
+            .filter(|r| filter.matches(r))
+            .take(limit)
+            .cloned()
+            .collect();
+        records.reverse();
+        records
     }
 
     /// デバッグログ
-    pub fn debug(&self, tag: &str, message: &str) {
-        self.log(&format!("DEBUG/{}", tag), message);
+    pub fn debug(&mut self, tag: &str, message: &str) {
+        self.log(LogLevel::Debug, tag, message);
     }
 
     /// 情報ログ
-    pub fn info(&self, tag: &str, message: &str) {
-        self.log(&format!("INFO/{}", tag), message);
+    pub fn info(&mut self, tag: &str, message: &str) {
+        self.log(LogLevel::Info, tag, message);
     }
 
     /// エラーログ
-    pub fn error(&self, tag: &str, message: &str) {
-        self.log(&format!("ERROR/{}", tag), message);
+    pub fn error(&mut self, tag: &str, message: &str) {
+        self.log(LogLevel::Error, tag, message);
     }
 
     /// 警告ログ
-    pub fn warn(&self, tag: &str, message: &str) {
-        self.log(&format!("WARN/{}", tag), message);
+    pub fn warn(&mut self, tag: &str, message: &str) {
+        self.log(LogLevel::Warn, tag, message);
     }
 }
 
@@ -156,37 +361,100 @@ pub fn init_logger() -> std::io::Result<()> {
     logger.init()
 }
 
-/// ログを出力
+/// ログを出力（情報レベル）
 pub fn log(tag: &str, message: &str) {
-    if let Ok(logger) = GLOBAL_LOGGER.lock() {
-        logger.log(tag, message);
-    }
+    info(tag, message);
 }
 
 /// デバッグログ
 pub fn debug(tag: &str, message: &str) {
-    if let Ok(logger) = GLOBAL_LOGGER.lock() {
+    if let Ok(mut logger) = GLOBAL_LOGGER.lock() {
         logger.debug(tag, message);
     }
 }
 
 /// 情報ログ
 pub fn info(tag: &str, message: &str) {
-    if let Ok(logger) = GLOBAL_LOGGER.lock() {
+    if let Ok(mut logger) = GLOBAL_LOGGER.lock() {
         logger.info(tag, message);
     }
 }
 
 /// エラーログ
 pub fn error(tag: &str, message: &str) {
-    if let Ok(logger) = GLOBAL_LOGGER.lock() {
+    if let Ok(mut logger) = GLOBAL_LOGGER.lock() {
         logger.error(tag, message);
     }
 }
 
 /// 警告ログ
 pub fn warn(tag: &str, message: &str) {
-    if let Ok(logger) = GLOBAL_LOGGER.lock() {
+    if let Ok(mut logger) = GLOBAL_LOGGER.lock() {
         logger.warn(tag, message);
     }
 }
+
+/// レベルを設定（ターゲットを指定しなければ既定のレベル）
+pub fn set_level(target: Option<&str>, level: LogLevel) {
+    if let Ok(mut logger) = GLOBAL_LOGGER.lock() {
+        logger.set_level(target, level);
+    }
+}
+
+/// 条件に合う直近のログ（古い順）
+pub fn recent_logs(filter: &LogFilter) -> Vec<LogRecord> {
+    GLOBAL_LOGGER
+        .lock()
+        .map(|logger| logger.recent(filter))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_and_filter() {
+        let mut logger = Logger::with_dir(std::env::temp_dir());
+        logger.set_level(None, LogLevel::Info);
+        logger.set_level(Some("Pty"), LogLevel::Debug);
+
+        logger.debug("Runner", "dropped");
+        logger.debug("Pty", "output 12 bytes");
+        logger.info("Runner", "pipeline started");
+        logger.error("Runner", "pipeline failed");
+
+        let all = logger.recent(&LogFilter::default());
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].message, "output 12 bytes");
+
+        let errors = logger.recent(&LogFilter { level: Some(LogLevel::Warn), ..Default::default() });
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].level, LogLevel::Error);
+
+        let runner = logger.recent(&LogFilter {
+            target: Some("runner".to_string()),
+            contains: Some("PIPELINE".to_string()),
+            since_seq: Some(all[1].seq - 1),
+            limit: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(runner.len(), 1);
+        assert_eq!(runner[0].message, "pipeline failed");
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = std::env::temp_dir().join(format!("re-voice-log-{}", uuid::Uuid::new_v4()));
+        let mut logger = Logger::with_dir(&dir).with_max_file_bytes(200);
+        logger.init().unwrap();
+        for i in 0..10 {
+            logger.info("Test", &format!("message {:02} {}", i, "x".repeat(40)));
+        }
+
+        let archived = fs::read_dir(dir.join("archive")).unwrap().count();
+        assert!(archived >= 2);
+        assert!(fs::metadata(dir.join("current.log")).unwrap().len() < 200);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,358 @@
+//! 吹替音声と動画の多重化
+//!
+//! ffmpegで元動画の映像に吹替トラックを重ねる。元の音声は置き換えるか、
+//! 吹替が鳴っている間だけ音量を下げて残す（サイドチェインによるダッキング）。
+//! 字幕はソフトサブとして追加するか、映像に焼き込む（映像を再エンコードする）。
+
+use serde::{Deserialize, Serialize};
+
+use crate::chapters::Chapter;
+use crate::ffmpeg::FfmpegCommand;
+
+/// ダッキング設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckingOptions {
+    /// 元音声の基本音量（1.0 = 原音）
+    #[serde(default = "default_original_volume")]
+    pub original_volume: f64,
+    /// 吹替がこのレベルを超えたら元音声を下げる（0.0〜1.0）
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+    /// 圧縮比（大きいほど強く下げる）
+    #[serde(default = "default_ratio")]
+    pub ratio: f64,
+    /// 下げ始めるまでの時間（ミリ秒）
+    #[serde(default = "default_attack_ms")]
+    pub attack_ms: u32,
+    /// 戻るまでの時間（ミリ秒）
+    #[serde(default = "default_release_ms")]
+    pub release_ms: u32,
+}
+
+fn default_original_volume() -> f64 {
+    0.8
+}
+
+fn default_threshold() -> f64 {
+    0.02
+}
+
+fn default_ratio() -> f64 {
+    8.0
+}
+
+fn default_attack_ms() -> u32 {
+    20
+}
+
+fn default_release_ms() -> u32 {
+    400
+}
+
+impl Default for DuckingOptions {
+    fn default() -> Self {
+        Self {
+            original_volume: default_original_volume(),
+            threshold: default_threshold(),
+            ratio: default_ratio(),
+            attack_ms: default_attack_ms(),
+            release_ms: default_release_ms(),
+        }
+    }
+}
+
+/// 元音声の扱い
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum OriginalAudio {
+    /// 吹替で置き換える
+    #[default]
+    Replace,
+    /// 吹替の下で音量を下げて残す
+    Duck(DuckingOptions),
+}
+
+/// 焼き込み字幕の位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubtitlePosition {
+    #[default]
+    Bottom,
+    Middle,
+    Top,
+}
+
+impl SubtitlePosition {
+    /// ASSのAlignment（テンキー配置、中央揃え）
+    fn alignment(self) -> u8 {
+        match self {
+            Self::Bottom => 2,
+            Self::Middle => 5,
+            Self::Top => 8,
+        }
+    }
+}
+
+/// 字幕焼き込み設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnInOptions {
+    /// フォント名（省略時はlibassの既定）
+    #[serde(default)]
+    pub font_name: Option<String>,
+    /// フォントサイズ
+    #[serde(default = "default_font_size")]
+    pub font_size: u32,
+    /// 表示位置
+    #[serde(default)]
+    pub position: SubtitlePosition,
+    /// 上下の余白
+    #[serde(default = "default_margin_v")]
+    pub margin_v: u32,
+    /// 再エンコードの画質（x264のCRF、小さいほど高画質）
+    #[serde(default = "default_crf")]
+    pub crf: u8,
+}
+
+fn default_font_size() -> u32 {
+    24
+}
+
+fn default_margin_v() -> u32 {
+    20
+}
+
+fn default_crf() -> u8 {
+    20
+}
+
+impl Default for BurnInOptions {
+    fn default() -> Self {
+        Self {
+            font_name: None,
+            font_size: default_font_size(),
+            position: SubtitlePosition::default(),
+            margin_v: default_margin_v(),
+            crf: default_crf(),
+        }
+    }
+}
+
+impl BurnInOptions {
+    /// 字幕ファイルを焼き込む `subtitles` フィルター
+    pub fn filter(&self, subtitles: &str) -> String {
+        let mut style = vec![
+            format!("FontSize={}", self.font_size),
+            format!("Alignment={}", self.position.alignment()),
+            format!("MarginV={}", self.margin_v),
+        ];
+        if let Some(ref font) = self.font_name {
+            style.insert(0, format!("FontName={}", font.replace(['\'', ',', ':'], "")));
+        }
+        format!(
+            "subtitles=filename={}:force_style='{}'",
+            escape_filter_path(subtitles),
+            style.join(",")
+        )
+    }
+}
+
+/// フィルターグラフ内のファイルパスをエスケープ（オプション値とグラフ記述の2段階）
+fn escape_filter_path(path: &str) -> String {
+    let escape = |text: &str, special: &[char]| {
+        text.chars().fold(String::new(), |mut out, c| {
+            if special.contains(&c) {
+                out.push('\\');
+            }
+            out.push(c);
+            out
+        })
+    };
+    let path = path.replace('\\', "/");
+    let value = escape(&path, &['\\', '\'', ':']);
+    escape(&value, &['\\', '\'', '[', ']', ',', ';'])
+}
+
+/// 多重化オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MuxOptions {
+    #[serde(default)]
+    pub original_audio: OriginalAudio,
+    /// 音声ビットレート
+    #[serde(default = "default_audio_bitrate")]
+    pub audio_bitrate: String,
+    /// 字幕を焼き込む（指定しなければソフトサブ）
+    #[serde(default)]
+    pub burn_in: Option<BurnInOptions>,
+}
+
+fn default_audio_bitrate() -> String {
+    "192k".to_string()
+}
+
+impl Default for MuxOptions {
+    fn default() -> Self {
+        Self {
+            original_audio: OriginalAudio::default(),
+            audio_bitrate: default_audio_bitrate(),
+            burn_in: None,
+        }
+    }
+}
+
+impl DuckingOptions {
+    /// 元音声（入力0）を吹替（入力1）でダッキングして混ぜるフィルターグラフ（出力 `[aout]`）
+    pub fn filter_graph(&self) -> String {
+        format!(
+            "[1:a]asplit=2[dub][key];\
+             [0:a]volume={volume}[orig];\
+             [orig][key]sidechaincompress=threshold={threshold}:ratio={ratio}:attack={attack}:release={release}[ducked];\
+             [ducked][dub]amix=inputs=2:duration=first:dropout_transition=0:normalize=0[aout]",
+            volume = self.original_volume,
+            threshold = self.threshold,
+            ratio = self.ratio,
+            attack = self.attack_ms,
+            release = self.release_ms,
+        )
+    }
+}
+
+/// 元動画と吹替トラックを多重化するコマンドを作成
+///
+/// `subtitles` を指定すると字幕をソフトサブ（mov_text）として追加する。
+/// `options.burn_in` がある場合は映像に焼き込む（このときだけ映像を再エンコードする）。
+pub fn build_mux_command(
+    video: &str,
+    dub: &str,
+    subtitles: Option<&str>,
+    options: &MuxOptions,
+    output: &str,
+) -> FfmpegCommand {
+    mux_onto(FfmpegCommand::new().input(video), dub, subtitles, options, output)
+}
+
+/// 元動画のチャプター部分と、チャプター単位に切り出した吹替トラック・字幕を多重化する
+///
+/// 映像をコピーする場合、切り出しはキーフレーム単位になる。
+pub fn build_chapter_mux_command(
+    video: &str,
+    chapter: &Chapter,
+    dub: &str,
+    subtitles: Option<&str>,
+    options: &MuxOptions,
+    output: &str,
+) -> FfmpegCommand {
+    let start = format!("{:.3}", chapter.start_ms as f64 / 1000.0);
+    let end = format!("{:.3}", chapter.end_ms as f64 / 1000.0);
+    let command = FfmpegCommand::new().input_with_args(&["-ss", &start, "-to", &end], video);
+    mux_onto(command, dub, subtitles, options, output)
+}
+
+/// 映像を入力0に追加済みのコマンドに吹替・字幕を重ねる
+fn mux_onto(
+    command: FfmpegCommand,
+    dub: &str,
+    subtitles: Option<&str>,
+    options: &MuxOptions,
+    output: &str,
+) -> FfmpegCommand {
+    let burn_in = subtitles.zip(options.burn_in.as_ref());
+    let soft_subtitles = subtitles.filter(|_| burn_in.is_none());
+
+    let mut command = command.input(dub);
+    if let Some(path) = soft_subtitles {
+        command = command.input(path);
+    }
+
+    let mut filters = Vec::new();
+    let video_stream = match burn_in {
+        Some((path, burn)) => {
+            filters.push(format!("[0:v]{}[vout]", burn.filter(path)));
+            "[vout]"
+        }
+        None => "0:v:0",
+    };
+    let audio_stream = match options.original_audio {
+        OriginalAudio::Replace => "1:a:0",
+        OriginalAudio::Duck(ref ducking) => {
+            filters.push(ducking.filter_graph());
+            "[aout]"
+        }
+    };
+    if !filters.is_empty() {
+        command = command.filter_complex(&filters.join(";"));
+    }
+    command = command.map(video_stream).map(audio_stream);
+
+    if soft_subtitles.is_some() {
+        command = command.map("2:s:0").arg("-c:s").arg("mov_text");
+    }
+
+    let command = match burn_in {
+        Some((_, burn)) => command
+            .video_codec("libx264")
+            .arg("-crf")
+            .arg(&burn.crf.to_string()),
+        None => command.video_codec("copy"),
+    };
+
+    command
+        .audio_codec("aac")
+        .arg("-b:a")
+        .arg(&options.audio_bitrate)
+        .output(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_maps_dub_audio() {
+        let args = build_mux_command("in.mp4", "dub.ja.wav", None, &MuxOptions::default(), "out.mp4")
+            .build_args()
+            .join(" ");
+        assert!(args.contains("-i in.mp4 -i dub.ja.wav -map 0:v:0 -map 1:a:0 -c:v copy -c:a aac -b:a 192k out.mp4"));
+        assert!(!args.contains("-filter_complex"));
+
+        let args = build_mux_command("in.mp4", "dub.ja.wav", Some("ja.vtt"), &MuxOptions::default(), "out.mp4")
+            .build_args()
+            .join(" ");
+        assert!(args.contains("-i ja.vtt -map 0:v:0 -map 1:a:0 -map 2:s:0 -c:s mov_text"));
+    }
+
+    #[test]
+    fn test_duck_uses_sidechain() {
+        let options: MuxOptions = serde_json::from_str(r#"{"original_audio":{"mode":"duck","ratio":4.0}}"#).unwrap();
+        let args = build_mux_command("in.mp4", "dub.ja.wav", None, &options, "out.mp4").build_args().join(" ");
+        assert!(args.contains("sidechaincompress=threshold=0.02:ratio=4:attack=20:release=400"));
+        assert!(args.contains("[0:a]volume=0.8[orig]"));
+        assert!(args.contains("-map [aout]"));
+    }
+
+    #[test]
+    fn test_chapter_seeks_video_input() {
+        let chapter = Chapter { title: "Intro".to_string(), start_ms: 61_500, end_ms: 120_000 };
+        let args = build_chapter_mux_command("in.mp4", &chapter, "dub.wav", None, &MuxOptions::default(), "out.mp4")
+            .build_args()
+            .join(" ");
+        assert!(args.contains("-ss 61.500 -to 120.000 -i in.mp4 -i dub.wav -map 0:v:0 -map 1:a:0"));
+    }
+
+    #[test]
+    fn test_burn_in_subtitles() {
+        let options: MuxOptions = serde_json::from_str(
+            r#"{"original_audio":{"mode":"duck"},"burn_in":{"font_name":"Noto Sans JP","position":"top"}}"#,
+        ).unwrap();
+        let args = build_mux_command("in.mp4", "dub.ja.wav", Some("C:\\out\\ja.vtt"), &options, "out.mp4").build_args();
+        let joined = args.join(" ");
+
+        let graph = &args[args.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+        assert!(graph.starts_with(
+            "[0:v]subtitles=filename=C\\\\:/out/ja.vtt:force_style='FontName=Noto Sans JP,FontSize=24,Alignment=8,MarginV=20'[vout];"
+        ));
+        assert!(graph.contains("sidechaincompress"));
+        assert!(joined.contains("-map [vout] -map [aout] -c:v libx264 -crf 20"));
+        assert!(!joined.contains("-i C:"));
+        assert!(!joined.contains("mov_text"));
+    }
+}
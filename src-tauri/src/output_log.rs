@@ -0,0 +1,152 @@
+//! 連番付きの出力ログ
+//!
+//! 出力をチャンク単位で連番を付けて保持し、呼び出し側が前回の続き（カーソル）から
+//! 差分だけを読めるようにする。バッファ全体を毎回コピー・送信しないためのもの。
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+/// 既定の保持サイズ（バイト）
+pub const DEFAULT_LOG_BYTES: usize = 100_000;
+
+/// カーソル以降の出力
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputSlice {
+    /// カーソル以降のチャンク（古い順）
+    pub chunks: Vec<String>,
+    /// 次回のカーソル
+    pub next_seq: u64,
+    /// カーソル位置のチャンクが既に破棄されていた（途中が欠けている）
+    pub truncated: bool,
+}
+
+impl OutputSlice {
+    /// チャンクを連結したテキスト
+    pub fn text(&self) -> String {
+        self.chunks.concat()
+    }
+}
+
+/// 連番付きの出力ログ（古いチャンクから破棄）
+#[derive(Debug, Clone)]
+pub struct OutputLog {
+    chunks: VecDeque<(u64, String)>,
+    next_seq: u64,
+    bytes: usize,
+    max_bytes: usize,
+}
+
+impl OutputLog {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_LOG_BYTES)
+    }
+
+    /// 保持サイズを指定して作成
+    pub fn with_capacity(max_bytes: usize) -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            next_seq: 0,
+            bytes: 0,
+            max_bytes,
+        }
+    }
+
+    /// チャンクを追加し、その連番を返す
+    pub fn push(&mut self, text: impl Into<String>) -> u64 {
+        let text = text.into();
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.bytes += text.len();
+        self.chunks.push_back((seq, text));
+
+        // 最新のチャンクは必ず残す
+        while self.bytes > self.max_bytes && self.chunks.len() > 1 {
+            if let Some((_, old)) = self.chunks.pop_front() {
+                self.bytes -= old.len();
+            }
+        }
+        seq
+    }
+
+    /// 次に追加されるチャンクの連番（現在のカーソル）
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// `seq` 以降のチャンクを取得
+    pub fn since(&self, seq: u64) -> OutputSlice {
+        let oldest = self.chunks.front().map(|(s, _)| *s).unwrap_or(self.next_seq);
+        OutputSlice {
+            chunks: self
+                .chunks
+                .iter()
+                .filter(|(s, _)| *s >= seq)
+                .map(|(_, text)| text.clone())
+                .collect(),
+            next_seq: self.next_seq,
+            truncated: seq < oldest,
+        }
+    }
+
+    /// 全チャンクを破棄する（連番は継続）
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.bytes = 0;
+    }
+}
+
+impl Default for OutputLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 画面キャプチャの差分: `current` のうち `previous` と一致しない行以降（変化がなければNone）
+pub fn diff_lines(previous: &str, current: &str) -> Option<String> {
+    if previous == current {
+        return None;
+    }
+    let unchanged = previous
+        .lines()
+        .zip(current.lines())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let text = current.lines().skip(unchanged).collect::<Vec<_>>().join("\n");
+    let text = text.trim_end();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_since_cursor() {
+        let mut log = OutputLog::with_capacity(10);
+        log.push("abcd");
+        let cursor = log.next_seq();
+        log.push("efgh");
+        log.push("ij");
+
+        let slice = log.since(cursor);
+        assert_eq!(slice.text(), "efghij");
+        assert_eq!(slice.next_seq, 3);
+        assert!(!slice.truncated);
+        assert!(log.since(slice.next_seq).chunks.is_empty());
+
+        // 容量を超えると古いチャンクが破棄される
+        log.push("klmnop");
+        let slice = log.since(0);
+        assert!(slice.truncated);
+        assert_eq!(slice.text(), "ijklmnop");
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(diff_lines("a\nb", "a\nb"), None);
+        assert_eq!(diff_lines("$ ls\n", "$ ls\nsrc\ndocs\n$ "), Some("src\ndocs\n$".to_string()));
+        assert_eq!(diff_lines("", "hello"), Some("hello".to_string()));
+    }
+}
@@ -0,0 +1,66 @@
+//! プラットフォームごとの機能
+//!
+//! WindowsではPTYはConPTYで動くが、tmuxは使えず、シェルは `cmd` になる。
+//! フロントエンドは `get_platform_capabilities` で使える機能を確認して画面を切り替える。
+
+use serde::Serialize;
+
+use crate::process_env;
+
+/// 使える機能
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformCapabilities {
+    /// `macos` / `linux` / `windows` など
+    pub os: String,
+    pub arch: String,
+    /// PTY（WindowsはConPTY）
+    pub pty: bool,
+    pub conpty: bool,
+    /// tmuxがインストールされていて使えるか
+    pub tmux: bool,
+    /// `execute_command` で使うシェル
+    pub shell: String,
+}
+
+/// tmuxを使えるか（Windowsでは常にfalse）
+pub fn tmux_available() -> bool {
+    if cfg!(windows) {
+        return false;
+    }
+    process_env::command("tmux")
+        .arg("-V")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// 現在のプラットフォームで使える機能
+pub fn capabilities() -> PlatformCapabilities {
+    PlatformCapabilities {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        pty: true,
+        conpty: cfg!(windows),
+        tmux: tmux_available(),
+        shell: process_env::SHELL.0.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let caps = capabilities();
+        assert_eq!(caps.os, std::env::consts::OS);
+        assert_eq!(caps.conpty, cfg!(windows));
+        if cfg!(windows) {
+            assert!(!caps.tmux);
+            assert_eq!(caps.shell, "cmd");
+        } else {
+            assert_eq!(caps.shell, "sh");
+        }
+    }
+}
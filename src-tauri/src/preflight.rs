@@ -0,0 +1,368 @@
+//! 事前チェック（プリフライト）
+//!
+//! メディアのダウンロードや大量のWAV生成を始める前に、
+//! 出力先ボリュームの空き容量と外部ツール（claude・yt-dlp・tmux・ffmpeg・VOICEVOX Engine）を確認する。
+//! 途中でENOSPCやツールの不足により失敗するのではなく、開始前に分かりやすいエラーを返す。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::acp::subtitle_parser::SubtitleSegment;
+use crate::process_env;
+use crate::settings::Settings;
+use crate::voicevox::VoicevoxClient;
+
+/// VOICEVOXの出力WAV（24kHz / 16bit / モノラル）の1秒あたりのバイト数
+pub const WAV_BYTES_PER_SECOND: u64 = 24_000 * 2;
+
+/// WAVヘッダーサイズ
+const WAV_HEADER_BYTES: u64 = 44;
+
+/// 字幕ファイル・segments.json等の小さな成果物用の見積もり
+pub const SUBTITLE_STAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 見積もりに上乗せする安全マージン
+pub const SAFETY_MARGIN_BYTES: u64 = 50 * 1024 * 1024;
+
+/// 合成音声は字幕の表示時間より長くなることがあるため係数を掛ける
+const SYNTHESIS_LENGTH_FACTOR: f64 = 1.5;
+
+/// 事前チェックエラー
+#[derive(Debug, Error)]
+pub enum PreflightError {
+    #[error("Insufficient disk space on {path}: required {required} bytes, available {available} bytes")]
+    InsufficientSpace {
+        path: String,
+        required: u64,
+        available: u64,
+    },
+
+    #[error("Failed to check disk space: {0}")]
+    CheckFailed(String),
+
+    #[error("Required tools are not available: {0}")]
+    MissingTools(String),
+}
+
+/// 環境チェックの対象
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalTool {
+    Claude,
+    Ytdlp,
+    Tmux,
+    Ffmpeg,
+    Voicevox,
+}
+
+impl ExternalTool {
+    pub const ALL: [ExternalTool; 5] = [Self::Claude, Self::Ytdlp, Self::Tmux, Self::Ffmpeg, Self::Voicevox];
+
+    /// 表示名
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Claude => "claude",
+            Self::Ytdlp => "yt-dlp",
+            Self::Tmux => "tmux",
+            Self::Ffmpeg => "ffmpeg",
+            Self::Voicevox => "VOICEVOX Engine",
+        }
+    }
+
+    /// 使えないときの対処方法
+    fn hint(&self, settings: &Settings) -> String {
+        match self {
+            Self::Claude => format!(
+                "npm install -g @anthropic-ai/claude-code でインストールし、設定の claude_path（{}）を確認してください",
+                settings.claude_path
+            ),
+            Self::Ytdlp => format!(
+                "brew install yt-dlp か pip install -U yt-dlp でインストールし、設定の ytdlp_path（{}）を確認してください",
+                settings.ytdlp_path
+            ),
+            Self::Tmux if cfg!(windows) => "Windowsではtmuxは使えません（エージェントはPTYで起動します）".to_string(),
+            Self::Tmux => "brew install tmux か apt install tmux でインストールしてください".to_string(),
+            Self::Ffmpeg => "brew install ffmpeg・apt install ffmpeg・winget install ffmpeg のいずれかでインストールしてください".to_string(),
+            Self::Voicevox => format!(
+                "VOICEVOX Engineを起動するか、設定の voicevox_url（{}）を確認してください",
+                settings.voicevox_url
+            ),
+        }
+    }
+}
+
+/// ツールごとのチェック結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCheck {
+    pub tool: ExternalTool,
+    pub available: bool,
+    /// バージョン（取得できた場合）
+    pub version: Option<String>,
+    /// 使えない理由
+    pub error: Option<String>,
+    /// 対処方法（使えない場合）
+    pub hint: Option<String>,
+}
+
+impl ToolCheck {
+    fn from_result(tool: ExternalTool, result: Result<String, String>, settings: &Settings) -> Self {
+        match result {
+            Ok(version) => Self { tool, available: true, version: Some(version), error: None, hint: None },
+            Err(error) => Self { tool, available: false, version: None, error: Some(error), hint: Some(tool.hint(settings)) },
+        }
+    }
+}
+
+/// 環境チェック結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentReport {
+    pub tools: Vec<ToolCheck>,
+    /// すべて使えるか
+    pub ok: bool,
+}
+
+impl EnvironmentReport {
+    fn new(tools: Vec<ToolCheck>) -> Self {
+        let ok = tools.iter().all(|t| t.available);
+        Self { tools, ok }
+    }
+
+    /// 使えないツールがあれば、名前と対処方法を並べたエラーを返す
+    pub fn ensure(&self) -> Result<(), PreflightError> {
+        let missing: Vec<String> = self
+            .tools
+            .iter()
+            .filter(|t| !t.available)
+            .map(|t| format!("{} ({})", t.tool.label(), t.hint.as_deref().unwrap_or_default()))
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(PreflightError::MissingTools(missing.join("; ")))
+        }
+    }
+}
+
+/// コマンドを実行し、出力の最初の行をバージョンとして返す
+fn command_version(program: &str, arg: &str) -> Result<String, String> {
+    let output = process_env::command(program)
+        .arg(arg)
+        .output()
+        .map_err(|e| format!("{} not found: {}", program, e))?;
+
+    if !output.status.success() {
+        return Err(format!("{} {} failed: {}", program, arg, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(first_line(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn first_line(output: &str) -> String {
+    output.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default().to_string()
+}
+
+/// コマンドで確認するツールをチェックする（VOICEVOX以外、ブロックする）
+fn check_command(tool: ExternalTool, settings: &Settings) -> ToolCheck {
+    let result = match tool {
+        ExternalTool::Claude => command_version(&settings.claude_path, "--version"),
+        ExternalTool::Ytdlp => command_version(&settings.ytdlp_path, "--version"),
+        ExternalTool::Tmux if cfg!(windows) => Err("tmux is not supported on Windows".to_string()),
+        ExternalTool::Tmux => command_version("tmux", "-V"),
+        ExternalTool::Ffmpeg => command_version("ffmpeg", "-version"),
+        ExternalTool::Voicevox => unreachable!("VOICEVOX is checked over HTTP"),
+    };
+    ToolCheck::from_result(tool, result, settings)
+}
+
+/// 指定したツールが使えるか確認する
+pub async fn check_tools(tools: &[ExternalTool]) -> EnvironmentReport {
+    let settings = crate::settings::current();
+    let mut checks = Vec::new();
+
+    for &tool in tools {
+        let check = if tool == ExternalTool::Voicevox {
+            let result = VoicevoxClient::with_url(&settings.voicevox_url)
+                .get_version()
+                .await
+                .map_err(|e| e.to_string());
+            ToolCheck::from_result(tool, result, &settings)
+        } else {
+            let settings = settings.clone();
+            tokio::task::spawn_blocking(move || check_command(tool, &settings))
+                .await
+                .unwrap_or_else(|e| ToolCheck::from_result(tool, Err(e.to_string()), &crate::settings::current()))
+        };
+        crate::log::info("Preflight", &format!(
+            "{}: available={}, version={:?}, error={:?}",
+            tool.label(), check.available, check.version, check.error
+        ));
+        checks.push(check);
+    }
+
+    EnvironmentReport::new(checks)
+}
+
+/// すべての外部ツールを確認する
+pub async fn check_environment() -> EnvironmentReport {
+    check_tools(&ExternalTool::ALL).await
+}
+
+/// 空き容量チェック結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskSpaceReport {
+    /// チェック対象パス
+    pub path: String,
+    /// 必要な容量（バイト、マージン込み）
+    pub required_bytes: u64,
+    /// 空き容量（バイト）
+    pub available_bytes: u64,
+    /// 十分な空きがあるか
+    pub sufficient: bool,
+}
+
+/// 指定パスのボリュームの空き容量を取得（バイト）
+///
+/// パスがまだ存在しない場合は、存在する最も近い親ディレクトリで確認する。
+/// `df` のような外部コマンドは使わないので Windows でも動く。
+pub fn available_space(path: &Path) -> Result<u64, PreflightError> {
+    let target = nearest_existing(path)
+        .ok_or_else(|| PreflightError::CheckFailed(format!("No existing ancestor for {}", path.display())))?;
+
+    fs2::available_space(&target)
+        .map_err(|e| PreflightError::CheckFailed(format!("{}: {}", target.display(), e)))
+}
+
+/// 存在する最も近い祖先パスを返す
+fn nearest_existing(path: &Path) -> Option<PathBuf> {
+    let mut current = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+
+    loop {
+        if current.exists() {
+            return Some(current);
+        }
+        if !current.pop() {
+            return None;
+        }
+    }
+}
+
+/// 必要容量（マージン込み）と空き容量を比較する
+pub fn check_disk_space(path: &Path, required_bytes: u64) -> Result<DiskSpaceReport, PreflightError> {
+    let required = required_bytes.saturating_add(SAFETY_MARGIN_BYTES);
+    let available = available_space(path)?;
+
+    let report = DiskSpaceReport {
+        path: path.to_string_lossy().to_string(),
+        required_bytes: required,
+        available_bytes: available,
+        sufficient: available >= required,
+    };
+
+    crate::log::info("Preflight", &format!(
+        "Disk space check: path={}, required={}, available={}",
+        report.path, report.required_bytes, report.available_bytes
+    ));
+
+    Ok(report)
+}
+
+/// 空き容量が不足していればエラーを返す
+pub fn ensure_disk_space(path: &Path, required_bytes: u64) -> Result<DiskSpaceReport, PreflightError> {
+    let report = check_disk_space(path, required_bytes)?;
+    if !report.sufficient {
+        return Err(PreflightError::InsufficientSpace {
+            path: report.path,
+            required: report.required_bytes,
+            available: report.available_bytes,
+        });
+    }
+    Ok(report)
+}
+
+/// 字幕セグメントから合成WAVの合計サイズを見積もる
+pub fn estimate_synthesis_bytes(segments: &[SubtitleSegment]) -> u64 {
+    segments
+        .iter()
+        .map(|s| {
+            let seconds = s.duration_ms() as f64 / 1000.0 * SYNTHESIS_LENGTH_FACTOR;
+            (seconds * WAV_BYTES_PER_SECOND as f64).ceil() as u64 + WAV_HEADER_BYTES
+        })
+        .sum()
+}
+
+/// メディアのメタデータ（長さ・ビットレート）からダウンロードサイズを見積もる
+///
+/// ファイルサイズが分かっている場合はそちらを優先する。
+pub fn estimate_media_bytes(
+    filesize: Option<u64>,
+    duration_secs: Option<f64>,
+    bitrate_kbps: Option<f64>,
+) -> u64 {
+    if let Some(size) = filesize {
+        return size;
+    }
+    match (duration_secs, bitrate_kbps) {
+        (Some(d), Some(kbps)) => (d * kbps * 1000.0 / 8.0).ceil() as u64,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_available_space() {
+        let dir = std::env::temp_dir();
+        assert!(available_space(&dir.join("re-voice-preflight-missing")).unwrap() > 0);
+    }
+
+    #[test]
+    fn test_estimate_synthesis_bytes() {
+        let segments = vec![
+            SubtitleSegment::new(0, 0, 2000, "a".to_string()),
+            SubtitleSegment::new(1, 2000, 4000, "b".to_string()),
+        ];
+        // 2秒 × 1.5 × 48000 + 44 を2セグメント分
+        assert_eq!(estimate_synthesis_bytes(&segments), 2 * (144_000 + 44));
+    }
+
+    #[test]
+    fn test_estimate_media_bytes() {
+        assert_eq!(estimate_media_bytes(Some(1234), Some(60.0), Some(128.0)), 1234);
+        assert_eq!(estimate_media_bytes(None, Some(8.0), Some(1000.0)), 1_000_000);
+        assert_eq!(estimate_media_bytes(None, None, Some(128.0)), 0);
+    }
+
+    #[test]
+    fn test_tool_checks() {
+        let settings = Settings::default();
+        let missing = command_version("re-voice-no-such-tool", "--version");
+        assert!(missing.is_err());
+        assert_eq!(first_line("\n  tmux 3.4\nextra\n"), "tmux 3.4");
+
+        let report = EnvironmentReport::new(vec![
+            ToolCheck::from_result(ExternalTool::Ffmpeg, Ok("ffmpeg version 6.1".to_string()), &settings),
+            ToolCheck::from_result(ExternalTool::Ytdlp, missing, &settings),
+        ]);
+        assert!(!report.ok);
+        assert!(report.tools[0].hint.is_none());
+        let PreflightError::MissingTools(message) = report.ensure().unwrap_err() else {
+            panic!("expected missing tools");
+        };
+        assert!(message.starts_with("yt-dlp (brew install yt-dlp"));
+        assert!(EnvironmentReport::new(vec![report.tools[0].clone()]).ensure().is_ok());
+    }
+
+    #[test]
+    fn test_nearest_existing() {
+        let dir = std::env::temp_dir();
+        let missing = dir.join("re-voice-preflight-missing").join("nested");
+        assert_eq!(nearest_existing(&missing), Some(dir));
+    }
+}
@@ -0,0 +1,82 @@
+//! 子プロセスの環境
+//!
+//! 外部コマンド（claude・yt-dlp・tmux・ffmpegなど）を起動するときのPATHと環境変数を
+//! 設定（`extra_path` / `env`）からまとめて組み立てる。
+
+use std::ffi::OsStr;
+
+use crate::settings::{self, Settings};
+
+/// シェルとスクリプトを渡す引数
+#[cfg(windows)]
+pub const SHELL: (&str, &str) = ("cmd", "/C");
+#[cfg(not(windows))]
+pub const SHELL: (&str, &str) = ("sh", "-c");
+
+/// 子プロセスに設定する環境変数（`extra_path` を前に追加したPATHと、設定の `env`）
+///
+/// `env` にPATHがあればそちらを優先する。
+pub fn vars_for(settings: &Settings) -> Vec<(String, String)> {
+    let mut vars = vec![("PATH".to_string(), settings.command_path())];
+    for (key, value) in &settings.env {
+        vars.retain(|(k, _)| k != key);
+        vars.push((key.clone(), value.clone()));
+    }
+    vars
+}
+
+/// 現在の設定での環境変数
+pub fn vars() -> Vec<(String, String)> {
+    vars_for(&settings::current())
+}
+
+/// 環境変数を設定したコマンド
+pub fn command(program: impl AsRef<OsStr>) -> std::process::Command {
+    let mut command = std::process::Command::new(program);
+    command.envs(vars());
+    command
+}
+
+/// シェル経由でスクリプトを実行するコマンド（Windowsは `cmd /C`、それ以外は `sh -c`）
+pub fn shell_command(script: &str) -> std::process::Command {
+    let mut command = self::command(SHELL.0);
+    command.arg(SHELL.1).arg(script);
+    command
+}
+
+/// 環境変数を設定したコマンド（tokio）
+pub fn tokio_command(program: impl AsRef<OsStr>) -> tokio::process::Command {
+    let mut command = tokio::process::Command::new(program);
+    command.envs(vars());
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vars_for() {
+        let mut settings = Settings {
+            extra_path: vec!["/opt/tools/bin".to_string()],
+            ..Settings::default()
+        };
+        settings.env.insert("YTDLP_PROXY".to_string(), "socks5://127.0.0.1:1080".to_string());
+
+        let vars = vars_for(&settings);
+        assert!(vars[0].1.starts_with("/opt/tools/bin"));
+        assert!(vars.contains(&("YTDLP_PROXY".to_string(), "socks5://127.0.0.1:1080".to_string())));
+
+        settings.env.insert("PATH".to_string(), "/only/this".to_string());
+        let vars = vars_for(&settings);
+        assert_eq!(vars.iter().filter(|(k, _)| k == "PATH").count(), 1);
+        assert!(vars.contains(&("PATH".to_string(), "/only/this".to_string())));
+    }
+
+    #[test]
+    fn test_shell_command() {
+        let output = shell_command("echo hello").output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+    }
+}
@@ -2,10 +2,16 @@ use anyhow::{anyhow, Result};
 use chrono;
 use parking_lot::Mutex;
 use portable_pty::{native_pty_system, Child, CommandBuilder, PtyPair, PtySize};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::output_log::{OutputLog, OutputSlice};
+use crate::transcript::TranscriptEntry;
 
 /// PTYイベント
 #[derive(Debug, Clone, serde::Serialize)]
@@ -13,6 +19,8 @@ use std::thread::{self, JoinHandle};
 pub enum PtyEvent {
     /// 出力チャンク
     Output(String),
+    /// 加工していない出力（rawモード時のみ、ANSIエスケープシーケンスを含む）
+    RawOutput(String),
     /// プロンプト検知（入力待ち状態）
     Prompt,
     /// エラー
@@ -24,22 +32,79 @@ pub enum PtyEvent {
         /// 直近のコンテキスト（ユーザーに表示用）
         context: String,
     },
+    /// 子プロセスが終了した（`shutdown` で停止した場合も含む）
+    Exited(PtyExit),
+    /// 確認プロンプトに対してポリシーに従って応答した（ユーザーに回した場合も含む）
+    AutoResponse(AutoResponseRecord),
+    /// メッセージ送信後、一定時間出力がない
+    Timeout(PtyTimeout),
+}
+
+/// 子プロセスの終了状態
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtyExit {
+    /// 終了コード（取得できなければNone）
+    pub exit_code: Option<u32>,
+    pub success: bool,
+    /// `shutdown` で停止したか
+    pub killed: bool,
+}
+
+/// PTYで起動したコマンド（再起動時に同じコマンドを起動し直す）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PtyCommand {
+    pub command: String,
+    pub args: Vec<String>,
+    /// 追加の環境変数
+    pub env: HashMap<String, String>,
+    /// 作業ディレクトリ（Noneならアプリと同じ）
+    pub cwd: Option<String>,
+}
+
+/// 応答がないときの対応
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PtyIdleAction {
+    /// 通知のみ
+    Notify,
+    /// Enterを送って促す
+    Nudge,
+    /// 終了して起動し直す
+    Restart,
+}
+
+/// 応答がなかったことの通知
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtyTimeout {
+    /// 最後の出力（または送信）からの経過時間（ミリ秒）
+    pub elapsed_ms: u64,
+    /// 行った対応
+    pub action: PtyIdleAction,
 }
 
+/// 保持する自動応答の監査ログの件数
+const MAX_AUTO_RESPONSE_AUDIT: usize = 200;
+
 /// PTYマネージャー - Claude Code等のCLIツールとの通信を管理
 /// イベント駆動で動作し、バックグラウンドスレッドで出力を読み取る
 pub struct PtyManager {
     pair: Option<PtyPair>,
-    #[allow(dead_code)]
     child: Option<Box<dyn Child + Send + Sync>>,
-    reader: Arc<Mutex<Option<Box<dyn Read + Send>>>>,
     writer: Arc<Mutex<Option<Box<dyn Write + Send>>>>,
     /// バックグラウンドリーダーのハンドル
     reader_handle: Option<JoinHandle<()>>,
-    /// リーダー停止フラグ
+    /// 現在のリーダーの停止フラグ（リーダースレッドごとに作り直す）
     stop_flag: Arc<AtomicBool>,
+    /// 加工していない出力も `PtyEvent::RawOutput` で通知するか
+    raw_mode: Arc<AtomicBool>,
+    /// 自動応答の監査ログ（古い順）
+    auto_response_audit: Arc<Mutex<VecDeque<AutoResponseRecord>>>,
     /// 出力バッファ
     output_buffer: Arc<Mutex<String>>,
+    /// 連番付きの出力ログ（カーソル読み取り用）
+    output_log: Arc<Mutex<OutputLog>>,
     /// 最後のプロンプト以降の出力（レスポンス用）
     response_buffer: Arc<Mutex<String>>,
     /// イベントコールバック
@@ -48,6 +113,12 @@ pub struct PtyManager {
     child_pid: Option<u32>,
     /// 最後のアクティビティ時刻（タイムアウト検出用）
     last_activity: Arc<Mutex<std::time::Instant>>,
+    /// 応答待ちになった時刻（send_message後、プロンプト検知まで）
+    awaiting_response: Arc<Mutex<Option<Instant>>>,
+    /// 最後に起動したコマンド
+    command: Option<PtyCommand>,
+    /// 記録（transcript）のセッションID（起動ごとに新しくなる）
+    transcript_id: Option<String>,
 }
 
 impl PtyManager {
@@ -55,15 +126,20 @@ impl PtyManager {
         Self {
             pair: None,
             child: None,
-            reader: Arc::new(Mutex::new(None)),
             writer: Arc::new(Mutex::new(None)),
             reader_handle: None,
             stop_flag: Arc::new(AtomicBool::new(false)),
+            raw_mode: Arc::new(AtomicBool::new(false)),
+            auto_response_audit: Arc::new(Mutex::new(VecDeque::new())),
             output_buffer: Arc::new(Mutex::new(String::new())),
+            output_log: Arc::new(Mutex::new(OutputLog::new())),
             response_buffer: Arc::new(Mutex::new(String::new())),
             event_callback: Arc::new(Mutex::new(None)),
             child_pid: None,
             last_activity: Arc::new(Mutex::new(std::time::Instant::now())),
+            awaiting_response: Arc::new(Mutex::new(None)),
+            command: None,
+            transcript_id: None,
         }
     }
 
@@ -77,26 +153,95 @@ impl PtyManager {
 
     /// Claude CodeをPTYで起動
     pub fn spawn_claude_code(&mut self) -> Result<()> {
+        // Claude Codeを起動（通常モード）
+        // PromptDetectorが確認プロンプトに自動応答する
+        let claude_path = crate::settings::current().claude_path;
+        self.spawn_agent_pty(&claude_path, &[], &HashMap::new(), None)
+    }
+
+    /// 任意のCLIエージェント（codex・gemini・aiderなど）をPTYで起動
+    ///
+    /// 環境変数は設定（`extra_path` / `env`）から組み立て、引数の `env` で上書きできる。
+    pub fn spawn_agent_pty(
+        &mut self,
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+        cwd: Option<&str>,
+    ) -> Result<()> {
+        if command.trim().is_empty() {
+            return Err(anyhow!("Command must not be empty"));
+        }
+        if let Some(cwd) = cwd {
+            if !std::path::Path::new(cwd).is_dir() {
+                return Err(anyhow!("Working directory not found: {}", cwd));
+            }
+        }
+
         let pty_system = native_pty_system();
+        let settings = crate::settings::current();
 
-        // 120x50の仮想端末を作成（スクロールバッファ拡大）
+        // 仮想端末を作成（既定120x50、スクロールバッファ拡大）
         let pair = pty_system
             .openpty(PtySize {
-                rows: 50,
-                cols: 120,
+                rows: settings.pty_rows,
+                cols: settings.pty_cols,
                 pixel_width: 0,
                 pixel_height: 0,
             })
             .map_err(|e| anyhow!("Failed to create PTY: {}", e))?;
 
-        // Claude Codeを起動（通常モード）
-        // PromptDetectorが確認プロンプトに自動応答する
-        let cmd = CommandBuilder::new("claude");
+        let mut cmd = CommandBuilder::new(command);
+        cmd.args(args);
+        for (key, value) in crate::process_env::vars() {
+            cmd.env(key, value);
+        }
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+        if let Some(cwd) = cwd {
+            cmd.cwd(cwd);
+        }
+
+        self.transcript_id = Some(crate::transcript::new_session_id("pty"));
+        self.spawn(pair, cmd)?;
+        self.command = Some(PtyCommand {
+            command: command.to_string(),
+            args: args.to_vec(),
+            env: env.clone(),
+            cwd: cwd.map(|s| s.to_string()),
+        });
+        Ok(())
+    }
+
+    /// 最後に起動したコマンドを起動し直す（まだ起動していなければClaude Code）
+    pub fn respawn(&mut self) -> Result<()> {
+        match self.command.clone() {
+            Some(c) => self.spawn_agent_pty(&c.command, &c.args, &c.env, c.cwd.as_deref()),
+            None => self.spawn_claude_code(),
+        }
+    }
+
+    /// 最後に起動したコマンド
+    pub fn command(&self) -> Option<&PtyCommand> {
+        self.command.as_ref()
+    }
+
+    /// 現在のセッションの記録ID（`export_transcript` に渡す）
+    pub fn transcript_id(&self) -> Option<&str> {
+        self.transcript_id.as_deref()
+    }
+
+    /// 作成済みのPTYでコマンドを起動し、バックグラウンドリーダーを開始
+    fn spawn(&mut self, pair: PtyPair, cmd: CommandBuilder) -> Result<()> {
+        if self.child.is_some() {
+            return Err(anyhow!("PTY is already running"));
+        }
 
         let child = pair
             .slave
             .spawn_command(cmd)
-            .map_err(|e| anyhow!("Failed to spawn claude code: {}", e))?;
+            .map_err(|e| anyhow!("Failed to spawn command: {}", e))?;
 
         let pid = child.process_id();
         eprintln!("[PTY] Child process spawned, PID: {:?}", pid);
@@ -113,7 +258,6 @@ impl PtyManager {
             .take_writer()
             .map_err(|e| anyhow!("Failed to take writer: {}", e))?;
 
-        *self.reader.lock() = Some(reader);
         *self.writer.lock() = Some(writer);
         self.pair = Some(pair);
 
@@ -121,25 +265,35 @@ impl PtyManager {
         // PtyChild + Send は portable-pty でサポートされている
         self.child = Some(child);
 
-        // バックグラウンドリーダーを開始
-        self.start_background_reader();
+        // バックグラウンドリーダーを開始（リーダーはスレッドに渡す）
+        self.start_background_reader(reader);
 
         Ok(())
     }
 
     /// バックグラウンドリーダーを開始
-    fn start_background_reader(&mut self) {
-        self.stop_flag.store(false, Ordering::SeqCst);
+    ///
+    /// 停止フラグはスレッドごとに新しく作るので、切り離した前のリーダーが
+    /// 再起動後に動き続けることはない。
+    fn start_background_reader(&mut self, mut reader: Box<dyn Read + Send>) {
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        self.stop_flag = Arc::clone(&stop_flag);
 
-        let reader = Arc::clone(&self.reader);
         let writer = Arc::clone(&self.writer);
-        let stop_flag = Arc::clone(&self.stop_flag);
+        let raw_mode = Arc::clone(&self.raw_mode);
+        let audit = Arc::clone(&self.auto_response_audit);
+        let last_activity = Arc::clone(&self.last_activity);
+        let awaiting_response = Arc::clone(&self.awaiting_response);
         let output_buffer = Arc::clone(&self.output_buffer);
+        let output_log = Arc::clone(&self.output_log);
         let response_buffer = Arc::clone(&self.response_buffer);
         let event_callback = Arc::clone(&self.event_callback);
+        let transcript_id = self.transcript_id.clone();
 
         let handle = thread::spawn(move || {
             let mut buffer = [0u8; 4096];
+            // rawモードでチャンク境界にまたがったUTF-8の途中のバイト
+            let mut raw_pending = Vec::new();
 
             fn log(msg: &str) {
                 let now = chrono::Local::now();
@@ -150,131 +304,191 @@ impl PtyManager {
 
             while !stop_flag.load(Ordering::SeqCst) {
                 log("[PTY READER] Waiting for data...");
-                let mut reader_lock = reader.lock();
-
-                if let Some(ref mut r) = *reader_lock {
-                    log("[PTY READER] Calling read()...");
-                    match r.read(&mut buffer) {
-                        Ok(0) => {
-                            // EOF - プロセスが終了
-                            drop(reader_lock);
-                            log("[PTY READER] EOF received");
-                            if let Some(cb) = event_callback.lock().as_ref() {
-                                cb(PtyEvent::Error("PTY EOF - process terminated".to_string()));
-                            }
+                // リーダーはこのスレッドだけが持つ（read()中にロックを握らない）
+                match reader.read(&mut buffer) {
+                    Ok(0) => {
+                        // EOF - プロセスが終了
+                        log("[PTY READER] EOF received");
+                        if stop_flag.load(Ordering::SeqCst) {
+                            // shutdownによる停止（終了はshutdownが通知する）
                             break;
                         }
-                        Ok(n) => {
-                            log(&format!("[PTY READER] Read {} bytes", n));
-                            log(&format!("[PTY READER] Raw bytes: {:?}", &buffer[..n]));
-                            drop(reader_lock); // ロックを解放
-
-                            // ANSIエスケープシーケンスを処理
-                            let clean_chunk = process_ansi(&buffer[..n]);
-                            log(&format!("[PTY READER] After process_ansi: {} bytes", clean_chunk.len()));
-
-                            // 出力バッファに追加
-                            let current_output = {
-                                let mut buf = output_buffer.lock();
-                                buf.push_str(&clean_chunk);
-
-                                // バッファサイズ制限（最新100KB）
-                                if buf.len() > 100_000 {
-                                    let drain = buf.len() - 100_000;
-                                    buf.drain(0..drain);
+                        if let Some(cb) = event_callback.lock().as_ref() {
+                            cb(PtyEvent::Error("PTY EOF - process terminated".to_string()));
+                        }
+                        break;
+                    }
+                    Ok(n) => {
+                        log(&format!("[PTY READER] Read {} bytes", n));
+                        *last_activity.lock() = Instant::now();
+                        log(&format!("[PTY READER] Raw bytes: {:?}", &buffer[..n]));
+
+                        // 端末表示用に加工前の出力を通知（プロンプト検知は加工後の出力で行う）
+                        if raw_mode.load(Ordering::SeqCst) {
+                            let raw = decode_utf8_chunk(&mut raw_pending, &buffer[..n]);
+                            if !raw.is_empty() {
+                                if let Some(cb) = event_callback.lock().as_ref() {
+                                    cb(PtyEvent::RawOutput(raw));
                                 }
-                                buf.clone()
-                            };
+                            }
+                        }
 
-                            // プロンプト検知（PromptDetector使用）
-                            if let Some(prompt_type) = PromptDetector::detect(&current_output) {
-                                log(&format!("[PTY READER] Prompt detected: {:?}", prompt_type));
-
-                                // 自動応答可能かチェック
-                                if let Some(response) = PromptDetector::get_auto_response(&prompt_type) {
-                                    log(&format!("[PTY READER] Auto-response would be: {:?}", response));
-
-                                    // 自動応答を送信
-                                    thread::sleep(std::time::Duration::from_millis(500));
-
-                                    log("[PTY READER] Acquiring writer for auto-response...");
-                                    if let Some(ref mut w) = *writer.lock() {
-                                        // 選択肢番号だけを送信（Enterなし）
-                                        let choice = response.trim();
-                                        log(&format!("[PTY READER] Writing choice: {:?}", choice.as_bytes()));
-                                        let _ = w.write_all(choice.as_bytes());
-                                        let _ = w.flush();
-                                        log("[PTY READER] Choice written, waiting...");
-
-                                        // 少し待ってからEnterを送信
-                                        thread::sleep(std::time::Duration::from_millis(300));
-
-                                        log("[PTY READER] Writing Enter...");
-                                        let _ = w.write_all(b"\r");
-                                        let _ = w.flush();
-                                        log("[PTY READER] Auto-response completed");
-                                    }
+                        // ANSIエスケープシーケンスを処理
+                        let clean_chunk = process_ansi(&buffer[..n]);
+                        log(&format!("[PTY READER] After process_ansi: {} bytes", clean_chunk.len()));
 
-                                    // 自動応答したので出力バッファをクリア（プロンプトを除外）
-                                    output_buffer.lock().clear();
-                                    response_buffer.lock().clear();
-
-                                    // 自動応答したプロンプトはイベント発火しない
-                                    continue;
-                                } else if matches!(prompt_type, PromptType::InputReady) {
-                                    // 通常の入力待ち - ユーザーに通知
-                                    response_buffer.lock().clear();
-                                    if let Some(cb) = event_callback.lock().as_ref() {
-                                        cb(PtyEvent::Prompt);
-                                    }
-                                    continue;
-                                } else if matches!(prompt_type, PromptType::PendingPrompt) {
-                                    // プロンプト検出中 - 選択肢待ち
-                                    // イベント発火せず、次のチャンクを待つ
-                                    log("[PTY READER] Pending prompt detected, waiting for choices...");
-                                    continue;
-                                } else if matches!(prompt_type, PromptType::AuthenticationRequired { .. })
-                                    || matches!(prompt_type, PromptType::UserInputRequired { .. })
+                        // 出力ログに追加（プロンプト検知でバッファをクリアしても残る）
+                        output_log.lock().push(clean_chunk.clone());
+                        if let Some(ref id) = transcript_id {
+                            crate::transcript::record(id, TranscriptEntry::output(&clean_chunk));
+                        }
+
+                        // 出力バッファに追加
+                        let current_output = {
+                            let mut buf = output_buffer.lock();
+                            buf.push_str(&clean_chunk);
+
+                            // バッファサイズ制限（最新100KB）
+                            if buf.len() > 100_000 {
+                                let drain = buf.len() - 100_000;
+                                buf.drain(0..drain);
+                            }
+                            buf.clone()
+                        };
+
+                        // プロンプト検知（PromptDetector使用）
+                        if let Some(prompt_type) = PromptDetector::detect(&current_output) {
+                            log(&format!("[PTY READER] Prompt detected: {:?}", prompt_type));
+
+                            // 自動応答の対象ならポリシーに従って応答を決める
+                            let record = if matches!(prompt_type, PromptType::Choice { .. } | PromptType::Confirmation { .. }) {
+                                let policy = crate::settings::current().auto_response;
+                                let record = AutoResponseRecord::decide(
+                                    &prompt_type,
+                                    &policy,
+                                    &PromptDetector::extract_last_lines(&current_output, 10),
+                                );
+                                crate::log::info("PtyAutoResponse", &format!(
+                                    "{:?} -> {:?} ({:?})",
+                                    record.prompt_type, record.action, record.response
+                                ));
                                 {
-                                    // ユーザー入力が必要 - フロントエンドに通知
-                                    log("[PTY READER] User input required, notifying frontend...");
-                                    if let Some(cb) = event_callback.lock().as_ref() {
-                                        cb(PtyEvent::InputRequired {
-                                            prompt_type,
-                                            context: current_output.clone(),
-                                        });
+                                    let mut audit = audit.lock();
+                                    audit.push_back(record.clone());
+                                    while audit.len() > MAX_AUTO_RESPONSE_AUDIT {
+                                        audit.pop_front();
                                     }
-                                    // 出力バッファはクリアしない（コンテキスト保持）
-                                    continue;
                                 }
-                            }
+                                if let Some(ref id) = transcript_id {
+                                    crate::transcript::record(id, TranscriptEntry::event(
+                                        "auto_response",
+                                        serde_json::to_value(&record).unwrap_or_default(),
+                                    ));
+                                }
+                                if let Some(cb) = event_callback.lock().as_ref() {
+                                    cb(PtyEvent::AutoResponse(record.clone()));
+                                }
+                                Some(record)
+                            } else {
+                                None
+                            };
+
+                            if let Some(response) = record.as_ref().and_then(|r| r.response.clone()) {
+                                log(&format!("[PTY READER] Auto-response would be: {:?}", response));
+
+                                // 自動応答を送信
+                                thread::sleep(std::time::Duration::from_millis(500));
+
+                                log("[PTY READER] Acquiring writer for auto-response...");
+                                if let Some(ref mut w) = *writer.lock() {
+                                    // 選択肢番号だけを送信（Enterなし）
+                                    let choice = response.trim();
+                                    log(&format!("[PTY READER] Writing choice: {:?}", choice.as_bytes()));
+                                    let _ = w.write_all(choice.as_bytes());
+                                    let _ = w.flush();
+                                    log("[PTY READER] Choice written, waiting...");
 
-                            // 自動応答不要の場合のみイベント発火
+                                    // 少し待ってからEnterを送信
+                                    thread::sleep(std::time::Duration::from_millis(300));
+
+                                    log("[PTY READER] Writing Enter...");
+                                    let _ = w.write_all(b"\r");
+                                    let _ = w.flush();
+                                    log("[PTY READER] Auto-response completed");
+                                }
+
+                                // 自動応答したので出力バッファをクリア（プロンプトを除外）
+                                output_buffer.lock().clear();
+                                response_buffer.lock().clear();
+
+                                // 自動応答したプロンプトはイベント発火しない
+                                continue;
+                            } else if let Some(record) = record {
+                                // ポリシーがask_user（または応答できる選択肢がない） - ユーザーに回す
+                                *awaiting_response.lock() = None;
+                                if let Some(cb) = event_callback.lock().as_ref() {
+                                    cb(PtyEvent::InputRequired {
+                                        prompt_type,
+                                        context: record.context,
+                                    });
+                                }
+                                // 同じプロンプトを続けて通知しないようにバッファをクリア
+                                output_buffer.lock().clear();
+                                continue;
+                            } else if matches!(prompt_type, PromptType::InputReady) {
+                                // 通常の入力待ち - ユーザーに通知
+                                response_buffer.lock().clear();
+                                *awaiting_response.lock() = None;
+                                if let Some(cb) = event_callback.lock().as_ref() {
+                                    cb(PtyEvent::Prompt);
+                                }
+                                continue;
+                            } else if matches!(prompt_type, PromptType::PendingPrompt) {
+                                // プロンプト検出中 - 選択肢待ち
+                                // イベント発火せず、次のチャンクを待つ
+                                log("[PTY READER] Pending prompt detected, waiting for choices...");
+                                continue;
+                            } else if matches!(prompt_type, PromptType::AuthenticationRequired { .. })
+                                || matches!(prompt_type, PromptType::UserInputRequired { .. })
                             {
-                                let mut resp = response_buffer.lock();
-                                resp.push_str(&clean_chunk);
+                                // ユーザー入力が必要 - フロントエンドに通知
+                                log("[PTY READER] User input required, notifying frontend...");
+                                *awaiting_response.lock() = None;
+                                if let Some(cb) = event_callback.lock().as_ref() {
+                                    cb(PtyEvent::InputRequired {
+                                        prompt_type,
+                                        context: current_output.clone(),
+                                    });
+                                }
+                                // 出力バッファはクリアしない（コンテキスト保持）
+                                continue;
                             }
+                        }
 
-                            if let Some(cb) = event_callback.lock().as_ref() {
-                                cb(PtyEvent::Output(clean_chunk));
-                            }
+                        // 自動応答不要の場合のみイベント発火
+                        {
+                            let mut resp = response_buffer.lock();
+                            resp.push_str(&clean_chunk);
                         }
-                        Err(e) => {
-                            drop(reader_lock);
-                            if e.kind() != std::io::ErrorKind::WouldBlock {
-                                log(&format!("[PTY READER] Error: {}", e));
-                                // エラー通知
-                                if let Some(cb) = event_callback.lock().as_ref() {
-                                    cb(PtyEvent::Error(e.to_string()));
-                                }
+
+                        if let Some(cb) = event_callback.lock().as_ref() {
+                            cb(PtyEvent::Output(clean_chunk));
+                        }
+                    }
+                    Err(e) => {
+                        if stop_flag.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        if e.kind() != std::io::ErrorKind::WouldBlock {
+                            log(&format!("[PTY READER] Error: {}", e));
+                            // エラー通知
+                            if let Some(cb) = event_callback.lock().as_ref() {
+                                cb(PtyEvent::Error(e.to_string()));
                             }
-                            // 少し待機してリトライ
-                            thread::sleep(std::time::Duration::from_millis(10));
                         }
+                        // 少し待機してリトライ
+                        thread::sleep(std::time::Duration::from_millis(10));
                     }
-                } else {
-                    drop(reader_lock);
-                    break;
                 }
             }
             log("[PTY READER] Background reader stopped");
@@ -287,10 +501,74 @@ impl PtyManager {
     pub fn stop_background_reader(&mut self) {
         self.stop_flag.store(true, Ordering::SeqCst);
 
-        if let Some(handle) = self.reader_handle.take() {
-            // スレッドの終了を待機（最大1秒）
-            // 注: PTYのreadがブロックしている場合、すぐには終了しない可能性がある
+        // PTYのread()がブロックしている場合はすぐに終わらないので、待ちすぎずに切り離す
+        self.join_reader(std::time::Duration::from_secs(1));
+    }
+
+    /// 子プロセスを終了し、リーダースレッドを止めてバッファをクリアする
+    ///
+    /// 終了状態は `PtyEvent::Exited` でも通知する。
+    pub fn shutdown(&mut self) -> Result<PtyExit> {
+        let Some(mut child) = self.child.take() else {
+            return Err(anyhow!("PTY not initialized"));
+        };
+        self.stop_flag.store(true, Ordering::SeqCst);
+
+        // すでに終了していればkillは失敗するので無視する
+        let killed = matches!(child.try_wait(), Ok(None));
+        if killed {
+            let _ = child.kill();
+        }
+        let status = child.wait();
+
+        // ライターとスレーブ側を閉じるとリーダーのread()がEOF/エラーで戻る
+        *self.writer.lock() = None;
+        self.pair = None;
+        // 孫プロセスが端末を開いたままだとread()が戻らないので、その場合は切り離す
+        // （リーダーはスレッドが持っているので、ここでロック待ちになることはない）
+        self.join_reader(std::time::Duration::from_secs(2));
+
+        self.output_buffer.lock().clear();
+        self.response_buffer.lock().clear();
+        self.output_log.lock().clear();
+        self.child_pid = None;
+        *self.awaiting_response.lock() = None;
+
+        let exit = match status {
+            Ok(status) => PtyExit {
+                exit_code: Some(status.exit_code()),
+                success: status.success(),
+                killed,
+            },
+            Err(e) => {
+                eprintln!("[PTY] Failed to wait for child: {}", e);
+                PtyExit { exit_code: None, success: false, killed }
+            }
+        };
+        eprintln!("[PTY] Child process stopped: {:?}", exit);
+        if let Some(ref id) = self.transcript_id {
+            crate::transcript::record(id, TranscriptEntry::event("exited", serde_json::to_value(&exit).unwrap_or_default()));
+        }
+
+        if let Some(cb) = self.event_callback.lock().as_ref() {
+            cb(PtyEvent::Exited(exit.clone()));
+        }
+        Ok(exit)
+    }
+
+    /// リーダースレッドの終了を待つ（`timeout` を過ぎたら待たずに切り離す）
+    fn join_reader(&mut self, timeout: std::time::Duration) {
+        let Some(handle) = self.reader_handle.take() else {
+            return;
+        };
+        let deadline = std::time::Instant::now() + timeout;
+        while !handle.is_finished() && std::time::Instant::now() < deadline {
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+        if handle.is_finished() {
             let _ = handle.join();
+        } else {
+            eprintln!("[PTY] Reader thread did not stop within {:?}, detaching", timeout);
         }
     }
 
@@ -329,9 +607,13 @@ impl PtyManager {
 
         // レスポンスバッファをクリア
         self.response_buffer.lock().clear();
+        *self.awaiting_response.lock() = Some(Instant::now());
 
         // メッセージ本体を送信（改行なし）
         self.write_input(message.as_bytes())?;
+        if let Some(ref id) = self.transcript_id {
+            crate::transcript::record(id, TranscriptEntry::prompt(message));
+        }
 
         // 少し待機してからEnterを送信（自動応答と同じパターン）
         std::thread::sleep(std::time::Duration::from_millis(100));
@@ -347,6 +629,11 @@ impl PtyManager {
         self.output_buffer.lock().clone()
     }
 
+    /// カーソル `seq` 以降の出力を取得（次回は `next_seq` を渡す）
+    pub fn get_output_since(&self, seq: u64) -> OutputSlice {
+        self.output_log.lock().since(seq)
+    }
+
     /// 現在のレスポンス（最後のメッセージ送信以降の出力）を取得
     pub fn get_response(&self) -> String {
         self.response_buffer.lock().clone()
@@ -402,6 +689,92 @@ impl PtyManager {
     pub fn child_pid(&self) -> Option<u32> {
         self.child_pid
     }
+
+    /// 応答待ちで `timeout` 以上出力がなければ通知し、`action` に従って対応する
+    ///
+    /// 通知後は応答待ちを解除する（`Nudge` はEnterを送ってから計測し直す）。
+    pub fn check_idle(&mut self, timeout: Duration, action: PtyIdleAction) -> Option<PtyTimeout> {
+        let since = (*self.awaiting_response.lock())?;
+        let last = since.max(*self.last_activity.lock());
+        let elapsed = last.elapsed();
+        if elapsed < timeout {
+            return None;
+        }
+
+        let timeout = PtyTimeout {
+            elapsed_ms: elapsed.as_millis() as u64,
+            action,
+        };
+        crate::log::warn("PtyWatchdog", &format!("No output for {}ms, {:?}", timeout.elapsed_ms, action));
+        *self.awaiting_response.lock() = None;
+        if let Some(cb) = self.event_callback.lock().as_ref() {
+            cb(PtyEvent::Timeout(timeout.clone()));
+        }
+
+        match action {
+            PtyIdleAction::Notify => {}
+            PtyIdleAction::Nudge => {
+                if let Err(e) = self.write_input(b"\r") {
+                    crate::log::error("PtyWatchdog", &format!("Failed to nudge: {}", e));
+                }
+                *self.awaiting_response.lock() = Some(Instant::now());
+            }
+            PtyIdleAction::Restart => {
+                if let Err(e) = self.shutdown().and_then(|_| self.respawn()) {
+                    crate::log::error("PtyWatchdog", &format!("Failed to restart: {}", e));
+                }
+            }
+        }
+        Some(timeout)
+    }
+
+    /// 自動応答の監査ログ（古い順）
+    pub fn auto_response_audit(&self) -> Vec<AutoResponseRecord> {
+        self.auto_response_audit.lock().iter().cloned().collect()
+    }
+
+    /// rawモードを切り替え（起動中でもすぐ反映）
+    pub fn set_raw_mode(&self, enabled: bool) {
+        self.raw_mode.store(enabled, Ordering::SeqCst);
+    }
+}
+
+/// 応答のないPTYセッションを検出するウォッチドッグ
+///
+/// タイムアウトと対応は設定（`pty_idle_timeout_secs` / `pty_idle_action`）から毎回読むので、
+/// 変更はすぐ反映される。タイムアウトが0なら何もしない。
+pub struct PtyWatchdog {
+    running: Arc<AtomicBool>,
+}
+
+impl PtyWatchdog {
+    /// `interval` ごとに確認するスレッドを開始
+    pub fn start(pty: Arc<Mutex<PtyManager>>, interval: Duration) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+
+        thread::spawn(move || {
+            while flag.load(Ordering::SeqCst) {
+                thread::sleep(interval);
+                let settings = crate::settings::current();
+                if settings.pty_idle_timeout_secs == 0 {
+                    continue;
+                }
+                pty.lock().check_idle(
+                    Duration::from_secs(settings.pty_idle_timeout_secs),
+                    settings.pty_idle_action,
+                );
+            }
+        });
+
+        Self { running }
+    }
+}
+
+impl Drop for PtyWatchdog {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
 }
 
 impl Drop for PtyManager {
@@ -421,7 +794,7 @@ impl Default for PtyManager {
 // ============================================================================
 
 /// 検出されたプロンプトの種類
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
 pub enum PromptType {
     /// ユーザー入力待ち（通常のプロンプト）
     InputReady,
@@ -438,12 +811,87 @@ pub enum PromptType {
 }
 
 /// 選択肢
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
 pub struct ChoiceOption {
     pub number: u8,
     pub label: String,
 }
 
+/// 確認プロンプトへの対応
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoResponseAction {
+    /// 承諾する選択肢を自動で選ぶ
+    AutoAccept,
+    /// 自動で応答せずユーザーに入力を求める（`pty-input-required`）
+    AskUser,
+    /// 拒否する選択肢を自動で選ぶ
+    Deny,
+}
+
+/// プロンプトの種類ごとの自動応答ポリシー
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoResponsePolicy {
+    /// 選択肢付きプロンプト（フォルダの信頼確認・Bypass Permissionsの確認など）
+    pub choice: AutoResponseAction,
+    /// Yes/Noの確認プロンプト
+    pub confirmation: AutoResponseAction,
+}
+
+impl Default for AutoResponsePolicy {
+    fn default() -> Self {
+        Self {
+            choice: AutoResponseAction::AutoAccept,
+            confirmation: AutoResponseAction::AutoAccept,
+        }
+    }
+}
+
+impl AutoResponsePolicy {
+    /// プロンプトに適用する対応（自動応答の対象外ならNone）
+    pub fn action_for(&self, prompt_type: &PromptType) -> Option<AutoResponseAction> {
+        match prompt_type {
+            PromptType::Choice { .. } => Some(self.choice),
+            PromptType::Confirmation { .. } => Some(self.confirmation),
+            _ => None,
+        }
+    }
+}
+
+/// 自動応答の監査ログ1件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoResponseRecord {
+    pub timestamp: String,
+    pub prompt_type: PromptType,
+    /// 実際に行った対応（応答できる選択肢がなければ `AskUser`）
+    pub action: AutoResponseAction,
+    /// 送信した応答（`AskUser` ならNone）
+    pub response: Option<String>,
+    /// プロンプト周辺の出力
+    pub context: String,
+}
+
+impl AutoResponseRecord {
+    /// ポリシーに従って応答を決める
+    pub fn decide(prompt_type: &PromptType, policy: &AutoResponsePolicy, context: &str) -> Self {
+        let requested = policy.action_for(prompt_type).unwrap_or(AutoResponseAction::AskUser);
+        let response = match requested {
+            AutoResponseAction::AutoAccept => PromptDetector::get_auto_response(prompt_type),
+            AutoResponseAction::Deny => PromptDetector::get_decline_response(prompt_type),
+            AutoResponseAction::AskUser => None,
+        };
+        Self {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            prompt_type: prompt_type.clone(),
+            action: if response.is_some() { requested } else { AutoResponseAction::AskUser },
+            response,
+            context: context.to_string(),
+        }
+    }
+}
+
 /// プロンプト検出器
 pub struct PromptDetector;
 
@@ -605,6 +1053,25 @@ impl PromptDetector {
         }
     }
 
+    /// 拒否する応答を返す（拒否の選択肢が見つからなければNone）
+    pub fn get_decline_response(prompt_type: &PromptType) -> Option<String> {
+        match prompt_type {
+            PromptType::Choice { options } => options
+                .iter()
+                .find(|opt| {
+                    let label_lower = opt.label.to_lowercase();
+                    // "No, exit" / "Cancel" / "Don't trust" パターン
+                    label_lower.starts_with("no")
+                        || label_lower.contains("exit")
+                        || label_lower.contains("cancel")
+                        || label_lower.contains("don't")
+                })
+                .map(|opt| format!("{}\n", opt.number)),
+            PromptType::Confirmation { .. } => Some("2\n".to_string()), // 通常 "2" が "No"
+            _ => None,
+        }
+    }
+
     /// 通常の入力プロンプトかどうか
     fn is_input_prompt(output: &str) -> bool {
         let prompt_patterns = ["❯ ", "> "];
@@ -632,6 +1099,21 @@ impl PromptDetector {
 // ヘルパー関数（PtyManagerのメソッドから独立させ、スレッド内で使用可能に）
 // ============================================================================
 
+/// 読み取ったバイト列をUTF-8として復号する
+///
+/// 末尾のマルチバイト文字が途中で切れている場合は `pending` に残し、次のチャンクと連結する。
+fn decode_utf8_chunk(pending: &mut Vec<u8>, bytes: &[u8]) -> String {
+    pending.extend_from_slice(bytes);
+    let valid = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => pending.len(),
+    };
+    let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+    pending.drain(..valid);
+    text
+}
+
 /// ANSIエスケープシーケンスを処理してプレーンテキストに変換
 ///
 /// 処理内容:
@@ -721,3 +1203,134 @@ fn process_ansi(bytes: &[u8]) -> String {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_agent_and_shutdown() {
+        let cwd = std::env::temp_dir().canonicalize().unwrap();
+        let args = vec!["-c".to_string(), "echo \"ready $AGENT_NAME $(pwd)\"; sleep 30".to_string()];
+        let env = HashMap::from([("AGENT_NAME".to_string(), "codex".to_string())]);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut pty = PtyManager::new();
+        let recorded = Arc::clone(&events);
+        pty.set_event_callback(move |event| recorded.lock().push(event));
+        pty.spawn_agent_pty("sh", &args, &env, cwd.to_str()).unwrap();
+        assert!(pty.spawn_agent_pty("sh", &args, &env, None).is_err());
+        assert_eq!(pty.command().map(|c| c.command.as_str()), Some("sh"));
+
+        let expected = format!("ready codex {}", cwd.display());
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !pty.get_output().contains(&expected) && std::time::Instant::now() < deadline {
+            thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(pty.get_output().contains(&expected), "{:?}", pty.get_output());
+
+        let exit = pty.shutdown().unwrap();
+        assert!(exit.killed);
+        assert!(!exit.success);
+        assert!(!pty.is_running());
+        assert!(pty.child_pid().is_none());
+        assert!(pty.get_output().is_empty());
+        assert!(pty.get_output_since(0).chunks.is_empty());
+        assert!(matches!(events.lock().last(), Some(PtyEvent::Exited(e)) if e == &exit));
+
+        assert!(pty.shutdown().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shutdown_with_grandchild_holding_tty() {
+        // 孫プロセスが端末を開いたままだとリーダーのread()は戻らない
+        let args = vec!["-c".to_string(), "sleep 10 & echo started; sleep 30".to_string()];
+        let mut pty = PtyManager::new();
+        pty.spawn_agent_pty("sh", &args, &HashMap::new(), None).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !pty.get_output().contains("started") && std::time::Instant::now() < deadline {
+            thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        let started = std::time::Instant::now();
+        pty.shutdown().unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+        // 切り離したリーダーが残っていても再起動できる
+        pty.spawn_agent_pty("sh", &["-c".to_string(), "sleep 30".to_string()], &HashMap::new(), None).unwrap();
+        assert!(pty.is_running());
+        pty.shutdown().unwrap();
+    }
+
+    #[test]
+    fn test_check_idle() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut pty = PtyManager::new();
+        let recorded = Arc::clone(&events);
+        pty.set_event_callback(move |event| recorded.lock().push(event));
+
+        // 応答待ちでなければ何もしない
+        assert!(pty.check_idle(Duration::ZERO, PtyIdleAction::Notify).is_none());
+
+        let sent = Instant::now() - Duration::from_secs(5);
+        *pty.awaiting_response.lock() = Some(sent);
+        *pty.last_activity.lock() = sent - Duration::from_secs(10);
+        assert!(pty.check_idle(Duration::from_secs(10), PtyIdleAction::Notify).is_none());
+
+        let timeout = pty.check_idle(Duration::from_secs(3), PtyIdleAction::Notify).unwrap();
+        assert!(timeout.elapsed_ms >= 5000);
+        assert!(matches!(events.lock().last(), Some(PtyEvent::Timeout(t)) if t == &timeout));
+
+        // 一度通知したら次の送信まで通知しない
+        assert!(pty.check_idle(Duration::from_secs(3), PtyIdleAction::Notify).is_none());
+    }
+
+    #[test]
+    fn test_auto_response_policy() {
+        let prompt = PromptType::Choice {
+            options: vec![
+                ChoiceOption { number: 1, label: "No, exit".to_string() },
+                ChoiceOption { number: 2, label: "Yes, I accept".to_string() },
+            ],
+        };
+        let mut policy = AutoResponsePolicy::default();
+
+        let accepted = AutoResponseRecord::decide(&prompt, &policy, "Bypass Permissions mode");
+        assert_eq!(accepted.action, AutoResponseAction::AutoAccept);
+        assert_eq!(accepted.response.as_deref(), Some("2\n"));
+
+        policy.choice = AutoResponseAction::Deny;
+        let denied = AutoResponseRecord::decide(&prompt, &policy, "");
+        assert_eq!(denied.action, AutoResponseAction::Deny);
+        assert_eq!(denied.response.as_deref(), Some("1\n"));
+
+        policy.choice = AutoResponseAction::AskUser;
+        let asked = AutoResponseRecord::decide(&prompt, &policy, "");
+        assert_eq!(asked.action, AutoResponseAction::AskUser);
+        assert_eq!(asked.response, None);
+
+        // 拒否の選択肢がなければユーザーに回す
+        let only_yes = PromptType::Choice {
+            options: vec![ChoiceOption { number: 1, label: "Yes, proceed".to_string() }],
+        };
+        policy.choice = AutoResponseAction::Deny;
+        assert_eq!(AutoResponseRecord::decide(&only_yes, &policy, "").action, AutoResponseAction::AskUser);
+    }
+
+    #[test]
+    fn test_decode_utf8_chunk_keeps_split_chars() {
+        let bytes = "\x1b[32mこんにちは\x1b[0m".as_bytes();
+        let mut pending = Vec::new();
+        let first = decode_utf8_chunk(&mut pending, &bytes[..7]);
+        assert_eq!(first, "\x1b[32m");
+        assert_eq!(pending.len(), 2);
+
+        let second = decode_utf8_chunk(&mut pending, &bytes[7..]);
+        assert_eq!(format!("{}{}", first, second), "\x1b[32mこんにちは\x1b[0m");
+        assert!(pending.is_empty());
+
+        assert_eq!(decode_utf8_chunk(&mut pending, b"a\xffb"), "a\u{fffd}b");
+    }
+}
@@ -0,0 +1,123 @@
+//! シークレット（APIキー）ストア
+//!
+//! 翻訳APIのキーなどを `<data_dir>/secrets/secrets.json` に保存する。
+//! アプリデータのエクスポートはデータディレクトリ直下の `*.json` のみを対象とするため、
+//! サブディレクトリに置くことでアーカイブに含まれないようにしている。
+//! 同名の環境変数（大文字）が設定されている場合はそちらを優先する。
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// シークレットのサブディレクトリ名
+pub const SECRETS_DIR: &str = "secrets";
+
+/// シークレットファイル名
+pub const SECRETS_FILE: &str = "secrets.json";
+
+/// DeepL APIキーのシークレット名
+pub const DEEPL_API_KEY: &str = "deepl_api_key";
+
+/// OpenAI APIキーのシークレット名
+pub const OPENAI_API_KEY: &str = "openai_api_key";
+
+/// シークレットストア
+#[derive(Debug, Clone, Default)]
+pub struct SecretStore {
+    /// 保存先（Noneならメモリのみ）
+    path: Option<PathBuf>,
+    /// 名前 -> 値
+    values: BTreeMap<String, String>,
+}
+
+impl SecretStore {
+    /// メモリのみのストアを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// データディレクトリ配下のシークレットファイルのパス
+    pub fn path_in(data_dir: &Path) -> PathBuf {
+        data_dir.join(SECRETS_DIR).join(SECRETS_FILE)
+    }
+
+    /// ファイルから読み込む（存在しなければ空のストア）
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let values = if path.exists() {
+            let content = std::fs::read_to_string(path)?;
+            serde_json::from_str(&content)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            values,
+        })
+    }
+
+    /// ファイルに保存（Unixでは所有者のみ読み書き可能にする）
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(&self.values)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+
+        Ok(())
+    }
+
+    /// シークレットを取得（環境変数を優先）
+    pub fn get(&self, name: &str) -> Option<String> {
+        std::env::var(name.to_uppercase())
+            .ok()
+            .filter(|v| !v.is_empty())
+            .or_else(|| self.values.get(name).cloned())
+    }
+
+    /// シークレットを設定
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_string(), value.to_string());
+    }
+
+    /// シークレットを削除
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.values.remove(name).is_some()
+    }
+
+    /// 保存されているシークレット名一覧（値は返さない）
+    pub fn names(&self) -> Vec<String> {
+        self.values.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("re-voice-secrets-{}", uuid::Uuid::new_v4()));
+        let path = SecretStore::path_in(&dir);
+
+        let mut store = SecretStore::load(&path).unwrap();
+        store.set("re_voice_test_key", "secret");
+        store.save().unwrap();
+
+        let loaded = SecretStore::load(&path).unwrap();
+        assert_eq!(loaded.get("re_voice_test_key").as_deref(), Some("secret"));
+        assert_eq!(loaded.names(), vec!["re_voice_test_key".to_string()]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,281 @@
+//! アプリ設定
+//!
+//! 外部コマンドのパス・ポーリング間隔・既定の話者・タイムアウトなどを
+//! `<app_config_dir>/settings.json` に保存する。各コンポーネントは `current()` で
+//! 現在の設定を参照する（未読み込みなら既定値）。
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+use thiserror::Error;
+
+use crate::pty::{AutoResponsePolicy, PtyIdleAction};
+
+/// 保存ファイル名（設定ディレクトリ直下）
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// 設定エラー
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Invalid setting: {0}")]
+    Invalid(String),
+}
+
+/// アプリ設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// 外部コマンド（claude・yt-dlp・ffmpeg）を探すときにPATHの前に追加するディレクトリ
+    pub extra_path: Vec<String>,
+    /// 子プロセスに追加・上書きする環境変数
+    pub env: BTreeMap<String, String>,
+    /// Claude Code CLIのパス
+    pub claude_path: String,
+    /// yt-dlpのパス
+    pub ytdlp_path: String,
+    /// Codex CLIのパス
+    pub codex_path: String,
+    /// Gemini CLIのパス
+    pub gemini_path: String,
+    /// VOICEVOX EngineのURL
+    pub voicevox_url: String,
+    /// 既定のVOICEVOX話者ID
+    pub default_speaker: i32,
+    /// 既定の出力ディレクトリ（未指定なら画面の既定値）
+    pub output_dir: Option<String>,
+    /// tmuxの状態ポーリング間隔（ミリ秒）
+    pub poller_interval_ms: u64,
+    /// Claude Code実行のタイムアウト（秒）
+    pub executor_timeout_secs: u64,
+    /// PTYの行数
+    pub pty_rows: u16,
+    /// PTYの桁数
+    pub pty_cols: u16,
+    /// PTYの確認プロンプトへの自動応答ポリシー
+    pub auto_response: AutoResponsePolicy,
+    /// メッセージ送信後に出力がないとみなすまでの秒数（0なら監視しない）
+    pub pty_idle_timeout_secs: u64,
+    /// 出力がないときの対応
+    pub pty_idle_action: PtyIdleAction,
+    /// 起動時にエージェントカードサーバーを開くアドレス（例 "127.0.0.1:41241"、Noneなら開かない）
+    pub agent_server_addr: Option<String>,
+    /// エージェントカードサーバーにタスクを送るときのBearerトークン（Noneなら最初に開くときに生成して保存）
+    pub agent_server_token: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            extra_path: default_extra_path(),
+            env: BTreeMap::new(),
+            claude_path: if cfg!(windows) { "claude.exe" } else { "claude" }.to_string(),
+            ytdlp_path: if cfg!(windows) { "yt-dlp.exe" } else { "yt-dlp" }.to_string(),
+            codex_path: if cfg!(windows) { "codex.cmd" } else { "codex" }.to_string(),
+            gemini_path: if cfg!(windows) { "gemini.cmd" } else { "gemini" }.to_string(),
+            voicevox_url: "http://localhost:50021".to_string(),
+            default_speaker: 1, // ずんだもん
+            output_dir: None,
+            poller_interval_ms: 200,
+            executor_timeout_secs: 300,
+            pty_rows: 50,
+            pty_cols: 120,
+            auto_response: AutoResponsePolicy::default(),
+            pty_idle_timeout_secs: 120,
+            pty_idle_action: PtyIdleAction::Notify,
+            agent_server_addr: None,
+            agent_server_token: None,
+        }
+    }
+}
+
+impl Settings {
+    /// ファイルから読み込む（存在しなければ既定値）
+    pub fn load(path: &Path) -> Result<Self, SettingsError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let settings: Self = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// ファイルに保存
+    pub fn save(&self, path: &Path) -> Result<(), SettingsError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 値を検証
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if !(self.voicevox_url.starts_with("http://") || self.voicevox_url.starts_with("https://")) {
+            return Err(SettingsError::Invalid(format!("voicevox_url: {}", self.voicevox_url)));
+        }
+        if [&self.claude_path, &self.ytdlp_path, &self.codex_path, &self.gemini_path].iter().any(|p| p.trim().is_empty()) {
+            return Err(SettingsError::Invalid("command paths must not be empty".to_string()));
+        }
+        if self.poller_interval_ms == 0 || self.executor_timeout_secs == 0 {
+            return Err(SettingsError::Invalid("intervals and timeouts must be positive".to_string()));
+        }
+        if let Some(key) = self.env.keys().find(|k| k.is_empty() || k.contains('=')) {
+            return Err(SettingsError::Invalid(format!("env: invalid name {:?}", key)));
+        }
+        if self.pty_rows == 0 || self.pty_cols == 0 {
+            return Err(SettingsError::Invalid("pty size must be positive".to_string()));
+        }
+        if let Some(ref addr) = self.agent_server_addr {
+            if addr.parse::<std::net::SocketAddr>().is_err() {
+                return Err(SettingsError::Invalid(format!("agent_server_addr: {}", addr)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 指定したキーだけを上書きした設定（`patch` はJSONオブジェクト）
+    pub fn merged(&self, patch: &Value) -> Result<Self, SettingsError> {
+        let Some(patch) = patch.as_object() else {
+            return Err(SettingsError::Invalid("patch must be an object".to_string()));
+        };
+        let mut value = serde_json::to_value(self)?;
+        if let Some(obj) = value.as_object_mut() {
+            for (key, v) in patch {
+                if !obj.contains_key(key) {
+                    return Err(SettingsError::Invalid(format!("unknown setting: {}", key)));
+                }
+                obj.insert(key.clone(), v.clone());
+            }
+        }
+        let settings: Self = serde_json::from_value(value)?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// `extra_path` を前に追加したPATH
+    pub fn command_path(&self) -> String {
+        let current = std::env::var("PATH").unwrap_or_default();
+        self.extra_path
+            .iter()
+            .map(|s| s.as_str())
+            .chain(std::iter::once(current.as_str()))
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(if cfg!(windows) { ";" } else { ":" })
+    }
+}
+
+/// OSごとの既定の追加PATH（パッケージマネージャーのインストール先）
+fn default_extra_path() -> Vec<String> {
+    let dirs: &[&str] = if cfg!(target_os = "macos") {
+        &["/opt/homebrew/bin", "/usr/local/bin"]
+    } else if cfg!(windows) {
+        &[]
+    } else {
+        &["/usr/local/bin"]
+    };
+    dirs.iter().map(|s| s.to_string()).collect()
+}
+
+/// 保存先と現在の設定
+#[derive(Default)]
+struct SettingsStore {
+    path: Option<PathBuf>,
+    settings: Settings,
+}
+
+// グローバル設定
+lazy_static::lazy_static! {
+    static ref GLOBAL_SETTINGS: RwLock<SettingsStore> = RwLock::new(SettingsStore::default());
+}
+
+/// 設定ファイルのパス
+pub fn settings_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    app.path()
+        .app_config_dir()
+        .unwrap_or_else(|_| PathBuf::from("config"))
+        .join(SETTINGS_FILE)
+}
+
+/// 設定ファイルを読み込み、以降の `current()` に反映する
+pub fn init(path: &Path) -> Result<Settings, SettingsError> {
+    let settings = Settings::load(path)?;
+    *GLOBAL_SETTINGS.write() = SettingsStore {
+        path: Some(path.to_path_buf()),
+        settings: settings.clone(),
+    };
+    Ok(settings)
+}
+
+/// 現在の設定
+pub fn current() -> Settings {
+    GLOBAL_SETTINGS.read().settings.clone()
+}
+
+/// エージェントカードサーバーのトークン（なければ生成して保存する）
+pub fn agent_server_token() -> Result<String, SettingsError> {
+    if let Some(token) = current().agent_server_token.filter(|t| !t.is_empty()) {
+        return Ok(token);
+    }
+    let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    update(&serde_json::json!({ "agent_server_token": token }))?;
+    Ok(token)
+}
+
+/// 指定したキーを更新して保存し、更新後の設定を返す
+pub fn update(patch: &Value) -> Result<Settings, SettingsError> {
+    let mut store = GLOBAL_SETTINGS.write();
+    let settings = store.settings.merged(patch)?;
+    if let Some(ref path) = store.path {
+        settings.save(path)?;
+    }
+    store.settings = settings.clone();
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merged() {
+        let settings = Settings::default();
+        let updated = settings
+            .merged(&serde_json::json!({ "default_speaker": 3, "voicevox_url": "http://127.0.0.1:50021" }))
+            .unwrap();
+        assert_eq!(updated.default_speaker, 3);
+        assert_eq!(updated.voicevox_url, "http://127.0.0.1:50021");
+        assert_eq!(updated.ytdlp_path, settings.ytdlp_path);
+
+        assert!(matches!(settings.merged(&serde_json::json!({ "unknown": 1 })), Err(SettingsError::Invalid(_))));
+        assert!(matches!(settings.merged(&serde_json::json!({ "pty_rows": 0 })), Err(SettingsError::Invalid(_))));
+        assert!(matches!(settings.merged(&serde_json::json!({ "voicevox_url": "localhost" })), Err(SettingsError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_load_partial_file() {
+        let dir = std::env::temp_dir().join(format!("re-voice-settings-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(SETTINGS_FILE);
+        assert_eq!(Settings::load(&path).unwrap(), Settings::default());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, r#"{"poller_interval_ms": 500}"#).unwrap();
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.poller_interval_ms, 500);
+        assert_eq!(settings.pty_cols, 120);
+
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load(&path).unwrap(), settings);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,519 @@
+//! 吹替音声トラックの組み立て
+//!
+//! セグメントごとのWAVを字幕の開始時刻に配置し、無音で埋めた1本の音声トラックを作る。
+//! VOICEVOXの出力（16bit PCM）をサンプル単位で連結する。
+//! 前のクリップと重なる場合は前のクリップの終了まで後ろにずらす。
+//! VOICEVOXの出力は前後に無音が入るため、配置の前に削ってから長さを計算する。
+//! 次の字幕までに収まらないクリップは、設定があればffmpegの `atempo` で縮める。
+//! それでも収まらない分は、設定があればフェードアウトを付けて切り詰める。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::acp::subtitle_parser::SubtitleSegment;
+
+/// 音声トラック組み立てエラー
+#[derive(Debug, Error)]
+pub enum TimelineError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid WAV file {path}: {reason}")]
+    InvalidWav { path: String, reason: String },
+
+    #[error("Sample rate mismatch: expected {expected} Hz, got {actual} Hz in {path}")]
+    SampleRateMismatch { path: String, expected: u32, actual: u32 },
+
+    #[error("Time-stretch failed: {0}")]
+    Stretch(String),
+}
+
+/// 16bit PCMの音声データ（モノラル）
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcmAudio {
+    pub sample_rate: u32,
+    pub samples: Vec<i16>,
+}
+
+impl PcmAudio {
+    /// 長さ（ミリ秒）
+    pub fn duration_ms(&self) -> u64 {
+        if self.sample_rate == 0 {
+            return 0;
+        }
+        self.samples.len() as u64 * 1000 / self.sample_rate as u64
+    }
+
+    /// WAVファイルを読み込む（ステレオはモノラルに変換）
+    pub fn read_wav(path: &Path) -> Result<Self, TimelineError> {
+        let bytes = std::fs::read(path)?;
+        let invalid = |reason: &str| TimelineError::InvalidWav {
+            path: path.display().to_string(),
+            reason: reason.to_string(),
+        };
+
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(invalid("not a RIFF/WAVE file"));
+        }
+
+        let mut format: Option<(u16, u16, u32, u16)> = None;
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let id = &bytes[pos..pos + 4];
+            let size = u32::from_le_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]]) as usize;
+            let body = pos + 8;
+            let end = (body + size).min(bytes.len());
+
+            match id {
+                b"fmt " if size >= 16 && body + 16 <= bytes.len() => {
+                    let u16_at = |i: usize| u16::from_le_bytes([bytes[body + i], bytes[body + i + 1]]);
+                    let rate = u32::from_le_bytes([bytes[body + 4], bytes[body + 5], bytes[body + 6], bytes[body + 7]]);
+                    format = Some((u16_at(0), u16_at(2), rate, u16_at(14)));
+                }
+                b"data" => {
+                    let (audio_format, channels, sample_rate, bits) = format.ok_or_else(|| invalid("data before fmt"))?;
+                    if audio_format != 1 || bits != 16 || channels == 0 {
+                        return Err(invalid("only 16-bit PCM is supported"));
+                    }
+                    let samples = bytes[body..end]
+                        .chunks_exact(2 * channels as usize)
+                        .map(|frame| {
+                            let sum: i32 = frame
+                                .chunks_exact(2)
+                                .map(|s| i16::from_le_bytes([s[0], s[1]]) as i32)
+                                .sum();
+                            (sum / channels as i32) as i16
+                        })
+                        .collect();
+                    return Ok(Self { sample_rate, samples });
+                }
+                _ => {}
+            }
+            // チャンクは2バイト境界に揃う
+            pos = body + size + (size & 1);
+        }
+
+        Err(invalid("missing data chunk"))
+    }
+
+    /// WAVファイルに書き出す
+    pub fn write_wav(&self, path: &Path) -> Result<(), TimelineError> {
+        let data_len = (self.samples.len() * 2) as u32;
+        let mut out = Vec::with_capacity(44 + data_len as usize);
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data_len).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        out.extend_from_slice(&1u16.to_le_bytes()); // モノラル
+        out.extend_from_slice(&self.sample_rate.to_le_bytes());
+        out.extend_from_slice(&(self.sample_rate * 2).to_le_bytes());
+        out.extend_from_slice(&2u16.to_le_bytes());
+        out.extend_from_slice(&16u16.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&data_len.to_le_bytes());
+        for sample in &self.samples {
+            out.extend_from_slice(&sample.to_le_bytes());
+        }
+        std::fs::write(path, out)?;
+        Ok(())
+    }
+}
+
+/// 波形表示用のピークデータ（-1.0〜1.0）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Waveform {
+    pub sample_rate: u32,
+    /// 1ビンの長さ（ミリ秒）
+    pub bin_ms: u32,
+    pub duration_ms: u64,
+    /// ビンごとの最小値
+    pub min: Vec<f32>,
+    /// ビンごとの最大値
+    pub max: Vec<f32>,
+}
+
+impl PcmAudio {
+    /// `bin_ms` ごとの最小値・最大値に間引く
+    pub fn waveform(&self, bin_ms: u32) -> Waveform {
+        let bin_ms = bin_ms.max(1);
+        let bin_len = ((self.sample_rate as u64 * bin_ms as u64 / 1000) as usize).max(1);
+        let scale = i16::MAX as f32;
+
+        let (min, max) = self
+            .samples
+            .chunks(bin_len)
+            .map(|bin| {
+                let lo = bin.iter().copied().min().unwrap_or(0);
+                let hi = bin.iter().copied().max().unwrap_or(0);
+                ((lo as f32 / scale).max(-1.0), hi as f32 / scale)
+            })
+            .unzip();
+
+        Waveform {
+            sample_rate: self.sample_rate,
+            bin_ms,
+            duration_ms: self.duration_ms(),
+            min,
+            max,
+        }
+    }
+}
+
+/// 前後の無音削除の設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SilenceTrim {
+    /// これ以下の振幅を無音とみなす（最大振幅に対する割合）
+    #[serde(default = "default_silence_threshold")]
+    pub threshold: f32,
+    /// 前後それぞれで削る最大の長さ（ミリ秒、0で無効）
+    #[serde(default = "default_max_trim_ms")]
+    pub max_trim_ms: u32,
+}
+
+fn default_silence_threshold() -> f32 {
+    0.01
+}
+
+fn default_max_trim_ms() -> u32 {
+    300
+}
+
+impl Default for SilenceTrim {
+    fn default() -> Self {
+        Self {
+            threshold: default_silence_threshold(),
+            max_trim_ms: default_max_trim_ms(),
+        }
+    }
+}
+
+impl PcmAudio {
+    /// 前後の無音を削る。削った長さ（ミリ秒）も返す
+    pub fn trim_silence(&self, trim: &SilenceTrim) -> (PcmAudio, u64) {
+        let max_trim = (self.sample_rate as u64 * trim.max_trim_ms as u64 / 1000) as usize;
+        let level = (trim.threshold.clamp(0.0, 1.0) * i16::MAX as f32) as i32;
+        let audible = |s: &i16| (*s as i32).abs() > level;
+
+        let len = self.samples.len();
+        let lead = self.samples.iter().position(audible).unwrap_or(len).min(max_trim);
+        let tail = self.samples[lead..].iter().rev().position(audible).unwrap_or(len - lead).min(max_trim);
+
+        let samples = self.samples[lead..len - tail].to_vec();
+        let trimmed_ms = if self.sample_rate == 0 {
+            0
+        } else {
+            (lead + tail) as u64 * 1000 / self.sample_rate as u64
+        };
+        (PcmAudio { sample_rate: self.sample_rate, samples }, trimmed_ms)
+    }
+}
+
+/// 次の字幕までに収まらないクリップの伸縮設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeStretch {
+    /// 最大の速度倍率（これを超える分は後ろにずらす。大きいほど不自然になる）
+    #[serde(default = "default_max_tempo")]
+    pub max_tempo: f64,
+    /// 縮めても収まらない分を切り詰める（falseなら後ろにずらす）
+    #[serde(default)]
+    pub cut_overflow: bool,
+}
+
+/// 切り詰めたクリップの末尾に付けるフェードアウト（ミリ秒）
+const CUT_FADE_MS: u64 = 20;
+
+fn default_max_tempo() -> f64 {
+    1.25
+}
+
+impl Default for TimeStretch {
+    fn default() -> Self {
+        Self { max_tempo: default_max_tempo(), cut_overflow: false }
+    }
+}
+
+impl TimeStretch {
+    /// 長さ `duration_ms` のクリップを `window_ms` に収めるための倍率（不要ならNone）
+    pub fn tempo_for(&self, duration_ms: u64, window_ms: u64) -> Option<f64> {
+        if window_ms == 0 || duration_ms <= window_ms {
+            return None;
+        }
+        let tempo = (duration_ms as f64 / window_ms as f64).min(self.max_tempo.max(1.0));
+        (tempo > 1.01).then_some(tempo)
+    }
+}
+
+impl PcmAudio {
+    /// `max_ms` で切り詰め、末尾 `fade_ms` をフェードアウトする
+    pub fn truncate(&self, max_ms: u64, fade_ms: u64) -> PcmAudio {
+        let to_samples = |ms: u64| (ms * self.sample_rate as u64 / 1000) as usize;
+        let len = to_samples(max_ms).min(self.samples.len());
+        let mut samples = self.samples[..len].to_vec();
+
+        let fade = to_samples(fade_ms).min(len);
+        for (i, sample) in samples[len - fade..].iter_mut().enumerate() {
+            *sample = (*sample as i64 * (fade - i) as i64 / fade as i64) as i16;
+        }
+        PcmAudio { sample_rate: self.sample_rate, samples }
+    }
+}
+
+/// ffmpegでクリップのテンポを変える（一時ファイル経由）
+fn stretch_clip(audio: &PcmAudio, tempo: f64) -> Result<PcmAudio, TimelineError> {
+    let dir = std::env::temp_dir();
+    let id = uuid::Uuid::new_v4();
+    let input = dir.join(format!("re-voice-stretch-{}-in.wav", id));
+    let output = dir.join(format!("re-voice-stretch-{}-out.wav", id));
+
+    audio.write_wav(&input)?;
+    let result = crate::ffmpeg::Ffmpeg::new()
+        .time_stretch(&input.to_string_lossy(), &output.to_string_lossy(), tempo)
+        .map_err(|e| TimelineError::Stretch(e.to_string()))
+        .and_then(|_| PcmAudio::read_wav(&output));
+
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+    result
+}
+
+/// 配置するクリップ
+#[derive(Debug, Clone)]
+pub struct TimelineClip {
+    /// 字幕の開始時刻（ミリ秒）
+    pub start_ms: u64,
+    /// 音声データ
+    pub audio: PcmAudio,
+}
+
+/// 組み立て結果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimelineReport {
+    /// 配置したクリップ数
+    pub clips: usize,
+    /// 前のクリップと重なったため後ろにずらしたクリップ数
+    pub shifted: usize,
+    /// 最大のずれ（ミリ秒）
+    pub max_drift_ms: u64,
+    /// 前後の無音として削った長さの合計（ミリ秒）
+    pub trimmed_ms: u64,
+    /// 次の字幕に収めるために縮めたクリップ数
+    pub stretched: usize,
+    /// 次の字幕に収まらず切り詰めたクリップ数
+    #[serde(default)]
+    pub cut: usize,
+    /// トラックの長さ（ミリ秒）
+    pub duration_ms: u64,
+}
+
+/// クリップを開始時刻に配置して1本のトラックにする
+///
+/// `min_duration_ms` 未満にはならないよう末尾を無音で埋める（元動画の長さに合わせる）。
+pub fn assemble(
+    clips: &[TimelineClip],
+    sample_rate: u32,
+    min_duration_ms: u64,
+) -> (PcmAudio, TimelineReport) {
+    let to_samples = |ms: u64| (ms * sample_rate as u64 / 1000) as usize;
+
+    let mut samples: Vec<i16> = Vec::new();
+    let mut report = TimelineReport::default();
+    let mut cursor = 0usize;
+
+    let mut ordered: Vec<&TimelineClip> = clips.iter().collect();
+    ordered.sort_by_key(|c| c.start_ms);
+
+    for clip in ordered {
+        let wanted = to_samples(clip.start_ms);
+        let start = wanted.max(cursor);
+        if start > wanted {
+            report.shifted += 1;
+            let drift_ms = ((start - wanted) as u64 * 1000) / sample_rate as u64;
+            report.max_drift_ms = report.max_drift_ms.max(drift_ms);
+        }
+
+        samples.resize(start, 0);
+        samples.extend_from_slice(&clip.audio.samples);
+        cursor = samples.len();
+        report.clips += 1;
+    }
+
+    let min_len = to_samples(min_duration_ms);
+    if samples.len() < min_len {
+        samples.resize(min_len, 0);
+    }
+
+    let track = PcmAudio { sample_rate, samples };
+    report.duration_ms = track.duration_ms();
+    (track, report)
+}
+
+/// 音声生成ステージの出力（`audio_{:04}.wav`）を字幕の開始時刻に配置する
+///
+/// 各WAVの前後の無音を削ってから配置する。`stretch` があれば次の字幕の開始までに
+/// 収まらないクリップを縮める（ffmpegが使えなければ縮めずに後ろにずらす）。
+/// `cut_overflow` なら縮めても収まらない分を切り詰め、字幕の時刻からずれないようにする。
+/// WAVが1つもなければNoneを返す。トラックは最後の字幕の終了時刻まで無音で埋める。
+pub fn assemble_from_dir(
+    segments: &[SubtitleSegment],
+    audio_dir: &Path,
+    trim: &SilenceTrim,
+    stretch: Option<&TimeStretch>,
+) -> Result<Option<(PcmAudio, TimelineReport)>, TimelineError> {
+    let mut clips = Vec::new();
+    let mut trimmed_ms = 0;
+    let mut stretched = 0;
+    let mut cut = 0;
+    let mut sample_rate: Option<u32> = None;
+
+    for (i, segment) in segments.iter().enumerate() {
+        let path = audio_dir.join(format!("audio_{:04}.wav", i));
+        if !path.exists() {
+            continue;
+        }
+
+        let audio = PcmAudio::read_wav(&path)?;
+        match sample_rate {
+            None => sample_rate = Some(audio.sample_rate),
+            Some(expected) if expected != audio.sample_rate => {
+                return Err(TimelineError::SampleRateMismatch {
+                    path: path.display().to_string(),
+                    expected,
+                    actual: audio.sample_rate,
+                });
+            }
+            Some(_) => {}
+        }
+        let (mut audio, trimmed) = audio.trim_silence(trim);
+        trimmed_ms += trimmed;
+
+        // 次の字幕の開始までの長さ
+        let window_ms = segments
+            .iter()
+            .map(|s| s.start_ms)
+            .filter(|start| *start > segment.start_ms)
+            .min()
+            .map(|next| next - segment.start_ms);
+        let tempo = stretch.zip(window_ms).and_then(|(config, window)| config.tempo_for(audio.duration_ms(), window));
+        if let Some(tempo) = tempo {
+            match stretch_clip(&audio, tempo) {
+                Ok(fitted) => {
+                    audio = fitted;
+                    stretched += 1;
+                }
+                Err(e) => crate::log::warn("Timeline", &format!("Segment {} not stretched: {}", i, e)),
+            }
+        }
+        if let (Some(config), Some(window)) = (stretch, window_ms) {
+            if config.cut_overflow && audio.duration_ms() > window {
+                audio = audio.truncate(window, CUT_FADE_MS);
+                cut += 1;
+            }
+        }
+
+        clips.push(TimelineClip { start_ms: segment.start_ms, audio });
+    }
+
+    let Some(sample_rate) = sample_rate else {
+        return Ok(None);
+    };
+    let end_ms = segments.iter().map(|s| s.end_ms).max().unwrap_or(0);
+    let (track, mut report) = assemble(&clips, sample_rate, end_ms);
+    report.trimmed_ms = trimmed_ms;
+    report.stretched = stretched;
+    report.cut = cut;
+    Ok(Some((track, report)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(start_ms: u64, len: usize, value: i16) -> TimelineClip {
+        TimelineClip {
+            start_ms,
+            audio: PcmAudio { sample_rate: 1000, samples: vec![value; len] },
+        }
+    }
+
+    #[test]
+    fn test_assemble_places_clips_at_cue_times() {
+        let (track, report) = assemble(&[clip(10, 5, 1), clip(30, 5, 2)], 1000, 50);
+        assert_eq!(track.samples.len(), 50);
+        assert_eq!(track.samples[9], 0);
+        assert_eq!(track.samples[10], 1);
+        assert_eq!(track.samples[30], 2);
+        assert_eq!(report.shifted, 0);
+    }
+
+    #[test]
+    fn test_assemble_shifts_overlapping_clip() {
+        let (track, report) = assemble(&[clip(0, 20, 1), clip(10, 5, 2)], 1000, 0);
+        assert_eq!(track.samples.len(), 25);
+        assert_eq!(track.samples[20], 2);
+        assert_eq!(report.shifted, 1);
+        assert_eq!(report.max_drift_ms, 10);
+    }
+
+    #[test]
+    fn test_trim_silence() {
+        let audio = PcmAudio { sample_rate: 1000, samples: vec![0, 0, 0, 5000, -5000, 10, 0] };
+        let (trimmed, ms) = audio.trim_silence(&SilenceTrim::default());
+        assert_eq!(trimmed.samples, vec![5000, -5000]);
+        assert_eq!(ms, 5);
+
+        // 最大値で打ち切る
+        let limit = SilenceTrim { max_trim_ms: 2, ..Default::default() };
+        let (trimmed, _) = audio.trim_silence(&limit);
+        assert_eq!(trimmed.samples, vec![0, 5000, -5000]);
+
+        let silent = PcmAudio { sample_rate: 1000, samples: vec![0; 4] };
+        assert_eq!(silent.trim_silence(&limit).0.samples.len(), 0);
+    }
+
+    #[test]
+    fn test_tempo_for() {
+        let config = TimeStretch::default();
+        assert_eq!(config.tempo_for(1000, 1000), None);
+        assert_eq!(config.tempo_for(1100, 1000), Some(1.1));
+        // 上限を超える分は縮めない
+        assert_eq!(config.tempo_for(3000, 1000), Some(1.25));
+    }
+
+    #[test]
+    fn test_truncate_with_fade() {
+        let audio = PcmAudio { sample_rate: 1000, samples: vec![100; 50] };
+        let cut = audio.truncate(30, 10);
+        assert_eq!(cut.samples.len(), 30);
+        assert_eq!(cut.samples[19], 100);
+        assert_eq!(cut.samples[20], 100);
+        assert_eq!(cut.samples[25], 50);
+        assert!(cut.samples[29] < 20);
+        // 短いクリップはそのまま
+        assert_eq!(audio.truncate(100, 0), audio);
+    }
+
+    #[test]
+    fn test_waveform_bins() {
+        let audio = PcmAudio {
+            sample_rate: 1000,
+            samples: vec![0, i16::MAX, -100, 0, i16::MIN, 0, 100],
+        };
+        let waveform = audio.waveform(3);
+        assert_eq!(waveform.max.len(), 3);
+        assert_eq!(waveform.max[0], 1.0);
+        assert_eq!(waveform.min[1], -1.0);
+        assert_eq!(waveform.min[2], waveform.max[2]);
+    }
+
+    #[test]
+    fn test_wav_roundtrip() {
+        let path = std::env::temp_dir().join(format!("re-voice-timeline-{}.wav", uuid::Uuid::new_v4()));
+        let audio = PcmAudio { sample_rate: 24_000, samples: vec![0, 100, -100, i16::MAX] };
+        audio.write_wav(&path).unwrap();
+        assert_eq!(PcmAudio::read_wav(&path).unwrap(), audio);
+        let _ = std::fs::remove_file(path);
+    }
+}
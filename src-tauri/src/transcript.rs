@@ -0,0 +1,362 @@
+//! セッションの記録
+//!
+//! PTYの出力・CLIエグゼキューターのstream-jsonイベント・送信したプロンプトを
+//! セッションごとに `<data_dir>/transcripts/<session_id>.jsonl` へ追記し、
+//! あとからMarkdownまたはHTMLに書き出せるようにする。
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+/// 記録の保存先ディレクトリ名（データディレクトリ直下）
+pub const TRANSCRIPTS_DIR: &str = "transcripts";
+
+/// 記録エラー
+#[derive(Debug, Error)]
+pub enum TranscriptError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Transcript not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid session id: {0}")]
+    InvalidSessionId(String),
+}
+
+/// 記録の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// 送信したプロンプト
+    Prompt,
+    /// エージェントの出力
+    Output,
+    /// イベント（stream-jsonの1行・自動応答・終了など）
+    Event,
+}
+
+/// 記録1件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub timestamp: String,
+    pub kind: EntryKind,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl TranscriptEntry {
+    fn new(kind: EntryKind, text: &str, data: Option<Value>) -> Self {
+        Self {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            kind,
+            text: text.to_string(),
+            data,
+        }
+    }
+
+    pub fn prompt(text: &str) -> Self {
+        Self::new(EntryKind::Prompt, text, None)
+    }
+
+    pub fn output(text: &str) -> Self {
+        Self::new(EntryKind::Output, text, None)
+    }
+
+    /// イベント（`name` は表示用の見出し）
+    pub fn event(name: &str, data: Value) -> Self {
+        Self::new(EntryKind::Event, name, Some(data))
+    }
+}
+
+/// 記録の一覧用
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptSummary {
+    pub session_id: String,
+    /// ファイルサイズ（バイト）
+    pub size: u64,
+    /// 最終更新日時
+    pub modified: Option<String>,
+}
+
+/// 書き出し形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    Markdown,
+    Html,
+}
+
+/// セッションごとのJSONLファイル
+pub struct TranscriptStore {
+    dir: PathBuf,
+}
+
+impl TranscriptStore {
+    /// 保存先ディレクトリを指定して作成
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, session_id: &str) -> Result<PathBuf, TranscriptError> {
+        // ファイル名に使うので区切り文字などを含むIDは拒否する
+        let valid = !session_id.is_empty()
+            && session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(TranscriptError::InvalidSessionId(session_id.to_string()));
+        }
+        Ok(self.dir.join(format!("{}.jsonl", session_id)))
+    }
+
+    /// 1件追記
+    pub fn append(&self, session_id: &str, entry: &TranscriptEntry) -> Result<(), TranscriptError> {
+        let path = self.path(session_id)?;
+        fs::create_dir_all(&self.dir)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// セッションの記録をすべて読み込む（壊れた行は読み飛ばす）
+    pub fn load(&self, session_id: &str) -> Result<Vec<TranscriptEntry>, TranscriptError> {
+        let path = self.path(session_id)?;
+        if !path.exists() {
+            return Err(TranscriptError::NotFound(session_id.to_string()));
+        }
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// 記録の一覧（新しい順）
+    pub fn list(&self) -> Result<Vec<TranscriptSummary>, TranscriptError> {
+        let mut summaries = Vec::new();
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Ok(summaries);
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let metadata = entry.metadata()?;
+            summaries.push(TranscriptSummary {
+                session_id: session_id.to_string(),
+                size: metadata.len(),
+                modified: metadata
+                    .modified()
+                    .ok()
+                    .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339()),
+            });
+        }
+        summaries.sort_by(|a, b| b.modified.cmp(&a.modified));
+        Ok(summaries)
+    }
+
+    /// MarkdownまたはHTMLに書き出す
+    pub fn export(&self, session_id: &str, format: TranscriptFormat) -> Result<String, TranscriptError> {
+        let entries = self.load(session_id)?;
+        Ok(match format {
+            TranscriptFormat::Markdown => render_markdown(session_id, &entries),
+            TranscriptFormat::Html => render_html(session_id, &entries),
+        })
+    }
+}
+
+/// 表示用のブロック（連続する出力は1つにまとめる）
+fn blocks(entries: &[TranscriptEntry]) -> Vec<(String, EntryKind, String, String)> {
+    let mut blocks: Vec<(String, EntryKind, String, String)> = Vec::new();
+    for entry in entries {
+        if entry.kind == EntryKind::Output {
+            if let Some(last) = blocks.last_mut().filter(|b| b.1 == EntryKind::Output) {
+                last.3.push_str(&entry.text);
+                continue;
+            }
+        }
+        let (title, body) = match entry.kind {
+            EntryKind::Prompt => ("Prompt".to_string(), entry.text.clone()),
+            EntryKind::Output => ("Output".to_string(), entry.text.clone()),
+            EntryKind::Event => (
+                format!("Event: {}", entry.text),
+                entry
+                    .data
+                    .as_ref()
+                    .map(|d| serde_json::to_string_pretty(d).unwrap_or_default())
+                    .unwrap_or_default(),
+            ),
+        };
+        blocks.push((entry.timestamp.clone(), entry.kind, title, body));
+    }
+    blocks
+}
+
+fn render_markdown(session_id: &str, entries: &[TranscriptEntry]) -> String {
+    let mut md = format!("# Transcript: {}\n\n", session_id);
+    for (timestamp, kind, title, body) in blocks(entries) {
+        let lang = if kind == EntryKind::Event { "json" } else { "text" };
+        // 本文にバッククォートが続いていてもフェンスが閉じないようにする
+        let fence = "`".repeat(body.split('\n').map(count_backticks).max().unwrap_or(0).max(2) + 1);
+        md.push_str(&format!(
+            "## {} ({})\n\n{}{}\n{}\n{}\n\n",
+            title,
+            timestamp,
+            fence,
+            lang,
+            body.trim_end(),
+            fence
+        ));
+    }
+    md
+}
+
+fn count_backticks(line: &str) -> usize {
+    line.chars().take_while(|c| *c == '`').count()
+}
+
+fn render_html(session_id: &str, entries: &[TranscriptEntry]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Transcript: {id}</title>\n\
+         <style>body{{font-family:sans-serif;max-width:960px;margin:auto}}\
+         pre{{white-space:pre-wrap;background:#f6f8fa;padding:8px}}\
+         .prompt pre{{background:#e8f0fe}}.event pre{{color:#555}}</style>\n\
+         </head>\n<body>\n<h1>Transcript: {id}</h1>\n",
+        id = escape_html(session_id)
+    );
+    for (timestamp, kind, title, body) in blocks(entries) {
+        let class = match kind {
+            EntryKind::Prompt => "prompt",
+            EntryKind::Output => "output",
+            EntryKind::Event => "event",
+        };
+        html.push_str(&format!(
+            "<section class=\"{}\">\n<h2>{} <small>{}</small></h2>\n<pre>{}</pre>\n</section>\n",
+            class,
+            escape_html(&title),
+            escape_html(&timestamp),
+            escape_html(body.trim_end())
+        ));
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// グローバル記録先（`init` するまでは記録しない）
+lazy_static::lazy_static! {
+    static ref GLOBAL_TRANSCRIPTS: Mutex<Option<TranscriptStore>> = Mutex::new(None);
+}
+
+/// 記録先ディレクトリを設定
+pub fn init(dir: &Path) {
+    *GLOBAL_TRANSCRIPTS.lock() = Some(TranscriptStore::new(dir));
+}
+
+/// 新しいセッションID（`<prefix>-<日時>-<乱数>`）
+pub fn new_session_id(prefix: &str) -> String {
+    let uuid = uuid::Uuid::new_v4().simple().to_string();
+    format!("{}-{}-{}", prefix, chrono::Local::now().format("%Y%m%d-%H%M%S"), &uuid[..8])
+}
+
+/// 記録する（未初期化なら何もしない、失敗はログに残すだけ）
+pub fn record(session_id: &str, entry: TranscriptEntry) {
+    if let Some(ref store) = *GLOBAL_TRANSCRIPTS.lock() {
+        if let Err(e) = store.append(session_id, &entry) {
+            crate::log::error("Transcript", &format!("Failed to record {}: {}", session_id, e));
+        }
+    }
+}
+
+/// 記録の一覧
+pub fn list() -> Result<Vec<TranscriptSummary>, TranscriptError> {
+    match *GLOBAL_TRANSCRIPTS.lock() {
+        Some(ref store) => store.list(),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 記録を読み込む
+pub fn load(session_id: &str) -> Result<Vec<TranscriptEntry>, TranscriptError> {
+    match *GLOBAL_TRANSCRIPTS.lock() {
+        Some(ref store) => store.load(session_id),
+        None => Err(TranscriptError::NotFound(session_id.to_string())),
+    }
+}
+
+/// 記録を書き出す
+pub fn export(session_id: &str, format: TranscriptFormat) -> Result<String, TranscriptError> {
+    match *GLOBAL_TRANSCRIPTS.lock() {
+        Some(ref store) => store.export(session_id, format),
+        None => Err(TranscriptError::NotFound(session_id.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> (TranscriptStore, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("re-voice-transcript-{}", uuid::Uuid::new_v4()));
+        (TranscriptStore::new(&dir), dir)
+    }
+
+    #[test]
+    fn test_append_load_and_list() {
+        let (store, dir) = temp_store();
+        let id = new_session_id("pty");
+        store.append(&id, &TranscriptEntry::prompt("翻訳して")).unwrap();
+        store.append(&id, &TranscriptEntry::output("Hello ")).unwrap();
+        store.append(&id, &TranscriptEntry::output("world")).unwrap();
+        store.append(&id, &TranscriptEntry::event("exited", serde_json::json!({ "exitCode": 0 }))).unwrap();
+
+        let entries = store.load(&id).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].kind, EntryKind::Prompt);
+        assert_eq!(store.list().unwrap()[0].session_id, id);
+
+        assert!(matches!(store.append("../x", &TranscriptEntry::output("")), Err(TranscriptError::InvalidSessionId(_))));
+        assert!(matches!(store.load("missing"), Err(TranscriptError::NotFound(_))));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export() {
+        let (store, dir) = temp_store();
+        store.append("s1", &TranscriptEntry::prompt("<run>")).unwrap();
+        store.append("s1", &TranscriptEntry::output("a")).unwrap();
+        store.append("s1", &TranscriptEntry::output("b```")).unwrap();
+
+        let md = store.export("s1", TranscriptFormat::Markdown).unwrap();
+        assert!(md.starts_with("# Transcript: s1"));
+        assert_eq!(md.matches("## Output").count(), 1);
+        assert!(md.contains("ab```"));
+
+        let html = store.export("s1", TranscriptFormat::Html).unwrap();
+        assert!(html.contains("&lt;run&gt;"));
+        assert!(html.contains("<section class=\"output\">"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,308 @@
+//! 音声合成バックエンド
+//!
+//! 音声生成ステージが使うTTSエンジンを切り替える。VOICEVOXとCOEIROINK（VOICEVOX互換API）は
+//! ローカルのEngine、OpenAI TTSはAPIキーがあればEngineを起動せずに使える。
+
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::acp::translator::primary_language;
+use crate::secrets::{SecretStore, OPENAI_API_KEY};
+use crate::timeline::PcmAudio;
+use crate::voicevox::{SpeedFit, SynthesisOptions, VoicevoxClient, VoicevoxError};
+
+/// COEIROINK（v1）EngineのURL
+pub const COEIROINK_URL: &str = "http://localhost:50031";
+
+/// OpenAI TTSの既定モデル
+pub const DEFAULT_OPENAI_TTS_MODEL: &str = "tts-1";
+
+/// OpenAI TTSの既定の声
+pub const DEFAULT_OPENAI_VOICE: &str = "alloy";
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// TTSエラー
+#[derive(Debug, Error)]
+pub enum TtsError {
+    #[error("Missing API key: {0}")]
+    MissingApiKey(String),
+
+    #[error("Request failed: {0}")]
+    Request(String),
+
+    #[error(transparent)]
+    Voicevox(#[from] VoicevoxError),
+
+    #[error("File I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// TTSエンジン
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsProvider {
+    /// VOICEVOX Engine
+    #[default]
+    Voicevox,
+    /// COEIROINK（VOICEVOX互換API）
+    Coeiroink,
+    /// OpenAI Audio Speech API
+    Openai,
+}
+
+/// 音声生成ステージのTTS設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TtsConfig {
+    #[serde(default)]
+    pub provider: TtsProvider,
+    /// EngineのURL（VOICEVOX互換のみ。省略時は各エンジンの既定）
+    #[serde(default)]
+    pub url: Option<String>,
+    /// モデル（OpenAIのみ）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 声の名前（OpenAIのみ。VOICEVOX互換は話者IDで指定）
+    #[serde(default)]
+    pub voice: Option<String>,
+}
+
+/// 音声合成バックエンド
+#[async_trait]
+pub trait TtsBackend: Send + Sync {
+    fn provider(&self) -> TtsProvider;
+
+    /// 合成できる言語か（既定は日本語のみ。`ja-JP` などの地域付きも可）
+    fn supports_language(&self, lang: &str) -> bool {
+        primary_language(lang) == "ja"
+    }
+
+    /// エンジンに接続できるか
+    async fn is_available(&self) -> bool;
+
+    /// テキストを合成して `output_path` にWAVで保存
+    async fn synthesize(
+        &self,
+        text: &str,
+        options: &SynthesisOptions,
+        output_path: &str,
+    ) -> Result<(), TtsError>;
+
+    /// `window_ms` に収まるよう話速を上げて合成し、使った話速を返す
+    async fn synthesize_fitted(
+        &self,
+        text: &str,
+        options: &SynthesisOptions,
+        window_ms: u64,
+        fit: &SpeedFit,
+        output_path: &str,
+    ) -> Result<f64, TtsError>;
+}
+
+/// VOICEVOX互換Engine（VOICEVOX / COEIROINK）
+pub struct VoicevoxBackend {
+    provider: TtsProvider,
+    client: VoicevoxClient,
+}
+
+impl VoicevoxBackend {
+    pub fn new(provider: TtsProvider, url: &str) -> Self {
+        Self { provider, client: VoicevoxClient::with_url(url) }
+    }
+}
+
+#[async_trait]
+impl TtsBackend for VoicevoxBackend {
+    fn provider(&self) -> TtsProvider {
+        self.provider
+    }
+
+    async fn is_available(&self) -> bool {
+        self.client.is_running().await
+    }
+
+    async fn synthesize(
+        &self,
+        text: &str,
+        options: &SynthesisOptions,
+        output_path: &str,
+    ) -> Result<(), TtsError> {
+        self.client.text_to_speech_with_options(text, options.clone(), output_path).await?;
+        Ok(())
+    }
+
+    async fn synthesize_fitted(
+        &self,
+        text: &str,
+        options: &SynthesisOptions,
+        window_ms: u64,
+        fit: &SpeedFit,
+        output_path: &str,
+    ) -> Result<f64, TtsError> {
+        Ok(self.client.text_to_speech_fitted(text, options.clone(), window_ms, fit, output_path).await?)
+    }
+}
+
+/// OpenAI Audio Speech API
+///
+/// 話者IDは使わず、`voice` の声で合成する。`speed_scale` はAPIの `speed`（0.25〜4.0）に渡す。
+pub struct OpenAiTts {
+    api_key: String,
+    model: String,
+    voice: String,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl OpenAiTts {
+    pub fn new(api_key: &str, model: Option<&str>, voice: Option<&str>) -> Self {
+        Self {
+            api_key: api_key.trim().to_string(),
+            model: model.unwrap_or(DEFAULT_OPENAI_TTS_MODEL).to_string(),
+            voice: voice.unwrap_or(DEFAULT_OPENAI_VOICE).to_string(),
+            base_url: OPENAI_BASE_URL.to_string(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(60))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+        }
+    }
+
+    /// 音声合成リクエストのボディ
+    fn request_body(&self, text: &str, speed: f64) -> serde_json::Value {
+        serde_json::json!({
+            "model": self.model,
+            "voice": self.voice,
+            "input": text,
+            "response_format": "wav",
+            "speed": speed.clamp(0.25, 4.0),
+        })
+    }
+
+    async fn speech(&self, text: &str, speed: f64, output_path: &str) -> Result<(), TtsError> {
+        let resp = self.client
+            .post(format!("{}/audio/speech", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&self.request_body(text, speed))
+            .send()
+            .await
+            .map_err(|e| TtsError::Request(e.to_string()))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(TtsError::Request(format!("{}: {}", status, body)));
+        }
+        let wav = resp.bytes().await.map_err(|e| TtsError::Request(e.to_string()))?;
+
+        if let Some(parent) = Path::new(output_path).parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(output_path, &wav).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TtsBackend for OpenAiTts {
+    fn provider(&self) -> TtsProvider {
+        TtsProvider::Openai
+    }
+
+    fn supports_language(&self, _lang: &str) -> bool {
+        true
+    }
+
+    async fn is_available(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    async fn synthesize(
+        &self,
+        text: &str,
+        options: &SynthesisOptions,
+        output_path: &str,
+    ) -> Result<(), TtsError> {
+        self.speech(text, options.speed_scale, output_path).await
+    }
+
+    /// AudioQueryがないので一度合成して長さを測り、超えていれば話速を上げて合成し直す
+    async fn synthesize_fitted(
+        &self,
+        text: &str,
+        options: &SynthesisOptions,
+        window_ms: u64,
+        fit: &SpeedFit,
+        output_path: &str,
+    ) -> Result<f64, TtsError> {
+        self.speech(text, options.speed_scale, output_path).await?;
+
+        let duration_ms = match PcmAudio::read_wav(Path::new(output_path)) {
+            Ok(audio) => audio.duration_ms(),
+            Err(_) => return Ok(options.speed_scale),
+        };
+        let speed = fit.speed_for(duration_ms as f64 / 1000.0, window_ms, options.speed_scale);
+        if speed > options.speed_scale {
+            self.speech(text, speed, output_path).await?;
+        }
+        Ok(speed)
+    }
+}
+
+/// 設定に応じたバックエンドを作成
+pub fn create_backend(config: &TtsConfig, secrets: &SecretStore) -> Result<Box<dyn TtsBackend>, TtsError> {
+    match config.provider {
+        TtsProvider::Voicevox => Ok(Box::new(VoicevoxBackend::new(
+            TtsProvider::Voicevox,
+            &config.url.clone().unwrap_or_else(|| crate::settings::current().voicevox_url),
+        ))),
+        TtsProvider::Coeiroink => Ok(Box::new(VoicevoxBackend::new(
+            TtsProvider::Coeiroink,
+            config.url.as_deref().unwrap_or(COEIROINK_URL),
+        ))),
+        TtsProvider::Openai => {
+            let key = secrets.get(OPENAI_API_KEY)
+                .ok_or_else(|| TtsError::MissingApiKey(OPENAI_API_KEY.to_string()))?;
+            Ok(Box::new(OpenAiTts::new(&key, config.model.as_deref(), config.voice.as_deref())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_backend() {
+        let mut secrets = SecretStore::new();
+        let config: TtsConfig = serde_json::from_str(r#"{"provider": "coeiroink"}"#).unwrap();
+        let backend = create_backend(&config, &secrets).unwrap();
+        assert_eq!(backend.provider(), TtsProvider::Coeiroink);
+        assert!(!backend.supports_language("en"));
+        assert!(backend.supports_language("ja-JP"));
+        assert!(backend.supports_language("ja_JP"));
+
+        let config = TtsConfig { provider: TtsProvider::Openai, ..Default::default() };
+        if std::env::var("OPENAI_API_KEY").is_err() {
+            assert!(matches!(create_backend(&config, &secrets), Err(TtsError::MissingApiKey(_))));
+        }
+
+        secrets.set(OPENAI_API_KEY, "sk-test");
+        let backend = create_backend(&config, &secrets).unwrap();
+        assert!(backend.supports_language("en"));
+    }
+
+    #[test]
+    fn test_openai_request_body() {
+        let tts = OpenAiTts::new("sk-test", None, Some("nova"));
+        let body = tts.request_body("こんにちは", 8.0);
+        assert_eq!(body["model"], DEFAULT_OPENAI_TTS_MODEL);
+        assert_eq!(body["voice"], "nova");
+        assert_eq!(body["response_format"], "wav");
+        assert_eq!(body["speed"], 4.0);
+    }
+}
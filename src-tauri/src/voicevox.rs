@@ -3,7 +3,9 @@
 //! VOICEVOX Engine (http://localhost:50021) と通信して
 //! テキストから音声を生成する。
 
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use thiserror::Error;
 
@@ -42,8 +44,12 @@ pub struct SpeakerStyle {
 }
 
 /// AudioQueryレスポンス
+///
+/// VOICEVOX Engineのキーに合わせて `accent_phrases` 以外はcamelCase。
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct AudioQuery {
+    #[serde(rename = "accent_phrases")]
     pub accent_phrases: Vec<AccentPhrase>,
     pub speed_scale: f64,
     pub pitch_scale: f64,
@@ -56,6 +62,18 @@ pub struct AudioQuery {
     pub kana: Option<String>,
 }
 
+impl AudioQuery {
+    /// 現在の `speed_scale` で合成したときの長さ（秒）
+    pub fn duration_secs(&self) -> f64 {
+        let mora_secs = |m: &Mora| m.consonant_length.unwrap_or(0.0) + m.vowel_length;
+        let phrases: f64 = self.accent_phrases.iter()
+            .map(|p| p.moras.iter().map(mora_secs).sum::<f64>() + p.pause_mora.as_ref().map(mora_secs).unwrap_or(0.0))
+            .sum();
+        let total = self.pre_phoneme_length + phrases + self.post_phoneme_length;
+        if self.speed_scale > 0.0 { total / self.speed_scale } else { total }
+    }
+}
+
 /// アクセント句
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccentPhrase {
@@ -104,7 +122,7 @@ fn default_volume() -> f64 { 1.0 }
 impl Default for SynthesisOptions {
     fn default() -> Self {
         Self {
-            speaker: 1, // ずんだもん
+            speaker: crate::settings::current().default_speaker,
             speed_scale: 1.0,
             pitch_scale: 0.0,
             intonation_scale: 1.0,
@@ -113,48 +131,140 @@ impl Default for SynthesisOptions {
     }
 }
 
+/// 字幕の表示時間に合わせて話速を上げる設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedFit {
+    /// 話速の上限（これ以上は上げず、収まらない分は組み立て時に処理する）
+    #[serde(default = "default_max_speed")]
+    pub max_speed: f64,
+}
+
+fn default_max_speed() -> f64 { 1.5 }
+
+impl Default for SpeedFit {
+    fn default() -> Self {
+        Self { max_speed: default_max_speed() }
+    }
+}
+
+impl SpeedFit {
+    /// 話速 `speed` で `duration_secs` 秒の音声を `window_ms` に収める話速
+    pub fn speed_for(&self, duration_secs: f64, window_ms: u64, speed: f64) -> f64 {
+        let window_secs = window_ms as f64 / 1000.0;
+        if window_secs <= 0.0 || duration_secs <= window_secs {
+            return speed;
+        }
+        (speed * duration_secs / window_secs).min(self.max_speed.max(speed))
+    }
+}
+
+/// 字幕の話者・セグメントごとのVOICEVOX話者の割り当て
+///
+/// 優先順位は `segments`（セグメント番号）→ `speakers`（話者ラベル）→ 話者分離の割り当て → `default`。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpeakerMapping {
+    /// 話者が決まらないセグメントの話者ID
+    #[serde(default)]
+    pub default: Option<i32>,
+    /// 話者ラベル（VTTの `<v>` タグや話者分離の結果）ごとの話者ID
+    #[serde(default)]
+    pub speakers: HashMap<String, i32>,
+    /// セグメント番号ごとの話者ID
+    #[serde(default)]
+    pub segments: BTreeMap<usize, i32>,
+}
+
+impl SpeakerMapping {
+    /// セグメントの話者ID（`detected` は話者分離の割り当て。どれにもなければNone）
+    pub fn resolve(&self, index: usize, label: Option<&str>, detected: &HashMap<String, i32>) -> Option<i32> {
+        self.segments.get(&index)
+            .or_else(|| label.and_then(|l| self.speakers.get(l).or_else(|| detected.get(l))))
+            .or(self.default.as_ref())
+            .copied()
+    }
+}
+
+/// 話者の試聴に使う既定の文
+pub const PREVIEW_TEXT: &str = "こんにちは。この声で吹き替えます。";
+
+/// 同時に合成するセグメント数の既定値
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// 一括合成の1件分の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub index: usize,
+    pub text: String,
+    /// 生成したWAV（失敗した場合はNone）
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+/// 一括合成の進捗（1件終わるごと）
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub item: BatchItem,
+}
+
+/// 一括合成の結果一覧
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SynthesisManifest {
+    pub output_dir: String,
+    pub speaker: i32,
+    /// 入力順の結果
+    pub items: Vec<BatchItem>,
+}
+
+impl SynthesisManifest {
+    /// 生成できたWAVのパス
+    pub fn files(&self) -> Vec<&str> {
+        self.items.iter().filter_map(|item| item.path.as_deref()).collect()
+    }
+}
+
 /// VOICEVOX API クライアント
+///
+/// 内部の `reqwest::Client` はコネクションプールを共有するので、
+/// クローンして複数のタスクから同時に呼び出せる。
+#[derive(Clone)]
 pub struct VoicevoxClient {
     base_url: String,
-    client: reqwest::blocking::Client,
+    client: reqwest::Client,
 }
 
 impl VoicevoxClient {
     /// 新しいクライアントを作成
     pub fn new() -> Self {
-        Self {
-            base_url: "http://localhost:50021".to_string(),
-            client: reqwest::blocking::Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| reqwest::blocking::Client::new()),
-        }
+        Self::with_url(&crate::settings::current().voicevox_url)
     }
 
     /// カスタムURLでクライアントを作成
     pub fn with_url(base_url: &str) -> Self {
         Self {
             base_url: base_url.to_string(),
-            client: reqwest::blocking::Client::builder()
+            client: reqwest::Client::builder()
                 .timeout(std::time::Duration::from_secs(30))
                 .build()
-                .unwrap_or_else(|_| reqwest::blocking::Client::new()),
+                .unwrap_or_else(|_| reqwest::Client::new()),
         }
     }
 
     /// VOICEVOX Engineが起動しているか確認
-    pub fn is_running(&self) -> bool {
-        match self.client.get(&format!("{}/version", self.base_url)).send() {
+    pub async fn is_running(&self) -> bool {
+        match self.client.get(&format!("{}/version", self.base_url)).send().await {
             Ok(resp) => resp.status().is_success(),
             Err(_) => false,
         }
     }
 
     /// バージョンを取得
-    pub fn get_version(&self) -> Result<String, VoicevoxError> {
+    pub async fn get_version(&self) -> Result<String, VoicevoxError> {
         let resp = self.client
             .get(&format!("{}/version", self.base_url))
             .send()
+            .await
             .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
 
         if !resp.status().is_success() {
@@ -164,14 +274,16 @@ impl VoicevoxClient {
         }
 
         resp.text()
+            .await
             .map_err(|e| VoicevoxError::HttpError(e.to_string()))
     }
 
     /// 話者一覧を取得
-    pub fn get_speakers(&self) -> Result<Vec<Speaker>, VoicevoxError> {
+    pub async fn get_speakers(&self) -> Result<Vec<Speaker>, VoicevoxError> {
         let resp = self.client
             .get(&format!("{}/speakers", self.base_url))
             .send()
+            .await
             .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
 
         if !resp.status().is_success() {
@@ -181,6 +293,7 @@ impl VoicevoxClient {
         }
 
         let body = resp.text()
+            .await
             .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
 
         let speakers: Vec<Speaker> = serde_json::from_str(&body)?;
@@ -188,7 +301,9 @@ impl VoicevoxClient {
     }
 
     /// AudioQueryを作成
-    pub fn create_audio_query(
+    ///
+    /// モーラごとの音高・長さを編集してから `synthesize_from_query` に渡せる。
+    pub async fn get_audio_query(
         &self,
         text: &str,
         speaker: i32,
@@ -203,23 +318,25 @@ impl VoicevoxClient {
         let resp = self.client
             .post(&url)
             .send()
+            .await
             .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
 
         if !resp.status().is_success() {
-            let error_body = resp.text().unwrap_or_default();
+            let error_body = resp.text().await.unwrap_or_default();
             return Err(VoicevoxError::SynthesisFailed(
                 format!("Audio query failed: {}", error_body)
             ));
         }
 
         let query: AudioQuery = resp.json()
+            .await
             .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
 
         Ok(query)
     }
 
     /// テキストから音声を合成してファイルに保存
-    pub fn text_to_speech(
+    pub async fn text_to_speech(
         &self,
         text: &str,
         speaker: i32,
@@ -228,18 +345,18 @@ impl VoicevoxClient {
         self.text_to_speech_with_options(text, SynthesisOptions {
             speaker,
             ..Default::default()
-        }, output_path)
+        }, output_path).await
     }
 
     /// オプション付きでテキストから音声を合成
-    pub fn text_to_speech_with_options(
+    pub async fn text_to_speech_with_options(
         &self,
         text: &str,
         options: SynthesisOptions,
         output_path: &str,
     ) -> Result<String, VoicevoxError> {
         // Step 1: AudioQueryを作成
-        let mut query = self.create_audio_query(text, options.speaker)?;
+        let mut query = self.get_audio_query(text, options.speaker).await?;
 
         // Step 2: パラメータを調整
         query.speed_scale = options.speed_scale;
@@ -247,22 +364,56 @@ impl VoicevoxClient {
         query.intonation_scale = options.intonation_scale;
         query.volume_scale = options.volume_scale;
 
+        self.synthesize_from_query(&query, options.speaker, output_path).await
+    }
+
+    /// 字幕の表示時間 `window_ms` に収まるよう話速を上げて合成
+    ///
+    /// AudioQueryの音素長から合成後の長さを見積もり、超える場合だけ
+    /// `speed_scale` を `fit.max_speed` まで上げる。戻り値は使った話速。
+    pub async fn text_to_speech_fitted(
+        &self,
+        text: &str,
+        options: SynthesisOptions,
+        window_ms: u64,
+        fit: &SpeedFit,
+        output_path: &str,
+    ) -> Result<f64, VoicevoxError> {
+        let mut query = self.get_audio_query(text, options.speaker).await?;
+        query.speed_scale = options.speed_scale;
+        query.pitch_scale = options.pitch_scale;
+        query.intonation_scale = options.intonation_scale;
+        query.volume_scale = options.volume_scale;
+
+        query.speed_scale = fit.speed_for(query.duration_secs(), window_ms, options.speed_scale);
+        self.synthesize_from_query(&query, options.speaker, output_path).await?;
+        Ok(query.speed_scale)
+    }
+
+    /// AudioQueryから音声を合成してファイルに保存
+    pub async fn synthesize_from_query(
+        &self,
+        query: &AudioQuery,
+        speaker: i32,
+        output_path: &str,
+    ) -> Result<String, VoicevoxError> {
         // Step 3: 音声合成
         let url = format!(
             "{}/synthesis?speaker={}",
             self.base_url,
-            options.speaker
+            speaker
         );
 
         let resp = self.client
             .post(&url)
             .header("Content-Type", "application/json")
-            .body(serde_json::to_string(&query)?)
+            .body(serde_json::to_string(query)?)
             .send()
+            .await
             .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
 
         if !resp.status().is_success() {
-            let error_body = resp.text().unwrap_or_default();
+            let error_body = resp.text().await.unwrap_or_default();
             return Err(VoicevoxError::SynthesisFailed(
                 format!("Synthesis failed: {}", error_body)
             ));
@@ -270,16 +421,15 @@ impl VoicevoxClient {
 
         // Step 4: WAVデータを保存
         let wav_data = resp.bytes()
+            .await
             .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
 
         // ディレクトリを作成（存在しない場合）
         if let Some(parent) = Path::new(output_path).parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)?;
-            }
+            tokio::fs::create_dir_all(parent).await?;
         }
 
-        std::fs::write(output_path, &wav_data)?;
+        tokio::fs::write(output_path, &wav_data).await?;
 
         crate::log::info("VoicevoxClient", &format!(
             "Saved audio: {} bytes to {}",
@@ -290,33 +440,71 @@ impl VoicevoxClient {
         Ok(output_path.to_string())
     }
 
-    /// 複数テキストを連続して合成
-    pub fn synthesize_batch(
+    /// 複数テキストを `<output_dir>/audio_NNNN.wav` に合成（最大 `DEFAULT_CONCURRENCY` 件ずつ同時に）
+    ///
+    /// 1件の失敗では止めずに結果一覧に記録する。`on_progress` は完了順に呼ばれる。
+    pub async fn synthesize_batch<F>(
         &self,
         texts: &[String],
         speaker: i32,
         output_dir: &str,
-    ) -> Result<Vec<String>, VoicevoxError> {
-        let mut outputs = Vec::new();
-
-        for (i, text) in texts.iter().enumerate() {
-            let output_path = format!("{}/audio_{:04}.wav", output_dir, i);
-            self.text_to_speech(text, speaker, &output_path)?;
-            outputs.push(output_path);
+        mut on_progress: F,
+    ) -> SynthesisManifest
+    where
+        F: FnMut(&BatchProgress),
+    {
+        let requests: Vec<_> = texts
+            .iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let output_path = format!("{}/audio_{:04}.wav", output_dir, index);
+                async move {
+                    let result = self.text_to_speech(text, speaker, &output_path).await;
+                    BatchItem {
+                        index,
+                        text: text.clone(),
+                        path: result.as_ref().ok().cloned(),
+                        error: result.err().map(|e| e.to_string()),
+                    }
+                }
+            })
+            .collect();
+        let mut results = futures::stream::iter(requests).buffer_unordered(DEFAULT_CONCURRENCY);
+
+        let mut items = Vec::with_capacity(texts.len());
+        while let Some(item) = results.next().await {
+            on_progress(&BatchProgress { completed: items.len() + 1, total: texts.len(), item: item.clone() });
+            items.push(item);
         }
+        items.sort_by_key(|item| item.index);
 
-        Ok(outputs)
+        SynthesisManifest { output_dir: output_dir.to_string(), speaker, items }
+    }
+
+    /// 話者の試聴用クリップを一時ディレクトリに合成してパスを返す
+    ///
+    /// 同じ話者のクリップは上書きする（`sample_text` 省略時は `PREVIEW_TEXT`）。
+    pub async fn preview_speaker(
+        &self,
+        speaker: i32,
+        sample_text: Option<&str>,
+    ) -> Result<String, VoicevoxError> {
+        let text = sample_text.map(str::trim).filter(|t| !t.is_empty()).unwrap_or(PREVIEW_TEXT);
+        let path = std::env::temp_dir()
+            .join("re-voice-preview")
+            .join(format!("speaker_{}.wav", speaker));
+        self.text_to_speech(text, speaker, &path.to_string_lossy()).await
     }
 
     /// アクセント句を調整してから合成
-    pub fn synthesize_with_accent(
+    pub async fn synthesize_with_accent(
         &self,
         text: &str,
         speaker: i32,
         accent_positions: &[usize],
         output_path: &str,
     ) -> Result<String, VoicevoxError> {
-        let mut query = self.create_audio_query(text, speaker)?;
+        let mut query = self.get_audio_query(text, speaker).await?;
 
         // アクセント位置を調整
         for (i, &accent) in accent_positions.iter().enumerate() {
@@ -325,28 +513,7 @@ impl VoicevoxClient {
             }
         }
 
-        // 合成
-        let url = format!("{}/synthesis?speaker={}", self.base_url, speaker);
-
-        let resp = self.client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .body(serde_json::to_string(&query)?)
-            .send()
-            .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
-
-        if !resp.status().is_success() {
-            return Err(VoicevoxError::SynthesisFailed(
-                format!("Synthesis failed: {}", resp.status())
-            ));
-        }
-
-        let wav_data = resp.bytes()
-            .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
-
-        std::fs::write(output_path, &wav_data)?;
-
-        Ok(output_path.to_string())
+        self.synthesize_from_query(&query, speaker, output_path).await
     }
 }
 
@@ -356,108 +523,6 @@ impl Default for VoicevoxClient {
     }
 }
 
-/// 非同期版VOICEVOXクライアント
-pub struct VoicevoxClientAsync {
-    base_url: String,
-    client: reqwest::Client,
-}
-
-impl VoicevoxClientAsync {
-    pub fn new() -> Self {
-        Self {
-            base_url: "http://localhost:50021".to_string(),
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_else(|_| reqwest::Client::new()),
-        }
-    }
-
-    /// VOICEVOX Engineが起動しているか確認
-    pub async fn is_running(&self) -> bool {
-        match self.client.get(&format!("{}/version", self.base_url)).send().await {
-            Ok(resp) => resp.status().is_success(),
-            Err(_) => false,
-        }
-    }
-
-    /// 話者一覧を取得
-    pub async fn get_speakers(&self) -> Result<Vec<Speaker>, VoicevoxError> {
-        let resp = self.client
-            .get(&format!("{}/speakers", self.base_url))
-            .send()
-            .await
-            .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
-
-        if !resp.status().is_success() {
-            return Err(VoicevoxError::HttpError(
-                format!("Failed to get speakers: {}", resp.status())
-            ));
-        }
-
-        let speakers: Vec<Speaker> = resp.json().await
-            .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
-
-        Ok(speakers)
-    }
-
-    /// テキストから音声を合成
-    pub async fn text_to_speech(
-        &self,
-        text: &str,
-        speaker: i32,
-        output_path: &str,
-    ) -> Result<String, VoicevoxError> {
-        // AudioQuery作成
-        let url = format!(
-            "{}/audio_query?text={}&speaker={}",
-            self.base_url,
-            urlencoding::encode(text),
-            speaker
-        );
-
-        let query: AudioQuery = self.client
-            .post(&url)
-            .send()
-            .await
-            .map_err(|e| VoicevoxError::HttpError(e.to_string()))?
-            .json()
-            .await
-            .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
-
-        // 合成
-        let url = format!("{}/synthesis?speaker={}", self.base_url, speaker);
-
-        let wav_data = self.client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .body(serde_json::to_string(&query)?)
-            .send()
-            .await
-            .map_err(|e| VoicevoxError::HttpError(e.to_string()))?
-            .bytes()
-            .await
-            .map_err(|e| VoicevoxError::HttpError(e.to_string()))?;
-
-        // ファイル保存
-        if let Some(parent) = Path::new(output_path).parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)?;
-            }
-        }
-
-        std::fs::write(output_path, &wav_data)?;
-
-        Ok(output_path.to_string())
-    }
-}
-
-impl Default for VoicevoxClientAsync {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -481,14 +546,161 @@ mod tests {
         assert_eq!(options.speed_scale, 1.0);
     }
 
-    // 注意: 以下のテストはVOICEVOX Engineが起動している場合のみ成功します
+    #[test]
+    fn test_speaker_mapping() {
+        let mapping: SpeakerMapping = serde_json::from_str(
+            r#"{"default": 3, "speakers": {"Alice": 8}, "segments": {"2": 13}}"#
+        ).unwrap();
+
+        let detected: HashMap<String, i32> = [("Alice".to_string(), 2), ("Bob".to_string(), 4)].into();
+
+        assert_eq!(mapping.resolve(0, Some("Alice"), &detected), Some(8));
+        assert_eq!(mapping.resolve(1, Some("Bob"), &detected), Some(4));
+        assert_eq!(mapping.resolve(1, Some("Carol"), &detected), Some(3));
+        assert_eq!(mapping.resolve(2, Some("Alice"), &detected), Some(13));
+        assert_eq!(SpeakerMapping::default().resolve(0, None, &detected), None);
+    }
 
     #[test]
+    fn test_speed_fit() {
+        let mora = |len: f64| Mora {
+            text: "ア".to_string(),
+            consonant: None,
+            consonant_length: None,
+            vowel: "a".to_string(),
+            vowel_length: len,
+            pitch: 5.0,
+        };
+        let mut query = AudioQuery {
+            accent_phrases: vec![AccentPhrase {
+                moras: vec![mora(0.5), mora(0.5)],
+                accent: 1,
+                pause_mora: Some(mora(0.4)),
+                is_interrogative: false,
+            }],
+            speed_scale: 1.0,
+            pitch_scale: 0.0,
+            intonation_scale: 1.0,
+            volume_scale: 1.0,
+            pre_phoneme_length: 0.1,
+            post_phoneme_length: 0.1,
+            output_sampling_rate: 24000,
+            output_stereo: false,
+            kana: None,
+        };
+        assert!((query.duration_secs() - 1.6).abs() < 1e-9);
+        query.speed_scale = 2.0;
+        assert!((query.duration_secs() - 0.8).abs() < 1e-9);
+
+        let fit = SpeedFit::default();
+        // 収まる場合は変えない
+        assert_eq!(fit.speed_for(1.6, 2000, 1.0), 1.0);
+        assert!((fit.speed_for(1.6, 1280, 1.0) - 1.25).abs() < 1e-9);
+        // 上限で頭打ち
+        assert_eq!(fit.speed_for(1.6, 500, 1.0), 1.5);
+    }
+
+    #[test]
+    fn test_audio_query_engine_keys() {
+        let json = r#"{
+            "accent_phrases": [{
+                "moras": [{"text": "コ", "consonant": "k", "consonant_length": 0.05, "vowel": "o", "vowel_length": 0.1, "pitch": 5.6}],
+                "accent": 1,
+                "pause_mora": null,
+                "is_interrogative": false
+            }],
+            "speedScale": 1.0, "pitchScale": 0.0, "intonationScale": 1.0, "volumeScale": 1.0,
+            "prePhonemeLength": 0.1, "postPhonemeLength": 0.1,
+            "outputSamplingRate": 24000, "outputStereo": false, "kana": "コ"
+        }"#;
+        let mut query: AudioQuery = serde_json::from_str(json).unwrap();
+        query.accent_phrases[0].moras[0].pitch = 6.0;
+
+        let value = serde_json::to_value(&query).unwrap();
+        assert_eq!(value["speedScale"], 1.0);
+        assert_eq!(value["outputSamplingRate"], 24000);
+        assert_eq!(value["accent_phrases"][0]["moras"][0]["pitch"], 6.0);
+    }
+
+    /// `/audio_query` と `/synthesis` だけ返す模擬Engine（同時に処理した合成数の最大値を記録）
+    async fn mock_engine(max_in_flight: std::sync::Arc<std::sync::atomic::AtomicUsize>) -> String {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let in_flight = std::sync::Arc::new(AtomicUsize::new(0));
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 8192];
+                    let n = stream.read(&mut buf).await.unwrap();
+                    let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                    let body = if request.starts_with("POST /audio_query") {
+                        r#"{"accent_phrases":[],"speedScale":1.0,"pitchScale":0.0,"intonationScale":1.0,"volumeScale":1.0,
+                            "prePhonemeLength":0.1,"postPhonemeLength":0.1,"outputSamplingRate":24000,"outputStereo":false,"kana":""}"#
+                    } else {
+                        let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_in_flight.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        "RIFF"
+                    };
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(), body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_synthesize_batch_concurrently() {
+        let max_in_flight = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = VoicevoxClient::with_url(&mock_engine(max_in_flight.clone()).await);
+        let dir = std::env::temp_dir().join(format!("voicevox_batch_{}", std::process::id()));
+        let texts: Vec<String> = (0..8).map(|i| format!("テキスト{}", i)).collect();
+
+        let mut progress = Vec::new();
+        let manifest = client
+            .synthesize_batch(&texts, 1, dir.to_str().unwrap(), |p| progress.push((p.completed, p.total)))
+            .await;
+
+        let outputs = manifest.files();
+        assert_eq!(outputs.len(), 8);
+        assert!(outputs[7].ends_with("audio_0007.wav"));
+        assert!(std::path::Path::new(outputs[0]).exists());
+        assert_eq!(manifest.items[3].text, "テキスト3");
+        assert_eq!(progress.last(), Some(&(8, 8)));
+        let max = max_in_flight.load(std::sync::atomic::Ordering::SeqCst);
+        assert!(max > 1 && max <= DEFAULT_CONCURRENCY, "max in flight: {}", max);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_preview_speaker() {
+        let max_in_flight = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = VoicevoxClient::with_url(&mock_engine(max_in_flight).await);
+
+        let path = client.preview_speaker(3, Some("  ")).await.unwrap();
+        assert!(path.ends_with("speaker_3.wav"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"RIFF");
+    }
+
+    // 注意: 以下のテストはVOICEVOX Engineが起動している場合のみ成功します
+
+    #[tokio::test]
     #[ignore] // VOICEVOX Engineが必要
-    fn test_get_speakers() {
+    async fn test_get_speakers() {
         let client = VoicevoxClient::new();
-        if client.is_running() {
-            let speakers = client.get_speakers().unwrap();
+        if client.is_running().await {
+            let speakers = client.get_speakers().await.unwrap();
             assert!(!speakers.is_empty());
 
             // ずんだもんが含まれているか確認
@@ -497,16 +709,16 @@ mod tests {
         }
     }
 
-    #[test]
+    #[tokio::test]
     #[ignore] // VOICEVOX Engineが必要
-    fn test_text_to_speech() {
+    async fn test_text_to_speech() {
         let client = VoicevoxClient::new();
-        if client.is_running() {
+        if client.is_running().await {
             let result = client.text_to_speech(
                 "こんにちは、世界です！",
                 1, // ずんだもん
                 "/tmp/test_voicevox.wav"
-            );
+            ).await;
 
             assert!(result.is_ok());
             assert!(std::path::Path::new("/tmp/test_voicevox.wav").exists());
@@ -0,0 +1,363 @@
+//! 監視フォルダ
+//!
+//! 設定したフォルダを定期的にスキャンし、新しい字幕ファイル（.vtt/.srt）や
+//! URLリストファイル（.txt）が置かれたら既定パラメータでパイプラインを自動起動する。
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+use tokio::sync::Semaphore;
+
+use crate::acp::runner::{PipelineRunner, TranslationOptions};
+use crate::events::RecordedEmit;
+use crate::log;
+
+/// 監視設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// 監視するフォルダ
+    pub folder: String,
+    /// パイプラインの出力先（ファイルごとにサブディレクトリを作成）
+    pub output_dir: String,
+    /// URLリストから起動する場合の字幕言語
+    #[serde(default = "default_subtitle_lang")]
+    pub subtitle_lang: String,
+    /// スキャン間隔（ミリ秒）
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+}
+
+fn default_subtitle_lang() -> String { "en".to_string() }
+fn default_interval_ms() -> u64 { 2000 }
+
+/// 自動起動するパイプラインの同時実行数（超えた分は順番待ちになる）
+const MAX_CONCURRENT_RUNS: usize = 2;
+
+/// 検出したファイルの種類
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchFileKind {
+    /// 字幕ファイル（.vtt/.srt）
+    Subtitle,
+    /// URLリスト（.txt、1行1URL）
+    UrlList,
+}
+
+impl WatchFileKind {
+    /// 拡張子から種類を判定
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "vtt" | "srt" => Some(Self::Subtitle),
+            "txt" => Some(Self::UrlList),
+            _ => None,
+        }
+    }
+}
+
+/// 自動起動イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchRunPayload {
+    /// 検出したファイル
+    pub file_path: String,
+    /// ファイルの種類
+    pub kind: WatchFileKind,
+    /// パイプラインの入力（字幕ファイルパスまたはURL）
+    pub source: String,
+    /// 出力ディレクトリ
+    pub output_dir: String,
+    /// 実行ID（完了・失敗時のみ）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_id: Option<String>,
+    /// エラーメッセージ（失敗時のみ）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 監視フォルダ
+pub struct FolderWatcher {
+    /// 監視設定
+    config: WatchConfig,
+    /// 実行中フラグ
+    running: Arc<AtomicBool>,
+    /// 監視スレッドハンドル
+    handle: Option<JoinHandle<()>>,
+    /// 処理済みファイル
+    seen: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl FolderWatcher {
+    /// 新しい監視フォルダを作成
+    pub fn new(config: WatchConfig) -> Self {
+        Self {
+            config,
+            running: Arc::new(AtomicBool::new(false)),
+            handle: None,
+            seen: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// 監視設定を取得
+    pub fn config(&self) -> &WatchConfig {
+        &self.config
+    }
+
+    /// 監視を開始
+    ///
+    /// 開始時点で既に存在するファイルは処理済みとして扱い、新規ファイルのみ起動する。
+    pub fn start<R: Runtime>(
+        &mut self,
+        app_handle: AppHandle<R>,
+        runner: Arc<PipelineRunner>,
+    ) -> Result<(), String> {
+        if self.running.load(Ordering::SeqCst) {
+            return Err("Watcher is already running".to_string());
+        }
+
+        let folder = PathBuf::from(&self.config.folder);
+        if !folder.is_dir() {
+            return Err(format!("Watch folder not found: {}", self.config.folder));
+        }
+
+        {
+            let mut seen = self.seen.lock();
+            seen.clear();
+            seen.extend(scan_candidates(&folder));
+        }
+
+        self.running.store(true, Ordering::SeqCst);
+        let running = self.running.clone();
+        let config = self.config.clone();
+        let seen = self.seen.clone();
+        let limit = Arc::new(Semaphore::new(MAX_CONCURRENT_RUNS));
+
+        let handle = thread::spawn(move || {
+            log::info("FolderWatcher", &format!(
+                "Watching {} (interval {}ms)",
+                config.folder, config.interval_ms
+            ));
+
+            // 書き込み途中のファイルを避けるため、サイズが2回連続で同じになるまで待つ
+            let mut pending: HashMap<PathBuf, u64> = HashMap::new();
+
+            while running.load(Ordering::SeqCst) {
+                for path in scan_candidates(&folder) {
+                    if seen.lock().contains(&path) {
+                        continue;
+                    }
+
+                    let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                    match pending.get(&path) {
+                        Some(prev) if *prev == size && size > 0 => {
+                            pending.remove(&path);
+                            seen.lock().insert(path.clone());
+                            trigger(&app_handle, &runner, &limit, &config, &path);
+                        }
+                        _ => {
+                            pending.insert(path, size);
+                        }
+                    }
+                }
+
+                thread::sleep(Duration::from_millis(config.interval_ms));
+            }
+
+            log::info("FolderWatcher", "Stopped");
+        });
+
+        self.handle = Some(handle);
+        Ok(())
+    }
+
+    /// 監視を停止
+    pub fn stop(&mut self) -> Result<(), String> {
+        if !self.running.load(Ordering::SeqCst) {
+            return Err("Watcher is not running".to_string());
+        }
+
+        self.running.store(false, Ordering::SeqCst);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+
+        Ok(())
+    }
+
+    /// 監視中かどうか
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for FolderWatcher {
+    fn drop(&mut self) {
+        if self.running.load(Ordering::SeqCst) {
+            self.running.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// フォルダ内の対象ファイルを列挙
+fn scan_candidates(folder: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(folder) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hidden = path.file_name()
+                .map(|n| n.to_string_lossy().starts_with('.'))
+                .unwrap_or(true);
+            if path.is_file() && !hidden && WatchFileKind::from_path(&path).is_some() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// URLリストファイルからURLを抽出（空行と#コメントは無視）
+pub fn parse_url_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter(|l| l.starts_with("http://") || l.starts_with("https://"))
+        .map(|l| l.to_string())
+        .collect()
+}
+
+/// ファイルごとの出力ディレクトリ名
+fn run_output_dir(base: &str, path: &Path, index: Option<usize>) -> String {
+    let stem = path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "watch".to_string());
+    match index {
+        Some(i) => format!("{}/{}-{:03}", base, stem, i + 1),
+        None => format!("{}/{}", base, stem),
+    }
+}
+
+/// 検出ファイルに応じてパイプラインを起動
+///
+/// URLリストの各URLは `limit` の許可を得た順に実行し、同時実行数を抑える。
+fn trigger<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    runner: &Arc<PipelineRunner>,
+    limit: &Arc<Semaphore>,
+    config: &WatchConfig,
+    path: &Path,
+) {
+    let Some(kind) = WatchFileKind::from_path(path) else {
+        return;
+    };
+
+    let file_path = path.to_string_lossy().to_string();
+    log::info("FolderWatcher", &format!("Detected {:?}: {}", kind, file_path));
+
+    let jobs: Vec<(String, String)> = match kind {
+        WatchFileKind::Subtitle => {
+            vec![(file_path.clone(), run_output_dir(&config.output_dir, path, None))]
+        }
+        WatchFileKind::UrlList => {
+            let content = match std::fs::read_to_string(path) {
+                Ok(c) => c,
+                Err(e) => {
+                    log::error("FolderWatcher", &format!("Failed to read {}: {}", file_path, e));
+                    return;
+                }
+            };
+            parse_url_list(&content)
+                .into_iter()
+                .enumerate()
+                .map(|(i, url)| (url, run_output_dir(&config.output_dir, path, Some(i))))
+                .collect()
+        }
+    };
+
+    for (source, output_dir) in jobs {
+        let payload = WatchRunPayload {
+            file_path: file_path.clone(),
+            kind: kind.clone(),
+            source: source.clone(),
+            output_dir: output_dir.clone(),
+            execution_id: None,
+            error: None,
+        };
+
+        let handle = app_handle.clone();
+        let runner = runner.clone();
+        let limit = limit.clone();
+        let lang = config.subtitle_lang.clone();
+
+        tauri::async_runtime::spawn(async move {
+            let Ok(_permit) = limit.acquire_owned().await else {
+                return;
+            };
+            if let Err(e) = handle.emit_recorded("watcher:run_started", &payload) {
+                log::error("FolderWatcher", &format!("Failed to emit run_started: {:?}", e));
+            }
+
+            let result = match payload.kind {
+                WatchFileKind::Subtitle => {
+                    runner.run_local_subtitle_pipeline(&source, &output_dir, &TranslationOptions::default()).await
+                }
+                WatchFileKind::UrlList => {
+                    runner.run_subtitle_pipeline(&source, &lang, &output_dir, &TranslationOptions::default()).await
+                }
+            };
+
+            let (event, payload) = match result {
+                Ok(exec) => ("watcher:run_finished", WatchRunPayload {
+                    execution_id: Some(exec.execution_id),
+                    ..payload
+                }),
+                Err(e) => {
+                    log::error("FolderWatcher", &format!("Auto-started run failed: {}", e));
+                    ("watcher:run_failed", WatchRunPayload {
+                        error: Some(e.to_string()),
+                        ..payload
+                    })
+                }
+            };
+            let _ = handle.emit_recorded(event, &payload);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_kind_from_path() {
+        assert_eq!(WatchFileKind::from_path(Path::new("a/b.vtt")), Some(WatchFileKind::Subtitle));
+        assert_eq!(WatchFileKind::from_path(Path::new("b.SRT")), Some(WatchFileKind::Subtitle));
+        assert_eq!(WatchFileKind::from_path(Path::new("urls.txt")), Some(WatchFileKind::UrlList));
+        assert_eq!(WatchFileKind::from_path(Path::new("video.mp4")), None);
+    }
+
+    #[test]
+    fn test_parse_url_list() {
+        let content = "# queue\nhttps://youtu.be/abc\n\n  https://www.youtube.com/watch?v=def  \nnot a url\n";
+        let urls = parse_url_list(content);
+        assert_eq!(urls, vec![
+            "https://youtu.be/abc".to_string(),
+            "https://www.youtube.com/watch?v=def".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_run_output_dir() {
+        let path = Path::new("/watch/talk.en.vtt");
+        assert_eq!(run_output_dir("/out", path, None), "/out/talk.en");
+        assert_eq!(run_output_dir("/out", Path::new("/watch/list.txt"), Some(0)), "/out/list-001");
+    }
+}
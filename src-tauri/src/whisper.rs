@@ -0,0 +1,166 @@
+//! Whisperによる文字起こし
+//!
+//! 字幕がない動画のフォールバックとして、whisper.cpp（`whisper-cli`）または
+//! OpenAI Whisperのコマンドラインで音声からVTTを作る。
+//! 入力は16kHzモノラルのWAV（whisper.cppの要件）に変換してから渡す。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ffmpeg::{Ffmpeg, FfmpegCommand, FfmpegError};
+use crate::process_env;
+
+/// 文字起こしエラー
+#[derive(Debug, Error)]
+pub enum WhisperError {
+    #[error("Whisper is not installed: {0}")]
+    NotFound(String),
+
+    #[error("Whisper failed: {0}")]
+    Failed(String),
+
+    #[error(transparent)]
+    Ffmpeg(#[from] FfmpegError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Whisperの実装
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhisperBackend {
+    /// whisper.cpp（`whisper-cli`）
+    #[default]
+    Cpp,
+    /// OpenAI Whisper（Python版の `whisper`）
+    Openai,
+}
+
+/// 文字起こし設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionConfig {
+    #[serde(default)]
+    pub backend: WhisperBackend,
+    /// 実行ファイル（省略時は `whisper-cli` / `whisper`）
+    #[serde(default)]
+    pub binary: Option<String>,
+    /// モデル（whisper.cppはモデルファイルのパス、OpenAI版はモデル名）
+    #[serde(default = "default_model")]
+    pub model: String,
+    /// 音声の言語（省略時は自動判定）
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+fn default_model() -> String {
+    "base".to_string()
+}
+
+impl Default for TranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            backend: WhisperBackend::default(),
+            binary: None,
+            model: default_model(),
+            language: None,
+        }
+    }
+}
+
+/// 出力ファイル名（拡張子なし）
+const TRANSCRIPT_STEM: &str = "transcript";
+
+impl TranscriptionConfig {
+    fn binary(&self) -> &str {
+        self.binary.as_deref().unwrap_or(match self.backend {
+            WhisperBackend::Cpp => "whisper-cli",
+            WhisperBackend::Openai => "whisper",
+        })
+    }
+
+    /// Whisperに渡す引数
+    fn build_args(&self, wav: &str, output_dir: &str) -> Vec<String> {
+        let mut args: Vec<String> = match self.backend {
+            WhisperBackend::Cpp => vec![
+                "-m".into(), self.model.clone(),
+                "-f".into(), wav.into(),
+                "-ovtt".into(),
+                "-of".into(), format!("{}/{}", output_dir, TRANSCRIPT_STEM),
+                "-l".into(), self.language.clone().unwrap_or_else(|| "auto".to_string()),
+            ],
+            WhisperBackend::Openai => vec![
+                wav.into(),
+                "--model".into(), self.model.clone(),
+                "--output_format".into(), "vtt".into(),
+                "--output_dir".into(), output_dir.into(),
+            ],
+        };
+        if let (WhisperBackend::Openai, Some(lang)) = (self.backend, &self.language) {
+            args.push("--language".into());
+            args.push(lang.clone());
+        }
+        args
+    }
+
+    /// 音声ファイルを文字起こしして `<output_dir>/transcript.vtt` を作る（ブロッキング）
+    pub fn transcribe(&self, audio: &str, output_dir: &str) -> Result<String, WhisperError> {
+        // 16kHzモノラルのWAVに変換
+        let wav = format!("{}/{}.wav", output_dir, TRANSCRIPT_STEM);
+        let command = FfmpegCommand::new()
+            .input(audio)
+            .arg("-ar").arg("16000")
+            .arg("-ac").arg("1")
+            .audio_codec("pcm_s16le")
+            .output(&wav);
+        Ffmpeg::new().run(&command, None, |_| {})?;
+
+        crate::log::info("Whisper", &format!("Transcribing {} with {}", wav, self.binary()));
+        let output = process_env::command(self.binary())
+            .args(self.build_args(&wav, output_dir))
+            .output()
+            .map_err(|e| WhisperError::NotFound(format!("{}: {}", self.binary(), e)))?;
+        if !output.status.success() {
+            return Err(WhisperError::Failed(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+        }
+
+        // OpenAI版は入力ファイル名で出力する（入力と同じ名前なので結果は同じパス）
+        let vtt = format!("{}/{}.vtt", output_dir, TRANSCRIPT_STEM);
+        if !Path::new(&vtt).exists() {
+            return Err(WhisperError::Failed(format!("{} was not created", vtt)));
+        }
+        let _ = std::fs::remove_file(&wav);
+        Ok(vtt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpp_args() {
+        let config = TranscriptionConfig {
+            model: "models/ggml-base.bin".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.binary(), "whisper-cli");
+        assert_eq!(
+            config.build_args("out/transcript.wav", "out").join(" "),
+            "-m models/ggml-base.bin -f out/transcript.wav -ovtt -of out/transcript -l auto"
+        );
+    }
+
+    #[test]
+    fn test_openai_args() {
+        let config: TranscriptionConfig =
+            serde_json::from_str(r#"{"backend":"openai","language":"en"}"#).unwrap();
+        assert_eq!(config.binary(), "whisper");
+        assert_eq!(
+            config.build_args("out/transcript.wav", "out").join(" "),
+            "out/transcript.wav --model base --output_format vtt --output_dir out --language en"
+        );
+    }
+}
@@ -2,11 +2,16 @@
 //!
 //! yt-dlpを使用してYouTube動画から字幕をダウンロードする。
 
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::chapters::Chapter;
+use crate::preflight::{self, PreflightError};
+
 /// 字幕ダウンロードエラー
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum YoutubeError {
@@ -18,6 +23,59 @@ pub enum YoutubeError {
     SubtitleNotFound { lang: String },
     /// ファイル保存失敗
     SaveFailed { message: String },
+    /// 地域制限
+    GeoBlocked { message: String },
+    /// 年齢制限（ログインが必要）
+    AgeRestricted { message: String },
+    /// レート制限（HTTP 429）
+    RateLimited { message: String },
+    /// 非公開・削除済みなどで視聴できない
+    Unavailable { message: String },
+    /// ネットワークエラー
+    Network { message: String },
+}
+
+impl YoutubeError {
+    /// yt-dlpのstderrからエラーを分類する（メッセージは最後の `ERROR:` 行）
+    pub fn from_stderr(stderr: &str) -> Self {
+        let message = stderr
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix("ERROR:"))
+            .unwrap_or(stderr)
+            .trim()
+            .to_string();
+        let lower = stderr.to_lowercase();
+        let has = |patterns: &[&str]| patterns.iter().any(|p| lower.contains(p));
+
+        if has(&["http error 429", "too many requests", "rate-limit", "rate limit"]) {
+            YoutubeError::RateLimited { message }
+        } else if has(&["available in your country", "geo restrict", "geo-restrict", "blocked it in your country"]) {
+            YoutubeError::GeoBlocked { message }
+        } else if has(&["confirm your age", "age-restricted", "age restricted", "inappropriate for some users"]) {
+            YoutubeError::AgeRestricted { message }
+        } else if has(&["private video", "video unavailable", "has been removed", "members-only", "this live event will begin"]) {
+            YoutubeError::Unavailable { message }
+        } else if has(&[
+            "unable to download webpage",
+            "timed out",
+            "connection reset",
+            "connection refused",
+            "temporary failure in name resolution",
+            "network is unreachable",
+            "http error 5",
+            "urlopen error",
+        ]) {
+            YoutubeError::Network { message }
+        } else {
+            YoutubeError::DownloadFailed { message }
+        }
+    }
+
+    /// 時間をおけば成功しうるエラーか
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, YoutubeError::RateLimited { .. } | YoutubeError::Network { .. })
+    }
 }
 
 impl std::fmt::Display for YoutubeError {
@@ -27,12 +85,101 @@ impl std::fmt::Display for YoutubeError {
             YoutubeError::DownloadFailed { message } => write!(f, "ダウンロード失敗: {}", message),
             YoutubeError::SubtitleNotFound { lang } => write!(f, "{}の字幕が見つかりません", lang),
             YoutubeError::SaveFailed { message } => write!(f, "保存失敗: {}", message),
+            YoutubeError::GeoBlocked { message } => write!(f, "地域制限により視聴できません: {}", message),
+            YoutubeError::AgeRestricted { message } => write!(f, "年齢制限のある動画です（cookiesの指定が必要）: {}", message),
+            YoutubeError::RateLimited { message } => write!(f, "レート制限中です: {}", message),
+            YoutubeError::Unavailable { message } => write!(f, "動画を視聴できません: {}", message),
+            YoutubeError::Network { message } => write!(f, "ネットワークエラー: {}", message),
         }
     }
 }
 
 impl std::error::Error for YoutubeError {}
 
+/// ダウンロードするメディア
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaFormat {
+    /// 映像と音声（MP4）
+    #[default]
+    Video,
+    /// 音声のみ（元の形式のまま）
+    Audio,
+}
+
+impl MediaFormat {
+    /// 保存するファイル名（拡張子なし）
+    fn stem(&self) -> &'static str {
+        match self {
+            MediaFormat::Video => "video",
+            MediaFormat::Audio => "audio",
+        }
+    }
+
+    /// 形式を選ぶyt-dlpの引数
+    fn ytdlp_args(&self) -> &'static [&'static str] {
+        match self {
+            MediaFormat::Video => &["-f", "bv*[ext=mp4]+ba[ext=m4a]/b[ext=mp4]/b", "--merge-output-format", "mp4"],
+            MediaFormat::Audio => &["-f", "bestaudio/b"],
+        }
+    }
+}
+
+/// yt-dlpのダウンロード進捗
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DownloadProgress {
+    /// 進捗率（0〜100）
+    pub percent: f32,
+    /// 合計サイズ（例: `10.00MiB`）
+    pub total: Option<String>,
+    /// 速度（例: `1.00MiB/s`）
+    pub speed: Option<String>,
+    /// 残り時間（例: `00:05`）
+    pub eta: Option<String>,
+}
+
+/// `youtube:download_progress` イベントのペイロード
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgressEvent {
+    pub url: String,
+    pub format: MediaFormat,
+    #[serde(flatten)]
+    pub progress: DownloadProgress,
+}
+
+/// 動画で利用できる字幕
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubtitleTrack {
+    /// 言語コード
+    pub lang: String,
+    /// 表示名（例: "English"）
+    pub name: Option<String>,
+    /// 自動生成字幕か
+    pub auto: bool,
+}
+
+/// 動画情報（`yt-dlp -J`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoMetadata {
+    pub id: String,
+    pub title: String,
+    /// 長さ（ミリ秒、ライブ配信などで不明ならNone）
+    pub duration_ms: Option<u64>,
+    pub channel: Option<String>,
+    pub thumbnail: Option<String>,
+    pub chapters: Vec<Chapter>,
+    /// 手動字幕を先に、言語コード順
+    pub subtitles: Vec<SubtitleTrack>,
+}
+
+/// プレイリストの動画（`yt-dlp --flat-playlist`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    pub id: String,
+    pub title: Option<String>,
+    pub url: String,
+}
+
 /// 字幕ダウンロード結果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubtitleDownloadResult {
@@ -46,30 +193,166 @@ pub struct SubtitleDownloadResult {
     pub size: u64,
 }
 
+/// yt-dlp呼び出しの再試行設定（レート制限・ネットワークエラーのみ再試行）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// 最初の1回を含む試行回数
+    pub max_attempts: u32,
+    /// 最初の再試行までの待ち時間（ミリ秒、再試行ごとに倍）
+    pub backoff_ms: u64,
+    /// レート制限時の最小待ち時間（ミリ秒）
+    pub rate_limit_backoff_ms: u64,
+    /// 待ち時間の上限（ミリ秒）
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff_ms: 2_000,
+            rate_limit_backoff_ms: 30_000,
+            max_backoff_ms: 120_000,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// 試行 `attempt`（1始まり）が `error` で失敗した後の待ち時間
+    pub fn delay(&self, attempt: u32, error: &YoutubeError) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        let mut ms = self.backoff_ms.saturating_mul(factor);
+        if matches!(error, YoutubeError::RateLimited { .. }) {
+            ms = ms.max(self.rate_limit_backoff_ms);
+        }
+        Duration::from_millis(ms.min(self.max_backoff_ms))
+    }
+}
+
+/// yt-dlpに渡すcookie（メンバー限定・年齢制限のある動画用）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct YtdlpCookies {
+    /// cookieを読み込むブラウザ（`--cookies-from-browser`、例: "chrome"、"firefox:default"）
+    #[serde(default)]
+    pub from_browser: Option<String>,
+    /// Netscape形式のcookies.txtのパス（`--cookies`）
+    #[serde(default)]
+    pub file: Option<String>,
+}
+
+impl YtdlpCookies {
+    /// cookieを指定していないか
+    pub fn is_empty(&self) -> bool {
+        self.from_browser.is_none() && self.file.is_none()
+    }
+
+    /// yt-dlpの引数（両方指定時はブラウザを優先）
+    pub(crate) fn args(&self) -> Vec<String> {
+        let non_empty = |v: &Option<String>| v.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+        if let Some(browser) = non_empty(&self.from_browser) {
+            vec!["--cookies-from-browser".to_string(), browser]
+        } else if let Some(file) = non_empty(&self.file) {
+            vec!["--cookies".to_string(), file]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 /// YouTube字幕ダウンローダー
+///
+/// yt-dlpの実行と再試行の待機はスレッドをブロックするので、
+/// 非同期コンテキストからは `spawn_blocking` の中で使う。
 pub struct YoutubeDownloader {
     /// yt-dlpのパス
     ytdlp_path: String,
+    retry: RetryConfig,
+    cookies: YtdlpCookies,
+    /// yt-dlpに追加する環境変数（設定のPATHなど、空なら継承）
+    env: Vec<(String, String)>,
 }
 
 impl YoutubeDownloader {
     /// 新しいダウンローダーを作成
     pub fn new() -> Self {
-        Self {
-            ytdlp_path: "yt-dlp".to_string(),
-        }
+        let settings = crate::settings::current();
+        let mut downloader = Self::with_path(&settings.ytdlp_path);
+        downloader.env = crate::process_env::vars_for(&settings);
+        downloader
     }
 
     /// yt-dlpのパスを指定して作成
     pub fn with_path(ytdlp_path: &str) -> Self {
         Self {
             ytdlp_path: ytdlp_path.to_string(),
+            retry: RetryConfig::default(),
+            cookies: YtdlpCookies::default(),
+            env: Vec::new(),
+        }
+    }
+
+    /// cookieを指定（メンバー限定・年齢制限のある動画用）
+    pub fn with_cookies(mut self, cookies: YtdlpCookies) -> Self {
+        self.cookies = cookies;
+        self
+    }
+
+    /// yt-dlpコマンド（設定の環境変数付き）
+    fn base_command(&self) -> Command {
+        let mut command = Command::new(&self.ytdlp_path);
+        command.envs(self.env.iter().map(|(k, v)| (k, v)));
+        command
+    }
+
+    /// cookie引数付きのyt-dlpコマンド
+    fn command(&self) -> Command {
+        let mut command = self.base_command();
+        command.args(self.cookies.args());
+        command
+    }
+
+    /// 再試行設定を指定
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// 再試行できるエラーの間は待ち時間を増やしながら `f` を繰り返す
+    ///
+    /// 待機は `std::thread::sleep`（最大 `max_backoff_ms`）なので、
+    /// ブロッキング用のスレッドでのみ呼ぶこと。
+    fn retrying<T>(&self, what: &str, mut f: impl FnMut() -> Result<T, YoutubeError>) -> Result<T, YoutubeError> {
+        let mut attempt = 1;
+        loop {
+            match f() {
+                Err(e) if e.is_retryable() && attempt < self.retry.max_attempts => {
+                    let delay = self.retry.delay(attempt, &e);
+                    crate::log::info("YoutubeDownloader", &format!(
+                        "{} failed (attempt {}/{}), retrying in {:?}: {}",
+                        what, attempt, self.retry.max_attempts, delay, e
+                    ));
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// yt-dlpを実行し、失敗したらstderrを分類したエラーを返す
+    fn run_ytdlp(&self, command: &mut Command) -> Result<Output, YoutubeError> {
+        let output = command.output().map_err(|_| YoutubeError::YtdlpNotFound)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            crate::log::error("YoutubeDownloader", &format!("yt-dlp failed: {}", stderr.trim()));
+            return Err(YoutubeError::from_stderr(&stderr));
         }
+        Ok(output)
     }
 
     /// yt-dlpがインストールされているか確認
     pub fn check_available(&self) -> Result<(), YoutubeError> {
-        let output = Command::new(&self.ytdlp_path)
+        let output = self.base_command()
             .arg("--version")
             .output()
             .map_err(|_| YoutubeError::YtdlpNotFound)?;
@@ -108,8 +391,8 @@ impl YoutubeDownloader {
         let output_template = format!("{}/%(title)s.{}.%(ext)s", output_dir, lang);
 
         // yt-dlpコマンド実行
-        let output = Command::new(&self.ytdlp_path)
-            .args([
+        let output = self.retrying("Subtitle download", || {
+            self.run_ytdlp(self.command().args([
                 "--write-sub",
                 "--write-auto-sub",  // 自動生成字幕も取得
                 "--sub-lang", lang,
@@ -118,27 +401,16 @@ impl YoutubeDownloader {
                 "-o", &output_template,
                 "--print", "%(title)s",  // タイトルを出力
                 url,
-            ])
-            .output()
-            .map_err(|e| YoutubeError::DownloadFailed {
-                message: e.to_string(),
-            })?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            crate::log::error("YoutubeDownloader", &format!("yt-dlp failed: {}", stderr));
-
+            ]))
+        }).map_err(|e| match e {
             // 字幕が見つからない場合のエラーメッセージ
-            if stderr.contains("Requested subtitles language") || stderr.contains("not available") {
-                return Err(YoutubeError::SubtitleNotFound {
-                    lang: lang.to_string(),
-                });
+            YoutubeError::DownloadFailed { message }
+                if message.contains("Requested subtitles language") || message.contains("not available") =>
+            {
+                YoutubeError::SubtitleNotFound { lang: lang.to_string() }
             }
-
-            return Err(YoutubeError::DownloadFailed {
-                message: stderr.to_string(),
-            });
-        }
+            e => e,
+        })?;
 
         // タイトルを取得
         let title = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -199,14 +471,174 @@ impl YoutubeDownloader {
         })
     }
 
-    /// 利用可能な字幕言語一覧を取得
-    pub fn list_available_subs(&self, url: &str) -> Result<Vec<String>, YoutubeError> {
-        let output = Command::new(&self.ytdlp_path)
-            .args(["--list-subs", url])
-            .output()
+    /// 動画または音声をダウンロードし、保存したファイルのパスを返す
+    ///
+    /// `<output_dir>/video.mp4` / `<output_dir>/audio.*` に保存する（既にあれば再利用）。
+    /// yt-dlpの進捗行（`--newline`）を解析し、出力されるたびに `on_progress` を呼ぶ。
+    pub fn download_media<F>(
+        &self,
+        url: &str,
+        format: MediaFormat,
+        output_dir: &str,
+        mut on_progress: F,
+    ) -> Result<String, YoutubeError>
+    where
+        F: FnMut(&DownloadProgress),
+    {
+        if let Some(existing) = find_downloaded(output_dir, format) {
+            return Ok(existing);
+        }
+
+        std::fs::create_dir_all(output_dir)
+            .map_err(|e| YoutubeError::SaveFailed {
+                message: e.to_string(),
+            })?;
+
+        // 空き容量チェック（動画情報から見積もれない・確認できない場合は警告だけ出して続ける）
+        match self.estimate_media_size(url, format) {
+            Ok(bytes) if bytes > 0 => match preflight::ensure_disk_space(Path::new(output_dir), bytes) {
+                Ok(_) => {}
+                Err(e @ PreflightError::InsufficientSpace { .. }) => {
+                    return Err(YoutubeError::SaveFailed { message: e.to_string() });
+                }
+                Err(e) => crate::log::warn("YoutubeDownloader", &format!("Disk space check skipped: {}", e)),
+            },
+            Ok(_) => {}
+            Err(e) => crate::log::warn("YoutubeDownloader", &format!("Media size estimate failed: {}", e)),
+        }
+
+        crate::log::info("YoutubeDownloader", &format!("Downloading {:?}: {}", format, url));
+
+        // 再試行時は .part から続きをダウンロードする
+        self.retrying("Media download", || self.spawn_download(url, format, output_dir, &mut on_progress))
+    }
+
+    fn spawn_download<F>(
+        &self,
+        url: &str,
+        format: MediaFormat,
+        output_dir: &str,
+        on_progress: &mut F,
+    ) -> Result<String, YoutubeError>
+    where
+        F: FnMut(&DownloadProgress),
+    {
+        let output_template = format!("{}/{}.%(ext)s", output_dir, format.stem());
+        let mut child = self.command()
+            .args(format.ytdlp_args())
+            .args([
+                "--newline",
+                // --print は既定で進捗を出さないので明示する
+                "--progress",
+                "--print", "after_move:filepath",
+                "-o", &output_template,
+                url,
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|_| YoutubeError::YtdlpNotFound)?;
+
+        // stderrはパイプが詰まらないよう別スレッドで読む
+        let mut stderr_pipe = child.stderr.take();
+        let stderr_reader = std::thread::spawn(move || {
+            let mut buf = String::new();
+            if let Some(ref mut pipe) = stderr_pipe {
+                let _ = pipe.read_to_string(&mut buf);
+            }
+            buf
+        });
+
+        let mut saved = None;
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some(progress) = parse_download_progress(&line) {
+                    on_progress(&progress);
+                } else if !line.trim().is_empty() && Path::new(line.trim()).exists() {
+                    saved = Some(line.trim().to_string());
+                }
+            }
+        }
+
+        let status = child.wait()
             .map_err(|e| YoutubeError::DownloadFailed {
                 message: e.to_string(),
             })?;
+        let stderr = stderr_reader.join().unwrap_or_default();
+        if !status.success() {
+            return Err(YoutubeError::from_stderr(&stderr));
+        }
+
+        saved
+            .or_else(|| find_downloaded(output_dir, format))
+            .ok_or_else(|| YoutubeError::SaveFailed {
+                message: format!("{} file not found in {}", format.stem(), output_dir),
+            })
+    }
+
+    /// 動画をMP4でダウンロード（`<output_dir>/video.mp4`、既にあれば再利用）
+    ///
+    /// 進捗（0〜100%）が出力されるたびに `on_progress` を呼ぶ。
+    pub fn download_video<F>(&self, url: &str, output_dir: &str, mut on_progress: F) -> Result<String, YoutubeError>
+    where
+        F: FnMut(f32),
+    {
+        self.download_media(url, MediaFormat::Video, output_dir, |progress| on_progress(progress.percent))
+    }
+
+    /// 音声のみをダウンロード（文字起こし用）。保存したファイルのパスを返す
+    ///
+    /// 同じディレクトリにダウンロード済みの音声があれば再利用する。
+    pub fn download_audio(&self, url: &str, output_dir: &str) -> Result<String, YoutubeError> {
+        self.download_media(url, MediaFormat::Audio, output_dir, |_| {})
+    }
+
+    /// ダウンロードするメディアのサイズを見積もる（バイト、分からなければ0）
+    pub fn estimate_media_size(&self, url: &str, format: MediaFormat) -> Result<u64, YoutubeError> {
+        Ok(media_size_from_info(&self.dump_info_with(url, format.ytdlp_args())?))
+    }
+
+    /// 動画のチャプター一覧を取得（チャプターがなければ空）
+    pub fn fetch_chapters(&self, url: &str) -> Result<Vec<Chapter>, YoutubeError> {
+        Ok(parse_chapters(&self.dump_info(url)?))
+    }
+
+    /// 動画情報（タイトル・長さ・チャンネル・チャプター・字幕言語）を取得
+    pub fn fetch_metadata(&self, url: &str) -> Result<VideoMetadata, YoutubeError> {
+        Ok(parse_metadata(&self.dump_info(url)?))
+    }
+
+    /// プレイリスト・チャンネルのURLを動画ごとに展開（単一の動画ならその1件）
+    pub fn expand_playlist(&self, url: &str) -> Result<Vec<PlaylistEntry>, YoutubeError> {
+        Ok(parse_playlist(&self.dump_info_with(url, &["--flat-playlist"])?))
+    }
+
+    /// `yt-dlp -J` の動画情報JSON
+    fn dump_info(&self, url: &str) -> Result<serde_json::Value, YoutubeError> {
+        self.dump_info_with(url, &[])
+    }
+
+    fn dump_info_with(&self, url: &str, extra_args: &[&str]) -> Result<serde_json::Value, YoutubeError> {
+        let output = self.retrying("Video info", || {
+            self.run_ytdlp(
+                self.command()
+                    .args(["--dump-single-json", "--skip-download", "--no-warnings"])
+                    .args(extra_args)
+                    .arg(url),
+            )
+        })?;
+
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| YoutubeError::DownloadFailed {
+                message: e.to_string(),
+            })
+    }
+
+    /// 利用可能な字幕言語一覧を取得
+    pub fn list_available_subs(&self, url: &str) -> Result<Vec<String>, YoutubeError> {
+        let output = self.retrying("Subtitle listing", || {
+            self.run_ytdlp(self.command().args(["--list-subs", url]))
+        })?;
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let langs: Vec<String> = stdout
@@ -226,6 +658,127 @@ impl YoutubeDownloader {
     }
 }
 
+/// ダウンロード済みのファイル（動画は `video.mp4`、音声は `audio.*`。途中のファイルは除く）
+fn find_downloaded(output_dir: &str, format: MediaFormat) -> Option<String> {
+    if format == MediaFormat::Video {
+        let video_path = Path::new(output_dir).join("video.mp4");
+        return video_path.exists().then(|| video_path.to_string_lossy().to_string());
+    }
+    std::fs::read_dir(output_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            name.starts_with("audio.") && !name.ends_with(".part") && !name.ends_with(".ytdl")
+        })
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// yt-dlpの動画情報（`chapters`）からチャプターを取り出す
+fn parse_chapters(info: &serde_json::Value) -> Vec<Chapter> {
+    let to_ms = |v: &serde_json::Value| v.as_f64().map(|s| (s * 1000.0).round() as u64);
+    info["chapters"]
+        .as_array()
+        .map(|chapters| {
+            chapters
+                .iter()
+                .filter_map(|c| {
+                    Some(Chapter {
+                        title: c["title"].as_str().unwrap_or_default().to_string(),
+                        start_ms: to_ms(&c["start_time"])?,
+                        end_ms: to_ms(&c["end_time"])?,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// yt-dlpの動画情報から `VideoMetadata` を作る
+fn parse_metadata(info: &serde_json::Value) -> VideoMetadata {
+    let text = |key: &str| info[key].as_str().map(str::to_string);
+    let tracks = |key: &str, auto: bool| -> Vec<SubtitleTrack> {
+        let mut tracks: Vec<SubtitleTrack> = info[key]
+            .as_object()
+            .map(|langs| {
+                langs
+                    .iter()
+                    // ライブチャットのリプレイは字幕ではない
+                    .filter(|(lang, _)| lang.as_str() != "live_chat")
+                    .map(|(lang, formats)| SubtitleTrack {
+                        lang: lang.clone(),
+                        name: formats[0]["name"].as_str().map(str::to_string),
+                        auto,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        tracks.sort_by(|a, b| a.lang.cmp(&b.lang));
+        tracks
+    };
+
+    let mut subtitles = tracks("subtitles", false);
+    subtitles.extend(tracks("automatic_captions", true));
+
+    VideoMetadata {
+        id: text("id").unwrap_or_default(),
+        title: text("title").unwrap_or_default(),
+        duration_ms: info["duration"].as_f64().map(|s| (s * 1000.0).round() as u64),
+        channel: text("channel").or_else(|| text("uploader")),
+        thumbnail: text("thumbnail"),
+        chapters: parse_chapters(info),
+        subtitles,
+    }
+}
+
+/// `-f` 付きの動画情報から、選ばれたフォーマットのサイズを見積もる
+///
+/// `filesize`（なければ `filesize_approx`）を優先し、どちらもなければ長さと合計ビットレートから計算する。
+fn media_size_from_info(info: &serde_json::Value) -> u64 {
+    preflight::estimate_media_bytes(
+        info["filesize"].as_u64().or_else(|| info["filesize_approx"].as_u64()),
+        info["duration"].as_f64(),
+        info["tbr"].as_f64(),
+    )
+}
+
+/// `--flat-playlist` の情報から動画一覧を取り出す
+///
+/// `entries` がなければ単一の動画として扱う。IDのない項目（非公開動画など）は除く。
+fn parse_playlist(info: &serde_json::Value) -> Vec<PlaylistEntry> {
+    let entry = |v: &serde_json::Value| {
+        let id = v["id"].as_str().filter(|id| !id.is_empty())?.to_string();
+        let url = v["webpage_url"].as_str()
+            .or_else(|| v["url"].as_str().filter(|u| u.starts_with("http")))
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("https://www.youtube.com/watch?v={}", id));
+        Some(PlaylistEntry { id, title: v["title"].as_str().map(str::to_string), url })
+    };
+    match info["entries"].as_array() {
+        Some(entries) => entries.iter().filter_map(entry).collect(),
+        None => entry(info).into_iter().collect(),
+    }
+}
+
+/// yt-dlpの進捗行（`[download]  42.3% of 10.00MiB at 1.00MiB/s ETA 00:05`）を解析する
+fn parse_download_progress(line: &str) -> Option<DownloadProgress> {
+    let rest = line.trim().strip_prefix("[download]")?.trim_start();
+    let (percent, rest) = rest.split_once('%')?;
+    let percent = percent.trim().parse().ok()?;
+
+    // "of ~ 10.00MiB" のように概算の印が入ることがある
+    let tokens: Vec<&str> = rest.split_whitespace().filter(|t| *t != "~").collect();
+    let after = |key: &str| {
+        tokens.iter()
+            .position(|t| *t == key)
+            .and_then(|i| tokens.get(i + 1))
+            .filter(|v| !v.starts_with("Unknown"))
+            .map(|v| v.to_string())
+    };
+    Some(DownloadProgress { percent, total: after("of"), speed: after("at"), eta: after("ETA") })
+}
+
 impl Default for YoutubeDownloader {
     fn default() -> Self {
         Self::new()
@@ -245,4 +798,150 @@ mod tests {
             println!("yt-dlp is available");
         }
     }
+
+    #[test]
+    fn test_parse_chapters() {
+        let info = serde_json::json!({
+            "chapters": [
+                { "title": "Intro", "start_time": 0.0, "end_time": 65.5 },
+                { "title": "Main", "start_time": 65.5, "end_time": 600.0 }
+            ]
+        });
+        let chapters = parse_chapters(&info);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[1].start_ms, 65_500);
+        assert!(parse_chapters(&serde_json::json!({ "chapters": null })).is_empty());
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let info = serde_json::json!({
+            "id": "abc123",
+            "title": "Demo",
+            "duration": 612.4,
+            "uploader": "Someone",
+            "subtitles": {
+                "live_chat": [{ "ext": "json" }],
+                "en": [{ "ext": "vtt", "name": "English" }]
+            },
+            "automatic_captions": {
+                "ja": [{ "ext": "vtt", "name": "Japanese" }],
+                "en": [{ "ext": "vtt" }]
+            }
+        });
+        let metadata = parse_metadata(&info);
+        assert_eq!(metadata.duration_ms, Some(612_400));
+        assert_eq!(metadata.channel.as_deref(), Some("Someone"));
+        assert!(metadata.chapters.is_empty());
+        assert_eq!(
+            metadata.subtitles.iter().map(|t| (t.lang.as_str(), t.auto)).collect::<Vec<_>>(),
+            vec![("en", false), ("en", true), ("ja", true)]
+        );
+        assert_eq!(metadata.subtitles[0].name.as_deref(), Some("English"));
+    }
+
+    #[test]
+    fn test_media_size_from_info() {
+        let approx = serde_json::json!({ "filesize": null, "filesize_approx": 5_000_000, "duration": 60.0, "tbr": 128.0 });
+        assert_eq!(media_size_from_info(&approx), 5_000_000);
+        let bitrate = serde_json::json!({ "duration": 8.0, "tbr": 1000.0 });
+        assert_eq!(media_size_from_info(&bitrate), 1_000_000);
+        assert_eq!(media_size_from_info(&serde_json::json!({ "duration": 8.0 })), 0);
+    }
+
+    #[test]
+    fn test_parse_playlist() {
+        let info = serde_json::json!({
+            "_type": "playlist",
+            "entries": [
+                { "id": "aaa", "title": "First", "url": "https://www.youtube.com/watch?v=aaa" },
+                { "id": "bbb", "title": "Second", "url": "bbb" },
+                { "id": null, "title": "[Private video]" }
+            ]
+        });
+        let entries = parse_playlist(&info);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].url, "https://www.youtube.com/watch?v=bbb");
+        assert_eq!(entries[0].title.as_deref(), Some("First"));
+
+        let video = serde_json::json!({ "id": "ccc", "webpage_url": "https://youtu.be/ccc" });
+        assert_eq!(parse_playlist(&video)[0].url, "https://youtu.be/ccc");
+    }
+
+    #[test]
+    fn test_cookie_args() {
+        assert!(YtdlpCookies::default().args().is_empty());
+        let cookies = YtdlpCookies { from_browser: Some("firefox".to_string()), file: Some("/tmp/cookies.txt".to_string()) };
+        assert_eq!(cookies.args(), vec!["--cookies-from-browser", "firefox"]);
+        let cookies = YtdlpCookies { from_browser: Some(" ".to_string()), file: Some("/tmp/cookies.txt".to_string()) };
+        assert_eq!(cookies.args(), vec!["--cookies", "/tmp/cookies.txt"]);
+    }
+
+    #[test]
+    fn test_error_from_stderr() {
+        let err = YoutubeError::from_stderr(
+            "WARNING: foo\nERROR: [youtube] abc: Unable to download webpage: HTTP Error 429: Too Many Requests\n",
+        );
+        assert!(matches!(&err, YoutubeError::RateLimited { message } if message.starts_with("[youtube] abc")));
+        assert!(err.is_retryable());
+
+        let err = YoutubeError::from_stderr("ERROR: [youtube] abc: The uploader has not made this video available in your country");
+        assert!(matches!(err, YoutubeError::GeoBlocked { .. }));
+        assert!(!err.is_retryable());
+        assert!(matches!(
+            YoutubeError::from_stderr("ERROR: [youtube] abc: Sign in to confirm your age"),
+            YoutubeError::AgeRestricted { .. }
+        ));
+        assert!(matches!(YoutubeError::from_stderr("ERROR: [youtube] abc: Private video"), YoutubeError::Unavailable { .. }));
+        assert!(YoutubeError::from_stderr("ERROR: <urlopen error [Errno -3] Temporary failure in name resolution>").is_retryable());
+        assert!(matches!(YoutubeError::from_stderr("ERROR: something else"), YoutubeError::DownloadFailed { .. }));
+
+        let retry = RetryConfig::default();
+        assert_eq!(retry.delay(1, &YoutubeError::Network { message: String::new() }), Duration::from_secs(2));
+        assert_eq!(retry.delay(2, &YoutubeError::RateLimited { message: String::new() }), Duration::from_secs(30));
+        assert_eq!(retry.delay(10, &YoutubeError::Network { message: String::new() }), Duration::from_secs(120));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_retries_rate_limit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // 2回目まで429で失敗し、3回目で動画情報を返す偽のyt-dlp
+        let dir = std::env::temp_dir().join(format!("re-voice-ytdlp-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("yt-dlp");
+        std::fs::write(&script, format!(
+            "#!/bin/sh\necho x >> '{count}'\nif [ $(wc -l < '{count}') -lt 3 ]; then\n  echo 'ERROR: HTTP Error 429: Too Many Requests' >&2\n  exit 1\nfi\necho '{{\"id\": \"abc\", \"title\": \"Demo\"}}'\n",
+            count = dir.join("count").display()
+        )).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let retry = RetryConfig { max_attempts: 3, backoff_ms: 1, rate_limit_backoff_ms: 1, max_backoff_ms: 10 };
+        let downloader = YoutubeDownloader::with_path(&script.to_string_lossy()).with_retry(retry.clone());
+        assert_eq!(downloader.fetch_metadata("https://youtu.be/abc").unwrap().title, "Demo");
+
+        std::fs::remove_file(dir.join("count")).unwrap();
+        let downloader = YoutubeDownloader::with_path(&script.to_string_lossy())
+            .with_retry(RetryConfig { max_attempts: 2, ..retry });
+        assert!(matches!(downloader.fetch_metadata("https://youtu.be/abc"), Err(YoutubeError::RateLimited { .. })));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_download_progress() {
+        let progress = parse_download_progress("[download]  42.3% of   10.00MiB at  1.00MiB/s ETA 00:05").unwrap();
+        assert_eq!(progress.percent, 42.3);
+        assert_eq!(progress.total.as_deref(), Some("10.00MiB"));
+        assert_eq!(progress.speed.as_deref(), Some("1.00MiB/s"));
+        assert_eq!(progress.eta.as_deref(), Some("00:05"));
+
+        let progress = parse_download_progress("[download]  5.0% of ~  20.00MiB at Unknown B/s ETA Unknown").unwrap();
+        assert_eq!(progress.total.as_deref(), Some("20.00MiB"));
+        assert_eq!(progress.speed, None);
+        assert_eq!(progress.eta, None);
+
+        assert_eq!(parse_download_progress("[download] Destination: video.f137.mp4"), None);
+        assert_eq!(parse_download_progress("[info] Downloading format"), None);
+    }
 }
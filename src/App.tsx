@@ -1,4 +1,4 @@
-import { useState, useEffect, useCallback } from "react";
+import { useState, useEffect, useCallback, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import { ACPClient, AgentCard, OrchestratorStats } from "./acp";
@@ -515,6 +515,8 @@ function App() {
   const [output, setOutput] = useState<string[]>([]);
   const [isLoading, setIsLoading] = useState(false);
   const [claudeRunning, setClaudeRunning] = useState(false);
+  // PTY出力の読み取りカーソル
+  const outputCursor = useRef(0);
   const [subtitleLang, setSubtitleLang] = useState("en");
 
   // ACP state
@@ -776,11 +778,17 @@ function App() {
     setIsLoading(false);
   };
 
-  // PTY出力を取得
+  // PTY出力を取得（前回の続きだけ）
   const handleGetOutput = async () => {
     try {
-      const result = await invoke<string>("read_from_claude");
-      addOutput(`[OUTPUT] ${result.length} chars: ${result.substring(0, 200)}...`);
+      const slice = await invoke<{ chunks: string[]; nextSeq: number; truncated: boolean }>(
+        "read_from_claude_since",
+        { seq: outputCursor.current }
+      );
+      outputCursor.current = slice.nextSeq;
+      const result = slice.chunks.join("");
+      const note = slice.truncated ? " (older output dropped)" : "";
+      addOutput(`[OUTPUT] ${result.length} new chars${note}: ${result.substring(0, 200)}...`);
     } catch (e) {
       addOutput(`[OUTPUT] Error: ${e}`);
     }
@@ -10,6 +10,9 @@ import type {
   TaskState,
   OrchestratorStats,
   SharedContext,
+  ContextItem,
+  ContextScope,
+  ContextValue,
 } from './types';
 
 export type InvokeFn = (cmd: string, args?: Record<string, unknown>) => Promise<unknown>;
@@ -147,13 +150,61 @@ export class ACPClient {
 
   /**
    * Get shared context
+   * @param scope Workspace or execution (default workspace if omitted)
    * @returns Current shared context
    */
-  async getContext(): Promise<SharedContext> {
-    const result = await this.invokeFn('acp_get_context');
+  async getContext(scope: ContextScope = {}): Promise<SharedContext> {
+    const result = await this.invokeFn('acp_get_context', { ...scope });
     return result as SharedContext;
   }
 
+  /**
+   * Set a value in the shared context
+   * @param key Key
+   * @param value Typed value
+   * @param scope Workspace or execution
+   * @param ttlSecs Remove the value after this many seconds
+   * @returns Stored item
+   */
+  async setContextValue(
+    key: string,
+    value: ContextValue,
+    scope: ContextScope = {},
+    ttlSecs?: number
+  ): Promise<ContextItem> {
+    const result = await this.invokeFn('acp_context_set', { key, value, ...scope, ttlSecs, updatedBy: 'ui' });
+    return result as ContextItem;
+  }
+
+  /**
+   * Get a value from the shared context
+   * @param key Key (a stage name for execution scopes)
+   * @param scope Workspace or execution
+   * @returns Item or null
+   */
+  async getContextValue(key: string, scope: ContextScope = {}): Promise<ContextItem | null> {
+    const result = await this.invokeFn('acp_context_get', { key, ...scope });
+    return result as ContextItem | null;
+  }
+
+  /**
+   * Delete a value from the shared context
+   * @returns Whether the value existed
+   */
+  async deleteContextValue(key: string, scope: ContextScope = {}): Promise<boolean> {
+    const result = await this.invokeFn('acp_context_delete', { key, ...scope });
+    return result as boolean;
+  }
+
+  /**
+   * Delete every value in a workspace or execution
+   * @returns Number of removed values
+   */
+  async clearContext(scope: ContextScope = {}): Promise<number> {
+    const result = await this.invokeFn('acp_context_clear', { ...scope });
+    return result as number;
+  }
+
   // ==========================================================================
   // Convenience Methods
   // ==========================================================================
@@ -3,7 +3,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
-import type { AgentCard, TaskResult } from '../types';
+import type { AgentCard, CapabilityFilter, CorrelatedReply, HistoryEntry, TaskResult } from '../types';
 
 /**
  * Tauri transport implementation
@@ -16,6 +16,27 @@ export class TauriTransport {
     return await invoke<string>('acp_register_agent', { agentType, instanceId });
   }
 
+  /**
+   * Register an agent from a full agent card
+   */
+  async registerAgentCard(card: AgentCard): Promise<string> {
+    return await invoke<string>('acp_register_agent', { card });
+  }
+
+  /**
+   * Replace the card of a registered agent
+   */
+  async updateAgent(agentId: string, card: AgentCard): Promise<AgentCard> {
+    return await invoke<AgentCard>('acp_update_agent', { agentId, card });
+  }
+
+  /**
+   * Unregister an agent
+   */
+  async unregisterAgent(agentId: string): Promise<void> {
+    await invoke('acp_unregister_agent', { agentId });
+  }
+
   /**
    * Discover agents
    */
@@ -48,6 +69,44 @@ export class TauriTransport {
     return await invoke<string>('acp_send_message', { to, content, from });
   }
 
+  /**
+   * Send message and wait for the response correlated to it
+   */
+  async sendAndWait(
+    to: string,
+    content: string,
+    from: string,
+    timeoutMs?: number
+  ): Promise<CorrelatedReply> {
+    return await invoke<CorrelatedReply>('acp_send_and_wait', { to, content, from, timeoutMs });
+  }
+
+  /**
+   * Send to the least-loaded matching agent and wait for its response
+   */
+  async dispatch(
+    content: string,
+    filter?: CapabilityFilter,
+    from?: string,
+    timeoutMs?: number
+  ): Promise<CorrelatedReply> {
+    return await invoke<CorrelatedReply>('acp_dispatch', { content, filter, from, timeoutMs });
+  }
+
+  /**
+   * Get message history (oldest first)
+   */
+  async getHistory(agentId?: string, since?: string, limit?: number): Promise<HistoryEntry[]> {
+    return await invoke<HistoryEntry[]>('acp_get_history', { agentId, since, limit });
+  }
+
+  /**
+   * Search message history
+   */
+  async searchHistory(query: string, limit?: number): Promise<HistoryEntry[]> {
+    return await invoke<HistoryEntry[]>('acp_search_history', { query, limit });
+  }
+
   /**
    * Broadcast message
    */
@@ -59,6 +118,73 @@ export class TauriTransport {
     return await invoke<string[]>('acp_broadcast', { content, capabilities, from });
   }
 
+  /**
+   * Subscribe to agent output (delivered via "acp:agent_output" events)
+   */
+  async subscribeAgent(agentId: string): Promise<number> {
+    return await invoke<number>('acp_subscribe_agent', { agentId });
+  }
+
+  /**
+   * Unsubscribe from agent output
+   */
+  async unsubscribeAgent(agentId: string, subscription: number): Promise<boolean> {
+    return await invoke<boolean>('acp_unsubscribe_agent', { agentId, subscription });
+  }
+
+  /**
+   * Close the agent's transport
+   */
+  async closeAgent(agentId: string): Promise<void> {
+    await invoke('acp_close_agent', { agentId });
+  }
+
+  /**
+   * Spawn an agent process and connect to it over stdio (newline-delimited ACP envelopes)
+   */
+  async spawnStdioAgent(
+    agentId: string,
+    command: { command: string; args?: string[]; env?: Record<string, string>; workingDir?: string },
+    capabilities?: string[]
+  ): Promise<string> {
+    return await invoke<string>('acp_spawn_stdio_agent', { agentId, command, capabilities });
+  }
+
+  /**
+   * Connect to a remote agent over WebSocket (ws:// or wss://)
+   */
+  async connectRemote(url: string, agentId?: string, capabilities?: string[]): Promise<string> {
+    return await invoke<string>('acp_connect_remote', { url, agentId, capabilities });
+  }
+
+  /**
+   * Disconnect a remote agent and remove it from the registry
+   */
+  async disconnectRemote(agentId: string): Promise<void> {
+    await invoke('acp_disconnect_remote', { agentId });
+  }
+
+  /**
+   * Fetch a remote agent card from <url>/.well-known/agent.json and register it
+   */
+  async discoverRemote(url: string, connect?: boolean): Promise<AgentCard> {
+    return await invoke<AgentCard>('acp_discover_remote', { url, connect });
+  }
+
+  /**
+   * Publish our own agent card over HTTP and accept A2A tasks
+   */
+  async startAgentServer(addr?: string): Promise<AgentCard> {
+    return await invoke<AgentCard>('acp_start_agent_server', { addr });
+  }
+
+  /**
+   * Stop the agent card server
+   */
+  async stopAgentServer(): Promise<boolean> {
+    return await invoke<boolean>('acp_stop_agent_server');
+  }
+
   /**
    * Get task state
    */
@@ -287,6 +287,7 @@ export interface CapabilityFilter {
 export interface PipelineStage {
   name: string;
   agent: AgentAddress;
+  selector?: CapabilityFilter;  // pick the agent by skills/tags at execution time
   promptTemplate?: string;
 }
 
@@ -355,6 +356,22 @@ export interface ACPEnvelope {
   metadata?: EnvelopeMetadata;
 }
 
+// Reply matched to a request by correlation ID (acp_send_and_wait)
+export interface CorrelatedReply {
+  messageId: string;
+  agentId: string;  // Agent that answered (the one chosen by dispatch)
+  response: ACPEnvelope;
+  chunks: string[];
+}
+
+export interface HistoryEntry {
+  seq: number;
+  agentId: string;
+  direction: 'sent' | 'received';
+  recordedAt: string;
+  message: ACPMessageV3;
+}
+
 // Legacy type
 export interface ACPMessage {
   id: string;
@@ -414,11 +431,23 @@ export interface PipelineDefinition {
 export interface PipelineExecution {
   pipelineId: string;
   executionId: string;
-  status: 'pending' | 'running' | 'completed' | 'failed' | 'cancelled';
+  status: 'pending' | 'running' | 'paused' | 'completed' | 'failed' | 'cancelled';
   currentStage?: number;
   results: PipelineStageResult[];
   startTime: string;
   endTime?: string;
+  /** Claude Code requests made by this execution */
+  usage?: UsageTotals;
+}
+
+/** Token counts and cost (snake_case, as sent by the executor) */
+export interface UsageTotals {
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_input_tokens: number;
+  cache_read_input_tokens: number;
+  cost_usd: number;
+  requests: number;
 }
 
 export interface PipelineStageResult {
@@ -441,6 +470,10 @@ export interface OrchestratorStats {
   tasksFailed: number;
   tasksInProgress: number;
   pipelinesRunning: number;
+  queuedMessages: number;
+  queueDepth: Record<string, number>;
+  expiredMessages: number;
+  inFlight: Record<string, number>;  // Unanswered requests per agent
 }
 
 // ============================================================================
@@ -453,10 +486,37 @@ export interface ContextEntry {
   timestamp: string;
 }
 
+export type ContextValue =
+  | { type: 'text'; value: string }
+  | { type: 'number'; value: number }
+  | { type: 'bool'; value: boolean }
+  | { type: 'json'; value: unknown };
+
+export interface ContextItem {
+  value: ContextValue;
+  updatedBy?: string;
+  updatedAt: string;
+  expiresAt?: string;
+}
+
+export interface ContextChange {
+  workspace: string;  // "default", custom names, or "execution:<executionId>"
+  key: string;
+  kind: 'set' | 'deleted' | 'expired';
+  item?: ContextItem;
+}
+
+/** Where a context value lives: a named workspace, or one pipeline execution */
+export interface ContextScope {
+  workspace?: string;
+  executionId?: string;
+}
+
 export interface SharedContext {
   conversationHistory: ContextEntry[];
   sharedFiles: string[];
   metadata: Record<string, unknown>;
+  values: Record<string, ContextItem>;
 }
 
 // ============================================================================
@@ -602,7 +662,7 @@ export interface PermissionRequest {
 export type ExecutorEvent =
   | { type: 'state_changed'; oldState: AgentState; newState: AgentState }
   | { type: 'output'; content: string }
-  | { type: 'tool_execution'; name: string; input: unknown; result: string | null; isError: boolean }
+  | { type: 'tool_execution'; id: string; name: string; input: unknown; result: string | null; isError: boolean; durationMs: number | null }
   | { type: 'permission_required'; requestId: string; toolName: string; options: string[] }
   | { type: 'progress'; message: string; percentage: number }
   | { type: 'completed'; output: string }
@@ -3,6 +3,7 @@
  *
  * Phase 3: 字幕翻訳パイプラインの実行UI
  * - YouTube URL入力
+ * - ローカル動画＋字幕ファイル選択
  * - 字幕言語選択
  * - VOICEVOX話者選択
  * - 実行ボタン
@@ -15,6 +16,7 @@
 import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { open } from "@tauri-apps/plugin-dialog";
 import "./PipelineRunner.css";
 
 // VOICEVOX話者情報
@@ -94,6 +96,8 @@ function PipelineRunner({ addOutput }: PipelineRunnerProps) {
   const [youtubeUrl, setYoutubeUrl] = useState("");
   const [subtitleLang, setSubtitleLang] = useState("en");
   const [outputDir, setOutputDir] = useState("/tmp/revoice");
+  const [videoPath, setVideoPath] = useState("");
+  const [subtitlePath, setSubtitlePath] = useState("");
 
   // VOICEVOX状態
   const [voicevoxRunning, setVoicevoxRunning] = useState(false);
@@ -124,6 +128,16 @@ function PipelineRunner({ addOutput }: PipelineRunnerProps) {
   const [currentAudio, setCurrentAudio] = useState<string | null>(null);
   const audioRef = useRef<HTMLAudioElement>(null);
 
+  // 設定から既定の出力先・話者を読み込む
+  useEffect(() => {
+    invoke<{ output_dir: string | null; default_speaker: number }>("get_settings")
+      .then((settings) => {
+        if (settings.output_dir) setOutputDir(settings.output_dir);
+        setSelectedSpeaker(settings.default_speaker);
+      })
+      .catch(() => {});
+  }, []);
+
   // CLIエグゼキューター状態チェック
   useEffect(() => {
     const checkExecutor = async () => {
@@ -329,6 +343,50 @@ function PipelineRunner({ addOutput }: PipelineRunnerProps) {
     }
   };
 
+  // ファイル選択
+  const handlePickFile = async (
+    name: string,
+    extensions: string[],
+    setPath: (path: string) => void
+  ) => {
+    const selected = await open({
+      multiple: false,
+      directory: false,
+      filters: [{ name, extensions }],
+    });
+    if (typeof selected === "string") {
+      setPath(selected);
+    }
+  };
+
+  // ローカル動画パイプライン実行
+  const handleRunLocalVideo = async () => {
+    if (!videoPath || !subtitlePath) {
+      addOutput("[PIPELINE] 動画と字幕ファイルを選択してください");
+      return;
+    }
+
+    setIsRunning(true);
+    setProgress(null);
+    setExecution(null);
+    setGeneratedFiles([]);
+
+    try {
+      addOutput(`[PIPELINE] 開始: ${videoPath} + ${subtitlePath}`);
+
+      const result = await invoke<string>("run_local_video_pipeline", {
+        videoPath,
+        subtitlePath,
+        outputDir,
+      });
+
+      addOutput(`[PIPELINE] パイプライン開始: ${result}`);
+    } catch (e) {
+      addOutput(`[PIPELINE] エラー: ${e}`);
+      setIsRunning(false);
+    }
+  };
+
   // キャンセル
   const handleCancel = async () => {
     if (!executionId) return;
@@ -383,6 +441,16 @@ function PipelineRunner({ addOutput }: PipelineRunnerProps) {
     }
   };
 
+  // 話者の試聴
+  const handlePreviewSpeaker = async () => {
+    try {
+      const path = await invoke<string>("voicevox_preview_speaker", { speakerId: selectedSpeaker });
+      await invoke("audio_play", { path });
+    } catch (e) {
+      addOutput(`[VOICEVOX] 試聴エラー: ${e}`);
+    }
+  };
+
   // 音声合成テスト
   const handleTestSynthesis = async () => {
     if (!voicevoxRunning) {
@@ -509,6 +577,13 @@ function PipelineRunner({ addOutput }: PipelineRunnerProps) {
               )
             )}
           </select>
+          <button
+            onClick={handlePreviewSpeaker}
+            disabled={isRunning || !voicevoxRunning}
+            className="btn-secondary"
+          >
+            試聴
+          </button>
         </div>
 
         <div className="input-section">
@@ -522,6 +597,47 @@ function PipelineRunner({ addOutput }: PipelineRunnerProps) {
         </div>
       </div>
 
+      {/* ローカル動画 */}
+      <div className="input-row">
+        <div className="input-section">
+          <label>ローカル動画</label>
+          <input
+            type="text"
+            placeholder="video.mkv"
+            value={videoPath}
+            onChange={(e) => setVideoPath(e.target.value)}
+            disabled={isRunning}
+          />
+          <button
+            onClick={() =>
+              handlePickFile("Video", ["mp4", "mkv", "webm", "mov"], setVideoPath)
+            }
+            disabled={isRunning}
+            className="btn-secondary"
+          >
+            選択...
+          </button>
+        </div>
+
+        <div className="input-section">
+          <label>字幕ファイル</label>
+          <input
+            type="text"
+            placeholder="subs.srt"
+            value={subtitlePath}
+            onChange={(e) => setSubtitlePath(e.target.value)}
+            disabled={isRunning}
+          />
+          <button
+            onClick={() => handlePickFile("Subtitles", ["srt", "vtt"], setSubtitlePath)}
+            disabled={isRunning}
+            className="btn-secondary"
+          >
+            選択...
+          </button>
+        </div>
+      </div>
+
       {/* アクションボタン */}
       <div className="button-group">
         {!cliExecutorRunning && (
@@ -542,6 +658,14 @@ function PipelineRunner({ addOutput }: PipelineRunnerProps) {
           {isRunning ? "実行中..." : "パイプライン実行"}
         </button>
 
+        <button
+          onClick={handleRunLocalVideo}
+          disabled={isRunning || !videoPath || !subtitlePath}
+          className="btn-primary"
+        >
+          ローカル動画を吹替
+        </button>
+
         {isRunning && (
           <button onClick={handleCancel} className="btn-danger">
             キャンセル